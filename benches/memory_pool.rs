@@ -0,0 +1,57 @@
+//! Deep call trees run with a memory pool that starts empty for every
+//! transaction, against one shared and already warm across transactions.
+//! Run with `cargo bench --bench memory_pool`.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use evm::Config;
+use evm::backend::MemoryBackend;
+use evm::executor::{MemoryPool, StackExecutor};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, block_on, deploy};
+
+const GAS_LIMIT: usize = 1_000_000_000;
+
+// Call itself twice with the depth given as the first calldata word minus
+// one, until the depth is zero: a binary tree of calls.
+const CALL_TREE: &str = "600035801561002c576001900360005260006000602060006000305af15060006000602060006000305af1505b00";
+
+fn transact(backend: &Arc<MemoryBackend>, pool: Option<&Arc<Mutex<MemoryPool>>>, depth: u64) {
+	let mut executor = StackExecutor::new(backend.clone(), GAS_LIMIT, Arc::new(Config::istanbul()));
+	if let Some(pool) = pool {
+		executor.set_memory_pool(pool.clone());
+	}
+	let (reason, _) = block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER),
+		H160::from_low_u64_be(TARGET),
+		U256::zero(),
+		H256::from_low_u64_be(depth).as_bytes().to_vec(),
+		GAS_LIMIT,
+	));
+	assert!(reason.is_succeed(), "call tree failed: {:?}", reason);
+}
+
+fn call_trees(c: &mut Criterion) {
+	let backend = deploy(CALL_TREE);
+
+	let mut group = c.benchmark_group("memory_pool");
+	for depth in [4, 8, 10] {
+		group.bench_with_input(BenchmarkId::new("fresh", depth), &depth, |b, depth| {
+			b.iter(|| transact(&backend, None, *depth))
+		});
+
+		let pool = Arc::new(Mutex::new(MemoryPool::default()));
+		group.bench_with_input(BenchmarkId::new("shared", depth), &depth, |b, depth| {
+			b.iter(|| transact(&backend, Some(&pool), *depth))
+		});
+	}
+	group.finish();
+}
+
+criterion_group!(benches, call_trees);
+criterion_main!(benches);
@@ -0,0 +1,258 @@
+//! Standard workloads run against `MemoryBackend` on both engines, with
+//! throughput reported by criterion in gas per second. Run with
+//! `cargo bench --bench workloads`, optionally followed by `--` and a filter
+//! on the workload names.
+//!
+//! Steps are counted in a separate run with a gas hook, so that counting
+//! does not slow down the timed runs, and printed with the gas of each
+//! workload: steps per second are the gas per second scaled by their ratio.
+//! `snailtracer.hex` is the runtime code of the SnailTracer ray tracer, the
+//! usual compute-bound EVM benchmark.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use evm::{Config, Engine, ExternalOpcode, Opcode};
+use evm::executor::{GasAction, GasHook, StackExecutor};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, block_on, deploy};
+
+const GAS_LIMIT: usize = 1_000_000_000;
+const SNAILTRACER: &str = include_str!("snailtracer.hex");
+/// Selector of `Benchmark()` of the SnailTracer contract.
+const SNAILTRACER_BENCHMARK: &str = "30627b7c";
+
+const STOP: u8 = 0x00;
+const ADD: u8 = 0x01;
+const MUL: u8 = 0x02;
+const SUB: u8 = 0x03;
+const MOD: u8 = 0x06;
+const ISZERO: u8 = 0x15;
+const SHA3: u8 = 0x20;
+const ADDRESS: u8 = 0x30;
+const CALLDATALOAD: u8 = 0x35;
+const POP: u8 = 0x50;
+const MSTORE: u8 = 0x52;
+const SLOAD: u8 = 0x54;
+const SSTORE: u8 = 0x55;
+const JUMPI: u8 = 0x57;
+const GAS: u8 = 0x5a;
+const DUP1: u8 = 0x80;
+const DUP2: u8 = 0x81;
+const SWAP1: u8 = 0x90;
+const CALL: u8 = 0xf1;
+
+/// Item of the assembly of a workload.
+enum Asm {
+	/// An opcode.
+	Op(u8),
+	/// The shortest `PUSHn` of the value, at least `PUSH1`.
+	Push(u64),
+	/// A `JUMPDEST` with a name.
+	Label(&'static str),
+	/// A `PUSH2` of the position of the named label.
+	Target(&'static str),
+}
+
+use Asm::*;
+
+fn push_len(value: u64) -> usize {
+	std::cmp::max(1, 8 - value.leading_zeros() as usize / 8)
+}
+
+fn assemble(items: &[Asm]) -> Vec<u8> {
+	let mut labels = Vec::new();
+	let mut position = 0;
+	for item in items {
+		match item {
+			Op(_) => position += 1,
+			Push(value) => position += 1 + push_len(*value),
+			Label(name) => {
+				labels.push((*name, position));
+				position += 1;
+			},
+			Target(_) => position += 3,
+		}
+	}
+
+	let mut code = Vec::with_capacity(position);
+	for item in items {
+		match item {
+			Op(op) => code.push(*op),
+			Push(value) => {
+				let len = push_len(*value);
+				code.push(0x5f + len as u8);
+				code.extend_from_slice(&value.to_be_bytes()[8 - len..]);
+			},
+			Label(_) => code.push(0x5b),
+			Target(name) => {
+				let (_, position) = labels.iter().find(|(label, _)| label == name).expect("label is defined");
+				code.push(0x61);
+				code.extend_from_slice(&(*position as u16).to_be_bytes());
+			},
+		}
+	}
+	code
+}
+
+/// Decrement the counter on top of the stack, and jump to `label` unless
+/// it reached zero.
+fn loop_back(label: &'static str) -> Vec<Asm> {
+	vec![Push(1), Op(SWAP1), Op(SUB), Op(DUP1), Target(label), Op(JUMPI)]
+}
+
+/// `x = x * x + i` for `iterations` values of `i`, with wrapping
+/// arithmetic.
+fn arithmetic(iterations: u64) -> Vec<u8> {
+	let mut items = vec![
+		Push(3), Push(iterations),
+		Label("loop"),
+		Op(SWAP1), Op(DUP1), Op(MUL), Op(DUP2), Op(ADD), Op(SWAP1),
+	];
+	items.extend(loop_back("loop"));
+	items.push(Op(STOP));
+	assemble(&items)
+}
+
+/// Hash a 32-byte word `iterations` times.
+fn keccak(iterations: u64) -> Vec<u8> {
+	let mut items = vec![
+		Push(iterations),
+		Label("loop"),
+		Op(DUP1), Push(0), Op(MSTORE),
+		Push(32), Push(0), Op(SHA3), Op(POP),
+	];
+	items.extend(loop_back("loop"));
+	items.push(Op(STOP));
+	assemble(&items)
+}
+
+/// ERC-20 style transfers of one token from a fixed holder to `transfers`
+/// different recipients, with balances in a mapping at slot 0.
+fn erc20_transfers(transfers: u64) -> Vec<u8> {
+	let mut items = vec![
+		Push(transfers),
+		Label("loop"),
+		// balances[holder] -= 1
+		Push(CALLER), Push(0), Op(MSTORE),
+		Push(0), Push(32), Op(MSTORE),
+		Push(64), Push(0), Op(SHA3),
+		Op(DUP1), Op(SLOAD), Push(1), Op(SWAP1), Op(SUB), Op(SWAP1), Op(SSTORE),
+		// balances[counter] += 1
+		Op(DUP1), Push(0), Op(MSTORE),
+		Push(64), Push(0), Op(SHA3),
+		Op(DUP1), Op(SLOAD), Push(1), Op(ADD), Op(SWAP1), Op(SSTORE),
+	];
+	items.extend(loop_back("loop"));
+	items.push(Op(STOP));
+	assemble(&items)
+}
+
+/// Write `writes` values to 64 slots, each slot rewritten many times.
+fn storage_churn(writes: u64) -> Vec<u8> {
+	let mut items = vec![
+		Push(writes),
+		Label("loop"),
+		Op(DUP1), Op(DUP1), Push(64), Op(SWAP1), Op(MOD), Op(SSTORE),
+	];
+	items.extend(loop_back("loop"));
+	items.push(Op(STOP));
+	assemble(&items)
+}
+
+/// Call itself twice with the depth given as the first calldata word minus
+/// one, until the depth is zero: a binary tree of calls.
+fn call_tree() -> Vec<u8> {
+	let call_self = || vec![
+		Push(0), Push(0), Push(32), Push(0), Push(0), Op(ADDRESS), Op(GAS), Op(CALL), Op(POP),
+	];
+	let mut items = vec![
+		Push(0), Op(CALLDATALOAD),
+		Op(DUP1), Op(ISZERO), Target("done"), Op(JUMPI),
+		Push(1), Op(SWAP1), Op(SUB), Push(0), Op(MSTORE),
+	];
+	items.extend(call_self());
+	items.extend(call_self());
+	items.push(Label("done"));
+	items.push(Op(STOP));
+	assemble(&items)
+}
+
+struct Workload {
+	name: &'static str,
+	code: Vec<u8>,
+	input: Vec<u8>,
+}
+
+fn workloads() -> Vec<Workload> {
+	vec![
+		Workload { name: "arithmetic", code: arithmetic(100_000), input: Vec::new() },
+		Workload { name: "keccak", code: keccak(20_000), input: Vec::new() },
+		Workload { name: "erc20_transfers", code: erc20_transfers(1_000), input: Vec::new() },
+		Workload { name: "storage_churn", code: storage_churn(10_000), input: Vec::new() },
+		Workload {
+			name: "call_tree",
+			code: call_tree(),
+			input: H256::from_low_u64_be(10).as_bytes().to_vec(),
+		},
+		Workload {
+			name: "snailtracer",
+			code: hex::decode(SNAILTRACER.trim()).unwrap(),
+			input: hex::decode(SNAILTRACER_BENCHMARK).unwrap(),
+		},
+	]
+}
+
+struct StepCounter(AtomicUsize);
+
+impl GasHook for StepCounter {
+	fn after_charge(&self, _: Result<Opcode, ExternalOpcode>, _: usize, _: usize) -> GasAction {
+		self.0.fetch_add(1, Ordering::Relaxed);
+		GasAction::Continue
+	}
+}
+
+/// Run the workload once, returning the used gas.
+fn run(workload: &Workload, config: &Arc<Config>, hook: Option<Arc<StepCounter>>) -> usize {
+	let backend = deploy(&hex::encode(&workload.code));
+	let mut executor = StackExecutor::new(backend, GAS_LIMIT, config.clone());
+	if let Some(hook) = hook {
+		executor.set_gas_hook(hook);
+	}
+	let (reason, _) = block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER),
+		H160::from_low_u64_be(TARGET),
+		U256::zero(),
+		workload.input.clone(),
+		GAS_LIMIT,
+	));
+	assert!(reason.is_succeed(), "{} failed: {:?}", workload.name, reason);
+	executor.used_gas()
+}
+
+fn bench_workloads(c: &mut Criterion) {
+	for workload in workloads() {
+		let mut group = c.benchmark_group(workload.name);
+		group.sample_size(10);
+
+		for engine in [Engine::Interpreter, Engine::Threaded] {
+			let config = Arc::new(Config::istanbul().with_engine(engine));
+			let counter = Arc::new(StepCounter(AtomicUsize::new(0)));
+			let gas = run(&workload, &config, Some(counter.clone()));
+			let steps = counter.0.load(Ordering::Relaxed);
+			println!("{}/{:?}: {} gas, {} steps", workload.name, engine, gas, steps);
+
+			group.throughput(Throughput::Elements(gas as u64));
+			group.bench_function(format!("{:?}", engine), |b| b.iter(|| run(&workload, &config, None)));
+		}
+		group.finish();
+	}
+}
+
+criterion_group!(benches, bench_workloads);
+criterion_main!(benches);
@@ -0,0 +1,118 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cmp::min;
+
+use primitive_types::U256;
+
+use crate::{ExternalOpcode, Opcode, Valids};
+use crate::eval::{Handler, handler};
+
+/// Instruction of analyzed code.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Instruction {
+	/// Decoded opcode.
+	pub opcode: Result<Opcode, ExternalOpcode>,
+	/// Gas cost known before execution, if supplied at analysis.
+	pub static_gas: Option<usize>,
+	/// Index of the `PUSHn` immediate in the immediates of the code, or
+	/// `u32::MAX` if there is none.
+	immediate: u32,
+}
+
+/// Bytecode pre-decoded into one instruction per code byte, with `PUSHn`
+/// immediates and valid jump destinations resolved once. Meant to be shared
+/// through an `Arc` by every frame running the same code.
+#[derive(Clone, Debug)]
+pub struct AnalyzedCode {
+	code: Arc<Vec<u8>>,
+	instructions: Vec<Instruction>,
+	immediates: Vec<U256>,
+	valids: Valids,
+	handlers: Option<Vec<Option<Handler>>>,
+}
+
+impl AnalyzedCode {
+	/// Analyze the given code, without static gas.
+	pub fn new(code: Arc<Vec<u8>>) -> Self {
+		Self::with_static_gas(code, |_| None)
+	}
+
+	/// Analyze the given code, recording the static gas of each instruction
+	/// as given by `static_gas`.
+	pub fn with_static_gas<F: Fn(Result<Opcode, ExternalOpcode>) -> Option<usize>>(
+		code: Arc<Vec<u8>>,
+		static_gas: F,
+	) -> Self {
+		let mut instructions = Vec::with_capacity(code.len());
+		let mut immediates = Vec::new();
+
+		// Bytes of `PUSHn` data are decoded as well, so that instructions are
+		// indexed by program counter. They are never executed, as jumps only
+		// land on valid destinations.
+		let mut data_end = 0;
+		for (position, byte) in code.iter().enumerate() {
+			let opcode = Opcode::parse(*byte);
+			let mut immediate = u32::MAX;
+			if position >= data_end {
+				if let Ok(Opcode::Push(n)) = opcode {
+					let end = min(position + 1 + n as usize, code.len());
+					immediate = immediates.len() as u32;
+					immediates.push(U256::from(&code[(position + 1)..end]));
+					data_end = position + 1 + n as usize;
+				}
+			}
+			instructions.push(Instruction { opcode, static_gas: static_gas(opcode), immediate });
+		}
+
+		Self {
+			valids: Valids::new(&code[..]),
+			code,
+			instructions,
+			immediates,
+			handlers: None,
+		}
+	}
+
+	/// Translate the instructions into handlers called directly by the
+	/// machine, for the threaded engine. External opcodes keep trapping.
+	pub fn threaded(mut self) -> Self {
+		self.handlers = Some(self.instructions.iter()
+			.map(|instruction| instruction.opcode.ok().map(handler))
+			.collect());
+		self
+	}
+
+	/// Whether the code was translated for the threaded engine.
+	pub fn is_threaded(&self) -> bool {
+		self.handlers.is_some()
+	}
+
+	/// Analyzed code.
+	pub fn code(&self) -> &Arc<Vec<u8>> {
+		&self.code
+	}
+
+	/// Valid jump destinations.
+	pub fn valids(&self) -> &Valids {
+		&self.valids
+	}
+
+	/// Instruction at the given program counter.
+	pub fn instruction(&self, position: usize) -> Option<&Instruction> {
+		self.instructions.get(position)
+	}
+
+	/// Handler of the instruction at the given program counter, if threaded.
+	pub(crate) fn handler(&self, position: usize) -> Option<Handler> {
+		self.handlers.as_ref().and_then(|handlers| handlers.get(position).cloned().flatten())
+	}
+
+	/// Immediate of the `PUSHn` at the given program counter.
+	pub fn immediate(&self, position: usize) -> Option<U256> {
+		match self.instructions.get(position) {
+			Some(Instruction { opcode: Ok(Opcode::Push(_)), immediate, .. }) =>
+				self.immediates.get(*immediate as usize).cloned(),
+			_ => None,
+		}
+	}
+}
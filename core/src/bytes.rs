@@ -0,0 +1,129 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Deref, Range};
+
+/// Cheaply clonable byte slice, sharing the buffer it was sliced from.
+#[derive(Clone, Default)]
+pub struct Bytes {
+	buffer: Arc<Vec<u8>>,
+	range: Range<usize>,
+}
+
+impl Bytes {
+	/// Empty bytes.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Share a range of a buffer, without copying it. The range is clamped
+	/// to the buffer.
+	pub fn from_shared(buffer: Arc<Vec<u8>>, range: Range<usize>) -> Self {
+		let end = range.end.min(buffer.len());
+		let start = range.start.min(end);
+		Self { buffer, range: start..end }
+	}
+
+	/// Share a sub-range of these bytes, without copying them. The range is
+	/// clamped to these bytes.
+	pub fn slice(&self, range: Range<usize>) -> Self {
+		let end = self.range.start + range.end.min(self.len());
+		let start = (self.range.start + range.start).min(end);
+		Self { buffer: self.buffer.clone(), range: start..end }
+	}
+
+	/// The bytes as a slice.
+	pub fn as_slice(&self) -> &[u8] {
+		&self.buffer[self.range.clone()]
+	}
+
+	/// Convert into a vector, copying only if the buffer is shared or holds
+	/// more than these bytes.
+	pub fn into_vec(self) -> Vec<u8> {
+		if self.range.start == 0 && self.range.end == self.buffer.len() {
+			match Arc::try_unwrap(self.buffer) {
+				Ok(buffer) => buffer,
+				Err(buffer) => buffer[self.range].to_vec(),
+			}
+		} else {
+			self.as_slice().to_vec()
+		}
+	}
+}
+
+impl Deref for Bytes {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		self.as_slice()
+	}
+}
+
+impl AsRef<[u8]> for Bytes {
+	fn as_ref(&self) -> &[u8] {
+		self.as_slice()
+	}
+}
+
+impl From<Vec<u8>> for Bytes {
+	fn from(buffer: Vec<u8>) -> Self {
+		let range = 0..buffer.len();
+		Self { buffer: Arc::new(buffer), range }
+	}
+}
+
+impl From<&[u8]> for Bytes {
+	fn from(data: &[u8]) -> Self {
+		Self::from(data.to_vec())
+	}
+}
+
+impl From<Bytes> for Vec<u8> {
+	fn from(bytes: Bytes) -> Self {
+		bytes.into_vec()
+	}
+}
+
+impl PartialEq for Bytes {
+	fn eq(&self, other: &Self) -> bool {
+		self.as_slice() == other.as_slice()
+	}
+}
+
+impl Eq for Bytes {}
+
+impl PartialEq<[u8]> for Bytes {
+	fn eq(&self, other: &[u8]) -> bool {
+		self.as_slice() == other
+	}
+}
+
+impl PartialEq<Vec<u8>> for Bytes {
+	fn eq(&self, other: &Vec<u8>) -> bool {
+		self.as_slice() == &other[..]
+	}
+}
+
+impl fmt::Debug for Bytes {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "Bytes(0x")?;
+		for byte in self.as_slice() {
+			write!(f, "{:02x}", byte)?;
+		}
+		write!(f, ")")
+	}
+}
+
+#[cfg(feature = "with-serde")]
+impl serde::Serialize for Bytes {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.as_slice().serialize(serializer)
+	}
+}
+
+#[cfg(feature = "with-serde")]
+impl<'de> serde::Deserialize<'de> for Bytes {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		Vec::<u8>::deserialize(deserializer).map(Self::from)
+	}
+}
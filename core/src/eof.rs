@@ -0,0 +1,352 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{ExternalOpcode, Opcode, OpcodeInfo};
+
+/// Magic bytes starting every EOF container, per EIP-3540.
+pub const EOF_MAGIC: [u8; 2] = [0xef, 0x00];
+/// Supported EOF version.
+pub const EOF_VERSION: u8 = 0x01;
+
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_DATA: u8 = 0x04;
+const TERMINATOR: u8 = 0x00;
+
+const MAX_CODE_SECTIONS: usize = 1024;
+const MAX_IO: u8 = 0x7f;
+const MAX_STACK_HEIGHT: u16 = 1023;
+
+/// Whether the code is meant to be an EOF container.
+pub fn is_eof(code: &[u8]) -> bool {
+	code.starts_with(&EOF_MAGIC)
+}
+
+/// EOF validation failure.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EofError {
+	/// Code does not start with `EOF_MAGIC`.
+	InvalidMagic,
+	/// Container version is not supported.
+	UnsupportedVersion(u8),
+	/// Header ends early or has sections in the wrong order.
+	InvalidHeader,
+	/// Number of code sections is zero or over the limit.
+	InvalidCodeSectionCount,
+	/// A code section is empty.
+	EmptyCodeSection,
+	/// Type section size does not match the number of code sections.
+	InvalidTypeSectionSize,
+	/// Type section entry is out of bounds, or the first section does not
+	/// take zero inputs and outputs.
+	InvalidTypes(usize),
+	/// Container size does not match the header.
+	InvalidContainerSize,
+	/// Undefined opcode, at the given offset into its code section.
+	UndefinedOpcode(usize),
+	/// Immediate argument runs past the end of its code section.
+	TruncatedImmediate(usize),
+	/// Relative jump targets outside of the section or into an immediate.
+	InvalidRelativeJump(usize),
+	/// `CALLF` targets a code section that does not exist.
+	InvalidSectionIndex(usize),
+	/// Code section does not end with a terminating instruction.
+	MissingTerminator(usize),
+	/// Instruction, at the given offset, is not reachable from the start
+	/// of its code section.
+	UnreachableCode(usize),
+	/// Instruction, at the given offset, takes more items than the stack
+	/// has.
+	StackUnderflow(usize),
+	/// Stack height at the given offset differs between the paths reaching
+	/// it, or differs from the section outputs at `RETF`.
+	InvalidStackHeight(usize),
+	/// Declared maximum stack height of the code section does not match
+	/// its code.
+	InvalidMaxStackHeight(usize),
+}
+
+/// Type section entry of a code section, per EIP-4750.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EofTypes {
+	/// Stack items consumed by the section.
+	pub inputs: u8,
+	/// Stack items returned by the section.
+	pub outputs: u8,
+	/// Maximum stack height reached within the section.
+	pub max_stack_height: u16,
+}
+
+/// Validated EOF container. Ranges index into the container bytes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EofContainer {
+	/// Type of each code section.
+	pub types: Vec<EofTypes>,
+	/// Code sections.
+	pub code: Vec<Range<usize>>,
+	/// Data section.
+	pub data: Range<usize>,
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, EofError> {
+	bytes.get(offset..offset + 2)
+		.map(|v| u16::from_be_bytes([v[0], v[1]]))
+		.ok_or(EofError::InvalidHeader)
+}
+
+fn expect_kind(bytes: &[u8], offset: usize, kind: u8) -> Result<(), EofError> {
+	match bytes.get(offset) {
+		Some(v) if *v == kind => Ok(()),
+		_ => Err(EofError::InvalidHeader),
+	}
+}
+
+/// Parse an opcode of EOF code. EOF-only opcodes take the place of the
+/// unknown bytes they are encoded with.
+pub fn parse_opcode(opcode: u8) -> Result<Opcode, ExternalOpcode> {
+	match opcode {
+		0xe0 => Ok(Opcode::RJump),
+		0xe1 => Ok(Opcode::RJumpI),
+		0xe3 => Ok(Opcode::CallF),
+		0xe4 => Ok(Opcode::RetF),
+		0xfe => Ok(Opcode::Invalid),
+		other => Opcode::parse(other),
+	}
+}
+
+/// Length of the immediate argument of an EOF opcode.
+fn immediate_len(opcode: u8) -> usize {
+	match parse_opcode(opcode) {
+		Ok(Opcode::Push(n)) => n as usize,
+		Ok(Opcode::RJump) | Ok(Opcode::RJumpI) | Ok(Opcode::CallF) => 2,
+		_ => 0,
+	}
+}
+
+/// Whether the opcode is defined in EOF code, per EIP-3670. Dynamic jumps,
+/// `PC`, `CALLCODE` and `SELFDESTRUCT` are not.
+fn is_defined(opcode: u8) -> bool {
+	!matches!(
+		parse_opcode(opcode),
+		Ok(Opcode::Jump) | Ok(Opcode::JumpI) | Ok(Opcode::PC) |
+		Err(ExternalOpcode::CallCode) | Err(ExternalOpcode::Suicide) | Err(ExternalOpcode::Other(_))
+	)
+}
+
+fn is_terminating(opcode: u8) -> bool {
+	matches!(opcode, 0x00 | 0xf3 | 0xfd | 0xfe | 0xe0 | 0xe4)
+}
+
+impl EofContainer {
+	/// Parse and validate a container, per EIP-3540, EIP-3670, EIP-4200,
+	/// EIP-4750 and EIP-5450.
+	pub fn new(bytes: &[u8]) -> Result<Self, EofError> {
+		let container = Self::parse(bytes)?;
+		for (index, code) in container.code.iter().enumerate() {
+			container.validate_code(&bytes[code.clone()], index)?;
+		}
+		Ok(container)
+	}
+
+	/// Parse the container header and type section.
+	fn parse(bytes: &[u8]) -> Result<Self, EofError> {
+		if !is_eof(bytes) {
+			return Err(EofError::InvalidMagic)
+		}
+		match bytes.get(2) {
+			Some(&EOF_VERSION) => (),
+			Some(version) => return Err(EofError::UnsupportedVersion(*version)),
+			None => return Err(EofError::InvalidHeader),
+		}
+
+		expect_kind(bytes, 3, KIND_TYPES)?;
+		let types_size = read_u16(bytes, 4)? as usize;
+
+		expect_kind(bytes, 6, KIND_CODE)?;
+		let sections = read_u16(bytes, 7)? as usize;
+		if sections == 0 || sections > MAX_CODE_SECTIONS {
+			return Err(EofError::InvalidCodeSectionCount)
+		}
+		let mut code_sizes = Vec::with_capacity(sections);
+		for i in 0..sections {
+			let size = read_u16(bytes, 9 + i * 2)? as usize;
+			if size == 0 {
+				return Err(EofError::EmptyCodeSection)
+			}
+			code_sizes.push(size);
+		}
+
+		let offset = 9 + sections * 2;
+		expect_kind(bytes, offset, KIND_DATA)?;
+		let data_size = read_u16(bytes, offset + 1)? as usize;
+		expect_kind(bytes, offset + 3, TERMINATOR)?;
+
+		if types_size != sections * 4 {
+			return Err(EofError::InvalidTypeSectionSize)
+		}
+
+		let mut position = offset + 4;
+		let total = position + types_size + code_sizes.iter().sum::<usize>() + data_size;
+		if bytes.len() != total {
+			return Err(EofError::InvalidContainerSize)
+		}
+
+		let mut types = Vec::with_capacity(sections);
+		for index in 0..sections {
+			let entry = EofTypes {
+				inputs: bytes[position],
+				outputs: bytes[position + 1],
+				max_stack_height: read_u16(bytes, position + 2)?,
+			};
+			let first_invalid = index == 0 && (entry.inputs != 0 || entry.outputs != 0);
+			if first_invalid || entry.inputs > MAX_IO || entry.outputs > MAX_IO ||
+				entry.max_stack_height > MAX_STACK_HEIGHT
+			{
+				return Err(EofError::InvalidTypes(index))
+			}
+			types.push(entry);
+			position += 4;
+		}
+
+		let mut code = Vec::with_capacity(sections);
+		for size in code_sizes {
+			code.push(position..position + size);
+			position += size;
+		}
+
+		Ok(Self { types, code, data: position..position + data_size })
+	}
+
+	/// Validate the instructions of a code section.
+	fn validate_code(&self, code: &[u8], section: usize) -> Result<(), EofError> {
+		let mut boundaries = Vec::new();
+		boundaries.resize(code.len(), false);
+		let mut jumps = Vec::new();
+
+		let mut i = 0;
+		let mut last = 0;
+		while i < code.len() {
+			let opcode = code[i];
+			if !is_defined(opcode) {
+				return Err(EofError::UndefinedOpcode(i))
+			}
+			boundaries[i] = true;
+
+			let immediate = immediate_len(opcode);
+			if i + immediate >= code.len() {
+				return Err(EofError::TruncatedImmediate(i))
+			}
+			match parse_opcode(opcode) {
+				Ok(Opcode::RJump) | Ok(Opcode::RJumpI) => {
+					let offset = i16::from_be_bytes([code[i + 1], code[i + 2]]);
+					jumps.push((i, i as isize + 3 + offset as isize));
+				},
+				Ok(Opcode::CallF) => {
+					let index = u16::from_be_bytes([code[i + 1], code[i + 2]]) as usize;
+					if index >= self.code.len() {
+						return Err(EofError::InvalidSectionIndex(i))
+					}
+				},
+				_ => (),
+			}
+
+			last = opcode;
+			i += 1 + immediate;
+		}
+
+		for (position, target) in jumps {
+			if target < 0 || target as usize >= code.len() || !boundaries[target as usize] {
+				return Err(EofError::InvalidRelativeJump(position))
+			}
+		}
+
+		if !is_terminating(last) {
+			return Err(EofError::MissingTerminator(section))
+		}
+
+		self.validate_stack(code, section)
+	}
+
+	/// Validate the stack heights of a valid code section, per EIP-5450:
+	/// every instruction is reachable with a single stack height, never
+	/// underflows, `RETF` returns the section outputs, and the declared
+	/// maximum height is the one reached.
+	fn validate_stack(&self, code: &[u8], section: usize) -> Result<(), EofError> {
+		let types = self.types[section];
+		let mut heights: Vec<Option<usize>> = Vec::new();
+		heights.resize(code.len(), None);
+		heights[0] = Some(types.inputs as usize);
+		let mut pending = alloc::vec![0];
+		let mut max_height = types.inputs as usize;
+
+		while let Some(i) = pending.pop() {
+			let height = heights[i].expect("pending instructions have a height");
+			let opcode = code[i];
+			let (inputs, outputs) = match parse_opcode(opcode) {
+				Ok(Opcode::RJump) => (0, 0),
+				Ok(Opcode::RJumpI) => (1, 0),
+				Ok(Opcode::CallF) => {
+					let index = u16::from_be_bytes([code[i + 1], code[i + 2]]) as usize;
+					(self.types[index].inputs as usize, self.types[index].outputs as usize)
+				},
+				Ok(Opcode::RetF) => {
+					if height != types.outputs as usize {
+						return Err(EofError::InvalidStackHeight(i))
+					}
+					(0, 0)
+				},
+				_ => OpcodeInfo::of(opcode)
+					.map(|info| (info.inputs as usize, info.outputs as usize))
+					.unwrap_or((0, 0)),
+			};
+			if height < inputs {
+				return Err(EofError::StackUnderflow(i))
+			}
+			let next_height = height - inputs + outputs;
+			max_height = core::cmp::max(max_height, next_height);
+
+			let next = i + 1 + immediate_len(opcode);
+			let mut successors = Vec::with_capacity(2);
+			if !is_terminating(opcode) {
+				successors.push(next);
+			}
+			if let Ok(Opcode::RJump) | Ok(Opcode::RJumpI) = parse_opcode(opcode) {
+				let offset = i16::from_be_bytes([code[i + 1], code[i + 2]]);
+				successors.push((next as isize + offset as isize) as usize);
+			}
+
+			for successor in successors {
+				match heights[successor] {
+					Some(expected) if expected != next_height =>
+						return Err(EofError::InvalidStackHeight(successor)),
+					Some(_) => (),
+					None => {
+						heights[successor] = Some(next_height);
+						pending.push(successor);
+					},
+				}
+			}
+		}
+
+		let mut i = 0;
+		while i < code.len() {
+			if heights[i].is_none() {
+				return Err(EofError::UnreachableCode(i))
+			}
+			i += 1 + immediate_len(code[i]);
+		}
+
+		if max_height != types.max_stack_height as usize {
+			return Err(EofError::InvalidMaxStackHeight(section))
+		}
+		Ok(())
+	}
+}
+
+/// Execution state of an EOF container.
+#[derive(Clone, Debug)]
+pub(crate) struct EofState {
+	pub container: EofContainer,
+	pub section: usize,
+	pub return_stack: Vec<(usize, usize)>,
+}
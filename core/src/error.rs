@@ -1,3 +1,5 @@
+use primitive_types::U256;
+
 use crate::ExternalOpcode;
 
 /// Trap which indicates that an `ExternalOpcode` has to be handled.
@@ -15,6 +17,7 @@ pub enum Capture<E, T> {
 
 /// Exit reason.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
 pub enum ExitReason {
 	/// Machine has succeeded.
 	Succeed(ExitSucceed),
@@ -39,6 +42,7 @@ impl ExitReason {
 
 /// Exit succeed reason.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
 pub enum ExitSucceed {
 	/// Machine encountered an explict stop.
 	Stopped,
@@ -56,6 +60,7 @@ impl From<ExitSucceed> for ExitReason {
 
 /// Exit revert reason.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
 pub enum ExitRevert {
 	/// Machine encountered an explict revert.
 	Reverted,
@@ -69,6 +74,7 @@ impl From<ExitRevert> for ExitReason {
 
 /// Exit error reason.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
 pub enum ExitError {
 	/// Trying to pop from an empty stack.
 	StackUnderflow,
@@ -84,8 +90,17 @@ pub enum ExitError {
 	CallTooDeep,
 	/// Create opcode encountered collision (runtime).
 	CreateCollision,
-	/// Create init code exceeds limit (runtime).
+	/// Deployed code exceeds the code size limit (runtime).
 	CreateContractLimit,
+	/// Create init code exceeds the init code size limit (runtime).
+	InitCodeLimit,
+	/// Logs of the transaction exceed the log data size limit (runtime).
+	LogDataLimit,
+	/// Return data of a frame exceeds the return data size limit (runtime).
+	ReturnDataLimit,
+	/// Code is not a valid EOF container, or deployed code starts with the
+	/// EOF magic without being one (runtime).
+	InvalidCode,
 
 	///	An opcode accesses external information, but the request is off offset
 	///	limit (runtime).
@@ -94,6 +109,19 @@ pub enum ExitError {
 	OutOfGas,
 	/// Not enough fund to start the execution (runtime).
 	OutOfFund,
+	/// The transaction nonce is not the current nonce of its caller.
+	InvalidNonce {
+		/// Current nonce of the caller.
+		expected: U256,
+		/// Nonce of the transaction.
+		got: U256,
+	},
+	/// The transaction gas price is below the base fee.
+	GasPriceBelowBaseFee,
+	/// Attempt to modify state inside a static call frame (runtime).
+	StaticModeViolation,
+	/// Opcode is disallowed by the configured opcode filter (runtime).
+	DisallowedOpcode(u8),
 
 	/// PC underflowed (unused).
 	PCUnderflow,
@@ -112,6 +140,7 @@ impl From<ExitError> for ExitReason {
 
 /// Exit fatal reason.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
 pub enum ExitFatal {
 	/// The operation is not supported.
 	NotSupported,
@@ -119,6 +148,17 @@ pub enum ExitFatal {
 	UnhandledInterrupt,
 	/// The environment explictly set call errors as fatal error.
 	CallErrorAsFatal(ExitError),
+	/// Execution was cancelled through a cancellation token.
+	Cancelled,
+	/// Execution ran past its deadline.
+	DeadlineExceeded,
+	/// The backend failed to read state.
+	BackendError,
+	/// State outside the executor's state allowlist was accessed.
+	MissingState,
+	/// Execution reached a condition rejected by the strict mode of the
+	/// runtime config.
+	Strict(StrictViolation),
 
 	/// Other fatal errors.
 	Other(&'static str),
@@ -129,3 +169,46 @@ impl From<ExitFatal> for ExitReason {
 		Self::Fatal(s)
 	}
 }
+
+/// Condition on which implementations commonly diverge, rejected in strict
+/// mode.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
+pub enum StrictViolation {
+	/// The opcode at `position` takes more stack items than the `len` items
+	/// on the stack.
+	StackOutOfRange {
+		/// Program counter of the opcode.
+		position: usize,
+		/// Opcode byte.
+		opcode: u8,
+		/// Stack length.
+		len: usize,
+	},
+	/// `RETURNDATACOPY` at `position` reads past the end of the return data
+	/// buffer.
+	ReturnDataOutOfBounds {
+		/// Program counter of the opcode.
+		position: usize,
+		/// Offset read in the return data buffer.
+		offset: U256,
+		/// Length read.
+		len: U256,
+		/// Length of the return data buffer.
+		available: usize,
+	},
+	/// The jump at `position` lands on a `JUMPDEST` byte inside `PUSHn`
+	/// data.
+	JumpIntoPushData {
+		/// Program counter of the jump.
+		position: usize,
+		/// Jump destination.
+		destination: usize,
+	},
+}
+
+impl From<StrictViolation> for ExitReason {
+	fn from(s: StrictViolation) -> Self {
+		Self::Fatal(ExitFatal::Strict(s))
+	}
+}
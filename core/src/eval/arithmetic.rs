@@ -37,46 +37,135 @@ pub fn srem(op1: U256, op2: U256) -> U256 {
 	}
 }
 
-pub fn addmod(op1: U256, op2: U256, op3: U256) -> U256 {
-	let op1: U512 = op1.into();
-	let op2: U512 = op2.into();
-	let op3: U512 = op3.into();
+/// Hot-path 256-bit arithmetic, so that an optimized implementation can
+/// replace the one on `primitive_types` without touching the opcodes.
+pub trait U256Ops {
+	/// `(op1 + op2) % op3`, computed without overflow, or zero if `op3` is
+	/// zero.
+	fn addmod(op1: U256, op2: U256, op3: U256) -> U256;
+	/// `(op1 * op2) % op3`, computed without overflow, or zero if `op3` is
+	/// zero.
+	fn mulmod(op1: U256, op2: U256, op3: U256) -> U256;
+	/// `op1 ** op2`, wrapping.
+	fn exp(op1: U256, op2: U256) -> U256;
+}
 
-	if op3 == U512::zero() {
-		U256::zero()
-	} else {
-		let v = (op1 + op2) % op3;
-		v.try_into().expect("op3 is less than U256::max_value(), thus it never overflows; qed")
+/// Arithmetic on `primitive_types`, widening to `U512` for modular
+/// operations.
+pub struct Reference;
+
+impl U256Ops for Reference {
+	fn addmod(op1: U256, op2: U256, op3: U256) -> U256 {
+		let op1: U512 = op1.into();
+		let op2: U512 = op2.into();
+		let op3: U512 = op3.into();
+
+		if op3 == U512::zero() {
+			U256::zero()
+		} else {
+			let v = (op1 + op2) % op3;
+			v.try_into().expect("op3 is less than U256::max_value(), thus it never overflows; qed")
+		}
 	}
-}
 
-pub fn mulmod(op1: U256, op2: U256, op3: U256) -> U256 {
-	let op1: U512 = op1.into();
-	let op2: U512 = op2.into();
-	let op3: U512 = op3.into();
+	fn mulmod(op1: U256, op2: U256, op3: U256) -> U256 {
+		let op1: U512 = op1.into();
+		let op2: U512 = op2.into();
+		let op3: U512 = op3.into();
 
-	if op3 == U512::zero() {
-		U256::zero()
-	} else {
-		let v = (op1 * op2) % op3;
-		v.try_into().expect("op3 is less than U256::max_value(), thus it never overflows; qed")
+		if op3 == U512::zero() {
+			U256::zero()
+		} else {
+			let v = (op1 * op2) % op3;
+			v.try_into().expect("op3 is less than U256::max_value(), thus it never overflows; qed")
+		}
+	}
+
+	fn exp(op1: U256, op2: U256) -> U256 {
+		let mut op1 = op1;
+		let mut op2 = op2;
+		let mut r: U256 = 1.into();
+
+		while op2 != 0.into() {
+			if op2 & 1.into() != 0.into() {
+				r = r.overflowing_mul(op1).0;
+			}
+			op2 = op2 >> 1;
+			op1 = op1.overflowing_mul(op1).0;
+		}
+
+		r
 	}
 }
 
-pub fn exp(op1: U256, op2: U256) -> U256 {
-	let mut op1 = op1;
-	let mut op2 = op2;
-	let mut r: U256 = 1.into();
+/// Arithmetic staying in 256 bits where the operands allow it, falling
+/// back to `Reference` otherwise. Used by the opcodes with the
+/// `fast-arithmetic` feature.
+pub struct Fast;
+
+impl U256Ops for Fast {
+	fn addmod(op1: U256, op2: U256, op3: U256) -> U256 {
+		if op3 == U256::zero() {
+			return U256::zero()
+		}
 
-	while op2 != 0.into() {
-		if op2 & 1.into() != 0.into() {
-			r = r.overflowing_mul(op1).0;
+		// Both reduced operands are below `op3`, so their sum is below
+		// `2 * op3` and one subtraction, wrapping on overflow, reduces it.
+		let (sum, overflow) = (op1 % op3).overflowing_add(op2 % op3);
+		if overflow || sum >= op3 {
+			sum.overflowing_sub(op3).0
+		} else {
+			sum
 		}
-		op2 = op2 >> 1;
-		op1 = op1.overflowing_mul(op1).0;
 	}
 
-	r
+	fn mulmod(op1: U256, op2: U256, op3: U256) -> U256 {
+		if op3 == U256::zero() {
+			return U256::zero()
+		}
+
+		let op1 = op1 % op3;
+		let op2 = op2 % op3;
+		if op1.bits() + op2.bits() <= 256 {
+			(op1 * op2) % op3
+		} else {
+			Reference::mulmod(op1, op2, op3)
+		}
+	}
+
+	fn exp(op1: U256, op2: U256) -> U256 {
+		if op1 == U256::from(2) {
+			return if op2 < U256::from(256) { U256::one() << op2.as_usize() } else { U256::zero() }
+		}
+
+		// Left-to-right square and multiply, reading exponent bits in place.
+		let mut r = U256::one();
+		for i in (0..op2.bits()).rev() {
+			r = r.overflowing_mul(r).0;
+			if op2.bit(i) {
+				r = r.overflowing_mul(op1).0;
+			}
+		}
+
+		r
+	}
+}
+
+#[cfg(not(feature = "fast-arithmetic"))]
+type Ops = Reference;
+#[cfg(feature = "fast-arithmetic")]
+type Ops = Fast;
+
+pub fn addmod(op1: U256, op2: U256, op3: U256) -> U256 {
+	Ops::addmod(op1, op2, op3)
+}
+
+pub fn mulmod(op1: U256, op2: U256, op3: U256) -> U256 {
+	Ops::mulmod(op1, op2, op3)
+}
+
+pub fn exp(op1: U256, op2: U256) -> U256 {
+	Ops::exp(op1, op2)
 }
 
 pub fn signextend(op1: U256, op2: U256) -> U256 {
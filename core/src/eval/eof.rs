@@ -0,0 +1,69 @@
+use primitive_types::H256;
+
+use crate::{ExitError, ExitFatal, ExitSucceed, Machine};
+
+use super::Control;
+
+/// Maximum depth of the `CALLF` return stack, per EIP-4750.
+const RETURN_STACK_LIMIT: usize = 1024;
+
+fn immediate(state: &Machine, position: usize) -> [u8; 2] {
+	[state.code[position + 1], state.code[position + 2]]
+}
+
+pub fn rjump(state: &mut Machine, position: usize) -> Control {
+	let offset = i16::from_be_bytes(immediate(state, position));
+	trace_op!("RJump: {}", offset);
+	Control::Jump((position as isize + 3 + offset as isize) as usize)
+}
+
+pub fn rjumpi(state: &mut Machine, position: usize) -> Control {
+	pop!(state, condition);
+	trace_op!("RJumpI: {}", condition);
+	if condition == H256::zero() {
+		Control::Continue(3)
+	} else {
+		rjump(state, position)
+	}
+}
+
+pub fn callf(state: &mut Machine, position: usize) -> Control {
+	let index = u16::from_be_bytes(immediate(state, position)) as usize;
+	trace_op!("CallF: {}", index);
+
+	let stack_len = state.stack.len();
+	let stack_limit = state.stack.limit();
+	let eof = match state.eof.as_mut() {
+		Some(eof) => eof,
+		None => return Control::Exit(ExitFatal::NotSupported.into()),
+	};
+	let types = eof.container.types[index];
+	if stack_len < types.inputs as usize {
+		return Control::Exit(ExitError::StackUnderflow.into())
+	}
+	if stack_len - types.inputs as usize + types.max_stack_height as usize > stack_limit ||
+		eof.return_stack.len() >= RETURN_STACK_LIMIT
+	{
+		return Control::Exit(ExitError::StackOverflow.into())
+	}
+
+	eof.return_stack.push((eof.section, position + 3));
+	eof.section = index;
+	Control::Jump(eof.container.code[index].start)
+}
+
+pub fn retf(state: &mut Machine) -> Control {
+	trace_op!("RetF");
+	let eof = match state.eof.as_mut() {
+		Some(eof) => eof,
+		None => return Control::Exit(ExitFatal::NotSupported.into()),
+	};
+
+	match eof.return_stack.pop() {
+		Some((section, position)) => {
+			eof.section = section;
+			Control::Jump(position)
+		},
+		None => Control::Exit(ExitSucceed::Stopped.into()),
+	}
+}
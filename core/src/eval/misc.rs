@@ -111,7 +111,7 @@ pub fn jump(state: &mut Machine) -> Control {
 	let dest = as_usize_or_fail!(dest, ExitError::InvalidJump);
 	trace_op!("Jump: {}", dest);
 
-	if state.valids.is_valid(dest) {
+	if state.analysis.valids().is_valid(dest) {
 		Control::Jump(dest)
 	} else {
 		Control::Exit(ExitError::InvalidJump.into())
@@ -124,7 +124,7 @@ pub fn jumpi(state: &mut Machine) -> Control {
 
 	if value != U256::zero() {
 		trace_op!("JumpI: {}", dest);
-		if state.valids.is_valid(dest) {
+		if state.analysis.valids().is_valid(dest) {
 			Control::Jump(dest)
 		} else {
 			Control::Exit(ExitError::InvalidJump.into())
@@ -148,8 +148,13 @@ pub fn msize(state: &mut Machine) -> Control {
 }
 
 pub fn push(state: &mut Machine, n: usize, position: usize) -> Control {
-	let end = min(position + 1 + n, state.code.len());
-	let val = U256::from(&state.code[(position + 1)..end]);
+	let val = match state.analysis.immediate(position) {
+		Some(val) => val,
+		None => {
+			let end = min(position + 1 + n, state.code.len());
+			U256::from(&state.code[(position + 1)..end])
+		},
+	};
 
 	push_u256!(state, val);
 	trace_op!("Push [@{}]: {}", state.stack.len() - 1, val);
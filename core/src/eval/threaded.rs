@@ -0,0 +1,85 @@
+use core::ops::{BitAnd, BitOr, BitXor};
+
+use primitive_types::{H256, U256};
+
+use crate::{ExitError, ExitSucceed, Machine, Opcode};
+use super::Control;
+
+/// Handler executing one instruction, given the machine and the program
+/// counter.
+pub type Handler = fn(&mut Machine, usize) -> Control;
+
+/// Translate an opcode into its handler. `eval` dispatches through it, and
+/// threaded code stores the handlers so that execution calls them directly
+/// instead of dispatching on the opcode at every step. `PUSHn`, `DUPn` and
+/// `SWAPn` handlers read `n` back from the code byte.
+pub fn handler(opcode: Opcode) -> Handler {
+	match opcode {
+		Opcode::Stop => |_, _| Control::Exit(ExitSucceed::Stopped.into()),
+		Opcode::Add => |state, _| op2_u256_tuple!(state, overflowing_add),
+		Opcode::Mul => |state, _| op2_u256_tuple!(state, overflowing_mul),
+		Opcode::Sub => |state, _| op2_u256_tuple!(state, overflowing_sub),
+		Opcode::Div => |state, _| op2_u256_fn!(state, super::arithmetic::div),
+		Opcode::SDiv => |state, _| op2_u256_fn!(state, super::arithmetic::sdiv),
+		Opcode::Mod => |state, _| op2_u256_fn!(state, super::arithmetic::rem),
+		Opcode::SMod => |state, _| op2_u256_fn!(state, super::arithmetic::srem),
+		Opcode::AddMod => |state, _| op3_u256_fn!(state, super::arithmetic::addmod),
+		Opcode::MulMod => |state, _| op3_u256_fn!(state, super::arithmetic::mulmod),
+		Opcode::Exp => |state, _| op2_u256_fn!(state, super::arithmetic::exp),
+		Opcode::SignExtend => |state, _| op2_u256_fn!(state, super::arithmetic::signextend),
+		Opcode::Lt => |state, _| op2_u256_bool_ref!(state, lt),
+		Opcode::Gt => |state, _| op2_u256_bool_ref!(state, gt),
+		Opcode::SLt => |state, _| op2_u256_fn!(state, super::bitwise::slt),
+		Opcode::SGt => |state, _| op2_u256_fn!(state, super::bitwise::sgt),
+		Opcode::Eq => |state, _| op2_u256_bool_ref!(state, eq),
+		Opcode::IsZero => |state, _| op1_u256_fn!(state, super::bitwise::iszero),
+		Opcode::And => |state, _| op2_u256!(state, bitand),
+		Opcode::Or => |state, _| op2_u256!(state, bitor),
+		Opcode::Xor => |state, _| op2_u256!(state, bitxor),
+		Opcode::Not => |state, _| op1_u256_fn!(state, super::bitwise::not),
+		Opcode::Byte => |state, _| op2_u256_fn!(state, super::bitwise::byte),
+		Opcode::Shl => |state, _| op2_u256_fn!(state, super::bitwise::shl),
+		Opcode::Shr => |state, _| op2_u256_fn!(state, super::bitwise::shr),
+		Opcode::Sar => |state, _| op2_u256_fn!(state, super::bitwise::sar),
+		Opcode::CodeSize => |state, _| super::misc::codesize(state),
+		Opcode::CodeCopy => |state, _| super::misc::codecopy(state),
+		Opcode::CallDataLoad => |state, _| super::misc::calldataload(state),
+		Opcode::CallDataSize => |state, _| super::misc::calldatasize(state),
+		Opcode::CallDataCopy => |state, _| super::misc::calldatacopy(state),
+		Opcode::Pop => |state, _| super::misc::pop(state),
+		Opcode::MLoad => |state, _| super::misc::mload(state),
+		Opcode::MStore => |state, _| super::misc::mstore(state),
+		Opcode::MStore8 => |state, _| super::misc::mstore8(state),
+		Opcode::Jump => |state, _| super::misc::jump(state),
+		Opcode::JumpI => |state, _| super::misc::jumpi(state),
+		Opcode::PC => super::misc::pc,
+		Opcode::MSize => |state, _| super::misc::msize(state),
+		Opcode::JumpDest => |_, _| Control::Continue(1),
+		Opcode::Push(_) => |state, position| {
+			let n = (state.code[position] - 0x5f) as usize;
+			super::misc::push(state, n, position)
+		},
+		Opcode::Dup(_) => |state, position| {
+			let n = (state.code[position] - 0x7f) as usize;
+			super::misc::dup(state, n)
+		},
+		Opcode::Swap(_) => |state, position| {
+			let n = (state.code[position] - 0x8f) as usize;
+			super::misc::swap(state, n)
+		},
+		Opcode::Return => |state, _| super::misc::ret(state),
+		Opcode::Revert => |state, _| super::misc::revert(state),
+		Opcode::Invalid => |_, _| Control::Exit(ExitError::DesignatedInvalid.into()),
+		#[cfg(feature = "eof")]
+		Opcode::RJump => super::eof::rjump,
+		#[cfg(feature = "eof")]
+		Opcode::RJumpI => super::eof::rjumpi,
+		#[cfg(feature = "eof")]
+		Opcode::CallF => super::eof::callf,
+		#[cfg(feature = "eof")]
+		Opcode::RetF => |state, _| super::eof::retf(state),
+		#[cfg(not(feature = "eof"))]
+		Opcode::RJump | Opcode::RJumpI | Opcode::CallF | Opcode::RetF =>
+			|_, _| Control::Exit(ExitError::DesignatedInvalid.into()),
+	}
+}
@@ -0,0 +1,251 @@
+use alloc::string::String;
+use core::fmt::Write;
+
+/// Fork introducing an opcode.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
+pub enum Fork {
+	/// Frontier.
+	Frontier,
+	/// Homestead.
+	Homestead,
+	/// Byzantium.
+	Byzantium,
+	/// Constantinople.
+	Constantinople,
+	/// Istanbul.
+	Istanbul,
+	/// Shanghai.
+	Shanghai,
+}
+
+impl Fork {
+	/// Lowercase name of the fork.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Fork::Frontier => "frontier",
+			Fork::Homestead => "homestead",
+			Fork::Byzantium => "byzantium",
+			Fork::Constantinople => "constantinople",
+			Fork::Istanbul => "istanbul",
+			Fork::Shanghai => "shanghai",
+		}
+	}
+}
+
+/// Static metadata of an opcode.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
+pub struct OpcodeInfo {
+	/// Opcode byte.
+	pub opcode: u8,
+	/// Mnemonic, as in `PUSH1` or `SSTORE`.
+	pub mnemonic: &'static str,
+	/// Number of stack items taken.
+	pub inputs: u8,
+	/// Number of stack items pushed.
+	pub outputs: u8,
+	/// Number of immediate bytes following the opcode in code.
+	pub immediate_size: u8,
+	/// Fork introducing the opcode.
+	pub fork: Fork,
+}
+
+impl OpcodeInfo {
+	/// Metadata of the opcode byte, or `None` if it is undefined.
+	pub fn of(opcode: u8) -> Option<&'static OpcodeInfo> {
+		OPCODE_INFOS.binary_search_by_key(&opcode, |info| info.opcode).ok().map(|index| &OPCODE_INFOS[index])
+	}
+
+	/// JSON object with `opcode`, `mnemonic`, `inputs`, `outputs`,
+	/// `immediateSize` and `fork` fields.
+	pub fn to_json(&self) -> String {
+		let mut json = String::new();
+		write!(
+			json,
+			"{{\"opcode\":{},\"mnemonic\":\"{}\",\"inputs\":{},\"outputs\":{},\"immediateSize\":{},\"fork\":\"{}\"}}",
+			self.opcode, self.mnemonic, self.inputs, self.outputs, self.immediate_size, self.fork.name(),
+		).expect("writing to a string cannot fail");
+		json
+	}
+}
+
+/// JSON array of `OPCODE_INFOS`, in the format of `OpcodeInfo::to_json`.
+pub fn opcode_infos_json() -> String {
+	let mut json = String::from("[");
+	for (i, info) in OPCODE_INFOS.iter().enumerate() {
+		if i > 0 {
+			json.push(',');
+		}
+		json.push_str(&info.to_json());
+	}
+	json.push(']');
+	json
+}
+
+const fn info(opcode: u8, mnemonic: &'static str, inputs: u8, outputs: u8, fork: Fork) -> OpcodeInfo {
+	OpcodeInfo { opcode, mnemonic, inputs, outputs, immediate_size: 0, fork }
+}
+
+const fn push(n: u8, mnemonic: &'static str, fork: Fork) -> OpcodeInfo {
+	OpcodeInfo { opcode: 0x5f + n, mnemonic, inputs: 0, outputs: 1, immediate_size: n, fork }
+}
+
+/// Metadata of every opcode defined in legacy code, by increasing opcode
+/// byte. The experimental `AUTH` and `AUTHCALL`, and the opcodes of EOF
+/// code, are not listed.
+pub static OPCODE_INFOS: &[OpcodeInfo] = &[
+	info(0x00, "STOP", 0, 0, Fork::Frontier),
+	info(0x01, "ADD", 2, 1, Fork::Frontier),
+	info(0x02, "MUL", 2, 1, Fork::Frontier),
+	info(0x03, "SUB", 2, 1, Fork::Frontier),
+	info(0x04, "DIV", 2, 1, Fork::Frontier),
+	info(0x05, "SDIV", 2, 1, Fork::Frontier),
+	info(0x06, "MOD", 2, 1, Fork::Frontier),
+	info(0x07, "SMOD", 2, 1, Fork::Frontier),
+	info(0x08, "ADDMOD", 3, 1, Fork::Frontier),
+	info(0x09, "MULMOD", 3, 1, Fork::Frontier),
+	info(0x0a, "EXP", 2, 1, Fork::Frontier),
+	info(0x0b, "SIGNEXTEND", 2, 1, Fork::Frontier),
+
+	info(0x10, "LT", 2, 1, Fork::Frontier),
+	info(0x11, "GT", 2, 1, Fork::Frontier),
+	info(0x12, "SLT", 2, 1, Fork::Frontier),
+	info(0x13, "SGT", 2, 1, Fork::Frontier),
+	info(0x14, "EQ", 2, 1, Fork::Frontier),
+	info(0x15, "ISZERO", 1, 1, Fork::Frontier),
+	info(0x16, "AND", 2, 1, Fork::Frontier),
+	info(0x17, "OR", 2, 1, Fork::Frontier),
+	info(0x18, "XOR", 2, 1, Fork::Frontier),
+	info(0x19, "NOT", 1, 1, Fork::Frontier),
+	info(0x1a, "BYTE", 2, 1, Fork::Frontier),
+	info(0x1b, "SHL", 2, 1, Fork::Constantinople),
+	info(0x1c, "SHR", 2, 1, Fork::Constantinople),
+	info(0x1d, "SAR", 2, 1, Fork::Constantinople),
+
+	info(0x20, "SHA3", 2, 1, Fork::Frontier),
+
+	info(0x30, "ADDRESS", 0, 1, Fork::Frontier),
+	info(0x31, "BALANCE", 1, 1, Fork::Frontier),
+	info(0x32, "ORIGIN", 0, 1, Fork::Frontier),
+	info(0x33, "CALLER", 0, 1, Fork::Frontier),
+	info(0x34, "CALLVALUE", 0, 1, Fork::Frontier),
+	info(0x35, "CALLDATALOAD", 1, 1, Fork::Frontier),
+	info(0x36, "CALLDATASIZE", 0, 1, Fork::Frontier),
+	info(0x37, "CALLDATACOPY", 3, 0, Fork::Frontier),
+	info(0x38, "CODESIZE", 0, 1, Fork::Frontier),
+	info(0x39, "CODECOPY", 3, 0, Fork::Frontier),
+	info(0x3a, "GASPRICE", 0, 1, Fork::Frontier),
+	info(0x3b, "EXTCODESIZE", 1, 1, Fork::Frontier),
+	info(0x3c, "EXTCODECOPY", 4, 0, Fork::Frontier),
+	info(0x3d, "RETURNDATASIZE", 0, 1, Fork::Byzantium),
+	info(0x3e, "RETURNDATACOPY", 3, 0, Fork::Byzantium),
+	info(0x3f, "EXTCODEHASH", 1, 1, Fork::Constantinople),
+
+	info(0x40, "BLOCKHASH", 1, 1, Fork::Frontier),
+	info(0x41, "COINBASE", 0, 1, Fork::Frontier),
+	info(0x42, "TIMESTAMP", 0, 1, Fork::Frontier),
+	info(0x43, "NUMBER", 0, 1, Fork::Frontier),
+	info(0x44, "DIFFICULTY", 0, 1, Fork::Frontier),
+	info(0x45, "GASLIMIT", 0, 1, Fork::Frontier),
+	info(0x46, "CHAINID", 0, 1, Fork::Istanbul),
+	info(0x47, "SELFBALANCE", 0, 1, Fork::Istanbul),
+
+	info(0x50, "POP", 1, 0, Fork::Frontier),
+	info(0x51, "MLOAD", 1, 1, Fork::Frontier),
+	info(0x52, "MSTORE", 2, 0, Fork::Frontier),
+	info(0x53, "MSTORE8", 2, 0, Fork::Frontier),
+	info(0x54, "SLOAD", 1, 1, Fork::Frontier),
+	info(0x55, "SSTORE", 2, 0, Fork::Frontier),
+	info(0x56, "JUMP", 1, 0, Fork::Frontier),
+	info(0x57, "JUMPI", 2, 0, Fork::Frontier),
+	info(0x58, "PC", 0, 1, Fork::Frontier),
+	info(0x59, "MSIZE", 0, 1, Fork::Frontier),
+	info(0x5a, "GAS", 0, 1, Fork::Frontier),
+	info(0x5b, "JUMPDEST", 0, 0, Fork::Frontier),
+
+	push(0, "PUSH0", Fork::Shanghai),
+	push(1, "PUSH1", Fork::Frontier),
+	push(2, "PUSH2", Fork::Frontier),
+	push(3, "PUSH3", Fork::Frontier),
+	push(4, "PUSH4", Fork::Frontier),
+	push(5, "PUSH5", Fork::Frontier),
+	push(6, "PUSH6", Fork::Frontier),
+	push(7, "PUSH7", Fork::Frontier),
+	push(8, "PUSH8", Fork::Frontier),
+	push(9, "PUSH9", Fork::Frontier),
+	push(10, "PUSH10", Fork::Frontier),
+	push(11, "PUSH11", Fork::Frontier),
+	push(12, "PUSH12", Fork::Frontier),
+	push(13, "PUSH13", Fork::Frontier),
+	push(14, "PUSH14", Fork::Frontier),
+	push(15, "PUSH15", Fork::Frontier),
+	push(16, "PUSH16", Fork::Frontier),
+	push(17, "PUSH17", Fork::Frontier),
+	push(18, "PUSH18", Fork::Frontier),
+	push(19, "PUSH19", Fork::Frontier),
+	push(20, "PUSH20", Fork::Frontier),
+	push(21, "PUSH21", Fork::Frontier),
+	push(22, "PUSH22", Fork::Frontier),
+	push(23, "PUSH23", Fork::Frontier),
+	push(24, "PUSH24", Fork::Frontier),
+	push(25, "PUSH25", Fork::Frontier),
+	push(26, "PUSH26", Fork::Frontier),
+	push(27, "PUSH27", Fork::Frontier),
+	push(28, "PUSH28", Fork::Frontier),
+	push(29, "PUSH29", Fork::Frontier),
+	push(30, "PUSH30", Fork::Frontier),
+	push(31, "PUSH31", Fork::Frontier),
+	push(32, "PUSH32", Fork::Frontier),
+
+	info(0x80, "DUP1", 1, 2, Fork::Frontier),
+	info(0x81, "DUP2", 2, 3, Fork::Frontier),
+	info(0x82, "DUP3", 3, 4, Fork::Frontier),
+	info(0x83, "DUP4", 4, 5, Fork::Frontier),
+	info(0x84, "DUP5", 5, 6, Fork::Frontier),
+	info(0x85, "DUP6", 6, 7, Fork::Frontier),
+	info(0x86, "DUP7", 7, 8, Fork::Frontier),
+	info(0x87, "DUP8", 8, 9, Fork::Frontier),
+	info(0x88, "DUP9", 9, 10, Fork::Frontier),
+	info(0x89, "DUP10", 10, 11, Fork::Frontier),
+	info(0x8a, "DUP11", 11, 12, Fork::Frontier),
+	info(0x8b, "DUP12", 12, 13, Fork::Frontier),
+	info(0x8c, "DUP13", 13, 14, Fork::Frontier),
+	info(0x8d, "DUP14", 14, 15, Fork::Frontier),
+	info(0x8e, "DUP15", 15, 16, Fork::Frontier),
+	info(0x8f, "DUP16", 16, 17, Fork::Frontier),
+
+	info(0x90, "SWAP1", 2, 2, Fork::Frontier),
+	info(0x91, "SWAP2", 3, 3, Fork::Frontier),
+	info(0x92, "SWAP3", 4, 4, Fork::Frontier),
+	info(0x93, "SWAP4", 5, 5, Fork::Frontier),
+	info(0x94, "SWAP5", 6, 6, Fork::Frontier),
+	info(0x95, "SWAP6", 7, 7, Fork::Frontier),
+	info(0x96, "SWAP7", 8, 8, Fork::Frontier),
+	info(0x97, "SWAP8", 9, 9, Fork::Frontier),
+	info(0x98, "SWAP9", 10, 10, Fork::Frontier),
+	info(0x99, "SWAP10", 11, 11, Fork::Frontier),
+	info(0x9a, "SWAP11", 12, 12, Fork::Frontier),
+	info(0x9b, "SWAP12", 13, 13, Fork::Frontier),
+	info(0x9c, "SWAP13", 14, 14, Fork::Frontier),
+	info(0x9d, "SWAP14", 15, 15, Fork::Frontier),
+	info(0x9e, "SWAP15", 16, 16, Fork::Frontier),
+	info(0x9f, "SWAP16", 17, 17, Fork::Frontier),
+
+	info(0xa0, "LOG0", 2, 0, Fork::Frontier),
+	info(0xa1, "LOG1", 3, 0, Fork::Frontier),
+	info(0xa2, "LOG2", 4, 0, Fork::Frontier),
+	info(0xa3, "LOG3", 5, 0, Fork::Frontier),
+	info(0xa4, "LOG4", 6, 0, Fork::Frontier),
+
+	info(0xf0, "CREATE", 3, 1, Fork::Frontier),
+	info(0xf1, "CALL", 7, 1, Fork::Frontier),
+	info(0xf2, "CALLCODE", 7, 1, Fork::Frontier),
+	info(0xf3, "RETURN", 2, 0, Fork::Frontier),
+	info(0xf4, "DELEGATECALL", 6, 1, Fork::Homestead),
+	info(0xf5, "CREATE2", 4, 1, Fork::Constantinople),
+	info(0xfa, "STATICCALL", 6, 1, Fork::Byzantium),
+	info(0xfd, "REVERT", 2, 0, Fork::Byzantium),
+	info(0xfe, "INVALID", 0, 0, Fork::Frontier),
+	info(0xff, "SELFDESTRUCT", 1, 0, Fork::Frontier),
+];
@@ -12,39 +12,53 @@ use alloc::vec::Vec;
 use core::ops::Range;
 use std::sync::Arc;
 
-use primitive_types::U256;
+use primitive_types::{H256, U256};
 
-pub use crate::error::{Capture, ExitError, ExitFatal, ExitReason, ExitRevert, ExitSucceed, Trap};
+#[cfg(feature = "eof")]
+pub use crate::eof::{EOF_MAGIC, EOF_VERSION, EofContainer, EofError, EofTypes, is_eof};
+pub use crate::analysis::{AnalyzedCode, Instruction};
+pub use crate::bytes::Bytes;
+pub use crate::error::{Capture, ExitError, ExitFatal, ExitReason, ExitRevert, ExitSucceed, StrictViolation, Trap};
+pub use crate::info::{Fork, OPCODE_INFOS, OpcodeInfo, opcode_infos_json};
 use crate::eval::{Control, eval};
+pub use crate::eval::{Fast, Reference, U256Ops};
 pub use crate::memory::Memory;
 pub use crate::opcode::{ExternalOpcode, Opcode};
 pub use crate::stack::Stack;
 pub use crate::valids::Valids;
 
+mod analysis;
+mod bytes;
 mod memory;
 mod stack;
 mod valids;
 mod opcode;
 mod error;
 mod eval;
+mod info;
 mod utils;
+#[cfg(feature = "eof")]
+mod eof;
 
 /// Core execution layer for EVM.
 pub struct Machine {
 	/// Program data.
-	data: Arc<Vec<u8>>,
+	data: Bytes,
 	/// Program code.
 	code: Arc<Vec<u8>>,
 	/// Program counter.
 	position: Result<usize, ExitReason>,
 	/// Return value.
 	return_range: Range<U256>,
-	/// Code validity maps.
-	valids: Valids,
+	/// Analysis of the program code.
+	analysis: Arc<AnalyzedCode>,
 	/// Memory.
 	memory: Memory,
 	/// Stack.
 	stack: Stack,
+	/// EOF container being executed, if any.
+	#[cfg(feature = "eof")]
+	eof: Option<eof::EofState>,
 }
 
 impl Machine {
@@ -56,27 +70,88 @@ impl Machine {
 	pub fn memory(&self) -> &Memory { &self.memory }
 	/// Mutable reference of machine memory.
 	pub fn memory_mut(&mut self) -> &mut Memory { &mut self.memory }
+	/// Reference of machine code.
+	pub fn code(&self) -> &[u8] { &self.code }
+	/// Reference of machine input data.
+	pub fn data(&self) -> &Bytes { &self.data }
+	/// Program counter, or the exit reason if the machine has exited.
+	pub fn position(&self) -> &Result<usize, ExitReason> { &self.position }
 
 	/// Create a new machine with given code and data.
 	pub fn new(
 		code: Arc<Vec<u8>>,
-		data: Arc<Vec<u8>>,
+		data: Bytes,
 		stack_limit: usize,
 		memory_limit: usize
 	) -> Self {
-		let valids = Valids::new(&code[..]);
+		Self::new_analyzed(Arc::new(AnalyzedCode::new(code)), data, stack_limit, memory_limit)
+	}
 
+	/// Create a new machine executing already analyzed code.
+	pub fn new_analyzed(
+		analysis: Arc<AnalyzedCode>,
+		data: Bytes,
+		stack_limit: usize,
+		memory_limit: usize
+	) -> Self {
 		Self {
 			data,
-			code,
+			code: analysis.code().clone(),
 			position: Ok(0),
 			return_range: U256::zero()..U256::zero(),
-			valids,
+			analysis,
 			memory: Memory::new(memory_limit),
 			stack: Stack::new(stack_limit),
+			#[cfg(feature = "eof")]
+			eof: None,
 		}
 	}
 
+	/// Create a new machine executing an EOF container, starting at its
+	/// first code section.
+	#[cfg(feature = "eof")]
+	pub fn new_eof(
+		code: Arc<Vec<u8>>,
+		data: Bytes,
+		stack_limit: usize,
+		memory_limit: usize
+	) -> Result<Self, EofError> {
+		let container = EofContainer::new(&code[..])?;
+
+		Ok(Self {
+			data,
+			code,
+			position: Ok(container.code[0].start),
+			return_range: U256::zero()..U256::zero(),
+			analysis: Arc::new(AnalyzedCode::new(Arc::new(Vec::new()))),
+			memory: Memory::new(memory_limit),
+			stack: Stack::new(stack_limit),
+			eof: Some(eof::EofState { container, section: 0, return_stack: Vec::new() }),
+		})
+	}
+
+	/// EOF container being executed, if any.
+	#[cfg(feature = "eof")]
+	pub fn eof(&self) -> Option<&EofContainer> {
+		self.eof.as_ref().map(|eof| &eof.container)
+	}
+
+	/// Parse an opcode of the executed code.
+	fn fetch(&self, position: usize) -> Option<Result<Opcode, ExternalOpcode>> {
+		#[cfg(feature = "eof")]
+		{
+			if self.eof.is_some() {
+				return self.code.get(position).map(|v| eof::parse_opcode(*v))
+			}
+		}
+		self.analysis.instruction(position).map(|instruction| instruction.opcode)
+	}
+
+	/// Analysis of the code being executed. Empty for EOF containers.
+	pub fn analysis(&self) -> &Arc<AnalyzedCode> {
+		&self.analysis
+	}
+
 	/// Explict exit of the machine. Further step will return error.
 	pub fn exit(&mut self, reason: ExitReason) {
 		self.position = Err(reason);
@@ -88,7 +163,32 @@ impl Machine {
 			Ok(position) => position,
 			Err(_) => return None,
 		};
-		self.code.get(position).map(|v| (Opcode::parse(*v), &self.stack))
+		self.fetch(position).map(|opcode| (opcode, &self.stack))
+	}
+
+	/// Static gas of the machine's next instruction, if computed when the
+	/// code was analyzed. Never computed for EOF containers.
+	pub fn static_gas(&self) -> Option<usize> {
+		#[cfg(feature = "eof")]
+		{
+			if self.eof.is_some() {
+				return None
+			}
+		}
+		let position = *self.position.as_ref().ok()?;
+		self.analysis.instruction(position)?.static_gas
+	}
+
+	/// Reuse the allocations of the given buffers for memory and stack. Only
+	/// meaningful before the machine starts executing, as both are cleared.
+	pub fn reuse_buffers(&mut self, memory: Vec<u8>, stack: Vec<H256>) {
+		self.memory = Memory::with_buffer(self.memory.limit(), memory);
+		self.stack = Stack::with_buffer(self.stack.limit(), stack);
+	}
+
+	/// Consume the machine, returning the buffers of its memory and stack.
+	pub fn into_buffers(self) -> (Vec<u8>, Vec<H256>) {
+		(self.memory.into_buffer(), self.stack.into_buffer())
 	}
 
 	/// Copy and get the return value of the machine, if any.
@@ -114,6 +214,28 @@ impl Machine {
 		}
 	}
 
+	/// Length of the return value of the machine.
+	pub fn return_len(&self) -> U256 {
+		self.return_range.end.saturating_sub(self.return_range.start)
+	}
+
+	/// Take the return value of the machine, sharing the memory it was
+	/// returned from instead of copying it. The memory is left empty, so
+	/// this is only meant for machines that exited.
+	pub fn take_return_value(&mut self) -> Bytes {
+		if self.return_range.start >= self.return_range.end {
+			return Bytes::new()
+		}
+		if self.return_range.end > U256::from(self.memory.len()) {
+			return Bytes::from(self.return_value())
+		}
+
+		let range = self.return_range.start.as_usize()..self.return_range.end.as_usize();
+		let limit = self.memory.limit();
+		let buffer = core::mem::replace(&mut self.memory, Memory::new(limit)).into_buffer();
+		Bytes::from_shared(Arc::new(buffer), range)
+	}
+
 	/// Loop stepping the machine, until it stops.
 	pub fn run(&mut self) -> Capture<ExitReason, Trap> {
 		loop {
@@ -128,9 +250,13 @@ impl Machine {
 	pub fn step(&mut self) -> Result<(), Capture<ExitReason, Trap>> {
 		let position = self.position.map_err(|reason| Capture::Exit(reason))?;
 
-		match self.code.get(position).map(|v| Opcode::parse(*v)) {
+		match self.fetch(position) {
 			Some(Ok(opcode)) => {
-				match eval(self, opcode, position) {
+				let control = match self.analysis.handler(position) {
+					Some(handler) => handler(self, position),
+					None => eval(self, opcode, position),
+				};
+				match control {
 					Control::Continue(p) => {
 						self.position = Ok(position + p);
 						Ok(())
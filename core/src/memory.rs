@@ -22,6 +22,22 @@ impl Memory {
 		}
 	}
 
+	/// Create a new memory with the given limit, reusing the allocation of
+	/// `buffer`. The buffer is cleared.
+	pub fn with_buffer(limit: usize, mut buffer: Vec<u8>) -> Self {
+		buffer.clear();
+		Self {
+			data: buffer,
+			effective_len: U256::zero(),
+			limit,
+		}
+	}
+
+	/// Consume the memory, returning its underlying buffer.
+	pub fn into_buffer(self) -> Vec<u8> {
+		self.data
+	}
+
 	/// Memory limit.
 	pub fn limit(&self) -> usize {
 		self.limit
@@ -80,13 +96,9 @@ impl Memory {
 		let mut ret = Vec::new();
 		ret.resize(size, 0);
 
-		for index in 0..size {
-			let position = offset + index;
-			if position >= self.data.len() {
-				break
-			}
-
-			ret[index] = self.data[position];
+		if offset < self.data.len() {
+			let end = min(offset.saturating_add(size), self.data.len());
+			ret[..end - offset].copy_from_slice(&self.data[offset..end]);
 		}
 
 		ret
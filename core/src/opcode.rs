@@ -86,7 +86,7 @@ pub enum Opcode {
 	/// `JUMPDEST`
 	JumpDest,
 
-	/// `PUSHn`
+	/// `PUSHn`, with `PUSH0` as `Push(0)`.
 	Push(u8),
 	/// `DUPn`
 	Dup(u8),
@@ -100,6 +100,15 @@ pub enum Opcode {
 
 	/// `INVALID`
 	Invalid,
+
+	/// `RJUMP`, in EOF code only. Never parsed without the `eof` feature.
+	RJump,
+	/// `RJUMPI`, in EOF code only. Never parsed without the `eof` feature.
+	RJumpI,
+	/// `CALLF`, in EOF code only. Never parsed without the `eof` feature.
+	CallF,
+	/// `RETF`, in EOF code only. Never parsed without the `eof` feature.
+	RetF,
 }
 
 impl Opcode {
@@ -176,6 +185,7 @@ impl Opcode {
 			0x5a => Err(ExternalOpcode::Gas),
 			0x5b => Ok(Opcode::JumpDest),
 
+			0x5f => Ok(Opcode::Push(0)),
 			0x60 => Ok(Opcode::Push(1)),
 			0x61 => Ok(Opcode::Push(2)),
 			0x62 => Ok(Opcode::Push(3)),
@@ -255,6 +265,10 @@ impl Opcode {
 			0xf3 => Ok(Opcode::Return),
 			0xf4 => Err(ExternalOpcode::DelegateCall),
 			0xf5 => Err(ExternalOpcode::Create2),
+			#[cfg(feature = "auth")]
+			0xf6 => Err(ExternalOpcode::Auth),
+			#[cfg(feature = "auth")]
+			0xf7 => Err(ExternalOpcode::AuthCall),
 			0xfa => Err(ExternalOpcode::StaticCall),
 			0xfd => Ok(Opcode::Revert),
 
@@ -262,6 +276,97 @@ impl Opcode {
 			other => Err(ExternalOpcode::Other(other)),
 		}
 	}
+
+	/// Byte of a parsed opcode, the inverse of `parse` and of EOF opcode
+	/// parsing.
+	pub fn byte(opcode: Result<Opcode, ExternalOpcode>) -> u8 {
+		match opcode {
+			Ok(Opcode::Stop) => 0x00,
+			Ok(Opcode::Add) => 0x01,
+			Ok(Opcode::Mul) => 0x02,
+			Ok(Opcode::Sub) => 0x03,
+			Ok(Opcode::Div) => 0x04,
+			Ok(Opcode::SDiv) => 0x05,
+			Ok(Opcode::Mod) => 0x06,
+			Ok(Opcode::SMod) => 0x07,
+			Ok(Opcode::AddMod) => 0x08,
+			Ok(Opcode::MulMod) => 0x09,
+			Ok(Opcode::Exp) => 0x0a,
+			Ok(Opcode::SignExtend) => 0x0b,
+			Ok(Opcode::Lt) => 0x10,
+			Ok(Opcode::Gt) => 0x11,
+			Ok(Opcode::SLt) => 0x12,
+			Ok(Opcode::SGt) => 0x13,
+			Ok(Opcode::Eq) => 0x14,
+			Ok(Opcode::IsZero) => 0x15,
+			Ok(Opcode::And) => 0x16,
+			Ok(Opcode::Or) => 0x17,
+			Ok(Opcode::Xor) => 0x18,
+			Ok(Opcode::Not) => 0x19,
+			Ok(Opcode::Byte) => 0x1a,
+			Ok(Opcode::Shl) => 0x1b,
+			Ok(Opcode::Shr) => 0x1c,
+			Ok(Opcode::Sar) => 0x1d,
+			Err(ExternalOpcode::Sha3) => 0x20,
+			Err(ExternalOpcode::Address) => 0x30,
+			Err(ExternalOpcode::Balance) => 0x31,
+			Err(ExternalOpcode::Origin) => 0x32,
+			Err(ExternalOpcode::Caller) => 0x33,
+			Err(ExternalOpcode::CallValue) => 0x34,
+			Ok(Opcode::CallDataLoad) => 0x35,
+			Ok(Opcode::CallDataSize) => 0x36,
+			Ok(Opcode::CallDataCopy) => 0x37,
+			Ok(Opcode::CodeSize) => 0x38,
+			Ok(Opcode::CodeCopy) => 0x39,
+			Err(ExternalOpcode::GasPrice) => 0x3a,
+			Err(ExternalOpcode::ExtCodeSize) => 0x3b,
+			Err(ExternalOpcode::ExtCodeCopy) => 0x3c,
+			Err(ExternalOpcode::ReturnDataSize) => 0x3d,
+			Err(ExternalOpcode::ReturnDataCopy) => 0x3e,
+			Err(ExternalOpcode::ExtCodeHash) => 0x3f,
+			Err(ExternalOpcode::BlockHash) => 0x40,
+			Err(ExternalOpcode::Coinbase) => 0x41,
+			Err(ExternalOpcode::Timestamp) => 0x42,
+			Err(ExternalOpcode::Number) => 0x43,
+			Err(ExternalOpcode::Difficulty) => 0x44,
+			Err(ExternalOpcode::GasLimit) => 0x45,
+			Err(ExternalOpcode::ChainId) => 0x46,
+			Err(ExternalOpcode::SelfBalance) => 0x47,
+			Ok(Opcode::Pop) => 0x50,
+			Ok(Opcode::MLoad) => 0x51,
+			Ok(Opcode::MStore) => 0x52,
+			Ok(Opcode::MStore8) => 0x53,
+			Err(ExternalOpcode::SLoad) => 0x54,
+			Err(ExternalOpcode::SStore) => 0x55,
+			Ok(Opcode::Jump) => 0x56,
+			Ok(Opcode::JumpI) => 0x57,
+			Ok(Opcode::PC) => 0x58,
+			Ok(Opcode::MSize) => 0x59,
+			Err(ExternalOpcode::Gas) => 0x5a,
+			Ok(Opcode::JumpDest) => 0x5b,
+			Err(ExternalOpcode::Create) => 0xf0,
+			Err(ExternalOpcode::Call) => 0xf1,
+			Err(ExternalOpcode::CallCode) => 0xf2,
+			Ok(Opcode::Return) => 0xf3,
+			Err(ExternalOpcode::DelegateCall) => 0xf4,
+			Err(ExternalOpcode::Create2) => 0xf5,
+			Err(ExternalOpcode::Auth) => 0xf6,
+			Err(ExternalOpcode::AuthCall) => 0xf7,
+			Err(ExternalOpcode::StaticCall) => 0xfa,
+			Ok(Opcode::Revert) => 0xfd,
+			Err(ExternalOpcode::Suicide) => 0xff,
+			Ok(Opcode::Push(n)) => 0x5f + n,
+			Ok(Opcode::Dup(n)) => 0x7f + n,
+			Ok(Opcode::Swap(n)) => 0x8f + n,
+			Err(ExternalOpcode::Log(n)) => 0xa0 + n,
+			Ok(Opcode::Invalid) => 0xfe,
+			Ok(Opcode::RJump) => 0xe0,
+			Ok(Opcode::RJumpI) => 0xe1,
+			Ok(Opcode::CallF) => 0xe3,
+			Ok(Opcode::RetF) => 0xe4,
+			Err(ExternalOpcode::Other(byte)) => byte,
+		}
+	}
 }
 
 /// External opcodes.
@@ -329,6 +434,10 @@ pub enum ExternalOpcode {
 	Suicide,
 	/// `CHAINID`
 	ChainId,
+	/// `AUTH`, per EIP-3074. Never parsed without the `auth` feature.
+	Auth,
+	/// `AUTHCALL`, per EIP-3074. Never parsed without the `auth` feature.
+	AuthCall,
 	/// Other unknown opcodes.
 	Other(u8),
 }
@@ -18,6 +18,21 @@ impl Stack {
 		}
 	}
 
+	/// Create a new stack with given limit, reusing the allocation of
+	/// `buffer`. The buffer is cleared.
+	pub fn with_buffer(limit: usize, mut buffer: Vec<H256>) -> Self {
+		buffer.clear();
+		Self {
+			data: buffer,
+			limit,
+		}
+	}
+
+	/// Consume the stack, returning its underlying buffer.
+	pub fn into_buffer(self) -> Vec<H256> {
+		self.data
+	}
+
 	/// Stack limit.
 	pub fn limit(&self) -> usize {
 		self.limit
@@ -81,7 +81,7 @@ impl Div for I256 {
 			return I256::min_value();
 		}
 
-		let d = (self.1 / other.1) & SIGN_BIT_MASK.into();
+		let d = self.1 / other.1;
 
 		if d == U256::zero() {
 			return I256::zero();
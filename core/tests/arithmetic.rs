@@ -0,0 +1,282 @@
+use std::sync::Arc;
+
+use evm_core::{Bytes, Capture, ExitSucceed, Fast, Machine, Reference, U256Ops};
+use primitive_types::U256;
+
+/// Run `op` on `op1` (top of the stack) and `op2`, returning the result.
+fn eval(op: u8, op1: U256, op2: U256) -> U256 {
+	let mut code = Vec::new();
+	for value in &[op2, op1] {
+		let mut word = [0u8; 32];
+		value.to_big_endian(&mut word);
+		code.push(0x7f);
+		code.extend_from_slice(&word);
+	}
+	code.push(op);
+	code.extend_from_slice(&hex::decode("60005260206000f3").unwrap());
+
+	let mut vm = Machine::new(Arc::new(code), Bytes::new(), 1024, 10000);
+	assert_eq!(vm.run(), Capture::Exit(ExitSucceed::Returned.into()));
+	U256::from_big_endian(&vm.return_value())
+}
+
+fn u(s: &str) -> U256 {
+	let s = s.trim_start_matches("0x");
+	let padded = format!("{:0>64}", s);
+	U256::from_big_endian(&hex::decode(padded).unwrap())
+}
+
+const SDIV: u8 = 0x05;
+const SMOD: u8 = 0x07;
+const SIGNEXTEND: u8 = 0x0b;
+const SLT: u8 = 0x12;
+const SGT: u8 = 0x13;
+const BYTE: u8 = 0x1a;
+const SHL: u8 = 0x1b;
+const SHR: u8 = 0x1c;
+const SAR: u8 = 0x1d;
+
+/// Test vectors of EIP-145, as `(opcode, value, shift, expected)`.
+const EIP145: &[(u8, &str, &str, &str)] = &[
+	(SHL, "0x0000000000000000000000000000000000000000000000000000000000000001", "0x00",
+	 "0x0000000000000000000000000000000000000000000000000000000000000001"),
+	(SHL, "0x0000000000000000000000000000000000000000000000000000000000000001", "0x01",
+	 "0x0000000000000000000000000000000000000000000000000000000000000002"),
+	(SHL, "0x0000000000000000000000000000000000000000000000000000000000000001", "0xff",
+	 "0x8000000000000000000000000000000000000000000000000000000000000000"),
+	(SHL, "0x0000000000000000000000000000000000000000000000000000000000000001", "0x0100",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+	(SHL, "0x0000000000000000000000000000000000000000000000000000000000000001", "0x0101",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+	(SHL, "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0x00",
+	 "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+	(SHL, "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0x01",
+	 "0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe"),
+	(SHL, "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0xff",
+	 "0x8000000000000000000000000000000000000000000000000000000000000000"),
+	(SHL, "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0x0100",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+	(SHL, "0x0000000000000000000000000000000000000000000000000000000000000000", "0x01",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+	(SHL, "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0x01",
+	 "0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe"),
+	(SHR, "0x0000000000000000000000000000000000000000000000000000000000000001", "0x00",
+	 "0x0000000000000000000000000000000000000000000000000000000000000001"),
+	(SHR, "0x0000000000000000000000000000000000000000000000000000000000000001", "0x01",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+	(SHR, "0x8000000000000000000000000000000000000000000000000000000000000000", "0x01",
+	 "0x4000000000000000000000000000000000000000000000000000000000000000"),
+	(SHR, "0x8000000000000000000000000000000000000000000000000000000000000000", "0xff",
+	 "0x0000000000000000000000000000000000000000000000000000000000000001"),
+	(SHR, "0x8000000000000000000000000000000000000000000000000000000000000000", "0x0100",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+	(SHR, "0x8000000000000000000000000000000000000000000000000000000000000000", "0x0101",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+	(SHR, "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0x00",
+	 "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+	(SHR, "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0x01",
+	 "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+	(SHR, "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0xff",
+	 "0x0000000000000000000000000000000000000000000000000000000000000001"),
+	(SHR, "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0x0100",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+	(SHR, "0x0000000000000000000000000000000000000000000000000000000000000000", "0x01",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+	(SAR, "0x0000000000000000000000000000000000000000000000000000000000000001", "0x00",
+	 "0x0000000000000000000000000000000000000000000000000000000000000001"),
+	(SAR, "0x0000000000000000000000000000000000000000000000000000000000000001", "0x01",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+	(SAR, "0x8000000000000000000000000000000000000000000000000000000000000000", "0x01",
+	 "0xc000000000000000000000000000000000000000000000000000000000000000"),
+	(SAR, "0x8000000000000000000000000000000000000000000000000000000000000000", "0xff",
+	 "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+	(SAR, "0x8000000000000000000000000000000000000000000000000000000000000000", "0x0100",
+	 "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+	(SAR, "0x8000000000000000000000000000000000000000000000000000000000000000", "0x0101",
+	 "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+	(SAR, "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0x00",
+	 "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+	(SAR, "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0x01",
+	 "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+	(SAR, "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0xff",
+	 "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+	(SAR, "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0x0100",
+	 "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+	(SAR, "0x0000000000000000000000000000000000000000000000000000000000000000", "0x01",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+	(SAR, "0x4000000000000000000000000000000000000000000000000000000000000000", "0xfe",
+	 "0x0000000000000000000000000000000000000000000000000000000000000001"),
+	(SAR, "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0xf8",
+	 "0x000000000000000000000000000000000000000000000000000000000000007f"),
+	(SAR, "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0xfe",
+	 "0x0000000000000000000000000000000000000000000000000000000000000001"),
+	(SAR, "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0xff",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+	(SAR, "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "0x0100",
+	 "0x0000000000000000000000000000000000000000000000000000000000000000"),
+];
+
+#[test]
+fn eip145_vectors() {
+	for (op, value, shift, expected) in EIP145 {
+		let (value, shift) = (u(value), u(shift));
+		assert_eq!(eval(*op, shift, value), u(expected), "op {:#x}: {:#x}, {:#x}", op, value, shift);
+	}
+}
+
+#[test]
+fn signed_edge_cases() {
+	let min = U256::one() << 255;
+	let minus_one = U256::max_value();
+
+	assert_eq!(eval(SDIV, min, U256::one()), min);
+	assert_eq!(eval(SDIV, min, minus_one), min);
+	assert_eq!(eval(SDIV, U256::from(7), U256::zero()), U256::zero());
+	assert_eq!(eval(SDIV, negate(U256::from(7)), U256::from(2)), negate(U256::from(3)));
+	assert_eq!(eval(SMOD, negate(U256::from(7)), U256::from(2)), minus_one);
+	assert_eq!(eval(SMOD, U256::from(7), negate(U256::from(2))), U256::one());
+	assert_eq!(eval(SMOD, min, minus_one), U256::zero());
+	assert_eq!(eval(SIGNEXTEND, U256::zero(), U256::from(0xff)), minus_one);
+	assert_eq!(eval(SIGNEXTEND, U256::zero(), U256::from(0x7f)), U256::from(0x7f));
+	assert_eq!(eval(SIGNEXTEND, U256::from(31), min), min);
+	assert_eq!(eval(SIGNEXTEND, minus_one, U256::from(0xff)), U256::from(0xff));
+	assert_eq!(eval(SLT, minus_one, U256::zero()), U256::one());
+	assert_eq!(eval(SGT, minus_one, U256::zero()), U256::zero());
+	assert_eq!(eval(SLT, min, minus_one), U256::one());
+	assert_eq!(eval(BYTE, U256::zero(), min), U256::from(0x80));
+	assert_eq!(eval(BYTE, U256::from(31), U256::from(0x1234)), U256::from(0x34));
+	assert_eq!(eval(BYTE, U256::from(32), minus_one), U256::zero());
+	assert_eq!(eval(BYTE, minus_one, minus_one), U256::zero());
+}
+
+fn negate(value: U256) -> U256 {
+	(!value).overflowing_add(U256::one()).0
+}
+
+fn is_negative(value: U256) -> bool {
+	value.bit(255)
+}
+
+fn abs(value: U256) -> U256 {
+	if is_negative(value) { negate(value) } else { value }
+}
+
+/// Reference model of signed operations over two's complement words.
+fn reference(op: u8, a: U256, b: U256) -> U256 {
+	let sign = U256::one() << 255;
+	let bool_word = |v: bool| if v { U256::one() } else { U256::zero() };
+
+	match op {
+		SDIV if b.is_zero() => U256::zero(),
+		SDIV => {
+			let q = abs(a) / abs(b);
+			if is_negative(a) != is_negative(b) { negate(q) } else { q }
+		},
+		SMOD if b.is_zero() => U256::zero(),
+		SMOD => {
+			let r = abs(a) % abs(b);
+			if is_negative(a) { negate(r) } else { r }
+		},
+		SIGNEXTEND if a >= U256::from(31) => b,
+		SIGNEXTEND => {
+			let bit = a.as_usize() * 8 + 7;
+			let mask = (U256::one() << (bit + 1)) - U256::one();
+			if b.bit(bit) { b | !mask } else { b & mask }
+		},
+		SLT => bool_word((a ^ sign) < (b ^ sign)),
+		SGT => bool_word((a ^ sign) > (b ^ sign)),
+		BYTE if a >= U256::from(32) => U256::zero(),
+		BYTE => (b >> (8 * (31 - a.as_usize()))) & U256::from(0xff),
+		SHL if a >= U256::from(256) => U256::zero(),
+		SHL => b << a.as_usize(),
+		SHR if a >= U256::from(256) => U256::zero(),
+		SHR => b >> a.as_usize(),
+		SAR if a >= U256::from(256) => if is_negative(b) { U256::max_value() } else { U256::zero() },
+		SAR if is_negative(b) => !((!b) >> a.as_usize()),
+		SAR => b >> a.as_usize(),
+		_ => unreachable!(),
+	}
+}
+
+/// Deterministic xorshift generator, biased towards edge case values.
+struct Rng(u64);
+
+impl Rng {
+	fn next_u64(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+
+	fn next_u256(&mut self) -> U256 {
+		match self.next_u64() % 8 {
+			0 => U256::zero(),
+			1 => U256::one(),
+			2 => U256::max_value(),
+			3 => U256::one() << 255,
+			4 => U256::from(self.next_u64() % 300),
+			5 => negate(U256::from(self.next_u64() % 300)),
+			_ => U256([self.next_u64(), self.next_u64(), self.next_u64(), self.next_u64()]),
+		}
+	}
+}
+
+#[test]
+fn signed_ops_match_reference() {
+	let mut rng = Rng(0x5eed_5eed_5eed_5eed);
+
+	for op in &[SDIV, SMOD, SIGNEXTEND, SLT, SGT, BYTE, SHL, SHR, SAR] {
+		for _ in 0..300 {
+			let (a, b) = (rng.next_u256(), rng.next_u256());
+			assert_eq!(eval(*op, a, b), reference(*op, a, b), "op {:#x}: {:#x}, {:#x}", op, a, b);
+		}
+	}
+}
+
+/// Operands around the edges of the unsigned and signed ranges.
+fn edge_operands() -> Vec<U256> {
+	let sign = U256::one() << 255;
+	vec![
+		U256::zero(),
+		U256::one(),
+		U256::from(2),
+		U256::from(255),
+		U256::from(256),
+		U256::max_value(),
+		U256::max_value() - 1,
+		sign,
+		sign - 1,
+		sign + 1,
+		U256::one() << 128,
+		(U256::one() << 128) - 1,
+	]
+}
+
+fn assert_fast_matches_reference(a: U256, b: U256, m: U256) {
+	assert_eq!(Fast::addmod(a, b, m), Reference::addmod(a, b, m), "addmod {:#x}, {:#x}, {:#x}", a, b, m);
+	assert_eq!(Fast::mulmod(a, b, m), Reference::mulmod(a, b, m), "mulmod {:#x}, {:#x}, {:#x}", a, b, m);
+	assert_eq!(Fast::exp(a, b), Reference::exp(a, b), "exp {:#x}, {:#x}", a, b);
+}
+
+#[test]
+fn fast_arithmetic_matches_reference_on_edges() {
+	let operands = edge_operands();
+	for a in &operands {
+		for b in &operands {
+			for m in &operands {
+				assert_fast_matches_reference(*a, *b, *m);
+			}
+		}
+	}
+}
+
+#[test]
+fn fast_arithmetic_matches_reference() {
+	let mut rng = Rng(0xa417_a417_a417_a417);
+
+	for _ in 0..1000 {
+		let (a, b, m) = (rng.next_u256(), rng.next_u256(), rng.next_u256());
+		assert_fast_matches_reference(a, b, m);
+	}
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use evm_core::{Capture, ExitSucceed, Machine};
+use evm_core::{Bytes, Capture, ExitSucceed, Machine};
 
 macro_rules! ret_test {
 	( $name:ident, $code:expr, $data:expr, $ret:expr ) => (
@@ -9,7 +9,7 @@ macro_rules! ret_test {
 			let code = hex::decode($code).unwrap();
 			let data = hex::decode($data).unwrap();
 
-			let mut vm = Machine::new(Arc::new(code), Arc::new(data), 1024, 10000);
+			let mut vm = Machine::new(Arc::new(code), data.into(), 1024, 10000);
 			assert_eq!(vm.run(), Capture::Exit(ExitSucceed::Returned.into()));
 			assert_eq!(vm.return_value(), hex::decode($ret).unwrap());
 		}
@@ -43,3 +43,15 @@ ret_test!(
 	"61047ff40000000000000000000000000000000000000000000000000000000000000010",
 	"00000000000000000000000000000000000000000000000000000000000003db"
 );
+
+#[test]
+fn sliced_calldata_is_shared() {
+	let buffer = Arc::new(vec![0xff; 100]);
+	let data = Bytes::from_shared(buffer.clone(), 4..68);
+
+	// Return CALLDATASIZE as a word.
+	let mut vm = Machine::new(Arc::new(hex::decode("3660005260206000f3").unwrap()), data, 1024, 10000);
+	assert_eq!(vm.run(), Capture::Exit(ExitSucceed::Returned.into()));
+	assert_eq!(vm.return_value()[31], 64);
+	assert_eq!(vm.data().as_ptr(), buffer[4..].as_ptr());
+}
@@ -56,12 +56,27 @@ pub fn sstore_refund(original: H256, current: H256, new: H256, config: &Config)
 	}
 }
 
-pub fn create2_cost(len: U256) -> Result<usize, ExitError> {
-	let base = U256::from(G_CREATE);
-	// ceil(len / 32.0)
-	let sha_addup_base = len / U256::from(32) +
-		if len % U256::from(32) == U256::zero() { U256::zero() } else { U256::one() };
-	let sha_addup = U256::from(G_SHA3WORD).checked_mul(sha_addup_base)
+/// Number of 32-byte words of `len` bytes.
+fn words(len: U256) -> U256 {
+	len / U256::from(32) +
+		if len % U256::from(32) == U256::zero() { U256::zero() } else { U256::one() }
+}
+
+pub fn create_cost(len: U256, config: &Config) -> Result<usize, ExitError> {
+	let initcode = U256::from(config.gas_initcode_word).checked_mul(words(len))
+		.ok_or(ExitError::OutOfGas)?;
+	let gas = U256::from(G_CREATE).checked_add(initcode).ok_or(ExitError::OutOfGas)?;
+
+	if gas > U256::from(usize::MAX) {
+		return Err(ExitError::OutOfGas)
+	}
+
+	Ok(gas.as_usize())
+}
+
+pub fn create2_cost(len: U256, config: &Config) -> Result<usize, ExitError> {
+	let base = U256::from(create_cost(len, config)?);
+	let sha_addup = U256::from(G_SHA3WORD).checked_mul(words(len))
 		.ok_or(ExitError::OutOfGas)?;
 	let gas = base.checked_add(sha_addup).ok_or(ExitError::OutOfGas)?;
 
@@ -172,7 +187,7 @@ pub fn sha3_cost(len: U256) -> Result<usize, ExitError> {
 pub fn sstore_cost(original: H256, current: H256, new: H256, gas: usize, config: &Config) -> Result<usize, ExitError> {
 	if config.sstore_gas_metering {
 		if config.sstore_revert_under_stipend {
-			if gas < config.call_stipend {
+			if gas <= config.call_stipend {
 				return Err(ExitError::OutOfGas)
 			}
 		}
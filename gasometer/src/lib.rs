@@ -5,19 +5,22 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use core::cmp::max;
+use core::cmp::{max, min};
 use std::sync::Arc;
 
 use primitive_types::{H160, H256, U256};
 
 use evm_core::{ExitError, ExternalOpcode, Opcode, Stack};
-use evm_runtime::{Config, Handler};
+use evm_runtime::{Config, StateQuery};
 
 mod consts;
 mod costs;
 mod memory;
+mod table;
 mod utils;
 
+pub use crate::table::{ConfigGasTable, DynamicCost, GasTable, OpcodeGas};
+
 macro_rules! try_or_fail {
 	( $inner:expr, $e:expr ) => (
 		match $e {
@@ -173,6 +176,18 @@ impl Gasometer {
 		Ok(())
 	}
 
+	/// Give back gas used by the frame, up to all of its used gas excluding
+	/// memory expansion. Returns the gas given back.
+	pub fn record_grant(
+		&mut self,
+		gas: usize,
+	) -> Result<usize, ExitError> {
+		let inner = self.inner_mut()?;
+		let granted = min(gas, inner.used_gas);
+		inner.used_gas -= granted;
+		Ok(granted)
+	}
+
 	/// Record transaction cost.
 	pub fn record_transaction(
 		&mut self,
@@ -185,9 +200,11 @@ impl Gasometer {
 					non_zero_data_len * self.config.gas_transaction_non_zero_data
 			},
 			TransactionCost::Create { zero_data_len, non_zero_data_len } => {
+				let words = (zero_data_len + non_zero_data_len).div_ceil(32);
 				self.config.gas_transaction_create +
 					zero_data_len * self.config.gas_transaction_zero_data +
-					non_zero_data_len * self.config.gas_transaction_non_zero_data
+					non_zero_data_len * self.config.gas_transaction_non_zero_data +
+					words * self.config.gas_initcode_word
 			},
 		};
 
@@ -221,16 +238,10 @@ pub fn create_transaction_cost(
 	TransactionCost::Create { zero_data_len, non_zero_data_len }
 }
 
-/// Calculate the opcode cost.
-pub async fn opcode_cost<H: Handler>(
-	address: H160,
-	opcode: Result<Opcode, ExternalOpcode>,
-	stack: &Stack,
-	is_static: bool,
-	config: &Config,
-	handler: &H
-) -> Result<(GasCost, Option<MemoryCost>), ExitError> {
-	let gas_cost = match opcode {
+/// Gas cost of an opcode that depends neither on the stack nor on the
+/// state, or `None` if it does.
+fn static_gas_cost(opcode: Result<Opcode, ExternalOpcode>, config: &Config) -> Option<GasCost> {
+	Some(match opcode {
 		Ok(Opcode::Stop) | Ok(Opcode::Return) => GasCost::Zero,
 
 		Ok(Opcode::Revert) if config.has_revert => GasCost::Zero,
@@ -247,6 +258,9 @@ pub async fn opcode_cost<H: Handler>(
 		Err(ExternalOpcode::ChainId) if config.has_chain_id => GasCost::Base,
 		Err(ExternalOpcode::ChainId) => GasCost::Invalid,
 
+		Ok(Opcode::Push(0)) if config.has_push0 => GasCost::Base,
+		Ok(Opcode::Push(0)) => GasCost::Invalid,
+
 		Ok(Opcode::Add) | Ok(Opcode::Sub) | Ok(Opcode::Not) | Ok(Opcode::Lt) |
 		Ok(Opcode::Gt) | Ok(Opcode::SLt) | Ok(Opcode::SGt) | Ok(Opcode::Eq) |
 		Ok(Opcode::IsZero) | Ok(Opcode::And) | Ok(Opcode::Or) | Ok(Opcode::Xor) |
@@ -275,90 +289,185 @@ pub async fn opcode_cost<H: Handler>(
 		Err(ExternalOpcode::ExtCodeHash) if config.has_ext_code_hash => GasCost::ExtCodeHash,
 		Err(ExternalOpcode::ExtCodeHash) => GasCost::Invalid,
 
-		Err(ExternalOpcode::CallCode) => GasCost::CallCode {
-			value: U256::from_big_endian(&stack.peek(2)?[..]),
-			gas: U256::from_big_endian(&stack.peek(0)?[..]),
-			target_exists: handler.exists(stack.peek(1)?.into()).await,
-		},
-		Err(ExternalOpcode::StaticCall) => GasCost::StaticCall {
-			gas: U256::from_big_endian(&stack.peek(0)?[..]),
-			target_exists: handler.exists(stack.peek(1)?.into()).await,
-		},
-		Err(ExternalOpcode::Sha3) => GasCost::Sha3 {
-			len: U256::from_big_endian(&stack.peek(1)?[..]),
-		},
-		Err(ExternalOpcode::ExtCodeCopy) => GasCost::ExtCodeCopy {
-			len: U256::from_big_endian(&stack.peek(3)?[..]),
-		},
-		Ok(Opcode::CallDataCopy) | Ok(Opcode::CodeCopy) => GasCost::VeryLowCopy {
-			len: U256::from_big_endian(&stack.peek(2)?[..]),
-		},
-		Ok(Opcode::Exp) => GasCost::Exp {
-			power: U256::from_big_endian(&stack.peek(1)?[..]),
-		},
 		Ok(Opcode::JumpDest) => GasCost::JumpDest,
 		Err(ExternalOpcode::SLoad) => GasCost::SLoad,
 
-		Err(ExternalOpcode::DelegateCall) if config.has_delegate_call => GasCost::DelegateCall {
-			gas: U256::from_big_endian(&stack.peek(0)?[..]),
-			target_exists: handler.exists(stack.peek(1)?.into()).await,
-		},
-		Err(ExternalOpcode::DelegateCall) => GasCost::Invalid,
+		Err(ExternalOpcode::DelegateCall) if !config.has_delegate_call => GasCost::Invalid,
 
 		Err(ExternalOpcode::ReturnDataSize) if config.has_return_data => GasCost::Base,
-		Err(ExternalOpcode::ReturnDataCopy) if config.has_return_data => GasCost::VeryLowCopy {
-			len: U256::from_big_endian(&stack.peek(2)?[..]),
-		},
-		Err(ExternalOpcode::ReturnDataSize) | Err(ExternalOpcode::ReturnDataCopy) => GasCost::Invalid,
+		Err(ExternalOpcode::ReturnDataSize) | Err(ExternalOpcode::ReturnDataCopy)
+			if !config.has_return_data => GasCost::Invalid,
 
-		Err(ExternalOpcode::SStore) if !is_static => {
-			let index = stack.peek(0)?;
-			let value = stack.peek(1)?;
+		Ok(Opcode::Invalid) => GasCost::Invalid,
 
-			GasCost::SStore {
-				original: handler.original_storage(address, index).await,
-				current: handler.storage(address, index).await,
-				new: value,
-			}
-		},
-		Err(ExternalOpcode::Log(n)) if !is_static => GasCost::Log {
-			n,
-			len: U256::from_big_endian(&stack.peek(1)?[..]),
-		},
-		Err(ExternalOpcode::Create) if !is_static => GasCost::Create,
-		Err(ExternalOpcode::Create2) if !is_static && config.has_create2 => GasCost::Create2 {
-			len: U256::from_big_endian(&stack.peek(2)?[..]),
-		},
-		Err(ExternalOpcode::Suicide) if !is_static => GasCost::Suicide {
-			value: handler.balance(address).await,
-			target_exists: handler.exists(stack.peek(0)?.into()).await,
-			already_removed: handler.deleted(address),
-		},
-		Err(ExternalOpcode::Call)
-			if !is_static ||
-			(is_static && U256::from_big_endian(&stack.peek(2)?[..]) == U256::zero()) =>
-			GasCost::Call {
+		#[cfg(feature = "auth")]
+		Err(ExternalOpcode::Auth) if config.has_auth => GasCost::Fixed(config.gas_auth),
+		#[cfg(feature = "auth")]
+		Err(ExternalOpcode::Auth) | Err(ExternalOpcode::AuthCall) if !config.has_auth => GasCost::Invalid,
+
+		#[cfg(feature = "eof")]
+		Ok(Opcode::RJump) => GasCost::Base,
+		#[cfg(feature = "eof")]
+		Ok(Opcode::RJumpI) => GasCost::Fixed(4),
+		#[cfg(feature = "eof")]
+		Ok(Opcode::CallF) => GasCost::Low,
+		#[cfg(feature = "eof")]
+		Ok(Opcode::RetF) => GasCost::VeryLow,
+
+		Err(ExternalOpcode::Other(_)) => GasCost::Invalid,
+
+		_ => return None,
+	})
+}
+
+/// Gas cost of an opcode known before execution, as it depends neither on
+/// the stack nor on the state. `None` for dynamic costs and for opcodes
+/// invalid under `config`.
+pub fn static_opcode_cost(opcode: Result<Opcode, ExternalOpcode>, config: &Config) -> Option<usize> {
+	Some(match static_gas_cost(opcode, config)? {
+		GasCost::Zero => consts::G_ZERO,
+		GasCost::Base => consts::G_BASE,
+		GasCost::VeryLow => consts::G_VERYLOW,
+		GasCost::Low => consts::G_LOW,
+		GasCost::Mid => consts::G_MID,
+		GasCost::High => consts::G_HIGH,
+		GasCost::JumpDest => consts::G_JUMPDEST,
+		GasCost::SLoad => config.gas_sload,
+		GasCost::ExtCodeSize => config.gas_ext_code,
+		GasCost::Balance => config.gas_balance,
+		GasCost::BlockHash => consts::G_BLOCKHASH,
+		GasCost::ExtCodeHash => config.gas_ext_code_hash,
+		GasCost::Fixed(gas) => gas,
+		_ => return None,
+	})
+}
+
+/// Static gas cost of an opcode with its override applied, as computed
+/// when code is analyzed. `None` where `static_opcode_cost` is.
+pub fn static_opcode_gas(opcode: Result<Opcode, ExternalOpcode>, config: &Config) -> Option<usize> {
+	static_opcode_cost(opcode, config).map(|cost| config.gas_override(opcode).unwrap_or(cost))
+}
+
+/// Calculate the opcode cost. `static_gas` is the static cost of the
+/// opcode, overrides included, computed when the code was analyzed with
+/// `static_opcode_gas`; it is charged as is when given.
+pub async fn opcode_cost<H: StateQuery>(
+	address: H160,
+	opcode: Result<Opcode, ExternalOpcode>,
+	static_gas: Option<usize>,
+	stack: &Stack,
+	is_static: bool,
+	config: &Config,
+	handler: &H
+) -> Result<(GasCost, Option<MemoryCost>), ExitError> {
+	if let Some(gas) = static_gas {
+		return Ok((GasCost::Fixed(gas), memory_cost(opcode, stack)?))
+	}
+
+	let gas_cost = match static_gas_cost(opcode, config) {
+		Some(cost) => cost,
+		None => match opcode {
+			Err(ExternalOpcode::CallCode) => GasCost::CallCode {
 				value: U256::from_big_endian(&stack.peek(2)?[..]),
 				gas: U256::from_big_endian(&stack.peek(0)?[..]),
 				target_exists: handler.exists(stack.peek(1)?.into()).await,
 			},
+			Err(ExternalOpcode::StaticCall) => GasCost::StaticCall {
+				gas: U256::from_big_endian(&stack.peek(0)?[..]),
+				target_exists: handler.exists(stack.peek(1)?.into()).await,
+			},
+			Err(ExternalOpcode::Sha3) => GasCost::Sha3 {
+				len: U256::from_big_endian(&stack.peek(1)?[..]),
+			},
+			Err(ExternalOpcode::ExtCodeCopy) => GasCost::ExtCodeCopy {
+				len: U256::from_big_endian(&stack.peek(3)?[..]),
+			},
+			Ok(Opcode::CallDataCopy) | Ok(Opcode::CodeCopy) => GasCost::VeryLowCopy {
+				len: U256::from_big_endian(&stack.peek(2)?[..]),
+			},
+			Ok(Opcode::Exp) => GasCost::Exp {
+				power: U256::from_big_endian(&stack.peek(1)?[..]),
+			},
+			Err(ExternalOpcode::DelegateCall) if config.has_delegate_call => GasCost::DelegateCall {
+				gas: U256::from_big_endian(&stack.peek(0)?[..]),
+				target_exists: handler.exists(stack.peek(1)?.into()).await,
+			},
 
-		Ok(Opcode::Invalid) => GasCost::Invalid,
-
-		Err(ExternalOpcode::Create) | Err(ExternalOpcode::Create2) |
-		Err(ExternalOpcode::SStore) | Err(ExternalOpcode::Log(_)) |
-		Err(ExternalOpcode::Suicide) | Err(ExternalOpcode::Call) |
+			Err(ExternalOpcode::ReturnDataCopy) if config.has_return_data => GasCost::VeryLowCopy {
+				len: U256::from_big_endian(&stack.peek(2)?[..]),
+			},
+			Err(ExternalOpcode::SStore) if !is_static => {
+				let index = stack.peek(0)?;
+				let value = stack.peek(1)?;
+
+				GasCost::SStore {
+					original: handler.original_storage(address, index).await,
+					current: handler.storage(address, index).await,
+					new: value,
+				}
+			},
+			Err(ExternalOpcode::Log(n)) if !is_static => GasCost::Log {
+				n,
+				len: U256::from_big_endian(&stack.peek(1)?[..]),
+			},
+			Err(ExternalOpcode::Create) if !is_static => GasCost::Create {
+				len: U256::from_big_endian(&stack.peek(2)?[..]),
+			},
+			Err(ExternalOpcode::Create2) if !is_static && config.has_create2 => GasCost::Create2 {
+				len: U256::from_big_endian(&stack.peek(2)?[..]),
+			},
+			Err(ExternalOpcode::Suicide) if !is_static => GasCost::Suicide {
+				value: handler.balance(address).await,
+				target_exists: handler.exists(stack.peek(0)?.into()).await,
+				already_removed: handler.deleted(address),
+			},
+			Err(ExternalOpcode::Call)
+				if !is_static ||
+				(is_static && U256::from_big_endian(&stack.peek(2)?[..]) == U256::zero()) =>
+				GasCost::Call {
+					value: U256::from_big_endian(&stack.peek(2)?[..]),
+					gas: U256::from_big_endian(&stack.peek(0)?[..]),
+					target_exists: handler.exists(stack.peek(1)?.into()).await,
+				},
+
+			#[cfg(feature = "auth")]
+			Err(ExternalOpcode::AuthCall)
+				if config.has_auth &&
+				(!is_static || U256::from_big_endian(&stack.peek(2)?[..]) == U256::zero()) =>
+				GasCost::Call {
+					value: U256::from_big_endian(&stack.peek(2)?[..]),
+					gas: U256::from_big_endian(&stack.peek(0)?[..]),
+					target_exists: handler.exists(stack.peek(1)?.into()).await,
+				},
+
+			_ => GasCost::Invalid,
+		},
+	};
 
-		Err(ExternalOpcode::Other(_)) => GasCost::Invalid,
+	let gas_cost = match (gas_cost, config.gas_override(opcode)) {
+		(GasCost::Invalid, _) => GasCost::Invalid,
+		(_, Some(gas)) => GasCost::Fixed(gas),
+		(gas_cost, None) => gas_cost,
 	};
 
-	let memory_cost = match opcode {
+	Ok((gas_cost, memory_cost(opcode, stack)?))
+}
+
+/// Memory expanded by the opcode, if any.
+fn memory_cost(opcode: Result<Opcode, ExternalOpcode>, stack: &Stack) -> Result<Option<MemoryCost>, ExitError> {
+	Ok(match opcode {
 		Err(ExternalOpcode::Sha3) | Ok(Opcode::Return) | Ok(Opcode::Revert) |
 		Err(ExternalOpcode::Log(_)) => Some(MemoryCost {
 			offset: U256::from_big_endian(&stack.peek(0)?[..]),
 			len: U256::from_big_endian(&stack.peek(1)?[..]),
 		}),
 
+		#[cfg(feature = "auth")]
+		Err(ExternalOpcode::Auth) => Some(MemoryCost {
+			offset: U256::from_big_endian(&stack.peek(1)?[..]),
+			len: U256::from_big_endian(&stack.peek(2)?[..]),
+		}),
+
 		Ok(Opcode::CodeCopy) | Ok(Opcode::CallDataCopy) |
 		Err(ExternalOpcode::ReturnDataCopy) => Some(MemoryCost {
 			offset: U256::from_big_endian(&stack.peek(0)?[..]),
@@ -385,6 +494,15 @@ pub async fn opcode_cost<H: Handler>(
 			len: U256::from_big_endian(&stack.peek(2)?[..]),
 		}),
 
+		#[cfg(feature = "auth")]
+		Err(ExternalOpcode::AuthCall) => Some(MemoryCost {
+			offset: U256::from_big_endian(&stack.peek(3)?[..]),
+			len: U256::from_big_endian(&stack.peek(4)?[..]),
+		}.join(MemoryCost {
+			offset: U256::from_big_endian(&stack.peek(5)?[..]),
+			len: U256::from_big_endian(&stack.peek(6)?[..]),
+		})),
+
 		Err(ExternalOpcode::Call) | Err(ExternalOpcode::CallCode) => Some(MemoryCost {
 			offset: U256::from_big_endian(&stack.peek(3)?[..]),
 			len: U256::from_big_endian(&stack.peek(4)?[..]),
@@ -403,9 +521,7 @@ pub async fn opcode_cost<H: Handler>(
 		})),
 
 		_ => None,
-	};
-
-	Ok((gas_cost, memory_cost))
+	})
 }
 
 #[derive(Clone)]
@@ -483,8 +599,8 @@ impl Inner {
 			GasCost::ExtCodeCopy { len } => costs::extcodecopy_cost(len, &self.config)?,
 			GasCost::VeryLowCopy { len } => costs::verylowcopy_cost(len)?,
 			GasCost::Exp { power } => costs::exp_cost(power, &self.config)?,
-			GasCost::Create => consts::G_CREATE,
-			GasCost::Create2 { len } => costs::create2_cost(len)?,
+			GasCost::Create { len } => costs::create_cost(len, &self.config)?,
+			GasCost::Create2 { len } => costs::create2_cost(len, &self.config)?,
 			GasCost::JumpDest => consts::G_JUMPDEST,
 			GasCost::SLoad => self.config.gas_sload,
 
@@ -500,6 +616,7 @@ impl Inner {
 			GasCost::Balance => self.config.gas_balance,
 			GasCost::BlockHash => consts::G_BLOCKHASH,
 			GasCost::ExtCodeHash => self.config.gas_ext_code_hash,
+			GasCost::Fixed(gas) => gas,
 		})
 	}
 
@@ -534,6 +651,9 @@ pub enum GasCost {
 	High,
 	/// Fail the gasometer.
 	Invalid,
+	/// Fixed gas cost, from a configured override or for an opcode outside
+	/// the gas tiers.
+	Fixed(usize),
 
 	/// Gas cost for `EXTCODESIZE`.
 	ExtCodeSize,
@@ -622,7 +742,10 @@ pub enum GasCost {
 		power: U256
 	},
 	/// Gas cost for `CREATE`.
-	Create,
+	Create {
+		/// Length.
+		len: U256
+	},
 	/// Gas cost for `CREATE2`.
 	Create2 {
 		/// Length.
@@ -0,0 +1,172 @@
+use evm_core::{ExternalOpcode, Opcode};
+use evm_runtime::Config;
+
+use crate::{GasCost, static_gas_cost, static_opcode_cost};
+
+/// Formula of a gas cost depending on the stack or on the state, named
+/// after the `GasCost` variant computing it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DynamicCost {
+	/// `SHA3`: a base cost plus a cost per hashed word.
+	Sha3,
+	/// `CALLDATACOPY`, `CODECOPY` and `RETURNDATACOPY`: very low plus a cost
+	/// per copied word.
+	VeryLowCopy,
+	/// `EXTCODECOPY`: the account access cost plus a cost per copied word.
+	ExtCodeCopy,
+	/// `EXP`: a base cost plus a cost per byte of the exponent.
+	Exp,
+	/// `LOGn`: a base cost, a cost per topic and a cost per data byte.
+	Log,
+	/// `SSTORE`: depends on the original, current and new values.
+	SStore,
+	/// `CALL`: the access cost, plus value transfer and account creation
+	/// costs, plus the forwarded gas.
+	Call,
+	/// `CALLCODE`: as `CALL`, without account creation.
+	CallCode,
+	/// `DELEGATECALL`: the access cost plus the forwarded gas.
+	DelegateCall,
+	/// `STATICCALL`: the access cost plus the forwarded gas.
+	StaticCall,
+	/// `CREATE`: a fixed cost, plus a cost per word of init code with
+	/// EIP-3860 metering.
+	Create,
+	/// `CREATE2`: a fixed cost plus a cost per hashed word of init code,
+	/// plus a cost per word of init code with EIP-3860 metering.
+	Create2,
+	/// `SUICIDE`: a base cost plus account creation for a new beneficiary.
+	Suicide,
+}
+
+/// Gas charged for an opcode under a config.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OpcodeGas {
+	/// Opcode byte.
+	pub byte: u8,
+	/// Parsed opcode.
+	pub opcode: Result<Opcode, ExternalOpcode>,
+	/// Cost known before execution, if the cost is static.
+	pub static_cost: Option<usize>,
+	/// Formula of the cost, if it depends on the stack or on the state.
+	pub dynamic_cost: Option<DynamicCost>,
+	/// Whether memory expansion is charged on top of the cost.
+	pub memory_expansion: bool,
+}
+
+/// Gas charged for every opcode valid under a config, as returned by
+/// `Config::gas_table`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GasTable {
+	entries: Vec<OpcodeGas>,
+}
+
+impl GasTable {
+	/// Gas table of the given config.
+	pub fn new(config: &Config) -> Self {
+		let entries = (0..=255u8)
+			.filter_map(|byte| {
+				let opcode = Opcode::parse(byte);
+				let (static_cost, dynamic_cost) = match config.gas_override(opcode) {
+					Some(gas) if is_valid(opcode, config) => (Some(gas), None),
+					_ => (static_opcode_cost(opcode, config), dynamic_cost(opcode, config)),
+				};
+				if static_cost.is_none() && dynamic_cost.is_none() {
+					return None
+				}
+				Some(OpcodeGas {
+					byte,
+					opcode,
+					static_cost,
+					dynamic_cost,
+					memory_expansion: has_memory_expansion(opcode),
+				})
+			})
+			.collect();
+		Self { entries }
+	}
+
+	/// Gas charged for the opcode byte, or `None` if it is invalid.
+	pub fn get(&self, byte: u8) -> Option<&OpcodeGas> {
+		self.entries.iter().find(|entry| entry.byte == byte)
+	}
+
+	/// Valid opcodes, by increasing byte.
+	pub fn iter(&self) -> impl Iterator<Item=&OpcodeGas> {
+		self.entries.iter()
+	}
+
+	/// Number of valid opcodes.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Whether no opcode is valid.
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+}
+
+/// Gas table of a config, through `config.gas_table()`.
+pub trait ConfigGasTable {
+	/// Static costs and dynamic cost formulas of the opcodes valid under
+	/// this config.
+	fn gas_table(&self) -> GasTable;
+}
+
+impl ConfigGasTable for Config {
+	fn gas_table(&self) -> GasTable {
+		GasTable::new(self)
+	}
+}
+
+fn is_valid(opcode: Result<Opcode, ExternalOpcode>, config: &Config) -> bool {
+	match static_gas_cost(opcode, config) {
+		Some(GasCost::Invalid) => false,
+		Some(_) => true,
+		None => dynamic_cost(opcode, config).is_some(),
+	}
+}
+
+/// Formula of the cost of an opcode charged by `opcode_cost` from the
+/// stack or the state, or `None` if its cost is static or it is invalid.
+fn dynamic_cost(opcode: Result<Opcode, ExternalOpcode>, config: &Config) -> Option<DynamicCost> {
+	if static_gas_cost(opcode, config).is_some() {
+		return None
+	}
+
+	Some(match opcode {
+		Err(ExternalOpcode::Sha3) => DynamicCost::Sha3,
+		Ok(Opcode::CallDataCopy) | Ok(Opcode::CodeCopy) => DynamicCost::VeryLowCopy,
+		Err(ExternalOpcode::ReturnDataCopy) if config.has_return_data => DynamicCost::VeryLowCopy,
+		Err(ExternalOpcode::ExtCodeCopy) => DynamicCost::ExtCodeCopy,
+		Ok(Opcode::Exp) => DynamicCost::Exp,
+		Err(ExternalOpcode::Log(_)) => DynamicCost::Log,
+		Err(ExternalOpcode::SStore) => DynamicCost::SStore,
+		Err(ExternalOpcode::Call) => DynamicCost::Call,
+		#[cfg(feature = "auth")]
+		Err(ExternalOpcode::AuthCall) if config.has_auth => DynamicCost::Call,
+		Err(ExternalOpcode::CallCode) => DynamicCost::CallCode,
+		Err(ExternalOpcode::DelegateCall) if config.has_delegate_call => DynamicCost::DelegateCall,
+		Err(ExternalOpcode::StaticCall) => DynamicCost::StaticCall,
+		Err(ExternalOpcode::Create) => DynamicCost::Create,
+		Err(ExternalOpcode::Create2) if config.has_create2 => DynamicCost::Create2,
+		Err(ExternalOpcode::Suicide) => DynamicCost::Suicide,
+		_ => return None,
+	})
+}
+
+fn has_memory_expansion(opcode: Result<Opcode, ExternalOpcode>) -> bool {
+	match opcode {
+		Err(ExternalOpcode::Sha3) | Ok(Opcode::Return) | Ok(Opcode::Revert) |
+		Err(ExternalOpcode::Log(_)) | Ok(Opcode::CodeCopy) | Ok(Opcode::CallDataCopy) |
+		Err(ExternalOpcode::ReturnDataCopy) | Err(ExternalOpcode::ExtCodeCopy) |
+		Ok(Opcode::MLoad) | Ok(Opcode::MStore) | Ok(Opcode::MStore8) |
+		Err(ExternalOpcode::Create) | Err(ExternalOpcode::Create2) |
+		Err(ExternalOpcode::Call) | Err(ExternalOpcode::CallCode) |
+		Err(ExternalOpcode::DelegateCall) | Err(ExternalOpcode::StaticCall) => true,
+		#[cfg(feature = "auth")]
+		Err(ExternalOpcode::Auth) | Err(ExternalOpcode::AuthCall) => true,
+		_ => false,
+	}
+}
@@ -32,10 +32,12 @@ pub enum CallScheme {
 	DelegateCall,
 	/// `STATICCALL`
 	StaticCall,
+	/// `AUTHCALL`, per EIP-3074.
+	AuthCall,
 }
 
 /// Context of the runtime.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Context {
 	/// Execution address.
 	pub address: H160,
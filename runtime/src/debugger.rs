@@ -0,0 +1,156 @@
+use alloc::collections::BTreeSet;
+
+use primitive_types::H256;
+
+use crate::{Capture, Context, ExitError, ExitFatal, ExitReason, Handler, Memory, Runtime, Stack};
+
+/// Condition pausing the debugger before an instruction executes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Breakpoint {
+	/// Pause at the given program counter.
+	Position(usize),
+	/// Pause before every execution of the given opcode.
+	Opcode(u8),
+}
+
+/// Reason the debugger returned control.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Pause {
+	/// A single instruction was executed.
+	Step,
+	/// A breakpoint was hit. The instruction has not executed yet.
+	Breakpoint(Breakpoint),
+	/// Execution finished.
+	Exit(ExitReason),
+}
+
+/// Interactive debugger driving a runtime one instruction at a time.
+///
+/// Calls and creates are executed by the handler as a single step. Handlers
+/// returning interrupts instead exit with `UnhandledInterrupt`.
+pub struct Debugger<'h, H: Handler> {
+	runtime: Runtime,
+	handler: &'h mut H,
+	breakpoints: BTreeSet<Breakpoint>,
+	exit: Option<ExitReason>,
+}
+
+impl<'h, H: Handler> Debugger<'h, H> {
+	/// Create a new debugger over the runtime and handler.
+	pub fn new(runtime: Runtime, handler: &'h mut H) -> Self {
+		Self {
+			runtime,
+			handler,
+			breakpoints: BTreeSet::new(),
+			exit: None,
+		}
+	}
+
+	/// Add a breakpoint.
+	pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+		self.breakpoints.insert(breakpoint);
+	}
+
+	/// Remove a breakpoint. Returns whether it was set.
+	pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+		self.breakpoints.remove(&breakpoint)
+	}
+
+	/// Currently set breakpoints.
+	pub fn breakpoints(&self) -> impl Iterator<Item=&Breakpoint> {
+		self.breakpoints.iter()
+	}
+
+	/// Program counter of the next instruction, if execution is not finished.
+	pub fn position(&self) -> Option<usize> {
+		match self.exit {
+			Some(_) => None,
+			None => self.runtime.machine().position().as_ref().ok().cloned(),
+		}
+	}
+
+	/// Next opcode to be executed, if any.
+	pub fn opcode(&self) -> Option<u8> {
+		self.position().and_then(|p| self.runtime.machine().code().get(p).cloned())
+	}
+
+	/// Get a reference to the stack.
+	pub fn stack(&self) -> &Stack {
+		self.runtime.machine().stack()
+	}
+
+	/// Get a mutable reference to the stack.
+	pub fn stack_mut(&mut self) -> &mut Stack {
+		self.runtime.machine_mut().stack_mut()
+	}
+
+	/// Get a reference to the memory.
+	pub fn memory(&self) -> &Memory {
+		self.runtime.machine().memory()
+	}
+
+	/// Get a mutable reference to the memory.
+	pub fn memory_mut(&mut self) -> &mut Memory {
+		self.runtime.machine_mut().memory_mut()
+	}
+
+	/// Context of the executing frame.
+	pub fn context(&self) -> &Context {
+		self.runtime.context()
+	}
+
+	/// Storage value of the executing contract, including pending writes.
+	pub async fn storage(&self, index: H256) -> H256 {
+		self.handler.storage(self.runtime.context().address, index).await
+	}
+
+	/// Modify a storage value of the executing contract.
+	pub async fn set_storage(&mut self, index: H256, value: H256) -> Result<(), ExitError> {
+		let address = self.runtime.context().address;
+		self.handler.set_storage(address, index, value).await
+	}
+
+	/// Execute a single instruction, ignoring breakpoints.
+	pub async fn step(&mut self) -> Pause {
+		if let Some(exit) = self.exit {
+			return Pause::Exit(exit)
+		}
+
+		let exit = match self.runtime.step(self.handler).await {
+			Ok(()) => return Pause::Step,
+			Err(Capture::Exit(exit)) => exit,
+			Err(Capture::Trap(_)) => ExitFatal::UnhandledInterrupt.into(),
+		};
+		self.exit = Some(exit);
+		Pause::Exit(exit)
+	}
+
+	/// Continue until a breakpoint is hit or execution finishes. A
+	/// breakpoint at the current instruction does not pause again.
+	pub async fn resume(&mut self) -> Pause {
+		loop {
+			if let Pause::Exit(exit) = self.step().await {
+				return Pause::Exit(exit)
+			}
+
+			if let Some(breakpoint) = self.current_breakpoint() {
+				return Pause::Breakpoint(breakpoint)
+			}
+		}
+	}
+
+	/// Breakpoint matching the next instruction, if any.
+	pub fn current_breakpoint(&self) -> Option<Breakpoint> {
+		let position = self.position()?;
+		let opcode = self.opcode();
+		self.breakpoints.iter().find(|b| match b {
+			Breakpoint::Position(p) => *p == position,
+			Breakpoint::Opcode(o) => Some(*o) == opcode,
+		}).cloned()
+	}
+
+	/// Finish debugging, returning the runtime.
+	pub fn into_runtime(self) -> Runtime {
+		self.runtime
+	}
+}
@@ -1,19 +1,23 @@
+use primitive_types::U256;
+
 use crate::{CallScheme, ExitReason, ExternalOpcode, Handler, Runtime};
 
 #[macro_use]
 mod macros;
 mod system;
 
+pub use self::system::{call_result, create_result};
+
 pub enum Control<H: Handler> {
 	Continue,
-	CallInterrupt(H::CallInterrupt),
+	CallInterrupt(H::CallInterrupt, U256, U256),
 	CreateInterrupt(H::CreateInterrupt),
 	Exit(ExitReason)
 }
 
 pub async fn eval<H: Handler>(state: &mut Runtime, opcode: ExternalOpcode, handler: &mut H) -> Control<H> {
 	match opcode {
-		ExternalOpcode::Sha3 => system::sha3(state),
+		ExternalOpcode::Sha3 => system::sha3(state, handler),
 		ExternalOpcode::Address => system::address(state),
 		ExternalOpcode::Balance => system::balance(state, handler).await,
 		ExternalOpcode::SelfBalance => system::selfbalance(state, handler).await,
@@ -44,6 +48,13 @@ pub async fn eval<H: Handler>(state: &mut Runtime, opcode: ExternalOpcode, handl
 		ExternalOpcode::DelegateCall => system::call(state, CallScheme::DelegateCall, handler).await,
 		ExternalOpcode::StaticCall => system::call(state, CallScheme::StaticCall, handler).await,
 		ExternalOpcode::ChainId => system::chainid(state, handler).await,
+		#[cfg(feature = "auth")]
+		ExternalOpcode::Auth => system::auth(state, handler).await,
+		#[cfg(feature = "auth")]
+		ExternalOpcode::AuthCall => system::call(state, CallScheme::AuthCall, handler).await,
+		#[cfg(not(feature = "auth"))]
+		ExternalOpcode::Auth | ExternalOpcode::AuthCall =>
+			Control::Exit(crate::ExitError::DesignatedInvalid.into()),
 		ExternalOpcode::Other(opcode) => {
 			match handler.other(
 				opcode,
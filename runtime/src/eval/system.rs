@@ -1,15 +1,14 @@
 use alloc::vec::Vec;
 use core::cmp::min;
 
-use primitive_types::{H256, U256};
-use sha3::{Digest, Keccak256};
+use primitive_types::{H160, H256, U256};
 
-use crate::{CallScheme, Capture, Context, CreateScheme, ExitError, ExitFatal,
+use crate::{Bytes, CallScheme, Capture, Context, CreateScheme, ExitError, ExitFatal,
 			ExitReason, ExitSucceed, Handler, Runtime, Transfer};
 
 use super::Control;
 
-pub fn sha3<H: Handler>(runtime: &mut Runtime) -> Control<H> {
+pub fn sha3<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
 	pop_u256!(runtime, from, len);
 
 	try_or_fail!(runtime.machine.memory_mut().resize_offset(from, len));
@@ -22,8 +21,7 @@ pub fn sha3<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 		runtime.machine.memory_mut().get(from, len)
 	};
 
-	let ret = Keccak256::digest(data.as_slice());
-	push!(runtime, H256::from_slice(ret.as_slice()));
+	push!(runtime, handler.keccak256(&data));
 
 	Control::Continue
 }
@@ -126,7 +124,8 @@ pub fn returndatasize<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 pub fn returndatacopy<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 	pop_u256!(runtime, memory_offset, data_offset, len);
 
-	try_or_fail!(runtime.machine.memory_mut().resize_offset(memory_offset, len));
+	// Unlike other copy opcodes, reading past the end of the return data
+	// buffer is an exceptional halt rather than zero-filled (EIP-211).
 	if data_offset.checked_add(len)
 		.map(|l| l > U256::from(runtime.return_data_buffer.len()))
 		.unwrap_or(true)
@@ -134,6 +133,8 @@ pub fn returndatacopy<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 		return Control::Exit(ExitError::OutOfOffset.into())
 	}
 
+	try_or_fail!(runtime.machine.memory_mut().resize_offset(memory_offset, len));
+
 	match runtime.machine.memory_mut().copy_large(memory_offset, data_offset, len, &runtime.return_data_buffer) {
 		Ok(()) => Control::Continue,
 		Err(e) => Control::Exit(e.into()),
@@ -236,23 +237,29 @@ pub async fn create<H: Handler>(
 	is_create2: bool,
 	handler: &mut H,
 ) -> Control<H> {
-	runtime.return_data_buffer = Vec::new();
+	runtime.return_data_buffer = Bytes::new();
 
 	pop_u256!(runtime, value, code_offset, len);
 
+	if let Some(limit) = runtime.config.max_initcode_size {
+		if len > U256::from(limit) {
+			return Control::Exit(ExitError::InitCodeLimit.into())
+		}
+	}
+
 	try_or_fail!(runtime.machine.memory_mut().resize_offset(code_offset, len));
 	let code = if len == U256::zero() {
-		Vec::new()
+		Bytes::new()
 	} else {
 		let code_offset = as_usize_or_fail!(code_offset);
 		let len = as_usize_or_fail!(len);
 
-		runtime.machine.memory().get(code_offset, len)
+		Bytes::from(runtime.machine.memory().get(code_offset, len))
 	};
 
 	let scheme = if is_create2 {
 		pop!(runtime, salt);
-		let code_hash = H256::from_slice(Keccak256::digest(&code).as_slice());
+		let code_hash = handler.keccak256(&code);
 		CreateScheme::Create2 {
 			caller: runtime.context.address,
 			salt,
@@ -266,26 +273,9 @@ pub async fn create<H: Handler>(
 
 	match handler.create(runtime.context.address, scheme, value, code, None).await {
 		Capture::Exit((reason, address, return_data)) => {
-			runtime.return_data_buffer = return_data;
-			let create_address: H256 = address.map(|a| a.into()).unwrap_or_default();
-
-			match reason {
-				ExitReason::Succeed(_) => {
-					push!(runtime, create_address.into());
-					Control::Continue
-				},
-				ExitReason::Revert(_) => {
-					push!(runtime, H256::default());
-					Control::Continue
-				},
-				ExitReason::Error(_) => {
-					push!(runtime, H256::default());
-					Control::Continue
-				},
-				ExitReason::Fatal(e) => {
-					push!(runtime, H256::default());
-					Control::Exit(e.into())
-				},
+			match create_result(runtime, reason, address, return_data) {
+				None => Control::Continue,
+				Some(exit) => Control::Exit(exit),
 			}
 		},
 		Capture::Trap(interrupt) => {
@@ -295,12 +285,72 @@ pub async fn create<H: Handler>(
 	}
 }
 
+/// Feed the result of a create to the runtime, as `CREATE` does. Returns
+/// the reason to exit the runtime with, if any.
+pub fn create_result(
+	runtime: &mut Runtime,
+	reason: ExitReason,
+	address: Option<H160>,
+	return_data: Bytes,
+) -> Option<ExitReason> {
+	runtime.return_data_buffer = return_data;
+	let create_address: H256 = address.map(|a| a.into()).unwrap_or_default();
+
+	let pushed = match reason {
+		ExitReason::Succeed(_) => runtime.machine.stack_mut().push(create_address),
+		_ => runtime.machine.stack_mut().push(H256::default()),
+	};
+	if let Err(e) = pushed {
+		return Some(e.into())
+	}
+
+	match reason {
+		ExitReason::Fatal(e) => Some(e.into()),
+		_ => None,
+	}
+}
+
+fn is_auth_call(scheme: CallScheme) -> bool {
+	scheme == CallScheme::AuthCall
+}
+
+/// Length of the `AUTH` memory input: y parity, r, s and commit.
+#[cfg(feature = "auth")]
+const AUTH_INPUT_LEN: usize = 97;
+
+#[cfg(feature = "auth")]
+pub async fn auth<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	pop!(runtime, authority);
+	pop_u256!(runtime, offset, len);
+
+	try_or_fail!(runtime.machine.memory_mut().resize_offset(offset, len));
+	let mut input = [0u8; AUTH_INPUT_LEN];
+	if len != U256::zero() {
+		// The memory resize above checked that the range fits in usize.
+		let len = min(len.as_usize(), AUTH_INPUT_LEN);
+		let data = runtime.machine.memory().get(offset.as_usize(), len);
+		input[..data.len()].copy_from_slice(&data);
+	}
+
+	let authorized = try_or_fail!(handler.auth(
+		runtime.context.address,
+		authority.into(),
+		input[0],
+		H256::from_slice(&input[1..33]),
+		H256::from_slice(&input[33..65]),
+		H256::from_slice(&input[65..97]),
+	).await);
+	push_u256!(runtime, if authorized { U256::one() } else { U256::zero() });
+
+	Control::Continue
+}
+
 pub async fn call<'config, H: Handler>(
 	runtime: &mut Runtime,
 	scheme: CallScheme,
 	handler: &mut H,
 ) -> Control<H> {
-	runtime.return_data_buffer = Vec::new();
+	runtime.return_data_buffer = Bytes::new();
 
 	pop_u256!(runtime, gas);
 	pop!(runtime, to);
@@ -311,6 +361,10 @@ pub async fn call<'config, H: Handler>(
 	};
 
 	let value = match scheme {
+		CallScheme::AuthCall => {
+			pop_u256!(runtime, value);
+			value
+		},
 		CallScheme::Call | CallScheme::CallCode => {
 			pop_u256!(runtime, value);
 			value
@@ -326,15 +380,26 @@ pub async fn call<'config, H: Handler>(
 	try_or_fail!(runtime.machine.memory_mut().resize_offset(out_offset, out_len));
 
 	let input = if in_len == U256::zero() {
-		Vec::new()
+		Bytes::new()
 	} else {
 		let in_offset = as_usize_or_fail!(in_offset);
 		let in_len = as_usize_or_fail!(in_len);
 
-		runtime.machine.memory().get(in_offset, in_len)
+		Bytes::from(runtime.machine.memory().get(in_offset, in_len))
+	};
+
+	let caller = match (scheme, handler.authorized()) {
+		(CallScheme::AuthCall, Some(authorized)) => authorized,
+		(CallScheme::AuthCall, None) => return Control::Exit(ExitError::Other("AUTHCALL without authorization").into()),
+		_ => runtime.context.address,
 	};
 
 	let context = match scheme {
+		CallScheme::AuthCall => Context {
+			address: to.into(),
+			caller,
+			apparent_value: value,
+		},
 		CallScheme::Call | CallScheme::StaticCall => Context {
 			address: to.into(),
 			caller: runtime.context.address,
@@ -358,6 +423,12 @@ pub async fn call<'config, H: Handler>(
 			target: to.into(),
 			value: value.into()
 		})
+	} else if is_auth_call(scheme) {
+		Some(Transfer {
+			source: caller,
+			target: to.into(),
+			value,
+		})
 	} else if scheme == CallScheme::CallCode {
 		Some(Transfer {
 			source: runtime.context.address,
@@ -370,54 +441,60 @@ pub async fn call<'config, H: Handler>(
 
 	match handler.call(to.into(), transfer, input, gas, scheme == CallScheme::StaticCall, context).await {
 		Capture::Exit((reason, return_data)) => {
-			runtime.return_data_buffer = return_data;
-			let target_len = min(out_len, U256::from(runtime.return_data_buffer.len()));
-
-			match reason {
-				ExitReason::Succeed(_) => {
-					match runtime.machine.memory_mut().copy_large(
-						out_offset,
-						U256::zero(),
-						target_len,
-						&runtime.return_data_buffer[..],
-					) {
-						Ok(()) => {
-							push_u256!(runtime, U256::one());
-							Control::Continue
-						},
-						Err(_) => {
-							push_u256!(runtime, U256::zero());
-							Control::Continue
-						},
-					}
-				},
-				ExitReason::Revert(_) => {
-					push_u256!(runtime, U256::zero());
-
-					let _ = runtime.machine.memory_mut().copy_large(
-						out_offset,
-						U256::zero(),
-						target_len,
-						&runtime.return_data_buffer[..],
-					);
-
-					Control::Continue
-				},
-				ExitReason::Error(_) => {
-					push_u256!(runtime, U256::zero());
-
-					Control::Continue
-				},
-				ExitReason::Fatal(e) => {
-					push_u256!(runtime, U256::zero());
-
-					Control::Exit(e.into())
-				},
+			match call_result(runtime, reason, return_data, out_offset, out_len) {
+				None => Control::Continue,
+				Some(exit) => Control::Exit(exit),
 			}
 		},
 		Capture::Trap(interrupt) => {
 			push!(runtime, H256::default());
-			Control::CallInterrupt(interrupt)
+			Control::CallInterrupt(interrupt, out_offset, out_len)
+		},
+	}
+}
+
+/// Feed the result of a call to the runtime, as `CALL` does, copying the
+/// return data to the output range of the call. Returns the reason to exit
+/// the runtime with, if any.
+pub fn call_result(
+	runtime: &mut Runtime,
+	reason: ExitReason,
+	return_data: Bytes,
+	out_offset: U256,
+	out_len: U256,
+) -> Option<ExitReason> {
+	runtime.return_data_buffer = return_data;
+	let target_len = min(out_len, U256::from(runtime.return_data_buffer.len()));
+
+	let success = match reason {
+		ExitReason::Succeed(_) => runtime.machine.memory_mut().copy_large(
+			out_offset,
+			U256::zero(),
+			target_len,
+			&runtime.return_data_buffer[..],
+		).is_ok(),
+		ExitReason::Revert(_) => {
+			let _ = runtime.machine.memory_mut().copy_large(
+				out_offset,
+				U256::zero(),
+				target_len,
+				&runtime.return_data_buffer[..],
+			);
+			false
 		},
+		ExitReason::Error(_) | ExitReason::Fatal(_) => false,
+	};
+
+	let mut value = H256::default();
+	if success {
+		U256::one().to_big_endian(&mut value[..]);
+	}
+	if let Err(e) = runtime.machine.stack_mut().push(value) {
+		return Some(e.into())
+	}
+
+	match reason {
+		ExitReason::Fatal(e) => Some(e.into()),
+		_ => None,
 	}
 }
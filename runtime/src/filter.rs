@@ -0,0 +1,37 @@
+/// Filter of opcodes allowed to execute, stored as a bitmap of banned
+/// opcode bytes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OpcodeFilter([u64; 4]);
+
+impl OpcodeFilter {
+	/// Filter allowing every opcode.
+	pub const fn allow_all() -> Self {
+		Self([0; 4])
+	}
+
+	/// Filter allowing only the given opcodes.
+	pub fn allow_only(opcodes: &[u8]) -> Self {
+		let mut filter = Self([!0; 4]);
+		for opcode in opcodes {
+			filter.0[(*opcode / 64) as usize] &= !(1 << (*opcode % 64));
+		}
+		filter
+	}
+
+	/// Ban the given opcode in addition to the currently banned ones.
+	pub fn ban(mut self, opcode: u8) -> Self {
+		self.0[(opcode / 64) as usize] |= 1 << (opcode % 64);
+		self
+	}
+
+	/// Whether the opcode is allowed to execute.
+	pub fn is_allowed(&self, opcode: u8) -> bool {
+		self.0[(opcode / 64) as usize] & (1 << (opcode % 64)) == 0
+	}
+}
+
+impl Default for OpcodeFilter {
+	fn default() -> Self {
+		Self::allow_all()
+	}
+}
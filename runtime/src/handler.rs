@@ -2,8 +2,8 @@ use alloc::vec::Vec;
 
 use primitive_types::{H160, H256, U256};
 
-use crate::{Capture, Context, CreateScheme, ExitError, ExitReason,
-			ExternalOpcode, Machine, Opcode, Stack};
+use crate::{Bytes, Capture, Context, CreateScheme, ExitError, ExitReason,
+			ExternalOpcode, Hasher, Keccak256Hasher, Machine, Opcode, Stack};
 
 /// Transfer from source to target, with given value.
 #[derive(Clone, Debug)]
@@ -16,18 +16,39 @@ pub struct Transfer {
 	pub value: U256,
 }
 
-/// EVM context handler.
-#[async_trait::async_trait]
-pub trait Handler {
-	/// Type of `CREATE` interrupt.
-	type CreateInterrupt;
-	/// Feedback value for `CREATE` interrupt.
-	type CreateFeedback;
-	/// Type of `CALL` interrupt.
-	type CallInterrupt;
-	/// Feedback value of `CALL` interrupt.
-	type CallFeedback;
+/// `Send`, unless the `no-send` feature relaxes the thread-safety bounds of
+/// handlers and backends.
+#[cfg(not(feature = "no-send"))]
+pub trait MaybeSend: Send {}
+#[cfg(not(feature = "no-send"))]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// `Send`, unless the `no-send` feature relaxes the thread-safety bounds of
+/// handlers and backends.
+#[cfg(feature = "no-send")]
+pub trait MaybeSend {}
+#[cfg(feature = "no-send")]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// `Sync`, unless the `no-send` feature relaxes the thread-safety bounds of
+/// handlers and backends.
+#[cfg(not(feature = "no-send"))]
+pub trait MaybeSync: Sync {}
+#[cfg(not(feature = "no-send"))]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+
+/// `Sync`, unless the `no-send` feature relaxes the thread-safety bounds of
+/// handlers and backends.
+#[cfg(feature = "no-send")]
+pub trait MaybeSync {}
+#[cfg(feature = "no-send")]
+impl<T: ?Sized> MaybeSync for T {}
 
+/// Read-only view of the EVM context. Queries take `&self`, so a handler
+/// shared by reference can answer them from several threads at once.
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+pub trait StateQuery {
 	/// Get balance of address.
 	async fn balance(&self, address: H160) -> U256;
 	/// Get code size of address.
@@ -66,6 +87,37 @@ pub trait Handler {
 	async fn exists(&self, address: H160) -> bool;
 	/// Check whether an address has already been deleted.
 	fn deleted(&self, address: H160) -> bool;
+	/// Hash function of `SHA3`, code hashes and `CREATE2` addresses.
+	fn hasher(&self) -> &dyn Hasher {
+		&Keccak256Hasher
+	}
+	/// Digest of `hasher` used by `SHA3` and for `CREATE2` code hashes.
+	/// Handlers may memoize it.
+	fn keccak256(&self, data: &[u8]) -> H256 {
+		self.hasher().hash(data)
+	}
+	/// Account authorized by `AUTH` in the current frame, if any.
+	fn authorized(&self) -> Option<H160> {
+		None
+	}
+}
+
+/// EVM context handler able to change state and spawn sub-executions.
+/// Methods take `&mut self`, as they update the state of the running
+/// transaction: its substate, gas and logs. Simulations sharing state do
+/// so through their backend, such as a `CachedBackend`, with one handler
+/// each.
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+pub trait StateMutator: StateQuery {
+	/// Type of `CREATE` interrupt.
+	type CreateInterrupt;
+	/// Feedback value for `CREATE` interrupt.
+	type CreateFeedback;
+	/// Type of `CALL` interrupt.
+	type CallInterrupt;
+	/// Feedback value of `CALL` interrupt.
+	type CallFeedback;
 
 	/// Set storage value of address at index.
 	async fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError>;
@@ -79,9 +131,9 @@ pub trait Handler {
 		caller: H160,
 		scheme: CreateScheme,
 		value: U256,
-		init_code: Vec<u8>,
+		init_code: Bytes,
 		target_gas: Option<usize>,
-	) -> Capture<(ExitReason, Option<H160>, Vec<u8>), Self::CreateInterrupt>;
+	) -> Capture<(ExitReason, Option<H160>, Bytes), Self::CreateInterrupt>;
 	/// Feed in create feedback.
 	fn create_feedback(
 		&mut self,
@@ -94,11 +146,11 @@ pub trait Handler {
 		&mut self,
 		code_address: H160,
 		transfer: Option<Transfer>,
-		input: Vec<u8>,
+		input: Bytes,
 		target_gas: Option<usize>,
 		is_static: bool,
 		context: Context,
-	) -> Capture<(ExitReason, Vec<u8>), Self::CallInterrupt>;
+	) -> Capture<(ExitReason, Bytes), Self::CallInterrupt>;
 	/// Feed in call feedback.
 	fn call_feedback(
 		&mut self,
@@ -107,11 +159,26 @@ pub trait Handler {
 		Ok(())
 	}
 
-	/// Pre-validation step for the runtime.
+	/// Check an EIP-3074 authorization of `invoker` signed by `authority`,
+	/// and set the authorized account of the current frame on success or
+	/// clear it otherwise. Only called with the `auth` feature.
+	async fn auth(
+		&mut self,
+		invoker: H160,
+		authority: H160,
+		y_parity: u8,
+		r: H256,
+		s: H256,
+		commit: H256,
+	) -> Result<bool, ExitError>;
+
+	/// Pre-validation step for the runtime. `static_gas` is the static gas
+	/// cost of the opcode computed when the code was analyzed, if any.
 	async fn pre_validate(
 		&mut self,
 		context: &Context,
 		opcode: Result<Opcode, ExternalOpcode>,
+		static_gas: Option<usize>,
 		stack: &Stack
 	) -> Result<(), ExitError>;
 	/// Handle other unknown xternal opcodes.
@@ -123,3 +190,8 @@ pub trait Handler {
 		Err(ExitError::OutOfGas)
 	}
 }
+
+/// Full EVM context handler, implemented for every `StateMutator`.
+pub trait Handler: StateMutator {}
+
+impl<T: StateMutator + ?Sized> Handler for T {}
@@ -0,0 +1,28 @@
+//! Hash function behind the Keccak256 digests of the EVM.
+
+use core::fmt::Debug;
+
+use primitive_types::H256;
+use sha3::{Digest, Keccak256};
+
+/// Hash function computing the digests the EVM specifies as Keccak256:
+/// `SHA3`, code hashes, `CREATE` and `CREATE2` addresses, and the
+/// Merkle-Patricia trie of state roots and proofs.
+///
+/// Embedders can substitute an accelerated or precomputed implementation,
+/// or a different hash entirely for experimental chains. Digests of other
+/// hash functions are not compatible with Ethereum.
+pub trait Hasher: Debug + Send + Sync {
+	/// Digest of `data`.
+	fn hash(&self, data: &[u8]) -> H256;
+}
+
+/// Keccak256, the hash function of Ethereum.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Keccak256Hasher;
+
+impl Hasher for Keccak256Hasher {
+	fn hash(&self, data: &[u8]) -> H256 {
+		H256::from_slice(Keccak256::digest(data).as_slice())
+	}
+}
@@ -1,4 +1,9 @@
-use crate::{ExitFatal, Handler, Runtime};
+use core::mem::ManuallyDrop;
+
+use primitive_types::{H160, U256};
+
+use crate::{Bytes, ExitFatal, ExitReason, Handler, Runtime};
+use crate::eval::{call_result, create_result};
 
 /// Interrupt resolution.
 pub enum Resolve<'a, H: Handler> {
@@ -8,7 +13,8 @@ pub enum Resolve<'a, H: Handler> {
 	Call(H::CallInterrupt, ResolveCall<'a>),
 }
 
-/// Create interrupt resolution.
+/// Create interrupt resolution. Dropping it unresolved exits the runtime
+/// with `ExitFatal::UnhandledInterrupt`.
 pub struct ResolveCreate<'a> {
 	runtime: &'a mut Runtime,
 }
@@ -17,6 +23,19 @@ impl<'a, 'config> ResolveCreate<'a> {
 	pub(crate) fn new(runtime: &'a mut Runtime) -> Self {
 		Self { runtime }
 	}
+
+	/// Resolve the interrupt with the result of the create, as if the
+	/// handler had returned it, so that the runtime can continue. The
+	/// address replaces the zero pushed when the interrupt was raised.
+	pub fn resolve(self, reason: ExitReason, address: Option<H160>, return_data: Bytes) {
+		let mut this = ManuallyDrop::new(self);
+		let runtime = &mut *this.runtime;
+		let _ = runtime.machine.stack_mut().pop();
+		if let Some(exit) = create_result(runtime, reason, address, return_data) {
+			runtime.machine.exit(exit);
+			runtime.status = Err(exit);
+		}
+	}
 }
 
 impl<'a, 'config> Drop for ResolveCreate<'a> {
@@ -26,14 +45,31 @@ impl<'a, 'config> Drop for ResolveCreate<'a> {
 	}
 }
 
-/// Call interrupt resolution.
+/// Call interrupt resolution. Dropping it unresolved exits the runtime
+/// with `ExitFatal::UnhandledInterrupt`.
 pub struct ResolveCall<'a> {
 	runtime: &'a mut Runtime,
+	out_offset: U256,
+	out_len: U256,
 }
 
 impl<'a, 'config> ResolveCall<'a> {
-	pub(crate) fn new(runtime: &'a mut Runtime) -> Self {
-		Self { runtime }
+	pub(crate) fn new(runtime: &'a mut Runtime, out_offset: U256, out_len: U256) -> Self {
+		Self { runtime, out_offset, out_len }
+	}
+
+	/// Resolve the interrupt with the result of the call, as if the handler
+	/// had returned it, so that the runtime can continue. The success flag
+	/// replaces the zero pushed when the interrupt was raised.
+	pub fn resolve(self, reason: ExitReason, return_data: Bytes) {
+		let mut this = ManuallyDrop::new(self);
+		let (out_offset, out_len) = (this.out_offset, this.out_len);
+		let runtime = &mut *this.runtime;
+		let _ = runtime.machine.stack_mut().pop();
+		if let Some(exit) = call_result(runtime, reason, return_data, out_offset, out_len) {
+			runtime.machine.exit(exit);
+			runtime.status = Err(exit);
+		}
 	}
 }
 
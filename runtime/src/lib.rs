@@ -13,18 +13,45 @@ use std::sync::Arc;
 pub use evm_core::*;
 
 pub use crate::context::{CallScheme, Context, CreateScheme};
-pub use crate::handler::{Handler, Transfer};
+pub use crate::debugger::{Breakpoint, Debugger, Pause};
+pub use crate::filter::OpcodeFilter;
+pub use crate::hasher::{Hasher, Keccak256Hasher};
+pub use crate::handler::{Handler, MaybeSend, MaybeSync, StateMutator, StateQuery, Transfer};
 pub use crate::interrupt::{Resolve, ResolveCall, ResolveCreate};
+pub use crate::resolve::{InterruptHandler, resolve};
 
 mod eval;
 mod context;
+mod debugger;
+mod filter;
 mod interrupt;
 mod handler;
+mod hasher;
+mod resolve;
+mod strict;
 
 macro_rules! step {
 	( $self:expr, $handler:expr, $return:tt $($err:path)?; $($ok:path)? ) => ({
+		if let Ok(position) = $self.machine.position() {
+			if let Some(opcode) = $self.machine.code().get(*position).cloned() {
+				if !$self.config.opcode_filter.is_allowed(opcode) {
+					let e = ExitError::DisallowedOpcode(opcode);
+					$self.machine.exit(e.into());
+					$self.status = Err(e.into());
+				}
+			}
+		}
+
+		if $self.config.strict && $self.status.is_ok() {
+			if let Err(violation) = strict::check(&$self.machine, &$self.return_data_buffer) {
+				$self.machine.exit(violation.into());
+				$self.status = Err(violation.into());
+			}
+		}
+
 		if let Some((opcode, stack)) = $self.machine.inspect() {
-			match $handler.pre_validate(&$self.context, opcode, stack).await {
+			let static_gas = $self.machine.static_gas();
+			match $handler.pre_validate(&$self.context, opcode, static_gas, stack).await {
 				Ok(()) => (),
 				Err(e) => {
 					$self.machine.exit(e.into());
@@ -51,8 +78,8 @@ macro_rules! step {
 			Err(Capture::Trap(opcode)) => {
 				match eval::eval($self, opcode, $handler).await {
 					eval::Control::Continue => $($ok)?(()),
-					eval::Control::CallInterrupt(interrupt) => {
-						let resolve = ResolveCall::new($self);
+					eval::Control::CallInterrupt(interrupt, out_offset, out_len) => {
+						let resolve = ResolveCall::new($self, out_offset, out_len);
 						#[allow(unused_parens)]
 						$return $($err)*(Capture::Trap(Resolve::Call(interrupt, resolve)))
 					},
@@ -79,25 +106,70 @@ macro_rules! step {
 pub struct Runtime {
 	machine: Machine,
 	status: Result<(), ExitReason>,
-	return_data_buffer: Vec<u8>,
+	return_data_buffer: Bytes,
 	context: Context,
-	_config: Arc<Config>,
+	config: Arc<Config>,
 }
 
 impl Runtime {
 	/// Create a new runtime with given code and data.
 	pub fn new(
 		code: Arc<Vec<u8>>,
-		data: Arc<Vec<u8>>,
+		data: Bytes,
+		context: Context,
+		config: Arc<Config>,
+	) -> Self {
+		Self::new_analyzed(Arc::new(AnalyzedCode::new(code)), data, context, config)
+	}
+
+	/// Create a new runtime executing already analyzed code.
+	pub fn new_analyzed(
+		analysis: Arc<AnalyzedCode>,
+		data: Bytes,
 		context: Context,
 		config: Arc<Config>,
 	) -> Self {
 		Self {
-			machine: Machine::new(code, data, config.stack_limit, config.memory_limit),
+			machine: Self::new_machine(analysis, data, &config),
 			status: Ok(()),
-			return_data_buffer: Vec::new(),
+			return_data_buffer: Bytes::new(),
 			context,
-			_config: config,
+			config,
+		}
+	}
+
+	#[cfg(not(feature = "eof"))]
+	fn new_machine(analysis: Arc<AnalyzedCode>, data: Bytes, config: &Config) -> Machine {
+		let analysis = Self::engine_analysis(analysis, config);
+		Machine::new_analyzed(analysis, data, config.stack_limit, config.memory_limit)
+	}
+
+	/// Translate the analysis for the threaded engine if it was not already.
+	fn engine_analysis(analysis: Arc<AnalyzedCode>, config: &Config) -> Arc<AnalyzedCode> {
+		if config.engine == Engine::Threaded && !analysis.is_threaded() {
+			Arc::new(analysis.as_ref().clone().threaded())
+		} else {
+			analysis
+		}
+	}
+
+	/// Code starting with the EOF magic runs as an EOF container once EOF is
+	/// enabled, and exits with `ExitError::InvalidCode` if it is not valid.
+	#[cfg(feature = "eof")]
+	fn new_machine(analysis: Arc<AnalyzedCode>, data: Bytes, config: &Config) -> Machine {
+		if !config.has_eof || !is_eof(&analysis.code()[..]) {
+			let analysis = Self::engine_analysis(analysis, config);
+			return Machine::new_analyzed(analysis, data, config.stack_limit, config.memory_limit)
+		}
+
+		let code = analysis.code().clone();
+		match Machine::new_eof(code, data.clone(), config.stack_limit, config.memory_limit) {
+			Ok(machine) => machine,
+			Err(_) => {
+				let mut machine = Machine::new_analyzed(analysis, data, config.stack_limit, config.memory_limit);
+				machine.exit(ExitError::InvalidCode.into());
+				machine
+			},
 		}
 	}
 
@@ -106,6 +178,21 @@ impl Runtime {
 		&self.machine
 	}
 
+	/// Get a mutable reference to the machine.
+	pub fn machine_mut(&mut self) -> &mut Machine {
+		&mut self.machine
+	}
+
+	/// Consume the runtime, returning its machine.
+	pub fn into_machine(self) -> Machine {
+		self.machine
+	}
+
+	/// Get a reference to the execution context.
+	pub fn context(&self) -> &Context {
+		&self.context
+	}
+
 	/// Step the runtime.
 	pub async fn step<'a, H: Handler>(
 		&'a mut self,
@@ -125,6 +212,17 @@ impl Runtime {
 	}
 }
 
+/// Execution engine of the machine.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Engine {
+	/// Dispatch on the opcode of every instruction. This is the reference
+	/// engine.
+	Interpreter,
+	/// Translate analyzed code into one handler per instruction, called
+	/// without dispatching on the opcode.
+	Threaded,
+}
+
 /// Runtime configuration.
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -154,13 +252,39 @@ pub struct Config {
 	pub gas_transaction_create: usize,
 	/// Gas paid for a message call transaction.
 	pub gas_transaction_call: usize,
+	/// Gas paid for each 32-byte word of init code of create transactions
+	/// and opcodes (EIP-3860).
+	pub gas_initcode_word: usize,
 	/// Gas paid for zero data in a transaction.
 	pub gas_transaction_zero_data: usize,
 	/// Gas paid for non-zero data in a transaction.
 	pub gas_transaction_non_zero_data: usize,
+	/// EIP-7623 floor gas per calldata token, if the floor is enabled.
+	pub gas_transaction_floor_per_token: Option<usize>,
+	/// Gas paid for each EIP-7702 authorization in a transaction.
+	pub gas_transaction_authorization: usize,
+	/// Gas refunded for each EIP-7702 authorization of an existing account.
+	pub refund_authorization_existing: isize,
+	/// Gas paid by a call to an EIP-7702 delegated account when its
+	/// delegate was not accessed before.
+	pub gas_delegate_cold_access: usize,
+	/// Gas paid by a call to an EIP-7702 delegated account when its
+	/// delegate was accessed before.
+	pub gas_delegate_warm_access: usize,
+	/// Gas paid for the bn128 addition precompile.
+	pub gas_bn128_add: usize,
+	/// Gas paid for the bn128 scalar multiplication precompile.
+	pub gas_bn128_mul: usize,
+	/// Base gas paid for the bn128 pairing precompile.
+	pub gas_bn128_pairing_base: usize,
+	/// Gas paid for the bn128 pairing precompile for every pair of points.
+	pub gas_bn128_pairing_point: usize,
+	/// EIP-2565.
+	pub modexp_eip2565: bool,
 	/// EIP-1283.
 	pub sstore_gas_metering: bool,
-	/// EIP-1706.
+	/// EIP-1706 and EIP-2200: `SSTORE` fails if the gas left is not more
+	/// than the call stipend.
 	pub sstore_revert_under_stipend: bool,
 	/// Whether to throw out of gas error when
 	/// CALL/CALLCODE/DELEGATECALL requires more than maximum amount
@@ -178,8 +302,19 @@ pub struct Config {
 	pub memory_limit: usize,
 	/// Call limit.
 	pub call_stack_limit: usize,
-	/// Create contract limit.
-	pub create_contract_limit: Option<usize>,
+	/// Maximum size of deployed code (EIP-170). Code larger than this is
+	/// only ever run as init code, and is not cached by the executor's
+	/// analysis cache.
+	pub max_code_size: Option<usize>,
+	/// Maximum size of init code of create transactions and opcodes
+	/// (EIP-3860).
+	pub max_initcode_size: Option<usize>,
+	/// Maximum total size of the data of the logs emitted by a transaction.
+	/// Reverted frames do not count towards it.
+	pub max_log_data_size: Option<usize>,
+	/// Maximum size of the return data of any frame, including the output
+	/// of the transaction.
+	pub max_return_data_size: Option<usize>,
 	/// Call stipend.
 	pub call_stipend: usize,
 	/// Has delegate call.
@@ -194,10 +329,48 @@ pub struct Config {
 	pub has_bitwise_shifting: bool,
 	/// Has chain ID.
 	pub has_chain_id: bool,
+	/// Has PUSH0 (EIP-3855).
+	pub has_push0: bool,
 	/// Has self balance.
 	pub has_self_balance: bool,
 	/// Has ext code hash.
 	pub has_ext_code_hash: bool,
+	/// Has set code transactions and delegation designators (EIP-7702).
+	pub has_set_code: bool,
+	/// Serve `BLOCKHASH` from the history storage contract (EIP-2935).
+	pub has_block_hash_history: bool,
+	/// Gas paid for AUTH opcode (EIP-3074).
+	pub gas_auth: usize,
+	/// Has AUTH and AUTHCALL (EIP-3074). Ignored without the `auth` feature.
+	pub has_auth: bool,
+	/// Has EVM object format containers (EIP-3540). Ignored without the
+	/// `eof` feature.
+	pub has_eof: bool,
+	/// Execution engine.
+	pub engine: Engine,
+	/// Opcodes allowed to execute.
+	pub opcode_filter: OpcodeFilter,
+	/// Opcode gas costs replacing the fork defaults, indexed by opcode
+	/// byte. The override replaces the whole opcode cost, but memory
+	/// expansion is still charged. Overrides of call, create, `SSTORE` and
+	/// `SELFDESTRUCT` opcodes are ignored, see `Config::with_gas_override`.
+	pub gas_overrides: [Option<usize>; 256],
+	/// Skip the check of transaction nonces against caller nonces, for
+	/// simulations such as `eth_call`.
+	pub disable_nonce_check: bool,
+	/// Skip the upfront balance check of transactions, and do not charge
+	/// callers for gas, for simulations such as `eth_call`.
+	pub disable_balance_check: bool,
+	/// Allow transaction gas prices below the base fee, as geth's `eth_call`
+	/// without a base fee.
+	pub disable_base_fee: bool,
+	/// Exit with `ExitFatal::Strict` on conditions where implementations
+	/// commonly diverge, instead of their usual outcome. Meant for
+	/// differential testing.
+	pub strict: bool,
+	/// Number of top stack items kept in the snapshot the executor records
+	/// of every frame that reverts or fails. `None` records no snapshots.
+	pub fault_snapshot_stack: Option<usize>,
 }
 
 impl Config {
@@ -217,8 +390,19 @@ impl Config {
 			gas_expbyte: 10,
 			gas_transaction_create: 21000,
 			gas_transaction_call: 21000,
+			gas_initcode_word: 0,
 			gas_transaction_zero_data: 4,
 			gas_transaction_non_zero_data: 68,
+			gas_transaction_floor_per_token: None,
+			gas_transaction_authorization: 25000,
+			refund_authorization_existing: 12500,
+			gas_delegate_cold_access: 2600,
+			gas_delegate_warm_access: 100,
+			gas_bn128_add: 500,
+			gas_bn128_mul: 40000,
+			gas_bn128_pairing_base: 100000,
+			gas_bn128_pairing_point: 80000,
+			modexp_eip2565: false,
 			sstore_gas_metering: false,
 			sstore_revert_under_stipend: false,
 			err_on_call_with_more_gas: true,
@@ -228,7 +412,10 @@ impl Config {
 			stack_limit: 1024,
 			memory_limit: usize::max_value(),
 			call_stack_limit: 1024,
-			create_contract_limit: None,
+			max_code_size: None,
+			max_initcode_size: None,
+			max_log_data_size: None,
+			max_return_data_size: None,
 			call_stipend: 2300,
 			has_delegate_call: false,
 			has_create2: false,
@@ -236,8 +423,22 @@ impl Config {
 			has_return_data: false,
 			has_bitwise_shifting: false,
 			has_chain_id: false,
+			has_push0: false,
 			has_self_balance: false,
 			has_ext_code_hash: false,
+			has_set_code: false,
+			has_block_hash_history: false,
+			gas_auth: 3100,
+			has_auth: false,
+			has_eof: false,
+			engine: Engine::Interpreter,
+			opcode_filter: OpcodeFilter::allow_all(),
+			gas_overrides: [None; 256],
+			disable_nonce_check: false,
+			disable_balance_check: false,
+			disable_base_fee: false,
+			strict: false,
+			fault_snapshot_stack: None,
 		}
 	}
 
@@ -257,8 +458,19 @@ impl Config {
 			gas_expbyte: 50,
 			gas_transaction_create: 53000,
 			gas_transaction_call: 21000,
+			gas_initcode_word: 0,
 			gas_transaction_zero_data: 4,
 			gas_transaction_non_zero_data: 16,
+			gas_transaction_floor_per_token: None,
+			gas_transaction_authorization: 25000,
+			refund_authorization_existing: 12500,
+			gas_delegate_cold_access: 2600,
+			gas_delegate_warm_access: 100,
+			gas_bn128_add: 150,
+			gas_bn128_mul: 6000,
+			gas_bn128_pairing_base: 45000,
+			gas_bn128_pairing_point: 34000,
+			modexp_eip2565: false,
 			sstore_gas_metering: true,
 			sstore_revert_under_stipend: true,
 			err_on_call_with_more_gas: false,
@@ -268,7 +480,10 @@ impl Config {
 			stack_limit: 1024,
 			memory_limit: usize::max_value(),
 			call_stack_limit: 1024,
-			create_contract_limit: Some(0x6000),
+			max_code_size: Some(0x6000),
+			max_initcode_size: None,
+			max_log_data_size: None,
+			max_return_data_size: None,
 			call_stipend: 2300,
 			has_delegate_call: true,
 			has_create2: true,
@@ -276,8 +491,143 @@ impl Config {
 			has_return_data: true,
 			has_bitwise_shifting: true,
 			has_chain_id: true,
+			has_push0: false,
 			has_self_balance: true,
 			has_ext_code_hash: true,
+			has_set_code: false,
+			has_block_hash_history: false,
+			gas_auth: 3100,
+			has_auth: false,
+			has_eof: false,
+			engine: Engine::Interpreter,
+			opcode_filter: OpcodeFilter::allow_all(),
+			gas_overrides: [None; 256],
+			disable_nonce_check: false,
+			disable_balance_check: false,
+			disable_base_fee: false,
+			strict: false,
+			fault_snapshot_stack: None,
 		}
 	}
+
+	/// Execute with the given engine.
+	pub fn with_engine(mut self, engine: Engine) -> Self {
+		self.engine = engine;
+		self
+	}
+
+	/// Set the maximum sizes of deployed code and init code, for chains
+	/// raising the mainnet limits.
+	pub fn with_code_size_limits(mut self, max_code_size: Option<usize>, max_initcode_size: Option<usize>) -> Self {
+		self.max_code_size = max_code_size;
+		self.max_initcode_size = max_initcode_size;
+		self
+	}
+
+	/// Set the maximum total log data size of a transaction and return data
+	/// size of a frame, for services persisting logs and outputs.
+	pub fn with_output_limits(mut self, max_log_data_size: Option<usize>, max_return_data_size: Option<usize>) -> Self {
+		self.max_log_data_size = max_log_data_size;
+		self.max_return_data_size = max_return_data_size;
+		self
+	}
+
+	/// Override the gas cost of an opcode.
+	///
+	/// # Panics
+	///
+	/// If the opcode is a call or create opcode, whose cost includes the gas
+	/// forwarded to the new frame, or `SSTORE` or `SELFDESTRUCT`, whose cost
+	/// comes with refunds and, for `SSTORE`, the EIP-2200 stipend check: a
+	/// fixed cost can express neither.
+	pub fn with_gas_override(mut self, opcode: u8, gas: usize) -> Self {
+		assert!(is_gas_overridable(opcode), "gas of opcode {:#04x} cannot be overridden", opcode);
+		self.gas_overrides[opcode as usize] = Some(gas);
+		self
+	}
+
+	/// Gas cost override of the opcode, if any.
+	pub fn gas_override(&self, opcode: Result<Opcode, ExternalOpcode>) -> Option<usize> {
+		let byte = Opcode::byte(opcode);
+		if !is_gas_overridable(byte) {
+			return None
+		}
+		self.gas_overrides[byte as usize]
+	}
+
+	/// Whether the opcode byte can execute in legacy code under this
+	/// config: it is defined by the enabled EIPs and allowed by the opcode
+	/// filter.
+	pub fn supports(&self, opcode: u8) -> bool {
+		if !self.opcode_filter.is_allowed(opcode) {
+			return false
+		}
+
+		match Opcode::parse(opcode) {
+			Ok(Opcode::Invalid) | Err(ExternalOpcode::Other(_)) => false,
+			Ok(Opcode::Push(0)) => self.has_push0,
+			Ok(Opcode::Revert) => self.has_revert,
+			Ok(Opcode::Shl) | Ok(Opcode::Shr) | Ok(Opcode::Sar) => self.has_bitwise_shifting,
+			Err(ExternalOpcode::ReturnDataSize) | Err(ExternalOpcode::ReturnDataCopy) =>
+				self.has_return_data,
+			Err(ExternalOpcode::ChainId) => self.has_chain_id,
+			Err(ExternalOpcode::SelfBalance) => self.has_self_balance,
+			Err(ExternalOpcode::ExtCodeHash) => self.has_ext_code_hash,
+			Err(ExternalOpcode::Create2) => self.has_create2,
+			Err(ExternalOpcode::DelegateCall) => self.has_delegate_call,
+			Err(ExternalOpcode::Auth) | Err(ExternalOpcode::AuthCall) => self.has_auth,
+			_ => true,
+		}
+	}
+
+	/// Numbers of the EIPs enabled by this config, in increasing order,
+	/// derived from its parameters. EIPs this crate does not implement are
+	/// never listed, whatever the fork.
+	pub fn active_eips(&self) -> Vec<u32> {
+		let mut eips = Vec::new();
+		let mut enable = |eip: u32, enabled: bool| if enabled {
+			eips.push(eip);
+		};
+
+		enable(2, self.gas_transaction_create > self.gas_transaction_call);
+		enable(7, self.has_delegate_call);
+		enable(140, self.has_revert);
+		enable(145, self.has_bitwise_shifting);
+		enable(150, self.call_l64_after_gas && !self.err_on_call_with_more_gas);
+		enable(160, self.gas_expbyte >= 50);
+		enable(161, !self.empty_considered_exists);
+		enable(170, self.max_code_size.is_some());
+		enable(211, self.has_return_data);
+		enable(1014, self.has_create2);
+		enable(1052, self.has_ext_code_hash);
+		enable(1108, self.gas_bn128_add <= 150);
+		enable(1344, self.has_chain_id);
+		enable(1706, self.sstore_revert_under_stipend);
+		enable(1884, self.has_self_balance);
+		enable(2028, self.gas_transaction_non_zero_data <= 16);
+		enable(2200, self.sstore_gas_metering && self.sstore_revert_under_stipend);
+		enable(2565, self.modexp_eip2565);
+		enable(2935, self.has_block_hash_history);
+		#[cfg(feature = "auth")]
+		enable(3074, self.has_auth);
+		#[cfg(feature = "eof")]
+		enable(3540, self.has_eof);
+		enable(3855, self.has_push0);
+		enable(3860, self.gas_initcode_word > 0);
+		enable(7623, self.gas_transaction_floor_per_token.is_some());
+		enable(7702, self.has_set_code);
+		eips
+	}
+}
+
+/// Whether the gas cost of the opcode byte can be overridden: every opcode
+/// but the call and create opcodes, `SSTORE` and `SELFDESTRUCT`.
+fn is_gas_overridable(opcode: u8) -> bool {
+	!matches!(
+		Opcode::parse(opcode),
+		Err(ExternalOpcode::Call) | Err(ExternalOpcode::CallCode) | Err(ExternalOpcode::DelegateCall) |
+		Err(ExternalOpcode::StaticCall) | Err(ExternalOpcode::AuthCall) |
+		Err(ExternalOpcode::Create) | Err(ExternalOpcode::Create2) |
+		Err(ExternalOpcode::SStore) | Err(ExternalOpcode::Suicide)
+	)
 }
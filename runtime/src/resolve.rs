@@ -0,0 +1,100 @@
+use alloc::boxed::Box;
+use core::future::Future;
+use core::pin::Pin;
+
+use primitive_types::H160;
+
+use crate::{Bytes, Capture, ExitReason, Handler, MaybeSend, MaybeSync, Resolve, Runtime};
+
+#[cfg(not(feature = "no-send"))]
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+#[cfg(feature = "no-send")]
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Handler whose call and create interrupts are run as child runtimes by
+/// `resolve`. The handler keeps track of its own frames: each
+/// `enter_call` or `enter_create` returning a runtime is matched by an
+/// `exit_call` or `exit_create` once that runtime exits.
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+pub trait InterruptHandler: Handler {
+	/// Enter the frame of a call interrupt. Returns either the result of
+	/// the call, for example for precompiles or failed value transfers, or
+	/// the runtime of the child frame.
+	async fn enter_call(
+		&mut self,
+		interrupt: Self::CallInterrupt,
+	) -> Capture<(ExitReason, Bytes), Runtime>;
+
+	/// Exit the frame of a call whose runtime exited with `reason`. Returns
+	/// the result of the call fed to the parent runtime, and the feedback
+	/// passed to `call_feedback`.
+	async fn exit_call(
+		&mut self,
+		runtime: Runtime,
+		reason: ExitReason,
+	) -> ((ExitReason, Bytes), Self::CallFeedback);
+
+	/// Enter the frame of a create interrupt. Returns either the result of
+	/// the create, or the runtime of the init code.
+	async fn enter_create(
+		&mut self,
+		interrupt: Self::CreateInterrupt,
+	) -> Capture<(ExitReason, Option<H160>, Bytes), Runtime>;
+
+	/// Exit the frame of a create whose runtime exited with `reason`.
+	/// Returns the result of the create fed to the parent runtime, and the
+	/// feedback passed to `create_feedback`.
+	async fn exit_create(
+		&mut self,
+		runtime: Runtime,
+		reason: ExitReason,
+	) -> ((ExitReason, Option<H160>, Bytes), Self::CreateFeedback);
+}
+
+/// Run the runtime until it exits, running the call and create interrupts
+/// of the handler in child runtimes, recursively, and feeding their results
+/// and feedback back.
+pub fn resolve<'a, H>(runtime: &'a mut Runtime, handler: &'a mut H) -> BoxFuture<'a, ExitReason> where
+	H: InterruptHandler + MaybeSend + MaybeSync,
+	H::CallInterrupt: MaybeSend,
+	H::CreateInterrupt: MaybeSend,
+{
+	Box::pin(async move {
+		loop {
+			match runtime.run(handler).await {
+				Capture::Exit(reason) => return reason,
+				Capture::Trap(Resolve::Call(interrupt, resolution)) => {
+					let (reason, return_data) = match handler.enter_call(interrupt).await {
+						Capture::Exit(result) => result,
+						Capture::Trap(mut child) => {
+							let reason = resolve(&mut child, handler).await;
+							let (result, feedback) = handler.exit_call(child, reason).await;
+							if let Err(e) = handler.call_feedback(feedback) {
+								(e.into(), Bytes::new())
+							} else {
+								result
+							}
+						},
+					};
+					resolution.resolve(reason, return_data);
+				},
+				Capture::Trap(Resolve::Create(interrupt, resolution)) => {
+					let (reason, address, return_data) = match handler.enter_create(interrupt).await {
+						Capture::Exit(result) => result,
+						Capture::Trap(mut child) => {
+							let reason = resolve(&mut child, handler).await;
+							let (result, feedback) = handler.exit_create(child, reason).await;
+							if let Err(e) = handler.create_feedback(feedback) {
+								(e.into(), None, Bytes::new())
+							} else {
+								result
+							}
+						},
+					};
+					resolution.resolve(reason, address, return_data);
+				},
+			}
+		}
+	})
+}
@@ -0,0 +1,55 @@
+use primitive_types::U256;
+
+use crate::{Bytes, Machine, OpcodeInfo, StrictViolation};
+
+/// Check the next instruction of the machine for the conditions rejected by
+/// `Config::strict`. EOF code is validated before it runs, and not checked.
+pub fn check(machine: &Machine, return_data: &Bytes) -> Result<(), StrictViolation> {
+	#[cfg(feature = "eof")]
+	{
+		if machine.eof().is_some() {
+			return Ok(())
+		}
+	}
+
+	let position = match machine.position() {
+		Ok(position) => *position,
+		Err(_) => return Ok(()),
+	};
+	let opcode = match machine.code().get(position) {
+		Some(opcode) => *opcode,
+		None => return Ok(()),
+	};
+	let stack = machine.stack();
+
+	if let Some(info) = OpcodeInfo::of(opcode) {
+		if stack.len() < info.inputs as usize {
+			return Err(StrictViolation::StackOutOfRange { position, opcode, len: stack.len() })
+		}
+	}
+
+	let word = |index: usize| stack.peek(index).map(|value| U256::from_big_endian(&value[..])).unwrap_or_default();
+	match opcode {
+		// RETURNDATACOPY
+		0x3e => {
+			let (offset, len) = (word(1), word(2));
+			let available = return_data.len();
+			if offset.checked_add(len).map(|end| end > U256::from(available)).unwrap_or(true) {
+				return Err(StrictViolation::ReturnDataOutOfBounds { position, offset, len, available })
+			}
+		},
+		// JUMP and JUMPI, when taken
+		0x56 | 0x57 if opcode == 0x56 || !word(1).is_zero() => {
+			let destination = word(0);
+			if destination < U256::from(machine.code().len()) {
+				let destination = destination.as_usize();
+				if machine.code()[destination] == 0x5b && !machine.analysis().valids().is_valid(destination) {
+					return Err(StrictViolation::JumpIntoPushData { position, destination })
+				}
+			}
+		},
+		_ => (),
+	}
+
+	Ok(())
+}
@@ -0,0 +1,115 @@
+use alloc::vec::Vec;
+
+use alloy_primitives::{Address, B256};
+use alloy_provider::network::Ethereum;
+use alloy_provider::transport::{TransportError, TransportErrorKind};
+use alloy_rpc_types_eth::BlockId as AlloyBlockId;
+use primitive_types::{H160, H256, U256};
+
+use super::{BlockHeader, BlockId, Provider};
+
+/// `Provider` over an `alloy` provider of an Ethereum network, so that an
+/// existing provider can back a `ProviderBackend`:
+///
+/// ```ignore
+/// let provider = AlloyProvider::new(ProviderBuilder::new().connect_http(url));
+/// let backend = ProviderBackend::pin(Arc::new(provider), block).await?;
+/// ```
+pub struct AlloyProvider<P> {
+	provider: P,
+}
+
+impl<P> AlloyProvider<P> {
+	/// Wrap the given provider.
+	pub fn new(provider: P) -> Self {
+		Self { provider }
+	}
+
+	/// Get the underlying provider.
+	pub fn provider(&self) -> &P {
+		&self.provider
+	}
+
+	/// Unwrap the underlying provider.
+	pub fn into_inner(self) -> P {
+		self.provider
+	}
+}
+
+fn address(address: H160) -> Address {
+	Address::from(address.0)
+}
+
+fn block_id(block: BlockId) -> Result<AlloyBlockId, TransportError> {
+	match block {
+		BlockId::Number(number) if number > U256::from(u64::MAX) =>
+			Err(TransportErrorKind::custom_str("block number does not fit in 64 bits")),
+		BlockId::Number(number) => Ok(AlloyBlockId::number(number.low_u64())),
+		BlockId::Hash(hash) => Ok(AlloyBlockId::hash(B256::from(hash.0))),
+	}
+}
+
+fn u256(value: alloy_primitives::U256) -> U256 {
+	U256::from_big_endian(&value.to_be_bytes::<32>())
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<P: alloy_provider::Provider<Ethereum> + 'static> Provider for AlloyProvider<P> {
+	type Error = TransportError;
+
+	async fn get_chain_id(&self) -> Result<U256, Self::Error> {
+		Ok(U256::from(self.provider.get_chain_id().await?))
+	}
+
+	async fn get_block(&self, block: BlockId) -> Result<Option<BlockHeader>, Self::Error> {
+		// Blocks past the 64-bit range cannot exist.
+		let id = match block_id(block) {
+			Ok(id) => id,
+			Err(_) => return Ok(None),
+		};
+		Ok(self.provider.get_block(id).await?.map(|block| {
+			let header = block.header;
+			// Post-merge blocks carry `prevrandao` in place of the mix hash
+			// and a zero difficulty.
+			let difficulty = if header.difficulty.is_zero() {
+				U256::from_big_endian(header.mix_hash.as_slice())
+			} else {
+				u256(header.difficulty)
+			};
+			BlockHeader {
+				number: U256::from(header.number),
+				hash: H256(header.hash.0),
+				coinbase: H160(header.beneficiary.into_array()),
+				timestamp: U256::from(header.timestamp),
+				difficulty,
+				gas_limit: U256::from(header.gas_limit),
+			}
+		}))
+	}
+
+	async fn get_balance(&self, address: H160, block: BlockId) -> Result<U256, Self::Error> {
+		let balance = self.provider.get_balance(self::address(address))
+			.block_id(block_id(block)?).await?;
+		Ok(u256(balance))
+	}
+
+	async fn get_transaction_count(&self, address: H160, block: BlockId) -> Result<U256, Self::Error> {
+		let nonce = self.provider.get_transaction_count(self::address(address))
+			.block_id(block_id(block)?).await?;
+		Ok(U256::from(nonce))
+	}
+
+	async fn get_code(&self, address: H160, block: BlockId) -> Result<Vec<u8>, Self::Error> {
+		let code = self.provider.get_code_at(self::address(address))
+			.block_id(block_id(block)?).await?;
+		Ok(code.to_vec())
+	}
+
+	async fn get_storage_at(&self, address: H160, index: H256, block: BlockId) -> Result<H256, Self::Error> {
+		let index = alloy_primitives::U256::from_be_bytes(index.0);
+		let value = self.provider.get_storage_at(self::address(address), index)
+			.block_id(block_id(block)?).await?;
+		Ok(H256(value.to_be_bytes::<32>()))
+	}
+}
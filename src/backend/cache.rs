@@ -0,0 +1,227 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use core::future::{Future, poll_fn};
+use core::task::Poll;
+use std::sync::{Arc, Mutex};
+
+use primitive_types::{H160, H256, U256};
+
+use super::{Backend, Basic, WitnessAccount};
+
+/// Default number of reads `warmup` keeps in flight.
+pub const DEFAULT_WARMUP_CONCURRENCY: usize = 16;
+
+/// Backend wrapper caching account reads behind a lock, so a single warmed
+/// cache can be shared by concurrent executors through an `Arc`. Each
+/// simulation runs on its own executor, whose `StateMutator` methods
+/// take `&mut self` for its transaction state; only the backend is shared.
+///
+/// Cached values are never invalidated; call `clear` after the underlying
+/// state changes.
+pub struct CachedBackend<B> {
+	inner: Arc<B>,
+	accounts: Mutex<BTreeMap<H160, WitnessAccount>>,
+}
+
+impl<B> CachedBackend<B> {
+	/// Create a new cache over the given backend.
+	pub fn new(inner: Arc<B>) -> Self {
+		Self {
+			inner,
+			accounts: Mutex::new(BTreeMap::new()),
+		}
+	}
+
+	/// Get the underlying backend.
+	pub fn inner(&self) -> &Arc<B> {
+		&self.inner
+	}
+
+	/// Number of accounts with at least one cached value.
+	pub fn len(&self) -> usize {
+		self.lock().len()
+	}
+
+	/// Whether nothing has been cached.
+	pub fn is_empty(&self) -> bool {
+		self.lock().is_empty()
+	}
+
+	/// Drop every cached value.
+	pub fn clear(&self) {
+		self.lock().clear()
+	}
+
+	fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<H160, WitnessAccount>> {
+		self.accounts.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn cached<T>(&self, address: H160, f: impl FnOnce(&WitnessAccount) -> Option<T>) -> Option<T> {
+		self.lock().get(&address).and_then(f)
+	}
+
+	fn cache(&self, address: H160, f: impl FnOnce(&mut WitnessAccount)) {
+		f(self.lock().entry(address).or_default())
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<B: Backend> Backend for CachedBackend<B> {
+	type Error = B::Error;
+
+	async fn gas_price(&self) -> Result<U256, B::Error> { self.inner.gas_price().await }
+	async fn origin(&self) -> Result<H160, B::Error> { self.inner.origin().await }
+	async fn block_hash(&self, number: U256) -> Result<H256, B::Error> {
+		self.inner.block_hash(number).await
+	}
+	async fn block_number(&self) -> Result<U256, B::Error> { self.inner.block_number().await }
+	async fn block_coinbase(&self) -> Result<H160, B::Error> { self.inner.block_coinbase().await }
+	async fn block_timestamp(&self) -> Result<U256, B::Error> { self.inner.block_timestamp().await }
+	async fn block_difficulty(&self) -> Result<U256, B::Error> { self.inner.block_difficulty().await }
+	async fn block_gas_limit(&self) -> Result<U256, B::Error> { self.inner.block_gas_limit().await }
+	async fn chain_id(&self) -> Result<U256, B::Error> { self.inner.chain_id().await }
+
+	async fn exists(&self, address: H160) -> Result<bool, B::Error> {
+		if let Some(value) = self.cached(address, |a| a.exists) {
+			return Ok(value)
+		}
+		let value = self.inner.exists(address).await?;
+		self.cache(address, |a| a.exists = Some(value));
+		Ok(value)
+	}
+	async fn basic(&self, address: H160) -> Result<Basic, B::Error> {
+		if let Some(value) = self.cached(address, |a| a.basic.clone()) {
+			return Ok(value)
+		}
+		let value = self.inner.basic(address).await?;
+		self.cache(address, |a| a.basic = Some(value.clone()));
+		Ok(value)
+	}
+	async fn code_hash(&self, address: H160) -> Result<H256, B::Error> {
+		if let Some(value) = self.cached(address, |a| a.code_hash) {
+			return Ok(value)
+		}
+		let value = self.inner.code_hash(address).await?;
+		self.cache(address, |a| a.code_hash = Some(value));
+		Ok(value)
+	}
+	async fn code_size(&self, address: H160) -> Result<usize, B::Error> {
+		if let Some(value) = self.cached(address, |a| a.code_size) {
+			return Ok(value)
+		}
+		let value = self.inner.code_size(address).await?;
+		self.cache(address, |a| a.code_size = Some(value));
+		Ok(value)
+	}
+	async fn code(&self, address: H160) -> Result<Vec<u8>, B::Error> {
+		if let Some(value) = self.cached(address, |a| a.code.clone()) {
+			return Ok(value)
+		}
+		let value = self.inner.code(address).await?;
+		self.cache(address, |a| a.code = Some(value.clone()));
+		Ok(value)
+	}
+	async fn storage(&self, address: H160, index: H256) -> Result<H256, B::Error> {
+		if let Some(value) = self.cached(address, |a| a.storage.get(&index).cloned()) {
+			return Ok(value)
+		}
+		let value = self.inner.storage(address, index).await?;
+		self.cache(address, |a| { a.storage.insert(index, value); });
+		Ok(value)
+	}
+	async fn storage_range(
+		&self,
+		address: H160,
+		start: H256,
+		limit: usize,
+	) -> Result<Vec<(H256, H256)>, B::Error> {
+		let values = self.inner.storage_range(address, start, limit).await?;
+		self.cache(address, |a| a.storage.extend(values.iter().cloned()));
+		Ok(values)
+	}
+	async fn account_storage_keys(&self, address: H160) -> Result<Option<Vec<H256>>, B::Error> {
+		self.inner.account_storage_keys(address).await
+	}
+}
+
+/// Read made by `warmup`.
+#[derive(Clone, Copy)]
+enum WarmupRead {
+	Exists(H160),
+	Basic(H160),
+	CodeHash(H160),
+	Code(H160),
+	Storage(H160, H256),
+}
+
+async fn warmup_read<B: Backend>(backend: &CachedBackend<B>, read: WarmupRead) -> Result<(), B::Error> {
+	match read {
+		WarmupRead::Exists(address) => { backend.exists(address).await?; },
+		WarmupRead::Basic(address) => { backend.basic(address).await?; },
+		WarmupRead::CodeHash(address) => { backend.code_hash(address).await?; },
+		WarmupRead::Code(address) => {
+			let size = backend.code(address).await?.len();
+			backend.cache(address, |a| a.code_size = Some(size));
+		},
+		WarmupRead::Storage(address, index) => { backend.storage(address, index).await?; },
+	}
+	Ok(())
+}
+
+/// Fetch the accounts and storage slots of an access list into the cache
+/// before execution: the existence, basic information, code hash and code
+/// of every listed account, and its listed slots. Reads run concurrently on
+/// the calling task, at most `concurrency` at a time, so backends reading
+/// over the network overlap their latency. Values already cached are not
+/// read again. Fails with the first backend error met, leaving the values
+/// read so far cached.
+pub async fn warmup<B: Backend>(
+	backend: &CachedBackend<B>,
+	access_list: &[(H160, Vec<H256>)],
+	concurrency: usize,
+) -> Result<(), B::Error> {
+	let mut accounts = BTreeMap::<H160, BTreeSet<H256>>::new();
+	for (address, keys) in access_list {
+		accounts.entry(*address).or_default().extend(keys.iter().cloned());
+	}
+	let mut reads = accounts.iter().flat_map(|(address, keys)| {
+		let address = *address;
+		IntoIterator::into_iter([
+			WarmupRead::Exists(address),
+			WarmupRead::Basic(address),
+			WarmupRead::CodeHash(address),
+			WarmupRead::Code(address),
+		]).chain(keys.iter().map(move |index| WarmupRead::Storage(address, *index)))
+	});
+
+	let concurrency = concurrency.max(1);
+	let mut in_flight = Vec::with_capacity(concurrency);
+	poll_fn(|context| loop {
+		while in_flight.len() < concurrency {
+			match reads.next() {
+				Some(read) => in_flight.push(Box::pin(warmup_read(backend, read))),
+				None => break,
+			}
+		}
+		if in_flight.is_empty() {
+			return Poll::Ready(Ok(()))
+		}
+
+		let mut completed = false;
+		let mut i = 0;
+		while i < in_flight.len() {
+			match in_flight[i].as_mut().poll(context) {
+				Poll::Ready(Ok(())) => {
+					drop(in_flight.swap_remove(i));
+					completed = true;
+				},
+				Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+				Poll::Pending => i += 1,
+			}
+		}
+		if !completed {
+			return Poll::Pending
+		}
+	}).await
+}
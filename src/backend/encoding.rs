@@ -0,0 +1,169 @@
+use alloc::vec::Vec;
+
+use primitive_types::{H160, H256};
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+
+use super::{Apply, Basic, Log};
+
+/// Owned apply operation, with storage changes collected in a vector.
+pub type OwnedApply = Apply<Vec<(H256, H256)>>;
+
+impl<I: IntoIterator<Item=(H256, H256)>> Apply<I> {
+	/// Collect the storage changes of the operation.
+	pub fn into_owned(self) -> OwnedApply {
+		match self {
+			Apply::Modify { address, basic, code, storage, reset_storage } => Apply::Modify {
+				address, basic, code, reset_storage,
+				storage: storage.into_iter().collect(),
+			},
+			Apply::Delete { address } => Apply::Delete { address },
+		}
+	}
+}
+
+/// Encoded as `[nonce, balance]`, the order of account encodings.
+impl Encodable for Basic {
+	fn rlp_append(&self, stream: &mut RlpStream) {
+		stream.begin_list(2);
+		stream.append(&self.nonce);
+		stream.append(&self.balance);
+	}
+}
+
+impl Decodable for Basic {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 2 {
+			return Err(DecoderError::RlpIncorrectListLen)
+		}
+		Ok(Self { nonce: rlp.val_at(0)?, balance: rlp.val_at(1)? })
+	}
+}
+
+/// Encoded as `[address, topics, data]`, as in receipts.
+impl Encodable for Log {
+	fn rlp_append(&self, stream: &mut RlpStream) {
+		stream.begin_list(3);
+		stream.append(&self.address);
+		stream.append_list(&self.topics);
+		stream.append(&self.data);
+	}
+}
+
+impl Decodable for Log {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 3 {
+			return Err(DecoderError::RlpIncorrectListLen)
+		}
+		Ok(Self {
+			address: rlp.val_at(0)?,
+			topics: list(&rlp.at(1)?)?,
+			data: rlp.val_at(2)?,
+		})
+	}
+}
+
+/// Modifications are encoded as
+/// `[0, address, basic, code, storage, reset_storage]`, with the code as
+/// a list of zero or one item and the storage as a list of
+/// `[index, value]` pairs, and deletions as `[1, address]`.
+impl Encodable for OwnedApply {
+	fn rlp_append(&self, stream: &mut RlpStream) {
+		match self {
+			Apply::Modify { address, basic, code, storage, reset_storage } => {
+				stream.begin_list(6);
+				stream.append(&0u8);
+				stream.append(address);
+				stream.append(basic);
+				match code {
+					Some(code) => { stream.begin_list(1).append(code); },
+					None => { stream.begin_list(0); },
+				}
+				stream.begin_list(storage.len());
+				for (index, value) in storage {
+					stream.begin_list(2).append(index).append(value);
+				}
+				stream.append(&(*reset_storage as u8));
+			},
+			Apply::Delete { address } => {
+				stream.begin_list(2);
+				stream.append(&1u8);
+				stream.append(address);
+			},
+		}
+	}
+}
+
+impl Decodable for OwnedApply {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		match (rlp.val_at::<u8>(0)?, rlp.item_count()?) {
+			(0, 6) => {
+				let code = list::<Vec<u8>>(&rlp.at(3)?)?;
+				if code.len() > 1 {
+					return Err(DecoderError::RlpIncorrectListLen)
+				}
+				let storage = items(&rlp.at(4)?)?.into_iter().map(|pair| {
+					if pair.item_count()? != 2 {
+						return Err(DecoderError::RlpIncorrectListLen)
+					}
+					Ok((pair.val_at(0)?, pair.val_at(1)?))
+				}).collect::<Result<_, _>>()?;
+				Ok(Apply::Modify {
+					address: rlp.val_at(1)?,
+					basic: rlp.val_at(2)?,
+					code: code.into_iter().next(),
+					storage,
+					reset_storage: flag(&rlp.at(5)?)?,
+				})
+			},
+			(1, 2) => Ok(Apply::Delete { address: rlp.val_at::<H160>(1)? }),
+			(0, _) | (1, _) => Err(DecoderError::RlpIncorrectListLen),
+			_ => Err(DecoderError::Custom("unknown apply operation")),
+		}
+	}
+}
+
+/// Encode the changes and logs of an execution as the list
+/// `[operations, logs]`.
+pub fn encode_apply_set(values: &[OwnedApply], logs: &[Log]) -> Vec<u8> {
+	let mut stream = RlpStream::new_list(2);
+	stream.append_list(values);
+	stream.append_list(logs);
+	stream.out()
+}
+
+/// Decode changes and logs encoded by `encode_apply_set`.
+pub fn decode_apply_set(bytes: &[u8]) -> Result<(Vec<OwnedApply>, Vec<Log>), DecoderError> {
+	let rlp = Rlp::new(bytes);
+	let fields = items(&rlp)?;
+	if fields.len() != 2 {
+		return Err(DecoderError::RlpIncorrectListLen)
+	}
+	Ok((list(&fields[0])?, list(&fields[1])?))
+}
+
+/// Items of a list, failing on malformed items where `Rlp::iter` and
+/// `Rlp::item_count` would stop.
+pub(crate) fn items<'a>(rlp: &Rlp<'a>) -> Result<Vec<Rlp<'a>>, DecoderError> {
+	let items = (0..rlp.item_count()?).map(|i| rlp.at(i)).collect::<Result<Vec<_>, _>>()?;
+	let payload = rlp.payload_info()?;
+	if payload.header_len + payload.value_len != rlp.as_raw().len()
+		|| items.iter().map(|item| item.as_raw().len()).sum::<usize>() != payload.value_len
+	{
+		return Err(DecoderError::RlpInconsistentLengthAndData)
+	}
+	Ok(items)
+}
+
+/// Values of a list, as `Rlp::as_list` but failing on malformed items.
+pub(crate) fn list<T: Decodable>(rlp: &Rlp) -> Result<Vec<T>, DecoderError> {
+	items(rlp)?.iter().map(|item| item.as_val()).collect()
+}
+
+/// Flag encoded as the byte `0` or `1`.
+pub(crate) fn flag(rlp: &Rlp) -> Result<bool, DecoderError> {
+	match rlp.as_val::<u8>()? {
+		0 => Ok(false),
+		1 => Ok(true),
+		_ => Err(DecoderError::Custom("invalid flag")),
+	}
+}
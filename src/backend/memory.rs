@@ -1,11 +1,14 @@
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::mem;
 use std::sync::Arc;
 
 use primitive_types::{H160, H256, U256};
-use sha3::{Digest, Keccak256};
 
-use super::{Apply, ApplyBackend, Backend, Basic, Log};
+use crate::Bytes;
+use crate::hasher::{Hasher, Keccak256Hasher};
+use super::{AccountProof, Apply, ApplyBackend, Backend, Basic, Log};
 
 /// Vivinity value of a memory backend.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -42,7 +45,29 @@ pub struct MemoryAccount {
 	/// Full account storage.
 	pub storage: BTreeMap<H256, H256>,
 	/// Account code.
-	pub code: Vec<u8>,
+	pub code: Bytes,
+}
+
+/// Approximate number of bytes used by a memory backend, excluding the
+/// overhead of the maps and allocator.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemoryUsage {
+	/// Addresses, balances and nonces of the accounts.
+	pub accounts: usize,
+	/// Storage keys and values.
+	pub storage: usize,
+	/// Account code, counted once per shared buffer with code
+	/// deduplication.
+	pub code: usize,
+	/// Addresses, topics and data of the logs.
+	pub logs: usize,
+}
+
+impl MemoryUsage {
+	/// Total number of bytes.
+	pub fn total(&self) -> usize {
+		self.accounts + self.storage + self.code + self.logs
+	}
 }
 
 /// Memory backend, storing all state values in a `BTreeMap` in memory.
@@ -50,7 +75,12 @@ pub struct MemoryAccount {
 pub struct MemoryBackend {
 	vicinity: Arc<MemoryVicinity>,
 	state: BTreeMap<H160, MemoryAccount>,
-	logs: Vec<Log>,
+	/// Logs with the number of the block they were applied in.
+	logs: Vec<(U256, Log)>,
+	/// Code shared by the accounts, by hash, with code deduplication.
+	codes: Option<BTreeMap<H256, Bytes>>,
+	/// Hash function of code hashes and proofs.
+	hasher: Arc<dyn Hasher>,
 }
 
 impl MemoryBackend {
@@ -60,77 +90,224 @@ impl MemoryBackend {
 			vicinity,
 			state,
 			logs: Vec::new(),
+			codes: None,
+			hasher: Arc::new(Keccak256Hasher),
 		}
 	}
 
+	/// Hash code and proofs with the given hash function instead of
+	/// Keccak256.
+	pub fn set_hasher(&mut self, hasher: Arc<dyn Hasher>) {
+		self.hasher = hasher;
+		if self.codes.is_some() {
+			self.set_code_deduplication(true);
+		}
+	}
+
+	/// Hash function of code hashes and proofs.
+	pub fn hasher(&self) -> &Arc<dyn Hasher> {
+		&self.hasher
+	}
+
 	/// Get the underlying `BTreeMap` storing the state.
 	pub fn state(&self) -> &BTreeMap<H160, MemoryAccount> {
 		&self.state
 	}
+
+	/// Vicinity of the backend.
+	pub fn vicinity(&self) -> &MemoryVicinity {
+		&self.vicinity
+	}
+
+	/// Replace the vicinity, for example to advance to the next block.
+	pub fn set_vicinity(&mut self, vicinity: Arc<MemoryVicinity>) {
+		self.vicinity = vicinity;
+	}
+
+	/// Logs applied to the backend, oldest first.
+	pub fn logs(&self) -> impl Iterator<Item = &Log> {
+		self.logs.iter().map(|(_, log)| log)
+	}
+
+	/// Logs applied to the backend with the number of the block they were
+	/// applied in, oldest first.
+	pub fn block_logs(&self) -> impl Iterator<Item = (U256, &Log)> {
+		self.logs.iter().map(|(number, log)| (*number, log))
+	}
+
+	/// Share the buffer of identical code between accounts, by code hash.
+	/// Enabling it deduplicates the code already stored.
+	pub fn set_code_deduplication(&mut self, enabled: bool) {
+		if !enabled {
+			self.codes = None;
+			return
+		}
+
+		let mut codes = BTreeMap::new();
+		for account in self.state.values_mut() {
+			account.code = intern(&mut codes, mem::take(&mut account.code), &*self.hasher);
+		}
+		self.codes = Some(codes);
+	}
+
+	/// Drop the history older than the last `retain_blocks` blocks before
+	/// the current one: logs applied in older blocks and older block hashes.
+	/// Also drops zero storage values and, with code deduplication, code no
+	/// longer used by any account. Returns the number of bytes freed, as
+	/// counted by `memory_usage`.
+	pub fn prune(&mut self, retain_blocks: usize) -> usize {
+		let before = self.memory_usage().total();
+
+		let oldest = self.vicinity.block_number.saturating_sub(U256::from(retain_blocks));
+		self.logs.retain(|(number, _)| *number >= oldest);
+		if self.vicinity.block_hashes.len() > retain_blocks {
+			Arc::make_mut(&mut self.vicinity).block_hashes.truncate(retain_blocks);
+		}
+
+		for account in self.state.values_mut() {
+			account.storage.retain(|_, value| *value != H256::default());
+		}
+
+		let hasher = &self.hasher;
+		if let Some(codes) = self.codes.as_mut() {
+			let used = self.state.values()
+				.map(|account| hasher.hash(&account.code))
+				.collect::<BTreeSet<_>>();
+			codes.retain(|hash, _| used.contains(hash));
+		}
+
+		before.saturating_sub(self.memory_usage().total())
+	}
+
+	/// Approximate number of bytes used by the state and logs.
+	pub fn memory_usage(&self) -> MemoryUsage {
+		let mut usage = MemoryUsage::default();
+		for account in self.state.values() {
+			usage.accounts += mem::size_of::<H160>() + mem::size_of::<MemoryAccount>();
+			usage.storage += account.storage.len() * 2 * mem::size_of::<H256>();
+			if self.codes.is_none() {
+				usage.code += account.code.len();
+			}
+		}
+		if let Some(codes) = self.codes.as_ref() {
+			usage.code = codes.values().map(|code| code.len()).sum();
+		}
+		for (_, log) in &self.logs {
+			usage.logs += mem::size_of::<H160>()
+				+ log.topics.len() * mem::size_of::<H256>()
+				+ log.data.len();
+		}
+		usage
+	}
+
+	/// Merkle proof of an account and the given storage slots against the
+	/// state root of the backend, in the `eth_getProof` format.
+	pub fn prove_account(&self, address: H160, slots: &[H256]) -> AccountProof {
+		super::trie::account_proof(&self.state, address, slots, &*self.hasher)
+	}
 }
 
-#[async_trait::async_trait]
+/// Shared buffer of the code in `codes`, keyed by its hash, inserting it
+/// if new.
+fn intern(codes: &mut BTreeMap<H256, Bytes>, code: Bytes, hasher: &dyn Hasher) -> Bytes {
+	codes.entry(hasher.hash(&code)).or_insert(code).clone()
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
 impl Backend for MemoryBackend {
-	async fn gas_price(&self) -> U256 { self.vicinity.gas_price }
-	async fn origin(&self) -> H160 { self.vicinity.origin }
-	async fn block_hash(&self, number: U256) -> H256 {
-		if number >= self.vicinity.block_number ||
+	type Error = Infallible;
+
+	async fn gas_price(&self) -> Result<U256, Infallible> { Ok(self.vicinity.gas_price) }
+	async fn origin(&self) -> Result<H160, Infallible> { Ok(self.vicinity.origin) }
+	async fn block_hash(&self, number: U256) -> Result<H256, Infallible> {
+		Ok(if number >= self.vicinity.block_number ||
 			self.vicinity.block_number - number - U256::one() >= U256::from(self.vicinity.block_hashes.len())
 		{
 			H256::default()
 		} else {
 			let index = (self.vicinity.block_number - number - U256::one()).as_usize();
 			self.vicinity.block_hashes[index]
-		}
+		})
 	}
-	async fn block_number(&self) -> U256 { self.vicinity.block_number }
-	async fn block_coinbase(&self) -> H160 { self.vicinity.block_coinbase }
-	async fn block_timestamp(&self) -> U256 { self.vicinity.block_timestamp }
-	async fn block_difficulty(&self) -> U256 { self.vicinity.block_difficulty }
-	async fn block_gas_limit(&self) -> U256 { self.vicinity.block_gas_limit }
+	async fn block_number(&self) -> Result<U256, Infallible> { Ok(self.vicinity.block_number) }
+	async fn block_coinbase(&self) -> Result<H160, Infallible> { Ok(self.vicinity.block_coinbase) }
+	async fn block_timestamp(&self) -> Result<U256, Infallible> { Ok(self.vicinity.block_timestamp) }
+	async fn block_difficulty(&self) -> Result<U256, Infallible> { Ok(self.vicinity.block_difficulty) }
+	async fn block_gas_limit(&self) -> Result<U256, Infallible> { Ok(self.vicinity.block_gas_limit) }
 
-	async fn chain_id(&self) -> U256 { self.vicinity.chain_id }
+	async fn chain_id(&self) -> Result<U256, Infallible> { Ok(self.vicinity.chain_id) }
 
-	async fn exists(&self, address: H160) -> bool {
-		self.state.contains_key(&address)
+	async fn exists(&self, address: H160) -> Result<bool, Infallible> {
+		Ok(self.state.contains_key(&address))
 	}
 
-	async fn basic(&self, address: H160) -> Basic {
-		self.state.get(&address).map(|a| {
+	async fn basic(&self, address: H160) -> Result<Basic, Infallible> {
+		Ok(self.state.get(&address).map(|a| {
 			Basic { balance: a.balance, nonce: a.nonce }
-		}).unwrap_or_default()
+		}).unwrap_or_default())
 	}
 
-	async fn code_hash(&self, address: H160) -> H256 {
-		self.state.get(&address).map(|v| {
-			H256::from_slice(Keccak256::digest(&v.code).as_slice())
-		}).unwrap_or(H256::from_slice(Keccak256::digest(&[]).as_slice()))
+	async fn code_hash(&self, address: H160) -> Result<H256, Infallible> {
+		Ok(self.state.get(&address).map(|v| {
+			self.hasher.hash(&v.code)
+		}).unwrap_or_default())
 	}
 
-	async fn code_size(&self, address: H160) -> usize {
-		self.state.get(&address).map(|v| v.code.len()).unwrap_or(0)
+	async fn code_size(&self, address: H160) -> Result<usize, Infallible> {
+		Ok(self.state.get(&address).map(|v| v.code.len()).unwrap_or(0))
 	}
 
-	async fn code(&self, address: H160) -> Vec<u8> {
-		self.state.get(&address).map(|v| v.code.clone()).unwrap_or_default()
+	async fn code(&self, address: H160) -> Result<Vec<u8>, Infallible> {
+		Ok(self.state.get(&address).map(|v| v.code.to_vec()).unwrap_or_default())
 	}
 
-	async fn storage(&self, address: H160, index: H256) -> H256 {
-		self.state.get(&address)
+	async fn storage(&self, address: H160, index: H256) -> Result<H256, Infallible> {
+		Ok(self.state.get(&address)
 			.map(|v| v.storage.get(&index).cloned().unwrap_or(H256::default()))
-			.unwrap_or(H256::default())
+			.unwrap_or(H256::default()))
+	}
+
+	async fn storage_range(
+		&self,
+		address: H160,
+		start: H256,
+		limit: usize,
+	) -> Result<Vec<(H256, H256)>, Infallible> {
+		Ok(self.state.get(&address)
+			.map(|v| {
+				v.storage.range(start..)
+					.filter(|(_, value)| **value != H256::default())
+					.take(limit)
+					.map(|(k, v)| (*k, *v))
+					.collect()
+			})
+			.unwrap_or_default())
+	}
+
+	async fn account_storage_keys(&self, address: H160) -> Result<Option<Vec<H256>>, Infallible> {
+		Ok(Some(self.state.get(&address)
+			.map(|v| {
+				v.storage.iter()
+					.filter(|(_, value)| **value != H256::default())
+					.map(|(k, _)| *k)
+					.collect()
+			})
+			.unwrap_or_default()))
 	}
 }
 
-#[async_trait::async_trait]
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
 impl ApplyBackend for MemoryBackend {
 	async fn apply<A, I, L>(
 		&mut self,
 		values: A,
 		logs: L,
 		delete_empty: bool,
-	) where
+	) -> Result<(), Infallible> where
 		A: Sync + Send + IntoIterator<Item=Apply<I>>,
+		A::IntoIter: Send,
 		I: Sync + Send + IntoIterator<Item=(H256, H256)>,
 		L: Sync + Send + IntoIterator<Item=Log>,
 	{
@@ -144,7 +321,10 @@ impl ApplyBackend for MemoryBackend {
 						account.balance = basic.balance;
 						account.nonce = basic.nonce;
 						if let Some(code) = code {
-							account.code = code;
+							account.code = match self.codes.as_mut() {
+								Some(codes) => intern(codes, code.into(), &*self.hasher),
+								None => code.into(),
+							};
 						}
 
 						if reset_storage {
@@ -186,7 +366,9 @@ impl ApplyBackend for MemoryBackend {
 		}
 
 		for log in logs {
-			self.logs.push(log);
+			self.logs.push((self.vicinity.block_number, log));
 		}
+
+		Ok(())
 	}
 }
@@ -2,16 +2,51 @@
 //!
 //! Backends store state information of the VM, and exposes it to runtime.
 
+use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
+use core::fmt::Debug;
 
 use primitive_types::{H160, H256, U256};
 
-pub use self::memory::{MemoryAccount, MemoryBackend, MemoryVicinity};
+use crate::{MaybeSend, MaybeSync};
 
+pub use self::cache::{CachedBackend, DEFAULT_WARMUP_CONCURRENCY, warmup};
+pub use self::encoding::{decode_apply_set, encode_apply_set, OwnedApply};
+pub use self::memory::{MemoryAccount, MemoryBackend, MemoryUsage, MemoryVicinity};
+pub use self::overlay::{BlockOverrides, OverlayAccount, OverlayBackend};
+#[cfg(feature = "provider")]
+pub use self::provider::{BlockHeader, BlockId, Provider, ProviderBackend, ProviderError};
+#[cfg(feature = "alloy")]
+pub use self::alloy::AlloyProvider;
+pub use self::remote::{
+	decode_requests, decode_responses, encode_requests, encode_responses, read_frame, write_frame,
+	RemoteBackend, RemoteError, RemoteHost, RemoteRequest, RemoteResponse, RemoteTransport, DEFAULT_APPLY_CHUNK, MAX_FRAME_LEN,
+};
+pub use self::snapshot::{SnapshotError, SNAPSHOT_MAGIC, SNAPSHOT_VERSION};
+pub use self::trie::{
+	sec_trie_proof, sec_trie_proof_with, sec_trie_root, sec_trie_root_with, state_root, state_root_with,
+	storage_root, storage_root_with, trie_proof, trie_proof_with, trie_root, trie_root_with, verify_proof,
+	verify_proof_with, AccountProof, ProofError, StorageProof,
+};
+pub(crate) use self::trie::{PartialTrie, account_rlp, proof_nodes};
+pub use self::witness::{Recorder, Witness, WitnessAccount, WitnessBackend, WitnessError};
+
+mod cache;
+mod encoding;
 mod memory;
+mod overlay;
+#[cfg(feature = "provider")]
+mod provider;
+#[cfg(feature = "alloy")]
+mod alloy;
+mod remote;
+mod snapshot;
+mod trie;
+mod witness;
 
 /// Basic account information.
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Basic {
 	/// Account balance.
 	pub balance: U256,
@@ -21,6 +56,7 @@ pub struct Basic {
 
 /// Log information.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Log {
 	/// Source address.
 	pub address: H160,
@@ -31,7 +67,8 @@ pub struct Log {
 }
 
 /// Apply state operation.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Apply<I> {
 	/// Modify or create at address.
 	Modify {
@@ -55,52 +92,138 @@ pub enum Apply<I> {
 }
 
 /// EVM backend.
-#[async_trait::async_trait]
-pub trait Backend: Send + Sync + 'static {
+///
+/// Reads return `Self::Error` when the state cannot be accessed, for example
+/// on a failed database or network read. Executors abort with
+/// `ExitFatal::BackendError` on such failures.
+///
+/// With the `no-send` feature, backends need not be `Send` or `Sync`, and
+/// implement this trait with `#[async_trait(?Send)]`.
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+pub trait Backend: MaybeSend + MaybeSync + 'static {
+	/// Error of a failed state read.
+	type Error: Debug + MaybeSend + MaybeSync;
+
 	/// Gas price.
-	async fn gas_price(&self) -> U256;
+	async fn gas_price(&self) -> Result<U256, Self::Error>;
 	/// Origin.
-	async fn origin(&self) -> H160;
+	async fn origin(&self) -> Result<H160, Self::Error>;
 	/// Environmental block hash.
-	async fn block_hash(&self, number: U256) -> H256;
+	async fn block_hash(&self, number: U256) -> Result<H256, Self::Error>;
 	/// Environmental block number.
-	async fn block_number(&self) -> U256;
+	async fn block_number(&self) -> Result<U256, Self::Error>;
 	/// Environmental coinbase.
-	async fn block_coinbase(&self) -> H160;
+	async fn block_coinbase(&self) -> Result<H160, Self::Error>;
 	/// Environmental block timestamp.
-	async fn block_timestamp(&self) -> U256;
+	async fn block_timestamp(&self) -> Result<U256, Self::Error>;
 	/// Environmental block difficulty.
-	async fn block_difficulty(&self) -> U256;
+	async fn block_difficulty(&self) -> Result<U256, Self::Error>;
 	/// Environmental block gas limit.
-	async fn block_gas_limit(&self) -> U256;
+	async fn block_gas_limit(&self) -> Result<U256, Self::Error>;
 	/// Environmental chain ID.
-	async fn chain_id(&self) -> U256;
+	async fn chain_id(&self) -> Result<U256, Self::Error>;
 
 	/// Whether account at address exists.
-	async fn exists(&self, address: H160) -> bool;
+	async fn exists(&self, address: H160) -> Result<bool, Self::Error>;
 	/// Get basic account information.
-	async fn basic(&self, address: H160) -> Basic;
-	/// Get account code hash.
-	async fn code_hash(&self, address: H160) -> H256;
+	async fn basic(&self, address: H160) -> Result<Basic, Self::Error>;
+	/// Get account code hash. Zero if the account does not exist.
+	async fn code_hash(&self, address: H160) -> Result<H256, Self::Error>;
 	/// Get account code size.
-	async fn code_size(&self, address: H160) -> usize;
+	async fn code_size(&self, address: H160) -> Result<usize, Self::Error>;
 	/// Get account code.
-	async fn code(&self, address: H160) -> Vec<u8>;
+	async fn code(&self, address: H160) -> Result<Vec<u8>, Self::Error>;
 	/// Get storage value of address at index.
-	async fn storage(&self, address: H160, index: H256) -> H256;
+	async fn storage(&self, address: H160, index: H256) -> Result<H256, Self::Error>;
+	/// Get up to `limit` non-zero storage values of address, in index order,
+	/// starting at index `start`. Backends unable to iterate storage return
+	/// nothing.
+	async fn storage_range(
+		&self,
+		_address: H160,
+		_start: H256,
+		_limit: usize,
+	) -> Result<Vec<(H256, H256)>, Self::Error> {
+		Ok(Vec::new())
+	}
+	/// Get the indexes of all non-zero storage values of address, in index
+	/// order, or `None` if the backend cannot list them.
+	async fn account_storage_keys(&self, _address: H160) -> Result<Option<Vec<H256>>, Self::Error> {
+		Ok(None)
+	}
+}
+
+/// Storage range of `base` with `pending` values applied over it. Zero
+/// pending values remove the index, and `reset` ignores `base` entirely.
+pub(crate) async fn merged_storage_range<B: Backend>(
+	base: &B,
+	address: H160,
+	pending: &BTreeMap<H256, H256>,
+	reset: bool,
+	start: H256,
+	limit: usize,
+) -> Result<Vec<(H256, H256)>, B::Error> {
+	let mut fetched = BTreeMap::new();
+	let mut next = Some(start);
+	let mut exhausted = reset || limit == 0;
+
+	loop {
+		// Pending values past the last fetched index may precede base values
+		// not fetched yet.
+		let bound = fetched.keys().next_back().cloned();
+		let mut merged = fetched.clone();
+		for (index, value) in pending.range(start..) {
+			if exhausted || bound.map(|bound| *index <= bound).unwrap_or(false) {
+				merged.insert(*index, *value);
+			}
+		}
+		let merged = merged.into_iter()
+			.filter(|(_, value)| *value != H256::default())
+			.take(limit)
+			.collect::<Vec<_>>();
+
+		let cursor = match next {
+			Some(cursor) if !exhausted && merged.len() < limit => cursor,
+			_ => return Ok(merged),
+		};
+
+		let batch = base.storage_range(address, cursor, limit).await?;
+		exhausted = batch.len() < limit;
+		for (index, value) in batch {
+			let (following, overflow) = U256::from_big_endian(index.as_bytes())
+				.overflowing_add(U256::one());
+			next = if overflow {
+				None
+			} else {
+				let mut following_index = H256::default();
+				following.to_big_endian(following_index.as_bytes_mut());
+				Some(following_index)
+			};
+			fetched.insert(index, value);
+		}
+		if next.is_none() {
+			exhausted = true;
+		}
+	}
 }
 
 /// EVM backend that can apply changes.
-#[async_trait::async_trait]
-pub trait ApplyBackend {
-	/// Apply given values and logs at backend.
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+pub trait ApplyBackend: Backend {
+	/// Apply given values and logs at backend. Changes may be partially
+	/// applied when an error is returned. With `delete_empty`, modified
+	/// accounts left empty are deleted; the changes of `StackExecutor`
+	/// already delete touched empty accounts per EIP-161.
 	async fn apply<A, I, L>(
 		&mut self,
 		values: A,
 		logs: L,
 		delete_empty: bool,
-	) where
+	) -> Result<(), Self::Error> where
 		A: Sync + Send + IntoIterator<Item=Apply<I>>,
+		A::IntoIter: Send,
 		I: Sync + Send + IntoIterator<Item=(H256, H256)>,
 		L: Sync + Send + IntoIterator<Item=Log>;
 }
@@ -0,0 +1,307 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use std::sync::Arc;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::hasher::{Hasher, Keccak256Hasher};
+use super::{Apply, ApplyBackend, Backend, Basic, Log, merged_storage_range};
+
+/// Block environment values replacing those of the underlying backend.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BlockOverrides {
+	/// Block number.
+	pub number: Option<U256>,
+	/// Coinbase.
+	pub coinbase: Option<H160>,
+	/// Block timestamp.
+	pub timestamp: Option<U256>,
+	/// Block difficulty.
+	pub difficulty: Option<U256>,
+	/// Block gas limit.
+	pub gas_limit: Option<U256>,
+}
+
+/// Account modified in an overlay.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OverlayAccount {
+	/// Basic account information.
+	pub basic: Basic,
+	/// Code. `None` means the code of the underlying backend.
+	pub code: Option<Vec<u8>>,
+	/// Modified storage values.
+	pub storage: BTreeMap<H256, H256>,
+	/// Whether storage of the underlying backend is ignored.
+	pub reset_storage: bool,
+}
+
+/// Backend layering applied changes over another backend, without
+/// modifying it.
+#[derive(Clone, Debug)]
+pub struct OverlayBackend<B> {
+	inner: Arc<B>,
+	overrides: BlockOverrides,
+	state: BTreeMap<H160, Option<OverlayAccount>>,
+	logs: Vec<Log>,
+	hasher: Arc<dyn Hasher>,
+}
+
+impl<B: Backend> OverlayBackend<B> {
+	/// Create a new overlay over the given backend.
+	pub fn new(inner: Arc<B>, overrides: BlockOverrides) -> Self {
+		Self {
+			inner,
+			overrides,
+			state: BTreeMap::new(),
+			logs: Vec::new(),
+			hasher: Arc::new(Keccak256Hasher),
+		}
+	}
+
+	/// Hash the code of modified accounts with the given hash function
+	/// instead of Keccak256. It should be the hash function of the
+	/// underlying backend.
+	pub fn set_hasher(&mut self, hasher: Arc<dyn Hasher>) {
+		self.hasher = hasher;
+	}
+
+	/// Hash function of the code of modified accounts.
+	pub fn hasher(&self) -> &Arc<dyn Hasher> {
+		&self.hasher
+	}
+
+	/// Modified accounts. `None` means the account was deleted.
+	pub fn state(&self) -> &BTreeMap<H160, Option<OverlayAccount>> {
+		&self.state
+	}
+
+	/// Logs applied so far.
+	pub fn logs(&self) -> &[Log] {
+		&self.logs
+	}
+
+	/// Changes of the overlay as a list of applies to the underlying backend.
+	pub fn diff(&self) -> Vec<Apply<BTreeMap<H256, H256>>> {
+		self.state.iter().map(|(address, account)| match account {
+			Some(account) => Apply::Modify {
+				address: *address,
+				basic: account.basic.clone(),
+				code: account.code.clone(),
+				storage: account.storage.clone(),
+				reset_storage: account.reset_storage,
+			},
+			None => Apply::Delete { address: *address },
+		}).collect()
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<B: Backend> Backend for OverlayBackend<B> {
+	type Error = B::Error;
+
+	async fn gas_price(&self) -> Result<U256, B::Error> { self.inner.gas_price().await }
+	async fn origin(&self) -> Result<H160, B::Error> { self.inner.origin().await }
+	async fn block_hash(&self, number: U256) -> Result<H256, B::Error> {
+		self.inner.block_hash(number).await
+	}
+	async fn block_number(&self) -> Result<U256, B::Error> {
+		match self.overrides.number {
+			Some(number) => Ok(number),
+			None => self.inner.block_number().await,
+		}
+	}
+	async fn block_coinbase(&self) -> Result<H160, B::Error> {
+		match self.overrides.coinbase {
+			Some(coinbase) => Ok(coinbase),
+			None => self.inner.block_coinbase().await,
+		}
+	}
+	async fn block_timestamp(&self) -> Result<U256, B::Error> {
+		match self.overrides.timestamp {
+			Some(timestamp) => Ok(timestamp),
+			None => self.inner.block_timestamp().await,
+		}
+	}
+	async fn block_difficulty(&self) -> Result<U256, B::Error> {
+		match self.overrides.difficulty {
+			Some(difficulty) => Ok(difficulty),
+			None => self.inner.block_difficulty().await,
+		}
+	}
+	async fn block_gas_limit(&self) -> Result<U256, B::Error> {
+		match self.overrides.gas_limit {
+			Some(gas_limit) => Ok(gas_limit),
+			None => self.inner.block_gas_limit().await,
+		}
+	}
+	async fn chain_id(&self) -> Result<U256, B::Error> { self.inner.chain_id().await }
+
+	async fn exists(&self, address: H160) -> Result<bool, B::Error> {
+		match self.state.get(&address) {
+			Some(account) => Ok(account.is_some()),
+			None => self.inner.exists(address).await,
+		}
+	}
+
+	async fn basic(&self, address: H160) -> Result<Basic, B::Error> {
+		match self.state.get(&address) {
+			Some(account) => Ok(account.as_ref().map(|a| a.basic.clone()).unwrap_or_default()),
+			None => self.inner.basic(address).await,
+		}
+	}
+
+	async fn code_hash(&self, address: H160) -> Result<H256, B::Error> {
+		match self.state.get(&address) {
+			Some(Some(OverlayAccount { code: None, .. })) | None =>
+				self.inner.code_hash(address).await,
+			Some(None) => Ok(H256::default()),
+			Some(_) => Ok(self.hasher.hash(&self.code(address).await?)),
+		}
+	}
+
+	async fn code_size(&self, address: H160) -> Result<usize, B::Error> {
+		match self.state.get(&address) {
+			Some(Some(OverlayAccount { code: None, .. })) | None =>
+				self.inner.code_size(address).await,
+			Some(_) => Ok(self.code(address).await?.len()),
+		}
+	}
+
+	async fn code(&self, address: H160) -> Result<Vec<u8>, B::Error> {
+		match self.state.get(&address) {
+			Some(Some(OverlayAccount { code: Some(code), .. })) => Ok(code.clone()),
+			Some(None) => Ok(Vec::new()),
+			Some(Some(OverlayAccount { code: None, .. })) | None => self.inner.code(address).await,
+		}
+	}
+
+	async fn storage(&self, address: H160, index: H256) -> Result<H256, B::Error> {
+		match self.state.get(&address) {
+			Some(Some(account)) => match account.storage.get(&index) {
+				Some(value) => Ok(*value),
+				None if account.reset_storage => Ok(H256::default()),
+				None => self.inner.storage(address, index).await,
+			},
+			Some(None) => Ok(H256::default()),
+			None => self.inner.storage(address, index).await,
+		}
+	}
+
+	async fn storage_range(
+		&self,
+		address: H160,
+		start: H256,
+		limit: usize,
+	) -> Result<Vec<(H256, H256)>, B::Error> {
+		match self.state.get(&address) {
+			Some(Some(account)) => merged_storage_range(
+				&*self.inner, address, &account.storage, account.reset_storage, start, limit,
+			).await,
+			Some(None) => Ok(Vec::new()),
+			None => self.inner.storage_range(address, start, limit).await,
+		}
+	}
+
+	async fn account_storage_keys(&self, address: H160) -> Result<Option<Vec<H256>>, B::Error> {
+		match self.state.get(&address) {
+			Some(Some(account)) => {
+				let mut keys = if account.reset_storage {
+					BTreeSet::new()
+				} else {
+					match self.inner.account_storage_keys(address).await? {
+						Some(keys) => keys.into_iter().collect(),
+						None => return Ok(None),
+					}
+				};
+				for (index, value) in &account.storage {
+					if *value == H256::default() {
+						keys.remove(index);
+					} else {
+						keys.insert(*index);
+					}
+				}
+				Ok(Some(keys.into_iter().collect()))
+			},
+			Some(None) => Ok(Some(Vec::new())),
+			None => self.inner.account_storage_keys(address).await,
+		}
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<B: Backend> ApplyBackend for OverlayBackend<B> {
+	async fn apply<A, I, L>(
+		&mut self,
+		values: A,
+		logs: L,
+		delete_empty: bool,
+	) -> Result<(), B::Error> where
+		A: Sync + Send + IntoIterator<Item=Apply<I>>,
+		A::IntoIter: Send,
+		I: Sync + Send + IntoIterator<Item=(H256, H256)>,
+		L: Sync + Send + IntoIterator<Item=Log>,
+	{
+		let values = values.into_iter().collect::<Vec<_>>();
+		for apply in values {
+			match apply {
+				Apply::Modify {
+					address, basic, code, storage, reset_storage,
+				} => {
+					let mut account = match self.state.remove(&address) {
+						Some(Some(account)) => account,
+						Some(None) => OverlayAccount {
+							code: Some(Vec::new()),
+							reset_storage: true,
+							..Default::default()
+						},
+						None => OverlayAccount::default(),
+					};
+					account.basic = basic;
+					if code.is_some() {
+						account.code = code;
+					}
+					if reset_storage {
+						account.storage = BTreeMap::new();
+						account.reset_storage = true;
+					}
+					for (index, value) in storage {
+						account.storage.insert(index, value);
+					}
+
+					let code_size = match account.code.as_ref() {
+						Some(code) => code.len(),
+						None => match self.inner.code_size(address).await {
+							Ok(code_size) => code_size,
+							Err(e) => {
+								self.state.insert(address, Some(account));
+								return Err(e)
+							},
+						},
+					};
+					let is_empty = account.basic.balance == U256::zero() &&
+						account.basic.nonce == U256::zero() &&
+						code_size == 0;
+
+					if is_empty && delete_empty {
+						self.state.insert(address, None);
+					} else {
+						self.state.insert(address, Some(account));
+					}
+				},
+				Apply::Delete {
+					address,
+				} => {
+					self.state.insert(address, None);
+				},
+			}
+		}
+
+		for log in logs {
+			self.logs.push(log);
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,191 @@
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use std::sync::Arc;
+
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+use super::{Backend, Basic, CachedBackend};
+use crate::{MaybeSend, MaybeSync};
+
+/// Block selector of provider reads.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BlockId {
+	/// Block with the given number.
+	Number(U256),
+	/// Block with the given hash.
+	Hash(H256),
+}
+
+/// Header fields of a block read from a provider.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BlockHeader {
+	/// Block number.
+	pub number: U256,
+	/// Block hash.
+	pub hash: H256,
+	/// Coinbase.
+	pub coinbase: H160,
+	/// Block timestamp.
+	pub timestamp: U256,
+	/// Block difficulty, or `prevrandao` after the merge.
+	pub difficulty: U256,
+	/// Block gas limit.
+	pub gas_limit: U256,
+}
+
+/// State reads of a JSON-RPC node, as exposed by the `Provider` types of
+/// `alloy` and `ethers`. Each method maps to one `eth_*` call, so an
+/// implementation for those providers only converts types.
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+pub trait Provider: MaybeSend + MaybeSync + 'static {
+	/// Error of a failed request.
+	type Error: Debug + MaybeSend + MaybeSync;
+
+	/// `eth_chainId`.
+	async fn get_chain_id(&self) -> Result<U256, Self::Error>;
+	/// `eth_getBlockByNumber` or `eth_getBlockByHash`, `None` if the
+	/// block is unknown.
+	async fn get_block(&self, block: BlockId) -> Result<Option<BlockHeader>, Self::Error>;
+	/// `eth_getBalance`.
+	async fn get_balance(&self, address: H160, block: BlockId) -> Result<U256, Self::Error>;
+	/// `eth_getTransactionCount`.
+	async fn get_transaction_count(&self, address: H160, block: BlockId) -> Result<U256, Self::Error>;
+	/// `eth_getCode`.
+	async fn get_code(&self, address: H160, block: BlockId) -> Result<Vec<u8>, Self::Error>;
+	/// `eth_getStorageAt`.
+	async fn get_storage_at(&self, address: H160, index: H256, block: BlockId) -> Result<H256, Self::Error>;
+}
+
+/// Error of a `ProviderBackend`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProviderError<E> {
+	/// The provider request failed.
+	Provider(E),
+	/// The block to pin is unknown to the provider.
+	UnknownBlock(BlockId),
+}
+
+impl<E> From<E> for ProviderError<E> {
+	fn from(error: E) -> Self {
+		ProviderError::Provider(error)
+	}
+}
+
+/// Backend reading state from a provider, pinned to a single block.
+///
+/// The block is resolved once, and every state read then targets it by
+/// hash, so that an execution sees consistent state even as the chain
+/// advances or reorganizes. The environment is the pinned block's header;
+/// wrap the backend in an `OverlayBackend` to execute in a following block.
+///
+/// JSON-RPC does not distinguish empty accounts from missing ones: both
+/// are reported as not existing.
+pub struct ProviderBackend<P: Provider> {
+	provider: Arc<P>,
+	header: BlockHeader,
+	chain_id: U256,
+	origin: H160,
+	gas_price: U256,
+}
+
+impl<P: Provider> ProviderBackend<P> {
+	/// Pin the backend to the given block of the provider.
+	pub async fn pin(provider: Arc<P>, block: BlockId) -> Result<Self, ProviderError<P::Error>> {
+		let header = provider.get_block(block).await?
+			.ok_or(ProviderError::UnknownBlock(block))?;
+		let chain_id = provider.get_chain_id().await?;
+		Ok(Self {
+			provider,
+			header,
+			chain_id,
+			origin: H160::default(),
+			gas_price: U256::zero(),
+		})
+	}
+
+	/// Set the transaction origin.
+	pub fn with_origin(mut self, origin: H160) -> Self {
+		self.origin = origin;
+		self
+	}
+
+	/// Set the transaction gas price.
+	pub fn with_gas_price(mut self, gas_price: U256) -> Self {
+		self.gas_price = gas_price;
+		self
+	}
+
+	/// Wrap the backend in a cache. Pinned state never changes, so cached
+	/// values never need invalidating.
+	pub fn cached(self) -> CachedBackend<Self> {
+		CachedBackend::new(Arc::new(self))
+	}
+
+	/// Header of the pinned block.
+	pub fn header(&self) -> &BlockHeader {
+		&self.header
+	}
+
+	/// Get the underlying provider.
+	pub fn provider(&self) -> &Arc<P> {
+		&self.provider
+	}
+
+	fn block(&self) -> BlockId {
+		BlockId::Hash(self.header.hash)
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<P: Provider> Backend for ProviderBackend<P> {
+	type Error = ProviderError<P::Error>;
+
+	async fn gas_price(&self) -> Result<U256, Self::Error> { Ok(self.gas_price) }
+	async fn origin(&self) -> Result<H160, Self::Error> { Ok(self.origin) }
+	async fn block_hash(&self, number: U256) -> Result<H256, Self::Error> {
+		if number > self.header.number {
+			return Ok(H256::default())
+		}
+		if number == self.header.number {
+			return Ok(self.header.hash)
+		}
+		Ok(self.provider.get_block(BlockId::Number(number)).await?
+			.map(|header| header.hash)
+			.unwrap_or_default())
+	}
+	async fn block_number(&self) -> Result<U256, Self::Error> { Ok(self.header.number) }
+	async fn block_coinbase(&self) -> Result<H160, Self::Error> { Ok(self.header.coinbase) }
+	async fn block_timestamp(&self) -> Result<U256, Self::Error> { Ok(self.header.timestamp) }
+	async fn block_difficulty(&self) -> Result<U256, Self::Error> { Ok(self.header.difficulty) }
+	async fn block_gas_limit(&self) -> Result<U256, Self::Error> { Ok(self.header.gas_limit) }
+	async fn chain_id(&self) -> Result<U256, Self::Error> { Ok(self.chain_id) }
+
+	async fn exists(&self, address: H160) -> Result<bool, Self::Error> {
+		let basic = self.basic(address).await?;
+		Ok(basic.balance != U256::zero() || basic.nonce != U256::zero() || self.code_size(address).await? != 0)
+	}
+	async fn basic(&self, address: H160) -> Result<Basic, Self::Error> {
+		Ok(Basic {
+			balance: self.provider.get_balance(address, self.block()).await?,
+			nonce: self.provider.get_transaction_count(address, self.block()).await?,
+		})
+	}
+	async fn code_hash(&self, address: H160) -> Result<H256, Self::Error> {
+		if !self.exists(address).await? {
+			return Ok(H256::default())
+		}
+		Ok(H256::from_slice(Keccak256::digest(&self.code(address).await?).as_slice()))
+	}
+	async fn code_size(&self, address: H160) -> Result<usize, Self::Error> {
+		Ok(self.code(address).await?.len())
+	}
+	async fn code(&self, address: H160) -> Result<Vec<u8>, Self::Error> {
+		Ok(self.provider.get_code(address, self.block()).await?)
+	}
+	async fn storage(&self, address: H160, index: H256) -> Result<H256, Self::Error> {
+		Ok(self.provider.get_storage_at(address, index, self.block()).await?)
+	}
+}
@@ -0,0 +1,567 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt::Debug;
+use std::io::{self, Read, Write};
+
+use primitive_types::{H160, H256, U256};
+use rlp::{Rlp, RlpStream};
+
+use super::{Apply, ApplyBackend, Backend, Basic, Log, OwnedApply};
+use super::encoding::{flag, items, list};
+use crate::{MaybeSend, MaybeSync};
+
+/// Default number of apply operations sent per message.
+pub const DEFAULT_APPLY_CHUNK: usize = 1024;
+/// Maximum length of a framed message, 64 MiB.
+pub const MAX_FRAME_LEN: usize = 64 << 20;
+
+/// Request of the remote backend protocol.
+///
+/// A message is the RLP list of a batch of requests, each the list of its
+/// tag followed by its arguments. Tags follow the order of the variants,
+/// from `0` for `GasPrice` to `17` for `Apply`. Flags are the bytes `0` and
+/// `1`, and options lists of zero or one item. `Apply` arguments are the
+/// index of the chunk, whether it is the last, the list of operations, the
+/// list of logs, and `delete_empty`, with operations and logs in their
+/// `Encodable` encoding.
+#[derive(Clone, Debug)]
+pub enum RemoteRequest {
+	/// `Backend::gas_price`.
+	GasPrice,
+	/// `Backend::origin`.
+	Origin,
+	/// `Backend::block_hash`.
+	BlockHash(U256),
+	/// `Backend::block_number`.
+	BlockNumber,
+	/// `Backend::block_coinbase`.
+	BlockCoinbase,
+	/// `Backend::block_timestamp`.
+	BlockTimestamp,
+	/// `Backend::block_difficulty`.
+	BlockDifficulty,
+	/// `Backend::block_gas_limit`.
+	BlockGasLimit,
+	/// `Backend::chain_id`.
+	ChainId,
+	/// `Backend::exists`.
+	Exists(H160),
+	/// `Backend::basic`.
+	Basic(H160),
+	/// `Backend::code_hash`.
+	CodeHash(H160),
+	/// `Backend::code_size`.
+	CodeSize(H160),
+	/// `Backend::code`.
+	Code(H160),
+	/// `Backend::storage`.
+	Storage(H160, H256),
+	/// `Backend::storage_range`.
+	StorageRange(H160, H256, usize),
+	/// `Backend::account_storage_keys`.
+	AccountStorageKeys(H160),
+	/// A chunk of `ApplyBackend::apply`. The host buffers chunks, and
+	/// applies them all with the last one.
+	Apply {
+		/// Index of the chunk, from `0` for the first of an apply.
+		index: usize,
+		/// Whether this is the last chunk of the apply.
+		last: bool,
+		/// Operations of the chunk.
+		values: Vec<OwnedApply>,
+		/// Logs, sent with the last chunk.
+		logs: Vec<Log>,
+		/// Whether modified accounts left empty are deleted.
+		delete_empty: bool,
+	},
+}
+
+/// Response of the remote backend protocol.
+///
+/// A message is the RLP list of the responses to a batch, in request
+/// order, each the list of its tag followed by its value. Tags follow the
+/// order of the variants. A message holding a single `Error` answers a
+/// batch that could not be decoded.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RemoteResponse {
+	/// A number.
+	Quantity(U256),
+	/// An address.
+	Address(H160),
+	/// A word.
+	Word(H256),
+	/// A flag.
+	Bool(bool),
+	/// Basic account information.
+	Basic(Basic),
+	/// Code.
+	Bytes(Vec<u8>),
+	/// A size.
+	Size(usize),
+	/// Storage values.
+	Range(Vec<(H256, H256)>),
+	/// Storage indexes, if the host can list them.
+	Keys(Option<Vec<H256>>),
+	/// The apply chunk was received, and with the last chunk, the apply
+	/// was applied.
+	Applied,
+	/// The host failed to answer the request.
+	Error(String),
+}
+
+/// Error of a `RemoteBackend`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RemoteError<E> {
+	/// The transport failed.
+	Transport(E),
+	/// The response message is malformed.
+	InvalidEncoding,
+	/// The response does not match the request.
+	UnexpectedResponse,
+	/// The host failed to answer the request.
+	Host(String),
+}
+
+/// Transport of remote backend messages, such as a socket, a pipe or the
+/// bytes field of an RPC. Streams frame messages with `write_frame` and
+/// `read_frame`.
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+pub trait RemoteTransport: MaybeSend + MaybeSync + 'static {
+	/// Error of a failed exchange.
+	type Error: Debug + MaybeSend + MaybeSync;
+
+	/// Send a request message and wait for the response message.
+	async fn round_trip(&self, request: Vec<u8>) -> Result<Vec<u8>, Self::Error>;
+}
+
+/// Write a message to a stream, prefixed with its length as four
+/// big-endian bytes. Messages are at most `MAX_FRAME_LEN` bytes.
+pub fn write_frame<W: Write>(writer: &mut W, message: &[u8]) -> io::Result<()> {
+	let len = u32::try_from(message.len()).ok()
+		.filter(|len| *len as usize <= MAX_FRAME_LEN)
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "message too long"))?;
+	writer.write_all(&len.to_be_bytes())?;
+	writer.write_all(message)?;
+	writer.flush()
+}
+
+/// Read a message written by `write_frame` from a stream. Lengths above
+/// `MAX_FRAME_LEN` fail with `io::ErrorKind::InvalidData`, and the message
+/// buffer grows as bytes arrive rather than by the announced length.
+pub fn read_frame<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+	let mut len = [0u8; 4];
+	reader.read_exact(&mut len)?;
+	let len = u32::from_be_bytes(len) as usize;
+	if len > MAX_FRAME_LEN {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "message too long"))
+	}
+	let mut message = Vec::new();
+	reader.take(len as u64).read_to_end(&mut message)?;
+	if message.len() != len {
+		return Err(io::ErrorKind::UnexpectedEof.into())
+	}
+	Ok(message)
+}
+
+/// Backend whose state lives in another process, reached through a
+/// transport. Every read is a round trip; `batch` sends many requests in
+/// one, and applies are streamed in chunks of `apply_chunk` operations,
+/// applied by the host once the last chunk is received.
+pub struct RemoteBackend<T> {
+	transport: T,
+	apply_chunk: usize,
+}
+
+impl<T: RemoteTransport> RemoteBackend<T> {
+	/// Create a new backend over the given transport.
+	pub fn new(transport: T) -> Self {
+		Self { transport, apply_chunk: DEFAULT_APPLY_CHUNK }
+	}
+
+	/// Set the number of apply operations sent per message.
+	pub fn with_apply_chunk(mut self, apply_chunk: usize) -> Self {
+		self.apply_chunk = core::cmp::max(apply_chunk, 1);
+		self
+	}
+
+	/// Get the underlying transport.
+	pub fn transport(&self) -> &T {
+		&self.transport
+	}
+
+	/// Send the requests in a single message. Host failures of single
+	/// requests are returned as `RemoteResponse::Error`.
+	pub async fn batch(&self, requests: &[RemoteRequest]) -> Result<Vec<RemoteResponse>, RemoteError<T::Error>> {
+		let response = self.transport.round_trip(encode_requests(requests)).await
+			.map_err(RemoteError::Transport)?;
+		let responses = decode_responses(&response).map_err(|_| RemoteError::InvalidEncoding)?;
+		if responses.len() == requests.len() {
+			return Ok(responses)
+		}
+		match responses.as_slice() {
+			[RemoteResponse::Error(message)] => Err(RemoteError::Host(message.clone())),
+			_ => Err(RemoteError::UnexpectedResponse),
+		}
+	}
+
+	async fn request(&self, request: RemoteRequest) -> Result<RemoteResponse, RemoteError<T::Error>> {
+		match self.batch(&[request]).await?.pop() {
+			Some(RemoteResponse::Error(message)) => Err(RemoteError::Host(message)),
+			Some(response) => Ok(response),
+			None => Err(RemoteError::UnexpectedResponse),
+		}
+	}
+}
+
+macro_rules! expect {
+	($response:expr, $variant:ident) => {
+		match $response {
+			RemoteResponse::$variant(value) => Ok(value),
+			_ => Err(RemoteError::UnexpectedResponse),
+		}
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<T: RemoteTransport> Backend for RemoteBackend<T> {
+	type Error = RemoteError<T::Error>;
+
+	async fn gas_price(&self) -> Result<U256, Self::Error> {
+		expect!(self.request(RemoteRequest::GasPrice).await?, Quantity)
+	}
+	async fn origin(&self) -> Result<H160, Self::Error> {
+		expect!(self.request(RemoteRequest::Origin).await?, Address)
+	}
+	async fn block_hash(&self, number: U256) -> Result<H256, Self::Error> {
+		expect!(self.request(RemoteRequest::BlockHash(number)).await?, Word)
+	}
+	async fn block_number(&self) -> Result<U256, Self::Error> {
+		expect!(self.request(RemoteRequest::BlockNumber).await?, Quantity)
+	}
+	async fn block_coinbase(&self) -> Result<H160, Self::Error> {
+		expect!(self.request(RemoteRequest::BlockCoinbase).await?, Address)
+	}
+	async fn block_timestamp(&self) -> Result<U256, Self::Error> {
+		expect!(self.request(RemoteRequest::BlockTimestamp).await?, Quantity)
+	}
+	async fn block_difficulty(&self) -> Result<U256, Self::Error> {
+		expect!(self.request(RemoteRequest::BlockDifficulty).await?, Quantity)
+	}
+	async fn block_gas_limit(&self) -> Result<U256, Self::Error> {
+		expect!(self.request(RemoteRequest::BlockGasLimit).await?, Quantity)
+	}
+	async fn chain_id(&self) -> Result<U256, Self::Error> {
+		expect!(self.request(RemoteRequest::ChainId).await?, Quantity)
+	}
+
+	async fn exists(&self, address: H160) -> Result<bool, Self::Error> {
+		expect!(self.request(RemoteRequest::Exists(address)).await?, Bool)
+	}
+	async fn basic(&self, address: H160) -> Result<Basic, Self::Error> {
+		expect!(self.request(RemoteRequest::Basic(address)).await?, Basic)
+	}
+	async fn code_hash(&self, address: H160) -> Result<H256, Self::Error> {
+		expect!(self.request(RemoteRequest::CodeHash(address)).await?, Word)
+	}
+	async fn code_size(&self, address: H160) -> Result<usize, Self::Error> {
+		expect!(self.request(RemoteRequest::CodeSize(address)).await?, Size)
+	}
+	async fn code(&self, address: H160) -> Result<Vec<u8>, Self::Error> {
+		expect!(self.request(RemoteRequest::Code(address)).await?, Bytes)
+	}
+	async fn storage(&self, address: H160, index: H256) -> Result<H256, Self::Error> {
+		expect!(self.request(RemoteRequest::Storage(address, index)).await?, Word)
+	}
+	async fn storage_range(
+		&self,
+		address: H160,
+		start: H256,
+		limit: usize,
+	) -> Result<Vec<(H256, H256)>, Self::Error> {
+		expect!(self.request(RemoteRequest::StorageRange(address, start, limit)).await?, Range)
+	}
+	async fn account_storage_keys(&self, address: H160) -> Result<Option<Vec<H256>>, Self::Error> {
+		expect!(self.request(RemoteRequest::AccountStorageKeys(address)).await?, Keys)
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<T: RemoteTransport> ApplyBackend for RemoteBackend<T> {
+	/// Stream the changes to the host, one message per chunk. The host
+	/// applies them once the last chunk is received, so a failure applies
+	/// none.
+	async fn apply<A, I, L>(
+		&mut self,
+		values: A,
+		logs: L,
+		delete_empty: bool,
+	) -> Result<(), Self::Error> where
+		A: Sync + Send + IntoIterator<Item=Apply<I>>,
+		A::IntoIter: Send,
+		I: Sync + Send + IntoIterator<Item=(H256, H256)>,
+		L: Sync + Send + IntoIterator<Item=Log>,
+	{
+		let mut values = values.into_iter().map(Apply::into_owned).peekable();
+		let mut logs = Some(logs);
+
+		for index in 0.. {
+			let chunk = values.by_ref().take(self.apply_chunk).collect::<Vec<_>>();
+			let last = values.peek().is_none();
+			let request = RemoteRequest::Apply {
+				index,
+				last,
+				values: chunk,
+				logs: if last { logs.take().into_iter().flatten().collect() } else { Vec::new() },
+				delete_empty,
+			};
+			expect_applied(self.request(request).await?)?;
+			if last {
+				break
+			}
+		}
+		Ok(())
+	}
+}
+
+fn expect_applied<E>(response: RemoteResponse) -> Result<(), RemoteError<E>> {
+	match response {
+		RemoteResponse::Applied => Ok(()),
+		_ => Err(RemoteError::UnexpectedResponse),
+	}
+}
+
+/// Host side of the protocol, answering request messages from a local
+/// backend.
+pub struct RemoteHost<B> {
+	backend: B,
+	/// Operations of the apply chunks received so far.
+	pending: Vec<OwnedApply>,
+	/// Index of the next apply chunk.
+	next_chunk: usize,
+}
+
+impl<B: ApplyBackend> RemoteHost<B> {
+	/// Serve the given backend.
+	pub fn new(backend: B) -> Self {
+		Self { backend, pending: Vec::new(), next_chunk: 0 }
+	}
+
+	/// Get the served backend.
+	pub fn backend(&self) -> &B {
+		&self.backend
+	}
+
+	/// Consume the host, returning the served backend.
+	pub fn into_inner(self) -> B {
+		self.backend
+	}
+
+	/// Answer a request message.
+	pub async fn handle(&mut self, request: &[u8]) -> Vec<u8> {
+		let requests = match decode_requests(request) {
+			Ok(requests) => requests,
+			Err(_) => return encode_responses(&[RemoteResponse::Error("invalid request".to_string())]),
+		};
+		let mut responses = Vec::with_capacity(requests.len());
+		for request in requests {
+			responses.push(self.answer(request).await.unwrap_or_else(|error| {
+				RemoteResponse::Error(alloc::format!("{:?}", error))
+			}));
+		}
+		encode_responses(&responses)
+	}
+
+	async fn answer(&mut self, request: RemoteRequest) -> Result<RemoteResponse, B::Error> {
+		let backend = &self.backend;
+		Ok(match request {
+			RemoteRequest::GasPrice => RemoteResponse::Quantity(backend.gas_price().await?),
+			RemoteRequest::Origin => RemoteResponse::Address(backend.origin().await?),
+			RemoteRequest::BlockHash(number) => RemoteResponse::Word(backend.block_hash(number).await?),
+			RemoteRequest::BlockNumber => RemoteResponse::Quantity(backend.block_number().await?),
+			RemoteRequest::BlockCoinbase => RemoteResponse::Address(backend.block_coinbase().await?),
+			RemoteRequest::BlockTimestamp => RemoteResponse::Quantity(backend.block_timestamp().await?),
+			RemoteRequest::BlockDifficulty => RemoteResponse::Quantity(backend.block_difficulty().await?),
+			RemoteRequest::BlockGasLimit => RemoteResponse::Quantity(backend.block_gas_limit().await?),
+			RemoteRequest::ChainId => RemoteResponse::Quantity(backend.chain_id().await?),
+			RemoteRequest::Exists(address) => RemoteResponse::Bool(backend.exists(address).await?),
+			RemoteRequest::Basic(address) => RemoteResponse::Basic(backend.basic(address).await?),
+			RemoteRequest::CodeHash(address) => RemoteResponse::Word(backend.code_hash(address).await?),
+			RemoteRequest::CodeSize(address) => RemoteResponse::Size(backend.code_size(address).await?),
+			RemoteRequest::Code(address) => RemoteResponse::Bytes(backend.code(address).await?),
+			RemoteRequest::Storage(address, index) => RemoteResponse::Word(backend.storage(address, index).await?),
+			RemoteRequest::StorageRange(address, start, limit) => {
+				RemoteResponse::Range(backend.storage_range(address, start, limit).await?)
+			},
+			RemoteRequest::AccountStorageKeys(address) => {
+				RemoteResponse::Keys(backend.account_storage_keys(address).await?)
+			},
+			RemoteRequest::Apply { index, last, values, logs, delete_empty } => {
+				// A first chunk starts a new apply, dropping the chunks of
+				// an apply its client gave up on.
+				if index == 0 {
+					self.pending.clear();
+				} else if index != self.next_chunk {
+					self.pending.clear();
+					self.next_chunk = 0;
+					return Ok(RemoteResponse::Error("apply chunk out of order".to_string()))
+				}
+				self.pending.extend(values);
+				self.next_chunk = index + 1;
+				if last {
+					let values = core::mem::take(&mut self.pending);
+					self.next_chunk = 0;
+					self.backend.apply(values, logs, delete_empty).await?;
+				}
+				RemoteResponse::Applied
+			},
+		})
+	}
+}
+
+/// Encode a batch of requests as a message.
+pub fn encode_requests(requests: &[RemoteRequest]) -> Vec<u8> {
+	let mut stream = RlpStream::new_list(requests.len());
+	for request in requests {
+		match request {
+			RemoteRequest::GasPrice => { tag(&mut stream, 0, 0); },
+			RemoteRequest::Origin => { tag(&mut stream, 1, 0); },
+			RemoteRequest::BlockHash(number) => { tag(&mut stream, 2, 1).append(number); },
+			RemoteRequest::BlockNumber => { tag(&mut stream, 3, 0); },
+			RemoteRequest::BlockCoinbase => { tag(&mut stream, 4, 0); },
+			RemoteRequest::BlockTimestamp => { tag(&mut stream, 5, 0); },
+			RemoteRequest::BlockDifficulty => { tag(&mut stream, 6, 0); },
+			RemoteRequest::BlockGasLimit => { tag(&mut stream, 7, 0); },
+			RemoteRequest::ChainId => { tag(&mut stream, 8, 0); },
+			RemoteRequest::Exists(address) => { tag(&mut stream, 9, 1).append(address); },
+			RemoteRequest::Basic(address) => { tag(&mut stream, 10, 1).append(address); },
+			RemoteRequest::CodeHash(address) => { tag(&mut stream, 11, 1).append(address); },
+			RemoteRequest::CodeSize(address) => { tag(&mut stream, 12, 1).append(address); },
+			RemoteRequest::Code(address) => { tag(&mut stream, 13, 1).append(address); },
+			RemoteRequest::Storage(address, index) => {
+				tag(&mut stream, 14, 2).append(address).append(index);
+			},
+			RemoteRequest::StorageRange(address, start, limit) => {
+				tag(&mut stream, 15, 3).append(address).append(start).append(&(*limit as u64));
+			},
+			RemoteRequest::AccountStorageKeys(address) => { tag(&mut stream, 16, 1).append(address); },
+			RemoteRequest::Apply { index, last, values, logs, delete_empty } => {
+				tag(&mut stream, 17, 5).append(&(*index as u64)).append(&(*last as u8));
+				stream.append_list(values).append_list(logs).append(&(*delete_empty as u8));
+			},
+		}
+	}
+	stream.out()
+}
+
+/// Decode a message of requests.
+pub fn decode_requests(message: &[u8]) -> Result<Vec<RemoteRequest>, rlp::DecoderError> {
+	items(&Rlp::new(message))?.into_iter().map(|request| {
+		Ok(match request.val_at::<u8>(0)? {
+			0 => RemoteRequest::GasPrice,
+			1 => RemoteRequest::Origin,
+			2 => RemoteRequest::BlockHash(request.val_at(1)?),
+			3 => RemoteRequest::BlockNumber,
+			4 => RemoteRequest::BlockCoinbase,
+			5 => RemoteRequest::BlockTimestamp,
+			6 => RemoteRequest::BlockDifficulty,
+			7 => RemoteRequest::BlockGasLimit,
+			8 => RemoteRequest::ChainId,
+			9 => RemoteRequest::Exists(request.val_at(1)?),
+			10 => RemoteRequest::Basic(request.val_at(1)?),
+			11 => RemoteRequest::CodeHash(request.val_at(1)?),
+			12 => RemoteRequest::CodeSize(request.val_at(1)?),
+			13 => RemoteRequest::Code(request.val_at(1)?),
+			14 => RemoteRequest::Storage(request.val_at(1)?, request.val_at(2)?),
+			15 => RemoteRequest::StorageRange(
+				request.val_at(1)?,
+				request.val_at(2)?,
+				request.val_at::<u64>(3)? as usize,
+			),
+			16 => RemoteRequest::AccountStorageKeys(request.val_at(1)?),
+			17 => RemoteRequest::Apply {
+				index: request.val_at::<u64>(1)? as usize,
+				last: flag(&request.at(2)?)?,
+				values: list(&request.at(3)?)?,
+				logs: list(&request.at(4)?)?,
+				delete_empty: flag(&request.at(5)?)?,
+			},
+			_ => return Err(rlp::DecoderError::Custom("unknown request")),
+		})
+	}).collect()
+}
+
+/// Encode the responses to a batch as a message.
+pub fn encode_responses(responses: &[RemoteResponse]) -> Vec<u8> {
+	let mut stream = RlpStream::new_list(responses.len());
+	for response in responses {
+		match response {
+			RemoteResponse::Quantity(value) => { tag(&mut stream, 0, 1).append(value); },
+			RemoteResponse::Address(value) => { tag(&mut stream, 1, 1).append(value); },
+			RemoteResponse::Word(value) => { tag(&mut stream, 2, 1).append(value); },
+			RemoteResponse::Bool(value) => { tag(&mut stream, 3, 1).append(&(*value as u8)); },
+			RemoteResponse::Basic(basic) => { tag(&mut stream, 4, 1).append(basic); },
+			RemoteResponse::Bytes(value) => { tag(&mut stream, 5, 1).append(value); },
+			RemoteResponse::Size(value) => { tag(&mut stream, 6, 1).append(&(*value as u64)); },
+			RemoteResponse::Range(values) => {
+				tag(&mut stream, 7, 1);
+				append_pairs(&mut stream, values);
+			},
+			RemoteResponse::Keys(keys) => {
+				tag(&mut stream, 8, 1);
+				match keys {
+					Some(keys) => { stream.begin_list(1).append_list(keys); },
+					None => { stream.begin_list(0); },
+				}
+			},
+			RemoteResponse::Applied => { tag(&mut stream, 9, 0); },
+			RemoteResponse::Error(message) => { tag(&mut stream, 10, 1).append(&message.as_bytes()); },
+		}
+	}
+	stream.out()
+}
+
+/// Decode a message of responses.
+pub fn decode_responses(message: &[u8]) -> Result<Vec<RemoteResponse>, rlp::DecoderError> {
+	items(&Rlp::new(message))?.into_iter().map(|response| {
+		let value = || response.at(1);
+		Ok(match response.val_at::<u8>(0)? {
+			0 => RemoteResponse::Quantity(value()?.as_val()?),
+			1 => RemoteResponse::Address(value()?.as_val()?),
+			2 => RemoteResponse::Word(value()?.as_val()?),
+			3 => RemoteResponse::Bool(flag(&value()?)?),
+			4 => RemoteResponse::Basic(value()?.as_val()?),
+			5 => RemoteResponse::Bytes(value()?.as_val()?),
+			6 => RemoteResponse::Size(value()?.as_val::<u64>()? as usize),
+			7 => RemoteResponse::Range(decode_pairs(&value()?)?),
+			8 => RemoteResponse::Keys(match value()?.item_count()? {
+				0 => None,
+				_ => Some(list(&value()?.at(0)?)?),
+			}),
+			9 => RemoteResponse::Applied,
+			10 => RemoteResponse::Error(
+				String::from_utf8(value()?.as_val()?).map_err(|_| rlp::DecoderError::Custom("invalid error"))?,
+			),
+			_ => return Err(rlp::DecoderError::Custom("unknown response")),
+		})
+	}).collect()
+}
+
+/// Begin the list of a tagged item with the given number of arguments.
+fn tag(stream: &mut RlpStream, tag: u8, arguments: usize) -> &mut RlpStream {
+	stream.begin_list(arguments + 1).append(&tag)
+}
+
+fn append_pairs(stream: &mut RlpStream, pairs: &[(H256, H256)]) {
+	stream.begin_list(pairs.len());
+	for (index, value) in pairs {
+		stream.begin_list(2).append(index).append(value);
+	}
+}
+
+fn decode_pairs(rlp: &Rlp) -> Result<Vec<(H256, H256)>, rlp::DecoderError> {
+	items(rlp)?.into_iter().map(|pair| Ok((pair.val_at(0)?, pair.val_at(1)?))).collect()
+}
@@ -0,0 +1,257 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+use std::sync::Arc;
+
+use primitive_types::{H256, U256};
+use rlp::{Rlp, RlpStream};
+
+use crate::deploy::decode_hex;
+use crate::json::Json;
+use crate::types;
+use super::{state_root_with, storage_root_with, MemoryAccount, MemoryBackend, MemoryVicinity};
+
+/// Prefix of binary snapshots.
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"EVMS";
+/// Version of the binary snapshot format written by
+/// `MemoryBackend::snapshot`.
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+/// State dump or snapshot loading failure.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SnapshotError {
+	/// The JSON is malformed at the given byte offset.
+	InvalidJson(usize),
+	/// The field of the JSON dump is missing or invalid.
+	InvalidField(String),
+	/// The snapshot does not start with `SNAPSHOT_MAGIC`.
+	InvalidHeader,
+	/// The snapshot was written by an unsupported version of the format.
+	UnsupportedVersion(u8),
+	/// The snapshot body is malformed.
+	InvalidEncoding,
+}
+
+impl From<rlp::DecoderError> for SnapshotError {
+	fn from(_: rlp::DecoderError) -> Self {
+		SnapshotError::InvalidEncoding
+	}
+}
+
+impl MemoryBackend {
+	/// State in the geth `dump` JSON format: the state root, and the
+	/// accounts by address with their balance, nonce, storage root, code
+	/// hash, and non-empty code and storage.
+	pub fn dump_json(&self) -> String {
+		let hasher = &**self.hasher();
+		let mut json = String::new();
+		write!(json, "{{\"root\":\"{:?}\",\"accounts\":{{", state_root_with(self.state(), hasher))
+			.expect("writing to a string cannot fail");
+		for (i, (address, account)) in self.state().iter().enumerate() {
+			if i > 0 {
+				json.push(',');
+			}
+			write!(
+				json,
+				"\"{:?}\":{{\"balance\":\"{}\",\"nonce\":{},\"root\":\"{:?}\",\"codeHash\":\"{:?}\"",
+				address,
+				account.balance,
+				account.nonce,
+				storage_root_with(&account.storage, hasher),
+				hasher.hash(&account.code),
+			).expect("writing to a string cannot fail");
+			if !account.code.is_empty() {
+				json.push_str(",\"code\":\"0x");
+				push_hex(&mut json, &account.code);
+				json.push('"');
+			}
+			let mut storage = account.storage.iter().filter(|(_, value)| **value != H256::default()).peekable();
+			if storage.peek().is_some() {
+				json.push_str(",\"storage\":{");
+				for (j, (index, value)) in storage.enumerate() {
+					if j > 0 {
+						json.push(',');
+					}
+					// Values are written as geth does, as hex without prefix
+					// and leading zero bytes.
+					write!(json, "\"{:?}\":\"", index).expect("writing to a string cannot fail");
+					let first = value.as_bytes().iter().position(|byte| *byte != 0).unwrap_or(32);
+					push_hex(&mut json, &value[first..]);
+					json.push('"');
+				}
+				json.push('}');
+			}
+			json.push('}');
+		}
+		json.push_str("}}");
+		json
+	}
+
+	/// Memory backend holding the state of a geth `dump` JSON document, in
+	/// the given environment. Roots and code hashes of the dump are not
+	/// checked. Quantities may be numbers, or hex or decimal strings.
+	pub fn load_json(vicinity: Arc<MemoryVicinity>, json: &str) -> Result<Self, SnapshotError> {
+		let json = Json::parse(json).map_err(SnapshotError::InvalidJson)?;
+		let accounts = json.get("accounts").and_then(Json::as_object)
+			.ok_or_else(|| SnapshotError::InvalidField("accounts".to_string()))?;
+
+		let mut state = BTreeMap::new();
+		for (address, fields) in accounts {
+			let invalid = |field: &str| SnapshotError::InvalidField(alloc::format!("{}.{}", address, field));
+			let parsed_address = types::parse_address(address)
+				.map_err(|_| SnapshotError::InvalidField(address.clone()))?;
+
+			let quantity = |field: &str| match fields.get(field) {
+				None => Ok(U256::zero()),
+				Some(value) => parse_quantity(value).ok_or_else(|| invalid(field)),
+			};
+			let code = match fields.get("code") {
+				None => Vec::new(),
+				Some(code) => code.as_str().and_then(decode_hex).ok_or_else(|| invalid("code"))?,
+			};
+			let mut storage = BTreeMap::new();
+			if let Some(values) = fields.get("storage") {
+				for (key, value) in values.as_object().ok_or_else(|| invalid("storage"))? {
+					let index = parse_word(key).ok_or_else(|| invalid(key))?;
+					let value = value.as_str().and_then(parse_word).ok_or_else(|| invalid(key))?;
+					if value != H256::default() {
+						storage.insert(index, value);
+					}
+				}
+			}
+
+			state.insert(parsed_address, MemoryAccount {
+				nonce: quantity("nonce")?,
+				balance: quantity("balance")?,
+				storage,
+				code: code.into(),
+			});
+		}
+
+		Ok(MemoryBackend::new(vicinity, state))
+	}
+
+	/// Binary snapshot of the environment and state of the backend:
+	/// `SNAPSHOT_MAGIC`, the `SNAPSHOT_VERSION` byte, then the RLP list of
+	/// the vicinity and of the accounts.
+	pub fn snapshot(&self) -> Vec<u8> {
+		let vicinity = self.vicinity();
+		let mut stream = RlpStream::new_list(2);
+
+		stream.begin_list(9);
+		stream.append(&vicinity.gas_price);
+		stream.append(&vicinity.origin);
+		stream.append(&vicinity.chain_id);
+		stream.append_list(&vicinity.block_hashes);
+		stream.append(&vicinity.block_number);
+		stream.append(&vicinity.block_coinbase);
+		stream.append(&vicinity.block_timestamp);
+		stream.append(&vicinity.block_difficulty);
+		stream.append(&vicinity.block_gas_limit);
+
+		stream.begin_list(self.state().len());
+		for (address, account) in self.state() {
+			stream.begin_list(5);
+			stream.append(address);
+			stream.append(&account.nonce);
+			stream.append(&account.balance);
+			stream.append(&account.code.as_slice());
+			let storage = account.storage.iter().filter(|(_, value)| **value != H256::default());
+			stream.begin_list(storage.clone().count());
+			for (index, value) in storage {
+				stream.begin_list(2);
+				stream.append(index);
+				stream.append(&U256::from_big_endian(value.as_bytes()));
+			}
+		}
+
+		let mut snapshot = Vec::from(&SNAPSHOT_MAGIC[..]);
+		snapshot.push(SNAPSHOT_VERSION);
+		snapshot.extend_from_slice(&stream.out());
+		snapshot
+	}
+
+	/// Memory backend restored from a binary snapshot.
+	pub fn from_snapshot(snapshot: &[u8]) -> Result<Self, SnapshotError> {
+		if snapshot.len() < SNAPSHOT_MAGIC.len() + 1 || snapshot[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+			return Err(SnapshotError::InvalidHeader)
+		}
+		let version = snapshot[SNAPSHOT_MAGIC.len()];
+		if version != SNAPSHOT_VERSION {
+			return Err(SnapshotError::UnsupportedVersion(version))
+		}
+
+		let rlp = Rlp::new(&snapshot[SNAPSHOT_MAGIC.len() + 1..]);
+		if rlp.item_count()? != 2 {
+			return Err(SnapshotError::InvalidEncoding)
+		}
+
+		let fields = rlp.at(0)?;
+		if fields.item_count()? != 9 {
+			return Err(SnapshotError::InvalidEncoding)
+		}
+		let vicinity = MemoryVicinity {
+			gas_price: fields.val_at(0)?,
+			origin: fields.val_at(1)?,
+			chain_id: fields.val_at(2)?,
+			block_hashes: fields.list_at(3)?,
+			block_number: fields.val_at(4)?,
+			block_coinbase: fields.val_at(5)?,
+			block_timestamp: fields.val_at(6)?,
+			block_difficulty: fields.val_at(7)?,
+			block_gas_limit: fields.val_at(8)?,
+		};
+
+		let mut state = BTreeMap::new();
+		for account in rlp.at(1)?.iter() {
+			if account.item_count()? != 5 {
+				return Err(SnapshotError::InvalidEncoding)
+			}
+			let mut storage = BTreeMap::new();
+			for slot in account.at(4)?.iter() {
+				let value: U256 = slot.val_at(1)?;
+				let mut word = H256::default();
+				value.to_big_endian(word.as_bytes_mut());
+				storage.insert(slot.val_at(0)?, word);
+			}
+			let code: Vec<u8> = account.val_at(3)?;
+			state.insert(account.val_at(0)?, MemoryAccount {
+				nonce: account.val_at(1)?,
+				balance: account.val_at(2)?,
+				storage,
+				code: code.into(),
+			});
+		}
+
+		Ok(MemoryBackend::new(Arc::new(vicinity), state))
+	}
+}
+
+fn push_hex(json: &mut String, bytes: &[u8]) {
+	for byte in bytes {
+		write!(json, "{:02x}", byte).expect("writing to a string cannot fail");
+	}
+}
+
+fn parse_quantity(value: &Json) -> Option<U256> {
+	match value {
+		Json::Number(number) => U256::from_dec_str(number).ok(),
+		Json::String(value) => types::parse_u256(value).ok(),
+		_ => None,
+	}
+}
+
+/// Word of a hex string of at most 32 bytes, with or without prefix,
+/// left-padded with zeros.
+fn parse_word(value: &str) -> Option<H256> {
+	let hex = value.strip_prefix("0x").unwrap_or(value);
+	if hex.len() > 64 {
+		return None
+	}
+	let padded = if hex.len() % 2 == 1 { alloc::format!("0{}", hex) } else { hex.to_string() };
+	let bytes = decode_hex(&padded)?;
+	let mut word = H256::default();
+	word[32 - bytes.len()..].copy_from_slice(&bytes);
+	Some(word)
+}
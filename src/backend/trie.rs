@@ -0,0 +1,735 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use primitive_types::{H160, H256, U256};
+use rlp::{Rlp, RlpStream};
+
+use crate::hasher::{Hasher, Keccak256Hasher};
+use super::MemoryAccount;
+
+fn nibbles(key: &[u8]) -> Vec<u8> {
+	let mut out = Vec::with_capacity(key.len() * 2);
+	for b in key {
+		out.push(b >> 4);
+		out.push(b & 0x0f);
+	}
+	out
+}
+
+/// Hex-prefix encoding of a nibble path.
+fn hex_prefix(path: &[u8], leaf: bool) -> Vec<u8> {
+	let flag = if leaf { 2 } else { 0 };
+	let mut out = Vec::with_capacity(path.len() / 2 + 1);
+	let rest = if path.len() % 2 == 1 {
+		out.push(((flag + 1) << 4) | path[0]);
+		&path[1..]
+	} else {
+		out.push(flag << 4);
+		path
+	};
+	for pair in rest.chunks(2) {
+		out.push((pair[0] << 4) | pair[1]);
+	}
+	out
+}
+
+/// Append a child node, inlining it if its encoding is shorter than a hash.
+fn append_child(stream: &mut RlpStream, node: &[u8], hasher: &dyn Hasher) {
+	if node.len() < 32 {
+		stream.append_raw(node, 1);
+	} else {
+		stream.append(&hasher.hash(node).as_bytes());
+	}
+}
+
+/// Nodes on the path to a key, collected while encoding a trie.
+struct ProofPath<'a> {
+	key: &'a [u8],
+	nodes: Vec<Vec<u8>>,
+}
+
+/// Encode the node for `items`, which are sorted and share the first
+/// `depth` nibbles. If `proof` is given, the node is on the path to its
+/// key, and is collected with the nodes below it on the path.
+fn encode_node(
+	items: &[(Vec<u8>, Vec<u8>)],
+	depth: usize,
+	mut proof: Option<&mut ProofPath>,
+	hasher: &dyn Hasher,
+) -> Vec<u8> {
+	let slot = proof.as_mut().map(|proof| {
+		proof.nodes.push(Vec::new());
+		proof.nodes.len() - 1
+	});
+	let node = encode_node_inner(items, depth, proof.as_deref_mut(), hasher);
+	if let (Some(proof), Some(slot)) = (proof, slot) {
+		proof.nodes[slot] = node.clone();
+	}
+	node
+}
+
+fn encode_node_inner(
+	items: &[(Vec<u8>, Vec<u8>)],
+	depth: usize,
+	mut proof: Option<&mut ProofPath>,
+	hasher: &dyn Hasher,
+) -> Vec<u8> {
+	if items.is_empty() {
+		return rlp::NULL_RLP.to_vec()
+	}
+
+	if items.len() == 1 {
+		let mut stream = RlpStream::new_list(2);
+		stream.append(&hex_prefix(&items[0].0[depth..], true));
+		stream.append(&items[0].1);
+		return stream.out()
+	}
+
+	let first = &items[0].0;
+	let last = &items[items.len() - 1].0;
+	let shared = first[depth..].iter().zip(&last[depth..])
+		.take_while(|(a, b)| a == b)
+		.count();
+
+	if shared > 0 {
+		let prefix = &first[depth..depth + shared];
+		let proof = proof.filter(|proof| proof.key.get(depth..depth + shared) == Some(prefix));
+		let mut stream = RlpStream::new_list(2);
+		stream.append(&hex_prefix(prefix, false));
+		append_child(&mut stream, &encode_node(items, depth + shared, proof, hasher), hasher);
+		return stream.out()
+	}
+
+	let mut stream = RlpStream::new_list(17);
+	let mut rest = items;
+	let value = match rest.first() {
+		Some((key, value)) if key.len() == depth => {
+			rest = &rest[1..];
+			Some(value)
+		},
+		_ => None,
+	};
+	for nibble in 0..16u8 {
+		let count = rest.iter().take_while(|(key, _)| key[depth] == nibble).count();
+		if count == 0 {
+			stream.append_empty_data();
+		} else {
+			let child_proof = match proof.as_mut() {
+				Some(proof) if proof.key.get(depth) == Some(&nibble) => Some(&mut **proof),
+				_ => None,
+			};
+			append_child(&mut stream, &encode_node(&rest[..count], depth + 1, child_proof, hasher), hasher);
+		}
+		rest = &rest[count..];
+	}
+	match value {
+		Some(value) => { stream.append(value); },
+		None => { stream.append_empty_data(); },
+	}
+	stream.out()
+}
+
+fn sorted_items<K, V, I>(items: I) -> Vec<(Vec<u8>, Vec<u8>)> where
+	K: AsRef<[u8]>,
+	V: AsRef<[u8]>,
+	I: IntoIterator<Item=(K, V)>,
+{
+	let mut items = items.into_iter()
+		.map(|(k, v)| (nibbles(k.as_ref()), v.as_ref().to_vec()))
+		.collect::<Vec<_>>();
+	items.sort();
+	items.dedup_by(|a, b| a.0 == b.0);
+	items
+}
+
+/// Merkle-Patricia trie root of the given key-value pairs.
+pub fn trie_root<K, V, I>(items: I) -> H256 where
+	K: AsRef<[u8]>,
+	V: AsRef<[u8]>,
+	I: IntoIterator<Item=(K, V)>,
+{
+	trie_root_with(items, &Keccak256Hasher)
+}
+
+/// Trie root as `trie_root`, with nodes hashed by `hasher`.
+pub fn trie_root_with<K, V, I>(items: I, hasher: &dyn Hasher) -> H256 where
+	K: AsRef<[u8]>,
+	V: AsRef<[u8]>,
+	I: IntoIterator<Item=(K, V)>,
+{
+	hasher.hash(&encode_node(&sorted_items(items), 0, None, hasher))
+}
+
+/// Merkle proof of `key` in the trie of the given key-value pairs: the
+/// nodes on the path to the key, root first. Nodes inlined in their parent
+/// are not repeated. The proof shows the absence of keys not in the trie.
+pub fn trie_proof<K, V, I>(items: I, key: &[u8]) -> Vec<Vec<u8>> where
+	K: AsRef<[u8]>,
+	V: AsRef<[u8]>,
+	I: IntoIterator<Item=(K, V)>,
+{
+	trie_proof_with(items, key, &Keccak256Hasher)
+}
+
+/// Merkle proof as `trie_proof`, with nodes hashed by `hasher`.
+pub fn trie_proof_with<K, V, I>(items: I, key: &[u8], hasher: &dyn Hasher) -> Vec<Vec<u8>> where
+	K: AsRef<[u8]>,
+	V: AsRef<[u8]>,
+	I: IntoIterator<Item=(K, V)>,
+{
+	let key = nibbles(key);
+	let mut proof = ProofPath { key: &key, nodes: Vec::new() };
+	encode_node(&sorted_items(items), 0, Some(&mut proof), hasher);
+
+	proof.nodes.into_iter()
+		.enumerate()
+		.filter(|(i, node)| *i == 0 || node.len() >= 32)
+		.map(|(_, node)| node)
+		.collect()
+}
+
+/// Merkle-Patricia trie root with keys hashed by Keccak-256.
+pub fn sec_trie_root<K, V, I>(items: I) -> H256 where
+	K: AsRef<[u8]>,
+	V: AsRef<[u8]>,
+	I: IntoIterator<Item=(K, V)>,
+{
+	sec_trie_root_with(items, &Keccak256Hasher)
+}
+
+/// Trie root as `sec_trie_root`, with keys and nodes hashed by `hasher`.
+pub fn sec_trie_root_with<K, V, I>(items: I, hasher: &dyn Hasher) -> H256 where
+	K: AsRef<[u8]>,
+	V: AsRef<[u8]>,
+	I: IntoIterator<Item=(K, V)>,
+{
+	trie_root_with(items.into_iter().map(|(k, v)| (hasher.hash(k.as_ref()), v)), hasher)
+}
+
+/// Merkle proof of `key` in the trie with keys hashed by Keccak-256.
+pub fn sec_trie_proof<K, V, I>(items: I, key: &[u8]) -> Vec<Vec<u8>> where
+	K: AsRef<[u8]>,
+	V: AsRef<[u8]>,
+	I: IntoIterator<Item=(K, V)>,
+{
+	sec_trie_proof_with(items, key, &Keccak256Hasher)
+}
+
+/// Merkle proof as `sec_trie_proof`, with keys and nodes hashed by
+/// `hasher`.
+pub fn sec_trie_proof_with<K, V, I>(items: I, key: &[u8], hasher: &dyn Hasher) -> Vec<Vec<u8>> where
+	K: AsRef<[u8]>,
+	V: AsRef<[u8]>,
+	I: IntoIterator<Item=(K, V)>,
+{
+	let items = items.into_iter().map(|(k, v)| (hasher.hash(k.as_ref()), v));
+	trie_proof_with(items, hasher.hash(key).as_bytes(), hasher)
+}
+
+/// Proof verification failure.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProofError {
+	/// The proof ends before the node holding the key.
+	MissingNode,
+	/// A node does not hash to the root or to the reference in its parent.
+	HashMismatch,
+	/// A node is not a valid trie node.
+	InvalidNode,
+	/// The proven value does not match the claimed value.
+	ValueMismatch,
+}
+
+impl From<rlp::DecoderError> for ProofError {
+	fn from(_: rlp::DecoderError) -> Self {
+		ProofError::InvalidNode
+	}
+}
+
+/// Decode a hex-prefix encoded path into its nibbles and leaf flag.
+fn decode_hex_prefix(data: &[u8]) -> Result<(Vec<u8>, bool), ProofError> {
+	let first = *data.first().ok_or(ProofError::InvalidNode)?;
+	let flag = first >> 4;
+	if flag > 3 {
+		return Err(ProofError::InvalidNode)
+	}
+	let mut path = Vec::with_capacity(data.len() * 2);
+	if flag & 1 == 1 {
+		path.push(first & 0x0f);
+	}
+	path.extend(nibbles(&data[1..]));
+	Ok((path, flag & 2 == 2))
+}
+
+/// Verify a Merkle proof of `key` against `root`, returning the value of
+/// the key, or `None` if the proof shows that the key is absent.
+pub fn verify_proof(root: H256, key: &[u8], proof: &[Vec<u8>]) -> Result<Option<Vec<u8>>, ProofError> {
+	verify_proof_with(root, key, proof, &Keccak256Hasher)
+}
+
+/// Verify a Merkle proof as `verify_proof`, with nodes hashed by `hasher`.
+pub fn verify_proof_with(
+	root: H256,
+	key: &[u8],
+	proof: &[Vec<u8>],
+	hasher: &dyn Hasher,
+) -> Result<Option<Vec<u8>>, ProofError> {
+	let key = nibbles(key);
+	let mut nodes = proof.iter();
+	let mut node = nodes.next().ok_or(ProofError::MissingNode)?.clone();
+	if hasher.hash(&node) != root {
+		return Err(ProofError::HashMismatch)
+	}
+	let mut depth = 0;
+
+	loop {
+		let rlp = Rlp::new(&node);
+		if !rlp.is_list() {
+			return if node.as_slice() == rlp::NULL_RLP {
+				Ok(None)
+			} else {
+				Err(ProofError::InvalidNode)
+			}
+		}
+
+		let child = match rlp.item_count()? {
+			2 => {
+				let (path, leaf) = decode_hex_prefix(rlp.at(0)?.data()?)?;
+				if leaf {
+					return Ok(if key[depth..] == path[..] { Some(rlp.val_at(1)?) } else { None })
+				}
+				if !key[depth..].starts_with(&path) {
+					return Ok(None)
+				}
+				depth += path.len();
+				rlp.at(1)?
+			},
+			17 => {
+				if depth == key.len() {
+					let value: Vec<u8> = rlp.val_at(16)?;
+					return Ok(if value.is_empty() { None } else { Some(value) })
+				}
+				depth += 1;
+				rlp.at(key[depth - 1] as usize)?
+			},
+			_ => return Err(ProofError::InvalidNode),
+		};
+
+		let next = if child.is_list() {
+			child.as_raw().to_vec()
+		} else if child.is_empty() {
+			return Ok(None)
+		} else if child.data()?.len() == 32 {
+			let hash = H256::from_slice(child.data()?);
+			let next = nodes.next().ok_or(ProofError::MissingNode)?;
+			if hasher.hash(next) != hash {
+				return Err(ProofError::HashMismatch)
+			}
+			next.clone()
+		} else {
+			return Err(ProofError::InvalidNode)
+		};
+		node = next;
+	}
+}
+
+/// Node of a `PartialTrie`.
+#[derive(Clone, Debug)]
+enum Node {
+	Empty,
+	Leaf(Vec<u8>, Vec<u8>),
+	Extension(Vec<u8>, Box<Node>),
+	Branch(Box<[Node; 16]>, Option<Vec<u8>>),
+	/// Node not given by the proofs, known only by its hash.
+	Hash(H256),
+}
+
+impl Node {
+	/// Decode an encoded node. Children referred to by hash are left
+	/// unresolved.
+	fn decode(node: &[u8]) -> Result<Node, ProofError> {
+		let rlp = Rlp::new(node);
+		if !rlp.is_list() {
+			return if node == rlp::NULL_RLP { Ok(Node::Empty) } else { Err(ProofError::InvalidNode) }
+		}
+
+		match rlp.item_count()? {
+			2 => {
+				let (path, leaf) = decode_hex_prefix(rlp.at(0)?.data()?)?;
+				if leaf {
+					Ok(Node::Leaf(path, rlp.val_at(1)?))
+				} else {
+					Ok(Node::Extension(path, Box::new(Self::decode_child(&rlp.at(1)?)?)))
+				}
+			},
+			17 => {
+				let mut children: Box<[Node; 16]> = Box::default();
+				for (i, child) in children.iter_mut().enumerate() {
+					*child = Self::decode_child(&rlp.at(i)?)?;
+				}
+				let value: Vec<u8> = rlp.val_at(16)?;
+				Ok(Node::Branch(children, Some(value).filter(|value| !value.is_empty())))
+			},
+			_ => Err(ProofError::InvalidNode),
+		}
+	}
+
+	fn decode_child(child: &Rlp) -> Result<Node, ProofError> {
+		if child.is_list() {
+			Self::decode(child.as_raw())
+		} else if child.is_empty() {
+			Ok(Node::Empty)
+		} else if child.data()?.len() == 32 {
+			Ok(Node::Hash(H256::from_slice(child.data()?)))
+		} else {
+			Err(ProofError::InvalidNode)
+		}
+	}
+
+	/// The node itself, decoded from `nodes` if only its hash is known.
+	fn resolve(self, nodes: &BTreeMap<H256, Vec<u8>>) -> Result<Node, ProofError> {
+		match self {
+			Node::Hash(hash) => Self::decode(nodes.get(&hash).ok_or(ProofError::MissingNode)?),
+			node => Ok(node),
+		}
+	}
+
+	fn insert(self, path: &[u8], value: Vec<u8>, nodes: &BTreeMap<H256, Vec<u8>>) -> Result<Node, ProofError> {
+		Ok(match self.resolve(nodes)? {
+			Node::Empty => Node::Leaf(path.to_vec(), value),
+			Node::Leaf(leaf_path, _) if leaf_path == path => Node::Leaf(leaf_path, value),
+			Node::Leaf(leaf_path, leaf_value) => {
+				let shared = shared_len(&leaf_path, path);
+				let branch = Node::Branch(Box::default(), None)
+					.insert(&leaf_path[shared..], leaf_value, nodes)?
+					.insert(&path[shared..], value, nodes)?;
+				Self::extend(&path[..shared], branch)
+			},
+			Node::Extension(extension_path, child) => {
+				let shared = shared_len(&extension_path, path);
+				if shared == extension_path.len() {
+					let child = child.insert(&path[shared..], value, nodes)?;
+					return Ok(Node::Extension(extension_path, Box::new(child)))
+				}
+				let mut children: Box<[Node; 16]> = Box::default();
+				children[extension_path[shared] as usize] = Self::extend(&extension_path[shared + 1..], *child);
+				let branch = Node::Branch(children, None).insert(&path[shared..], value, nodes)?;
+				Self::extend(&path[..shared], branch)
+			},
+			Node::Branch(mut children, branch_value) => match path.split_first() {
+				None => Node::Branch(children, Some(value)),
+				Some((nibble, rest)) => {
+					let child = core::mem::take(&mut children[*nibble as usize]);
+					children[*nibble as usize] = child.insert(rest, value, nodes)?;
+					Node::Branch(children, branch_value)
+				},
+			},
+			Node::Hash(_) => unreachable!("resolved above; qed"),
+		})
+	}
+
+	fn remove(self, path: &[u8], nodes: &BTreeMap<H256, Vec<u8>>) -> Result<Node, ProofError> {
+		match self.resolve(nodes)? {
+			Node::Leaf(leaf_path, _) if leaf_path == path => Ok(Node::Empty),
+			Node::Extension(extension_path, child) if path.starts_with(&extension_path) => {
+				let child = child.remove(&path[extension_path.len()..], nodes)?;
+				Ok(Self::extend(&extension_path, child))
+			},
+			Node::Branch(mut children, value) => {
+				let value = match path.split_first() {
+					None => None,
+					Some((nibble, rest)) => {
+						let child = core::mem::take(&mut children[*nibble as usize]);
+						children[*nibble as usize] = child.remove(rest, nodes)?;
+						value
+					},
+				};
+				Self::collapse(children, value, nodes)
+			},
+			node => Ok(node),
+		}
+	}
+
+	/// Node for `child` below the nibbles of `path`.
+	fn extend(path: &[u8], child: Node) -> Node {
+		if path.is_empty() {
+			return child
+		}
+		match child {
+			Node::Empty => Node::Empty,
+			Node::Leaf(rest, value) => Node::Leaf([path, &rest].concat(), value),
+			Node::Extension(rest, child) => Node::Extension([path, &rest].concat(), child),
+			child => Node::Extension(path.to_vec(), Box::new(child)),
+		}
+	}
+
+	/// Branch left with the given children and value, replaced by the
+	/// single node it holds, if any. A lone child is merged into its
+	/// parent, so it must be resolved.
+	fn collapse(
+		mut children: Box<[Node; 16]>,
+		value: Option<Vec<u8>>,
+		nodes: &BTreeMap<H256, Vec<u8>>,
+	) -> Result<Node, ProofError> {
+		let mut occupied = children.iter().enumerate().filter(|(_, child)| !matches!(child, Node::Empty));
+		match (occupied.next().map(|(nibble, _)| nibble), occupied.next(), value) {
+			(None, _, None) => Ok(Node::Empty),
+			(None, _, Some(value)) => Ok(Node::Leaf(Vec::new(), value)),
+			(Some(nibble), None, None) => {
+				let child = core::mem::take(&mut children[nibble]).resolve(nodes)?;
+				Ok(Self::extend(&[nibble as u8], child))
+			},
+			(_, _, value) => Ok(Node::Branch(children, value)),
+		}
+	}
+
+	fn encode(&self, hasher: &dyn Hasher) -> Vec<u8> {
+		match self {
+			Node::Empty => rlp::NULL_RLP.to_vec(),
+			Node::Leaf(path, value) => {
+				let mut stream = RlpStream::new_list(2);
+				stream.append(&hex_prefix(path, true));
+				stream.append(value);
+				stream.out()
+			},
+			Node::Extension(path, child) => {
+				let mut stream = RlpStream::new_list(2);
+				stream.append(&hex_prefix(path, false));
+				child.append_to(&mut stream, hasher);
+				stream.out()
+			},
+			Node::Branch(children, value) => {
+				let mut stream = RlpStream::new_list(17);
+				for child in children.iter() {
+					child.append_to(&mut stream, hasher);
+				}
+				match value {
+					Some(value) => { stream.append(value); },
+					None => { stream.append_empty_data(); },
+				}
+				stream.out()
+			},
+			Node::Hash(hash) => rlp::encode(&hash.as_bytes()).to_vec(),
+		}
+	}
+
+	/// Append a reference to the node to its parent.
+	fn append_to(&self, stream: &mut RlpStream, hasher: &dyn Hasher) {
+		match self {
+			Node::Empty => { stream.append_empty_data(); },
+			Node::Hash(hash) => { stream.append(&hash.as_bytes()); },
+			node => append_child(stream, &node.encode(hasher), hasher),
+		}
+	}
+}
+
+impl Default for Node {
+	fn default() -> Self {
+		Node::Empty
+	}
+}
+
+fn shared_len(a: &[u8], b: &[u8]) -> usize {
+	a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// Trie with keys hashed by `hasher`, known only through the nodes of
+/// Merkle proofs. Updating a key off the proven paths, or removing a key
+/// whose branch is left with a single unproven child, fails with
+/// `ProofError::MissingNode`.
+pub(crate) struct PartialTrie<'a> {
+	root: Node,
+	nodes: &'a BTreeMap<H256, Vec<u8>>,
+	hasher: &'a dyn Hasher,
+}
+
+impl<'a> PartialTrie<'a> {
+	/// Trie of the given root, with the proof nodes in `nodes` by hash.
+	pub fn new(root: H256, nodes: &'a BTreeMap<H256, Vec<u8>>, hasher: &'a dyn Hasher) -> Self {
+		let root = if root == hasher.hash(&rlp::NULL_RLP) { Node::Empty } else { Node::Hash(root) };
+		Self { root, nodes, hasher }
+	}
+
+	/// Set the value of a key.
+	pub fn insert(&mut self, key: &[u8], value: Vec<u8>) -> Result<(), ProofError> {
+		let path = nibbles(self.hasher.hash(key).as_bytes());
+		self.root = core::mem::take(&mut self.root).insert(&path, value, self.nodes)?;
+		Ok(())
+	}
+
+	/// Remove a key.
+	pub fn remove(&mut self, key: &[u8]) -> Result<(), ProofError> {
+		let path = nibbles(self.hasher.hash(key).as_bytes());
+		self.root = core::mem::take(&mut self.root).remove(&path, self.nodes)?;
+		Ok(())
+	}
+
+	/// Root of the trie.
+	pub fn root(&self) -> H256 {
+		match &self.root {
+			Node::Hash(hash) => *hash,
+			node => self.hasher.hash(&node.encode(self.hasher)),
+		}
+	}
+}
+
+/// Nodes of the account and storage proofs, by hash.
+pub(crate) fn proof_nodes<'a>(
+	proofs: impl IntoIterator<Item=&'a AccountProof>,
+	hasher: &dyn Hasher,
+) -> BTreeMap<H256, Vec<u8>> {
+	proofs.into_iter()
+		.flat_map(|proof| proof.account_proof.iter()
+			.chain(proof.storage_proof.iter().flat_map(|slot| slot.proof.iter())))
+		.map(|node| (hasher.hash(node), node.clone()))
+		.collect()
+}
+
+fn storage_items(storage: &BTreeMap<H256, H256>) -> impl Iterator<Item=(H256, Vec<u8>)> + '_ {
+	storage.iter()
+		.filter(|(_, value)| **value != H256::default())
+		.map(|(index, value)| {
+			(*index, rlp::encode(&U256::from_big_endian(value.as_bytes())))
+		})
+}
+
+pub(crate) fn account_rlp(nonce: U256, balance: U256, storage_hash: H256, code_hash: H256) -> Vec<u8> {
+	let mut stream = RlpStream::new_list(4);
+	stream.append(&nonce);
+	stream.append(&balance);
+	stream.append(&storage_hash);
+	stream.append(&code_hash);
+	stream.out()
+}
+
+fn state_items<'a>(
+	state: &'a BTreeMap<H160, MemoryAccount>,
+	hasher: &'a dyn Hasher,
+) -> impl Iterator<Item=(H160, Vec<u8>)> + 'a {
+	state.iter().map(move |(address, account)| {
+		let rlp = account_rlp(
+			account.nonce,
+			account.balance,
+			storage_root_with(&account.storage, hasher),
+			hasher.hash(&account.code),
+		);
+		(*address, rlp)
+	})
+}
+
+/// Storage root of an account storage. Zero values are omitted.
+pub fn storage_root(storage: &BTreeMap<H256, H256>) -> H256 {
+	storage_root_with(storage, &Keccak256Hasher)
+}
+
+/// Storage root as `storage_root`, hashed by `hasher`.
+pub fn storage_root_with(storage: &BTreeMap<H256, H256>, hasher: &dyn Hasher) -> H256 {
+	sec_trie_root_with(storage_items(storage), hasher)
+}
+
+/// State root of the given accounts.
+pub fn state_root(state: &BTreeMap<H160, MemoryAccount>) -> H256 {
+	state_root_with(state, &Keccak256Hasher)
+}
+
+/// State root as `state_root`, with code hashes and tries hashed by
+/// `hasher`.
+pub fn state_root_with(state: &BTreeMap<H160, MemoryAccount>, hasher: &dyn Hasher) -> H256 {
+	sec_trie_root_with(state_items(state, hasher), hasher)
+}
+
+/// Merkle proof of a storage slot, in the `eth_getProof` format.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StorageProof {
+	/// Storage index.
+	pub key: H256,
+	/// Storage value, zero for absent slots.
+	pub value: U256,
+	/// Nodes of the storage trie on the path to the slot, root first.
+	pub proof: Vec<Vec<u8>>,
+}
+
+/// Merkle proof of an account and some of its storage slots, in the
+/// `eth_getProof` format (EIP-1186). Absent accounts are proven with empty
+/// account fields.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccountProof {
+	/// Account address.
+	pub address: H160,
+	/// Account balance.
+	pub balance: U256,
+	/// Account nonce.
+	pub nonce: U256,
+	/// Hash of the account code.
+	pub code_hash: H256,
+	/// Root of the account storage trie.
+	pub storage_hash: H256,
+	/// Nodes of the state trie on the path to the account, root first.
+	pub account_proof: Vec<Vec<u8>>,
+	/// Proofs of the requested storage slots.
+	pub storage_proof: Vec<StorageProof>,
+}
+
+impl AccountProof {
+	/// Verify the account proof against `state_root`, and the storage
+	/// proofs against the storage hash of the account.
+	pub fn verify(&self, state_root: H256) -> Result<(), ProofError> {
+		self.verify_with(state_root, &Keccak256Hasher)
+	}
+
+	/// Verify the proofs as `verify`, with tries hashed by `hasher`.
+	pub fn verify_with(&self, state_root: H256, hasher: &dyn Hasher) -> Result<(), ProofError> {
+		let key = hasher.hash(self.address.as_bytes());
+		let value = verify_proof_with(state_root, key.as_bytes(), &self.account_proof, hasher)?;
+		let expected = account_rlp(self.nonce, self.balance, self.storage_hash, self.code_hash);
+		match value {
+			Some(value) if value == expected => (),
+			None if self.nonce.is_zero() && self.balance.is_zero() &&
+				self.code_hash == hasher.hash(&[]) && self.storage_hash == hasher.hash(&rlp::NULL_RLP) => (),
+			_ => return Err(ProofError::ValueMismatch),
+		}
+
+		for slot in &self.storage_proof {
+			let key = hasher.hash(slot.key.as_bytes());
+			let value = verify_proof_with(self.storage_hash, key.as_bytes(), &slot.proof, hasher)?;
+			let value = match value {
+				Some(value) => Rlp::new(&value).as_val::<U256>()?,
+				None => U256::zero(),
+			};
+			if value != slot.value {
+				return Err(ProofError::ValueMismatch)
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Proof of `address` and its `slots` in the given state.
+pub(crate) fn account_proof(
+	state: &BTreeMap<H160, MemoryAccount>,
+	address: H160,
+	slots: &[H256],
+	hasher: &dyn Hasher,
+) -> AccountProof {
+	let account_proof = sec_trie_proof_with(state_items(state, hasher), address.as_bytes(), hasher);
+
+	let empty = MemoryAccount::default();
+	let account = state.get(&address).unwrap_or(&empty);
+	let storage_proof = slots.iter()
+		.map(|key| StorageProof {
+			key: *key,
+			value: U256::from_big_endian(account.storage.get(key).copied().unwrap_or_default().as_bytes()),
+			proof: sec_trie_proof_with(storage_items(&account.storage), key.as_bytes(), hasher),
+		})
+		.collect();
+
+	AccountProof {
+		address,
+		balance: account.balance,
+		nonce: account.nonce,
+		code_hash: hasher.hash(&account.code),
+		storage_hash: storage_root_with(&account.storage, hasher),
+		account_proof,
+		storage_proof,
+	}
+}
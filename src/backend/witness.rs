@@ -0,0 +1,342 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use std::sync::{Arc, Mutex};
+
+use primitive_types::{H160, H256, U256};
+
+use crate::hasher::{Hasher, Keccak256Hasher};
+use super::{Backend, Basic, MemoryAccount};
+
+/// Account values read from a backend during an execution.
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WitnessAccount {
+	/// Whether the account exists.
+	pub exists: Option<bool>,
+	/// Basic account information.
+	pub basic: Option<Basic>,
+	/// Account code hash.
+	pub code_hash: Option<H256>,
+	/// Account code size.
+	pub code_size: Option<usize>,
+	/// Account code.
+	pub code: Option<Vec<u8>>,
+	/// Storage values read.
+	pub storage: BTreeMap<H256, H256>,
+}
+
+/// Every backend value consumed during an execution. `None` means the
+/// value was never read.
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Witness {
+	/// Gas price.
+	pub gas_price: Option<U256>,
+	/// Origin.
+	pub origin: Option<H160>,
+	/// Chain ID.
+	pub chain_id: Option<U256>,
+	/// Environmental block number.
+	pub block_number: Option<U256>,
+	/// Environmental coinbase.
+	pub block_coinbase: Option<H160>,
+	/// Environmental block timestamp.
+	pub block_timestamp: Option<U256>,
+	/// Environmental block difficulty.
+	pub block_difficulty: Option<U256>,
+	/// Environmental block gas limit.
+	pub block_gas_limit: Option<U256>,
+	/// Block hashes read, by block number.
+	pub block_hashes: BTreeMap<U256, H256>,
+	/// Accounts read.
+	pub accounts: BTreeMap<H160, WitnessAccount>,
+}
+
+impl Witness {
+	/// Add the complete state of an account to the witness.
+	pub fn insert_account(&mut self, address: H160, account: &MemoryAccount) {
+		self.insert_account_with(address, account, &Keccak256Hasher)
+	}
+
+	/// Add the complete state of an account to the witness, hashing its
+	/// code with the given hash function.
+	pub fn insert_account_with(&mut self, address: H160, account: &MemoryAccount, hasher: &dyn Hasher) {
+		let entry = self.accounts.entry(address).or_default();
+		entry.exists = Some(true);
+		entry.basic = Some(Basic { balance: account.balance, nonce: account.nonce });
+		entry.code_hash = Some(hasher.hash(&account.code));
+		entry.code_size = Some(account.code.len());
+		entry.code = Some(account.code.to_vec());
+		entry.storage.extend(account.storage.iter().map(|(k, v)| (*k, *v)));
+	}
+
+	/// State described by the witness. Accounts without any witnessed
+	/// value, and empty accounts, are omitted.
+	pub fn state(&self) -> BTreeMap<H160, MemoryAccount> {
+		self.accounts.iter().filter_map(|(address, witnessed)| {
+			if witnessed.exists == Some(false) {
+				return None
+			}
+
+			let basic = witnessed.basic.clone().unwrap_or_default();
+			let account = MemoryAccount {
+				nonce: basic.nonce,
+				balance: basic.balance,
+				storage: witnessed.storage.iter()
+					.filter(|(_, v)| **v != H256::default())
+					.map(|(k, v)| (*k, *v))
+					.collect(),
+				code: witnessed.code.clone().unwrap_or_default().into(),
+			};
+
+			if account == MemoryAccount::default() {
+				None
+			} else {
+				Some((*address, account))
+			}
+		}).collect()
+	}
+}
+
+/// Backend wrapper recording every value read from the inner backend into
+/// a `Witness`.
+pub struct Recorder<B> {
+	inner: Arc<B>,
+	witness: Mutex<Witness>,
+}
+
+impl<B: Backend> Recorder<B> {
+	/// Create a new recorder over the given backend.
+	pub fn new(inner: Arc<B>) -> Self {
+		Self {
+			inner,
+			witness: Mutex::new(Witness::default()),
+		}
+	}
+
+	/// Get the underlying backend.
+	pub fn inner(&self) -> &Arc<B> {
+		&self.inner
+	}
+
+	/// Get the witness recorded so far.
+	pub fn witness(&self) -> Witness {
+		self.record(|witness| witness.clone())
+	}
+
+	fn record<R>(&self, f: impl FnOnce(&mut Witness) -> R) -> R {
+		let mut witness = self.witness.lock().unwrap_or_else(|e| e.into_inner());
+		f(&mut witness)
+	}
+
+	fn record_account(&self, address: H160, f: impl FnOnce(&mut WitnessAccount)) {
+		self.record(|witness| f(witness.accounts.entry(address).or_default()))
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<B: Backend> Backend for Recorder<B> {
+	type Error = B::Error;
+
+	async fn gas_price(&self) -> Result<U256, B::Error> {
+		let value = self.inner.gas_price().await?;
+		self.record(|w| w.gas_price = Some(value));
+		Ok(value)
+	}
+	async fn origin(&self) -> Result<H160, B::Error> {
+		let value = self.inner.origin().await?;
+		self.record(|w| w.origin = Some(value));
+		Ok(value)
+	}
+	async fn block_hash(&self, number: U256) -> Result<H256, B::Error> {
+		let value = self.inner.block_hash(number).await?;
+		self.record(|w| { w.block_hashes.insert(number, value); });
+		Ok(value)
+	}
+	async fn block_number(&self) -> Result<U256, B::Error> {
+		let value = self.inner.block_number().await?;
+		self.record(|w| w.block_number = Some(value));
+		Ok(value)
+	}
+	async fn block_coinbase(&self) -> Result<H160, B::Error> {
+		let value = self.inner.block_coinbase().await?;
+		self.record(|w| w.block_coinbase = Some(value));
+		Ok(value)
+	}
+	async fn block_timestamp(&self) -> Result<U256, B::Error> {
+		let value = self.inner.block_timestamp().await?;
+		self.record(|w| w.block_timestamp = Some(value));
+		Ok(value)
+	}
+	async fn block_difficulty(&self) -> Result<U256, B::Error> {
+		let value = self.inner.block_difficulty().await?;
+		self.record(|w| w.block_difficulty = Some(value));
+		Ok(value)
+	}
+	async fn block_gas_limit(&self) -> Result<U256, B::Error> {
+		let value = self.inner.block_gas_limit().await?;
+		self.record(|w| w.block_gas_limit = Some(value));
+		Ok(value)
+	}
+	async fn chain_id(&self) -> Result<U256, B::Error> {
+		let value = self.inner.chain_id().await?;
+		self.record(|w| w.chain_id = Some(value));
+		Ok(value)
+	}
+
+	async fn exists(&self, address: H160) -> Result<bool, B::Error> {
+		let value = self.inner.exists(address).await?;
+		self.record_account(address, |a| a.exists = Some(value));
+		Ok(value)
+	}
+	async fn basic(&self, address: H160) -> Result<Basic, B::Error> {
+		let value = self.inner.basic(address).await?;
+		self.record_account(address, |a| a.basic = Some(value.clone()));
+		Ok(value)
+	}
+	async fn code_hash(&self, address: H160) -> Result<H256, B::Error> {
+		let value = self.inner.code_hash(address).await?;
+		self.record_account(address, |a| a.code_hash = Some(value));
+		Ok(value)
+	}
+	async fn code_size(&self, address: H160) -> Result<usize, B::Error> {
+		let value = self.inner.code_size(address).await?;
+		self.record_account(address, |a| a.code_size = Some(value));
+		Ok(value)
+	}
+	async fn code(&self, address: H160) -> Result<Vec<u8>, B::Error> {
+		let value = self.inner.code(address).await?;
+		self.record_account(address, |a| a.code = Some(value.clone()));
+		Ok(value)
+	}
+	async fn storage(&self, address: H160, index: H256) -> Result<H256, B::Error> {
+		let value = self.inner.storage(address, index).await?;
+		self.record_account(address, |a| { a.storage.insert(index, value); });
+		Ok(value)
+	}
+	async fn storage_range(
+		&self,
+		address: H160,
+		start: H256,
+		limit: usize,
+	) -> Result<Vec<(H256, H256)>, B::Error> {
+		let values = self.inner.storage_range(address, start, limit).await?;
+		self.record_account(address, |a| a.storage.extend(values.iter().cloned()));
+		Ok(values)
+	}
+}
+
+/// Read of a value missing from the witness of a `WitnessBackend`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WitnessError {
+	/// Environment value, by the name of its `Backend` method.
+	Environment(&'static str),
+	/// Hash of the given block number.
+	BlockHash(U256),
+	/// Account value, by the name of its `Backend` method.
+	Account(H160, &'static str),
+	/// Storage slot of an account.
+	Storage(H160, H256),
+}
+
+/// Backend replaying the values of a `Witness`. Reading a value missing
+/// from the witness fails with a `WitnessError`.
+#[derive(Debug)]
+pub struct WitnessBackend {
+	witness: Witness,
+}
+
+impl WitnessBackend {
+	/// Create a new backend replaying the given witness.
+	pub fn new(witness: Witness) -> Self {
+		Self { witness }
+	}
+
+	/// Get the underlying witness.
+	pub fn witness(&self) -> &Witness {
+		&self.witness
+	}
+
+	fn environment<T: Copy>(&self, value: Option<T>, method: &'static str) -> Result<T, WitnessError> {
+		value.ok_or(WitnessError::Environment(method))
+	}
+
+	fn account<T>(
+		&self,
+		address: H160,
+		method: &'static str,
+		f: impl FnOnce(&WitnessAccount) -> Option<T>,
+	) -> Result<T, WitnessError> {
+		self.witness.accounts.get(&address)
+			.and_then(f)
+			.ok_or(WitnessError::Account(address, method))
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl Backend for WitnessBackend {
+	type Error = WitnessError;
+
+	async fn gas_price(&self) -> Result<U256, WitnessError> {
+		self.environment(self.witness.gas_price, "gas_price")
+	}
+	async fn origin(&self) -> Result<H160, WitnessError> {
+		self.environment(self.witness.origin, "origin")
+	}
+	async fn block_hash(&self, number: U256) -> Result<H256, WitnessError> {
+		self.witness.block_hashes.get(&number).cloned().ok_or(WitnessError::BlockHash(number))
+	}
+	async fn block_number(&self) -> Result<U256, WitnessError> {
+		self.environment(self.witness.block_number, "block_number")
+	}
+	async fn block_coinbase(&self) -> Result<H160, WitnessError> {
+		self.environment(self.witness.block_coinbase, "block_coinbase")
+	}
+	async fn block_timestamp(&self) -> Result<U256, WitnessError> {
+		self.environment(self.witness.block_timestamp, "block_timestamp")
+	}
+	async fn block_difficulty(&self) -> Result<U256, WitnessError> {
+		self.environment(self.witness.block_difficulty, "block_difficulty")
+	}
+	async fn block_gas_limit(&self) -> Result<U256, WitnessError> {
+		self.environment(self.witness.block_gas_limit, "block_gas_limit")
+	}
+	async fn chain_id(&self) -> Result<U256, WitnessError> {
+		self.environment(self.witness.chain_id, "chain_id")
+	}
+
+	async fn exists(&self, address: H160) -> Result<bool, WitnessError> {
+		self.account(address, "exists", |a| a.exists)
+	}
+	async fn basic(&self, address: H160) -> Result<Basic, WitnessError> {
+		self.account(address, "basic", |a| a.basic.clone())
+	}
+	async fn code_hash(&self, address: H160) -> Result<H256, WitnessError> {
+		self.account(address, "code_hash", |a| a.code_hash)
+	}
+	async fn code_size(&self, address: H160) -> Result<usize, WitnessError> {
+		self.account(address, "code_size", |a| a.code_size)
+	}
+	async fn code(&self, address: H160) -> Result<Vec<u8>, WitnessError> {
+		self.account(address, "code", |a| a.code.clone())
+	}
+	async fn storage(&self, address: H160, index: H256) -> Result<H256, WitnessError> {
+		self.witness.accounts.get(&address)
+			.and_then(|a| a.storage.get(&index).cloned())
+			.ok_or(WitnessError::Storage(address, index))
+	}
+	async fn storage_range(
+		&self,
+		address: H160,
+		start: H256,
+		limit: usize,
+	) -> Result<Vec<(H256, H256)>, WitnessError> {
+		self.account(address, "storage_range", |a| Some(a.storage.range(start..)
+			.filter(|(_, value)| **value != H256::default())
+			.take(limit)
+			.map(|(index, value)| (*index, *value))
+			.collect()))
+	}
+}
@@ -0,0 +1,190 @@
+//! Genesis files of private networks.
+//!
+//! Types mirror the geth `genesis.json` format, and can be deserialized from
+//! it with the `with-serde` feature. Quantities are given as hex (`0x`
+//! prefixed) or decimal strings.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use std::sync::Arc;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::{types, Config};
+use crate::backend::{MemoryAccount, MemoryBackend, MemoryVicinity};
+use crate::deploy::decode_hex;
+use crate::executor::{ForkActivation, ForkSchedule};
+
+/// Chain id and fork activations of a genesis file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "with-serde", serde(rename_all = "camelCase", default))]
+pub struct ChainConfig {
+	/// Chain id.
+	pub chain_id: u64,
+	/// Homestead activation block.
+	pub homestead_block: Option<u64>,
+	/// Byzantium activation block.
+	pub byzantium_block: Option<u64>,
+	/// Constantinople activation block.
+	pub constantinople_block: Option<u64>,
+	/// Istanbul activation block.
+	pub istanbul_block: Option<u64>,
+	/// Berlin activation block.
+	pub berlin_block: Option<u64>,
+	/// London activation block.
+	pub london_block: Option<u64>,
+	/// Shanghai activation timestamp.
+	pub shanghai_time: Option<u64>,
+	/// Cancun activation timestamp.
+	pub cancun_time: Option<u64>,
+	/// Prague activation timestamp.
+	pub prague_time: Option<u64>,
+}
+
+impl ChainConfig {
+	/// Fork schedule of the chain. Forks this crate has no config for run
+	/// with the config of the latest earlier fork it has: blocks before
+	/// Istanbul run with `Config::frontier`, and later blocks with
+	/// `Config::istanbul`. There is no config from Berlin on, as its access
+	/// costs (EIP-2929) and London's refund changes (EIP-3529) are not
+	/// implemented.
+	pub fn fork_schedule(&self) -> ForkSchedule {
+		let mut schedule = ForkSchedule::new(Config::frontier());
+		if let Some(block) = self.istanbul_block {
+			schedule = schedule.with_fork(ForkActivation::Block(block), Config::istanbul());
+		}
+		schedule
+	}
+
+	/// Config of the block with the given number and timestamp.
+	pub fn config_at(&self, number: u64, timestamp: u64) -> Arc<Config> {
+		self.fork_schedule().config_at(number, timestamp)
+	}
+}
+
+/// Account of a genesis allocation.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "with-serde", serde(default))]
+pub struct GenesisAccount {
+	/// Balance.
+	pub balance: String,
+	/// Nonce, zero if not given.
+	pub nonce: Option<String>,
+	/// Hex encoded code.
+	pub code: Option<String>,
+	/// Storage, by slot.
+	pub storage: BTreeMap<String, String>,
+}
+
+/// Genesis file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "with-serde", serde(rename_all = "camelCase", default))]
+pub struct Genesis {
+	/// Chain id and fork activations.
+	pub config: ChainConfig,
+	/// Genesis timestamp.
+	pub timestamp: Option<String>,
+	/// Genesis block gas limit.
+	pub gas_limit: Option<String>,
+	/// Genesis difficulty.
+	pub difficulty: Option<String>,
+	/// Genesis coinbase.
+	pub coinbase: Option<String>,
+	/// Genesis accounts, by address.
+	pub alloc: BTreeMap<String, GenesisAccount>,
+}
+
+/// Genesis loading failure.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChainSpecError {
+	/// The value is not a valid address.
+	InvalidAddress(String),
+	/// The value is not a valid quantity.
+	InvalidQuantity(String),
+	/// The value is not valid hex.
+	InvalidHex(String),
+}
+
+impl Genesis {
+	/// Genesis state.
+	pub fn state(&self) -> Result<BTreeMap<H160, MemoryAccount>, ChainSpecError> {
+		let mut state = BTreeMap::new();
+		for (address, account) in &self.alloc {
+			let mut storage = BTreeMap::new();
+			for (index, value) in &account.storage {
+				storage.insert(word(index)?, word(value)?);
+			}
+
+			state.insert(parse_address(address)?, MemoryAccount {
+				nonce: match account.nonce.as_ref() {
+					Some(nonce) => parse_quantity(nonce)?,
+					None => U256::zero(),
+				},
+				balance: parse_quantity(&account.balance)?,
+				storage,
+				code: match account.code.as_ref() {
+					Some(code) => decode_hex(code).ok_or_else(|| ChainSpecError::InvalidHex(code.clone()))?,
+					None => Vec::new(),
+				}.into(),
+			});
+		}
+		Ok(state)
+	}
+
+	/// Environment of the genesis block.
+	pub fn vicinity(&self) -> Result<MemoryVicinity, ChainSpecError> {
+		let quantity = |value: &Option<String>| match value.as_ref() {
+			Some(value) => parse_quantity(value),
+			None => Ok(U256::zero()),
+		};
+
+		Ok(MemoryVicinity {
+			gas_price: U256::zero(),
+			origin: H160::zero(),
+			chain_id: U256::from(self.config.chain_id),
+			block_hashes: Vec::new(),
+			block_number: U256::zero(),
+			block_coinbase: match self.coinbase.as_ref() {
+				Some(coinbase) => parse_address(coinbase)?,
+				None => H160::zero(),
+			},
+			block_timestamp: quantity(&self.timestamp)?,
+			block_difficulty: quantity(&self.difficulty)?,
+			block_gas_limit: quantity(&self.gas_limit)?,
+		})
+	}
+
+	/// Memory backend holding the genesis state, in the environment of the
+	/// genesis block.
+	pub fn backend(&self) -> Result<MemoryBackend, ChainSpecError> {
+		Ok(MemoryBackend::new(Arc::new(self.vicinity()?), self.state()?))
+	}
+
+	/// Fork schedule of the chain.
+	pub fn fork_schedule(&self) -> ForkSchedule {
+		self.config.fork_schedule()
+	}
+
+	/// Config of the block with the given number and timestamp.
+	pub fn config_at(&self, number: u64, timestamp: u64) -> Arc<Config> {
+		self.config.config_at(number, timestamp)
+	}
+}
+
+fn parse_quantity(value: &str) -> Result<U256, ChainSpecError> {
+	types::parse_u256(value).map_err(|_| ChainSpecError::InvalidQuantity(value.to_string()))
+}
+
+fn parse_address(value: &str) -> Result<H160, ChainSpecError> {
+	types::parse_address(value).map_err(|_| ChainSpecError::InvalidAddress(value.to_string()))
+}
+
+fn word(value: &str) -> Result<H256, ChainSpecError> {
+	let mut word = H256::default();
+	parse_quantity(value)?.to_big_endian(word.as_bytes_mut());
+	Ok(word)
+}
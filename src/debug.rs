@@ -0,0 +1,68 @@
+//! Debugging helpers, for state assertions in tests and debug endpoints.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::backend::Backend;
+
+/// Full state of an account.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountDump {
+	/// Balance.
+	pub balance: U256,
+	/// Nonce.
+	pub nonce: U256,
+	/// Code.
+	pub code: Vec<u8>,
+	/// Non-zero storage values, by index.
+	pub storage: BTreeMap<H256, H256>,
+}
+
+impl AccountDump {
+	/// JSON object with `balance`, `nonce`, `code` and `storage` fields, in
+	/// the `0x` prefixed hex encoding of the JSON-RPC API.
+	pub fn to_json(&self) -> String {
+		let mut json = String::new();
+		write!(json, "{{\"balance\":\"{:#x}\",\"nonce\":\"{:#x}\",\"code\":\"0x", self.balance, self.nonce)
+			.expect("writing to a string cannot fail");
+		for byte in &self.code {
+			write!(json, "{:02x}", byte).expect("writing to a string cannot fail");
+		}
+		json.push_str("\",\"storage\":{");
+		for (i, (index, value)) in self.storage.iter().enumerate() {
+			if i > 0 {
+				json.push(',');
+			}
+			write!(json, "\"{:#x}\":\"{:#x}\"", index, value).expect("writing to a string cannot fail");
+		}
+		json.push_str("}}");
+		json
+	}
+}
+
+/// Dump the account at `address`, or `None` if the backend cannot list its
+/// storage keys.
+pub async fn dump_account<B: Backend>(backend: &B, address: H160) -> Result<Option<AccountDump>, B::Error> {
+	let keys = match backend.account_storage_keys(address).await? {
+		Some(keys) => keys,
+		None => return Ok(None),
+	};
+
+	let basic = backend.basic(address).await?;
+	let mut storage = BTreeMap::new();
+	for index in keys {
+		storage.insert(index, backend.storage(address, index).await?);
+	}
+
+	Ok(Some(AccountDump {
+		balance: basic.balance,
+		nonce: basic.nonce,
+		code: backend.code(address).await?,
+		storage,
+	}))
+}
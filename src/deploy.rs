@@ -0,0 +1,184 @@
+//! Contract deployment from compiler artifacts.
+//!
+//! Types mirror the `bytecode` and `deployedBytecode` objects of solc,
+//! Hardhat and Foundry artifacts, and can be deserialized from them with
+//! the `with-serde` feature.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::{CreateScheme, ExitReason};
+use crate::backend::Backend;
+use crate::executor::StackExecutor;
+
+/// Byte range of a placeholder in bytecode.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Offset {
+	/// First byte.
+	pub start: usize,
+	/// Length in bytes.
+	pub length: usize,
+}
+
+/// Compiled bytecode with its unresolved references.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bytecode {
+	/// Hex encoded bytecode, possibly containing `__$...$__` library
+	/// placeholders.
+	pub object: String,
+	/// Library placeholders, by source file and library name.
+	#[cfg_attr(feature = "with-serde", serde(rename = "linkReferences", default))]
+	pub link_references: BTreeMap<String, BTreeMap<String, Vec<Offset>>>,
+	/// Immutable variable slots, by AST identifier. Only set for deployed
+	/// bytecode.
+	#[cfg_attr(feature = "with-serde", serde(rename = "immutableReferences", default))]
+	pub immutable_references: BTreeMap<String, Vec<Offset>>,
+}
+
+/// Compiler artifact of a contract.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Artifact {
+	/// Creation bytecode.
+	pub bytecode: Bytecode,
+	/// Runtime bytecode.
+	#[cfg_attr(feature = "with-serde", serde(rename = "deployedBytecode", default))]
+	pub deployed_bytecode: Bytecode,
+}
+
+/// Deployment failure.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DeployError {
+	/// The bytecode is not valid hex.
+	InvalidBytecode,
+	/// No address was given for the library, as `file:Name`.
+	UnlinkedLibrary(String),
+	/// A placeholder lies outside of the bytecode.
+	InvalidOffset(Offset),
+	/// No value was given for the immutable with the given identifier.
+	MissingImmutable(String),
+	/// The creation did not succeed.
+	Failed(ExitReason),
+}
+
+/// Deployed contract.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Deployment {
+	/// Address of the created contract.
+	pub address: H160,
+	/// Gas used by the creation.
+	pub gas_used: usize,
+}
+
+pub(crate) fn decode_hex(value: &str) -> Option<Vec<u8>> {
+	let value = value.strip_prefix("0x").unwrap_or(value);
+	if value.len() % 2 == 1 {
+		return None
+	}
+
+	fn nibble(c: u8) -> Option<u8> {
+		match c {
+			b'0'..=b'9' => Some(c - b'0'),
+			b'a'..=b'f' => Some(c - b'a' + 10),
+			b'A'..=b'F' => Some(c - b'A' + 10),
+			_ => None,
+		}
+	}
+
+	value.as_bytes().chunks(2)
+		.map(|pair| Some(nibble(pair[0])? << 4 | nibble(pair[1])?))
+		.collect()
+}
+
+fn patch(code: &mut [u8], offset: Offset, value: &[u8]) -> Result<(), DeployError> {
+	let end = offset.start.checked_add(offset.length).ok_or(DeployError::InvalidOffset(offset))?;
+	if end > code.len() || offset.length > value.len() {
+		return Err(DeployError::InvalidOffset(offset))
+	}
+	code[offset.start..end].copy_from_slice(&value[value.len() - offset.length..]);
+	Ok(())
+}
+
+impl Bytecode {
+	/// Resolve library placeholders and decode the bytecode. Libraries are
+	/// looked up as `file:Name`, then as `Name`.
+	pub fn link(&self, libraries: &BTreeMap<String, H160>) -> Result<Vec<u8>, DeployError> {
+		// Placeholders are not hex; blank them before decoding.
+		let mut object = String::from(self.object.strip_prefix("0x").unwrap_or(&self.object));
+		let mut links = Vec::new();
+		for (file, references) in &self.link_references {
+			for (name, offsets) in references {
+				let qualified = [file.as_str(), ":", name.as_str()].concat();
+				let address = libraries.get(&qualified).or_else(|| libraries.get(name))
+					.ok_or(DeployError::UnlinkedLibrary(qualified))?;
+				for offset in offsets {
+					let range = offset.start * 2..(offset.start + offset.length) * 2;
+					if range.end > object.len() || !object.is_char_boundary(range.start) ||
+						!object.is_char_boundary(range.end)
+					{
+						return Err(DeployError::InvalidOffset(*offset))
+					}
+					object.replace_range(range, &"0".repeat(offset.length * 2));
+					links.push((*offset, *address));
+				}
+			}
+		}
+
+		if let Some(start) = object.find("__$") {
+			return Err(DeployError::UnlinkedLibrary(object[start..].chars().take(40).collect()))
+		}
+		let mut code = decode_hex(&object).ok_or(DeployError::InvalidBytecode)?;
+		for (offset, address) in links {
+			patch(&mut code, offset, address.as_bytes())?;
+		}
+		Ok(code)
+	}
+
+	/// Link the bytecode, and write immutable values, given as 32-byte words
+	/// by AST identifier. Used to install runtime code without running the
+	/// constructor.
+	pub fn link_with_immutables(
+		&self,
+		libraries: &BTreeMap<String, H160>,
+		immutables: &BTreeMap<String, H256>,
+	) -> Result<Vec<u8>, DeployError> {
+		let mut code = self.link(libraries)?;
+		for (id, offsets) in &self.immutable_references {
+			let value = immutables.get(id).ok_or_else(|| DeployError::MissingImmutable(id.clone()))?;
+			for offset in offsets {
+				patch(&mut code, *offset, value.as_bytes())?;
+			}
+		}
+		Ok(code)
+	}
+}
+
+/// Deploy a contract from its artifact with a `CREATE` transaction from
+/// `caller`. `constructor_args` is the ABI encoded constructor input,
+/// appended to the linked creation bytecode.
+pub async fn from_artifact<B: Backend>(
+	executor: &mut StackExecutor<B>,
+	caller: H160,
+	artifact: &Artifact,
+	constructor_args: &[u8],
+	libraries: &BTreeMap<String, H160>,
+	gas_limit: usize,
+) -> Result<Deployment, DeployError> {
+	let mut init_code = artifact.bytecode.link(libraries)?;
+	init_code.extend_from_slice(constructor_args);
+
+	let address = executor.create_address(CreateScheme::Legacy { caller }).await;
+	let used_gas = executor.used_gas();
+	match executor.transact_create(caller, U256::zero(), init_code, gas_limit).await {
+		ExitReason::Succeed(_) => Ok(Deployment {
+			address,
+			gas_used: executor.used_gas() - used_gas,
+		}),
+		reason => Err(DeployError::Failed(reason)),
+	}
+}
@@ -0,0 +1,363 @@
+//! Typed decoding of event logs.
+//!
+//! Events are described by their human-readable signature, such as
+//! `event Transfer(address indexed from, address indexed to, uint256 value)`,
+//! and decoded from the logs of an executor without an ABI library.
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+use crate::backend::Log;
+
+/// ABI type of an event parameter.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParamType {
+	/// `address`.
+	Address,
+	/// `bool`.
+	Bool,
+	/// `uintN`, with its size in bits.
+	Uint(usize),
+	/// `intN`, with its size in bits.
+	Int(usize),
+	/// `bytesN`, with its size in bytes.
+	FixedBytes(usize),
+	/// `bytes`.
+	Bytes,
+	/// `string`.
+	String,
+	/// `T[]`.
+	Array(Box<ParamType>),
+}
+
+impl ParamType {
+	/// Parse a type name. `uint` and `int` are aliases of `uint256` and
+	/// `int256`.
+	pub fn parse(name: &str) -> Option<ParamType> {
+		if let Some(inner) = name.strip_suffix("[]") {
+			return Some(ParamType::Array(Box::new(ParamType::parse(inner)?)))
+		}
+
+		fn size(digits: &str, default: usize) -> Option<usize> {
+			if digits.is_empty() {
+				Some(default)
+			} else {
+				digits.parse().ok()
+			}
+		}
+
+		Some(match name {
+			"address" => ParamType::Address,
+			"bool" => ParamType::Bool,
+			"bytes" => ParamType::Bytes,
+			"string" => ParamType::String,
+			_ => if let Some(bits) = name.strip_prefix("uint") {
+				match size(bits, 256)? {
+					bits if bits > 0 && bits <= 256 && bits % 8 == 0 => ParamType::Uint(bits),
+					_ => return None,
+				}
+			} else if let Some(bits) = name.strip_prefix("int") {
+				match size(bits, 256)? {
+					bits if bits > 0 && bits <= 256 && bits % 8 == 0 => ParamType::Int(bits),
+					_ => return None,
+				}
+			} else if let Some(len) = name.strip_prefix("bytes") {
+				match size(len, 0)? {
+					len if len > 0 && len <= 32 => ParamType::FixedBytes(len),
+					_ => return None,
+				}
+			} else {
+				return None
+			},
+		})
+	}
+
+	/// Whether values of the type are encoded out of place.
+	pub fn is_dynamic(&self) -> bool {
+		matches!(self, ParamType::Bytes | ParamType::String | ParamType::Array(_))
+	}
+}
+
+impl fmt::Display for ParamType {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ParamType::Address => write!(f, "address"),
+			ParamType::Bool => write!(f, "bool"),
+			ParamType::Uint(bits) => write!(f, "uint{}", bits),
+			ParamType::Int(bits) => write!(f, "int{}", bits),
+			ParamType::FixedBytes(len) => write!(f, "bytes{}", len),
+			ParamType::Bytes => write!(f, "bytes"),
+			ParamType::String => write!(f, "string"),
+			ParamType::Array(inner) => write!(f, "{}[]", inner),
+		}
+	}
+}
+
+/// Decoded parameter value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+	/// `address`.
+	Address(H160),
+	/// `bool`.
+	Bool(bool),
+	/// `uintN`.
+	Uint(U256),
+	/// `intN`, in two's complement.
+	Int(U256),
+	/// `bytesN`.
+	FixedBytes(Vec<u8>),
+	/// `bytes`.
+	Bytes(Vec<u8>),
+	/// `string`.
+	String(String),
+	/// `T[]`.
+	Array(Vec<Value>),
+	/// Indexed dynamic value, of which the topic only holds the hash.
+	Hash(H256),
+}
+
+/// Event parameter.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EventParam {
+	/// Parameter name, possibly empty.
+	pub name: String,
+	/// Parameter type.
+	pub kind: ParamType,
+	/// Whether the parameter is a topic.
+	pub indexed: bool,
+}
+
+/// Event description.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Event {
+	/// Event name.
+	pub name: String,
+	/// Parameters, in declaration order.
+	pub inputs: Vec<EventParam>,
+	/// Whether the event has no signature topic.
+	pub anonymous: bool,
+}
+
+impl Event {
+	/// Parse a human-readable event, such as
+	/// `event Transfer(address indexed from, address indexed to, uint256 value)`.
+	/// The `event` keyword and parameter names are optional.
+	pub fn parse(signature: &str) -> Option<Event> {
+		let signature = signature.trim();
+		let signature = signature.strip_prefix("event ").unwrap_or(signature).trim_start();
+		let open = signature.find('(')?;
+		let rest = signature[open + 1..].trim_end();
+		let (params, anonymous) = match rest.strip_suffix("anonymous") {
+			Some(params) => (params.trim_end(), true),
+			None => (rest, false),
+		};
+		let params = params.strip_suffix(')')?;
+		let name = signature[..open].trim();
+		if name.is_empty() {
+			return None
+		}
+
+		let mut inputs = Vec::new();
+		for param in params.split(',').map(str::trim).filter(|param| !param.is_empty()) {
+			let mut words = param.split_whitespace();
+			let kind = ParamType::parse(words.next()?)?;
+			let mut indexed = false;
+			let mut name = String::new();
+			for word in words {
+				match word {
+					"indexed" if !indexed && name.is_empty() => indexed = true,
+					_ if name.is_empty() => name = word.to_string(),
+					_ => return None,
+				}
+			}
+			inputs.push(EventParam { name, kind, indexed });
+		}
+
+		Some(Event { name: name.to_string(), inputs, anonymous })
+	}
+
+	/// Canonical signature, such as `Transfer(address,address,uint256)`.
+	pub fn signature(&self) -> String {
+		let kinds = self.inputs.iter()
+			.map(|param| param.kind.to_string())
+			.collect::<Vec<_>>();
+		alloc::format!("{}({})", self.name, kinds.join(","))
+	}
+
+	/// Signature topic, the hash of the canonical signature.
+	pub fn topic(&self) -> H256 {
+		H256::from_slice(Keccak256::digest(self.signature().as_bytes()).as_slice())
+	}
+}
+
+/// Log decoding failure.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+	/// The first topic is not the event signature topic.
+	SignatureMismatch,
+	/// The number of topics does not match the indexed parameters.
+	TopicCount,
+	/// The topics or data are not a valid encoding of the parameters.
+	InvalidData,
+}
+
+/// Decode the parameters of `event` from `log`, in declaration order, with
+/// their names.
+pub fn decode(log: &Log, event: &Event) -> Result<Vec<(String, Value)>, DecodeError> {
+	let mut topics = log.topics.iter();
+	if !event.anonymous && topics.next() != Some(&event.topic()) {
+		return Err(DecodeError::SignatureMismatch)
+	}
+	if topics.len() != event.inputs.iter().filter(|param| param.indexed).count() {
+		return Err(DecodeError::TopicCount)
+	}
+
+	let kinds = event.inputs.iter()
+		.filter(|param| !param.indexed)
+		.map(|param| param.kind.clone())
+		.collect::<Vec<_>>();
+	let mut data = decode_tuple(&kinds, &log.data)?.into_iter();
+
+	let mut values = Vec::with_capacity(event.inputs.len());
+	for param in &event.inputs {
+		let value = if param.indexed {
+			let topic = topics.next().ok_or(DecodeError::TopicCount)?;
+			if param.kind.is_dynamic() {
+				Value::Hash(*topic)
+			} else {
+				decode_word(&param.kind, topic.as_bytes())?
+			}
+		} else {
+			data.next().ok_or(DecodeError::InvalidData)?
+		};
+		values.push((param.name.clone(), value));
+	}
+	Ok(values)
+}
+
+fn decode_tuple(kinds: &[ParamType], data: &[u8]) -> Result<Vec<Value>, DecodeError> {
+	let mut values = Vec::with_capacity(kinds.len());
+	for (i, kind) in kinds.iter().enumerate() {
+		let head = data.get(i * 32..(i + 1) * 32).ok_or(DecodeError::InvalidData)?;
+		values.push(if kind.is_dynamic() {
+			let offset = decode_usize(head)?;
+			decode_dynamic(kind, data.get(offset..).ok_or(DecodeError::InvalidData)?)?
+		} else {
+			decode_word(kind, head)?
+		});
+	}
+	Ok(values)
+}
+
+fn decode_dynamic(kind: &ParamType, data: &[u8]) -> Result<Value, DecodeError> {
+	let len = decode_usize(data.get(..32).ok_or(DecodeError::InvalidData)?)?;
+	let body = &data[32..];
+	match kind {
+		ParamType::Bytes | ParamType::String => {
+			let bytes = body.get(..len).ok_or(DecodeError::InvalidData)?.to_vec();
+			if *kind == ParamType::Bytes {
+				Ok(Value::Bytes(bytes))
+			} else {
+				String::from_utf8(bytes).map(Value::String).map_err(|_| DecodeError::InvalidData)
+			}
+		},
+		ParamType::Array(inner) => {
+			if len > body.len() / 32 {
+				return Err(DecodeError::InvalidData)
+			}
+			let kinds = alloc::vec![(**inner).clone(); len];
+			decode_tuple(&kinds, body).map(Value::Array)
+		},
+		_ => Err(DecodeError::InvalidData),
+	}
+}
+
+fn decode_word(kind: &ParamType, word: &[u8]) -> Result<Value, DecodeError> {
+	let value = U256::from_big_endian(word);
+	Ok(match kind {
+		ParamType::Address if value.bits() <= 160 => Value::Address(H160::from_slice(&word[12..])),
+		ParamType::Bool if value <= U256::one() => Value::Bool(!value.is_zero()),
+		ParamType::Uint(bits) if value.bits() <= *bits => Value::Uint(value),
+		ParamType::Int(_) => Value::Int(value),
+		ParamType::FixedBytes(len) if word[*len..].iter().all(|byte| *byte == 0) =>
+			Value::FixedBytes(word[..*len].to_vec()),
+		_ => return Err(DecodeError::InvalidData),
+	})
+}
+
+fn decode_usize(word: &[u8]) -> Result<usize, DecodeError> {
+	let value = U256::from_big_endian(word);
+	if value > U256::from(u32::MAX) {
+		return Err(DecodeError::InvalidData)
+	}
+	Ok(value.as_usize())
+}
+
+/// A decoded log.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DecodedLog {
+	/// Source address.
+	pub address: H160,
+	/// Name of the matched event.
+	pub event: String,
+	/// Parameters, in declaration order, with their names.
+	pub params: Vec<(String, Value)>,
+}
+
+/// Matcher of logs against a set of events, optionally restricted to
+/// source addresses.
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+	addresses: Vec<H160>,
+	events: Vec<Event>,
+}
+
+impl EventFilter {
+	/// Create a filter matching no event.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Also match the given event.
+	pub fn event(mut self, event: Event) -> Self {
+		self.events.push(event);
+		self
+	}
+
+	/// Only match logs from the given address, or any of the addresses given
+	/// in previous calls.
+	pub fn address(mut self, address: H160) -> Self {
+		self.addresses.push(address);
+		self
+	}
+
+	/// Decode the log with the first event it is a valid encoding of.
+	pub fn decode(&self, log: &Log) -> Option<DecodedLog> {
+		if !self.addresses.is_empty() && !self.addresses.contains(&log.address) {
+			return None
+		}
+
+		self.events.iter().find_map(|event| {
+			decode(log, event).ok().map(|params| DecodedLog {
+				address: log.address,
+				event: event.name.clone(),
+				params,
+			})
+		})
+	}
+
+	/// Whether the log matches any event of the filter.
+	pub fn matches(&self, log: &Log) -> bool {
+		self.decode(log).is_some()
+	}
+
+	/// Decode the matching logs, in order.
+	pub fn filter<'a, I: IntoIterator<Item=&'a Log>>(&self, logs: I) -> Vec<DecodedLog> {
+		logs.into_iter().filter_map(|log| self.decode(log)).collect()
+	}
+}
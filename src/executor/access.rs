@@ -0,0 +1,209 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use primitive_types::{H160, H256};
+
+use crate::backend::{Apply, Witness};
+
+/// Set of state locations read or written by an execution.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccessSet {
+	/// Accounts whose balance, nonce, code or existence was accessed.
+	pub accounts: BTreeSet<H160>,
+	/// Storage slots accessed.
+	pub storage: BTreeSet<(H160, H256)>,
+	/// Accounts whose whole storage was cleared.
+	pub cleared_storage: BTreeSet<H160>,
+}
+
+impl AccessSet {
+	/// Locations read by an execution, as recorded in a witness.
+	pub fn from_witness(witness: &Witness) -> Self {
+		let mut set = Self::default();
+		for (address, account) in &witness.accounts {
+			if account.exists.is_some() || account.basic.is_some() || account.code_hash.is_some() ||
+				account.code_size.is_some() || account.code.is_some()
+			{
+				set.accounts.insert(*address);
+			}
+			set.storage.extend(account.storage.keys().map(|index| (*address, *index)));
+		}
+		set
+	}
+
+	/// Locations written by the given applies.
+	pub fn from_applies<'a>(applies: impl IntoIterator<Item=&'a Apply<BTreeMap<H256, H256>>>) -> Self {
+		let mut set = Self::default();
+		for apply in applies {
+			match apply {
+				Apply::Modify { address, storage, reset_storage, .. } => {
+					set.accounts.insert(*address);
+					set.storage.extend(storage.keys().map(|index| (*address, *index)));
+					if *reset_storage {
+						set.cleared_storage.insert(*address);
+					}
+				},
+				Apply::Delete { address } => {
+					set.accounts.insert(*address);
+					set.cleared_storage.insert(*address);
+				},
+			}
+		}
+		set
+	}
+
+	/// Whether nothing was accessed.
+	pub fn is_empty(&self) -> bool {
+		self.accounts.is_empty() && self.storage.is_empty() && self.cleared_storage.is_empty()
+	}
+
+	/// Add every location of `other`.
+	pub fn extend(&mut self, other: &AccessSet) {
+		self.accounts.extend(other.accounts.iter().cloned());
+		self.storage.extend(other.storage.iter().cloned());
+		self.cleared_storage.extend(other.cleared_storage.iter().cloned());
+	}
+
+	/// Whether both sets share a location. A cleared storage overlaps every
+	/// slot of its account.
+	pub fn intersects(&self, other: &AccessSet) -> bool {
+		fn clears(set: &AccessSet, other: &AccessSet) -> bool {
+			set.cleared_storage.iter().any(|address| {
+				other.cleared_storage.contains(address) ||
+					other.storage.range((*address, H256::zero())..=(*address, H256::repeat_byte(0xff)))
+						.next().is_some()
+			})
+		}
+
+		self.accounts.intersection(&other.accounts).next().is_some() ||
+			self.storage.intersection(&other.storage).next().is_some() ||
+			clears(self, other) || clears(other, self)
+	}
+}
+
+/// State locations by field, finer than `AccessSet`. An address in
+/// `accounts` stands for the existence of the account, which overlaps its
+/// balance, nonce and code, and an address in `cleared_storage` overlaps
+/// every storage slot of the account.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocationSet {
+	/// Accounts whose existence or emptiness was read, or which were
+	/// deleted.
+	pub accounts: BTreeSet<H160>,
+	/// Account balances.
+	pub balances: BTreeSet<H160>,
+	/// Account nonces.
+	pub nonces: BTreeSet<H160>,
+	/// Account code, including its size and hash.
+	pub code: BTreeSet<H160>,
+	/// Storage slots.
+	pub storage: BTreeSet<(H160, H256)>,
+	/// Accounts whose whole storage was cleared.
+	pub cleared_storage: BTreeSet<H160>,
+}
+
+impl LocationSet {
+	/// Whether the set has no location.
+	pub fn is_empty(&self) -> bool {
+		self.accounts.is_empty() && self.balances.is_empty() && self.nonces.is_empty() &&
+			self.code.is_empty() && self.storage.is_empty() && self.cleared_storage.is_empty()
+	}
+
+	/// Add every location of `other`.
+	pub fn extend(&mut self, other: &LocationSet) {
+		self.accounts.extend(other.accounts.iter().cloned());
+		self.balances.extend(other.balances.iter().cloned());
+		self.nonces.extend(other.nonces.iter().cloned());
+		self.code.extend(other.code.iter().cloned());
+		self.storage.extend(other.storage.iter().cloned());
+		self.cleared_storage.extend(other.cleared_storage.iter().cloned());
+	}
+
+	/// Locations of this set overlapping a location of `other`.
+	pub fn intersection(&self, other: &LocationSet) -> LocationSet {
+		let field = |set: &BTreeSet<H160>, overlaps: &dyn Fn(&H160) -> bool| {
+			set.iter().filter(|address| overlaps(address)).cloned().collect()
+		};
+
+		LocationSet {
+			accounts: field(&self.accounts, &|address| other.has_account_field(address)),
+			balances: field(&self.balances, &|address| {
+				other.balances.contains(address) || other.accounts.contains(address)
+			}),
+			nonces: field(&self.nonces, &|address| {
+				other.nonces.contains(address) || other.accounts.contains(address)
+			}),
+			code: field(&self.code, &|address| {
+				other.code.contains(address) || other.accounts.contains(address)
+			}),
+			storage: self.storage.iter()
+				.filter(|(address, index)| {
+					other.storage.contains(&(*address, *index)) || other.cleared_storage.contains(address)
+				})
+				.cloned()
+				.collect(),
+			cleared_storage: field(&self.cleared_storage, &|address| other.has_storage(address)),
+		}
+	}
+
+	/// Whether both sets share a location.
+	pub fn intersects(&self, other: &LocationSet) -> bool {
+		!self.intersection(other).is_empty()
+	}
+
+	/// Accounts with a location in the set.
+	pub fn addresses(&self) -> BTreeSet<H160> {
+		self.accounts.iter()
+			.chain(&self.balances)
+			.chain(&self.nonces)
+			.chain(&self.code)
+			.chain(self.storage.iter().map(|(address, _)| address))
+			.chain(&self.cleared_storage)
+			.cloned()
+			.collect()
+	}
+
+	fn has_account_field(&self, address: &H160) -> bool {
+		self.accounts.contains(address) || self.balances.contains(address) ||
+			self.nonces.contains(address) || self.code.contains(address)
+	}
+
+	fn has_storage(&self, address: &H160) -> bool {
+		self.cleared_storage.contains(address) ||
+			self.storage.range((*address, H256::zero())..=(*address, H256::repeat_byte(0xff))).next().is_some()
+	}
+}
+
+/// Locations a transaction read and wrote. Reads include those of reverted
+/// frames, as they decided the outcome, while writes only include the
+/// changes kept. Changing a balance or a nonce also reads it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReadWriteSet {
+	/// Locations read.
+	pub reads: LocationSet,
+	/// Locations written.
+	pub writes: LocationSet,
+}
+
+impl ReadWriteSet {
+	/// Whether the outcome of this transaction may change if `earlier` is
+	/// executed before it, that is whether it reads a location `earlier`
+	/// writes.
+	pub fn depends_on(&self, earlier: &ReadWriteSet) -> bool {
+		self.reads.intersects(&earlier.writes)
+	}
+
+	/// Whether both transactions must be executed in order: one reads or
+	/// writes a location the other writes.
+	pub fn conflicts_with(&self, other: &ReadWriteSet) -> bool {
+		self.depends_on(other) || other.depends_on(self) || self.writes.intersects(&other.writes)
+	}
+
+	/// Add the reads and writes of `other`.
+	pub fn extend(&mut self, other: &ReadWriteSet) {
+		self.reads.extend(&other.reads);
+		self.writes.extend(&other.writes);
+	}
+}
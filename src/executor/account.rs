@@ -0,0 +1,43 @@
+use primitive_types::U256;
+
+use crate::Config;
+use crate::backend::Basic;
+
+/// Existence of an account, as defined by EIP-161.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccountState {
+	/// The account does not exist.
+	NonExistent,
+	/// The account exists, with a zero nonce, a zero balance and no code.
+	Empty,
+	/// The account exists and is not empty.
+	NonEmpty,
+}
+
+impl AccountState {
+	/// State of an existing account with the given nonce, balance and code
+	/// size.
+	pub fn of(basic: &Basic, code_size: usize) -> Self {
+		if basic.nonce == U256::zero() && basic.balance == U256::zero() && code_size == 0 {
+			AccountState::Empty
+		} else {
+			AccountState::NonEmpty
+		}
+	}
+
+	/// Whether the account does not exist or is empty.
+	pub fn is_empty(&self) -> bool {
+		*self != AccountState::NonEmpty
+	}
+
+	/// Whether the account is considered existing by `EXTCODEHASH`, and
+	/// by the account creation costs of calls and self-destructs. After
+	/// EIP-161 empty accounts are not.
+	pub fn exists(&self, config: &Config) -> bool {
+		match self {
+			AccountState::NonExistent => false,
+			AccountState::Empty => config.empty_considered_exists,
+			AccountState::NonEmpty => true,
+		}
+	}
+}
@@ -0,0 +1,156 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use primitive_types::H256;
+
+use crate::{AnalyzedCode, Config, Engine, Opcode};
+use crate::hasher::{Hasher, Keccak256Hasher};
+use crate::gasometer::static_opcode_gas;
+
+/// Default number of codes an `AnalysisCache` holds.
+pub const DEFAULT_ANALYSIS_CACHE_CAPACITY: usize = 4096;
+
+/// Analyzed code shared by call frames and, through
+/// `StackExecutor::set_analysis_cache`, by executions. Static gas and the
+/// threaded translation depend on the config, so code is keyed by its hash
+/// and the `fingerprint` of the config it was analyzed for: executors of
+/// different configs can share a cache without reusing each other's
+/// analysis. Once the cache holds its capacity, the least recently used
+/// code is evicted.
+#[derive(Clone, Debug)]
+pub struct AnalysisCache {
+	codes: BTreeMap<(H256, H256), (Arc<AnalyzedCode>, u64)>,
+	/// Keys of the cached codes by the clock of their last use.
+	recency: BTreeMap<u64, (H256, H256)>,
+	capacity: usize,
+	clock: u64,
+	hits: usize,
+	misses: usize,
+}
+
+impl Default for AnalysisCache {
+	fn default() -> Self {
+		Self::with_capacity(DEFAULT_ANALYSIS_CACHE_CAPACITY)
+	}
+}
+
+impl AnalysisCache {
+	/// Create a cache holding at most `capacity` codes.
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self {
+			codes: BTreeMap::new(),
+			recency: BTreeMap::new(),
+			capacity,
+			clock: 0,
+			hits: 0,
+			misses: 0,
+		}
+	}
+
+	/// Maximum number of cached codes.
+	pub fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	/// Fingerprint of what an analysis depends on in `config`: the static
+	/// gas of every opcode, overrides included, and the engine.
+	pub fn fingerprint(config: &Config) -> H256 {
+		let mut encoded = Vec::with_capacity(256 * 8 + 1);
+		for byte in 0..=255u8 {
+			let gas = static_opcode_gas(Opcode::parse(byte), config).map(|gas| gas as u64);
+			encoded.extend_from_slice(&gas.unwrap_or(u64::MAX).to_be_bytes());
+		}
+		encoded.push(match config.engine {
+			Engine::Interpreter => 0,
+			Engine::Threaded => 1,
+		});
+		Keccak256Hasher.hash(&encoded)
+	}
+
+	/// Analysis of the given code, computed on first use. Code larger than
+	/// `Config::max_code_size` can only be init code, run once, and is
+	/// analyzed without being cached.
+	pub fn get_or_analyze(&mut self, code: Vec<u8>, config: &Config) -> Arc<AnalyzedCode> {
+		self.get_or_analyze_with(code, config, Self::fingerprint(config), &Keccak256Hasher)
+	}
+
+	/// Analysis of the given code, cached by its hash under `hasher`. The
+	/// hash function must be the one of the executors sharing the cache, and
+	/// `fingerprint` the `fingerprint` of `config`, computed once by the
+	/// caller.
+	pub fn get_or_analyze_with(
+		&mut self,
+		code: Vec<u8>,
+		config: &Config,
+		fingerprint: H256,
+		hasher: &dyn Hasher,
+	) -> Arc<AnalyzedCode> {
+		let key = (hasher.hash(&code), fingerprint);
+		self.clock += 1;
+		if let Some((analysis, last_used)) = self.codes.get_mut(&key) {
+			self.hits += 1;
+			self.recency.remove(last_used);
+			self.recency.insert(self.clock, key);
+			*last_used = self.clock;
+			return analysis.clone()
+		}
+
+		self.misses += 1;
+		let len = code.len();
+		let mut analysis = AnalyzedCode::with_static_gas(
+			Arc::new(code),
+			|opcode| static_opcode_gas(opcode, config),
+		);
+		if config.engine == Engine::Threaded {
+			analysis = analysis.threaded();
+		}
+		let analysis = Arc::new(analysis);
+		if self.capacity > 0 && config.max_code_size.map(|limit| len <= limit).unwrap_or(true) {
+			if self.codes.len() >= self.capacity {
+				self.evict();
+			}
+			self.codes.insert(key, (analysis.clone(), self.clock));
+			self.recency.insert(self.clock, key);
+		}
+		analysis
+	}
+
+	/// Analysis of the code with the given hash for `config`, if cached.
+	pub fn get(&self, code_hash: &H256, config: &Config) -> Option<&Arc<AnalyzedCode>> {
+		self.codes.get(&(*code_hash, Self::fingerprint(config))).map(|(analysis, _)| analysis)
+	}
+
+	/// Drop the least recently used code.
+	fn evict(&mut self) {
+		if let Some((_, key)) = self.recency.pop_first() {
+			self.codes.remove(&key);
+		}
+	}
+
+	/// Number of lookups served from the cache.
+	pub fn hits(&self) -> usize {
+		self.hits
+	}
+
+	/// Number of lookups that analyzed code.
+	pub fn misses(&self) -> usize {
+		self.misses
+	}
+
+	/// Number of cached codes.
+	pub fn len(&self) -> usize {
+		self.codes.len()
+	}
+
+	/// Whether no code is cached.
+	pub fn is_empty(&self) -> bool {
+		self.codes.is_empty()
+	}
+
+	/// Drop every cached analysis.
+	pub fn clear(&mut self) {
+		self.codes.clear();
+		self.recency.clear();
+	}
+}
@@ -0,0 +1,132 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use primitive_types::{H160, H256};
+
+use crate::backend::{Apply, Log};
+use super::AccessSet;
+
+/// Owned, mergeable changes of one or more executions, with at most one
+/// operation per account.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ApplySet {
+	applies: BTreeMap<H160, Apply<BTreeMap<H256, H256>>>,
+	logs: Vec<Log>,
+	reads: AccessSet,
+}
+
+impl ApplySet {
+	/// Create an empty set.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Collect the changes of `StackExecutor::deconstruct`, or of any
+	/// sequence of operations, later operations winning.
+	pub fn from_applies<A, I, L>(values: A, logs: L) -> Self where
+		A: IntoIterator<Item=Apply<I>>,
+		I: IntoIterator<Item=(H256, H256)>,
+		L: IntoIterator<Item=Log>,
+	{
+		let mut set = Self::new();
+		for apply in values {
+			set.push(apply);
+		}
+		set.logs.extend(logs);
+		set
+	}
+
+	/// Set the state read while producing the changes, considered by
+	/// `conflicts_with`.
+	pub fn with_reads(mut self, reads: AccessSet) -> Self {
+		self.reads = reads;
+		self
+	}
+
+	/// Add an operation over the current changes of its account.
+	///
+	/// A modification keeps the code and storage changes it does not
+	/// override, unless it resets storage. A modification after a deletion
+	/// recreates the account with empty code and storage.
+	pub fn push<I: IntoIterator<Item=(H256, H256)>>(&mut self, apply: Apply<I>) {
+		let (address, basic, code, storage, reset_storage) = match apply {
+			Apply::Modify { address, basic, code, storage, reset_storage } => {
+				(address, basic, code, storage, reset_storage)
+			},
+			Apply::Delete { address } => {
+				self.applies.insert(address, Apply::Delete { address });
+				return
+			},
+		};
+
+		let (code, mut merged, reset_storage) = match self.applies.remove(&address) {
+			Some(Apply::Modify { code: previous_code, storage: previous, reset_storage: previous_reset, .. }) => (
+				code.or(previous_code),
+				if reset_storage { BTreeMap::new() } else { previous },
+				reset_storage || previous_reset,
+			),
+			Some(Apply::Delete { .. }) => (Some(code.unwrap_or_default()), BTreeMap::new(), true),
+			None => (code, BTreeMap::new(), reset_storage),
+		};
+		merged.extend(storage);
+
+		self.applies.insert(address, Apply::Modify { address, basic, code, storage: merged, reset_storage });
+	}
+
+	/// Apply the changes of `other` over these ones, appending its logs
+	/// and adding its reads.
+	pub fn merge(&mut self, other: ApplySet) {
+		for apply in other.applies.into_values() {
+			self.push(apply);
+		}
+		self.logs.extend(other.logs);
+		self.reads.extend(&other.reads);
+	}
+
+	/// Locations written by the changes.
+	pub fn writes(&self) -> AccessSet {
+		AccessSet::from_applies(self.applies.values())
+	}
+
+	/// State read while producing the changes.
+	pub fn reads(&self) -> &AccessSet {
+		&self.reads
+	}
+
+	/// Whether the changes of both sets depend on their order: one writes
+	/// state the other reads or writes.
+	pub fn conflicts_with(&self, other: &ApplySet) -> bool {
+		let (writes, other_writes) = (self.writes(), other.writes());
+		writes.intersects(&other_writes) || writes.intersects(&other.reads) || self.reads.intersects(&other_writes)
+	}
+
+	/// Operation on the given account.
+	pub fn get(&self, address: &H160) -> Option<&Apply<BTreeMap<H256, H256>>> {
+		self.applies.get(address)
+	}
+
+	/// Operations, by account.
+	pub fn applies(&self) -> impl Iterator<Item=&Apply<BTreeMap<H256, H256>>> {
+		self.applies.values()
+	}
+
+	/// Logs, in order.
+	pub fn logs(&self) -> &[Log] {
+		&self.logs
+	}
+
+	/// Number of accounts changed.
+	pub fn len(&self) -> usize {
+		self.applies.len()
+	}
+
+	/// Whether no account is changed and no log emitted.
+	pub fn is_empty(&self) -> bool {
+		self.applies.is_empty() && self.logs.is_empty()
+	}
+
+	/// Operations and logs, in the form taken by `ApplyBackend::apply`.
+	pub fn into_applies(self) -> (Vec<Apply<BTreeMap<H256, H256>>>, Vec<Log>) {
+		(self.applies.into_values().collect(), self.logs)
+	}
+}
@@ -0,0 +1,45 @@
+use alloc::collections::BTreeMap;
+
+use primitive_types::H160;
+
+/// Resources used by the frames running the code of an address, and the
+/// accesses to its storage.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
+pub struct AddressUsage {
+	/// Number of frames running the code of the address.
+	pub calls: usize,
+	/// Gas used by those frames, excluding the frames they entered.
+	pub gas_used: usize,
+	/// `SLOAD`s of the storage of the address.
+	pub storage_reads: usize,
+	/// `SSTORE`s to the storage of the address.
+	pub storage_writes: usize,
+}
+
+/// Gas and storage accesses of an execution, by address. Frames that
+/// revert or fail are included.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
+pub struct GasAttribution {
+	/// Usage of each address.
+	pub addresses: BTreeMap<H160, AddressUsage>,
+}
+
+impl GasAttribution {
+	/// Add the usage of another attribution.
+	pub fn merge(&mut self, other: GasAttribution) {
+		for (address, usage) in other.addresses {
+			let entry = self.addresses.entry(address).or_default();
+			entry.calls += usage.calls;
+			entry.gas_used += usage.gas_used;
+			entry.storage_reads += usage.storage_reads;
+			entry.storage_writes += usage.storage_writes;
+		}
+	}
+
+	/// Total gas attributed to addresses.
+	pub fn gas_used(&self) -> usize {
+		self.addresses.values().map(|usage| usage.gas_used).sum()
+	}
+}
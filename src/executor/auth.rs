@@ -0,0 +1,18 @@
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+/// Byte prefixing EIP-3074 authorization messages.
+pub const AUTH_MAGIC: u8 = 0x04;
+
+/// Message an authority signs to let `invoker` send calls on its behalf
+/// through `AUTHCALL`, per EIP-3074. `nonce` is the current nonce of the
+/// authority.
+pub fn auth_message(chain_id: U256, nonce: U256, invoker: H160, commit: H256) -> H256 {
+	let mut message = [0u8; 129];
+	message[0] = AUTH_MAGIC;
+	chain_id.to_big_endian(&mut message[1..33]);
+	nonce.to_big_endian(&mut message[33..65]);
+	message[77..97].copy_from_slice(&invoker[..]);
+	message[97..].copy_from_slice(&commit[..]);
+	H256::from_slice(Keccak256::digest(&message[..]).as_slice())
+}
@@ -0,0 +1,312 @@
+use alloc::vec::Vec;
+use std::sync::Arc;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::{Config, ExitReason};
+use crate::backend::ApplyBackend;
+use super::{BundleTransactionResult, ExecutionResult, StackExecutor, Transaction};
+
+/// Caller of system calls.
+pub const SYSTEM_ADDRESS: H160 = H160([
+	0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+	0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+]);
+/// Gas given to system calls. It is not counted towards the block gas.
+pub const SYSTEM_CALL_GAS: usize = 30_000_000;
+
+/// Address of the beacon roots contract (EIP-4788).
+pub const BEACON_ROOTS_ADDRESS: H160 = H160([
+	0x00, 0x0f, 0x3d, 0xf6, 0xd7, 0x32, 0x80, 0x7e, 0xf1, 0x31,
+	0x9f, 0xb7, 0xb8, 0xbb, 0x85, 0x22, 0xd0, 0xbe, 0xac, 0x02,
+]);
+/// Number of roots kept by the beacon roots contract, indexed by timestamp
+/// modulo this length.
+pub const BEACON_ROOTS_HISTORY_LENGTH: u64 = 8191;
+/// Deployed code of the beacon roots contract. It uses `PUSH0`.
+pub const BEACON_ROOTS_CODE: &[u8] = &[
+	0x33, 0x73, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+	0xff, 0xff, 0xff, 0xff, 0xff, 0xfe, 0x14, 0x60, 0x4d, 0x57, 0x60, 0x20, 0x36, 0x14, 0x60, 0x24,
+	0x57, 0x5f, 0x5f, 0xfd, 0x5b, 0x5f, 0x35, 0x80, 0x15, 0x60, 0x49, 0x57, 0x62, 0x00, 0x1f, 0xff,
+	0x81, 0x06, 0x90, 0x81, 0x54, 0x14, 0x60, 0x3c, 0x57, 0x5f, 0x5f, 0xfd, 0x5b, 0x62, 0x00, 0x1f,
+	0xff, 0x01, 0x54, 0x5f, 0x52, 0x60, 0x20, 0x5f, 0xf3, 0x5b, 0x5f, 0x5f, 0xfd, 0x5b, 0x62, 0x00,
+	0x1f, 0xff, 0x42, 0x06, 0x42, 0x81, 0x55, 0x5f, 0x35, 0x90, 0x62, 0x00, 0x1f, 0xff, 0x01, 0x55,
+	0x00,
+];
+
+/// Address of the history storage contract (EIP-2935).
+pub const HISTORY_STORAGE_ADDRESS: H160 = H160([
+	0x00, 0x00, 0xf9, 0x08, 0x27, 0xf1, 0xc5, 0x3a, 0x10, 0xcb,
+	0x7a, 0x02, 0x33, 0x5b, 0x17, 0x53, 0x20, 0x00, 0x29, 0x35,
+]);
+/// Number of block hashes kept by the history storage contract, indexed by
+/// block number modulo this length.
+pub const HISTORY_SERVE_WINDOW: u64 = 8191;
+/// Deployed code of the history storage contract. It uses `PUSH0`.
+pub const HISTORY_STORAGE_CODE: &[u8] = &[
+	0x33, 0x73, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+	0xff, 0xff, 0xff, 0xff, 0xff, 0xfe, 0x14, 0x60, 0x46, 0x57, 0x60, 0x20, 0x36, 0x03, 0x60, 0x42,
+	0x57, 0x5f, 0x35, 0x60, 0x01, 0x43, 0x03, 0x81, 0x11, 0x60, 0x42, 0x57, 0x61, 0x1f, 0xff, 0x81,
+	0x43, 0x03, 0x11, 0x60, 0x42, 0x57, 0x61, 0x1f, 0xff, 0x90, 0x06, 0x54, 0x5f, 0x52, 0x60, 0x20,
+	0x5f, 0xf3, 0x5b, 0x5f, 0x5f, 0xfd, 0x5b, 0x5f, 0x35, 0x61, 0x1f, 0xff, 0x60, 0x01, 0x43, 0x03,
+	0x06, 0x55, 0x00,
+];
+
+/// Validator withdrawal (EIP-4895).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Withdrawal {
+	/// Withdrawal index.
+	pub index: u64,
+	/// Index of the withdrawing validator.
+	pub validator_index: u64,
+	/// Recipient.
+	pub address: H160,
+	/// Amount, in gwei.
+	pub amount: u64,
+}
+
+/// Block to execute.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Block {
+	/// Transactions, in order.
+	pub transactions: Vec<Transaction>,
+	/// Withdrawals, credited after the transactions by `WithdrawalCredits`.
+	pub withdrawals: Vec<Withdrawal>,
+	/// Root of the parent beacon block, stored before the transactions by
+	/// `BeaconRoots` (EIP-4788). `None` before Cancun.
+	pub parent_beacon_block_root: Option<H256>,
+	/// Hash of the parent block, stored before the transactions by
+	/// `BlockHashHistory` (EIP-2935). `None` before Prague.
+	pub parent_hash: Option<H256>,
+}
+
+/// State change made by a block outside of its transactions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SystemOperation {
+	/// Call the address from `SYSTEM_ADDRESS` with `SYSTEM_CALL_GAS`,
+	/// without transferring value, charging fees or incrementing a nonce.
+	/// Logs of the call are discarded.
+	Call {
+		/// Called address.
+		address: H160,
+		/// Call data.
+		data: Vec<u8>,
+	},
+	/// Credit an account, for example with a withdrawal.
+	Credit {
+		/// Credited address.
+		address: H160,
+		/// Credited amount, in wei.
+		amount: U256,
+	},
+}
+
+/// Chain-defined system operations run before and after the transactions
+/// of every block.
+pub trait BlockHook: Send + Sync {
+	/// Operations run before the transactions of the block.
+	fn pre_transactions(&self, _block: &Block) -> Vec<SystemOperation> {
+		Vec::new()
+	}
+
+	/// Operations run after the transactions of the block.
+	fn post_transactions(&self, _block: &Block) -> Vec<SystemOperation> {
+		Vec::new()
+	}
+}
+
+/// Credit the withdrawals of the block after its transactions (EIP-4895).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WithdrawalCredits;
+
+impl BlockHook for WithdrawalCredits {
+	fn post_transactions(&self, block: &Block) -> Vec<SystemOperation> {
+		block.withdrawals.iter()
+			.map(|withdrawal| SystemOperation::Credit {
+				address: withdrawal.address,
+				amount: U256::from(withdrawal.amount) * U256::exp10(9),
+			})
+			.collect()
+	}
+}
+
+/// Store the parent beacon block root in the beacon roots contract before
+/// the transactions of blocks having one (EIP-4788).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BeaconRoots;
+
+impl BlockHook for BeaconRoots {
+	fn pre_transactions(&self, block: &Block) -> Vec<SystemOperation> {
+		match block.parent_beacon_block_root {
+			Some(root) => alloc::vec![SystemOperation::Call {
+				address: BEACON_ROOTS_ADDRESS,
+				data: root.as_bytes().to_vec(),
+			}],
+			None => Vec::new(),
+		}
+	}
+}
+
+/// Store the parent block hash in the history storage contract before the
+/// transactions of blocks having one (EIP-2935). Executors with
+/// `Config::has_block_hash_history` serve `BLOCKHASH` from that contract.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BlockHashHistory;
+
+impl BlockHook for BlockHashHistory {
+	fn pre_transactions(&self, block: &Block) -> Vec<SystemOperation> {
+		match block.parent_hash {
+			Some(hash) => alloc::vec![SystemOperation::Call {
+				address: HISTORY_STORAGE_ADDRESS,
+				data: hash.as_bytes().to_vec(),
+			}],
+			None => Vec::new(),
+		}
+	}
+}
+
+/// Result of a system call.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SystemCallResult {
+	/// Called address.
+	pub address: H160,
+	/// Exit reason. Succeeds without executing for addresses without code.
+	pub reason: ExitReason,
+	/// Return data.
+	pub output: Vec<u8>,
+}
+
+/// Result of a block execution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlockResult {
+	/// Per-transaction results, in order.
+	pub results: Vec<BundleTransactionResult>,
+	/// Gas used by all transactions.
+	pub cumulative_gas: usize,
+	/// Results of the system calls, in order, before and after the
+	/// transactions.
+	pub system_calls: Vec<SystemCallResult>,
+}
+
+/// Executor of whole blocks, committing their changes to the backend.
+/// Hooks run their system operations in the order they were added.
+pub struct BlockExecutor<B> {
+	backend: Arc<B>,
+	config: Arc<Config>,
+	hooks: Vec<Arc<dyn BlockHook>>,
+}
+
+impl<B: ApplyBackend> BlockExecutor<B> {
+	/// Create a block executor over the given backend.
+	pub fn new(backend: B, config: Arc<Config>) -> Self {
+		Self {
+			backend: Arc::new(backend),
+			config,
+			hooks: Vec::new(),
+		}
+	}
+
+	/// Also run the system operations of the given hook.
+	pub fn with_hook(mut self, hook: Arc<dyn BlockHook>) -> Self {
+		self.hooks.push(hook);
+		self
+	}
+
+	/// Set the config of the next blocks.
+	pub fn set_config(&mut self, config: Arc<Config>) {
+		self.config = config;
+	}
+
+	/// Backend holding the state after the executed blocks.
+	pub fn backend(&self) -> &B {
+		&self.backend
+	}
+
+	/// Mutable backend, for example to move it to the next block.
+	pub fn backend_mut(&mut self) -> &mut B {
+		Arc::get_mut(&mut self.backend).expect("executors are dropped after each step")
+	}
+
+	/// Take the backend.
+	pub fn into_backend(self) -> B {
+		Arc::try_unwrap(self.backend).ok().expect("executors are dropped after each step")
+	}
+
+	/// Execute the block in the environment of the backend: the pre-block
+	/// system operations, the transactions, then the post-block system
+	/// operations. Fails with the first backend error met, possibly with
+	/// part of the block applied.
+	pub async fn execute_block(&mut self, block: &Block) -> Result<BlockResult, B::Error> {
+		let mut system_calls = Vec::new();
+
+		let operations = self.hooks.iter()
+			.flat_map(|hook| hook.pre_transactions(block))
+			.collect::<Vec<_>>();
+		for operation in operations {
+			if let Some(result) = self.system_operation(operation).await? {
+				system_calls.push(result);
+			}
+		}
+
+		let mut results = Vec::with_capacity(block.transactions.len());
+		let mut cumulative_gas = 0;
+		for transaction in &block.transactions {
+			let result = self.transaction(transaction.clone()).await?;
+			cumulative_gas += result.used_gas;
+			results.push(result);
+		}
+
+		let operations = self.hooks.iter()
+			.flat_map(|hook| hook.post_transactions(block))
+			.collect::<Vec<_>>();
+		for operation in operations {
+			if let Some(result) = self.system_operation(operation).await? {
+				system_calls.push(result);
+			}
+		}
+
+		Ok(BlockResult { results, cumulative_gas, system_calls })
+	}
+
+	async fn transaction(&mut self, transaction: Transaction) -> Result<BundleTransactionResult, B::Error> {
+		let mut executor = StackExecutor::new(self.backend.clone(), transaction.gas_limit, self.config.clone());
+		let ExecutionResult { reason, output, .. } = executor.transact(transaction).await;
+		if let Some(e) = executor.take_backend_error() {
+			return Err(e)
+		}
+		let used_gas = executor.used_gas();
+		let (applies, logs) = executor.deconstruct();
+		let logs = logs.into_iter().collect::<Vec<_>>();
+
+		let delete_empty = !self.config.empty_considered_exists;
+		self.backend_mut().apply(applies, logs.clone(), delete_empty).await?;
+
+		Ok(BundleTransactionResult { reason, output, used_gas, logs })
+	}
+
+	async fn system_operation(
+		&mut self,
+		operation: SystemOperation,
+	) -> Result<Option<SystemCallResult>, B::Error> {
+		let mut executor = StackExecutor::new(self.backend.clone(), SYSTEM_CALL_GAS, self.config.clone());
+		let result = match operation {
+			SystemOperation::Call { address, data } => {
+				let (reason, output) = executor.transact_system_call(address, data).await;
+				Some(SystemCallResult { address, reason, output })
+			},
+			SystemOperation::Credit { address, amount } => {
+				executor.deposit(address, amount).await;
+				None
+			},
+		};
+		if let Some(e) = executor.take_backend_error() {
+			return Err(e)
+		}
+
+		let (applies, _) = executor.deconstruct();
+		let delete_empty = !self.config.empty_considered_exists;
+		self.backend_mut().apply(applies, Vec::new(), delete_empty).await?;
+
+		Ok(result)
+	}
+}
@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use crate::Config;
+use crate::backend::Backend;
+use super::{AsyncPrecompile, CodeOverrides, FeePolicy, GasHook, PrecompileFn, StackExecutor};
+
+/// Optional component installed on the executor built by `ExecutorBuilder`.
+/// `()` installs nothing. Layers are installed through the executor
+/// setters, so an executor built with a layer behaves as one configured by
+/// hand.
+pub trait ExecutorLayer<B: Backend> {
+	/// Install the component on the executor being built.
+	fn install(self, executor: &mut StackExecutor<B>);
+}
+
+impl<B: Backend> ExecutorLayer<B> for () {
+	#[inline]
+	fn install(self, _executor: &mut StackExecutor<B>) {}
+}
+
+impl<B: Backend> ExecutorLayer<B> for PrecompileFn {
+	fn install(self, executor: &mut StackExecutor<B>) {
+		executor.set_precompile(self);
+	}
+}
+
+impl<B: Backend> ExecutorLayer<B> for CodeOverrides {
+	fn install(self, executor: &mut StackExecutor<B>) {
+		executor.set_code_overrides(Some(self));
+	}
+}
+
+/// Asynchronous precompiles installed by `ExecutorBuilder::async_precompile`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsyncPrecompileLayer<T>(pub T);
+
+impl<B: Backend, T: AsyncPrecompile<B> + 'static> ExecutorLayer<B> for AsyncPrecompileLayer<T> {
+	fn install(self, executor: &mut StackExecutor<B>) {
+		executor.set_async_precompile(Arc::new(self.0));
+	}
+}
+
+/// Gas hook inspecting every charged instruction, installed by
+/// `ExecutorBuilder::inspector`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InspectorLayer<H>(pub H);
+
+impl<B: Backend, H: GasHook + 'static> ExecutorLayer<B> for InspectorLayer<H> {
+	fn install(self, executor: &mut StackExecutor<B>) {
+		executor.set_gas_hook(Arc::new(self.0));
+	}
+}
+
+/// Fee policy installed by `ExecutorBuilder::fee_policy`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FeePolicyLayer<F>(pub F);
+
+impl<B: Backend, F: FeePolicy + 'static> ExecutorLayer<B> for FeePolicyLayer<F> {
+	fn install(self, executor: &mut StackExecutor<B>) {
+		executor.set_fee_policy(Arc::new(self.0));
+	}
+}
+
+/// Builder of a `StackExecutor`, composing the precompiles `P`, the
+/// asynchronous precompiles `A`, the inspector `I`, the fee policy `F` and
+/// the code overrides `O` on top of the config. Each layer is a type
+/// parameter, `()` when unset, and is installed once by `build`. Other
+/// options are set on the built executor.
+pub struct ExecutorBuilder<B: Backend, P = (), A = (), I = (), F = (), O = ()> {
+	backend: Arc<B>,
+	gas_limit: usize,
+	config: Arc<Config>,
+	precompile: P,
+	async_precompile: A,
+	inspector: I,
+	fee_policy: F,
+	overrides: O,
+}
+
+impl<B: Backend> ExecutorBuilder<B> {
+	/// Builder of an executor with the given gas limit and config, and no
+	/// layer.
+	pub fn new(backend: Arc<B>, gas_limit: usize, config: Arc<Config>) -> Self {
+		Self {
+			backend,
+			gas_limit,
+			config,
+			precompile: (),
+			async_precompile: (),
+			inspector: (),
+			fee_policy: (),
+			overrides: (),
+		}
+	}
+}
+
+impl<B: Backend, P, A, I, F, O> ExecutorBuilder<B, P, A, I, F, O> {
+	/// Replace the gas limit.
+	pub fn gas_limit(mut self, gas_limit: usize) -> Self {
+		self.gas_limit = gas_limit;
+		self
+	}
+
+	/// Replace the config.
+	pub fn config(mut self, config: Arc<Config>) -> Self {
+		self.config = config;
+		self
+	}
+
+	/// Use the given synchronous precompiles.
+	pub fn precompile(self, precompile: PrecompileFn) -> ExecutorBuilder<B, PrecompileFn, A, I, F, O> {
+		ExecutorBuilder {
+			backend: self.backend,
+			gas_limit: self.gas_limit,
+			config: self.config,
+			precompile,
+			async_precompile: self.async_precompile,
+			inspector: self.inspector,
+			fee_policy: self.fee_policy,
+			overrides: self.overrides,
+		}
+	}
+
+	/// Consult the given asynchronous precompiles for calls to addresses the
+	/// synchronous precompiles do not handle.
+	pub fn async_precompile<T: AsyncPrecompile<B> + 'static>(
+		self,
+		precompile: T,
+	) -> ExecutorBuilder<B, P, AsyncPrecompileLayer<T>, I, F, O> {
+		ExecutorBuilder {
+			backend: self.backend,
+			gas_limit: self.gas_limit,
+			config: self.config,
+			precompile: self.precompile,
+			async_precompile: AsyncPrecompileLayer(precompile),
+			inspector: self.inspector,
+			fee_policy: self.fee_policy,
+			overrides: self.overrides,
+		}
+	}
+
+	/// Invoke the given gas hook after the gas of every instruction is
+	/// charged.
+	pub fn inspector<H: GasHook + 'static>(self, hook: H) -> ExecutorBuilder<B, P, A, InspectorLayer<H>, F, O> {
+		ExecutorBuilder {
+			backend: self.backend,
+			gas_limit: self.gas_limit,
+			config: self.config,
+			precompile: self.precompile,
+			async_precompile: self.async_precompile,
+			inspector: InspectorLayer(hook),
+			fee_policy: self.fee_policy,
+			overrides: self.overrides,
+		}
+	}
+
+	/// Use the given fee policy instead of `DefaultFeePolicy`.
+	pub fn fee_policy<T: FeePolicy + 'static>(self, policy: T) -> ExecutorBuilder<B, P, A, I, FeePolicyLayer<T>, O> {
+		ExecutorBuilder {
+			backend: self.backend,
+			gas_limit: self.gas_limit,
+			config: self.config,
+			precompile: self.precompile,
+			async_precompile: self.async_precompile,
+			inspector: self.inspector,
+			fee_policy: FeePolicyLayer(policy),
+			overrides: self.overrides,
+		}
+	}
+
+	/// Run the replacement code of the given overrides.
+	pub fn overrides(self, overrides: CodeOverrides) -> ExecutorBuilder<B, P, A, I, F, CodeOverrides> {
+		ExecutorBuilder {
+			backend: self.backend,
+			gas_limit: self.gas_limit,
+			config: self.config,
+			precompile: self.precompile,
+			async_precompile: self.async_precompile,
+			inspector: self.inspector,
+			fee_policy: self.fee_policy,
+			overrides,
+		}
+	}
+}
+
+impl<B, P, A, I, F, O> ExecutorBuilder<B, P, A, I, F, O> where
+	B: Backend,
+	P: ExecutorLayer<B>,
+	A: ExecutorLayer<B>,
+	I: ExecutorLayer<B>,
+	F: ExecutorLayer<B>,
+	O: ExecutorLayer<B>,
+{
+	/// Create the executor and install the layers.
+	pub fn build(self) -> StackExecutor<B> {
+		let mut executor = StackExecutor::new(self.backend, self.gas_limit, self.config);
+		self.precompile.install(&mut executor);
+		self.async_precompile.install(&mut executor);
+		self.inspector.install(&mut executor);
+		self.fee_policy.install(&mut executor);
+		self.overrides.install(&mut executor);
+		executor
+	}
+}
@@ -0,0 +1,72 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use std::sync::Arc;
+
+use primitive_types::H256;
+
+use crate::{Config, ExitReason};
+use crate::backend::{Apply, ApplyBackend, Backend, BlockOverrides, Log, OverlayBackend};
+use super::{ExecutionResult, StackExecutor, Transaction};
+
+/// Result of a single transaction of a bundle.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BundleTransactionResult {
+	/// Exit reason.
+	pub reason: ExitReason,
+	/// Return data.
+	pub output: Vec<u8>,
+	/// Gas used, after refunds.
+	pub used_gas: usize,
+	/// Logs emitted.
+	pub logs: Vec<Log>,
+}
+
+/// Result of a bundle simulation.
+#[derive(Clone, Debug)]
+pub struct BundleResult {
+	/// Per-transaction results, in order.
+	pub results: Vec<BundleTransactionResult>,
+	/// Gas used by all transactions.
+	pub cumulative_gas: usize,
+	/// State changes of the whole bundle.
+	pub diff: Vec<Apply<BTreeMap<H256, H256>>>,
+}
+
+/// Execute transactions in sequence, each seeing the changes of the
+/// previous ones, without committing anything to the backend. Fails with
+/// the first backend error met.
+pub async fn simulate_bundle<B: Backend>(
+	backend: Arc<B>,
+	config: &Config,
+	transactions: Vec<Transaction>,
+	overrides: BlockOverrides,
+) -> Result<BundleResult, B::Error> {
+	let config = Arc::new(config.clone());
+	let mut overlay = Arc::new(OverlayBackend::new(backend, overrides));
+	let mut results = Vec::with_capacity(transactions.len());
+	let mut cumulative_gas = 0;
+
+	for transaction in transactions {
+		let mut executor = StackExecutor::new(overlay.clone(), transaction.gas_limit, config.clone());
+		let ExecutionResult { reason, output, .. } = executor.transact(transaction).await;
+		if let Some(e) = executor.take_backend_error() {
+			return Err(e)
+		}
+		let used_gas = executor.used_gas();
+		let (applies, logs) = executor.deconstruct();
+		let logs = logs.into_iter().collect::<Vec<_>>();
+
+		Arc::get_mut(&mut overlay)
+			.expect("executor was dropped by deconstruct")
+			.apply(applies, logs.clone(), !config.empty_considered_exists).await?;
+
+		cumulative_gas += used_gas;
+		results.push(BundleTransactionResult { reason, output, used_gas, logs });
+	}
+
+	Ok(BundleResult {
+		results,
+		cumulative_gas,
+		diff: overlay.diff(),
+	})
+}
@@ -0,0 +1,67 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll};
+use core::time::Duration;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Token aborting an execution from another task or thread. The executor
+/// checks it before every instruction and exits with `ExitFatal::Cancelled`.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+	/// Create a new token, not yet cancelled.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Cancel every execution holding a clone of this token.
+	pub fn cancel(&self) {
+		self.0.store(true, Ordering::Relaxed);
+	}
+
+	/// Whether the token was cancelled.
+	pub fn is_cancelled(&self) -> bool {
+		self.0.load(Ordering::Relaxed)
+	}
+}
+
+/// Number of instructions between two reads of the clock by executors
+/// with a deadline.
+pub const DEADLINE_CHECK_INTERVAL: usize = 256;
+
+/// Deadline giving an execution `gas_limit` gas at `gas_per_second`, from
+/// now. `None` if it is too far to be represented, or if `gas_per_second`
+/// is zero.
+pub fn gas_deadline(gas_limit: usize, gas_per_second: u64) -> Option<Instant> {
+	if gas_per_second == 0 {
+		return None
+	}
+	let budget = Duration::try_from_secs_f64(gas_limit as f64 / gas_per_second as f64).ok()?;
+	Instant::now().checked_add(budget)
+}
+
+/// Future returning pending once, letting the async runtime run other
+/// tasks before polling it again.
+pub(crate) struct YieldNow(bool);
+
+impl YieldNow {
+	pub(crate) fn new() -> Self {
+		YieldNow(false)
+	}
+}
+
+impl Future for YieldNow {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+		if self.0 {
+			return Poll::Ready(())
+		}
+		self.0 = true;
+		context.waker().wake_by_ref();
+		Poll::Pending
+	}
+}
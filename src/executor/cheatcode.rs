@@ -0,0 +1,118 @@
+use alloc::vec::Vec;
+
+use primitive_types::{H160, H256, U256};
+
+/// Address of the cheatcode contract, as used by Foundry and hevm.
+pub const CHEATCODE_ADDRESS: H160 = H160([
+	0x71, 0x09, 0x70, 0x9e, 0xcf, 0xa9, 0x1a, 0x80, 0x62, 0x6f,
+	0xf3, 0x98, 0x9d, 0x68, 0xf6, 0x7f, 0x5b, 0x1d, 0xd1, 0x2d,
+]);
+
+/// Caller override set by `prank` or `startPrank`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Prank {
+	/// Caller seen by the pranked calls.
+	pub caller: H160,
+	/// Depth of the frame whose calls are pranked.
+	pub depth: Option<usize>,
+	/// Whether the prank lasts until `stopPrank`, instead of a single call.
+	pub persistent: bool,
+}
+
+/// Revert expected from the next call by `expectRevert`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExpectedRevert {
+	/// Expected revert data. `None` accepts any revert.
+	pub data: Option<Vec<u8>>,
+	/// Whether `data` only has to prefix the revert data.
+	pub prefix: bool,
+	/// Depth of the frame whose next call is expected to revert.
+	pub depth: Option<usize>,
+}
+
+/// Test environment changes requested through cheatcode calls.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Cheatcodes {
+	/// Block timestamp set by `warp`.
+	pub timestamp: Option<U256>,
+	/// Block number set by `roll`.
+	pub number: Option<U256>,
+	/// Active caller override.
+	pub prank: Option<Prank>,
+	/// Pending revert expectation.
+	pub expected_revert: Option<ExpectedRevert>,
+}
+
+/// Decoded cheatcode call.
+pub(crate) enum Cheatcode {
+	Prank(H160),
+	StartPrank(H160),
+	StopPrank,
+	Deal(H160, U256),
+	Warp(U256),
+	Roll(U256),
+	ExpectRevert(Option<Vec<u8>>, bool),
+}
+
+fn word(input: &[u8], index: usize) -> Option<&[u8]> {
+	input.get(4 + index * 32..4 + (index + 1) * 32)
+}
+
+fn address(input: &[u8], index: usize) -> Option<H160> {
+	word(input, index).map(|word| H160::from(H256::from_slice(word)))
+}
+
+fn uint(input: &[u8], index: usize) -> Option<U256> {
+	word(input, index).map(U256::from_big_endian)
+}
+
+fn bytes(input: &[u8], index: usize) -> Option<Vec<u8>> {
+	let offset = uint(input, index)?;
+	let len = U256::from_big_endian(input.get(4..)?.get(offset.low_u64() as usize..)?.get(..32)?);
+	let start = 4 + offset.low_u64() as usize + 32;
+	input.get(start..start.checked_add(len.low_u64() as usize)?).map(|data| data.to_vec())
+}
+
+impl Cheatcode {
+	/// Decode the ABI encoded input of a cheatcode call.
+	pub(crate) fn decode(input: &[u8]) -> Option<Self> {
+		let selector = input.get(..4)?;
+		Some(match selector {
+			[0xca, 0x66, 0x9f, 0xa7] => Cheatcode::Prank(address(input, 0)?),
+			[0x06, 0x44, 0x7d, 0x56] => Cheatcode::StartPrank(address(input, 0)?),
+			[0x90, 0xc5, 0x01, 0x3b] => Cheatcode::StopPrank,
+			[0xc8, 0x8a, 0x5e, 0x6d] => Cheatcode::Deal(address(input, 0)?, uint(input, 1)?),
+			[0xe5, 0xd6, 0xbf, 0x02] => Cheatcode::Warp(uint(input, 0)?),
+			[0x1f, 0x7b, 0x4f, 0x30] => Cheatcode::Roll(uint(input, 0)?),
+			[0xf4, 0x84, 0x48, 0x14] => Cheatcode::ExpectRevert(None, false),
+			[0xf2, 0x8d, 0xce, 0xb3] => Cheatcode::ExpectRevert(Some(bytes(input, 0)?), false),
+			[0xc3, 0x1e, 0xb0, 0xe0] => Cheatcode::ExpectRevert(Some(word(input, 0)?[..4].to_vec()), true),
+			_ => return None,
+		})
+	}
+}
+
+impl ExpectedRevert {
+	/// Whether the revert data satisfies the expectation.
+	pub fn matches(&self, data: &[u8]) -> bool {
+		match self.data.as_ref() {
+			None => true,
+			Some(expected) if self.prefix => data.starts_with(expected),
+			Some(expected) => data == &expected[..],
+		}
+	}
+}
+
+/// ABI encoding of `Error(string)` with the given message.
+pub(crate) fn revert_message(message: &str) -> Vec<u8> {
+	let mut out = Vec::with_capacity(4 + 64 + message.len().div_ceil(32) * 32);
+	out.extend_from_slice(&[0x08, 0xc3, 0x79, 0xa0]);
+	let mut word = [0u8; 32];
+	U256::from(32).to_big_endian(&mut word);
+	out.extend_from_slice(&word);
+	U256::from(message.len()).to_big_endian(&mut word);
+	out.extend_from_slice(&word);
+	out.extend_from_slice(message.as_bytes());
+	out.resize(out.len() + (32 - message.len() % 32) % 32, 0);
+	out
+}
@@ -0,0 +1,54 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use primitive_types::H160;
+
+/// Replacement code of accounts, run instead of their code for a whole
+/// execution, inner calls included, while their storage is kept. Meant for
+/// swapping instrumented implementations behind proxies.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CodeOverrides {
+	codes: BTreeMap<H160, Vec<u8>>,
+	/// Whether `EXTCODESIZE`, `EXTCODECOPY` and `EXTCODEHASH` see the
+	/// replacement code as well, instead of the code of the account.
+	pub ext_code: bool,
+}
+
+impl CodeOverrides {
+	/// Overrides replacing no code, also seen by `EXTCODE*`.
+	pub fn new() -> Self {
+		Self { codes: BTreeMap::new(), ext_code: true }
+	}
+
+	/// Replace the code of `address`.
+	pub fn with_code(mut self, address: H160, code: Vec<u8>) -> Self {
+		self.insert(address, code);
+		self
+	}
+
+	/// Set whether `EXTCODE*` see the replacement code.
+	pub fn with_ext_code(mut self, ext_code: bool) -> Self {
+		self.ext_code = ext_code;
+		self
+	}
+
+	/// Replace the code of `address`, returning the replacement it had.
+	pub fn insert(&mut self, address: H160, code: Vec<u8>) -> Option<Vec<u8>> {
+		self.codes.insert(address, code)
+	}
+
+	/// Stop replacing the code of `address`, returning its replacement.
+	pub fn remove(&mut self, address: H160) -> Option<Vec<u8>> {
+		self.codes.remove(&address)
+	}
+
+	/// Replacement code of `address`, if any.
+	pub fn get(&self, address: H160) -> Option<&Vec<u8>> {
+		self.codes.get(&address)
+	}
+
+	/// Whether no code is replaced.
+	pub fn is_empty(&self) -> bool {
+		self.codes.is_empty()
+	}
+}
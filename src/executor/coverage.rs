@@ -0,0 +1,42 @@
+use alloc::collections::BTreeMap;
+
+use primitive_types::H256;
+
+/// Coverage of a single code.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CodeCoverage {
+	/// Number of executions of each program counter.
+	pub hits: BTreeMap<usize, usize>,
+	/// Number of times each `JUMPI` was taken and not taken.
+	pub branches: BTreeMap<usize, (usize, usize)>,
+}
+
+impl CodeCoverage {
+	/// Add the counts of another coverage.
+	pub fn merge(&mut self, other: CodeCoverage) {
+		for (position, count) in other.hits {
+			*self.hits.entry(position).or_insert(0) += count;
+		}
+		for (position, (taken, not_taken)) in other.branches {
+			let entry = self.branches.entry(position).or_insert((0, 0));
+			entry.0 += taken;
+			entry.1 += not_taken;
+		}
+	}
+}
+
+/// Coverage of an execution, keyed by code hash.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CoverageReport {
+	/// Coverage of each executed code.
+	pub codes: BTreeMap<H256, CodeCoverage>,
+}
+
+impl CoverageReport {
+	/// Add the counts of another report.
+	pub fn merge(&mut self, other: CoverageReport) {
+		for (code_hash, coverage) in other.codes {
+			self.codes.entry(code_hash).or_default().merge(coverage);
+		}
+	}
+}
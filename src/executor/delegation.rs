@@ -0,0 +1,22 @@
+use alloc::vec::Vec;
+
+use primitive_types::H160;
+
+/// Prefix of EIP-7702 delegation designators.
+pub const DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+
+/// Code delegating an account to `address`, per EIP-7702.
+pub fn delegation_designator(address: H160) -> Vec<u8> {
+	let mut code = DELEGATION_PREFIX.to_vec();
+	code.extend_from_slice(&address[..]);
+	code
+}
+
+/// Address the code delegates to, if it is a delegation designator.
+pub fn delegated_address(code: &[u8]) -> Option<H160> {
+	if code.len() == DELEGATION_PREFIX.len() + 20 && code.starts_with(&DELEGATION_PREFIX) {
+		Some(H160::from_slice(&code[DELEGATION_PREFIX.len()..]))
+	} else {
+		None
+	}
+}
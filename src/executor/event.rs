@@ -0,0 +1,41 @@
+use primitive_types::{H160, H256};
+
+use crate::ExitReason;
+use crate::backend::Log;
+
+/// Event streamed by the executor as execution proceeds. Events are sent
+/// as they happen, so they include those of frames that later revert.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExecutorEvent {
+	/// A call or create frame started executing.
+	Enter {
+		/// Whether the frame is a create frame.
+		is_create: bool,
+		/// Caller of the frame.
+		caller: H160,
+		/// Address of the frame, as in `CallTrace`.
+		address: H160,
+		/// Call depth of the frame.
+		depth: usize,
+	},
+	/// A frame finished executing.
+	Exit {
+		/// Address of the frame, as in `CallTrace`.
+		address: H160,
+		/// Call depth of the frame.
+		depth: usize,
+		/// Exit reason of the frame.
+		reason: ExitReason,
+	},
+	/// A log was emitted.
+	Log(Log),
+	/// A storage slot was written.
+	StorageChanged {
+		/// Address of the storage.
+		address: H160,
+		/// Storage index.
+		index: H256,
+		/// New value.
+		value: H256,
+	},
+}
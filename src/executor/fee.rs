@@ -0,0 +1,118 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::min;
+
+use primitive_types::{H160, U256};
+
+use crate::{Config, ExitError};
+use super::{Transaction, TransactionAction};
+
+/// How the fee of a transaction is distributed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FeeDistribution {
+	/// Amounts credited to each recipient.
+	pub credits: Vec<(H160, U256)>,
+	/// Amount burned.
+	pub burned: U256,
+}
+
+impl FeeDistribution {
+	/// Sum of the credited and burned amounts, `None` on overflow.
+	pub fn total(&self) -> Option<U256> {
+		self.credits.iter().try_fold(self.burned, |total, (_, amount)| total.checked_add(*amount))
+	}
+}
+
+/// Failure to settle the fee of a transaction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FeeError<E> {
+	/// The caller cannot pay the fee.
+	Exit(ExitError),
+	/// The fee policy distributed more or less than the charged fee.
+	InvalidDistribution {
+		/// Fee charged to the caller.
+		fee: U256,
+		/// Credited and burned amounts, `None` if their sum overflows.
+		distributed: Option<U256>,
+	},
+	/// A backend read failed.
+	Backend(E),
+}
+
+/// Intrinsic gas of a transaction: the base cost plus calldata cost, plus
+/// the EIP-3860 init code cost of create transactions.
+pub fn intrinsic_gas(transaction: &Transaction, config: &Config) -> usize {
+	let zero_data_len = transaction.data.iter().filter(|v| **v == 0).count();
+	let non_zero_data_len = transaction.data.len() - zero_data_len;
+	let base = match transaction.action {
+		TransactionAction::Call(_) => config.gas_transaction_call,
+		TransactionAction::Create | TransactionAction::Create2(_) =>
+			config.gas_transaction_create +
+				transaction.data.len().div_ceil(32) * config.gas_initcode_word,
+	};
+
+	base + zero_data_len * config.gas_transaction_zero_data +
+		non_zero_data_len * config.gas_transaction_non_zero_data
+}
+
+/// EIP-7623 minimum gas used by a transaction, if the floor is enabled.
+/// Zero bytes count as one calldata token, others as four.
+pub fn floor_gas(transaction: &Transaction, config: &Config) -> Option<usize> {
+	config.gas_transaction_floor_per_token.map(|per_token| {
+		let zero_data_len = transaction.data.iter().filter(|v| **v == 0).count();
+		let tokens = zero_data_len + (transaction.data.len() - zero_data_len) * 4;
+		config.gas_transaction_call + tokens * per_token
+	})
+}
+
+/// Fee market rules consulted by the executor.
+pub trait FeePolicy: Send + Sync {
+	/// Intrinsic gas charged before a transaction executes.
+	fn intrinsic_gas(&self, transaction: &Transaction, config: &Config) -> usize;
+
+	/// Gas refunded to the caller, given the used gas and the accumulated
+	/// refund counter.
+	fn refund(&self, used_gas: usize, refunded_gas: isize) -> usize;
+
+	/// Distribute the fee of the used gas, paid by the caller.
+	fn distribute(&self, used_gas: usize, gas_price: U256, coinbase: H160) -> FeeDistribution;
+
+	/// Minimum gas price of transactions.
+	fn base_fee(&self) -> U256 {
+		U256::zero()
+	}
+}
+
+/// Mainnet fee policy. The base fee is burned and the remaining priority
+/// fee is paid to the coinbase.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DefaultFeePolicy {
+	/// Base fee per gas.
+	pub base_fee: U256,
+}
+
+impl FeePolicy for DefaultFeePolicy {
+	fn intrinsic_gas(&self, transaction: &Transaction, config: &Config) -> usize {
+		intrinsic_gas(transaction, config)
+	}
+
+	fn refund(&self, used_gas: usize, refunded_gas: isize) -> usize {
+		if refunded_gas <= 0 {
+			0
+		} else {
+			min(used_gas / 2, refunded_gas as usize)
+		}
+	}
+
+	fn distribute(&self, used_gas: usize, gas_price: U256, coinbase: H160) -> FeeDistribution {
+		let base_fee = min(self.base_fee, gas_price);
+		FeeDistribution {
+			credits: vec![(coinbase, U256::from(used_gas) * (gas_price - base_fee))],
+			burned: U256::from(used_gas) * base_fee,
+		}
+	}
+
+	fn base_fee(&self) -> U256 {
+		self.base_fee
+	}
+}
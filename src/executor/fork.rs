@@ -0,0 +1,64 @@
+use alloc::vec::Vec;
+use std::sync::Arc;
+
+use crate::Config;
+
+/// Activation point of a fork.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ForkActivation {
+	/// Active from the block with the given number.
+	Block(u64),
+	/// Active from the first block with at least the given timestamp.
+	Timestamp(u64),
+}
+
+impl ForkActivation {
+	/// Whether the fork is active in the block with the given number and
+	/// timestamp.
+	pub fn is_active(&self, number: u64, timestamp: u64) -> bool {
+		match self {
+			ForkActivation::Block(block) => *block <= number,
+			ForkActivation::Timestamp(time) => *time <= timestamp,
+		}
+	}
+}
+
+/// Configs of a chain by fork activation, for executing blocks of a chain
+/// across its forks.
+#[derive(Clone, Debug)]
+pub struct ForkSchedule {
+	genesis: Arc<Config>,
+	forks: Vec<(ForkActivation, Arc<Config>)>,
+}
+
+impl ForkSchedule {
+	/// Create a schedule with the config active from genesis.
+	pub fn new(genesis: Config) -> Self {
+		Self {
+			genesis: Arc::new(genesis),
+			forks: Vec::new(),
+		}
+	}
+
+	/// Add a fork. Forks must be added in activation order, block-activated
+	/// forks before timestamp-activated ones.
+	pub fn with_fork(mut self, activation: ForkActivation, config: Config) -> Self {
+		self.forks.push((activation, Arc::new(config)));
+		self
+	}
+
+	/// Forks of the schedule, in activation order.
+	pub fn forks(&self) -> &[(ForkActivation, Arc<Config>)] {
+		&self.forks
+	}
+
+	/// Config of the block with the given number and timestamp, that of the
+	/// last active fork.
+	pub fn config_at(&self, number: u64, timestamp: u64) -> Arc<Config> {
+		self.forks.iter()
+			.rev()
+			.find(|(activation, _)| activation.is_active(number, timestamp))
+			.map(|(_, config)| config.clone())
+			.unwrap_or_else(|| self.genesis.clone())
+	}
+}
@@ -0,0 +1,28 @@
+use crate::{ExitError, ExternalOpcode, Opcode};
+
+/// Decision of a gas hook after an instruction was charged.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GasAction {
+	/// Run the instruction.
+	Continue,
+	/// Give gas back to the frame before running the instruction, up to the
+	/// gas the frame used so far excluding memory expansion. Granted gas is
+	/// not charged to the transaction.
+	Grant(usize),
+	/// Exit the frame with the given error instead of running the
+	/// instruction.
+	Terminate(ExitError),
+}
+
+/// Hook invoked after the gas of every instruction is charged, for
+/// alternative metering and gas sponsorship.
+pub trait GasHook: Send + Sync {
+	/// Decide what happens after `opcode` was charged `cost`, leaving
+	/// `remaining` gas to the frame.
+	fn after_charge(
+		&self,
+		opcode: Result<Opcode, ExternalOpcode>,
+		cost: usize,
+		remaining: usize,
+	) -> GasAction;
+}
@@ -0,0 +1,171 @@
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use std::sync::Arc;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::{Bytes, Capture, Config, Context, CreateScheme, ExitError, ExitReason, ExternalOpcode,
+			Machine, Opcode, Stack, StateMutator, StateQuery, Transfer};
+use crate::backend::Backend;
+use crate::hasher::Hasher;
+use super::StackExecutor;
+
+/// Ready-made `Handler` over any backend, for running bytecode against a
+/// state without implementing the handler traits. Accounts, storage,
+/// substates, logs and gas are tracked by the wrapped `StackExecutor`, to
+/// which the handler traits are delegated, so it can also drive a
+/// `Runtime` directly.
+pub struct BackendHandler<B: Backend> {
+	executor: StackExecutor<B>,
+}
+
+impl<B: Backend> BackendHandler<B> {
+	/// Create a handler over the given backend with a total gas limit for
+	/// all runs.
+	pub fn new(backend: Arc<B>, gas_limit: usize, config: Arc<Config>) -> Self {
+		Self {
+			executor: StackExecutor::new(backend, gas_limit, config),
+		}
+	}
+
+	/// Run `code` with the given input at `context.address`, with all
+	/// remaining gas. State changes of succeeded runs are kept for later
+	/// runs.
+	pub async fn run(
+		&mut self,
+		code: Vec<u8>,
+		input: Vec<u8>,
+		context: Context,
+	) -> (ExitReason, Vec<u8>) {
+		self.executor.execute_code(code, input, context).await
+	}
+
+	/// Remaining gas.
+	pub fn gas(&self) -> usize {
+		self.executor.gas()
+	}
+
+	/// The underlying handler.
+	pub fn executor(&self) -> &StackExecutor<B> {
+		&self.executor
+	}
+
+	/// The underlying handler, mutably, to call `Handler` methods directly.
+	pub fn executor_mut(&mut self) -> &mut StackExecutor<B> {
+		&mut self.executor
+	}
+
+	/// Consume the handler, returning the executor, for example to
+	/// deconstruct its state changes.
+	pub fn into_executor(self) -> StackExecutor<B> {
+		self.executor
+	}
+}
+
+impl<B: Backend> From<StackExecutor<B>> for BackendHandler<B> {
+	fn from(executor: StackExecutor<B>) -> Self {
+		Self { executor }
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<B: Backend> StateQuery for BackendHandler<B> {
+	async fn balance(&self, address: H160) -> U256 { self.executor.balance(address).await }
+	async fn code_size(&self, address: H160) -> U256 { self.executor.code_size(address).await }
+	async fn code_hash(&self, address: H160) -> H256 { self.executor.code_hash(address).await }
+	async fn code(&self, address: H160) -> Vec<u8> { self.executor.code(address).await }
+	async fn storage(&self, address: H160, index: H256) -> H256 {
+		self.executor.storage(address, index).await
+	}
+	async fn original_storage(&self, address: H160, index: H256) -> H256 {
+		self.executor.original_storage(address, index).await
+	}
+
+	fn gas_left(&self) -> U256 { self.executor.gas_left() }
+	async fn gas_price(&self) -> U256 { self.executor.gas_price().await }
+	async fn origin(&self) -> H160 { self.executor.origin().await }
+	async fn block_hash(&self, number: U256) -> H256 { self.executor.block_hash(number).await }
+	async fn block_number(&self) -> U256 { self.executor.block_number().await }
+	async fn block_coinbase(&self) -> H160 { self.executor.block_coinbase().await }
+	async fn block_timestamp(&self) -> U256 { self.executor.block_timestamp().await }
+	async fn block_difficulty(&self) -> U256 { self.executor.block_difficulty().await }
+	async fn block_gas_limit(&self) -> U256 { self.executor.block_gas_limit().await }
+	async fn chain_id(&self) -> U256 { self.executor.chain_id().await }
+
+	async fn exists(&self, address: H160) -> bool { self.executor.exists(address).await }
+	fn deleted(&self, address: H160) -> bool { self.executor.deleted(address) }
+	fn hasher(&self) -> &dyn Hasher { self.executor.hasher() }
+	fn keccak256(&self, data: &[u8]) -> H256 { self.executor.keccak256(data) }
+	fn authorized(&self) -> Option<H160> { self.executor.authorized() }
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<B: Backend> StateMutator for BackendHandler<B> {
+	type CreateInterrupt = Infallible;
+	type CreateFeedback = Infallible;
+	type CallInterrupt = Infallible;
+	type CallFeedback = Infallible;
+
+	async fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError> {
+		self.executor.set_storage(address, index, value).await
+	}
+
+	fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) -> Result<(), ExitError> {
+		self.executor.log(address, topics, data)
+	}
+
+	async fn mark_delete(&mut self, address: H160, target: H160) -> Result<(), ExitError> {
+		self.executor.mark_delete(address, target).await
+	}
+
+	async fn create(
+		&mut self,
+		caller: H160,
+		scheme: CreateScheme,
+		value: U256,
+		init_code: Bytes,
+		target_gas: Option<usize>,
+	) -> Capture<(ExitReason, Option<H160>, Bytes), Infallible> {
+		self.executor.create(caller, scheme, value, init_code, target_gas).await
+	}
+
+	async fn call(
+		&mut self,
+		code_address: H160,
+		transfer: Option<Transfer>,
+		input: Bytes,
+		target_gas: Option<usize>,
+		is_static: bool,
+		context: Context,
+	) -> Capture<(ExitReason, Bytes), Infallible> {
+		self.executor.call(code_address, transfer, input, target_gas, is_static, context).await
+	}
+
+	async fn auth(
+		&mut self,
+		invoker: H160,
+		authority: H160,
+		y_parity: u8,
+		r: H256,
+		s: H256,
+		commit: H256,
+	) -> Result<bool, ExitError> {
+		self.executor.auth(invoker, authority, y_parity, r, s, commit).await
+	}
+
+	async fn pre_validate(
+		&mut self,
+		context: &Context,
+		opcode: Result<Opcode, ExternalOpcode>,
+		static_gas: Option<usize>,
+		stack: &Stack
+	) -> Result<(), ExitError> {
+		self.executor.pre_validate(context, opcode, static_gas, stack).await
+	}
+
+	fn other(&mut self, opcode: u8, stack: &mut Machine) -> Result<(), ExitError> {
+		self.executor.other(opcode, stack)
+	}
+}
@@ -0,0 +1,71 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use primitive_types::H256;
+
+use crate::hasher::{Hasher, Keccak256Hasher};
+
+/// Keccak256 digests memoized by preimage, for `SHA3` and `CREATE2` code
+/// hashes. Mapping-heavy contracts hash the same short preimages, such as a
+/// key and a slot, many times per transaction.
+#[derive(Clone, Debug, Default)]
+pub struct KeccakCache {
+	digests: BTreeMap<Vec<u8>, H256>,
+	hits: usize,
+	misses: usize,
+}
+
+impl KeccakCache {
+	/// Maximum number of memoized digests. Further preimages are hashed
+	/// without being memoized.
+	pub const MAX_ENTRIES: usize = 4096;
+	/// Longer preimages are hashed without being memoized, as comparing them
+	/// would cost as much as hashing them.
+	pub const MAX_PREIMAGE_LEN: usize = 256;
+
+	/// Digest of `data`, memoized. With the `constant-time` feature, digests
+	/// are not memoized, as lookups would reveal repeated preimages.
+	pub fn keccak256(&mut self, data: &[u8]) -> H256 {
+		self.digest(data, &Keccak256Hasher)
+	}
+
+	/// Digest of `data` by `hasher`, memoized as `keccak256`. A cache must
+	/// only be used with a single hash function.
+	pub fn digest(&mut self, data: &[u8], hasher: &dyn Hasher) -> H256 {
+		if cfg!(feature = "constant-time") || data.len() > Self::MAX_PREIMAGE_LEN {
+			return hasher.hash(data)
+		}
+
+		if let Some(digest) = self.digests.get(data) {
+			self.hits += 1;
+			return *digest
+		}
+
+		self.misses += 1;
+		let digest = hasher.hash(data);
+		if self.digests.len() < Self::MAX_ENTRIES {
+			self.digests.insert(data.to_vec(), digest);
+		}
+		digest
+	}
+
+	/// Number of digests served from the cache.
+	pub fn hits(&self) -> usize {
+		self.hits
+	}
+
+	/// Number of memoizable preimages that were hashed.
+	pub fn misses(&self) -> usize {
+		self.misses
+	}
+
+	/// Number of memoized digests.
+	pub fn len(&self) -> usize {
+		self.digests.len()
+	}
+
+	/// Whether no digest is memoized.
+	pub fn is_empty(&self) -> bool {
+		self.digests.is_empty()
+	}
+}
@@ -3,6 +3,77 @@
 //! Executors are structs that hook gasometer and the EVM core together. It
 //! also handles the call stacks in EVM.
 
+mod access;
+mod account;
+mod analysis;
+mod apply_set;
+mod attribution;
+#[cfg(feature = "auth")]
+mod auth;
+mod block;
+mod builder;
+mod bundle;
+mod cancel;
+mod cheatcode;
+mod code_override;
+mod coverage;
+mod delegation;
+mod event;
+mod fee;
+mod fork;
+mod gas_hook;
+mod handler;
+mod keccak;
+mod pending;
+mod pool;
+mod precompile;
+mod profile;
+mod replay;
+mod resolver;
+mod result;
+mod result_cache;
+mod security;
 mod stack;
+mod trace;
+mod validate;
+mod verify;
 
+pub use self::access::{AccessSet, LocationSet, ReadWriteSet};
+pub use self::account::AccountState;
+pub use self::analysis::{AnalysisCache, DEFAULT_ANALYSIS_CACHE_CAPACITY};
+pub use self::apply_set::ApplySet;
+pub use self::attribution::{AddressUsage, GasAttribution};
+#[cfg(feature = "auth")]
+pub use self::auth::{AUTH_MAGIC, auth_message};
+pub use self::block::{
+	BEACON_ROOTS_ADDRESS, BEACON_ROOTS_CODE, BEACON_ROOTS_HISTORY_LENGTH, BeaconRoots, Block,
+	BlockExecutor, BlockHashHistory, BlockHook, BlockResult, HISTORY_SERVE_WINDOW,
+	HISTORY_STORAGE_ADDRESS, HISTORY_STORAGE_CODE, SYSTEM_ADDRESS, SYSTEM_CALL_GAS,
+	SystemCallResult, SystemOperation, Withdrawal, WithdrawalCredits,
+};
+pub use self::builder::{AsyncPrecompileLayer, ExecutorBuilder, ExecutorLayer, FeePolicyLayer, InspectorLayer};
+pub use self::bundle::{BundleResult, BundleTransactionResult, simulate_bundle};
+pub use self::cancel::{CancellationToken, DEADLINE_CHECK_INTERVAL, gas_deadline};
+pub use self::cheatcode::{CHEATCODE_ADDRESS, Cheatcodes, ExpectedRevert, Prank};
+pub use self::code_override::CodeOverrides;
+pub use self::coverage::{CodeCoverage, CoverageReport};
+pub use self::delegation::{DELEGATION_PREFIX, delegated_address, delegation_designator};
+pub use self::event::ExecutorEvent;
+pub use self::fee::{DefaultFeePolicy, FeeDistribution, FeeError, FeePolicy, floor_gas, intrinsic_gas};
+pub use self::fork::{ForkActivation, ForkSchedule};
+pub use self::gas_hook::{GasAction, GasHook};
+pub use self::handler::BackendHandler;
+pub use self::keccak::KeccakCache;
+pub use self::pending::{PendingResult, PendingState};
+pub use self::pool::MemoryPool;
+pub use self::precompile::{AsyncPrecompile, PrecompileFn, PrecompileOutput};
+pub use self::profile::{FrameGas, Profile};
+pub use self::replay::{ReplayEntry, ReplayLog};
+pub use self::resolver::{AddressResolver, L1_TO_L2_ALIAS_OFFSET, L1ToL2Alias};
+pub use self::result::ExecutionResult;
+pub use self::result_cache::{LruResultCache, ResultCache, ResultCacheKey, transaction_hash};
+pub use self::security::Finding;
 pub use self::stack::{StackAccount, StackExecutor};
+pub use self::trace::{CallTrace, FAULT_MEMORY_WINDOW, FaultSnapshot, StorageProvenance};
+pub use self::validate::{DefaultTxValidator, Transaction, TransactionAction, TxValidationError, TxValidator};
+pub use self::verify::{VerifyError, verify_execution};
@@ -0,0 +1,194 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use std::sync::Arc;
+
+use primitive_types::H256;
+
+use crate::Config;
+use crate::backend::{Apply, ApplyBackend, Backend, BlockOverrides, OverlayBackend, Recorder};
+use super::{AccessSet, BundleTransactionResult, ExecutionResult, StackExecutor, Transaction};
+
+/// Outcome of a pending transaction, with the state it read and wrote.
+#[derive(Clone, Debug)]
+pub struct PendingResult {
+	/// Execution result.
+	pub result: BundleTransactionResult,
+	/// State read by the transaction.
+	pub reads: AccessSet,
+	/// State written by the transaction.
+	pub writes: AccessSet,
+	applies: Vec<Apply<BTreeMap<H256, H256>>>,
+}
+
+struct PendingEntry {
+	transaction: Transaction,
+	result: Option<PendingResult>,
+}
+
+/// Ordered transactions applied over a base backend without committing
+/// them.
+///
+/// Results are recomputed lazily by `results`. A transaction is executed
+/// again only if it is new, was moved, or read state written differently
+/// since its last execution; otherwise its cached writes are replayed.
+pub struct PendingState<B> {
+	base: Arc<B>,
+	config: Arc<Config>,
+	overrides: BlockOverrides,
+	entries: Vec<PendingEntry>,
+	/// State whose value may differ from the last computation.
+	dirty: AccessSet,
+	state: Option<OverlayBackend<B>>,
+	executions: usize,
+}
+
+impl<B: Backend> PendingState<B> {
+	/// Create an empty pending state over the given backend.
+	pub fn new(base: Arc<B>, config: &Config, overrides: BlockOverrides) -> Self {
+		Self {
+			base,
+			config: Arc::new(config.clone()),
+			overrides,
+			entries: Vec::new(),
+			dirty: AccessSet::default(),
+			state: None,
+			executions: 0,
+		}
+	}
+
+	/// Pending transactions, in order.
+	pub fn transactions(&self) -> impl Iterator<Item=&Transaction> {
+		self.entries.iter().map(|entry| &entry.transaction)
+	}
+
+	/// Number of pending transactions.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Whether there are no pending transactions.
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Number of transaction executions so far.
+	pub fn executions(&self) -> usize {
+		self.executions
+	}
+
+	/// Append a transaction.
+	pub fn push(&mut self, transaction: Transaction) {
+		self.insert(self.entries.len(), transaction)
+	}
+
+	/// Insert a transaction at the given position.
+	pub fn insert(&mut self, index: usize, transaction: Transaction) {
+		self.entries.insert(index, PendingEntry { transaction, result: None });
+		self.state = None;
+	}
+
+	/// Remove the transaction at the given position.
+	pub fn remove(&mut self, index: usize) -> Transaction {
+		let entry = self.entries.remove(index);
+		self.invalidate(&entry);
+		self.state = None;
+		entry.transaction
+	}
+
+	/// Move the transaction at position `from` to position `to`.
+	pub fn reorder(&mut self, from: usize, to: usize) {
+		let mut entry = self.entries.remove(from);
+		self.invalidate(&entry);
+		entry.result = None;
+		self.entries.insert(to, entry);
+		self.state = None;
+	}
+
+	fn invalidate(&mut self, entry: &PendingEntry) {
+		if let Some(result) = entry.result.as_ref() {
+			self.dirty.extend(&result.writes);
+		}
+	}
+
+	/// Execute the transactions whose results may have changed, and return
+	/// the results of every pending transaction.
+	pub async fn results(&mut self) -> Result<Vec<&PendingResult>, B::Error> {
+		if self.state.is_none() {
+			self.recompute().await?;
+		}
+
+		Ok(self.entries.iter().filter_map(|entry| entry.result.as_ref()).collect())
+	}
+
+	/// State after every pending transaction, once computed by `results`.
+	pub fn state(&self) -> Option<&OverlayBackend<B>> {
+		self.state.as_ref()
+	}
+
+	async fn recompute(&mut self) -> Result<(), B::Error> {
+		let mut overlay = Arc::new(OverlayBackend::new(self.base.clone(), self.overrides.clone()));
+		let delete_empty = !self.config.empty_considered_exists;
+
+		for index in 0..self.entries.len() {
+			let reusable = self.entries[index].result.as_ref()
+				.map(|result| !result.reads.intersects(&self.dirty))
+				.unwrap_or(false);
+
+			if !reusable {
+				let result = self.execute(&overlay, self.entries[index].transaction.clone()).await?;
+				if let Some(previous) = self.entries[index].result.as_ref() {
+					self.dirty.extend(&previous.writes);
+				}
+				self.dirty.extend(&result.writes);
+				self.entries[index].result = Some(result);
+			}
+
+			let result = self.entries[index].result.as_ref().expect("result was computed above");
+			Arc::get_mut(&mut overlay)
+				.expect("executor was dropped after execution")
+				.apply(result.applies.clone(), result.result.logs.clone(), delete_empty).await?;
+		}
+
+		self.dirty = AccessSet::default();
+		self.state = Some(Arc::try_unwrap(overlay).ok().expect("overlay is not shared"));
+		Ok(())
+	}
+
+	async fn execute(
+		&mut self,
+		overlay: &Arc<OverlayBackend<B>>,
+		transaction: Transaction,
+	) -> Result<PendingResult, B::Error> {
+		self.executions += 1;
+
+		let recorder = Arc::new(Recorder::new(overlay.clone()));
+		let mut executor = StackExecutor::new(recorder.clone(), transaction.gas_limit, self.config.clone());
+		let ExecutionResult { reason, output, .. } = executor.transact(transaction).await;
+		if let Some(e) = executor.take_backend_error() {
+			return Err(e)
+		}
+		let used_gas = executor.used_gas();
+		let (applies, logs) = executor.deconstruct();
+		let applies = applies.into_iter()
+			.map(|apply| match apply {
+				Apply::Modify { address, basic, code, storage, reset_storage } => Apply::Modify {
+					address, basic, code, reset_storage,
+					storage: storage.into_iter().collect(),
+				},
+				Apply::Delete { address } => Apply::Delete { address },
+			})
+			.collect::<Vec<_>>();
+
+		Ok(PendingResult {
+			result: BundleTransactionResult {
+				reason,
+				output,
+				used_gas,
+				logs: logs.into_iter().collect(),
+			},
+			reads: AccessSet::from_witness(&recorder.witness()),
+			writes: AccessSet::from_applies(&applies),
+			applies,
+		})
+	}
+}
@@ -0,0 +1,58 @@
+use alloc::vec::Vec;
+
+use primitive_types::H256;
+
+/// Memory and stack buffers of finished call frames, handed to new frames
+/// so they reuse allocations instead of growing fresh vectors.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryPool {
+	memories: Vec<Vec<u8>>,
+	stacks: Vec<Vec<H256>>,
+	reused: usize,
+}
+
+impl MemoryPool {
+	/// Maximum number of buffers of each kind kept in the pool.
+	pub const MAX_BUFFERS: usize = 64;
+	/// Memory buffers with a larger capacity are dropped instead of pooled.
+	pub const MAX_MEMORY_CAPACITY: usize = 1 << 20;
+
+	/// Take a memory and a stack buffer, empty if the pool has none.
+	pub fn take(&mut self) -> (Vec<u8>, Vec<H256>) {
+		let memory = self.memories.pop();
+		let stack = self.stacks.pop();
+		if memory.is_some() || stack.is_some() {
+			self.reused += 1;
+		}
+		(memory.unwrap_or_default(), stack.unwrap_or_default())
+	}
+
+	/// Return the buffers of a finished frame to the pool.
+	pub fn give(&mut self, mut memory: Vec<u8>, mut stack: Vec<H256>) {
+		if memory.capacity() > 0 && memory.capacity() <= Self::MAX_MEMORY_CAPACITY &&
+			self.memories.len() < Self::MAX_BUFFERS
+		{
+			memory.clear();
+			self.memories.push(memory);
+		}
+		if stack.capacity() > 0 && self.stacks.len() < Self::MAX_BUFFERS {
+			stack.clear();
+			self.stacks.push(stack);
+		}
+	}
+
+	/// Number of frames that started with pooled buffers.
+	pub fn reused(&self) -> usize {
+		self.reused
+	}
+
+	/// Number of memory and stack buffers held.
+	pub fn len(&self) -> usize {
+		self.memories.len() + self.stacks.len()
+	}
+
+	/// Whether the pool holds no buffers.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
@@ -0,0 +1,31 @@
+use alloc::vec::Vec;
+
+use primitive_types::H160;
+
+use crate::{Context, ExitError, ExitSucceed, MaybeSend, MaybeSync};
+use crate::backend::Backend;
+
+/// Outcome of a precompile: exit status, output and gas cost.
+pub type PrecompileOutput = Result<(ExitSucceed, Vec<u8>, usize), ExitError>;
+
+/// Synchronous precompiles, returning `None` for addresses that are not
+/// precompiles. Called directly, without boxing a future.
+pub type PrecompileFn = fn(H160, &[u8], Option<usize>) -> Option<PrecompileOutput>;
+
+/// Asynchronous precompiles, for precompiles reading the backend or
+/// performing external I/O. Consulted for calls to addresses the
+/// synchronous precompiles do not handle.
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+pub trait AsyncPrecompile<B: Backend>: MaybeSend + MaybeSync {
+	/// Execute the precompile at `address`, or return `None` if there is
+	/// none.
+	async fn execute(
+		&self,
+		address: H160,
+		input: &[u8],
+		target_gas: Option<usize>,
+		context: &Context,
+		backend: &B,
+	) -> Option<PrecompileOutput>;
+}
@@ -0,0 +1,56 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use primitive_types::{H160, H256};
+
+/// Gas used by a single call frame.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FrameGas {
+	/// Index of the frame, in the order frames were entered.
+	pub frame: usize,
+	/// Call depth of the frame.
+	pub depth: usize,
+	/// Address whose code the frame ran.
+	pub address: H160,
+	/// Gas used by the frame, including the frames it called.
+	pub gas_used: usize,
+}
+
+/// Resource watermarks of an execution.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Profile {
+	/// Deepest call depth reached.
+	pub max_depth: usize,
+	/// Largest stack of any frame, in words.
+	pub max_stack: usize,
+	/// Largest memory of any frame, in bytes.
+	pub max_memory: usize,
+	/// Gas used by each finished frame, in the order frames exited.
+	pub frames: Vec<FrameGas>,
+	/// Gas used by each instruction, keyed by code hash and program counter.
+	/// Call and create instructions include the gas of the frames they enter.
+	pub instructions: BTreeMap<(H256, usize), usize>,
+}
+
+impl Profile {
+	/// The `n` instructions that used the most gas, most expensive first.
+	pub fn top(&self, n: usize) -> Vec<((H256, usize), usize)> {
+		let mut instructions = self.instructions.iter()
+			.map(|(key, gas)| (*key, *gas))
+			.collect::<Vec<_>>();
+		instructions.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+		instructions.truncate(n);
+		instructions
+	}
+
+	/// Add the watermarks and gas of another profile.
+	pub fn merge(&mut self, other: Profile) {
+		self.max_depth = self.max_depth.max(other.max_depth);
+		self.max_stack = self.max_stack.max(other.max_stack);
+		self.max_memory = self.max_memory.max(other.max_memory);
+		self.frames.extend(other.frames);
+		for (key, gas) in other.instructions {
+			*self.instructions.entry(key).or_insert(0) += gas;
+		}
+	}
+}
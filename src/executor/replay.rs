@@ -0,0 +1,133 @@
+use alloc::vec::Vec;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::backend::Basic;
+use crate::hasher::Hasher;
+
+/// Read issued to the backend during execution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
+pub struct ReplayEntry {
+	/// Backend method, such as `storage`.
+	pub method: &'static str,
+	/// Arguments of the read, concatenated: 20 bytes per address and 32
+	/// bytes per word.
+	pub input: Vec<u8>,
+	/// Digest of the returned value, `None` if the read failed.
+	pub value: Option<H256>,
+	/// Commitment to this read and all previous ones.
+	pub commitment: H256,
+}
+
+/// Log of the values returned by the backend, folded into a rolling
+/// commitment. Two executions against the same state and environment have
+/// equal commitments; otherwise `first_divergence` finds the first read
+/// that differs, for example a stale value of a caching backend.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
+pub struct ReplayLog {
+	/// Commitment to all reads, zero if there are none.
+	pub commitment: H256,
+	/// Reads in the order they were issued.
+	pub entries: Vec<ReplayEntry>,
+}
+
+impl ReplayLog {
+	/// Append a read returning the value of digest `value`, `None` if it
+	/// failed.
+	pub fn record(
+		&mut self,
+		hasher: &dyn Hasher,
+		method: &'static str,
+		input: Vec<u8>,
+		value: Option<H256>,
+	) {
+		let mut preimage = Vec::with_capacity(32 + method.len() + input.len() + 33);
+		preimage.extend_from_slice(&self.commitment[..]);
+		preimage.extend_from_slice(method.as_bytes());
+		preimage.extend_from_slice(&input);
+		match value {
+			Some(value) => {
+				preimage.push(1);
+				preimage.extend_from_slice(&value[..]);
+			},
+			None => preimage.push(0),
+		}
+		self.commitment = hasher.hash(&preimage);
+		self.entries.push(ReplayEntry { method, input, value, commitment: self.commitment });
+	}
+
+	/// Append the reads of another log, as if they were issued after the
+	/// reads of this one.
+	pub fn extend(&mut self, hasher: &dyn Hasher, other: &ReplayLog) {
+		for entry in &other.entries {
+			self.record(hasher, entry.method, entry.input.clone(), entry.value);
+		}
+	}
+
+	/// Index of the first read differing from the read at the same index in
+	/// `other`, or the length of the shorter log if one is a prefix of the
+	/// other. `None` if the logs are equal.
+	pub fn first_divergence(&self, other: &ReplayLog) -> Option<usize> {
+		if self.commitment == other.commitment && self.entries.len() == other.entries.len() {
+			return None
+		}
+		Some(
+			self.entries.iter().zip(&other.entries)
+				.position(|(a, b)| a.commitment != b.commitment)
+				.unwrap_or_else(|| self.entries.len().min(other.entries.len()))
+		)
+	}
+}
+
+/// Encoding of backend arguments and values in the replay log.
+pub(crate) trait ReplayValue {
+	fn encode(&self, out: &mut Vec<u8>);
+}
+
+impl ReplayValue for H160 {
+	fn encode(&self, out: &mut Vec<u8>) {
+		out.extend_from_slice(&self[..]);
+	}
+}
+
+impl ReplayValue for H256 {
+	fn encode(&self, out: &mut Vec<u8>) {
+		out.extend_from_slice(&self[..]);
+	}
+}
+
+impl ReplayValue for U256 {
+	fn encode(&self, out: &mut Vec<u8>) {
+		let mut word = [0u8; 32];
+		self.to_big_endian(&mut word);
+		out.extend_from_slice(&word);
+	}
+}
+
+impl ReplayValue for usize {
+	fn encode(&self, out: &mut Vec<u8>) {
+		U256::from(*self).encode(out);
+	}
+}
+
+impl ReplayValue for bool {
+	fn encode(&self, out: &mut Vec<u8>) {
+		out.push(*self as u8);
+	}
+}
+
+impl ReplayValue for Basic {
+	fn encode(&self, out: &mut Vec<u8>) {
+		self.balance.encode(out);
+		self.nonce.encode(out);
+	}
+}
+
+impl ReplayValue for Vec<u8> {
+	fn encode(&self, out: &mut Vec<u8>) {
+		self.len().encode(out);
+		out.extend_from_slice(self);
+	}
+}
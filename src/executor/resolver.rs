@@ -0,0 +1,49 @@
+use primitive_types::{H160, U256};
+
+/// Offset added to the address of an L1 contract sending a message to L2,
+/// so that it cannot be confused with an L2 account of the same address.
+pub const L1_TO_L2_ALIAS_OFFSET: H160 = H160([
+	0x11, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x11,
+]);
+
+/// Hook rewriting the addresses of frames before their context is built,
+/// for chains that alias or redirect accounts. Transfers of the frame are
+/// rewritten along.
+pub trait AddressResolver: Send + Sync {
+	/// Caller of a call or create frame at `depth`, zero for the frame of
+	/// the transaction. The resolved caller of a create pays its value and
+	/// derives the created address.
+	fn caller(&self, caller: H160, _depth: usize) -> H160 {
+		caller
+	}
+
+	/// Address whose code a call frame at `depth` runs. For `CALL` and
+	/// `STATICCALL`, it is also the address of the frame.
+	fn target(&self, target: H160, _depth: usize) -> H160 {
+		target
+	}
+}
+
+/// Alias the caller of transactions by `L1_TO_L2_ALIAS_OFFSET`, as rollups
+/// do for messages sent from L1 contracts. Calls made during the
+/// transaction are left unchanged.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct L1ToL2Alias;
+
+impl L1ToL2Alias {
+	/// L2 alias of an L1 address.
+	pub fn alias(address: H160) -> H160 {
+		let offset = U256::from_big_endian(&L1_TO_L2_ALIAS_OFFSET[..]);
+		let (sum, _) = U256::from_big_endian(&address[..]).overflowing_add(offset);
+		let mut bytes = [0u8; 32];
+		sum.to_big_endian(&mut bytes);
+		H160::from_slice(&bytes[12..])
+	}
+}
+
+impl AddressResolver for L1ToL2Alias {
+	fn caller(&self, caller: H160, depth: usize) -> H160 {
+		if depth == 0 { Self::alias(caller) } else { caller }
+	}
+}
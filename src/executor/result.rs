@@ -0,0 +1,58 @@
+use alloc::vec::Vec;
+
+use primitive_types::{H160, U256};
+
+use crate::ExitReason;
+use crate::backend::Log;
+use super::{AccessSet, FaultSnapshot, GasAttribution, ReadWriteSet, ReplayLog, TxValidationError};
+
+/// Outcome of a transaction executed by `StackExecutor::transact`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
+pub struct ExecutionResult {
+	/// Exit reason of the transaction.
+	pub reason: ExitReason,
+	/// Return or revert data. Empty for succeeded creates.
+	pub output: Vec<u8>,
+	/// Address of the contract deployed by a succeeded create.
+	pub created_address: Option<H160>,
+	/// Logs emitted by the transaction.
+	pub logs: Vec<Log>,
+	/// Gas used, after refunds and the calldata floor.
+	pub gas_used: usize,
+	/// Gas refunded at the end of the transaction.
+	pub gas_refunded: usize,
+	/// Gas given to the top-level frame, that is the gas limit minus the
+	/// intrinsic gas. Zero if the transaction was rejected before execution.
+	pub gas_forwarded: usize,
+	/// Accounts and storage slots accessed by the transaction.
+	pub accessed: AccessSet,
+	/// Locations read and written by the transaction, by field.
+	pub read_write_set: ReadWriteSet,
+	/// Number of reads issued to the backend.
+	pub backend_reads: usize,
+	/// Wei destroyed by the transaction: the fee burned by the fee policy,
+	/// balances of accounts that self-destructed to themselves, and value
+	/// received by accounts after they self-destructed, as they are deleted
+	/// at the end of the transaction.
+	pub burned: U256,
+	/// Snapshots of the frames that reverted or failed, in exit order, if
+	/// enabled by `Config::fault_snapshot_stack`.
+	pub fault_snapshots: Vec<FaultSnapshot>,
+	/// Gas and storage accesses by address, if enabled by
+	/// `StackExecutor::enable_gas_attribution`.
+	pub gas_attribution: Option<GasAttribution>,
+	/// Why the transaction was rejected before execution, if it was. The
+	/// exit reason is then the error it converts to.
+	pub validation_error: Option<TxValidationError>,
+	/// Values returned by the backend during the transaction, if enabled
+	/// by `StackExecutor::enable_replay_log`.
+	pub replay_log: Option<ReplayLog>,
+}
+
+impl ExecutionResult {
+	/// Whether the transaction succeeded.
+	pub fn is_succeed(&self) -> bool {
+		self.reason.is_succeed()
+	}
+}
@@ -0,0 +1,200 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use std::sync::Mutex;
+
+use primitive_types::{H256, U256};
+use rlp::RlpStream;
+use sha3::{Digest, Keccak256};
+
+use crate::{MaybeSend, MaybeSync};
+use super::{ExecutionResult, Transaction, TransactionAction};
+
+/// Key of a cached execution result: an identifier of the pre-state, such
+/// as a state root or a block hash, and the hash of the transaction.
+///
+/// Results also depend on the block environment and on the config, so the
+/// pre-state identifier must change whenever either does.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ResultCacheKey {
+	/// Identifier of the pre-state.
+	pub state: H256,
+	/// Hash of the transaction.
+	pub transaction: H256,
+}
+
+impl ResultCacheKey {
+	/// Key of `transaction` executed on the pre-state `state`.
+	pub fn new(state: H256, transaction: &Transaction) -> Self {
+		Self { state, transaction: transaction_hash(transaction) }
+	}
+}
+
+/// Hash of the fields of a transaction, identifying identical calls.
+pub fn transaction_hash(transaction: &Transaction) -> H256 {
+	let mut stream = RlpStream::new_list(6);
+	stream.append(&transaction.caller);
+	match transaction.action {
+		TransactionAction::Call(address) => {
+			stream.append(&address);
+			stream.append_empty_data();
+		},
+		TransactionAction::Create => {
+			stream.append_empty_data();
+			stream.append_empty_data();
+		},
+		TransactionAction::Create2(salt) => {
+			stream.append_empty_data();
+			stream.append(&salt);
+		},
+	}
+	stream.append(&transaction.value);
+	stream.append(&transaction.data);
+	stream.append(&U256::from(transaction.gas_limit));
+	H256::from_slice(Keccak256::digest(&stream.out()).as_slice())
+}
+
+/// Store of execution results, used by `StackExecutor::transact_cached`.
+/// Implementations backed by external stores, such as Redis, serialize the
+/// results themselves.
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+pub trait ResultCache: MaybeSend + MaybeSync {
+	/// Cached result for the key, if any.
+	async fn get(&self, key: &ResultCacheKey) -> Option<ExecutionResult>;
+
+	/// Cache the result for the key.
+	async fn insert(&self, key: ResultCacheKey, result: ExecutionResult);
+
+	/// Drop every result computed on the given pre-state.
+	async fn invalidate(&self, state: H256);
+
+	/// Drop every cached result.
+	async fn clear(&self);
+}
+
+#[derive(Debug, Default)]
+struct LruEntries {
+	results: BTreeMap<ResultCacheKey, (u64, ExecutionResult)>,
+	recency: BTreeMap<u64, ResultCacheKey>,
+	tick: u64,
+	hits: usize,
+	misses: usize,
+}
+
+impl LruEntries {
+	fn touch(&mut self, key: &ResultCacheKey) {
+		self.tick += 1;
+		let tick = self.tick;
+		if let Some((last, _)) = self.results.get_mut(key) {
+			self.recency.remove(last);
+			*last = tick;
+			self.recency.insert(tick, *key);
+		}
+	}
+}
+
+/// In-memory result cache evicting the least recently used result once it
+/// holds `capacity` results.
+#[derive(Debug)]
+pub struct LruResultCache {
+	capacity: usize,
+	entries: Mutex<LruEntries>,
+}
+
+impl LruResultCache {
+	/// Create a cache holding at most `capacity` results.
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			capacity,
+			entries: Mutex::new(LruEntries::default()),
+		}
+	}
+
+	fn lock(&self) -> std::sync::MutexGuard<'_, LruEntries> {
+		self.entries.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	/// Maximum number of cached results.
+	pub fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	/// Number of lookups served from the cache.
+	pub fn hits(&self) -> usize {
+		self.lock().hits
+	}
+
+	/// Number of lookups that found no result.
+	pub fn misses(&self) -> usize {
+		self.lock().misses
+	}
+
+	/// Number of cached results.
+	pub fn len(&self) -> usize {
+		self.lock().results.len()
+	}
+
+	/// Whether no result is cached.
+	pub fn is_empty(&self) -> bool {
+		self.lock().results.is_empty()
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl ResultCache for LruResultCache {
+	async fn get(&self, key: &ResultCacheKey) -> Option<ExecutionResult> {
+		let mut entries = self.lock();
+		let result = entries.results.get(key).map(|(_, result)| result.clone());
+		match result {
+			Some(_) => {
+				entries.hits += 1;
+				entries.touch(key);
+			},
+			None => entries.misses += 1,
+		}
+		result
+	}
+
+	async fn insert(&self, key: ResultCacheKey, result: ExecutionResult) {
+		if self.capacity == 0 {
+			return
+		}
+
+		let mut entries = self.lock();
+		entries.tick += 1;
+		let tick = entries.tick;
+		if let Some((last, _)) = entries.results.insert(key, (tick, result)) {
+			entries.recency.remove(&last);
+		}
+		entries.recency.insert(tick, key);
+
+		while entries.results.len() > self.capacity {
+			let oldest = match entries.recency.keys().next() {
+				Some(tick) => *tick,
+				None => break,
+			};
+			if let Some(key) = entries.recency.remove(&oldest) {
+				entries.results.remove(&key);
+			}
+		}
+	}
+
+	async fn invalidate(&self, state: H256) {
+		let mut entries = self.lock();
+		let stale = entries.results.iter()
+			.filter(|(key, _)| key.state == state)
+			.map(|(key, (tick, _))| (*key, *tick))
+			.collect::<Vec<_>>();
+		for (key, tick) in stale {
+			entries.results.remove(&key);
+			entries.recency.remove(&tick);
+		}
+	}
+
+	async fn clear(&self) {
+		let mut entries = self.lock();
+		entries.results.clear();
+		entries.recency.clear();
+	}
+}
@@ -0,0 +1,130 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+use primitive_types::{H160, H256};
+
+/// Pattern flagged by the security analysis of `StackExecutor`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
+pub enum Finding {
+	/// A frame entered the storage context of a frame still running lower
+	/// in the call stack, through a call from another contract.
+	Reentrancy {
+		/// Re-entered storage context.
+		address: H160,
+		/// Call depth of the re-entering frame.
+		depth: usize,
+	},
+	/// A frame wrote a storage slot it read before calling another
+	/// contract, which could have re-entered it in between.
+	WriteAfterCall {
+		/// Storage context of the frame.
+		address: H160,
+		/// Storage index.
+		index: H256,
+		/// Program counter of the `SSTORE`.
+		position: usize,
+	},
+	/// Code run through `DELEGATECALL` or `CALLCODE` wrote a storage slot
+	/// also accessed by the own code of the storage owner, or the other way
+	/// around.
+	StorageCollision {
+		/// Storage owner.
+		address: H160,
+		/// Address of the delegated code.
+		code_address: H160,
+		/// Storage index.
+		index: H256,
+	},
+}
+
+#[derive(Clone, Debug)]
+struct Frame {
+	address: H160,
+	code_address: H160,
+	read: BTreeSet<H256>,
+	read_before_call: BTreeSet<H256>,
+}
+
+/// State of the security analysis, shared by an executor and its
+/// substates.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SecurityAnalysis {
+	frames: Vec<Frame>,
+	/// Slots accessed by the own code of each account.
+	own: BTreeMap<H160, BTreeSet<H256>>,
+	/// Slots written by delegated code, with the address of the code.
+	delegated: BTreeMap<H160, BTreeMap<H256, H160>>,
+	pub(crate) findings: Vec<Finding>,
+}
+
+impl SecurityAnalysis {
+	fn flag(&mut self, finding: Finding) {
+		if !self.findings.contains(&finding) {
+			self.findings.push(finding);
+		}
+	}
+
+	/// A frame at `depth` started running the code of `code_address` in
+	/// the storage context of `address`.
+	pub(crate) fn enter(&mut self, address: H160, code_address: H160, depth: usize) {
+		self.frames.truncate(depth);
+		let parent = self.frames.last().map(|frame| frame.address);
+		if parent.is_some() && parent != Some(address) && self.frames.iter().any(|frame| frame.address == address) {
+			self.flag(Finding::Reentrancy { address, depth });
+		}
+		self.frames.push(Frame { address, code_address, read: BTreeSet::new(), read_before_call: BTreeSet::new() });
+	}
+
+	fn own_access(&mut self, address: H160, index: H256) {
+		self.own.entry(address).or_default().insert(index);
+		if let Some(code_address) = self.delegated.get(&address).and_then(|slots| slots.get(&index)).cloned() {
+			self.flag(Finding::StorageCollision { address, code_address, index });
+		}
+	}
+
+	/// `SLOAD` of `index` by the frame at `depth`.
+	pub(crate) fn sload(&mut self, depth: usize, index: H256) {
+		let (address, own) = match self.frames.get_mut(depth) {
+			Some(frame) => {
+				frame.read.insert(index);
+				(frame.address, frame.address == frame.code_address)
+			},
+			None => return,
+		};
+		if own {
+			self.own_access(address, index);
+		}
+	}
+
+	/// `SSTORE` to `index` by the frame at `depth`, at `position`.
+	pub(crate) fn sstore(&mut self, depth: usize, index: H256, position: usize) {
+		let frame = match self.frames.get(depth) {
+			Some(frame) => frame,
+			None => return,
+		};
+		let (address, code_address) = (frame.address, frame.code_address);
+		if frame.read_before_call.contains(&index) {
+			self.flag(Finding::WriteAfterCall { address, index, position });
+		}
+
+		if address == code_address {
+			self.own_access(address, index);
+		} else {
+			self.delegated.entry(address).or_default().insert(index, code_address);
+			if self.own.get(&address).map(|slots| slots.contains(&index)).unwrap_or(false) {
+				self.flag(Finding::StorageCollision { address, code_address, index });
+			}
+		}
+	}
+
+	/// `CALL` to `target` by the frame at `depth`.
+	pub(crate) fn call(&mut self, depth: usize, target: H160) {
+		if let Some(frame) = self.frames.get_mut(depth) {
+			if target != frame.address {
+				let read = frame.read.clone();
+				frame.read_before_call.extend(read);
+			}
+		}
+	}
+}
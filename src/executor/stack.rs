@@ -1,16 +1,55 @@
 use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::vec::Vec;
-use core::cmp::min;
+use core::cmp::{max, min};
 use core::convert::Infallible;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
+use std::time::Instant;
 
 use primitive_types::{H160, H256, U256};
-use sha3::{Digest, Keccak256};
 
-use crate::{Capture, Config, Context, CreateScheme, ExitError, ExitReason, ExitSucceed,
-			ExternalOpcode, Handler, Opcode, Runtime, Stack, Transfer};
-use crate::backend::{Apply, Backend, Basic, Log};
+use crate::{Bytes, Capture, Config, Context, CreateScheme, ExitError, ExitFatal, ExitReason,
+			ExitRevert, ExitSucceed, ExternalOpcode, Opcode, Runtime, Stack, StateMutator, StateQuery,
+			Transfer};
+use crate::backend::{Apply, Backend, Basic, Log, merged_storage_range};
 use crate::gasometer::{self, Gasometer};
+use crate::hasher::{Hasher, Keccak256Hasher};
+use super::{AccessSet, AccountState, AnalysisCache, ApplySet, AsyncPrecompile, CHEATCODE_ADDRESS, CallTrace, CancellationToken, Cheatcodes, CodeOverrides, CoverageReport,
+			DefaultFeePolicy, DefaultTxValidator, ExecutionResult, ExecutorEvent, FaultSnapshot, Finding, FeeDistribution, FeeError, FeePolicy, ForkSchedule, FrameGas,
+			AddressResolver, GasAction, GasAttribution, GasHook, HISTORY_SERVE_WINDOW, HISTORY_STORAGE_ADDRESS,
+			KeccakCache, LocationSet, MemoryPool, PrecompileFn, PrecompileOutput, Profile, ReadWriteSet, ReplayLog, ResultCache, ResultCacheKey,
+			SYSTEM_ADDRESS, SYSTEM_CALL_GAS, StorageProvenance, Transaction, TransactionAction, TxValidationError, TxValidator, delegated_address,
+			floor_gas};
+use super::replay::ReplayValue;
+use super::cheatcode::{Cheatcode, ExpectedRevert, Prank, revert_message};
+use super::security::SecurityAnalysis;
+use super::trace::{memory_operand, memory_window};
+use super::cancel::{DEADLINE_CHECK_INTERVAL, YieldNow};
+
+/// Read from the backend through `StackExecutor::read`, recording the value
+/// in the replay log if enabled. With the `tracing` feature, the await
+/// latency is recorded as an event of the current span.
+macro_rules! backend_read {
+	( $self:expr, $method:ident ( $( $arg:expr ),* ) ) => ({
+		#[cfg(feature = "tracing")]
+		let start = std::time::Instant::now();
+		$self.backend_reads.fetch_add(1, Ordering::Relaxed);
+		let value = $self.backend.$method($( $arg ),*).await;
+		$self.record_replay(stringify!($method), &[$( &$arg ),*], value.as_ref().ok());
+		#[cfg(feature = "tracing")]
+		tracing::trace!(
+			method = stringify!($method),
+			latency_us = start.elapsed().as_micros() as u64,
+			"backend read",
+		);
+		$self.read(value)
+	})
+}
+
+/// Minimum length, in bytes, of frame outputs shared with the caller rather
+/// than copied.
+const SHARED_RETURN_LEN: usize = 4096;
 
 /// Account definition for the stack-based executor.
 #[derive(Default, Clone, Debug, Eq, PartialEq)]
@@ -32,19 +71,113 @@ pub struct StackExecutor<B: Backend> {
 	backend: Arc<B>,
 	config: Arc<Config>,
 	gasometer: Gasometer,
+	/// Accounts touched by the transactions, as defined by EIP-161: every
+	/// state change goes through `account_mut`.
 	state: BTreeMap<H160, StackAccount>,
 	deleted: BTreeSet<H160>,
+	burned: U256,
+	/// Size of the log data emitted by the transaction, for
+	/// `Config::max_log_data_size`.
+	log_data: usize,
 	logs: Vec<Log>,
-	precompile: fn(H160, &[u8], Option<usize>) -> Option<Result<(ExitSucceed, Vec<u8>, usize), ExitError>>,
+	precompile: PrecompileFn,
+	async_precompile: Option<Arc<dyn AsyncPrecompile<B>>>,
 	is_static: bool,
 	depth: Option<usize>,
+	context: Option<Context>,
+	call_traces: Vec<CallTrace>,
+	tx_validator: Arc<dyn TxValidator<B>>,
+	fee_policy: Arc<dyn FeePolicy>,
+	gas_hook: Option<Arc<dyn GasHook>>,
+	address_resolver: Option<Arc<dyn AddressResolver>>,
+	coverage: Option<CoverageReport>,
+	profile: Option<Profile>,
+	gas_attribution: Option<GasAttribution>,
+	provenance: Option<BTreeMap<(H160, H256), StorageProvenance>>,
+	frame: usize,
+	next_frame: usize,
+	position: usize,
+	events: Option<Sender<ExecutorEvent>>,
+	cancellation: Option<CancellationToken>,
+	yield_interval: Option<usize>,
+	deadline: Option<Instant>,
+	floor_gas: usize,
+	backend_error: Arc<Mutex<Option<B::Error>>>,
+	backend_reads: Arc<AtomicUsize>,
+	accessed: Arc<Mutex<AccessSet>>,
+	replay: Option<Arc<Mutex<ReplayLog>>>,
+	/// Locations read by this executor and its substates.
+	reads: Arc<Mutex<LocationSet>>,
+	/// Locations written by this frame and its succeeded substates.
+	writes: LocationSet,
+	state_allowlist: Option<Arc<AccessSet>>,
+	missing_state: Arc<Mutex<AccessSet>>,
+	fault_snapshots: Arc<Mutex<Vec<FaultSnapshot>>>,
+	cheatcodes: Option<Arc<Mutex<Cheatcodes>>>,
+	security: Option<Arc<Mutex<SecurityAnalysis>>>,
+	code_overrides: Option<Arc<CodeOverrides>>,
+	keccak_cache: Option<Arc<Mutex<KeccakCache>>>,
+	hasher: Arc<dyn Hasher>,
+	system_address: H160,
+	pool: Arc<Mutex<MemoryPool>>,
+	analysis: Arc<Mutex<AnalysisCache>>,
+	/// `AnalysisCache::fingerprint` of the config, computed once.
+	analysis_fingerprint: H256,
+	origin: Option<H160>,
+	origin_override: Option<H160>,
+	#[cfg(feature = "auth")]
+	authorized: Option<H160>,
+}
+
+/// What a `StackExecutor` tracked before a transaction, set aside by
+/// `start_transaction`.
+struct TransactionStart {
+	logs: usize,
+	backend_reads: usize,
+	accessed: AccessSet,
+	reads: LocationSet,
+	writes: LocationSet,
+	burned: U256,
+	deleted: BTreeSet<H160>,
+	fault_snapshots: usize,
+	gas_attribution: Option<GasAttribution>,
+	replay_log: Option<ReplayLog>,
+}
+
+/// Write-protection check for opcodes executed inside a static call frame,
+/// per EIP-214.
+fn static_check(
+	opcode: Result<Opcode, ExternalOpcode>,
+	stack: &Stack,
+) -> Result<(), ExitError> {
+	match opcode {
+		Err(ExternalOpcode::SStore) | Err(ExternalOpcode::Log(_)) |
+		Err(ExternalOpcode::Create) | Err(ExternalOpcode::Create2) |
+		Err(ExternalOpcode::Suicide) => Err(ExitError::StaticModeViolation),
+		#[cfg(feature = "auth")]
+		Err(ExternalOpcode::AuthCall) => {
+			if U256::from_big_endian(&stack.peek(2)?[..]) != U256::zero() {
+				Err(ExitError::StaticModeViolation)
+			} else {
+				Ok(())
+			}
+		},
+		Err(ExternalOpcode::Call) => {
+			if U256::from_big_endian(&stack.peek(2)?[..]) != U256::zero() {
+				Err(ExitError::StaticModeViolation)
+			} else {
+				Ok(())
+			}
+		},
+		_ => Ok(()),
+	}
 }
 
 fn no_precompile(
 	_address: H160,
 	_input: &[u8],
 	_target_gas: Option<usize>
-) -> Option<Result<(ExitSucceed, Vec<u8>, usize), ExitError>> {
+) -> Option<PrecompileOutput> {
 	None
 }
 
@@ -58,23 +191,82 @@ impl<B: Backend> StackExecutor<B> {
 		Self::new_with_precompile(backend, gas_limit, config, no_precompile)
 	}
 
+	/// Create a new stack-based executor with the config of the block of the
+	/// backend environment in the given fork schedule.
+	pub async fn new_for_block(
+		backend: Arc<B>,
+		gas_limit: usize,
+		schedule: &ForkSchedule,
+	) -> Result<Self, B::Error> {
+		fn low_u64(value: U256) -> u64 {
+			if value > U256::from(u64::MAX) { u64::MAX } else { value.as_u64() }
+		}
+
+		let number = low_u64(backend.block_number().await?);
+		let timestamp = low_u64(backend.block_timestamp().await?);
+		Ok(Self::new(backend, gas_limit, schedule.config_at(number, timestamp)))
+	}
+
 	/// Create a new stack-based executor with given precompiles.
 	pub fn new_with_precompile(
 		backend: Arc<B>,
 		gas_limit: usize,
 		config: Arc<Config>,
-		precompile: fn(H160, &[u8], Option<usize>) -> Option<Result<(ExitSucceed, Vec<u8>, usize), ExitError>>,
+		precompile: PrecompileFn,
 	) -> Self {
 		Self {
 			backend,
 			gasometer: Gasometer::new(gas_limit, config.clone()),
+			analysis_fingerprint: AnalysisCache::fingerprint(&config),
 			state: BTreeMap::new(),
 			deleted: BTreeSet::new(),
+			burned: U256::zero(),
+			log_data: 0,
 			config,
 			logs: Vec::new(),
 			precompile,
+			async_precompile: None,
 			is_static: false,
 			depth: None,
+			context: None,
+			call_traces: Vec::new(),
+			tx_validator: Arc::new(DefaultTxValidator),
+			fee_policy: Arc::new(DefaultFeePolicy::default()),
+			gas_hook: None,
+			address_resolver: None,
+			coverage: None,
+			profile: None,
+			gas_attribution: None,
+			provenance: None,
+			frame: 0,
+			next_frame: 0,
+			position: 0,
+			events: None,
+			cancellation: None,
+			yield_interval: None,
+			deadline: None,
+			floor_gas: 0,
+			backend_error: Arc::new(Mutex::new(None)),
+			backend_reads: Arc::new(AtomicUsize::new(0)),
+			accessed: Arc::new(Mutex::new(AccessSet::default())),
+			replay: None,
+			reads: Arc::new(Mutex::new(LocationSet::default())),
+			writes: LocationSet::default(),
+			state_allowlist: None,
+			missing_state: Arc::new(Mutex::new(AccessSet::default())),
+			fault_snapshots: Arc::new(Mutex::new(Vec::new())),
+			cheatcodes: None,
+			security: None,
+			code_overrides: None,
+			keccak_cache: None,
+			hasher: Arc::new(Keccak256Hasher),
+			system_address: SYSTEM_ADDRESS,
+			pool: Arc::new(Mutex::new(MemoryPool::default())),
+			analysis: Arc::new(Mutex::new(AnalysisCache::default())),
+			origin: None,
+			origin_override: None,
+			#[cfg(feature = "auth")]
+			authorized: None,
 		}
 	}
 
@@ -86,21 +278,714 @@ impl<B: Backend> StackExecutor<B> {
 			config: self.config.clone(),
 			state: self.state.clone(),
 			deleted: self.deleted.clone(),
-			logs: self.logs.clone(),
+			burned: self.burned,
+			log_data: self.log_data,
+			logs: Vec::new(),
 			precompile: self.precompile,
+			async_precompile: self.async_precompile.clone(),
 			is_static: is_static || self.is_static,
 			depth: match self.depth {
 				None => Some(0),
 				Some(n) => Some(n + 1),
 			},
+			context: None,
+			call_traces: Vec::new(),
+			tx_validator: self.tx_validator.clone(),
+			fee_policy: self.fee_policy.clone(),
+			gas_hook: self.gas_hook.clone(),
+			address_resolver: self.address_resolver.clone(),
+			coverage: self.coverage.as_ref().map(|_| CoverageReport::default()),
+			profile: self.profile.as_ref().map(|_| Profile::default()),
+			gas_attribution: self.gas_attribution.as_ref().map(|_| GasAttribution::default()),
+			provenance: self.provenance.clone(),
+			frame: self.next_frame,
+			next_frame: self.next_frame + 1,
+			position: 0,
+			events: self.events.clone(),
+			cancellation: self.cancellation.clone(),
+			yield_interval: self.yield_interval,
+			deadline: self.deadline,
+			floor_gas: 0,
+			backend_error: self.backend_error.clone(),
+			backend_reads: self.backend_reads.clone(),
+			accessed: self.accessed.clone(),
+			replay: self.replay.clone(),
+			reads: self.reads.clone(),
+			writes: LocationSet::default(),
+			state_allowlist: self.state_allowlist.clone(),
+			missing_state: self.missing_state.clone(),
+			fault_snapshots: self.fault_snapshots.clone(),
+			cheatcodes: self.cheatcodes.clone(),
+			security: self.security.clone(),
+			code_overrides: self.code_overrides.clone(),
+			keccak_cache: self.keccak_cache.clone(),
+			hasher: self.hasher.clone(),
+			system_address: self.system_address,
+			pool: self.pool.clone(),
+			analysis: self.analysis.clone(),
+			analysis_fingerprint: self.analysis_fingerprint,
+			origin: self.origin,
+			origin_override: self.origin_override,
+			#[cfg(feature = "auth")]
+			authorized: None,
+		}
+	}
+
+	/// Replace the validator invoked before transactions execute.
+	pub fn set_tx_validator(&mut self, validator: Arc<dyn TxValidator<B>>) {
+		self.tx_validator = validator;
+	}
+
+	/// Invoke the given hook after the gas of every instruction is charged.
+	/// Without a hook, instructions are charged without any call.
+	pub fn set_gas_hook(&mut self, hook: Arc<dyn GasHook>) {
+		self.gas_hook = Some(hook);
+	}
+
+	/// Rewrite the caller and target addresses of frames with the given
+	/// resolver before entering them. `None` removes the resolver.
+	pub fn set_address_resolver(&mut self, resolver: Option<Arc<dyn AddressResolver>>) {
+		self.address_resolver = resolver;
+	}
+
+	/// Depth of the frames entered by this executor.
+	fn frame_depth(&self) -> usize {
+		self.depth.map(|depth| depth + 1).unwrap_or(0)
+	}
+
+	/// Replace the synchronous precompiles.
+	pub fn set_precompile(&mut self, precompile: PrecompileFn) {
+		self.precompile = precompile;
+	}
+
+	/// Consult the given asynchronous precompiles for calls to addresses the
+	/// synchronous precompiles do not handle.
+	pub fn set_async_precompile(&mut self, precompile: Arc<dyn AsyncPrecompile<B>>) {
+		self.async_precompile = Some(precompile);
+	}
+
+	/// Replace the fee policy used for intrinsic gas, refunds and fee
+	/// settlement.
+	pub fn set_fee_policy(&mut self, policy: Arc<dyn FeePolicy>) {
+		self.fee_policy = policy;
+	}
+
+	/// Charge the intrinsic gas of a transaction carrying the given number
+	/// of EIP-7702 authorizations, and check its gas limit covers the
+	/// EIP-7623 floor if enabled.
+	fn record_intrinsic_gas(&mut self, transaction: &Transaction, authorizations: usize) -> Result<(), TxValidationError> {
+		let cost = self.fee_policy.intrinsic_gas(transaction, &self.config)
+			.saturating_add(authorizations.saturating_mul(self.config.gas_transaction_authorization));
+		let floor = floor_gas(transaction, &self.config);
+		let needed = max(cost, floor.unwrap_or_default());
+		if transaction.gas_limit < needed {
+			return Err(TxValidationError::GasLimitTooLow { needed })
+		}
+		if let Some(floor) = floor {
+			self.floor_gas = floor;
+		}
+
+		self.gasometer.record_cost(cost).map_err(|_| TxValidationError::GasLimitTooLow { needed })
+	}
+
+	async fn validate_transaction(&mut self, transaction: &Transaction) -> Result<(), TxValidationError> {
+		self.origin = Some(transaction.caller);
+		self.log_data = 0;
+		self.call_traces.clear();
+		if let Some(limit) = self.config.max_initcode_size {
+			let size = transaction.data.len();
+			if !matches!(transaction.action, TransactionAction::Call(_)) && size > limit {
+				return Err(TxValidationError::InitCodeTooLarge { size, limit })
+			}
+		}
+		if let Some(got) = transaction.nonce.filter(|_| !self.config.disable_nonce_check) {
+			let expected = self.nonce(transaction.caller).await;
+			if got > expected {
+				return Err(TxValidationError::NonceTooHigh { expected, got })
+			}
+			if got < expected {
+				return Err(TxValidationError::NonceTooLow { expected, got })
+			}
+		}
+		let validator = self.tx_validator.clone();
+		validator.validate(self, transaction).await
+	}
+
+	/// Override `ORIGIN` for subsequent executions, regardless of the
+	/// transaction caller. `None` removes the override.
+	pub fn set_origin(&mut self, origin: Option<H160>) {
+		self.origin_override = origin;
+	}
+
+	/// Share a pool of memory and stack buffers, for example between the
+	/// executors of consecutive transactions.
+	pub fn set_memory_pool(&mut self, pool: Arc<Mutex<MemoryPool>>) {
+		self.pool = pool;
+	}
+
+	/// Pool of memory and stack buffers reused by call frames.
+	pub fn memory_pool(&self) -> Arc<Mutex<MemoryPool>> {
+		self.pool.clone()
+	}
+
+	fn lock_pool(&self) -> std::sync::MutexGuard<'_, MemoryPool> {
+		self.pool.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	/// Share a cache of analyzed code, for example between the executors of
+	/// consecutive transactions.
+	pub fn set_analysis_cache(&mut self, cache: Arc<Mutex<AnalysisCache>>) {
+		self.analysis = cache;
+	}
+
+	/// Cache of analyzed code used by call frames.
+	pub fn analysis_cache(&self) -> Arc<Mutex<AnalysisCache>> {
+		self.analysis.clone()
+	}
+
+	/// Create a runtime for a new frame over cached analyzed code, with
+	/// memory and stack buffers taken from the pool.
+	fn new_runtime(&self, code: Vec<u8>, data: Bytes, context: Context) -> Runtime {
+		let analysis = self.analysis.lock().unwrap_or_else(|e| e.into_inner())
+			.get_or_analyze_with(code, &self.config, self.analysis_fingerprint, &*self.hasher);
+		let mut runtime = Runtime::new_analyzed(analysis, data, context, self.config.clone());
+		let (memory, stack) = self.lock_pool().take();
+		runtime.machine_mut().reuse_buffers(memory, stack);
+		runtime
+	}
+
+	/// Return the output of a finished frame and give its buffers back to
+	/// the pool. Only succeeded and reverted frames have output. Outputs of
+	/// at least `SHARED_RETURN_LEN` bytes share the frame memory instead of
+	/// copying it, and the memory is not pooled.
+	fn finish_runtime(&self, runtime: Runtime, reason: ExitReason) -> Bytes {
+		let mut machine = runtime.into_machine();
+		let output = match reason {
+			ExitReason::Succeed(_) | ExitReason::Revert(_) if machine.return_len() >= U256::from(SHARED_RETURN_LEN) =>
+				machine.take_return_value(),
+			ExitReason::Succeed(_) | ExitReason::Revert(_) => machine.return_value().into(),
+			_ => Bytes::new(),
+		};
+		let (memory, stack) = machine.into_buffers();
+		self.lock_pool().give(memory, stack);
+		output
+	}
+
+	/// Stream execution events to the given channel. Events are dropped
+	/// once the receiver hangs up.
+	pub fn set_event_sender(&mut self, sender: Sender<ExecutorEvent>) {
+		self.events = Some(sender);
+	}
+
+	/// Abort execution with `ExitFatal::Cancelled` once the token is
+	/// cancelled.
+	pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+		self.cancellation = Some(token);
+	}
+
+	/// Yield to the async runtime every `steps` instructions, so that a long
+	/// execution does not starve the other tasks of its thread. `None`, the
+	/// default, never yields.
+	pub fn set_yield_interval(&mut self, steps: Option<usize>) {
+		self.yield_interval = steps.filter(|steps| *steps > 0);
+	}
+
+	/// Abort execution with `ExitFatal::DeadlineExceeded` once the deadline
+	/// passes. The clock is read every `DEADLINE_CHECK_INTERVAL`
+	/// instructions. See `gas_deadline` for a deadline proportional to gas.
+	pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+		self.deadline = deadline;
+	}
+
+	fn emit(&self, event: ExecutorEvent) {
+		if let Some(events) = self.events.as_ref() {
+			let _ = events.send(event);
+		}
+	}
+
+	/// Record visited program counters and branch outcomes of this and all
+	/// subsequent executions.
+	pub fn enable_coverage(&mut self) {
+		self.coverage.get_or_insert_with(CoverageReport::default);
+	}
+
+	/// Coverage recorded so far, if enabled.
+	pub fn coverage(&self) -> Option<&CoverageReport> {
+		self.coverage.as_ref()
+	}
+
+	/// Record call depth, stack and memory watermarks, and gas per frame and
+	/// per instruction, of this and all subsequent executions.
+	pub fn enable_profile(&mut self) {
+		self.profile.get_or_insert_with(Profile::default);
+	}
+
+	/// Profile recorded so far, if enabled.
+	pub fn profile(&self) -> Option<&Profile> {
+		self.profile.as_ref()
+	}
+
+	/// Attribute gas and storage accesses to addresses in this and all
+	/// subsequent executions.
+	pub fn enable_gas_attribution(&mut self) {
+		self.gas_attribution.get_or_insert_with(GasAttribution::default);
+	}
+
+	/// Gas attribution recorded so far, if enabled.
+	pub fn gas_attribution(&self) -> Option<&GasAttribution> {
+		self.gas_attribution.as_ref()
+	}
+
+	/// Track the frame and program counter of the last write to each
+	/// storage slot in this and all subsequent executions.
+	pub fn enable_storage_provenance(&mut self) {
+		self.provenance.get_or_insert_with(BTreeMap::new);
+	}
+
+	/// Last writer of each storage slot written so far, if enabled. Writes
+	/// of reverted frames are discarded.
+	pub fn storage_provenance(&self) -> Option<&BTreeMap<(H160, H256), StorageProvenance>> {
+		self.provenance.as_ref()
+	}
+
+	/// Take the first backend failure met by this executor or its substates.
+	/// Execution exits with `ExitFatal::BackendError` once a read fails.
+	pub fn take_backend_error(&mut self) -> Option<B::Error> {
+		self.lock_backend_error().take()
+	}
+
+	/// Number of reads this executor and its substates issued to the backend.
+	pub fn backend_reads(&self) -> usize {
+		self.backend_reads.load(Ordering::Relaxed)
+	}
+
+	/// Accounts and storage slots accessed by this executor and its
+	/// substates, including those of reverted frames.
+	pub fn accessed(&self) -> AccessSet {
+		self.lock_accessed().clone()
+	}
+
+	fn lock_accessed(&self) -> std::sync::MutexGuard<'_, AccessSet> {
+		self.accessed.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	/// Log the values returned by the backend to this executor and its
+	/// substates from now on, see `ReplayLog`.
+	pub fn enable_replay_log(&mut self) {
+		self.replay.get_or_insert_with(|| Arc::new(Mutex::new(ReplayLog::default())));
+	}
+
+	/// Values returned by the backend so far, if the replay log is enabled.
+	pub fn replay_log(&self) -> Option<ReplayLog> {
+		self.replay.as_ref().map(|replay| replay.lock().unwrap_or_else(|e| e.into_inner()).clone())
+	}
+
+	fn record_replay<T: ReplayValue>(&self, method: &'static str, args: &[&dyn ReplayValue], value: Option<&T>) {
+		if let Some(replay) = self.replay.as_ref() {
+			let mut input = Vec::new();
+			for arg in args {
+				arg.encode(&mut input);
+			}
+			let value = value.map(|value| {
+				let mut encoded = Vec::new();
+				value.encode(&mut encoded);
+				self.hasher.hash(&encoded)
+			});
+			replay.lock().unwrap_or_else(|e| e.into_inner()).record(&*self.hasher, method, input, value);
+		}
+	}
+
+	/// Locations read by this executor and its substates, including those
+	/// of reverted frames, and written by the changes it kept.
+	pub fn read_write_set(&self) -> ReadWriteSet {
+		ReadWriteSet { reads: self.lock_reads().clone(), writes: self.writes.clone() }
+	}
+
+	fn lock_reads(&self) -> std::sync::MutexGuard<'_, LocationSet> {
+		self.reads.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	/// Only allow access to the given accounts and storage slots, for
+	/// executing against partial state. Storage of accounts in
+	/// `cleared_storage` is considered fully known. Execution exits with
+	/// `ExitFatal::MissingState` once other state is accessed. `None`
+	/// allows all state.
+	pub fn set_state_allowlist(&mut self, allowlist: Option<AccessSet>) {
+		self.state_allowlist = allowlist.map(Arc::new);
+	}
+
+	/// Accounts and storage slots accessed outside the state allowlist by
+	/// this executor and its substates.
+	pub fn missing_state(&self) -> AccessSet {
+		self.lock_missing_state().clone()
+	}
+
+	fn lock_missing_state(&self) -> std::sync::MutexGuard<'_, AccessSet> {
+		self.missing_state.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn has_missing_state(&self) -> bool {
+		self.state_allowlist.is_some() && !self.lock_missing_state().is_empty()
+	}
+
+	fn touch(&self, address: H160) {
+		self.lock_accessed().accounts.insert(address);
+		if let Some(allowlist) = self.state_allowlist.as_ref() {
+			if !allowlist.accounts.contains(&address) {
+				self.lock_missing_state().accounts.insert(address);
+			}
+		}
+	}
+
+	fn touch_storage(&self, address: H160, index: H256) {
+		self.lock_accessed().storage.insert((address, index));
+		if let Some(allowlist) = self.state_allowlist.as_ref() {
+			if !allowlist.storage.contains(&(address, index)) && !allowlist.cleared_storage.contains(&address) {
+				self.lock_missing_state().storage.insert((address, index));
+			}
+		}
+	}
+
+	/// Current value of a storage slot, from the state or the backend,
+	/// without recording the access.
+	async fn current_storage(&self, address: H160, index: H256) -> H256 {
+		let value = self.state.get(&address)
+			.and_then(|v| {
+				let s = v.storage.get(&index).cloned();
+
+				if v.reset_storage {
+					Some(s.unwrap_or(H256::default()))
+				} else {
+					s
+				}
+
+			});
+
+		match value {
+			Some(value) => value,
+			None => backend_read!(self, storage(address, index)),
+		}
+	}
+
+	/// Take the snapshots of the frames of this executor and its substates
+	/// that reverted or failed, recorded with `Config::fault_snapshot_stack`.
+	pub fn take_fault_snapshots(&mut self) -> Vec<FaultSnapshot> {
+		core::mem::take(&mut *self.lock_fault_snapshots())
+	}
+
+	fn lock_fault_snapshots(&self) -> std::sync::MutexGuard<'_, Vec<FaultSnapshot>> {
+		self.fault_snapshots.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn lock_backend_error(&self) -> std::sync::MutexGuard<'_, Option<B::Error>> {
+		self.backend_error.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn has_backend_error(&self) -> bool {
+		self.lock_backend_error().is_some()
+	}
+
+	/// Unwrap a backend read, recording the first failure and substituting a
+	/// default value.
+	fn read<T: Default>(&self, value: Result<T, B::Error>) -> T {
+		value.unwrap_or_else(|e| {
+			self.lock_backend_error().get_or_insert(e);
+			T::default()
+		})
+	}
+
+	/// Replace the exit reason by `ExitFatal::BackendError` once a backend
+	/// read failed, or by `ExitFatal::MissingState` once state outside the
+	/// allowlist was accessed.
+	fn check_backend(&self, reason: ExitReason) -> ExitReason {
+		if self.has_backend_error() {
+			ExitFatal::BackendError.into()
+		} else if self.has_missing_state() {
+			ExitFatal::MissingState.into()
+		} else {
+			reason
+		}
+	}
+
+	/// Handle calls to `CHEATCODE_ADDRESS` from now on. Cheatcodes only
+	/// affect calls and creates made by executed code.
+	pub fn enable_cheatcodes(&mut self) {
+		self.cheatcodes.get_or_insert_with(|| Arc::new(Mutex::new(Cheatcodes::default())));
+	}
+
+	/// Snapshot of the state set through cheatcodes, if enabled.
+	pub fn cheatcodes(&self) -> Option<Cheatcodes> {
+		self.lock_cheatcodes().map(|cheatcodes| cheatcodes.clone())
+	}
+
+	fn lock_cheatcodes(&self) -> Option<std::sync::MutexGuard<'_, Cheatcodes>> {
+		self.cheatcodes.as_ref().map(|cheatcodes| cheatcodes.lock().unwrap_or_else(|e| e.into_inner()))
+	}
+
+	/// Flag reentrancy and delegated storage collision patterns in this and
+	/// all subsequent executions, including frames that revert.
+	pub fn enable_security_analysis(&mut self) {
+		self.security.get_or_insert_with(|| Arc::new(Mutex::new(SecurityAnalysis::default())));
+	}
+
+	/// Findings of the security analysis so far, in the order they were
+	/// met, if enabled. Each finding is reported once.
+	pub fn security_findings(&self) -> Option<Vec<Finding>> {
+		self.lock_security().map(|security| security.findings.clone())
+	}
+
+	fn lock_security(&self) -> Option<std::sync::MutexGuard<'_, SecurityAnalysis>> {
+		self.security.as_ref().map(|security| security.lock().unwrap_or_else(|e| e.into_inner()))
+	}
+
+	/// Run the given replacement code instead of the code of accounts, in
+	/// this and all subsequent executions. `None` removes the overrides.
+	pub fn set_code_overrides(&mut self, overrides: Option<CodeOverrides>) {
+		self.code_overrides = overrides.map(Arc::new);
+	}
+
+	/// Replacement code of `address`, if overridden. With `ext_code`, only
+	/// if the overrides apply to `EXTCODE*` as well.
+	fn code_override(&self, address: H160, ext_code: bool) -> Option<&Vec<u8>> {
+		self.code_overrides.as_ref()
+			.filter(|overrides| !ext_code || overrides.ext_code)
+			.and_then(|overrides| overrides.get(address))
+	}
+
+	/// Memoize Keccak256 digests of `SHA3` preimages and `CREATE2` init
+	/// code from now on.
+	pub fn enable_keccak_cache(&mut self) {
+		self.keccak_cache.get_or_insert_with(|| Arc::new(Mutex::new(KeccakCache::default())));
+	}
+
+	/// Snapshot of the Keccak256 memoization, if enabled.
+	pub fn keccak_cache(&self) -> Option<KeccakCache> {
+		self.keccak_cache.as_ref()
+			.map(|cache| cache.lock().unwrap_or_else(|e| e.into_inner()).clone())
+	}
+
+	/// Compute `SHA3`, code hashes and created addresses with the given
+	/// hash function instead of Keccak256. Set it before executing, as the
+	/// Keccak256 memoization is not cleared.
+	pub fn set_hasher(&mut self, hasher: Arc<dyn Hasher>) {
+		self.hasher = hasher;
+	}
+
+	/// Caller of `transact_system_call`, `SYSTEM_ADDRESS` by default.
+	pub fn set_system_address(&mut self, address: H160) {
+		self.system_address = address;
+	}
+
+	/// Execute a call to the cheatcode address. Cheatcodes changing state
+	/// fail in a static context.
+	async fn cheatcode(&mut self, input: &[u8], is_static: bool) -> (ExitReason, Bytes) {
+		let cheatcode = match Cheatcode::decode(input) {
+			Some(cheatcode) => cheatcode,
+			None => return (ExitRevert::Reverted.into(), revert_message("unknown cheatcode").into()),
+		};
+
+		if let Cheatcode::Deal(address, balance) = cheatcode {
+			if is_static {
+				return (ExitError::StaticModeViolation.into(), Bytes::new())
+			}
+			self.account_mut(address).await.basic.balance = balance;
+			self.writes.balances.insert(address);
+			return (ExitSucceed::Returned.into(), Bytes::new())
+		}
+
+		let depth = self.depth;
+		let mut cheatcodes = self.lock_cheatcodes().expect("cheatcodes are enabled");
+		match cheatcode {
+			Cheatcode::Prank(caller) => {
+				cheatcodes.prank = Some(Prank { caller, depth, persistent: false });
+			},
+			Cheatcode::StartPrank(caller) => {
+				cheatcodes.prank = Some(Prank { caller, depth, persistent: true });
+			},
+			Cheatcode::StopPrank => cheatcodes.prank = None,
+			Cheatcode::Warp(timestamp) => cheatcodes.timestamp = Some(timestamp),
+			Cheatcode::Roll(number) => cheatcodes.number = Some(number),
+			Cheatcode::ExpectRevert(data, prefix) => {
+				cheatcodes.expected_revert = Some(ExpectedRevert { data, prefix, depth });
+			},
+			Cheatcode::Deal(..) => unreachable!("handled above"),
+		}
+
+		(ExitSucceed::Returned.into(), Bytes::new())
+	}
+
+	/// Take the caller override and revert expectation applying to a call
+	/// made from this frame.
+	fn take_cheatcodes(&self) -> (Option<H160>, Option<ExpectedRevert>) {
+		let depth = self.depth;
+		let mut cheatcodes = match self.lock_cheatcodes() {
+			Some(cheatcodes) => cheatcodes,
+			None => return (None, None),
+		};
+
+		let caller = match cheatcodes.prank {
+			Some(prank) if prank.depth == depth => {
+				if !prank.persistent {
+					cheatcodes.prank = None;
+				}
+				Some(prank.caller)
+			},
+			_ => None,
+		};
+		let expected_revert = match cheatcodes.expected_revert {
+			Some(ref expected) if expected.depth == depth => cheatcodes.expected_revert.take(),
+			_ => None,
+		};
+
+		(caller, expected_revert)
+	}
+
+	/// Turn the result of a call expected to revert into success, or into a
+	/// revert if it did not revert as expected.
+	fn check_expected_revert(
+		expected: ExpectedRevert,
+		reason: ExitReason,
+		output: Bytes,
+	) -> (ExitReason, Bytes) {
+		match reason {
+			ExitReason::Revert(_) | ExitReason::Error(_) if expected.matches(&output) =>
+				(ExitSucceed::Returned.into(), Bytes::new()),
+			ExitReason::Revert(_) | ExitReason::Error(_) =>
+				(ExitRevert::Reverted.into(), revert_message("call reverted with unexpected data").into()),
+			ExitReason::Succeed(_) =>
+				(ExitRevert::Reverted.into(), revert_message("call did not revert as expected").into()),
+			ExitReason::Fatal(_) => (reason, output),
 		}
 	}
 
 	/// Execute the runtime until it returns.
 	pub async fn execute(&mut self, runtime: &mut Runtime) -> ExitReason {
-		match runtime.run(self).await {
-			Capture::Exit(s) => s,
-			Capture::Trap(_) => unreachable!("Trap is Infallible"),
+		let mut reason = self.execute_runtime(runtime).await;
+		if let (Some(limit), ExitReason::Succeed(_) | ExitReason::Revert(_)) = (self.config.max_return_data_size, reason) {
+			if runtime.machine().return_len() > U256::from(limit) {
+				reason = ExitError::ReturnDataLimit.into();
+				runtime.machine_mut().exit(reason);
+			}
+		}
+		self.check_backend(reason)
+	}
+
+	async fn execute_runtime(&mut self, runtime: &mut Runtime) -> ExitReason {
+		if self.coverage.is_none() && self.provenance.is_none() && self.cancellation.is_none()
+			&& self.profile.is_none() && self.state_allowlist.is_none()
+			&& self.yield_interval.is_none() && self.deadline.is_none()
+			&& self.config.fault_snapshot_stack.is_none() && self.security.is_none()
+		{
+			return match runtime.run(self).await {
+				Capture::Exit(s) => s,
+				Capture::Trap(_) => unreachable!("Trap is Infallible"),
+			}
+		}
+
+		let code_hash = self.hasher.hash(runtime.machine().code());
+		let mut steps = 0usize;
+		loop {
+			if self.cancellation.as_ref().map(|c| c.is_cancelled()).unwrap_or(false) {
+				let reason = ExitFatal::Cancelled.into();
+				runtime.machine_mut().exit(reason);
+				return reason
+			}
+
+			steps = steps.wrapping_add(1);
+			if let Some(deadline) = self.deadline {
+				if steps.is_multiple_of(DEADLINE_CHECK_INTERVAL) && Instant::now() >= deadline {
+					let reason = ExitFatal::DeadlineExceeded.into();
+					runtime.machine_mut().exit(reason);
+					return reason
+				}
+			}
+			if let Some(interval) = self.yield_interval {
+				if steps.is_multiple_of(interval) {
+					YieldNow::new().await;
+				}
+			}
+
+			if let Ok(position) = runtime.machine().position() {
+				self.position = *position;
+			}
+
+			if let (Some(coverage), Ok(position)) = (self.coverage.as_mut(), runtime.machine().position()) {
+				let code = coverage.codes.entry(code_hash).or_default();
+				*code.hits.entry(*position).or_insert(0) += 1;
+
+				// JUMPI, taken when the condition below the destination is non-zero.
+				if runtime.machine().code().get(*position) == Some(&0x57) {
+					if let Ok(condition) = runtime.machine().stack().peek(1) {
+						let branch = code.branches.entry(*position).or_insert((0, 0));
+						if condition != H256::default() {
+							branch.0 += 1;
+						} else {
+							branch.1 += 1;
+						}
+					}
+				}
+			}
+
+			let gas = self.gasometer.gas();
+			let position = runtime.machine().position().ok();
+			// Opcode, top stack items and memory operand of the instruction,
+			// for a snapshot if it exits the frame with a failure.
+			let fault = match (self.config.fault_snapshot_stack, position) {
+				(Some(items), Some(position)) => {
+					let machine = runtime.machine();
+					let stack = machine.stack();
+					let opcode = machine.code().get(position).cloned().unwrap_or(0);
+					let top = (0..min(items, stack.len()))
+						.map(|index| stack.peek(index).expect("index is within the stack"))
+						.collect::<Vec<_>>();
+					Some((position, opcode, top, memory_operand(opcode, stack)))
+				},
+				_ => None,
+			};
+			let step = match runtime.step(self).await {
+				Ok(()) => None,
+				Err(Capture::Exit(s)) => Some(s),
+				Err(Capture::Trap(_)) => unreachable!("Trap is Infallible"),
+			};
+
+			if let (Some(reason), Some((position, opcode, stack, operand))) = (step, fault) {
+				if !reason.is_succeed() {
+					let (memory_offset, memory) = operand
+						.map(|operand| memory_window(runtime.machine().memory(), operand))
+						.unwrap_or_default();
+					self.lock_fault_snapshots().push(FaultSnapshot {
+						frame: self.frame,
+						depth: self.depth.unwrap_or(0),
+						position,
+						opcode,
+						reason,
+						stack,
+						memory_offset,
+						memory,
+					});
+				}
+			}
+
+			if let Some(profile) = self.profile.as_mut() {
+				let machine = runtime.machine();
+				profile.max_depth = profile.max_depth.max(self.depth.unwrap_or(0));
+				profile.max_stack = profile.max_stack.max(machine.stack().len());
+				profile.max_memory = profile.max_memory.max(machine.memory().len());
+				if let Some(position) = position {
+					*profile.instructions.entry((code_hash, position)).or_insert(0) +=
+						gas.saturating_sub(self.gasometer.gas());
+				}
+			}
+
+			if let Some(reason) = step {
+				return reason
+			}
+
+			if self.has_missing_state() {
+				let reason = ExitFatal::MissingState.into();
+				runtime.machine_mut().exit(reason);
+				return reason
+			}
 		}
 	}
 
@@ -109,14 +994,102 @@ impl<B: Backend> StackExecutor<B> {
 		self.gasometer.gas()
 	}
 
+	/// Context of the frame this executor runs, or `None` for the top-level
+	/// executor. Storage accesses of the frame apply to `context.address`,
+	/// which for `DELEGATECALL` and `CALLCODE` is the calling contract.
+	pub fn context(&self) -> Option<&Context> {
+		self.context.as_ref()
+	}
+
+	/// Address whose storage the frame this executor runs reads and writes.
+	pub fn storage_address(&self) -> Option<H160> {
+		self.context.as_ref().map(|context| context.address)
+	}
+
+	/// Call traces of frames entered from the current executor, cleared
+	/// when a transaction starts.
+	pub fn call_traces(&self) -> &[CallTrace] {
+		&self.call_traces
+	}
+
+	/// Record the call trace of a finished substate executor. Must be called
+	/// before the substate is merged.
+	fn record_trace<OB: Backend>(
+		&mut self,
+		substate: &mut StackExecutor<OB>,
+		is_create: bool,
+		address: H160,
+		gas_limit: usize,
+		reason: ExitReason,
+	) {
+		let (gas_returned, gas_refunded) = match reason {
+			ExitReason::Succeed(_) =>
+				(substate.gasometer.gas(), substate.gasometer.refunded_gas()),
+			ExitReason::Revert(_) => (substate.gasometer.gas(), 0),
+			ExitReason::Error(_) | ExitReason::Fatal(_) => (0, 0),
+		};
+
+		self.emit(ExecutorEvent::Exit {
+			address,
+			depth: substate.depth.unwrap_or(0),
+			reason,
+		});
+
+		if let Some(profile) = self.profile.as_mut() {
+			if let Some(other) = substate.profile.take() {
+				profile.merge(other);
+			}
+			profile.frames.push(FrameGas {
+				frame: substate.frame,
+				depth: substate.depth.unwrap_or(0),
+				address,
+				gas_used: gas_limit - gas_returned,
+			});
+		}
+
+		if let Some(attribution) = self.gas_attribution.as_mut() {
+			if let Some(other) = substate.gas_attribution.take() {
+				attribution.merge(other);
+			}
+			let inner = substate.call_traces.iter().map(|trace| trace.gas_used).sum::<usize>();
+			let usage = attribution.addresses.entry(address).or_default();
+			usage.calls += 1;
+			usage.gas_used += (gas_limit - gas_returned).saturating_sub(inner);
+		}
+
+		self.call_traces.push(CallTrace {
+			is_create,
+			address,
+			context: substate.context.clone().expect("frames have a context"),
+			gas_limit,
+			gas_used: gas_limit - gas_returned,
+			gas_returned,
+			gas_refunded,
+			reason,
+			calls: core::mem::take(&mut substate.call_traces),
+		});
+	}
+
+	fn merge_tracking<OB: Backend>(&mut self, substate: &mut StackExecutor<OB>) {
+		self.next_frame = substate.next_frame;
+		if let (Some(coverage), Some(other)) = (self.coverage.as_mut(), substate.coverage.take()) {
+			coverage.merge(other);
+		}
+	}
+
 	/// Merge a substate executor that succeeded.
 	pub fn merge_succeed<OB: Backend>(
 		&mut self,
 		mut substate: StackExecutor<OB>
 	) -> Result<(), ExitError> {
+		self.merge_tracking(&mut substate);
 		self.logs.append(&mut substate.logs);
 		self.deleted.append(&mut substate.deleted);
+		self.burned = substate.burned;
+		self.log_data = substate.log_data;
 		self.state = substate.state;
+		self.writes.extend(&substate.writes);
+		self.provenance = substate.provenance;
 
 		self.gasometer.record_stipend(substate.gasometer.gas())?;
 		self.gasometer.record_refund(substate.gasometer.refunded_gas())?;
@@ -128,7 +1101,7 @@ impl<B: Backend> StackExecutor<B> {
 		&mut self,
 		mut substate: StackExecutor<OB>
 	) -> Result<(), ExitError> {
-		self.logs.append(&mut substate.logs);
+		self.merge_tracking(&mut substate);
 
 		self.gasometer.record_stipend(substate.gasometer.gas())?;
 		Ok(())
@@ -139,7 +1112,7 @@ impl<B: Backend> StackExecutor<B> {
 		&mut self,
 		mut substate: StackExecutor<OB>
 	) -> Result<(), ExitError> {
-		self.logs.append(&mut substate.logs);
+		self.merge_tracking(&mut substate);
 
 		Ok(())
 	}
@@ -152,23 +1125,14 @@ impl<B: Backend> StackExecutor<B> {
 		init_code: Vec<u8>,
 		gas_limit: usize,
 	) -> ExitReason {
-		let transaction_cost = gasometer::create_transaction_cost(&init_code);
-		match self.gasometer.record_transaction(transaction_cost) {
-			Ok(()) => (),
-			Err(e) => return e.into(),
-		}
-
-		match self.create_inner(
+		self.execute_create(Transaction {
 			caller,
-			CreateScheme::Legacy { caller },
+			action: TransactionAction::Create,
 			value,
-			init_code,
-			Some(gas_limit),
-			false,
-		).await {
-			Capture::Exit((s, _, _)) => s,
-			Capture::Trap(_) => unreachable!(),
-		}
+			data: init_code,
+			gas_limit,
+			nonce: None,
+		}, false).await.unwrap_or_else(|e| self.check_backend(e.into()))
 	}
 
 	/// Execute a `CREATE2` transaction.
@@ -180,65 +1144,493 @@ impl<B: Backend> StackExecutor<B> {
 		salt: H256,
 		gas_limit: usize,
 	) -> ExitReason {
-		let transaction_cost = gasometer::create_transaction_cost(&init_code);
-		match self.gasometer.record_transaction(transaction_cost) {
-			Ok(()) => (),
-			Err(e) => return e.into(),
-		}
-		let code_hash = H256::from_slice(Keccak256::digest(&init_code).as_slice());
+		self.execute_create(Transaction {
+			caller,
+			action: TransactionAction::Create2(salt),
+			value,
+			data: init_code,
+			gas_limit,
+			nonce: None,
+		}, false).await.unwrap_or_else(|e| self.check_backend(e.into()))
+	}
+
+	/// Execute a create transaction, buying its gas and settling its fee
+	/// with `pay_gas`.
+	async fn execute_create(&mut self, transaction: Transaction, pay_gas: bool) -> Result<ExitReason, TxValidationError> {
+		let gas_price = self.check_transaction(&transaction, 0, pay_gas).await?;
+		let Transaction { caller, action, value, data: init_code, gas_limit, .. } = transaction;
+		let scheme = match action {
+			TransactionAction::Create2(salt) => {
+				let code_hash = self.keccak256(&init_code);
+				CreateScheme::Create2 { caller, code_hash, salt }
+			},
+			_ => CreateScheme::Legacy { caller },
+		};
 
-		match self.create_inner(
+		let reason = match self.create_inner(
 			caller,
-			CreateScheme::Create2 { caller, code_hash, salt },
+			scheme,
 			value,
-			init_code,
+			init_code.into(),
 			Some(gas_limit),
 			false,
 		).await {
 			Capture::Exit((s, _, _)) => s,
 			Capture::Trap(_) => unreachable!(),
+		};
+		let reason = self.settle_gas(caller, gas_limit, gas_price).await.err().unwrap_or(reason);
+		Ok(self.check_backend(reason))
+	}
+
+	/// Execute a `CALL` transaction.
+	pub async fn transact_call(
+		&mut self,
+		caller: H160,
+		address: H160,
+		value: U256,
+		data: Vec<u8>,
+		gas_limit: usize,
+	) -> (ExitReason, Vec<u8>) {
+		self.execute_call(Transaction {
+			caller,
+			action: TransactionAction::Call(address),
+			value,
+			data,
+			gas_limit,
+			nonce: None,
+		}, false).await.unwrap_or_else(|e| (self.check_backend(e.into()), Vec::new()))
+	}
+
+	/// Validate the transaction, buy its gas with `pay_gas` and record its
+	/// intrinsic gas, including that of its `authorizations`, returning the
+	/// gas price. Gas bought for a rejected transaction is refunded.
+	async fn check_transaction(
+		&mut self,
+		transaction: &Transaction,
+		authorizations: usize,
+		pay_gas: bool,
+	) -> Result<U256, TxValidationError> {
+		self.validate_transaction(transaction).await?;
+		let gas_price = self.buy_gas(transaction, pay_gas).await?;
+		if let Err(e) = self.record_intrinsic_gas(transaction, authorizations) {
+			self.deposit(transaction.caller, U256::from(transaction.gas_limit) * gas_price).await;
+			return Err(e)
+		}
+		Ok(gas_price)
+	}
+
+	/// Execute a call transaction, buying its gas and settling its fee
+	/// with `pay_gas`.
+	async fn execute_call(
+		&mut self,
+		transaction: Transaction,
+		pay_gas: bool,
+	) -> Result<(ExitReason, Vec<u8>), TxValidationError> {
+		let gas_price = self.check_transaction(&transaction, 0, pay_gas).await?;
+		let Transaction { caller, action, value, data, gas_limit, .. } = transaction;
+		let address = match action {
+			TransactionAction::Call(address) => address,
+			_ => unreachable!("only call transactions are executed as calls"),
+		};
+
+		self.increment_nonce(caller).await;
+
+		let (reason, output) = self.call_transaction(caller, address, value, data, gas_limit).await;
+		let reason = self.settle_gas(caller, gas_limit, gas_price).await.err().unwrap_or(reason);
+		Ok((self.check_backend(reason), output))
+	}
+
+	/// Check that the gas price is at least the base fee of the fee policy,
+	/// unless `Config::disable_base_fee` is set, and withdraw the gas limit
+	/// of the transaction at the gas price from its caller, who must also
+	/// afford the value, returning the price. Nothing is checked or
+	/// withdrawn without `pay_gas` or with `Config::disable_balance_check`.
+	async fn buy_gas(&mut self, transaction: &Transaction, pay_gas: bool) -> Result<U256, TxValidationError> {
+		if !pay_gas {
+			return Ok(U256::zero())
+		}
+		let gas_price = backend_read!(self, gas_price());
+		let base_fee = self.fee_policy.base_fee();
+		if !self.config.disable_base_fee && gas_price < base_fee {
+			return Err(TxValidationError::FeeCapTooLow { gas_price, base_fee })
+		}
+		if self.config.disable_balance_check {
+			return Ok(U256::zero())
+		}
+
+		let cost = U256::from(transaction.gas_limit).checked_mul(gas_price);
+		let need = cost.and_then(|cost| cost.checked_add(transaction.value));
+		let have = self.balance(transaction.caller).await;
+		let (cost, need) = match (cost, need) {
+			(Some(cost), Some(need)) if have >= need => (cost, need),
+			(_, need) => return Err(TxValidationError::InsufficientFunds { have, need: need.unwrap_or(U256::MAX) }),
+		};
+		self.withdraw(transaction.caller, cost).await
+			.map_err(|_| TxValidationError::InsufficientFunds { have, need })?;
+		Ok(gas_price)
+	}
+
+	/// Give the gas bought by `buy_gas` back to the caller, and charge the
+	/// fee of the used gas through `settle_fees`, adding its burned part to
+	/// the burned amount. A distribution not adding up to the fee leaves the
+	/// gas unpaid and fails the transaction.
+	async fn settle_gas(&mut self, caller: H160, gas_limit: usize, gas_price: U256) -> Result<(), ExitReason> {
+		if gas_price.is_zero() {
+			return Ok(())
+		}
+		self.deposit(caller, U256::from(gas_limit) * gas_price).await;
+		match self.settle_fees(caller, gas_price).await {
+			Ok(distribution) => {
+				self.burned = self.burned.saturating_add(distribution.burned);
+				Ok(())
+			},
+			Err(FeeError::Exit(e)) => Err(e.into()),
+			Err(FeeError::InvalidDistribution { .. }) => Err(ExitFatal::Other("invalid fee distribution").into()),
+			Err(FeeError::Backend(e)) => {
+				*self.lock_backend_error() = Some(e);
+				Err(ExitFatal::BackendError.into())
+			},
+		}
+	}
+
+	async fn call_transaction(
+		&mut self,
+		caller: H160,
+		address: H160,
+		value: U256,
+		data: Vec<u8>,
+		gas_limit: usize,
+	) -> (ExitReason, Vec<u8>) {
+		let context = Context {
+			caller,
+			address,
+			apparent_value: value,
+		};
+
+		match self.call_inner(address, Some(Transfer {
+			source: caller,
+			target: address,
+			value
+		}), data.into(), Some(gas_limit), false, false, false, context).await {
+			Capture::Exit((s, v)) => (self.check_backend(s), v.into_vec()),
+			Capture::Trap(_) => unreachable!(),
+		}
+	}
+
+	/// Run `code` with the given input in a new frame at `context.address`,
+	/// as if it were the code of that address, with all remaining gas. The
+	/// frame is tracked and merged like a call frame, without a transfer.
+	pub async fn execute_code(
+		&mut self,
+		code: Vec<u8>,
+		input: Vec<u8>,
+		context: Context,
+	) -> (ExitReason, Vec<u8>) {
+		let gas_limit = self.gasometer.gas();
+		if let Err(e) = self.gasometer.record_cost(gas_limit) {
+			return (e.into(), Vec::new())
+		}
+
+		let address = context.address;
+		let mut substate = self.substate(gas_limit, false);
+		substate.context = Some(context.clone());
+		substate.account_mut(address).await;
+		substate.emit(ExecutorEvent::Enter {
+			is_create: false,
+			caller: context.caller,
+			address,
+			depth: substate.depth.unwrap_or(0),
+		});
+		if let Some(mut security) = substate.lock_security() {
+			security.enter(address, address, substate.depth.unwrap_or(0));
+		}
+
+		let mut runtime = self.new_runtime(code, input.into(), context);
+		let reason = substate.execute(&mut runtime).await;
+		let output = self.finish_runtime(runtime, reason);
+		self.record_trace(&mut substate, false, address, gas_limit, reason);
+
+		match reason {
+			ExitReason::Succeed(_) => { let _ = self.merge_succeed(substate); },
+			ExitReason::Revert(_) => { let _ = self.merge_revert(substate); },
+			ExitReason::Error(_) => { let _ = self.merge_fail(substate); },
+			ExitReason::Fatal(_) => { self.gasometer.fail(); },
+		}
+
+		(self.check_backend(reason), output.into_vec())
+	}
+
+	/// Call `address` from the system address, as protocol-level calls
+	/// made each block do. The call is not a transaction: no value is
+	/// transferred, no intrinsic gas is charged, and the nonce and balance
+	/// of the system address are neither checked nor changed. The call runs
+	/// with `SYSTEM_CALL_GAS`, leaving the gas of the executor untouched.
+	/// Calling an address without code succeeds with no output.
+	pub async fn transact_system_call(&mut self, address: H160, data: Vec<u8>) -> (ExitReason, Vec<u8>) {
+		let caller = self.system_address;
+		self.origin = Some(caller);
+		self.log_data = 0;
+		self.call_traces.clear();
+
+		let code = self.code(address).await;
+		if code.is_empty() {
+			return (self.check_backend(ExitSucceed::Stopped.into()), Vec::new())
+		}
+
+		let system_gasometer = Gasometer::new(SYSTEM_CALL_GAS, self.gasometer.config());
+		let gasometer = core::mem::replace(&mut self.gasometer, system_gasometer);
+		let result = self.execute_code(code, data, Context { caller, address, apparent_value: U256::zero() }).await;
+		self.gasometer = gasometer;
+		result
+	}
+
+	/// Execute an EIP-7702 set code transaction. Valid authorizations set
+	/// the code of their authority to a delegation designator before the
+	/// call; invalid ones are skipped. Gas is bought and settled as by
+	/// `transact`, and the gas limit must cover the cost of every
+	/// authorization on top of the intrinsic gas.
+	#[cfg(feature = "k256")]
+	pub async fn transact_set_code(
+		&mut self,
+		caller: H160,
+		address: H160,
+		value: U256,
+		data: Vec<u8>,
+		gas_limit: usize,
+		authorizations: &[crate::signing::SignedAuthorization],
+	) -> ExecutionResult {
+		let transaction = Transaction {
+			caller,
+			action: TransactionAction::Call(address),
+			value,
+			data,
+			gas_limit,
+			nonce: None,
+		};
+		let start = self.start_transaction();
+		let executed = self.execute_set_code(transaction, authorizations).await;
+		self.finish_transaction(start, executed)
+	}
+
+	/// Execute a set code transaction, buying its gas and settling its fee.
+	#[cfg(feature = "k256")]
+	async fn execute_set_code(
+		&mut self,
+		transaction: Transaction,
+		authorizations: &[crate::signing::SignedAuthorization],
+	) -> Result<(ExitReason, Vec<u8>), TxValidationError> {
+		if !self.config.has_set_code {
+			return Ok((ExitFatal::NotSupported.into(), Vec::new()))
+		}
+		if authorizations.is_empty() {
+			return Ok((ExitError::Other("empty authorization list").into(), Vec::new()))
+		}
+
+		let gas_price = self.check_transaction(&transaction, authorizations.len(), true).await?;
+		let Transaction { caller, action, value, data, gas_limit, .. } = transaction;
+		let address = match action {
+			TransactionAction::Call(address) => address,
+			_ => unreachable!("set code transactions are calls"),
+		};
+
+		self.increment_nonce(caller).await;
+
+		let chain_id = self.chain_id().await;
+		for authorization in authorizations {
+			self.apply_authorization(authorization, chain_id).await;
+		}
+
+		let (reason, output) = self.call_transaction(caller, address, value, data, gas_limit).await;
+		let reason = self.settle_gas(caller, gas_limit, gas_price).await.err().unwrap_or(reason);
+		Ok((self.check_backend(reason), output))
+	}
+
+	/// Apply an authorization if it is valid, per EIP-7702. Returns whether
+	/// it was applied.
+	#[cfg(feature = "k256")]
+	async fn apply_authorization(
+		&mut self,
+		authorization: &crate::signing::SignedAuthorization,
+		chain_id: U256,
+	) -> bool {
+		let crate::signing::Authorization { chain_id: authorized_chain, address, nonce } =
+			authorization.authorization;
+		if !authorized_chain.is_zero() && authorized_chain != chain_id {
+			return false
+		}
+		if nonce >= U256::from(u64::MAX) {
+			return false
+		}
+		let authority = match authorization.authority() {
+			Ok(authority) => authority,
+			Err(_) => return false,
+		};
+
+		let code = self.code(authority).await;
+		if !code.is_empty() && delegated_address(&code).is_none() {
+			return false
+		}
+		if self.nonce(authority).await != nonce {
+			return false
 		}
+
+		if self.exists(authority).await {
+			let _ = self.gasometer.record_refund(self.config.refund_authorization_existing);
+		}
+
+		let code = if address == H160::zero() { Vec::new() } else { super::delegation_designator(address) };
+		self.account_mut(authority).await.code = Some(code);
+		self.writes.code.insert(authority);
+		self.increment_nonce(authority).await;
+		true
 	}
 
-	/// Execute a `CALL` transaction.
-	pub async fn transact_call(
-		&mut self,
-		caller: H160,
-		address: H160,
-		value: U256,
-		data: Vec<u8>,
-		gas_limit: usize,
-	) -> (ExitReason, Vec<u8>) {
-		let transaction_cost = gasometer::call_transaction_cost(&data);
-		match self.gasometer.record_transaction(transaction_cost) {
-			Ok(()) => (),
-			Err(e) => return (e.into(), Vec::new()),
+	/// Execute a transaction, dispatching on its action.
+	pub async fn transact(&mut self, transaction: Transaction) -> ExecutionResult {
+		let start = self.start_transaction();
+		let executed = match transaction.action {
+			TransactionAction::Call(_) => self.execute_call(transaction, true).await,
+			TransactionAction::Create | TransactionAction::Create2(_) =>
+				self.execute_create(transaction, true).await.map(|reason| (reason, Vec::new())),
+		};
+		self.finish_transaction(start, executed)
+	}
+
+	/// Set aside what the executor tracked before a transaction, so that
+	/// `finish_transaction` reports the transaction alone.
+	fn start_transaction(&mut self) -> TransactionStart {
+		let accessed = core::mem::take(&mut *self.lock_accessed());
+		let reads = core::mem::take(&mut *self.lock_reads());
+		let fault_snapshots = self.lock_fault_snapshots().len();
+		TransactionStart {
+			logs: self.logs.len(),
+			backend_reads: self.backend_reads(),
+			accessed,
+			reads,
+			writes: core::mem::take(&mut self.writes),
+			burned: self.burned,
+			deleted: self.deleted.clone(),
+			fault_snapshots,
+			gas_attribution: self.gas_attribution.as_mut().map(core::mem::take),
+			replay_log: self.replay.as_ref()
+				.map(|replay| core::mem::take(&mut *replay.lock().unwrap_or_else(|e| e.into_inner()))),
 		}
+	}
 
-		self.account_mut(caller).await.basic.nonce += U256::one();
+	/// Result of the transaction started by `start_transaction`, merging
+	/// back what the executor tracked before it.
+	fn finish_transaction(
+		&mut self,
+		start: TransactionStart,
+		executed: Result<(ExitReason, Vec<u8>), TxValidationError>,
+	) -> ExecutionResult {
+		let TransactionStart {
+			logs,
+			backend_reads,
+			accessed: previous,
+			reads: previous_reads,
+			writes: previous_writes,
+			burned,
+			deleted,
+			fault_snapshots,
+			gas_attribution: attribution,
+			replay_log: replay,
+		} = start;
+		let (reason, output, validation_error) = match executed {
+			Ok((reason, output)) => (reason, output, None),
+			Err(e) => (self.check_backend(e.into()), Vec::new(), Some(e)),
+		};
 
-		let context = Context {
-			caller,
-			address,
-			apparent_value: value,
+		let accessed = {
+			let mut lock = self.lock_accessed();
+			let accessed = lock.clone();
+			lock.extend(&previous);
+			accessed
 		};
+		let read_write_set = ReadWriteSet {
+			reads: {
+				let mut lock = self.lock_reads();
+				let reads = core::mem::replace(&mut *lock, previous_reads);
+				lock.extend(&reads);
+				reads
+			},
+			writes: core::mem::replace(&mut self.writes, previous_writes),
+		};
+		self.writes.extend(&read_write_set.writes);
+		let gas_attribution = match (self.gas_attribution.as_mut(), attribution) {
+			(Some(current), Some(previous)) => {
+				let attribution = core::mem::replace(current, previous);
+				current.merge(attribution.clone());
+				Some(attribution)
+			},
+			_ => None,
+		};
+		let replay_log = match (self.replay.as_ref(), replay) {
+			(Some(current), Some(previous)) => {
+				let mut lock = current.lock().unwrap_or_else(|e| e.into_inner());
+				let log = core::mem::replace(&mut *lock, previous);
+				lock.extend(&*self.hasher, &log);
+				Some(log)
+			},
+			_ => None,
+		};
+		let trace = self.call_traces.first();
+		let gas_used = self.used_gas();
+		let burned = self.deleted.difference(&deleted)
+			.filter_map(|address| self.state.get(address))
+			.fold(self.burned - burned, |burned, account| burned.saturating_add(account.basic.balance));
+
+		ExecutionResult {
+			reason,
+			output,
+			created_address: trace
+				.filter(|trace| trace.is_create && reason.is_succeed())
+				.map(|trace| trace.address),
+			logs: self.logs[logs..].to_vec(),
+			gas_used,
+			gas_refunded: self.gasometer.total_used_gas().saturating_sub(gas_used),
+			gas_forwarded: trace.map(|trace| trace.gas_limit).unwrap_or(0),
+			accessed,
+			read_write_set,
+			backend_reads: self.backend_reads() - backend_reads,
+			burned,
+			fault_snapshots: self.lock_fault_snapshots().split_off(fault_snapshots),
+			gas_attribution,
+			validation_error,
+			replay_log,
+		}
+	}
 
-		match self.call_inner(address, Some(Transfer {
-			source: caller,
-			target: address,
-			value
-		}), data, Some(gas_limit), false, false, false, context).await {
-			Capture::Exit((s, v)) => (s, v),
-			Capture::Trap(_) => unreachable!(),
+	/// Execute a transaction on the pre-state identified by `state`, serving
+	/// identical transactions from `cache`. A cached result is returned
+	/// without executing, so the executor state is left unchanged: this is
+	/// meant for calls whose state changes are discarded, such as
+	/// `eth_call`. Results of fatal exits and of executions that met a
+	/// backend error are not cached.
+	pub async fn transact_cached(
+		&mut self,
+		cache: &dyn ResultCache,
+		state: H256,
+		transaction: Transaction,
+	) -> ExecutionResult {
+		let key = ResultCacheKey::new(state, &transaction);
+		if let Some(result) = cache.get(&key).await {
+			return result
+		}
+
+		let result = self.transact(transaction).await;
+		if !matches!(result.reason, ExitReason::Fatal(_)) && !self.has_backend_error() {
+			cache.insert(key, result.clone()).await;
 		}
+		result
 	}
 
 	/// Get used gas for the current executor, given the price.
 	pub fn used_gas(
 		&self,
 	) -> usize {
-		self.gasometer.total_used_gas() -
-			min(self.gasometer.total_used_gas() / 2, self.gasometer.refunded_gas() as usize)
+		let used_gas = self.gasometer.total_used_gas();
+		max(used_gas - self.fee_policy.refund(used_gas, self.gasometer.refunded_gas()), self.floor_gas)
 	}
 
 	/// Get fee needed for the current executor, given the price.
@@ -250,11 +1642,47 @@ impl<B: Backend> StackExecutor<B> {
 		U256::from(used_gas) * price
 	}
 
-	/// Deconstruct the executor, return state to be applied.
+	/// Charge the caller for the used gas and distribute the fee according
+	/// to the fee policy. Fails without charging anything if the credited
+	/// and burned amounts of the distribution do not add up to the fee.
+	pub async fn settle_fees(
+		&mut self,
+		caller: H160,
+		gas_price: U256,
+	) -> Result<FeeDistribution, FeeError<B::Error>> {
+		let used_gas = self.used_gas();
+		let coinbase = backend_read!(self, block_coinbase());
+		if let Some(e) = self.take_backend_error() {
+			return Err(FeeError::Backend(e))
+		}
+		let distribution = self.fee_policy.distribute(used_gas, gas_price, coinbase);
+		let fee = self.fee(gas_price);
+		let distributed = distribution.total();
+		if distributed != Some(fee) {
+			return Err(FeeError::InvalidDistribution { fee, distributed })
+		}
+
+		let withdrawn = self.withdraw(caller, fee).await;
+		if let Some(e) = self.take_backend_error() {
+			return Err(FeeError::Backend(e))
+		}
+		withdrawn.map_err(FeeError::Exit)?;
+		for (recipient, amount) in &distribution.credits {
+			self.deposit(*recipient, *amount).await;
+		}
+		if let Some(e) = self.take_backend_error() {
+			return Err(FeeError::Backend(e))
+		}
+
+		Ok(distribution)
+	}
+
+	/// Deconstruct the executor, return state to be applied. After EIP-161,
+	/// touched accounts left empty are deleted.
 	#[must_use]
 	pub fn deconstruct(
 		self
-	) -> (impl IntoIterator<Item=Apply<impl IntoIterator<Item=(H256, H256)>>>,
+	) -> (impl IntoIterator<Item=Apply<impl IntoIterator<Item=(H256, H256)>>, IntoIter: Send>,
 		  impl IntoIterator<Item=Log>)
 	{
 		let mut applies = Vec::<Apply<BTreeMap<H256, H256>>>::new();
@@ -264,6 +1692,14 @@ impl<B: Backend> StackExecutor<B> {
 				continue
 			}
 
+			// The code of touched accounts with a zero nonce is always known
+			// when empty, see `account_mut`.
+			let state = account.code.as_ref().map(|code| AccountState::of(&account.basic, code.len()));
+			if !self.config.empty_considered_exists && state == Some(AccountState::Empty) {
+				applies.push(Apply::Delete { address });
+				continue
+			}
+
 			applies.push(Apply::Modify {
 				address,
 				basic: account.basic,
@@ -282,24 +1718,138 @@ impl<B: Backend> StackExecutor<B> {
 		(applies, logs)
 	}
 
-	/// Get mutable account reference.
+	/// Deconstruct the executor into an `ApplySet`, with the state it
+	/// accessed as reads.
+	#[must_use]
+	pub fn into_apply_set(self) -> ApplySet {
+		let reads = self.accessed();
+		let (applies, logs) = self.deconstruct();
+		ApplySet::from_applies(applies, logs).with_reads(reads)
+	}
+
+	/// Get mutable account reference, touching the account.
 	pub async fn account_mut(&mut self, address: H160) -> &mut StackAccount {
-		self.state.entry(address).or_insert(StackAccount {
-			basic: self.backend.basic(address).await,
-			code: None,
-			storage: BTreeMap::new(),
-			reset_storage: false,
-		})
+		self.touch(address);
+		if !self.state.contains_key(&address) {
+			let basic = backend_read!(self, basic(address));
+			// The nonce of an account never decreases, so whether a touched
+			// account is left empty is known without reading its code again.
+			let code = if !self.config.empty_considered_exists && basic.nonce == U256::zero() &&
+				backend_read!(self, code_size(address)) == 0
+			{
+				Some(Vec::new())
+			} else {
+				None
+			};
+			self.state.insert(address, StackAccount {
+				basic,
+				code,
+				storage: BTreeMap::new(),
+				reset_storage: false,
+			});
+		}
+		self.state.get_mut(&address).expect("account was inserted above")
+	}
+
+	/// Code run by calls to the address. With EIP-7702, an account
+	/// delegated to another runs the code of the delegate: a delegate that
+	/// is itself delegated is not followed, so its designator fails as
+	/// invalid code, and a precompile delegate runs the code stored at its
+	/// address, usually none, rather than the precompile.
+	/// `EXTCODESIZE`, `EXTCODECOPY` and `EXTCODEHASH` of a delegated
+	/// account see the designator, and of a precompile the code stored at
+	/// its address, usually none. Code overrides replace the code of the
+	/// address or its delegate.
+	pub async fn executed_code(&self, address: H160) -> Vec<u8> {
+		if let Some(code) = self.code_override(address, false) {
+			self.touch(address);
+			return code.clone()
+		}
+		let code = self.code(address).await;
+		match delegated_address(&code) {
+			Some(delegate) if self.config.has_set_code => match self.code_override(delegate, false) {
+				Some(code) => {
+					self.touch(delegate);
+					code.clone()
+				},
+				None => self.code(delegate).await,
+			},
+			_ => code,
+		}
+	}
+
+	/// Charge a call to `address` for the access of its EIP-7702 delegate,
+	/// if it is delegated: the warm access cost if the delegate is already
+	/// in `accessed`, the cold access cost otherwise. Access costs of other
+	/// accounts (EIP-2929) are not implemented.
+	async fn record_delegate_access(&mut self, address: H160) -> Result<(), ExitError> {
+		if !self.config.has_set_code || self.code_override(address, false).is_some() {
+			return Ok(())
+		}
+		let delegate = match delegated_address(&self.code(address).await) {
+			Some(delegate) => delegate,
+			None => return Ok(()),
+		};
+		let cost = if self.lock_accessed().accounts.contains(&delegate) {
+			self.config.gas_delegate_warm_access
+		} else {
+			self.config.gas_delegate_cold_access
+		};
+		self.touch(delegate);
+		self.gasometer.record_cost(cost)
+	}
+
+	/// Whether the account exists and is empty, with the changes of this
+	/// executor applied.
+	pub async fn account_state(&self, address: H160) -> AccountState {
+		self.touch(address);
+		self.lock_reads().accounts.insert(address);
+		let (basic, code) = match self.state.get(&address) {
+			Some(account) => (account.basic.clone(), account.code.as_ref().map(|code| code.len())),
+			None => {
+				if !backend_read!(self, exists(address)) {
+					return AccountState::NonExistent
+				}
+				(backend_read!(self, basic(address)), None)
+			},
+		};
+		let code_size = match code {
+			Some(code_size) => code_size,
+			None => backend_read!(self, code_size(address)),
+		};
+		AccountState::of(&basic, code_size)
+	}
+
+	/// Get up to `limit` non-zero storage values of address, in index order,
+	/// starting at index `start`, with pending writes of this executor applied.
+	pub async fn storage_range(
+		&self,
+		address: H160,
+		start: H256,
+		limit: usize,
+	) -> Result<Vec<(H256, H256)>, B::Error> {
+		match self.state.get(&address) {
+			Some(account) => merged_storage_range(
+				&*self.backend, address, &account.storage, account.reset_storage, start, limit,
+			).await,
+			None => self.backend.storage_range(address, start, limit).await,
+		}
 	}
 
 	/// Get account nonce.
 	pub async fn nonce(&self, address: H160) -> U256 {
-		self.state.get(&address).map(|v| v.basic.nonce)
-			.unwrap_or(self.backend.basic(address).await.nonce)
+		self.touch(address);
+		self.lock_reads().nonces.insert(address);
+		match self.state.get(&address) {
+			Some(account) => account.basic.nonce,
+			None => backend_read!(self, basic(address)).nonce,
+		}
 	}
 
 	/// Withdraw balance from address.
 	pub async fn withdraw(&mut self, address: H160, balance: U256) -> Result<(), ExitError> {
+		self.lock_reads().balances.insert(address);
+		self.writes.balances.insert(address);
 		let source = self.account_mut(address).await;
 		if source.basic.balance < balance {
 			return Err(ExitError::OutOfFund.into())
@@ -311,10 +1861,19 @@ impl<B: Backend> StackExecutor<B> {
 
 	/// Deposit balance to address.
 	pub async fn deposit(&mut self, address: H160, balance: U256) {
+		self.lock_reads().balances.insert(address);
+		self.writes.balances.insert(address);
 		let target = self.account_mut(address).await;
 		target.basic.balance += balance;
 	}
 
+	/// Increment the nonce of the account.
+	async fn increment_nonce(&mut self, address: H160) {
+		self.lock_reads().nonces.insert(address);
+		self.writes.nonces.insert(address);
+		self.account_mut(address).await.basic.nonce += U256::one();
+	}
+
 	/// Transfer balance with the given struct.
 	pub async fn transfer(&mut self, transfer: Transfer) -> Result<(), ExitError> {
 		self.withdraw(transfer.source, transfer.value).await?;
@@ -327,19 +1886,19 @@ impl<B: Backend> StackExecutor<B> {
 	pub async fn create_address(&self, scheme: CreateScheme) -> H160 {
 		match scheme {
 			CreateScheme::Create2 { caller, code_hash, salt } => {
-				let mut hasher = Keccak256::new();
-				hasher.input(&[0xff]);
-				hasher.input(&caller[..]);
-				hasher.input(&salt[..]);
-				hasher.input(&code_hash[..]);
-				H256::from_slice(hasher.result().as_slice()).into()
+				let mut preimage = [0u8; 85];
+				preimage[0] = 0xff;
+				preimage[1..21].copy_from_slice(&caller[..]);
+				preimage[21..53].copy_from_slice(&salt[..]);
+				preimage[53..].copy_from_slice(&code_hash[..]);
+				self.hasher.hash(&preimage).into()
 			},
 			CreateScheme::Legacy { caller } => {
 				let nonce = self.nonce(caller).await;
 				let mut stream = rlp::RlpStream::new_list(2);
 				stream.append(&caller);
 				stream.append(&nonce);
-				H256::from_slice(Keccak256::digest(&stream.out()).as_slice()).into()
+				self.hasher.hash(&stream.out()).into()
 			},
 			CreateScheme::Fixed(naddress) => {
 				naddress
@@ -352,15 +1911,73 @@ impl<B: Backend> StackExecutor<B> {
 		caller: H160,
 		scheme: CreateScheme,
 		value: U256,
-		init_code: Vec<u8>,
+		init_code: Bytes,
+		target_gas: Option<usize>,
+		take_l64: bool,
+	) -> Capture<(ExitReason, Option<H160>, Bytes), Infallible> {
+		let (caller, scheme) = match self.address_resolver.as_ref() {
+			Some(resolver) => {
+				let caller = resolver.caller(caller, self.frame_depth());
+				(caller, match scheme {
+					CreateScheme::Legacy { .. } => CreateScheme::Legacy { caller },
+					CreateScheme::Create2 { code_hash, salt, .. } =>
+						CreateScheme::Create2 { caller, code_hash, salt },
+					CreateScheme::Fixed(address) => CreateScheme::Fixed(address),
+				})
+			},
+			None => (caller, scheme),
+		};
+
+		#[cfg(feature = "tracing")]
+		let span = if self.depth.is_none() {
+			tracing::info_span!(
+				"transaction", kind = "create", caller = ?caller, gas_limit = ?target_gas,
+				address = tracing::field::Empty, gas_used = tracing::field::Empty,
+				reason = tracing::field::Empty,
+			)
+		} else {
+			tracing::debug_span!(
+				"create", depth = ?self.depth, caller = ?caller, gas_limit = ?target_gas,
+				address = tracing::field::Empty, gas_used = tracing::field::Empty,
+				reason = tracing::field::Empty,
+			)
+		};
+
+		#[cfg(feature = "tracing")]
+		let traces = self.call_traces.len();
+		let result = self.create_frame(caller, scheme, value, init_code, target_gas, take_l64);
+		#[cfg(feature = "tracing")]
+		let result = tracing::Instrument::instrument(result, span.clone());
+		let result = result.await;
+
+		#[cfg(feature = "tracing")]
+		if let Capture::Exit((reason, address, _)) = &result {
+			span.record("reason", tracing::field::debug(reason));
+			if let Some(address) = address {
+				span.record("address", tracing::field::debug(address));
+			}
+			if self.call_traces.len() > traces {
+				span.record("gas_used", self.call_traces[self.call_traces.len() - 1].gas_used);
+			}
+		}
+
+		result
+	}
+
+	async fn create_frame(
+		&mut self,
+		caller: H160,
+		scheme: CreateScheme,
+		value: U256,
+		init_code: Bytes,
 		target_gas: Option<usize>,
 		take_l64: bool,
-	) -> Capture<(ExitReason, Option<H160>, Vec<u8>), Infallible> {
+	) -> Capture<(ExitReason, Option<H160>, Bytes), Infallible> {
 		macro_rules! try_or_fail {
 			( $e:expr ) => {
 				match $e {
 					Ok(v) => v,
-					Err(e) => return Capture::Exit((e.into(), None, Vec::new())),
+					Err(e) => return Capture::Exit((e.into(), None, Bytes::new())),
 				}
 			}
 		}
@@ -371,12 +1988,12 @@ impl<B: Backend> StackExecutor<B> {
 
 		if let Some(depth) = self.depth {
 			if depth + 1 > self.config.call_stack_limit {
-				return Capture::Exit((ExitError::CallTooDeep.into(), None, Vec::new()))
+				return Capture::Exit((ExitError::CallTooDeep.into(), None, Bytes::new()))
 			}
 		}
 
 		if self.balance(caller).await < value {
-			return Capture::Exit((ExitError::OutOfFund.into(), None, Vec::new()))
+			return Capture::Exit((ExitError::OutOfFund.into(), None, Bytes::new()))
 		}
 
 		let mut after_gas = self.gasometer.gas();
@@ -389,32 +2006,35 @@ impl<B: Backend> StackExecutor<B> {
 		try_or_fail!(self.gasometer.record_cost(gas_limit));
 
 		let address = self.create_address(scheme).await;
-		self.account_mut(caller).await.basic.nonce += U256::one();
+		self.increment_nonce(caller).await;
 
 		let mut substate = self.substate(gas_limit, false);
 		{
 			if let Some(code) = substate.account_mut(address).await.code.as_ref() {
 				if code.len() != 0 {
 					let _ = self.merge_fail(substate);
-					return Capture::Exit((ExitError::CreateCollision.into(), None, Vec::new()))
+					return Capture::Exit((ExitError::CreateCollision.into(), None, Bytes::new()))
 				}
 			} else  {
-				let code = substate.backend.clone().code(address).await;
+				let code = backend_read!(substate, code(address));
 				substate.account_mut(address).await.code = Some(code.clone());
+				substate.lock_reads().code.insert(address);
 
 				if code.len() != 0 {
 					let _ = self.merge_fail(substate);
-					return Capture::Exit((ExitError::CreateCollision.into(), None, Vec::new()))
+					return Capture::Exit((ExitError::CreateCollision.into(), None, Bytes::new()))
 				}
 			}
 
+			substate.lock_reads().nonces.insert(address);
 			if substate.account_mut(address).await.basic.nonce > U256::zero() {
 				let _ = self.merge_fail(substate);
-				return Capture::Exit((ExitError::CreateCollision.into(), None, Vec::new()))
+				return Capture::Exit((ExitError::CreateCollision.into(), None, Bytes::new()))
 			}
 
 			substate.account_mut(address).await.reset_storage = true;
 			substate.account_mut(address).await.storage = BTreeMap::new();
+			substate.writes.cleared_storage.insert(address);
 		}
 
 		let context = Context {
@@ -422,6 +2042,7 @@ impl<B: Backend> StackExecutor<B> {
 			caller,
 			apparent_value: value,
 		};
+		substate.context = Some(context.clone());
 		let transfer = Transfer {
 			source: caller,
 			target: address,
@@ -431,62 +2052,84 @@ impl<B: Backend> StackExecutor<B> {
 			Ok(()) => (),
 			Err(e) => {
 				let _ = self.merge_revert(substate);
-				return Capture::Exit((ExitReason::Error(e), None, Vec::new()))
+				return Capture::Exit((ExitReason::Error(e), None, Bytes::new()))
 			},
 		}
 
 		if self.config.create_increase_nonce {
-			substate.account_mut(address).await.basic.nonce += U256::one();
+			substate.increment_nonce(address).await;
 		}
 
-		let mut runtime = Runtime::new(
-			Arc::new(init_code),
-			Arc::new(Vec::new()),
-			context,
-			self.config.clone(),
-		);
+		let mut runtime = self.new_runtime(init_code.into_vec(), Bytes::new(), context);
 
+		substate.emit(ExecutorEvent::Enter {
+			is_create: true,
+			caller,
+			address,
+			depth: substate.depth.unwrap_or(0),
+		});
+		if let Some(mut security) = substate.lock_security() {
+			security.enter(address, address, substate.depth.unwrap_or(0));
+		}
 		let reason = substate.execute(&mut runtime).await;
 		log::debug!(target: "evm", "Create execution using address {}: {:?}", address, reason);
+		let out = self.finish_runtime(runtime, reason);
 
 		match reason {
 			ExitReason::Succeed(s) => {
-				let out = runtime.machine().return_value();
-
-				if let Some(limit) = self.config.create_contract_limit {
+				if let Some(limit) = self.config.max_code_size {
 					if out.len() > limit {
 						substate.gasometer.fail();
+						let e = ExitError::CreateContractLimit;
+						self.record_trace(&mut substate, true, address, gas_limit, e.into());
+						let _ = self.merge_fail(substate);
+						return Capture::Exit((e.into(), None, Bytes::new()))
+					}
+				}
+
+				#[cfg(feature = "eof")]
+				{
+					if self.config.has_eof && crate::is_eof(&out) && crate::EofContainer::new(&out).is_err() {
+						substate.gasometer.fail();
+						let e = ExitError::InvalidCode;
+						self.record_trace(&mut substate, true, address, gas_limit, e.into());
 						let _ = self.merge_fail(substate);
-						return Capture::Exit((ExitError::CreateContractLimit.into(), None, Vec::new()))
+						return Capture::Exit((e.into(), None, Bytes::new()))
 					}
 				}
 
 				match substate.gasometer.record_deposit(out.len()) {
 					Ok(()) => {
+						self.record_trace(&mut substate, true, address, gas_limit, reason);
 						let e = self.merge_succeed(substate);
 						self.state.entry(address).or_insert(Default::default())
-							.code = Some(out);
+							.code = Some(out.into_vec());
+						self.writes.code.insert(address);
 						try_or_fail!(e);
-						Capture::Exit((ExitReason::Succeed(s), Some(address), Vec::new()))
+						Capture::Exit((ExitReason::Succeed(s), Some(address), Bytes::new()))
 					},
 					Err(e) => {
+						self.record_trace(&mut substate, true, address, gas_limit, e.into());
 						let _ = self.merge_fail(substate);
-						Capture::Exit((ExitReason::Error(e), None, Vec::new()))
+						Capture::Exit((ExitReason::Error(e), None, Bytes::new()))
 					},
 				}
 			},
 			ExitReason::Error(e) => {
 				substate.gasometer.fail();
+				self.record_trace(&mut substate, true, address, gas_limit, reason);
 				let _ = self.merge_fail(substate);
-				Capture::Exit((ExitReason::Error(e), None, Vec::new()))
+				Capture::Exit((ExitReason::Error(e), None, Bytes::new()))
 			},
 			ExitReason::Revert(e) => {
+				self.record_trace(&mut substate, true, address, gas_limit, reason);
 				let _ = self.merge_revert(substate);
-				Capture::Exit((ExitReason::Revert(e), None, runtime.machine().return_value()))
+				Capture::Exit((ExitReason::Revert(e), None, out))
 			},
 			ExitReason::Fatal(e) => {
+				self.record_trace(&mut substate, true, address, gas_limit, reason);
 				self.gasometer.fail();
-				Capture::Exit((ExitReason::Fatal(e), None, Vec::new()))
+				Capture::Exit((ExitReason::Fatal(e), None, Bytes::new()))
 			},
 		}
 	}
@@ -495,18 +2138,94 @@ impl<B: Backend> StackExecutor<B> {
 		&mut self,
 		code_address: H160,
 		transfer: Option<Transfer>,
-		input: Vec<u8>,
+		input: Bytes,
+		target_gas: Option<usize>,
+		is_static: bool,
+		take_l64: bool,
+		take_stipend: bool,
+		context: Context,
+	) -> Capture<(ExitReason, Bytes), Infallible> {
+		let (code_address, transfer, context) = match self.address_resolver.as_ref() {
+			Some(resolver) => {
+				let depth = self.frame_depth();
+				let caller = resolver.caller(context.caller, depth);
+				let target = resolver.target(code_address, depth);
+				let resolve = |address: H160| {
+					if address == context.caller {
+						caller
+					} else if address == code_address {
+						target
+					} else {
+						address
+					}
+				};
+				let transfer = transfer.map(|transfer| Transfer {
+					source: resolve(transfer.source),
+					target: resolve(transfer.target),
+					value: transfer.value,
+				});
+				let context = Context {
+					caller,
+					address: if context.address == code_address { target } else { context.address },
+					apparent_value: context.apparent_value,
+				};
+				(target, transfer, context)
+			},
+			None => (code_address, transfer, context),
+		};
+
+		#[cfg(feature = "tracing")]
+		let span = if self.depth.is_none() {
+			tracing::info_span!(
+				"transaction", kind = "call", caller = ?context.caller, address = ?code_address,
+				gas_limit = ?target_gas, gas_used = tracing::field::Empty,
+				reason = tracing::field::Empty,
+			)
+		} else {
+			tracing::debug_span!(
+				"call", depth = ?self.depth, caller = ?context.caller, address = ?code_address,
+				gas_limit = ?target_gas, is_static, gas_used = tracing::field::Empty,
+				reason = tracing::field::Empty,
+			)
+		};
+
+		#[cfg(feature = "tracing")]
+		let traces = self.call_traces.len();
+		let result = self.call_frame(
+			code_address, transfer, input, target_gas, is_static, take_l64, take_stipend, context,
+		);
+		#[cfg(feature = "tracing")]
+		let result = tracing::Instrument::instrument(result, span.clone());
+		let result = result.await;
+
+		#[cfg(feature = "tracing")]
+		if let Capture::Exit((reason, _)) = &result {
+			span.record("reason", tracing::field::debug(reason));
+			if self.call_traces.len() > traces {
+				span.record("gas_used", self.call_traces[self.call_traces.len() - 1].gas_used);
+			}
+		}
+
+		result
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	async fn call_frame(
+		&mut self,
+		code_address: H160,
+		transfer: Option<Transfer>,
+		input: Bytes,
 		target_gas: Option<usize>,
 		is_static: bool,
 		take_l64: bool,
 		take_stipend: bool,
 		context: Context,
-	) -> Capture<(ExitReason, Vec<u8>), Infallible> {
+	) -> Capture<(ExitReason, Bytes), Infallible> {
 		macro_rules! try_or_fail {
 			( $e:expr ) => {
 				match $e {
 					Ok(v) => v,
-					Err(e) => return Capture::Exit((e.into(), Vec::new())),
+					Err(e) => return Capture::Exit((e.into(), Bytes::new())),
 				}
 			}
 		}
@@ -531,15 +2250,16 @@ impl<B: Backend> StackExecutor<B> {
 			}
 		}
 
-		let code = self.code(code_address).await;
+		let code = self.executed_code(code_address).await;
 
 		let mut substate = self.substate(gas_limit, is_static);
+		substate.context = Some(context.clone());
 		substate.account_mut(context.address).await;
 
 		if let Some(depth) = self.depth {
 			if depth + 1 > self.config.call_stack_limit {
 				let _ = self.merge_revert(substate);
-				return Capture::Exit((ExitError::CallTooDeep.into(), Vec::new()))
+				return Capture::Exit((ExitError::CallTooDeep.into(), Bytes::new()))
 			}
 		}
 
@@ -548,177 +2268,272 @@ impl<B: Backend> StackExecutor<B> {
 				Ok(()) => (),
 				Err(e) => {
 					let _ = self.merge_revert(substate);
-					return Capture::Exit((ExitReason::Error(e), Vec::new()))
+					return Capture::Exit((ExitReason::Error(e), Bytes::new()))
 				},
 			}
 		}
 
-		if let Some(ret) = (substate.precompile)(code_address, &input, Some(gas_limit)) {
+		substate.emit(ExecutorEvent::Enter {
+			is_create: false,
+			caller: context.caller,
+			address: code_address,
+			depth: substate.depth.unwrap_or(0),
+		});
+		if let Some(mut security) = substate.lock_security() {
+			security.enter(context.address, code_address, substate.depth.unwrap_or(0));
+		}
+
+		let mut precompile = (substate.precompile)(code_address, &input, Some(gas_limit));
+		if precompile.is_none() {
+			if let Some(async_precompile) = substate.async_precompile.clone() {
+				precompile = async_precompile.execute(
+					code_address, &input, Some(gas_limit), &context, &*substate.backend,
+				).await;
+			}
+		}
+
+		if let Some(ret) = precompile {
+			let ret = ret.and_then(|(s, out, cost)| {
+				substate.gasometer.record_cost(cost)?;
+				Ok((s, out))
+			});
+
 			return match ret {
-				Ok((s, out, cost)) => {
-					let _ = substate.gasometer.record_cost(cost);
+				Ok((s, out)) => {
+					self.record_trace(&mut substate, false, code_address, gas_limit, s.into());
 					let _ = self.merge_succeed(substate);
-					Capture::Exit((ExitReason::Succeed(s), out))
+					Capture::Exit((ExitReason::Succeed(s), out.into()))
 				},
 				Err(e) => {
+					self.record_trace(&mut substate, false, code_address, gas_limit, e.into());
 					let _ = self.merge_fail(substate);
-					Capture::Exit((ExitReason::Error(e), Vec::new()))
+					Capture::Exit((ExitReason::Error(e), Bytes::new()))
 				},
 			}
 		}
 
-		let mut runtime = Runtime::new(
-			Arc::new(code),
-			Arc::new(input),
-			context,
-			self.config.clone(),
-		);
+		let mut runtime = self.new_runtime(code, input, context);
 
 		let reason = substate.execute(&mut runtime).await;
 		log::debug!(target: "evm", "Call execution using address {}: {:?}", code_address, reason);
+		let output = self.finish_runtime(runtime, reason);
+		self.record_trace(&mut substate, false, code_address, gas_limit, reason);
 
 		match reason {
 			ExitReason::Succeed(s) => {
 				let _ = self.merge_succeed(substate);
-				Capture::Exit((ExitReason::Succeed(s), runtime.machine().return_value()))
+				Capture::Exit((ExitReason::Succeed(s), output))
 			},
 			ExitReason::Error(e) => {
 				let _ = self.merge_fail(substate);
-				Capture::Exit((ExitReason::Error(e), Vec::new()))
+				Capture::Exit((ExitReason::Error(e), Bytes::new()))
 			},
 			ExitReason::Revert(e) => {
 				let _ = self.merge_revert(substate);
-				Capture::Exit((ExitReason::Revert(e), runtime.machine().return_value()))
+				Capture::Exit((ExitReason::Revert(e), output))
 			},
 			ExitReason::Fatal(e) => {
 				self.gasometer.fail();
-				Capture::Exit((ExitReason::Fatal(e), Vec::new()))
+				Capture::Exit((ExitReason::Fatal(e), Bytes::new()))
 			},
 		}
 	}
 }
 
-#[async_trait::async_trait]
-impl<B: Backend> Handler for StackExecutor<B> {
-	type CreateInterrupt = Infallible;
-	type CreateFeedback = Infallible;
-	type CallInterrupt = Infallible;
-	type CallFeedback = Infallible;
-
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<B: Backend> StateQuery for StackExecutor<B> {
 	async fn balance(&self, address: H160) -> U256 {
-		self.state.get(&address).map(|v| v.basic.balance)
-			.unwrap_or(self.backend.basic(address).await.balance)
+		self.touch(address);
+		self.lock_reads().balances.insert(address);
+		match self.state.get(&address) {
+			Some(account) => account.basic.balance,
+			None => backend_read!(self, basic(address)).balance,
+		}
 	}
 
 	async fn code_size(&self, address: H160) -> U256 {
-		U256::from(
-			self.state.get(&address).and_then(|v| v.code.as_ref().map(|c| c.len()))
-				.unwrap_or(self.backend.code_size(address).await)
-		)
+		self.touch(address);
+		self.lock_reads().code.insert(address);
+		if let Some(code) = self.code_override(address, true) {
+			return U256::from(code.len())
+		}
+		U256::from(match self.state.get(&address).and_then(|v| v.code.as_ref()) {
+			Some(code) => code.len(),
+			None => backend_read!(self, code_size(address)),
+		})
 	}
 
 	async fn code_hash(&self, address: H160) -> H256 {
+		// EIP-1052: zero for accounts that do not exist, which after EIP-161
+		// includes empty accounts, and the hash of the empty code for
+		// existing accounts without code.
+		self.lock_reads().code.insert(address);
+		if let Some(code) = self.code_override(address, true) {
+			self.touch(address);
+			return self.hasher.hash(code)
+		}
 		if !self.exists(address).await {
 			return H256::default()
 		}
 
-		let (balance, nonce, code_size) = if let Some(account) = self.state.get(&address) {
-			(account.basic.balance, account.basic.nonce,
-			 account.code.as_ref().map(|c| U256::from(c.len())).unwrap_or(self.code_size(address).await))
-		} else {
-			let basic = self.backend.basic(address).await;
-			(basic.balance, basic.nonce, U256::from(self.backend.code_size(address).await))
-		};
-
-		if balance == U256::zero() && nonce == U256::zero() && code_size == U256::zero() {
-			return H256::default()
+		match self.state.get(&address).and_then(|account| account.code.as_ref()) {
+			Some(code) => self.hasher.hash(code),
+			None => backend_read!(self, code_hash(address)),
 		}
-
-		let value = self.state.get(&address).and_then(|v| {
-			v.code.as_ref().map(|c| {
-				H256::from_slice(Keccak256::digest(&c).as_slice())
-			})
-		}).unwrap_or(self.backend.code_hash(address).await);
-		value
 	}
 
 	async fn code(&self, address: H160) -> Vec<u8> {
-		self.state.get(&address).and_then(|v| {
-			v.code.clone()
-		}).unwrap_or(self.backend.code(address).await)
+		self.touch(address);
+		self.lock_reads().code.insert(address);
+		if let Some(code) = self.code_override(address, true) {
+			return code.clone()
+		}
+		match self.state.get(&address).and_then(|v| v.code.clone()) {
+			Some(code) => code,
+			None => backend_read!(self, code(address)),
+		}
 	}
 
 	async fn storage(&self, address: H160, index: H256) -> H256 {
-		self.state.get(&address)
-			.and_then(|v| {
-				let s = v.storage.get(&index).cloned();
-
-				if v.reset_storage {
-					Some(s.unwrap_or(H256::default()))
-				} else {
-					s
-				}
-
-			})
-			.unwrap_or(self.backend.storage(address, index).await)
+		self.touch_storage(address, index);
+		self.lock_reads().storage.insert((address, index));
+		self.current_storage(address, index).await
 	}
 
 	async fn original_storage(&self, address: H160, index: H256) -> H256 {
+		self.touch_storage(address, index);
+		self.lock_reads().storage.insert((address, index));
 		if let Some(account) = self.state.get(&address) {
 			if account.reset_storage {
 				return H256::default()
 			}
 		}
-		self.backend.storage(address, index).await
+		backend_read!(self, storage(address, index))
 	}
 
 	async fn exists(&self, address: H160) -> bool {
-		if self.config.empty_considered_exists {
-			self.state.get(&address).is_some() || self.backend.exists(address).await
-		} else {
-			if let Some(account) = self.state.get(&address) {
-				account.basic.nonce != U256::zero() ||
-					account.basic.balance != U256::zero() ||
-					account.code.as_ref().map(|c| c.len() != 0).unwrap_or(false) ||
-					self.backend.code(address).await.len() != 0
-			} else {
-				let account = self.backend.basic(address).await;
-				account.nonce != U256::zero() ||
-					account.balance != U256::zero() ||
-					self.backend.code(address).await.len() != 0
-			}
-		}
+		self.account_state(address).await.exists(&self.config)
 	}
 
 	fn gas_left(&self) -> U256 { U256::from(self.gasometer.gas()) }
 
-	async fn gas_price(&self) -> U256 { self.backend.gas_price().await }
-	async fn origin(&self) -> H160 { self.backend.origin().await }
-	async fn block_hash(&self, number: U256) -> H256 { self.backend.block_hash(number).await }
-	async fn block_number(&self) -> U256 { self.backend.block_number().await }
-	async fn block_coinbase(&self) -> H160 { self.backend.block_coinbase().await }
-	async fn block_timestamp(&self) -> U256 { self.backend.block_timestamp().await }
-	async fn block_difficulty(&self) -> U256 { self.backend.block_difficulty().await }
-	async fn block_gas_limit(&self) -> U256 { self.backend.block_gas_limit().await }
-	async fn chain_id(&self) -> U256 { self.backend.chain_id().await }
+	async fn gas_price(&self) -> U256 { backend_read!(self, gas_price()) }
+	async fn origin(&self) -> H160 {
+		// The override, then the caller of the executing transaction, and
+		// the backend only for executions started outside a transaction.
+		match self.origin_override.or(self.origin) {
+			Some(origin) => origin,
+			None => backend_read!(self, origin()),
+		}
+	}
+	async fn block_hash(&self, number: U256) -> H256 {
+		// Within the history window, hashes stored by the history contract,
+		// falling back to the backend for slots written before its
+		// deployment. The slot is read by the protocol, not by the running
+		// code, so its access is not recorded.
+		if self.config.has_block_hash_history {
+			let current = self.block_number().await;
+			if number >= current || current - number > U256::from(HISTORY_SERVE_WINDOW) {
+				return H256::default()
+			}
+			let index = H256::from_low_u64_be((number % U256::from(HISTORY_SERVE_WINDOW)).low_u64());
+			let hash = self.current_storage(HISTORY_STORAGE_ADDRESS, index).await;
+			if hash != H256::default() {
+				return hash
+			}
+		}
+		backend_read!(self, block_hash(number))
+	}
+	async fn block_number(&self) -> U256 {
+		match self.lock_cheatcodes().and_then(|cheatcodes| cheatcodes.number) {
+			Some(number) => number,
+			None => backend_read!(self, block_number()),
+		}
+	}
+	async fn block_coinbase(&self) -> H160 { backend_read!(self, block_coinbase()) }
+	async fn block_timestamp(&self) -> U256 {
+		match self.lock_cheatcodes().and_then(|cheatcodes| cheatcodes.timestamp) {
+			Some(timestamp) => timestamp,
+			None => backend_read!(self, block_timestamp()),
+		}
+	}
+	async fn block_difficulty(&self) -> U256 { backend_read!(self, block_difficulty()) }
+	async fn block_gas_limit(&self) -> U256 { backend_read!(self, block_gas_limit()) }
+	async fn chain_id(&self) -> U256 { backend_read!(self, chain_id()) }
 
 	fn deleted(&self, address: H160) -> bool { self.deleted.contains(&address) }
 
+	fn hasher(&self) -> &dyn Hasher {
+		&*self.hasher
+	}
+
+	fn keccak256(&self, data: &[u8]) -> H256 {
+		match self.keccak_cache.as_ref() {
+			Some(cache) => cache.lock().unwrap_or_else(|e| e.into_inner()).digest(data, &*self.hasher),
+			None => self.hasher.hash(data),
+		}
+	}
+
+	#[cfg(feature = "auth")]
+	fn authorized(&self) -> Option<H160> { self.authorized }
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<B: Backend> StateMutator for StackExecutor<B> {
+	type CreateInterrupt = Infallible;
+	type CreateFeedback = Infallible;
+	type CallInterrupt = Infallible;
+	type CallFeedback = Infallible;
+
 	async fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError> {
+		if self.is_static {
+			return Err(ExitError::StaticModeViolation)
+		}
+
+		self.touch_storage(address, index);
 		self.account_mut(address).await.storage.insert(index, value);
+		self.writes.storage.insert((address, index));
+
+		self.emit(ExecutorEvent::StorageChanged { address, index, value });
+
+		if let Some(provenance) = self.provenance.as_mut() {
+			provenance.insert((address, index), StorageProvenance {
+				frame: self.frame,
+				depth: self.depth.unwrap_or(0),
+				position: self.position,
+			});
+		}
 
 		Ok(())
 	}
 
 	fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) -> Result<(), ExitError> {
-		self.logs.push(Log {
+		if self.is_static {
+			return Err(ExitError::StaticModeViolation)
+		}
+
+		let log_data = self.log_data.saturating_add(data.len());
+		if self.config.max_log_data_size.map(|limit| log_data > limit).unwrap_or(false) {
+			return Err(ExitError::LogDataLimit)
+		}
+		self.log_data = log_data;
+
+		let log = Log {
 			address, topics, data
-		});
+		};
+		self.emit(ExecutorEvent::Log(log.clone()));
+		self.logs.push(log);
 
 		Ok(())
 	}
 
 	async fn mark_delete(&mut self, address: H160, target: H160) -> Result<(), ExitError> {
+		if self.is_static {
+			return Err(ExitError::StaticModeViolation)
+		}
+
 		let balance = self.balance(address).await;
 
 		self.transfer(Transfer {
@@ -727,8 +2542,14 @@ impl<B: Backend> Handler for StackExecutor<B> {
 			value: balance
 		}).await?;
 		self.account_mut(address).await.basic.balance = U256::zero();
+		self.writes.balances.insert(address);
+		if target == address {
+			self.burned = self.burned.saturating_add(balance);
+		}
 
 		self.deleted.insert(address);
+		self.writes.accounts.insert(address);
+		self.writes.cleared_storage.insert(address);
 
 		Ok(())
 	}
@@ -738,28 +2559,115 @@ impl<B: Backend> Handler for StackExecutor<B> {
 		caller: H160,
 		scheme: CreateScheme,
 		value: U256,
-		init_code: Vec<u8>,
+		init_code: Bytes,
 		target_gas: Option<usize>,
-	) -> Capture<(ExitReason, Option<H160>, Vec<u8>), Self::CreateInterrupt> {
-		self.create_inner(caller, scheme, value, init_code, target_gas, true).await
+	) -> Capture<(ExitReason, Option<H160>, Bytes), Self::CreateInterrupt> {
+		if self.is_static {
+			return Capture::Exit((ExitError::StaticModeViolation.into(), None, Bytes::new()))
+		}
+
+		let (prank, expected_revert) = self.take_cheatcodes();
+		let (caller, scheme) = match prank {
+			Some(caller) => (caller, match scheme {
+				CreateScheme::Legacy { .. } => CreateScheme::Legacy { caller },
+				CreateScheme::Create2 { code_hash, salt, .. } =>
+					CreateScheme::Create2 { caller, code_hash, salt },
+				CreateScheme::Fixed(address) => CreateScheme::Fixed(address),
+			}),
+			None => (caller, scheme),
+		};
+
+		let capture = self.create_inner(caller, scheme, value, init_code, target_gas, true).await;
+		match (expected_revert, capture) {
+			(Some(expected), Capture::Exit((reason, address, output))) => {
+				let (reason, output) = Self::check_expected_revert(expected, reason, output);
+				let address = if reason.is_succeed() { address } else { None };
+				Capture::Exit((reason, address, output))
+			},
+			(_, capture) => capture,
+		}
 	}
 
 	async fn call(
 		&mut self,
 		code_address: H160,
 		transfer: Option<Transfer>,
-		input: Vec<u8>,
+		input: Bytes,
 		target_gas: Option<usize>,
 		is_static: bool,
 		context: Context,
-	) -> Capture<(ExitReason, Vec<u8>), Self::CallInterrupt> {
-		self.call_inner(code_address, transfer, input, target_gas, is_static, true, true, context).await
+	) -> Capture<(ExitReason, Bytes), Self::CallInterrupt> {
+		if code_address == CHEATCODE_ADDRESS && self.cheatcodes.is_some() {
+			return Capture::Exit(self.cheatcode(&input, is_static || self.is_static).await)
+		}
+
+		let (prank, expected_revert) = self.take_cheatcodes();
+		let (transfer, context) = match prank {
+			Some(caller) => (
+				transfer.map(|transfer| Transfer { source: caller, ..transfer }),
+				Context { caller, ..context },
+			),
+			None => (transfer, context),
+		};
+
+		let capture = self.call_inner(
+			code_address, transfer, input, target_gas, is_static, true, true, context,
+		).await;
+		match (expected_revert, capture) {
+			(Some(expected), Capture::Exit((reason, output))) =>
+				Capture::Exit(Self::check_expected_revert(expected, reason, output)),
+			(_, capture) => capture,
+		}
+	}
+
+	#[cfg(feature = "auth")]
+	async fn auth(
+		&mut self,
+		invoker: H160,
+		authority: H160,
+		y_parity: u8,
+		r: H256,
+		s: H256,
+		commit: H256,
+	) -> Result<bool, ExitError> {
+		self.authorized = None;
+		let signature = crate::signing::Signature {
+			y_parity: y_parity == 1,
+			r: U256::from_big_endian(&r[..]),
+			s: U256::from_big_endian(&s[..]),
+		};
+		if y_parity > 1 || signature.is_high_s() {
+			return Ok(false)
+		}
+
+		let message = super::auth_message(
+			self.chain_id().await, self.nonce(authority).await, invoker, commit,
+		);
+		if crate::signing::recover(&message, &signature).ok() == Some(authority) {
+			self.authorized = Some(authority);
+		}
+
+		Ok(self.authorized.is_some())
+	}
+
+	#[cfg(not(feature = "auth"))]
+	async fn auth(
+		&mut self,
+		_invoker: H160,
+		_authority: H160,
+		_y_parity: u8,
+		_r: H256,
+		_s: H256,
+		_commit: H256,
+	) -> Result<bool, ExitError> {
+		Ok(false)
 	}
 
 	async fn pre_validate(
 		&mut self,
 		context: &Context,
 		opcode: Result<Opcode, ExternalOpcode>,
+		static_gas: Option<usize>,
 		stack: &Stack
 	) -> Result<(), ExitError> {
 		log::info!("{:?} {:?} | {:?} {:?} {:?} {:?}", context.address, opcode,
@@ -769,12 +2677,57 @@ impl<B: Backend> Handler for StackExecutor<B> {
 			stack.peek(3),
 		);
 
+		if self.is_static {
+			static_check(opcode, stack)?;
+		}
+
 		let (gas_cost, memory_cost) = gasometer::opcode_cost(
-			context.address, opcode, stack, self.is_static, &self.config, self
+			context.address, opcode, static_gas, stack, self.is_static, &self.config, self
 		).await?;
 
+		// Halt before the opcode runs on defaults of a failed read. The exit
+		// reason is replaced by `ExitFatal::BackendError` in `execute`.
+		if self.has_backend_error() {
+			return Err(ExitError::Other("backend error"))
+		}
+
+		let gas = self.gasometer.gas();
+		if let Err(ExternalOpcode::Call) | Err(ExternalOpcode::CallCode) |
+			Err(ExternalOpcode::DelegateCall) | Err(ExternalOpcode::StaticCall) = opcode
+		{
+			self.record_delegate_access(stack.peek(1)?.into()).await?;
+		}
 		self.gasometer.record_opcode(gas_cost, memory_cost)?;
 
+		if let Some(attribution) = self.gas_attribution.as_mut() {
+			match opcode {
+				Err(ExternalOpcode::SLoad) =>
+					attribution.addresses.entry(context.address).or_default().storage_reads += 1,
+				Err(ExternalOpcode::SStore) =>
+					attribution.addresses.entry(context.address).or_default().storage_writes += 1,
+				_ => (),
+			}
+		}
+
+		if let Some(hook) = self.gas_hook.as_ref() {
+			let remaining = self.gasometer.gas();
+			match hook.after_charge(opcode, gas - remaining, remaining) {
+				GasAction::Continue => (),
+				GasAction::Grant(gas) => { self.gasometer.record_grant(gas)?; },
+				GasAction::Terminate(e) => return Err(e),
+			}
+		}
+
+		if let Some(mut security) = self.lock_security() {
+			let depth = self.depth.unwrap_or(0);
+			match opcode {
+				Err(ExternalOpcode::SLoad) => security.sload(depth, stack.peek(0)?),
+				Err(ExternalOpcode::SStore) => security.sstore(depth, stack.peek(0)?, self.position),
+				Err(ExternalOpcode::Call) => security.call(depth, stack.peek(1)?.into()),
+				_ => (),
+			}
+		}
+
 		Ok(())
 	}
 }
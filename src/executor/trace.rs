@@ -0,0 +1,109 @@
+use alloc::vec::Vec;
+use core::cmp::min;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::{Context, ExitReason, Memory, Stack};
+
+/// Maximum number of memory bytes kept in a `FaultSnapshot`.
+pub const FAULT_MEMORY_WINDOW: usize = 1024;
+
+/// Gas accounting of a single call or create frame, as recorded by the
+/// executor.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CallTrace {
+	/// Whether the frame is a create frame.
+	pub is_create: bool,
+	/// Code address of a call frame, or the created address of a create frame.
+	pub address: H160,
+	/// Context the frame ran in. Its address is the storage context, which
+	/// differs from `address` for `DELEGATECALL` and `CALLCODE`.
+	pub context: Context,
+	/// Gas limit given to the frame, including any call stipend.
+	pub gas_limit: usize,
+	/// Gas consumed by the frame.
+	pub gas_used: usize,
+	/// Unused gas given back to the parent frame. It is zero if the frame
+	/// exited with an error, in which case all gas is burned.
+	pub gas_returned: usize,
+	/// Refund passed to the parent frame. Only succeeded frames pass
+	/// their refunds.
+	pub gas_refunded: isize,
+	/// Exit reason of the frame.
+	pub reason: ExitReason,
+	/// Traces of frames entered from this frame, in execution order.
+	pub calls: Vec<CallTrace>,
+}
+
+/// Call frame and instruction that last wrote a storage slot.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StorageProvenance {
+	/// Index of the writing frame in call entry order, matching a pre-order
+	/// walk of the call traces.
+	pub frame: usize,
+	/// Call depth of the writing frame.
+	pub depth: usize,
+	/// Program counter of the `SSTORE`.
+	pub position: usize,
+}
+
+/// State of a frame at the instruction that reverted or failed it, recorded
+/// with `Config::fault_snapshot_stack`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
+pub struct FaultSnapshot {
+	/// Index of the frame in call entry order, matching a pre-order walk of
+	/// the call traces.
+	pub frame: usize,
+	/// Call depth of the frame.
+	pub depth: usize,
+	/// Program counter of the instruction.
+	pub position: usize,
+	/// Opcode byte of the instruction.
+	pub opcode: u8,
+	/// Exit reason of the frame.
+	pub reason: ExitReason,
+	/// Top stack items before the instruction, top first.
+	pub stack: Vec<H256>,
+	/// Offset of `memory` in the frame memory.
+	pub memory_offset: usize,
+	/// Memory the instruction reads or writes, clipped to the frame memory
+	/// and to `FAULT_MEMORY_WINDOW` bytes. Empty for other instructions.
+	pub memory: Vec<u8>,
+}
+
+/// Memory range, as offset and length, read or written by the opcode
+/// with the given stack.
+pub(crate) fn memory_operand(opcode: u8, stack: &Stack) -> Option<(U256, U256)> {
+	let word = |index: usize| stack.peek(index).ok().map(|value| U256::from_big_endian(&value[..]));
+	match opcode {
+		// MLOAD, MSTORE
+		0x51 | 0x52 => Some((word(0)?, U256::from(32))),
+		// MSTORE8
+		0x53 => Some((word(0)?, U256::one())),
+		// SHA3, LOGn, RETURN, REVERT
+		0x20 | 0xa0..=0xa4 | 0xf3 | 0xfd => Some((word(0)?, word(1)?)),
+		// CALLDATACOPY, CODECOPY, RETURNDATACOPY
+		0x37 | 0x39 | 0x3e => Some((word(0)?, word(2)?)),
+		// EXTCODECOPY
+		0x3c => Some((word(1)?, word(3)?)),
+		// CREATE, CREATE2
+		0xf0 | 0xf5 => Some((word(1)?, word(2)?)),
+		// CALL and CALLCODE input
+		0xf1 | 0xf2 => Some((word(3)?, word(4)?)),
+		// DELEGATECALL and STATICCALL input
+		0xf4 | 0xfa => Some((word(2)?, word(3)?)),
+		_ => None,
+	}
+}
+
+/// Bytes of the memory range, clipped to the memory and to
+/// `FAULT_MEMORY_WINDOW` bytes, with their offset.
+pub(crate) fn memory_window(memory: &Memory, (offset, len): (U256, U256)) -> (usize, Vec<u8>) {
+	if offset >= U256::from(memory.len()) {
+		return (0, Vec::new())
+	}
+	let offset = offset.as_usize();
+	let len = min(min(len, U256::from(FAULT_MEMORY_WINDOW)).as_usize(), memory.len() - offset);
+	(offset, memory.get(offset, len))
+}
@@ -0,0 +1,171 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::{ExitError, ExitReason, MaybeSend, MaybeSync, StateQuery};
+use crate::backend::Backend;
+use super::StackExecutor;
+
+/// Action of a transaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransactionAction {
+	/// Message call to the address.
+	Call(H160),
+	/// Contract creation with the `CREATE` scheme.
+	Create,
+	/// Contract creation with the `CREATE2` scheme and the given salt.
+	Create2(H256),
+}
+
+/// Transaction about to be executed. The caller is considered already
+/// authenticated, for example by signature recovery.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Transaction {
+	/// Caller of the transaction.
+	pub caller: H160,
+	/// Action of the transaction.
+	pub action: TransactionAction,
+	/// Value transferred.
+	pub value: U256,
+	/// Call data, or init code for creations.
+	pub data: Vec<u8>,
+	/// Gas limit.
+	pub gas_limit: usize,
+	/// Nonce, checked against the current nonce of the caller unless
+	/// `Config::disable_nonce_check` is set. `None` skips the check.
+	pub nonce: Option<U256>,
+}
+
+/// Reason a transaction was rejected before execution. Rejected
+/// transactions change no state, and exit with the `ExitError` this
+/// converts to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
+pub enum TxValidationError {
+	/// The gas limit does not cover the intrinsic gas, or the calldata
+	/// floor (EIP-7623).
+	GasLimitTooLow {
+		/// Minimum gas limit of the transaction.
+		needed: usize,
+	},
+	/// The caller cannot afford the gas limit at the gas price plus the
+	/// transferred value.
+	InsufficientFunds {
+		/// Balance of the caller.
+		have: U256,
+		/// Cost of the transaction.
+		need: U256,
+	},
+	/// The transaction nonce is above the current nonce of its caller.
+	NonceTooHigh {
+		/// Current nonce of the caller.
+		expected: U256,
+		/// Nonce of the transaction.
+		got: U256,
+	},
+	/// The transaction nonce is below the current nonce of its caller.
+	NonceTooLow {
+		/// Current nonce of the caller.
+		expected: U256,
+		/// Nonce of the transaction.
+		got: U256,
+	},
+	/// The gas price is below the base fee.
+	FeeCapTooLow {
+		/// Gas price of the transaction.
+		gas_price: U256,
+		/// Base fee of the fee policy.
+		base_fee: U256,
+	},
+	/// The init code of a creation exceeds `Config::max_initcode_size`.
+	InitCodeTooLarge {
+		/// Size of the init code.
+		size: usize,
+		/// Maximum size.
+		limit: usize,
+	},
+	/// Rejected by a custom `TxValidator` for another reason.
+	Rejected(ExitError),
+}
+
+impl From<TxValidationError> for ExitError {
+	fn from(error: TxValidationError) -> ExitError {
+		match error {
+			TxValidationError::GasLimitTooLow { .. } => ExitError::OutOfGas,
+			TxValidationError::InsufficientFunds { .. } => ExitError::OutOfFund,
+			TxValidationError::NonceTooHigh { expected, got } |
+			TxValidationError::NonceTooLow { expected, got } => ExitError::InvalidNonce { expected, got },
+			TxValidationError::FeeCapTooLow { .. } => ExitError::GasPriceBelowBaseFee,
+			TxValidationError::InitCodeTooLarge { .. } => ExitError::InitCodeLimit,
+			TxValidationError::Rejected(error) => error,
+		}
+	}
+}
+
+impl From<TxValidationError> for ExitReason {
+	fn from(error: TxValidationError) -> ExitReason {
+		ExitError::from(error).into()
+	}
+}
+
+impl fmt::Display for TxValidationError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			TxValidationError::GasLimitTooLow { needed } =>
+				write!(f, "intrinsic gas too low: need {}", needed),
+			TxValidationError::InsufficientFunds { have, need } =>
+				write!(f, "insufficient funds for gas * price + value: have {} want {}", have, need),
+			TxValidationError::NonceTooHigh { expected, got } =>
+				write!(f, "nonce too high: next nonce {}, tx nonce {}", expected, got),
+			TxValidationError::NonceTooLow { expected, got } =>
+				write!(f, "nonce too low: next nonce {}, tx nonce {}", expected, got),
+			TxValidationError::FeeCapTooLow { gas_price, base_fee } =>
+				write!(f, "max fee per gas less than block base fee: gas price {}, base fee {}", gas_price, base_fee),
+			TxValidationError::InitCodeTooLarge { size, limit } =>
+				write!(f, "max initcode size exceeded: code size {} limit {}", size, limit),
+			TxValidationError::Rejected(error) => write!(f, "transaction rejected: {:?}", error),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TxValidationError {}
+
+/// Validation hook invoked by `transact_*` before a transaction executes.
+///
+/// A custom validator replaces the default checks entirely. It can modify
+/// the executor state, for example to charge fees from a sponsor account.
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+pub trait TxValidator<B: Backend>: MaybeSend + MaybeSync {
+	/// Validate the transaction. Returning an error aborts the transaction
+	/// with that error before any gas is charged.
+	async fn validate(
+		&self,
+		executor: &mut StackExecutor<B>,
+		transaction: &Transaction,
+	) -> Result<(), TxValidationError>;
+}
+
+/// Default transaction validator. Checks that the caller can afford the
+/// transferred value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultTxValidator;
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<B: Backend> TxValidator<B> for DefaultTxValidator {
+	async fn validate(
+		&self,
+		executor: &mut StackExecutor<B>,
+		transaction: &Transaction,
+	) -> Result<(), TxValidationError> {
+		let have = executor.balance(transaction.caller).await;
+		if have < transaction.value {
+			return Err(TxValidationError::InsufficientFunds { have, need: transaction.value })
+		}
+
+		Ok(())
+	}
+}
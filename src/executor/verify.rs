@@ -0,0 +1,174 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use std::sync::Arc;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::{Config, ExitReason};
+use crate::backend::{
+	AccountProof, Apply, PartialTrie, ProofError, Witness, WitnessAccount, WitnessBackend, WitnessError,
+	account_rlp, proof_nodes, verify_proof,
+};
+use crate::hasher::{Hasher, Keccak256Hasher};
+use super::{StackExecutor, Transaction};
+
+/// Failure of a stateless verification.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VerifyError {
+	/// Proof of the account, or of its storage, does not verify against the
+	/// pre-state root.
+	InvalidProof(H160, ProofError),
+	/// Witnessed value not shown by the proofs, or differing from them.
+	UnprovenWitness(WitnessError),
+	/// Execution read a value not present in the witness.
+	IncompleteWitness(WitnessError),
+	/// Change to the account cannot be applied to the tries known from the
+	/// proofs.
+	IncompleteProof(H160, ProofError),
+	/// Post-state root differs from the expected one.
+	StateRootMismatch {
+		/// Expected state root.
+		expected: H256,
+		/// State root after execution.
+		actual: H256,
+	},
+}
+
+/// Proof of an account, with whether it shows the account in the trie.
+struct Proven<'a> {
+	proof: &'a AccountProof,
+	exists: bool,
+}
+
+/// Re-execute a transaction using only the state in the witness, and check
+/// the resulting state root.
+///
+/// Each witnessed account must come with an `AccountProof` against
+/// `pre_root`, holding a storage proof of each witnessed slot, such as
+/// `MemoryBackend::prove_account` or `eth_getProof` give. Code is proven
+/// by its hash, so code whose size is witnessed must be witnessed too. The
+/// post-state root is computed by applying the changes to the tries known
+/// from the proofs. Clearing a slot or an account that leaves a trie
+/// branch with a single child needs a proof of that child as well, failing
+/// with `VerifyError::IncompleteProof` otherwise.
+pub async fn verify_execution(
+	witness: Witness,
+	proofs: &[AccountProof],
+	transaction: Transaction,
+	pre_root: H256,
+	expected_post_root: H256,
+	config: &Config,
+) -> Result<(ExitReason, Vec<u8>), VerifyError> {
+	let hasher = Keccak256Hasher;
+	let mut proven = BTreeMap::new();
+	for proof in proofs {
+		proof.verify(pre_root).map_err(|error| VerifyError::InvalidProof(proof.address, error))?;
+		let exists = verify_proof(pre_root, hasher.hash(proof.address.as_bytes()).as_bytes(), &proof.account_proof)
+			.map_err(|error| VerifyError::InvalidProof(proof.address, error))?
+			.is_some();
+		proven.insert(proof.address, Proven { proof, exists });
+	}
+	for (address, witnessed) in &witness.accounts {
+		check_account(*address, witnessed, proven.get(address), &hasher)
+			.map_err(VerifyError::UnprovenWitness)?;
+	}
+
+	let backend = Arc::new(WitnessBackend::new(witness));
+	let mut executor = StackExecutor::new(
+		backend,
+		transaction.gas_limit,
+		Arc::new(config.clone()),
+	);
+
+	let result = executor.transact(transaction).await;
+	if let Some(error) = executor.take_backend_error() {
+		return Err(VerifyError::IncompleteWitness(error))
+	}
+	let (applies, _) = executor.deconstruct();
+
+	let nodes = proof_nodes(proofs, &hasher);
+	let empty_root = hasher.hash(&rlp::NULL_RLP);
+	let empty_code_hash = hasher.hash(&[]);
+	let mut state = PartialTrie::new(pre_root, &nodes, &hasher);
+	for apply in applies {
+		match apply {
+			Apply::Modify { address, basic, code, storage, reset_storage } => {
+				let incomplete = |error| VerifyError::IncompleteProof(address, error);
+				// Modified accounts were read, so were proven.
+				let proof = proven.get(&address).map(|proven| proven.proof)
+					.ok_or(VerifyError::IncompleteProof(address, ProofError::MissingNode))?;
+
+				let storage_root = if reset_storage { empty_root } else { proof.storage_hash };
+				let mut trie = PartialTrie::new(storage_root, &nodes, &hasher);
+				for (index, value) in storage {
+					if value == H256::default() {
+						trie.remove(index.as_bytes()).map_err(incomplete)?;
+					} else {
+						let value = rlp::encode(&U256::from_big_endian(value.as_bytes())).to_vec();
+						trie.insert(index.as_bytes(), value).map_err(incomplete)?;
+					}
+				}
+
+				let code_hash = code.map(|code| hasher.hash(&code)).unwrap_or(proof.code_hash);
+				let is_empty = basic.balance.is_zero() && basic.nonce.is_zero() && code_hash == empty_code_hash;
+				if is_empty && !config.empty_considered_exists {
+					state.remove(address.as_bytes()).map_err(incomplete)?;
+				} else {
+					let account = account_rlp(basic.nonce, basic.balance, trie.root(), code_hash);
+					state.insert(address.as_bytes(), account).map_err(incomplete)?;
+				}
+			},
+			Apply::Delete { address } => {
+				state.remove(address.as_bytes())
+					.map_err(|error| VerifyError::IncompleteProof(address, error))?;
+			},
+		}
+	}
+
+	let actual = state.root();
+	if actual != expected_post_root {
+		return Err(VerifyError::StateRootMismatch { expected: expected_post_root, actual })
+	}
+
+	Ok((result.reason, result.output))
+}
+
+/// Check each witnessed value of an account against its proof.
+fn check_account(
+	address: H160,
+	witnessed: &WitnessAccount,
+	proven: Option<&Proven>,
+	hasher: &dyn Hasher,
+) -> Result<(), WitnessError> {
+	let check = |method, valid: &dyn Fn(&Proven) -> bool| match proven {
+		Some(proven) if valid(proven) => Ok(()),
+		_ => Err(WitnessError::Account(address, method)),
+	};
+
+	if let Some(exists) = witnessed.exists {
+		check("exists", &|proven| proven.exists == exists)?;
+	}
+	if let Some(basic) = &witnessed.basic {
+		check("basic", &|proven| proven.proof.balance == basic.balance && proven.proof.nonce == basic.nonce)?;
+	}
+	if let Some(code_hash) = witnessed.code_hash {
+		check("code_hash", &|proven| {
+			code_hash == if proven.exists { proven.proof.code_hash } else { H256::zero() }
+		})?;
+	}
+	if let Some(code) = &witnessed.code {
+		check("code", &|proven| hasher.hash(code) == proven.proof.code_hash)?;
+	}
+	if let Some(code_size) = witnessed.code_size {
+		check("code_size", &|_| witnessed.code.as_ref().map(|code| code.len()) == Some(code_size))?;
+	}
+
+	for (index, value) in &witnessed.storage {
+		let value = U256::from_big_endian(value.as_bytes());
+		let slot = proven.and_then(|proven| proven.proof.storage_proof.iter().find(|slot| slot.key == *index));
+		if slot.map(|slot| slot.value) != Some(value) {
+			return Err(WitnessError::Storage(address, *index))
+		}
+	}
+	Ok(())
+}
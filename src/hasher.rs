@@ -0,0 +1,4 @@
+//! Hash function behind the Keccak256 digests of the EVM, shared with the
+//! runtime so that `SHA3`, code hashes and state roots agree.
+
+pub use evm_runtime::{Hasher, Keccak256Hasher};
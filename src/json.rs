@@ -0,0 +1,200 @@
+//! Minimal JSON reader, for the hand-written JSON formats of the crate.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Maximum nesting depth of arrays and objects. Deeper documents are
+/// rejected rather than overflowing the stack of the recursive parser.
+pub(crate) const MAX_DEPTH: usize = 128;
+
+/// Parsed JSON value. Numbers are kept as their text.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Json {
+	Null,
+	Bool(bool),
+	Number(String),
+	String(String),
+	Array(Vec<Json>),
+	Object(Vec<(String, Json)>),
+}
+
+impl Json {
+	/// Parse a JSON document, or return the byte offset of the first error.
+	/// Documents nesting more than `MAX_DEPTH` arrays and objects are
+	/// rejected.
+	pub(crate) fn parse(text: &str) -> Result<Json, usize> {
+		let mut parser = Parser { bytes: text.as_bytes(), pos: 0, depth: 0 };
+		let value = parser.value()?;
+		parser.whitespace();
+		if parser.pos != parser.bytes.len() {
+			return Err(parser.pos)
+		}
+		Ok(value)
+	}
+
+	/// Value of the field `key`, if an object with that field.
+	pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+		match self {
+			Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+			_ => None,
+		}
+	}
+
+	pub(crate) fn as_str(&self) -> Option<&str> {
+		match self {
+			Json::String(value) => Some(value),
+			_ => None,
+		}
+	}
+
+	#[cfg(feature = "rpc")]
+	pub(crate) fn as_array(&self) -> Option<&[Json]> {
+		match self {
+			Json::Array(items) => Some(items),
+			_ => None,
+		}
+	}
+
+	pub(crate) fn as_object(&self) -> Option<&[(String, Json)]> {
+		match self {
+			Json::Object(fields) => Some(fields),
+			_ => None,
+		}
+	}
+}
+
+struct Parser<'a> {
+	bytes: &'a [u8],
+	pos: usize,
+	depth: usize,
+}
+
+impl<'a> Parser<'a> {
+	fn whitespace(&mut self) {
+		while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.bytes.get(self.pos) {
+			self.pos += 1;
+		}
+	}
+
+	fn expect(&mut self, literal: &str) -> Result<(), usize> {
+		if self.bytes[self.pos..].starts_with(literal.as_bytes()) {
+			self.pos += literal.len();
+			Ok(())
+		} else {
+			Err(self.pos)
+		}
+	}
+
+	fn value(&mut self) -> Result<Json, usize> {
+		self.whitespace();
+		match self.bytes.get(self.pos) {
+			Some(b'n') => self.expect("null").map(|_| Json::Null),
+			Some(b't') => self.expect("true").map(|_| Json::Bool(true)),
+			Some(b'f') => self.expect("false").map(|_| Json::Bool(false)),
+			Some(b'"') => self.string().map(Json::String),
+			Some(b'[') => self.nested(Self::array),
+			Some(b'{') => self.nested(Self::object),
+			Some(b'-' | b'0'..=b'9') => Ok(self.number()),
+			_ => Err(self.pos),
+		}
+	}
+
+	fn nested(&mut self, parse: fn(&mut Self) -> Result<Json, usize>) -> Result<Json, usize> {
+		if self.depth == MAX_DEPTH {
+			return Err(self.pos)
+		}
+		self.depth += 1;
+		let value = parse(self);
+		self.depth -= 1;
+		value
+	}
+
+	fn number(&mut self) -> Json {
+		let start = self.pos;
+		while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.bytes.get(self.pos) {
+			self.pos += 1;
+		}
+		Json::Number(String::from_utf8_lossy(&self.bytes[start..self.pos]).into_owned())
+	}
+
+	fn string(&mut self) -> Result<String, usize> {
+		self.expect("\"")?;
+		let mut value = Vec::new();
+		loop {
+			match self.bytes.get(self.pos) {
+				None => return Err(self.pos),
+				Some(b'"') => {
+					self.pos += 1;
+					return String::from_utf8(value).map_err(|_| self.pos)
+				},
+				Some(b'\\') => {
+					let escaped = match self.bytes.get(self.pos + 1) {
+						Some(b'"') => '"',
+						Some(b'\\') => '\\',
+						Some(b'/') => '/',
+						Some(b'b') => '\u{8}',
+						Some(b'f') => '\u{c}',
+						Some(b'n') => '\n',
+						Some(b'r') => '\r',
+						Some(b't') => '\t',
+						Some(b'u') => {
+							let hex = self.bytes.get(self.pos + 2..self.pos + 6).ok_or(self.pos)?;
+							let code = core::str::from_utf8(hex).ok()
+								.and_then(|hex| u32::from_str_radix(hex, 16).ok())
+								.and_then(char::from_u32)
+								.ok_or(self.pos)?;
+							self.pos += 4;
+							code
+						},
+						_ => return Err(self.pos),
+					};
+					self.pos += 2;
+					let mut buffer = [0u8; 4];
+					value.extend_from_slice(escaped.encode_utf8(&mut buffer).as_bytes());
+				},
+				Some(&byte) => {
+					value.push(byte);
+					self.pos += 1;
+				},
+			}
+		}
+	}
+
+	fn array(&mut self) -> Result<Json, usize> {
+		self.expect("[")?;
+		let mut items = Vec::new();
+		self.whitespace();
+		if self.expect("]").is_ok() {
+			return Ok(Json::Array(items))
+		}
+		loop {
+			items.push(self.value()?);
+			self.whitespace();
+			if self.expect(",").is_err() {
+				self.expect("]")?;
+				return Ok(Json::Array(items))
+			}
+		}
+	}
+
+	fn object(&mut self) -> Result<Json, usize> {
+		self.expect("{")?;
+		let mut fields = Vec::new();
+		self.whitespace();
+		if self.expect("}").is_ok() {
+			return Ok(Json::Object(fields))
+		}
+		loop {
+			self.whitespace();
+			let key = self.string()?;
+			self.whitespace();
+			self.expect(":")?;
+			fields.push((key, self.value()?));
+			self.whitespace();
+			if self.expect(",").is_err() {
+				self.expect("}")?;
+				return Ok(Json::Object(fields))
+			}
+		}
+	}
+}
@@ -0,0 +1,340 @@
+//! Solidity storage layout helpers.
+//!
+//! Types mirror the `storageLayout` output of solc and can be deserialized
+//! from it with the `with-serde` feature.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+use crate::backend::Backend;
+
+/// Storage variable, or struct member.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageEntry {
+	/// Variable name.
+	pub label: String,
+	/// Byte offset within the slot.
+	pub offset: usize,
+	/// Slot, as a decimal string.
+	pub slot: String,
+	/// Type identifier, a key of `StorageLayout::types`.
+	#[cfg_attr(feature = "with-serde", serde(rename = "type"))]
+	pub ty: String,
+}
+
+/// Storage type description.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageType {
+	/// Encoding: `inplace`, `mapping`, `dynamic_array` or `bytes`.
+	pub encoding: String,
+	/// Solidity type name.
+	pub label: String,
+	/// Size in bytes, as a decimal string.
+	#[cfg_attr(feature = "with-serde", serde(rename = "numberOfBytes"))]
+	pub number_of_bytes: String,
+	/// Mapping key type.
+	#[cfg_attr(feature = "with-serde", serde(default))]
+	pub key: Option<String>,
+	/// Mapping value type.
+	#[cfg_attr(feature = "with-serde", serde(default))]
+	pub value: Option<String>,
+	/// Array element type.
+	#[cfg_attr(feature = "with-serde", serde(default))]
+	pub base: Option<String>,
+	/// Struct members.
+	#[cfg_attr(feature = "with-serde", serde(default))]
+	pub members: Option<Vec<StorageEntry>>,
+}
+
+/// Storage layout of a contract.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageLayout {
+	/// State variables.
+	pub storage: Vec<StorageEntry>,
+	/// Types, by identifier.
+	#[cfg_attr(feature = "with-serde", serde(default))]
+	pub types: BTreeMap<String, StorageType>,
+}
+
+/// Step of a path into storage.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PathSegment<'a> {
+	/// State variable or struct member, by name.
+	Field(&'a str),
+	/// Mapping key. Value types are given as their 32-byte ABI encoding,
+	/// `bytes` and `string` keys as their raw contents.
+	Key(&'a [u8]),
+	/// Array index.
+	Index(U256),
+}
+
+/// Position of a value in storage.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Location {
+	/// First slot of the value.
+	pub slot: H256,
+	/// Byte offset within the slot, from the right.
+	pub offset: usize,
+	/// Type identifier of the value.
+	pub ty: String,
+}
+
+/// Decoded storage value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StorageValue {
+	/// Boolean.
+	Bool(bool),
+	/// Address or contract.
+	Address(H160),
+	/// Unsigned integer or enum.
+	Uint(U256),
+	/// Signed integer, sign-extended to 256-bit two's complement.
+	Int(U256),
+	/// Fixed-size or dynamic bytes.
+	Bytes(Vec<u8>),
+	/// String.
+	String(String),
+}
+
+/// Storage layout error.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LayoutError {
+	/// No variable or member with the given name.
+	UnknownLabel(String),
+	/// Type identifier missing from the layout.
+	UnknownType(String),
+	/// Path segment does not apply to the type reached so far.
+	InvalidPath,
+	/// Malformed number in the layout.
+	InvalidNumber(String),
+	/// Type, by identifier, whose size is zero or too large to be stored.
+	InvalidSize(String),
+	/// Type cannot be decoded as a single value.
+	NotAValue(String),
+	/// The backend failed to read storage.
+	Backend(String),
+}
+
+/// Bytes reserved upfront when reading a `bytes` or `string` value, whose
+/// length is read from storage.
+const MAX_PREALLOCATED_BYTES: usize = 4096;
+
+fn keccak(data: &[u8]) -> H256 {
+	H256::from_slice(Keccak256::digest(data).as_slice())
+}
+
+fn parse(value: &str) -> Result<U256, LayoutError> {
+	U256::from_dec_str(value).map_err(|_| LayoutError::InvalidNumber(value.into()))
+}
+
+/// Size in bytes of the type `id`, checked to be non-zero.
+fn size(ty: &StorageType, id: &str) -> Result<U256, LayoutError> {
+	let size = parse(&ty.number_of_bytes)?;
+	if size.is_zero() {
+		return Err(LayoutError::InvalidSize(id.into()))
+	}
+	Ok(size)
+}
+
+fn add(slot: H256, value: U256) -> H256 {
+	let (sum, _) = U256::from_big_endian(slot.as_bytes()).overflowing_add(value);
+	let mut out = H256::default();
+	sum.to_big_endian(out.as_bytes_mut());
+	out
+}
+
+/// Slot of a mapping value. `key` is the 32-byte ABI encoding of value type
+/// keys, or the raw contents of `bytes` and `string` keys.
+pub fn mapping_slot(slot: H256, key: &[u8]) -> H256 {
+	let mut data = Vec::with_capacity(key.len() + 32);
+	data.extend_from_slice(key);
+	data.extend_from_slice(slot.as_bytes());
+	keccak(&data)
+}
+
+/// Slots of the mapping values of several value type keys, hashing every
+/// preimage in one reused buffer.
+pub fn mapping_slots(slot: H256, keys: &[H256]) -> Vec<H256> {
+	let mut data = [0u8; 64];
+	data[32..].copy_from_slice(slot.as_bytes());
+	keys.iter()
+		.map(|key| {
+			data[..32].copy_from_slice(key.as_bytes());
+			keccak(&data)
+		})
+		.collect()
+}
+
+/// First slot of the elements of a dynamic array.
+pub fn dynamic_array_slot(slot: H256) -> H256 {
+	keccak(slot.as_bytes())
+}
+
+impl StorageLayout {
+	fn ty(&self, id: &str) -> Result<&StorageType, LayoutError> {
+		self.types.get(id).ok_or_else(|| LayoutError::UnknownType(id.into()))
+	}
+
+	fn member<'a>(
+		entries: &'a [StorageEntry],
+		label: &str,
+	) -> Result<&'a StorageEntry, LayoutError> {
+		entries.iter().find(|e| e.label == label)
+			.ok_or_else(|| LayoutError::UnknownLabel(label.into()))
+	}
+
+	/// Locate the value at the given path. The first segment must name a
+	/// state variable.
+	pub fn locate(&self, path: &[PathSegment]) -> Result<Location, LayoutError> {
+		let (first, rest) = path.split_first().ok_or(LayoutError::InvalidPath)?;
+		let entry = match first {
+			PathSegment::Field(label) => Self::member(&self.storage, label)?,
+			_ => return Err(LayoutError::InvalidPath),
+		};
+		let mut location = Location {
+			slot: add(H256::default(), parse(&entry.slot)?),
+			offset: entry.offset,
+			ty: entry.ty.clone(),
+		};
+
+		for segment in rest {
+			let ty = self.ty(&location.ty)?;
+			location = match (segment, ty.encoding.as_str()) {
+				(PathSegment::Field(label), "inplace") => {
+					let members = ty.members.as_ref().ok_or(LayoutError::InvalidPath)?;
+					let member = Self::member(members, label)?;
+					Location {
+						slot: add(location.slot, parse(&member.slot)?),
+						offset: member.offset,
+						ty: member.ty.clone(),
+					}
+				},
+				(PathSegment::Key(key), "mapping") => Location {
+					slot: mapping_slot(location.slot, key),
+					offset: 0,
+					ty: ty.value.clone().ok_or(LayoutError::InvalidPath)?,
+				},
+				(PathSegment::Index(index), "dynamic_array") |
+				(PathSegment::Index(index), "inplace") => {
+					let base_id = ty.base.as_ref().ok_or(LayoutError::InvalidPath)?;
+					let size = size(self.ty(base_id)?, base_id)?;
+					let start = if ty.encoding == "dynamic_array" {
+						dynamic_array_slot(location.slot)
+					} else {
+						location.slot
+					};
+
+					let (slot, offset) = if size < U256::from(32) {
+						let per_slot = U256::from(32) / size;
+						(*index / per_slot, (*index % per_slot * size).as_usize())
+					} else {
+						let slots = size.checked_add(U256::from(31))
+							.ok_or_else(|| LayoutError::InvalidSize(base_id.clone()))? / 32;
+						(index.overflowing_mul(slots).0, 0)
+					};
+					Location {
+						slot: add(start, slot),
+						offset,
+						ty: base_id.clone(),
+					}
+				},
+				_ => return Err(LayoutError::InvalidPath),
+			};
+		}
+
+		Ok(location)
+	}
+
+	/// Read and decode the value at the given path of a contract's storage.
+	pub async fn read<B: Backend>(
+		&self,
+		backend: &B,
+		address: H160,
+		path: &[PathSegment<'_>],
+	) -> Result<StorageValue, LayoutError> {
+		let location = self.locate(path)?;
+		let ty = self.ty(&location.ty)?;
+		let word = storage(backend, address, location.slot).await?;
+
+		if ty.encoding == "bytes" {
+			let bytes = read_bytes(backend, address, location.slot, word).await?;
+			return Ok(if ty.label == "string" {
+				StorageValue::String(String::from_utf8_lossy(&bytes).into())
+			} else {
+				StorageValue::Bytes(bytes)
+			})
+		}
+
+		if ty.encoding != "inplace" || ty.members.is_some() || ty.base.is_some() {
+			return Err(LayoutError::NotAValue(location.ty))
+		}
+
+		let size = size(ty, &location.ty)?;
+		if size > U256::from(32) {
+			return Err(LayoutError::InvalidSize(location.ty))
+		}
+		let size = size.as_usize();
+		if location.offset > 32 - size {
+			return Err(LayoutError::NotAValue(location.ty))
+		}
+		let end = 32 - location.offset;
+		let bytes = &word.as_bytes()[end - size..end];
+
+		let label = ty.label.as_str();
+		Ok(if label == "bool" {
+			StorageValue::Bool(bytes[size - 1] != 0)
+		} else if label.starts_with("address") || label.starts_with("contract ") {
+			StorageValue::Address(H160::from_slice(&bytes[size - 20..]))
+		} else if label.starts_with("bytes") {
+			StorageValue::Bytes(bytes.to_vec())
+		} else if label.starts_with("int") {
+			let fill = if bytes[0] & 0x80 != 0 { 0xff } else { 0 };
+			let mut extended = [fill; 32];
+			extended[32 - size..].copy_from_slice(bytes);
+			StorageValue::Int(U256::from_big_endian(&extended))
+		} else {
+			StorageValue::Uint(U256::from_big_endian(bytes))
+		})
+	}
+}
+
+async fn storage<B: Backend>(backend: &B, address: H160, index: H256) -> Result<H256, LayoutError> {
+	backend.storage(address, index).await.map_err(|e| LayoutError::Backend(format!("{:?}", e)))
+}
+
+async fn read_bytes<B: Backend>(
+	backend: &B,
+	address: H160,
+	slot: H256,
+	word: H256,
+) -> Result<Vec<u8>, LayoutError> {
+	let value = U256::from_big_endian(word.as_bytes());
+	if !value.bit(0) {
+		let len = (word[31] / 2) as usize;
+		return Ok(word[..len].to_vec())
+	}
+
+	let len = (value - 1) / 2;
+	if len > U256::from(usize::MAX) {
+		return Err(LayoutError::InvalidSize(format!("{}", len)))
+	}
+	let len = len.as_usize();
+	let start = dynamic_array_slot(slot);
+	let mut out = Vec::with_capacity(core::cmp::min(len, MAX_PREALLOCATED_BYTES));
+	let mut index = 0u64;
+	while out.len() < len {
+		let chunk = storage(backend, address, add(start, U256::from(index))).await?;
+		let take = core::cmp::min(32, len - out.len());
+		out.extend_from_slice(&chunk[..take]);
+		index += 1;
+	}
+	Ok(out)
+}
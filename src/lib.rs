@@ -1,7 +1,9 @@
 //! Ethereum Virtual Machine implementation in Rust
 
-#![deny(warnings)]
-#![forbid(unsafe_code, missing_docs, unused_variables, unused_imports)]
+#![deny(warnings, unused_imports)]
+// `tracing` macros locally allow unused imports, so that lint is denied
+// rather than forbidden.
+#![forbid(unsafe_code, missing_docs, unused_variables)]
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -13,3 +15,19 @@ pub use evm_gasometer as gasometer;
 
 pub mod executor;
 pub mod backend;
+pub mod chainspec;
+pub mod debug;
+pub mod deploy;
+pub mod events;
+pub mod hasher;
+pub mod layout;
+pub mod precompiles;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+pub mod token;
+pub mod types;
+mod json;
+#[cfg(feature = "k256")]
+pub mod signing;
+#[cfg(feature = "testutil")]
+pub mod testutil;
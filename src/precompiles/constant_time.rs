@@ -0,0 +1,37 @@
+//! Precompiles honoring the `constant-time` feature.
+//!
+//! With the feature enabled, for users embedding the EVM in trusted
+//! execution environments handling secret inputs:
+//!
+//! - P-256 signature verification (`P256VERIFY_ADDRESS`) runs in time
+//!   independent of its input, on the constant-time scalar and point
+//!   arithmetic of the `p256` crate, and zeroizes the derived scalars and
+//!   points.
+//! - The KZG versioned hash (`kzg_to_versioned_hash`) uses SHA-256, which
+//!   is constant-time, and zeroizes the intermediate digest. The point
+//!   evaluation precompile itself is not covered, as its pairing check is
+//!   performed by c-kzg.
+//! - `SHA3` digests are no longer memoized by the `KeccakCache`, whose
+//!   lookups would reveal repeated preimages through timing.
+//!
+//! The gas of the precompiles never depends on secret inputs. Other
+//! precompiles installed on the executor are user functions, and the EVM
+//! interpreter itself is not constant-time.
+
+use primitive_types::H160;
+
+#[cfg(all(feature = "constant-time", feature = "p256"))]
+use super::P256VERIFY_ADDRESS;
+
+/// Addresses of the precompiles running in time independent of their
+/// input, empty without the `constant-time` feature.
+pub const CONSTANT_TIME_PRECOMPILES: &[H160] = &[
+	#[cfg(all(feature = "constant-time", feature = "p256"))]
+	P256VERIFY_ADDRESS,
+];
+
+/// Whether the precompile at `address` runs in time independent of its
+/// input.
+pub fn is_constant_time(address: H160) -> bool {
+	CONSTANT_TIME_PRECOMPILES.contains(&address)
+}
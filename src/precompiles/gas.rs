@@ -0,0 +1,108 @@
+use core::cmp::max;
+
+use primitive_types::U256;
+
+use crate::Config;
+
+/// Length of the blake2f precompile input (EIP-152).
+pub const BLAKE2F_INPUT_LEN: usize = 213;
+/// Length of a pair of points of the bn128 pairing precompile input.
+pub const BN128_PAIR_LEN: usize = 192;
+
+/// Gas of the modexp precompile, by the EIP-2565 formula if the config
+/// enables it and the EIP-198 formula otherwise. Saturates at
+/// `usize::MAX`.
+pub fn modexp_gas(input: &[u8], config: &Config) -> usize {
+	let base_len = word(input, 0);
+	let exp_len = word(input, 32);
+	let mod_len = word(input, 64);
+
+	let exp_offset = U256::from(96).saturating_add(base_len);
+	let adjusted_exp_len = adjusted_exp_len(exp_len, &exp_head(input, exp_offset, exp_len));
+	let iterations = max(adjusted_exp_len, U256::one());
+	let len = max(base_len, mod_len);
+
+	let gas = if config.modexp_eip2565 {
+		let words = len.saturating_add(U256::from(7)) / 8;
+		let complexity = words.saturating_mul(words);
+		max(complexity.saturating_mul(iterations) / 3, U256::from(200))
+	} else {
+		eip198_complexity(len).saturating_mul(iterations) / 20
+	};
+
+	if gas > U256::from(usize::MAX) {
+		usize::MAX
+	} else {
+		gas.as_usize()
+	}
+}
+
+/// Gas of the bn128 addition precompile.
+pub fn bn128_add_gas(config: &Config) -> usize {
+	config.gas_bn128_add
+}
+
+/// Gas of the bn128 scalar multiplication precompile.
+pub fn bn128_mul_gas(config: &Config) -> usize {
+	config.gas_bn128_mul
+}
+
+/// Gas of the bn128 pairing precompile for an input of the given length.
+pub fn bn128_pairing_gas(input_len: usize, config: &Config) -> usize {
+	config.gas_bn128_pairing_point
+		.saturating_mul(input_len / BN128_PAIR_LEN)
+		.saturating_add(config.gas_bn128_pairing_base)
+}
+
+/// Gas of the blake2f precompile, one per round (EIP-152), or `None` if the
+/// input is not of the blake2f input length.
+pub fn blake2f_gas(input: &[u8]) -> Option<usize> {
+	if input.len() != BLAKE2F_INPUT_LEN {
+		return None
+	}
+	let mut rounds = [0u8; 4];
+	rounds.copy_from_slice(&input[..4]);
+	Some(u32::from_be_bytes(rounds) as usize)
+}
+
+/// Big-endian word at `offset`, right padded with zeros past the input.
+fn word(input: &[u8], offset: usize) -> U256 {
+	let mut word = [0u8; 32];
+	if offset < input.len() {
+		let available = &input[offset..];
+		let len = available.len().min(32);
+		word[..len].copy_from_slice(&available[..len]);
+	}
+	U256::from_big_endian(&word)
+}
+
+/// First 32 bytes of the exponent, or all of it if shorter, right padded
+/// with zeros past the input.
+fn exp_head(input: &[u8], offset: U256, exp_len: U256) -> U256 {
+	if offset >= U256::from(input.len()) || exp_len.is_zero() {
+		return U256::zero()
+	}
+	let head_len = if exp_len > U256::from(32) { 32 } else { exp_len.as_usize() };
+	let head = word(input, offset.as_usize());
+	head >> (8 * (32 - head_len))
+}
+
+fn adjusted_exp_len(exp_len: U256, head: &U256) -> U256 {
+	let head_bits = U256::from(head.bits().saturating_sub(1));
+	if exp_len <= U256::from(32) {
+		head_bits
+	} else {
+		(exp_len - 32).saturating_mul(U256::from(8)).saturating_add(head_bits)
+	}
+}
+
+fn eip198_complexity(x: U256) -> U256 {
+	if x <= U256::from(64) {
+		x * x
+	} else if x <= U256::from(1024) {
+		x * x / 4 + x * 96 - 3072
+	} else {
+		let square = x.saturating_mul(x);
+		(square / 16).saturating_add(x.saturating_mul(U256::from(480))) - 199680
+	}
+}
@@ -0,0 +1,136 @@
+use alloc::sync::Arc;
+
+use c_kzg::{Bytes32, Bytes48, KzgSettings};
+use primitive_types::{H160, U256};
+use sha2::{Digest, Sha256};
+
+use crate::{Context, ExitError, ExitSucceed};
+use crate::backend::Backend;
+use crate::executor::{AsyncPrecompile, PrecompileOutput};
+
+/// Address of the point evaluation precompile.
+pub const POINT_EVALUATION_ADDRESS: H160 = H160([
+	0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0a,
+]);
+/// Gas of the point evaluation precompile.
+pub const POINT_EVALUATION_GAS: usize = 50000;
+/// Version byte of versioned hashes of KZG commitments.
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+/// Field elements in a blob.
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+/// Modulus of the BLS12-381 scalar field, big-endian.
+pub const BLS_MODULUS: [u8; 32] = [
+	0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+	0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// Length of a compressed G1 point.
+const G1_LEN: usize = 48;
+/// Length of the point evaluation input.
+const INPUT_LEN: usize = 192;
+
+/// Versioned hash of a KZG commitment.
+pub fn kzg_to_versioned_hash(commitment: &[u8]) -> [u8; 32] {
+	let mut hash = [0u8; 32];
+	#[cfg_attr(not(feature = "constant-time"), allow(unused_mut))]
+	let mut digest = Sha256::digest(commitment);
+	hash.copy_from_slice(&digest[..]);
+	hash[0] = VERSIONED_HASH_VERSION_KZG;
+	#[cfg(feature = "constant-time")]
+	zeroize::Zeroize::zeroize(&mut digest[..]);
+	hash
+}
+
+/// Point evaluation precompile (EIP-4844), at `POINT_EVALUATION_ADDRESS`.
+/// Proofs are verified by c-kzg, with the mainnet trusted setup embedded
+/// in it unless other settings are given.
+pub struct PointEvaluation {
+	settings: Arc<KzgSettings>,
+}
+
+impl PointEvaluation {
+	/// Create the precompile with the mainnet trusted setup.
+	pub fn new() -> Self {
+		Self::with_settings(c_kzg::ethereum_kzg_settings_arc(0))
+	}
+
+	/// Create the precompile with the given trusted setup, such as one
+	/// loaded with `KzgSettings::parse_kzg_trusted_setup`.
+	pub fn with_settings(settings: Arc<KzgSettings>) -> Self {
+		Self { settings }
+	}
+
+	/// Trusted setup of the precompile.
+	pub fn settings(&self) -> &KzgSettings {
+		&self.settings
+	}
+
+	/// Evaluate the precompile on `input`.
+	pub fn evaluate(&self, input: &[u8], target_gas: Option<usize>) -> PrecompileOutput {
+		if let Some(target_gas) = target_gas {
+			if target_gas < POINT_EVALUATION_GAS {
+				return Err(ExitError::OutOfGas)
+			}
+		}
+		if input.len() != INPUT_LEN {
+			return Err(ExitError::Other("invalid point evaluation input length"))
+		}
+
+		let mut z = [0u8; 32];
+		let mut y = [0u8; 32];
+		let mut commitment = [0u8; G1_LEN];
+		let mut proof = [0u8; G1_LEN];
+		z.copy_from_slice(&input[32..64]);
+		y.copy_from_slice(&input[64..96]);
+		commitment.copy_from_slice(&input[96..144]);
+		proof.copy_from_slice(&input[144..192]);
+
+		if kzg_to_versioned_hash(&commitment)[..] != input[..32] {
+			return Err(ExitError::Other("mismatched versioned hash"))
+		}
+		let modulus = U256::from_big_endian(&BLS_MODULUS);
+		if U256::from_big_endian(&z) >= modulus || U256::from_big_endian(&y) >= modulus {
+			return Err(ExitError::Other("non-canonical field element"))
+		}
+		// Invalid point encodings are reported as errors by c-kzg, and
+		// rejected as invalid proofs.
+		let verified = self.settings.verify_kzg_proof(
+			&Bytes48::new(commitment),
+			&Bytes32::new(z),
+			&Bytes32::new(y),
+			&Bytes48::new(proof),
+		);
+		if !matches!(verified, Ok(true)) {
+			return Err(ExitError::Other("invalid kzg proof"))
+		}
+
+		let mut output = alloc::vec![0u8; 64];
+		U256::from(FIELD_ELEMENTS_PER_BLOB).to_big_endian(&mut output[..32]);
+		output[32..].copy_from_slice(&BLS_MODULUS);
+		Ok((ExitSucceed::Returned, output, POINT_EVALUATION_GAS))
+	}
+}
+
+impl Default for PointEvaluation {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<B: Backend> AsyncPrecompile<B> for PointEvaluation {
+	async fn execute(
+		&self,
+		address: H160,
+		input: &[u8],
+		target_gas: Option<usize>,
+		_context: &Context,
+		_backend: &B,
+	) -> Option<PrecompileOutput> {
+		if address != POINT_EVALUATION_ADDRESS {
+			return None
+		}
+		Some(self.evaluate(input, target_gas))
+	}
+}
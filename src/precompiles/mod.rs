@@ -0,0 +1,34 @@
+//! # Precompiles
+//!
+//! Gas schedules of the standard precompiled contracts, to be used by
+//! precompile functions installed on the executor, and precompiles
+//! installed as asynchronous precompiles.
+//!
+//! The `constant-time` feature selects implementations running in time
+//! independent of their input, listed in `CONSTANT_TIME_PRECOMPILES`.
+
+pub use self::constant_time::{is_constant_time, CONSTANT_TIME_PRECOMPILES};
+pub use self::gas::{
+	blake2f_gas, bn128_add_gas, bn128_mul_gas, bn128_pairing_gas, modexp_gas,
+	BLAKE2F_INPUT_LEN, BN128_PAIR_LEN,
+};
+#[cfg(feature = "kzg")]
+pub use self::kzg::{
+	kzg_to_versioned_hash, PointEvaluation, BLS_MODULUS, FIELD_ELEMENTS_PER_BLOB,
+	POINT_EVALUATION_ADDRESS, POINT_EVALUATION_GAS, VERSIONED_HASH_VERSION_KZG,
+};
+#[cfg(feature = "kzg")]
+pub use c_kzg::KzgSettings;
+#[cfg(feature = "p256")]
+pub use self::p256::{
+	p256_verify, verify_signature as p256_verify_signature,
+	verify_signature_constant_time as p256_verify_signature_constant_time, P256VERIFY_ADDRESS,
+	P256VERIFY_GAS,
+};
+
+mod constant_time;
+mod gas;
+#[cfg(feature = "kzg")]
+mod kzg;
+#[cfg(feature = "p256")]
+mod p256;
@@ -0,0 +1,128 @@
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use p256::elliptic_curve::ops::Reduce;
+use p256::elliptic_curve::point::AffineCoordinates;
+use p256::elliptic_curve::sec1::FromEncodedPoint;
+use p256::elliptic_curve::subtle::ConstantTimeEq;
+use p256::elliptic_curve::{Field, Group, PrimeField};
+use p256::{AffinePoint, EncodedPoint, FieldBytes, ProjectivePoint, Scalar};
+use primitive_types::{H160, U256};
+
+#[cfg(feature = "constant-time")]
+use zeroize::Zeroize;
+
+use crate::{ExitError, ExitSucceed};
+use crate::executor::PrecompileOutput;
+
+/// Address of the P-256 signature verification precompile (RIP-7212).
+pub const P256VERIFY_ADDRESS: H160 = H160([
+	0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01, 0x00,
+]);
+/// Gas of the P-256 signature verification precompile.
+pub const P256VERIFY_GAS: usize = 3450;
+
+/// Length of the precompile input: hash, r, s and the public key
+/// coordinates.
+const INPUT_LEN: usize = 160;
+
+/// P-256 signature verification precompile (RIP-7212). Returns the word
+/// `1` for valid signatures, and no output for invalid signatures or
+/// inputs. With the `constant-time` feature, signatures are verified by
+/// `verify_signature_constant_time`.
+pub fn p256_verify(input: &[u8], target_gas: Option<usize>) -> PrecompileOutput {
+	if let Some(target_gas) = target_gas {
+		if target_gas < P256VERIFY_GAS {
+			return Err(ExitError::OutOfGas)
+		}
+	}
+
+	#[cfg(not(feature = "constant-time"))]
+	let verify = verify_signature;
+	#[cfg(feature = "constant-time")]
+	let verify = verify_signature_constant_time;
+
+	let valid = input.len() == INPUT_LEN && verify(
+		U256::from_big_endian(&input[0..32]),
+		U256::from_big_endian(&input[32..64]),
+		U256::from_big_endian(&input[64..96]),
+		U256::from_big_endian(&input[96..128]),
+		U256::from_big_endian(&input[128..160]),
+	);
+
+	let output = if valid {
+		let mut word = alloc::vec![0u8; 32];
+		word[31] = 1;
+		word
+	} else {
+		alloc::vec::Vec::new()
+	};
+	Ok((ExitSucceed::Returned, output, P256VERIFY_GAS))
+}
+
+/// Whether `(r, s)` is a valid ECDSA signature of `hash` by the public key
+/// `(x, y)` on P-256, as verified by the `p256` crate. Both high and low
+/// `s` values are accepted, as RIP-7212 does not require normalized
+/// signatures.
+pub fn verify_signature(hash: U256, r: U256, s: U256, x: U256, y: U256) -> bool {
+	let signature = match Signature::from_scalars(bytes(r), bytes(s)) {
+		Ok(signature) => signature,
+		Err(_) => return false,
+	};
+	let point = EncodedPoint::from_affine_coordinates(&bytes(x), &bytes(y), false);
+	let key = match VerifyingKey::from_encoded_point(&point) {
+		Ok(key) => key,
+		Err(_) => return false,
+	};
+	key.verify_prehash(&bytes(hash), &signature).is_ok()
+}
+
+fn bytes(value: U256) -> FieldBytes {
+	let mut bytes = FieldBytes::default();
+	value.to_big_endian(&mut bytes);
+	bytes
+}
+
+/// Same as `verify_signature`, in time independent of its inputs: the
+/// inputs are checked and combined with the constant-time `Choice`, scalar
+/// and point arithmetic of the `p256` crate, without early returns. With
+/// the `constant-time` feature, the scalars and points derived from the
+/// inputs are zeroized before returning.
+#[cfg_attr(not(feature = "constant-time"), allow(unused_mut))]
+pub fn verify_signature_constant_time(hash: U256, r: U256, s: U256, x: U256, y: U256) -> bool {
+	let r = Scalar::from_repr(bytes(r));
+	let s = Scalar::from_repr(bytes(s));
+	let point = EncodedPoint::from_affine_coordinates(&bytes(x), &bytes(y), false);
+	let q = AffinePoint::from_encoded_point(&point);
+	let mut valid = r.is_some() & s.is_some() & q.is_some();
+
+	// Invalid inputs are replaced by valid ones, so that the arithmetic
+	// below runs the same way; `valid` is already cleared for them.
+	let r = r.unwrap_or(Scalar::ONE);
+	let mut s = s.unwrap_or(Scalar::ONE);
+	let mut q = ProjectivePoint::from(q.unwrap_or(AffinePoint::GENERATOR));
+	valid &= !r.is_zero() & !s.is_zero();
+
+	let mut s_inv = s.invert().unwrap_or(Scalar::ONE);
+	let mut u1 = <Scalar as Reduce<p256::U256>>::reduce_bytes(&bytes(hash)) * s_inv;
+	let mut u2 = r * s_inv;
+	let mut point = ProjectivePoint::GENERATOR * u1 + q * u2;
+
+	// The affine x coordinate, reduced modulo the group order, is compared
+	// to `r`.
+	valid &= !point.is_identity();
+	let mut affine = point.to_affine();
+	valid &= <Scalar as Reduce<p256::U256>>::reduce_bytes(&affine.x()).ct_eq(&r);
+
+	#[cfg(feature = "constant-time")]
+	{
+		s.zeroize();
+		q.zeroize();
+		s_inv.zeroize();
+		u1.zeroize();
+		u2.zeroize();
+		point.zeroize();
+		affine.zeroize();
+	}
+
+	valid.into()
+}
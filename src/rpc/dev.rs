@@ -0,0 +1,260 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::max;
+use core::fmt::Write;
+use core::time::Duration;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use primitive_types::{H160, H256, U256};
+use rlp::RlpStream;
+use sha3::{Digest, Keccak256};
+
+use crate::Config;
+use crate::backend::{Backend, MemoryAccount, MemoryBackend, MemoryVicinity};
+use crate::json::Json;
+use crate::signing::{LegacyTransaction, UnsignedTransaction, secret_address};
+use super::{CallRequest, Dispatch, Error, INVALID_PARAMS, RpcServer, SERVER_ERROR, handle, hex};
+
+/// Chain ID of dev nodes.
+pub const DEV_CHAIN_ID: u64 = 31337;
+/// Number of prefunded dev accounts.
+pub const DEV_ACCOUNTS: u64 = 10;
+/// Balance of each dev account, 10000 ether.
+pub const DEV_BALANCE: U256 = U256([0x19e0c9bab2400000, 0x21e, 0, 0]);
+/// Block gas limit of dev nodes.
+pub const DEV_GAS_LIMIT: u64 = 30_000_000;
+
+/// When a dev node mines blocks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MiningMode {
+	/// Mine a block after each transaction.
+	Auto,
+	/// Mine a block when the interval elapsed since the last one, checked
+	/// on each request and on `DevNode::poll`.
+	Interval(Duration),
+	/// Only mine on `evm_mine`.
+	Manual,
+}
+
+/// Local development node over a memory backend, in the style of anvil.
+///
+/// The backend environment is the pending block: transactions execute in
+/// it as they are received, and mining seals it and moves to the next
+/// block. Block hashes are derived from the number, timestamp and parent
+/// hash only, as no headers are built. Besides the methods of `RpcServer`,
+/// it serves `eth_accounts`, `eth_blockNumber`, `eth_chainId`,
+/// `eth_sendTransaction` from dev accounts, `evm_mine` and
+/// `evm_setNextBlockTimestamp`.
+///
+/// Dev accounts use the secret keys 1 to `DEV_ACCOUNTS`, and must never
+/// hold value on a public network.
+pub struct DevNode {
+	server: RpcServer<MemoryBackend>,
+	accounts: Vec<(H160, H256)>,
+	mining: MiningMode,
+	latest_timestamp: U256,
+	last_mined: Instant,
+}
+
+impl DevNode {
+	/// Create a dev node at block 1, with the dev accounts prefunded.
+	pub fn new(config: Arc<Config>, mining: MiningMode) -> Self {
+		let accounts = (1..=DEV_ACCOUNTS)
+			.map(|i| {
+				let secret = H256::from_low_u64_be(i);
+				(secret_address(&secret).expect("small scalars are valid secret keys"), secret)
+			})
+			.collect::<Vec<_>>();
+		let state = accounts.iter()
+			.map(|(address, _)| (*address, MemoryAccount { balance: DEV_BALANCE, ..Default::default() }))
+			.collect();
+
+		let genesis_timestamp = U256::from(unix_time());
+		let vicinity = MemoryVicinity {
+			gas_price: U256::zero(),
+			origin: H160::default(),
+			chain_id: U256::from(DEV_CHAIN_ID),
+			block_hashes: vec![block_hash(U256::zero(), genesis_timestamp, H256::default())],
+			block_number: U256::one(),
+			block_coinbase: H160::default(),
+			block_timestamp: genesis_timestamp + 1,
+			block_difficulty: U256::zero(),
+			block_gas_limit: U256::from(DEV_GAS_LIMIT),
+		};
+
+		Self {
+			server: RpcServer::new(MemoryBackend::new(Arc::new(vicinity), state), config),
+			accounts,
+			mining,
+			latest_timestamp: genesis_timestamp,
+			last_mined: Instant::now(),
+		}
+	}
+
+	/// Addresses and secret keys of the dev accounts.
+	pub fn accounts(&self) -> &[(H160, H256)] {
+		&self.accounts
+	}
+
+	/// Backend holding the state, in the environment of the pending block.
+	pub fn backend(&self) -> &MemoryBackend {
+		self.server.backend()
+	}
+
+	/// Mutable backend, for example to set up accounts.
+	pub fn backend_mut(&mut self) -> &mut MemoryBackend {
+		self.server.backend_mut()
+	}
+
+	/// Number of the latest mined block.
+	pub fn block_number(&self) -> U256 {
+		self.backend().vicinity().block_number - U256::one()
+	}
+
+	/// Seal the pending block and move to the next one, timestamped at the
+	/// current time or one second after the sealed block.
+	pub fn mine(&mut self) {
+		let backend = self.server.backend_mut();
+		let mut vicinity = backend.vicinity().clone();
+		let parent = vicinity.block_hashes.first().copied().unwrap_or_default();
+		let hash = block_hash(vicinity.block_number, vicinity.block_timestamp, parent);
+
+		self.latest_timestamp = vicinity.block_timestamp;
+		vicinity.block_hashes.insert(0, hash);
+		vicinity.block_hashes.truncate(256);
+		vicinity.block_number += U256::one();
+		vicinity.block_timestamp = max(self.latest_timestamp + 1, U256::from(unix_time()));
+		backend.set_vicinity(Arc::new(vicinity));
+	}
+
+	/// Mine a block if the mining interval elapsed at `now`. Returns whether
+	/// a block was mined.
+	pub fn poll(&mut self, now: Instant) -> bool {
+		match self.mining {
+			MiningMode::Interval(interval) if now.saturating_duration_since(self.last_mined) >= interval => {
+				self.mine();
+				self.last_mined = now;
+				true
+			},
+			_ => false,
+		}
+	}
+
+	/// Handle a JSON-RPC request or batch of requests, returning the
+	/// response JSON. A block is mined first if the mining interval
+	/// elapsed.
+	pub async fn handle(&mut self, request: &str) -> String {
+		self.poll(Instant::now());
+		handle(self, request).await
+	}
+
+	/// Timestamp the pending block. It must be after the latest mined
+	/// block.
+	fn set_timestamp(&mut self, timestamp: U256) -> Result<(), Error> {
+		if timestamp <= self.latest_timestamp {
+			return Err(Error::params("timestamp must be after the latest block"))
+		}
+		let backend = self.server.backend_mut();
+		let mut vicinity = backend.vicinity().clone();
+		vicinity.block_timestamp = timestamp;
+		backend.set_vicinity(Arc::new(vicinity));
+		Ok(())
+	}
+
+	async fn send_raw_transaction(&mut self, params: &[Json]) -> Result<String, Error> {
+		let hash = self.server.dispatch("eth_sendRawTransaction", params).await?;
+		if self.mining == MiningMode::Auto {
+			self.mine();
+		}
+		Ok(hash)
+	}
+
+	/// Sign the transaction with the key of its dev account sender, and send
+	/// it as a raw transaction.
+	async fn send_transaction(&mut self, params: &[Json]) -> Result<String, Error> {
+		let call = CallRequest::parse(params.first())?;
+		let secret = self.accounts.iter()
+			.find(|(address, _)| *address == call.from)
+			.map(|(_, secret)| *secret)
+			.ok_or_else(|| Error::new(SERVER_ERROR, "sender is not a dev account"))?;
+		let gas_limit = self.server.gas_limit(&call).await?;
+		let nonce = match params.first().and_then(|call| call.get("nonce")) {
+			Some(nonce) => parse_number(nonce, "nonce")?,
+			None => self.backend().basic(call.from).await
+				.map_err(|e| Error::new(SERVER_ERROR, alloc::format!("{:?}", e)))?
+				.nonce,
+		};
+
+		let vicinity = self.backend().vicinity();
+		let signed = UnsignedTransaction::Legacy(LegacyTransaction {
+			chain_id: Some(vicinity.chain_id),
+			nonce,
+			gas_price: vicinity.gas_price,
+			gas_limit: U256::from(gas_limit),
+			to: call.to,
+			value: call.value,
+			data: call.data,
+		}).sign(&secret).map_err(|_| Error::new(SERVER_ERROR, "signing failed"))?;
+
+		self.send_raw_transaction(&[Json::String(hex(&signed.encode()))]).await
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl Dispatch for DevNode {
+	async fn dispatch(&mut self, method: &str, params: &[Json]) -> Result<String, Error> {
+		match method {
+			"eth_accounts" => {
+				let mut json = String::from("[");
+				for (i, (address, _)) in self.accounts.iter().enumerate() {
+					if i > 0 {
+						json.push(',');
+					}
+					write!(json, "\"{:?}\"", address).expect("writing to a string cannot fail");
+				}
+				json.push(']');
+				Ok(json)
+			},
+			"eth_blockNumber" => Ok(alloc::format!("\"{:#x}\"", self.block_number())),
+			"eth_chainId" => Ok(alloc::format!("\"{:#x}\"", self.backend().vicinity().chain_id)),
+			"eth_sendTransaction" => self.send_transaction(params).await,
+			"eth_sendRawTransaction" => self.send_raw_transaction(params).await,
+			"evm_mine" => {
+				if let Some(timestamp) = params.first().filter(|timestamp| **timestamp != Json::Null) {
+					self.set_timestamp(parse_number(timestamp, "timestamp")?)?;
+				}
+				self.mine();
+				Ok(String::from("\"0x0\""))
+			},
+			"evm_setNextBlockTimestamp" => {
+				let timestamp = params.first().ok_or_else(|| Error::params("missing timestamp"))?;
+				self.set_timestamp(parse_number(timestamp, "timestamp")?)?;
+				Ok(String::from("null"))
+			},
+			_ => self.server.dispatch(method, params).await,
+		}
+	}
+}
+
+/// Quantity given as a hex or decimal string, or as a number.
+fn parse_number(value: &Json, field: &str) -> Result<U256, Error> {
+	match value {
+		Json::Number(number) => U256::from_dec_str(number).ok(),
+		Json::String(value) => crate::types::parse_u256(value).ok(),
+		_ => None,
+	}.ok_or_else(|| Error::new(INVALID_PARAMS, alloc::format!("invalid {}", field)))
+}
+
+fn block_hash(number: U256, timestamp: U256, parent: H256) -> H256 {
+	let mut stream = RlpStream::new_list(3);
+	stream.append(&number);
+	stream.append(&timestamp);
+	stream.append(&parent);
+	H256::from_slice(Keccak256::digest(&stream.out()).as_slice())
+}
+
+fn unix_time() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0)
+}
@@ -0,0 +1,634 @@
+//! Minimal JSON-RPC facade over the executor, serving `eth_call`,
+//! `eth_estimateGas`, `eth_getLogs`, `debug_traceCall` and
+//! `eth_sendRawTransaction` against a backend. The transport is left to
+//! the caller: requests and responses are exchanged as JSON text.
+//!
+//! `DevNode` builds a local development node on top of it, mining blocks
+//! over a memory backend.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+use std::sync::Arc;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::Config;
+use crate::backend::{ApplyBackend, Log, MemoryBackend};
+use crate::deploy::decode_hex;
+use crate::executor::{CallTrace, ExecutionResult, StackExecutor, Transaction, TransactionAction, TxValidationError};
+use crate::json::Json;
+use crate::signing::{SignedTransaction, UnsignedTransaction};
+use crate::types;
+use crate::{ExitReason, ExitRevert};
+
+pub use self::dev::{DEV_ACCOUNTS, DEV_BALANCE, DEV_CHAIN_ID, DEV_GAS_LIMIT, DevNode, MiningMode};
+
+mod dev;
+
+/// Invalid JSON was received.
+pub const PARSE_ERROR: i64 = -32700;
+/// The JSON is not a valid request object.
+pub const INVALID_REQUEST: i64 = -32600;
+/// The method does not exist.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// Invalid method parameters.
+pub const INVALID_PARAMS: i64 = -32602;
+/// The request could not be served, for example on a backend error or a
+/// rejected transaction.
+pub const SERVER_ERROR: i64 = -32000;
+/// The transaction was rejected before execution, for example for its
+/// nonce or for insufficient funds. The message describes the
+/// `TxValidationError`.
+pub const TRANSACTION_REJECTED: i64 = -32003;
+/// The execution reverted. The revert data is the data of the error.
+pub const EXECUTION_REVERTED: i64 = 3;
+
+/// Backend serving `eth_getLogs`.
+pub trait LogSource {
+	/// Logs with the number of the block they were applied in, oldest first.
+	fn block_logs(&self) -> Vec<(U256, &Log)>;
+}
+
+impl LogSource for MemoryBackend {
+	fn block_logs(&self) -> Vec<(U256, &Log)> {
+		MemoryBackend::block_logs(self).collect()
+	}
+}
+
+/// Error response of a request.
+struct Error {
+	code: i64,
+	message: String,
+	data: Option<Vec<u8>>,
+}
+
+impl Error {
+	fn new(code: i64, message: impl ToString) -> Self {
+		Self { code, message: message.to_string(), data: None }
+	}
+
+	fn params(message: impl ToString) -> Self {
+		Self::new(INVALID_PARAMS, message)
+	}
+
+	fn rejected(error: TxValidationError) -> Self {
+		Self::new(TRANSACTION_REJECTED, error)
+	}
+}
+
+/// Call object of `eth_call`, `eth_estimateGas` and `debug_traceCall`.
+struct CallRequest {
+	from: H160,
+	to: Option<H160>,
+	gas: Option<usize>,
+	value: U256,
+	data: Vec<u8>,
+}
+
+impl CallRequest {
+	fn parse(value: Option<&Json>) -> Result<Self, Error> {
+		let value = value.filter(|value| value.as_object().is_some())
+			.ok_or_else(|| Error::params("missing call object"))?;
+		let data = match value.get("input").or_else(|| value.get("data")) {
+			Some(data) => parse_data(data, "data")?,
+			None => Vec::new(),
+		};
+
+		Ok(Self {
+			from: value.get("from").map(|from| parse_address(from, "from")).transpose()?.unwrap_or_default(),
+			to: value.get("to").filter(|to| **to != Json::Null).map(|to| parse_address(to, "to")).transpose()?,
+			gas: value.get("gas").map(|gas| parse_gas(gas, "gas")).transpose()?,
+			value: value.get("value").map(|value| parse_quantity(value, "value")).transpose()?.unwrap_or_default(),
+			data,
+		})
+	}
+
+	fn transaction(&self, gas_limit: usize) -> Transaction {
+		Transaction {
+			caller: self.from,
+			action: match self.to {
+				Some(to) => TransactionAction::Call(to),
+				None => TransactionAction::Create,
+			},
+			value: self.value,
+			data: self.data.clone(),
+			gas_limit,
+			nonce: None,
+		}
+	}
+}
+
+/// JSON-RPC server over a backend. Calls run on the latest state and
+/// discard their changes; raw transactions are applied to the backend.
+///
+/// Calls skip the nonce, balance and base fee checks. Transactions are
+/// charged at the gas price of the backend, whatever the fees they carry.
+/// EIP-7702 transactions are not supported.
+pub struct RpcServer<B> {
+	backend: Arc<B>,
+	config: Arc<Config>,
+	call_config: Arc<Config>,
+}
+
+impl<B: ApplyBackend + LogSource> RpcServer<B> {
+	/// Create a server over the given backend.
+	pub fn new(backend: B, config: Arc<Config>) -> Self {
+		let call_config = Config {
+			disable_nonce_check: true,
+			disable_balance_check: true,
+			disable_base_fee: true,
+			..(*config).clone()
+		};
+
+		Self {
+			backend: Arc::new(backend),
+			config,
+			call_config: Arc::new(call_config),
+		}
+	}
+
+	/// Backend holding the state after the applied transactions.
+	pub fn backend(&self) -> &B {
+		&self.backend
+	}
+
+	/// Mutable backend, for example to move it to the next block.
+	pub fn backend_mut(&mut self) -> &mut B {
+		Arc::get_mut(&mut self.backend).expect("executors are dropped after each request")
+	}
+
+	/// Take the backend.
+	pub fn into_backend(self) -> B {
+		Arc::try_unwrap(self.backend).ok().expect("executors are dropped after each request")
+	}
+
+	/// Handle a JSON-RPC request or batch of requests, returning the
+	/// response JSON.
+	pub async fn handle(&mut self, request: &str) -> String {
+		handle(self, request).await
+	}
+
+	async fn call(&self, params: &[Json]) -> Result<String, Error> {
+		let call = CallRequest::parse(params.first())?;
+		check_latest(params.get(1))?;
+		let gas_limit = self.gas_limit(&call).await?;
+
+		let (result, _) = self.execute(&call, gas_limit).await?;
+		check_succeed(&result)?;
+		Ok(hex_string(&result.output))
+	}
+
+	async fn estimate_gas(&self, params: &[Json]) -> Result<String, Error> {
+		let call = CallRequest::parse(params.first())?;
+		check_latest(params.get(1))?;
+		let mut high = self.gas_limit(&call).await?;
+
+		let (result, _) = self.execute(&call, high).await?;
+		check_succeed(&result)?;
+
+		// Executions need at least the gas they use, so the search starts
+		// just below it.
+		let mut low = result.gas_used.saturating_sub(1);
+		while low + 1 < high {
+			let middle = low + (high - low) / 2;
+			let (result, _) = self.execute(&call, middle).await?;
+			if result.is_succeed() {
+				high = middle;
+			} else {
+				low = middle;
+			}
+		}
+		Ok(alloc::format!("\"{:#x}\"", high))
+	}
+
+	async fn logs(&self, params: &[Json]) -> Result<String, Error> {
+		let filter = params.first().filter(|filter| filter.as_object().is_some())
+			.ok_or_else(|| Error::params("missing filter object"))?;
+		if filter.get("blockHash").is_some() {
+			return Err(Error::params("blockHash filters are not supported"))
+		}
+		let latest = self.backend.block_number().await
+			.map_err(|e| Error::new(SERVER_ERROR, alloc::format!("{:?}", e)))?;
+		let from = block_number(filter.get("fromBlock"), latest, "fromBlock")?;
+		let to = block_number(filter.get("toBlock"), latest, "toBlock")?;
+
+		let addresses = match filter.get("address") {
+			None | Some(Json::Null) => Vec::new(),
+			Some(Json::Array(addresses)) => addresses.iter()
+				.map(|address| parse_address(address, "address"))
+				.collect::<Result<Vec<_>, _>>()?,
+			Some(address) => vec![parse_address(address, "address")?],
+		};
+		let topics = match filter.get("topics") {
+			None | Some(Json::Null) => Vec::new(),
+			Some(Json::Array(topics)) => topics.iter().map(|topic| match topic {
+				Json::Null => Ok(Vec::new()),
+				Json::Array(alternatives) => alternatives.iter()
+					.map(|topic| parse_word(topic, "topics"))
+					.collect::<Result<Vec<_>, _>>(),
+				topic => Ok(vec![parse_word(topic, "topics")?]),
+			}).collect::<Result<Vec<_>, _>>()?,
+			Some(_) => return Err(Error::params("invalid topics")),
+		};
+
+		let mut json = String::from("[");
+		let mut block = None;
+		let mut index = 0usize;
+		let mut first = true;
+		for (number, log) in self.backend.block_logs() {
+			if block != Some(number) {
+				block = Some(number);
+				index = 0;
+			}
+			let log_index = index;
+			index += 1;
+
+			let matches = number >= from && number <= to
+				&& (addresses.is_empty() || addresses.contains(&log.address))
+				&& topics.len() <= log.topics.len()
+				&& topics.iter().zip(&log.topics)
+					.all(|(alternatives, topic)| alternatives.is_empty() || alternatives.contains(topic));
+			if !matches {
+				continue
+			}
+
+			if !first {
+				json.push(',');
+			}
+			first = false;
+			write!(json, "{{\"address\":\"{:?}\",\"topics\":[", log.address)
+				.expect("writing to a string cannot fail");
+			for (i, topic) in log.topics.iter().enumerate() {
+				if i > 0 {
+					json.push(',');
+				}
+				write!(json, "\"{:?}\"", topic).expect("writing to a string cannot fail");
+			}
+			write!(
+				json,
+				"],\"data\":{},\"blockNumber\":\"{:#x}\",\"logIndex\":\"{:#x}\"}}",
+				hex_string(&log.data),
+				number,
+				log_index,
+			).expect("writing to a string cannot fail");
+		}
+		json.push(']');
+		Ok(json)
+	}
+
+	async fn trace_call(&self, params: &[Json]) -> Result<String, Error> {
+		let call = CallRequest::parse(params.first())?;
+		check_latest(params.get(1))?;
+		let gas_limit = self.gas_limit(&call).await?;
+
+		let (result, trace) = self.execute(&call, gas_limit).await?;
+		if let Some(e) = result.validation_error {
+			return Err(Error::rejected(e))
+		}
+		let trace = trace.ok_or_else(|| Error::new(SERVER_ERROR, reason_message(result.reason)))?;
+
+		let mut json = String::new();
+		write!(
+			json,
+			"{{\"type\":\"{}\",\"from\":\"{:?}\",\"to\":\"{:?}\",\"value\":\"{:#x}\",\"gas\":\"{:#x}\",\"gasUsed\":\"{:#x}\",\"input\":{},\"output\":{}",
+			if trace.is_create { "CREATE" } else { "CALL" },
+			call.from,
+			trace.address,
+			call.value,
+			gas_limit,
+			result.gas_used,
+			hex_string(&call.data),
+			hex_string(&result.output),
+		).expect("writing to a string cannot fail");
+		push_trace_tail(&mut json, &trace);
+		Ok(json)
+	}
+
+	async fn send_raw_transaction(&mut self, params: &[Json]) -> Result<String, Error> {
+		let raw = params.first().ok_or_else(|| Error::params("missing raw transaction"))?;
+		let raw = parse_data(raw, "raw transaction")?;
+		let signed = SignedTransaction::decode(&raw).map_err(|_| Error::params("invalid raw transaction"))?;
+		let caller = signed.sender().map_err(|_| Error::params("invalid signature"))?;
+
+		let (chain_id, nonce, gas_limit, to, value, data) = match &signed.transaction {
+			UnsignedTransaction::Legacy(tx) => (tx.chain_id, tx.nonce, tx.gas_limit, tx.to, tx.value, &tx.data),
+			UnsignedTransaction::Eip1559(tx) =>
+				(Some(tx.chain_id), tx.nonce, tx.gas_limit, tx.to, tx.value, &tx.data),
+			UnsignedTransaction::Eip7702(_) =>
+				return Err(Error::params("set code transactions are not supported")),
+		};
+		if let Some(chain_id) = chain_id {
+			let expected = self.backend.chain_id().await
+				.map_err(|e| Error::new(SERVER_ERROR, alloc::format!("{:?}", e)))?;
+			if chain_id != expected {
+				return Err(Error::new(SERVER_ERROR, "invalid chain id"))
+			}
+		}
+		if gas_limit > U256::from(usize::MAX) {
+			return Err(Error::new(SERVER_ERROR, "gas limit too high"))
+		}
+		let gas_limit = gas_limit.as_usize();
+
+		let mut executor = StackExecutor::new(self.backend.clone(), gas_limit, self.config.clone());
+		let result = executor.transact(Transaction {
+			caller,
+			action: match to {
+				Some(to) => TransactionAction::Call(to),
+				None => TransactionAction::Create,
+			},
+			value,
+			data: data.clone(),
+			gas_limit,
+			nonce: Some(nonce),
+		}).await;
+		if let Some(e) = executor.take_backend_error() {
+			return Err(Error::new(SERVER_ERROR, alloc::format!("{:?}", e)))
+		}
+		// Transactions rejected before execution change nothing.
+		if let Some(e) = result.validation_error {
+			return Err(Error::rejected(e))
+		}
+
+		let (applies, logs) = executor.deconstruct();
+		let delete_empty = !self.config.empty_considered_exists;
+		self.backend_mut().apply(applies, logs, delete_empty).await
+			.map_err(|e| Error::new(SERVER_ERROR, alloc::format!("{:?}", e)))?;
+		Ok(alloc::format!("\"{:?}\"", signed.hash()))
+	}
+
+	/// Gas limit of the call, or the block gas limit.
+	async fn gas_limit(&self, call: &CallRequest) -> Result<usize, Error> {
+		if let Some(gas) = call.gas {
+			return Ok(gas)
+		}
+		let limit = self.backend.block_gas_limit().await
+			.map_err(|e| Error::new(SERVER_ERROR, alloc::format!("{:?}", e)))?;
+		Ok(if limit > U256::from(usize::MAX) { usize::MAX } else { limit.as_usize() })
+	}
+
+	/// Execute the call with the given gas limit, discarding its changes,
+	/// and return its result and trace.
+	async fn execute(
+		&self,
+		call: &CallRequest,
+		gas_limit: usize,
+	) -> Result<(ExecutionResult, Option<CallTrace>), Error> {
+		let mut executor = StackExecutor::new(self.backend.clone(), gas_limit, self.call_config.clone());
+		let result = executor.transact(call.transaction(gas_limit)).await;
+		if let Some(e) = executor.take_backend_error() {
+			return Err(Error::new(SERVER_ERROR, alloc::format!("{:?}", e)))
+		}
+		let trace = executor.call_traces().first().cloned();
+		Ok((result, trace))
+	}
+}
+
+/// Server of JSON-RPC methods.
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+trait Dispatch {
+	/// Serve the method, returning its result JSON.
+	async fn dispatch(&mut self, method: &str, params: &[Json]) -> Result<String, Error>;
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl<B: ApplyBackend + LogSource> Dispatch for RpcServer<B> {
+	async fn dispatch(&mut self, method: &str, params: &[Json]) -> Result<String, Error> {
+		match method {
+			"eth_call" => self.call(params).await,
+			"eth_estimateGas" => self.estimate_gas(params).await,
+			"eth_getLogs" => self.logs(params).await,
+			"debug_traceCall" => self.trace_call(params).await,
+			"eth_sendRawTransaction" => self.send_raw_transaction(params).await,
+			_ => Err(Error::new(METHOD_NOT_FOUND, alloc::format!("method {} not found", method))),
+		}
+	}
+}
+
+/// Handle a request or batch of requests with the given server.
+async fn handle<D: Dispatch>(server: &mut D, request: &str) -> String {
+	let request = match Json::parse(request) {
+		Ok(request) => request,
+		Err(_) => return response(&Json::Null, Err(Error::new(PARSE_ERROR, "parse error"))),
+	};
+
+	match request {
+		Json::Array(requests) => {
+			if requests.is_empty() {
+				return response(&Json::Null, Err(Error::new(INVALID_REQUEST, "empty batch")))
+			}
+			let mut json = String::from("[");
+			for (i, request) in requests.iter().enumerate() {
+				if i > 0 {
+					json.push(',');
+				}
+				json.push_str(&handle_request(server, request).await);
+			}
+			json.push(']');
+			json
+		},
+		request => handle_request(server, &request).await,
+	}
+}
+
+async fn handle_request<D: Dispatch>(server: &mut D, request: &Json) -> String {
+	let id = request.get("id").cloned().unwrap_or(Json::Null);
+	let method = match request.get("method").and_then(Json::as_str) {
+		Some(method) => method,
+		None => return response(&id, Err(Error::new(INVALID_REQUEST, "invalid request"))),
+	};
+	let params = match request.get("params") {
+		None => &[][..],
+		Some(params) => match params.as_array() {
+			Some(params) => params,
+			None => return response(&id, Err(Error::params("params must be an array"))),
+		},
+	};
+
+	let result = server.dispatch(method, params).await;
+	response(&id, result)
+}
+
+/// Response JSON of a request with the given id.
+fn response(id: &Json, result: Result<String, Error>) -> String {
+	let mut json = String::from("{\"jsonrpc\":\"2.0\",\"id\":");
+	push_json(&mut json, id);
+	match result {
+		Ok(result) => {
+			json.push_str(",\"result\":");
+			json.push_str(&result);
+		},
+		Err(error) => {
+			write!(json, ",\"error\":{{\"code\":{},\"message\":", error.code)
+				.expect("writing to a string cannot fail");
+			push_string(&mut json, &error.message);
+			if let Some(data) = error.data {
+				json.push_str(",\"data\":");
+				json.push_str(&hex_string(&data));
+			}
+			json.push('}');
+		},
+	}
+	json.push('}');
+	json
+}
+
+/// Fail with the validation error of rejected transactions, the revert data
+/// of reverted executions, and the exit reason of other failed executions.
+fn check_succeed(result: &ExecutionResult) -> Result<(), Error> {
+	if let Some(e) = result.validation_error {
+		return Err(Error::rejected(e))
+	}
+	match result.reason {
+		ExitReason::Succeed(_) => Ok(()),
+		ExitReason::Revert(ExitRevert::Reverted) => Err(Error {
+			code: EXECUTION_REVERTED,
+			message: "execution reverted".to_string(),
+			data: Some(result.output.clone()),
+		}),
+		reason => Err(Error::new(SERVER_ERROR, reason_message(reason))),
+	}
+}
+
+fn reason_message(reason: ExitReason) -> String {
+	alloc::format!("{:?}", reason)
+}
+
+/// Only the latest state is served.
+fn check_latest(block: Option<&Json>) -> Result<(), Error> {
+	match block {
+		None | Some(Json::Null) => Ok(()),
+		Some(Json::String(tag)) if tag == "latest" || tag == "pending" => Ok(()),
+		Some(_) => Err(Error::params("only the latest block is served")),
+	}
+}
+
+fn block_number(value: Option<&Json>, latest: U256, field: &str) -> Result<U256, Error> {
+	match value {
+		None | Some(Json::Null) => Ok(latest),
+		Some(Json::String(tag)) if tag == "latest" || tag == "pending" => Ok(latest),
+		Some(Json::String(tag)) if tag == "earliest" => Ok(U256::zero()),
+		Some(value) => parse_quantity(value, field),
+	}
+}
+
+fn parse_address(value: &Json, field: &str) -> Result<H160, Error> {
+	value.as_str().and_then(|value| types::parse_address(value).ok())
+		.ok_or_else(|| Error::params(alloc::format!("invalid {}", field)))
+}
+
+fn parse_word(value: &Json, field: &str) -> Result<H256, Error> {
+	value.as_str().and_then(|value| types::parse_h256(value).ok())
+		.ok_or_else(|| Error::params(alloc::format!("invalid {}", field)))
+}
+
+fn parse_quantity(value: &Json, field: &str) -> Result<U256, Error> {
+	value.as_str().and_then(|value| types::parse_u256(value).ok())
+		.ok_or_else(|| Error::params(alloc::format!("invalid {}", field)))
+}
+
+fn parse_gas(value: &Json, field: &str) -> Result<usize, Error> {
+	let gas = parse_quantity(value, field)?;
+	if gas > U256::from(usize::MAX) {
+		return Err(Error::params(alloc::format!("invalid {}", field)))
+	}
+	Ok(gas.as_usize())
+}
+
+fn parse_data(value: &Json, field: &str) -> Result<Vec<u8>, Error> {
+	value.as_str().filter(|value| value.starts_with("0x")).and_then(decode_hex)
+		.ok_or_else(|| Error::params(alloc::format!("invalid {}", field)))
+}
+
+/// Fields of a trace frame after its input and output: its error and
+/// nested frames.
+fn push_trace_tail(json: &mut String, trace: &CallTrace) {
+	if !trace.reason.is_succeed() {
+		json.push_str(",\"error\":");
+		push_string(json, &reason_message(trace.reason));
+	}
+	if !trace.calls.is_empty() {
+		json.push_str(",\"calls\":[");
+		for (i, call) in trace.calls.iter().enumerate() {
+			if i > 0 {
+				json.push(',');
+			}
+			write!(
+				json,
+				"{{\"type\":\"{}\",\"from\":\"{:?}\",\"to\":\"{:?}\",\"value\":\"{:#x}\",\"gas\":\"{:#x}\",\"gasUsed\":\"{:#x}\"",
+				if call.is_create { "CREATE" } else { "CALL" },
+				call.context.caller,
+				call.address,
+				call.context.apparent_value,
+				call.gas_limit,
+				call.gas_used,
+			).expect("writing to a string cannot fail");
+			push_trace_tail(json, call);
+		}
+		json.push(']');
+	}
+	json.push('}');
+}
+
+/// `0x` prefixed hex string of the bytes, quoted.
+fn hex_string(bytes: &[u8]) -> String {
+	alloc::format!("\"{}\"", hex(bytes))
+}
+
+/// `0x` prefixed hex string of the bytes.
+fn hex(bytes: &[u8]) -> String {
+	let mut hex = String::with_capacity(bytes.len() * 2 + 2);
+	hex.push_str("0x");
+	for byte in bytes {
+		write!(hex, "{:02x}", byte).expect("writing to a string cannot fail");
+	}
+	hex
+}
+
+fn push_string(json: &mut String, value: &str) {
+	json.push('"');
+	for c in value.chars() {
+		match c {
+			'"' => json.push_str("\\\""),
+			'\\' => json.push_str("\\\\"),
+			c if (c as u32) < 0x20 => {
+				write!(json, "\\u{:04x}", c as u32).expect("writing to a string cannot fail");
+			},
+			c => json.push(c),
+		}
+	}
+	json.push('"');
+}
+
+fn push_json(json: &mut String, value: &Json) {
+	match value {
+		Json::Null => json.push_str("null"),
+		Json::Bool(value) => json.push_str(if *value { "true" } else { "false" }),
+		Json::Number(number) => json.push_str(number),
+		Json::String(value) => push_string(json, value),
+		Json::Array(items) => {
+			json.push('[');
+			for (i, item) in items.iter().enumerate() {
+				if i > 0 {
+					json.push(',');
+				}
+				push_json(json, item);
+			}
+			json.push(']');
+		},
+		Json::Object(fields) => {
+			json.push('{');
+			for (i, (key, value)) in fields.iter().enumerate() {
+				if i > 0 {
+					json.push(',');
+				}
+				push_string(json, key);
+				json.push(':');
+				push_json(json, value);
+			}
+			json.push('}');
+		},
+	}
+}
@@ -0,0 +1,496 @@
+//! Transaction signing and encoding, for legacy (EIP-155), EIP-1559 and
+//! EIP-7702 transactions.
+
+use alloc::vec::Vec;
+
+use k256::ecdsa::{RecoveryId, Signature as EcdsaSignature, SigningKey, VerifyingKey};
+use primitive_types::{H160, H256, U256};
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+use sha3::{Digest, Keccak256};
+
+fn keccak(data: &[u8]) -> H256 {
+	H256::from_slice(Keccak256::digest(data).as_slice())
+}
+
+fn append_to(stream: &mut RlpStream, to: &Option<H160>) {
+	match to {
+		Some(to) => { stream.append(to); },
+		None => { stream.append_empty_data(); },
+	}
+}
+
+fn append_access_list(stream: &mut RlpStream, access_list: &[(H160, Vec<H256>)]) {
+	stream.begin_list(access_list.len());
+	for (address, keys) in access_list {
+		stream.begin_list(2);
+		stream.append(address);
+		stream.begin_list(keys.len());
+		for key in keys {
+			stream.append(key);
+		}
+	}
+}
+
+fn decode_to(rlp: &Rlp) -> Result<Option<H160>, DecoderError> {
+	if rlp.is_empty() {
+		Ok(None)
+	} else {
+		rlp.as_val().map(Some)
+	}
+}
+
+fn decode_access_list(rlp: &Rlp) -> Result<Vec<(H160, Vec<H256>)>, DecoderError> {
+	rlp.iter().map(|item| Ok((item.val_at(0)?, item.list_at(1)?))).collect()
+}
+
+fn decode_y_parity(value: u8) -> Result<bool, DecoderError> {
+	match value {
+		0 => Ok(false),
+		1 => Ok(true),
+		_ => Err(DecoderError::Custom("invalid y parity")),
+	}
+}
+
+fn decode_signature(rlp: &Rlp, index: usize) -> Result<Signature, DecoderError> {
+	Ok(Signature {
+		y_parity: decode_y_parity(rlp.val_at(index)?)?,
+		r: rlp.val_at(index + 1)?,
+		s: rlp.val_at(index + 2)?,
+	})
+}
+
+/// Half of the secp256k1 curve order.
+const SECP256K1N_HALF: [u8; 32] = [
+	0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+	0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+/// Byte prefixing EIP-7702 authorization messages.
+pub const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+/// Legacy transaction. Signed with EIP-155 replay protection when a chain
+/// ID is given.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegacyTransaction {
+	/// Chain ID, as returned by `Backend::chain_id`.
+	pub chain_id: Option<U256>,
+	/// Nonce.
+	pub nonce: U256,
+	/// Gas price.
+	pub gas_price: U256,
+	/// Gas limit.
+	pub gas_limit: U256,
+	/// Target address, or `None` for contract creation.
+	pub to: Option<H160>,
+	/// Value transferred.
+	pub value: U256,
+	/// Call data, or init code for creations.
+	pub data: Vec<u8>,
+}
+
+/// EIP-1559 transaction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Eip1559Transaction {
+	/// Chain ID, as returned by `Backend::chain_id`.
+	pub chain_id: U256,
+	/// Nonce.
+	pub nonce: U256,
+	/// Maximum priority fee per gas.
+	pub max_priority_fee_per_gas: U256,
+	/// Maximum fee per gas.
+	pub max_fee_per_gas: U256,
+	/// Gas limit.
+	pub gas_limit: U256,
+	/// Target address, or `None` for contract creation.
+	pub to: Option<H160>,
+	/// Value transferred.
+	pub value: U256,
+	/// Call data, or init code for creations.
+	pub data: Vec<u8>,
+	/// Access list.
+	pub access_list: Vec<(H160, Vec<H256>)>,
+}
+
+/// EIP-7702 set code transaction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Eip7702Transaction {
+	/// Chain ID, as returned by `Backend::chain_id`.
+	pub chain_id: U256,
+	/// Nonce.
+	pub nonce: U256,
+	/// Maximum priority fee per gas.
+	pub max_priority_fee_per_gas: U256,
+	/// Maximum fee per gas.
+	pub max_fee_per_gas: U256,
+	/// Gas limit.
+	pub gas_limit: U256,
+	/// Target address. Set code transactions cannot create contracts.
+	pub to: H160,
+	/// Value transferred.
+	pub value: U256,
+	/// Call data.
+	pub data: Vec<u8>,
+	/// Access list.
+	pub access_list: Vec<(H160, Vec<H256>)>,
+	/// Code delegations to set before the call.
+	pub authorization_list: Vec<SignedAuthorization>,
+}
+
+/// Transaction to be signed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UnsignedTransaction {
+	/// Legacy transaction.
+	Legacy(LegacyTransaction),
+	/// EIP-1559 transaction.
+	Eip1559(Eip1559Transaction),
+	/// EIP-7702 transaction.
+	Eip7702(Eip7702Transaction),
+}
+
+/// Delegation of an account's code to `address`, per EIP-7702.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Authorization {
+	/// Chain ID the authorization is valid on, or zero for any chain.
+	pub chain_id: U256,
+	/// Address whose code the authority delegates to. The zero address
+	/// clears the delegation.
+	pub address: H160,
+	/// Nonce the authority must have when the authorization is applied.
+	pub nonce: U256,
+}
+
+/// Authorization signed by its authority.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SignedAuthorization {
+	/// Authorization.
+	pub authorization: Authorization,
+	/// Signature.
+	pub signature: Signature,
+}
+
+/// Secp256k1 signature of a transaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Signature {
+	/// Parity of the y coordinate of the signature point.
+	pub y_parity: bool,
+	/// R value.
+	pub r: U256,
+	/// S value.
+	pub s: U256,
+}
+
+impl Signature {
+	/// Whether `s` is above half the curve order. Such signatures are
+	/// malleable, and rejected per EIP-2.
+	pub fn is_high_s(&self) -> bool {
+		self.s > U256::from_big_endian(&SECP256K1N_HALF)
+	}
+}
+
+/// Signed transaction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignedTransaction {
+	/// Transaction.
+	pub transaction: UnsignedTransaction,
+	/// Signature.
+	pub signature: Signature,
+}
+
+impl UnsignedTransaction {
+	/// Chain ID of the transaction, if replay protected.
+	pub fn chain_id(&self) -> Option<U256> {
+		match self {
+			UnsignedTransaction::Legacy(tx) => tx.chain_id,
+			UnsignedTransaction::Eip1559(tx) => Some(tx.chain_id),
+			UnsignedTransaction::Eip7702(tx) => Some(tx.chain_id),
+		}
+	}
+
+	fn encode(&self, signature: Option<&Signature>) -> Vec<u8> {
+		match self {
+			UnsignedTransaction::Legacy(tx) => {
+				let replay_protected = signature.is_some() || tx.chain_id.is_some();
+				let mut stream = RlpStream::new_list(if replay_protected { 9 } else { 6 });
+				stream.append(&tx.nonce);
+				stream.append(&tx.gas_price);
+				stream.append(&tx.gas_limit);
+				append_to(&mut stream, &tx.to);
+				stream.append(&tx.value);
+				stream.append(&tx.data);
+				match (signature, tx.chain_id) {
+					(Some(signature), chain_id) => {
+						let v = match chain_id {
+							Some(chain_id) => chain_id * 2 + 35,
+							None => U256::from(27),
+						} + U256::from(signature.y_parity as u8);
+						stream.append(&v);
+						stream.append(&signature.r);
+						stream.append(&signature.s);
+					},
+					(None, Some(chain_id)) => {
+						stream.append(&chain_id);
+						stream.append_empty_data();
+						stream.append_empty_data();
+					},
+					(None, None) => (),
+				}
+				stream.out()
+			},
+			UnsignedTransaction::Eip1559(tx) => {
+				let mut stream = RlpStream::new_list(if signature.is_some() { 12 } else { 9 });
+				stream.append(&tx.chain_id);
+				stream.append(&tx.nonce);
+				stream.append(&tx.max_priority_fee_per_gas);
+				stream.append(&tx.max_fee_per_gas);
+				stream.append(&tx.gas_limit);
+				append_to(&mut stream, &tx.to);
+				stream.append(&tx.value);
+				stream.append(&tx.data);
+				append_access_list(&mut stream, &tx.access_list);
+				if let Some(signature) = signature {
+					stream.append(&(signature.y_parity as u8));
+					stream.append(&signature.r);
+					stream.append(&signature.s);
+				}
+
+				let mut out = Vec::new();
+				out.push(0x02);
+				out.extend_from_slice(&stream.out());
+				out
+			},
+			UnsignedTransaction::Eip7702(tx) => {
+				let mut stream = RlpStream::new_list(if signature.is_some() { 13 } else { 10 });
+				stream.append(&tx.chain_id);
+				stream.append(&tx.nonce);
+				stream.append(&tx.max_priority_fee_per_gas);
+				stream.append(&tx.max_fee_per_gas);
+				stream.append(&tx.gas_limit);
+				stream.append(&tx.to);
+				stream.append(&tx.value);
+				stream.append(&tx.data);
+				append_access_list(&mut stream, &tx.access_list);
+				stream.append_list(&tx.authorization_list);
+				if let Some(signature) = signature {
+					stream.append(&(signature.y_parity as u8));
+					stream.append(&signature.r);
+					stream.append(&signature.s);
+				}
+
+				let mut out = Vec::new();
+				out.push(0x04);
+				out.extend_from_slice(&stream.out());
+				out
+			},
+		}
+	}
+
+	/// Hash signed by the sender.
+	pub fn signing_hash(&self) -> H256 {
+		keccak(&self.encode(None))
+	}
+
+	/// Sign the transaction with the given secret key.
+	pub fn sign(self, secret: &H256) -> Result<SignedTransaction, k256::ecdsa::Error> {
+		let signature = sign_hash(&self.signing_hash(), secret)?;
+		Ok(SignedTransaction { transaction: self, signature })
+	}
+}
+
+impl SignedTransaction {
+	/// Raw transaction bytes, as submitted to a network.
+	pub fn encode(&self) -> Vec<u8> {
+		self.transaction.encode(Some(&self.signature))
+	}
+
+	/// Transaction hash.
+	pub fn hash(&self) -> H256 {
+		keccak(&self.encode())
+	}
+
+	/// Recover the sender address from the signature. Signatures with a
+	/// high `s` value are rejected, per EIP-2.
+	pub fn sender(&self) -> Result<H160, k256::ecdsa::Error> {
+		if self.signature.is_high_s() {
+			return Err(k256::ecdsa::Error::new())
+		}
+		recover(&self.transaction.signing_hash(), &self.signature)
+	}
+
+	/// Decode raw transaction bytes, as returned by `encode`.
+	pub fn decode(bytes: &[u8]) -> Result<SignedTransaction, DecoderError> {
+		match bytes.first() {
+			None => Err(DecoderError::RlpIsTooShort),
+			Some(0x02) => {
+				let rlp = Rlp::new(&bytes[1..]);
+				if rlp.item_count()? != 12 {
+					return Err(DecoderError::RlpIncorrectListLen)
+				}
+				Ok(SignedTransaction {
+					transaction: UnsignedTransaction::Eip1559(Eip1559Transaction {
+						chain_id: rlp.val_at(0)?,
+						nonce: rlp.val_at(1)?,
+						max_priority_fee_per_gas: rlp.val_at(2)?,
+						max_fee_per_gas: rlp.val_at(3)?,
+						gas_limit: rlp.val_at(4)?,
+						to: decode_to(&rlp.at(5)?)?,
+						value: rlp.val_at(6)?,
+						data: rlp.val_at(7)?,
+						access_list: decode_access_list(&rlp.at(8)?)?,
+					}),
+					signature: decode_signature(&rlp, 9)?,
+				})
+			},
+			Some(0x04) => {
+				let rlp = Rlp::new(&bytes[1..]);
+				if rlp.item_count()? != 13 {
+					return Err(DecoderError::RlpIncorrectListLen)
+				}
+				Ok(SignedTransaction {
+					transaction: UnsignedTransaction::Eip7702(Eip7702Transaction {
+						chain_id: rlp.val_at(0)?,
+						nonce: rlp.val_at(1)?,
+						max_priority_fee_per_gas: rlp.val_at(2)?,
+						max_fee_per_gas: rlp.val_at(3)?,
+						gas_limit: rlp.val_at(4)?,
+						to: rlp.val_at(5)?,
+						value: rlp.val_at(6)?,
+						data: rlp.val_at(7)?,
+						access_list: decode_access_list(&rlp.at(8)?)?,
+						authorization_list: rlp.list_at(9)?,
+					}),
+					signature: decode_signature(&rlp, 10)?,
+				})
+			},
+			Some(byte) if *byte >= 0xc0 => {
+				let rlp = Rlp::new(bytes);
+				if rlp.item_count()? != 9 {
+					return Err(DecoderError::RlpIncorrectListLen)
+				}
+				let v: U256 = rlp.val_at(6)?;
+				let (chain_id, y_parity) = if v == U256::from(27) || v == U256::from(28) {
+					(None, v == U256::from(28))
+				} else if v >= U256::from(35) {
+					(Some((v - 35) / 2), (v - 35) % 2 == U256::one())
+				} else {
+					return Err(DecoderError::Custom("invalid v"))
+				};
+				Ok(SignedTransaction {
+					transaction: UnsignedTransaction::Legacy(LegacyTransaction {
+						chain_id,
+						nonce: rlp.val_at(0)?,
+						gas_price: rlp.val_at(1)?,
+						gas_limit: rlp.val_at(2)?,
+						to: decode_to(&rlp.at(3)?)?,
+						value: rlp.val_at(4)?,
+						data: rlp.val_at(5)?,
+					}),
+					signature: Signature { y_parity, r: rlp.val_at(7)?, s: rlp.val_at(8)? },
+				})
+			},
+			Some(_) => Err(DecoderError::Custom("unsupported transaction type")),
+		}
+	}
+}
+
+impl Authorization {
+	/// Hash signed by the authority.
+	pub fn signing_hash(&self) -> H256 {
+		let mut stream = RlpStream::new_list(3);
+		stream.append(&self.chain_id);
+		stream.append(&self.address);
+		stream.append(&self.nonce);
+
+		let mut message = Vec::new();
+		message.push(AUTHORIZATION_MAGIC);
+		message.extend_from_slice(&stream.out());
+		keccak(&message)
+	}
+
+	/// Sign the authorization with the given secret key.
+	pub fn sign(self, secret: &H256) -> Result<SignedAuthorization, k256::ecdsa::Error> {
+		let signature = sign_hash(&self.signing_hash(), secret)?;
+		Ok(SignedAuthorization { authorization: self, signature })
+	}
+}
+
+impl SignedAuthorization {
+	/// Recover the authority from the signature. Signatures with a high `s`
+	/// value are rejected.
+	pub fn authority(&self) -> Result<H160, k256::ecdsa::Error> {
+		if self.signature.is_high_s() {
+			return Err(k256::ecdsa::Error::new())
+		}
+		recover(&self.authorization.signing_hash(), &self.signature)
+	}
+}
+
+impl Encodable for SignedAuthorization {
+	fn rlp_append(&self, stream: &mut RlpStream) {
+		stream.begin_list(6);
+		stream.append(&self.authorization.chain_id);
+		stream.append(&self.authorization.address);
+		stream.append(&self.authorization.nonce);
+		stream.append(&(self.signature.y_parity as u8));
+		stream.append(&self.signature.r);
+		stream.append(&self.signature.s);
+	}
+}
+
+impl Decodable for SignedAuthorization {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 6 {
+			return Err(DecoderError::RlpIncorrectListLen)
+		}
+		let y_parity = decode_y_parity(rlp.val_at(3)?)?;
+
+		Ok(Self {
+			authorization: Authorization {
+				chain_id: rlp.val_at(0)?,
+				address: rlp.val_at(1)?,
+				nonce: rlp.val_at(2)?,
+			},
+			signature: Signature { y_parity, r: rlp.val_at(4)?, s: rlp.val_at(5)? },
+		})
+	}
+}
+
+/// Decode an RLP encoded authorization list.
+pub fn decode_authorization_list(bytes: &[u8]) -> Result<Vec<SignedAuthorization>, DecoderError> {
+	Rlp::new(bytes).as_list()
+}
+
+/// Sign a message hash with the given secret key.
+pub fn sign_hash(hash: &H256, secret: &H256) -> Result<Signature, k256::ecdsa::Error> {
+	let key = SigningKey::from_slice(secret.as_bytes())?;
+	let (signature, recovery_id) = key.sign_prehash_recoverable(hash.as_bytes())?;
+	let bytes = signature.to_bytes();
+
+	Ok(Signature {
+		y_parity: recovery_id.is_y_odd(),
+		r: U256::from_big_endian(&bytes[..32]),
+		s: U256::from_big_endian(&bytes[32..]),
+	})
+}
+
+/// Recover the signer address of a message hash.
+pub fn recover(hash: &H256, signature: &Signature) -> Result<H160, k256::ecdsa::Error> {
+	let mut bytes = [0u8; 64];
+	signature.r.to_big_endian(&mut bytes[..32]);
+	signature.s.to_big_endian(&mut bytes[32..]);
+	let ecdsa = EcdsaSignature::from_slice(&bytes)?;
+	let recovery_id = RecoveryId::new(signature.y_parity, false);
+
+	let key = VerifyingKey::recover_from_prehash(hash.as_bytes(), &ecdsa, recovery_id)?;
+	Ok(address(&key))
+}
+
+/// Address of the given public key.
+pub fn address(key: &VerifyingKey) -> H160 {
+	let point = key.to_encoded_point(false);
+	H160::from_slice(&keccak(&point.as_bytes()[1..])[12..])
+}
+
+/// Address of the given secret key.
+pub fn secret_address(secret: &H256) -> Result<H160, k256::ecdsa::Error> {
+	Ok(address(SigningKey::from_slice(secret.as_bytes())?.verifying_key()))
+}
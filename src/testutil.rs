@@ -0,0 +1,172 @@
+//! Deterministic pseudo-random state and transaction generators for property
+//! tests. The same seed always produces the same output.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::OpcodeInfo;
+use crate::backend::MemoryAccount;
+use crate::executor::{Transaction, TransactionAction};
+
+/// Number of accounts in a generated state.
+pub const ACCOUNTS: usize = 8;
+
+/// Opcodes emitted by `random_code`.
+const OPCODES: &[u8] = &[
+	0x01, 0x02, 0x03, 0x04, 0x10, 0x14, 0x15, 0x16, 0x20, 0x30, 0x31, 0x33, 0x34,
+	0x35, 0x36, 0x3d, 0x50, 0x51, 0x52, 0x54, 0x55, 0x5a, 0xa1, 0xf1, 0xf4, 0xfa,
+];
+
+/// SplitMix64 pseudo-random number generator.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+	/// Create a generator from a seed.
+	pub fn new(seed: u64) -> Self {
+		Self(seed)
+	}
+
+	/// Next 64 random bits.
+	pub fn next_u64(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+		z ^ (z >> 31)
+	}
+
+	/// Random value in `0..n`. `n` must not be zero.
+	pub fn below(&mut self, n: u64) -> u64 {
+		self.next_u64() % n
+	}
+
+	/// Random bytes.
+	pub fn bytes(&mut self, len: usize) -> Vec<u8> {
+		(0..len).map(|_| self.next_u64() as u8).collect()
+	}
+
+	/// Random 256-bit hash.
+	pub fn h256(&mut self) -> H256 {
+		H256::from_slice(&self.bytes(32))
+	}
+
+	/// Random account of a generated state.
+	pub fn address(&mut self) -> H160 {
+		address(self.below(ACCOUNTS as u64) as usize)
+	}
+}
+
+/// Address of the account at `index` in a generated state.
+pub fn address(index: usize) -> H160 {
+	H160::from_low_u64_be(0x1000 + index as u64)
+}
+
+/// Random code of `ops` instructions. Every instruction has its inputs pushed
+/// before it, and every jump targets a later `JUMPDEST`, so generated code
+/// always terminates.
+pub fn random_code(rng: &mut Rng, ops: usize) -> Vec<u8> {
+	let mut code = Vec::new();
+	let mut jumps = Vec::new();
+	let mut jumpdests = Vec::new();
+
+	for _ in 0..ops {
+		match rng.below(8) {
+			0 => {
+				jumpdests.push(code.len());
+				code.push(0x5b);
+			},
+			1 => {
+				let conditional = rng.below(2) == 0;
+				if conditional {
+					code.extend_from_slice(&[0x60, rng.below(2) as u8]);
+				}
+				jumps.push(code.len());
+				code.extend_from_slice(&[0x61, 0, 0, if conditional { 0x57 } else { 0x56 }]);
+			},
+			_ => {
+				let opcode = OPCODES[rng.below(OPCODES.len() as u64) as usize];
+				let inputs = OpcodeInfo::of(opcode).expect("generated opcodes are defined").inputs;
+				for input in (0..inputs).rev() {
+					// The address input of calls targets a generated account.
+					if (opcode == 0xf1 || opcode == 0xf4 || opcode == 0xfa) && input == 1 {
+						code.push(0x73);
+						code.extend_from_slice(&rng.address()[..]);
+					} else {
+						code.extend_from_slice(&[0x60, rng.below(64) as u8]);
+					}
+				}
+				code.push(opcode);
+			},
+		}
+	}
+
+	jumpdests.push(code.len());
+	code.push(0x5b);
+	match rng.below(3) {
+		0 => code.push(0x00),
+		1 => code.extend_from_slice(&[0x60, 0x20, 0x60, 0x00, 0xf3]),
+		_ => code.extend_from_slice(&[0x60, 0x20, 0x60, 0x00, 0xfd]),
+	}
+
+	for jump in jumps {
+		let later = jumpdests.iter().filter(|dest| **dest > jump).count() as u64;
+		let dest = jumpdests[jumpdests.len() - 1 - rng.below(later) as usize];
+		code[jump + 1] = (dest >> 8) as u8;
+		code[jump + 2] = dest as u8;
+	}
+
+	code
+}
+
+/// Random set of `ACCOUNTS` accounts, with code and storage.
+pub fn random_state(seed: u64) -> BTreeMap<H160, MemoryAccount> {
+	let mut rng = Rng::new(seed);
+
+	(0..ACCOUNTS).map(|index| {
+		let ops = rng.below(32) as usize;
+		let code = if rng.below(4) == 0 { Vec::new() } else { random_code(&mut rng, ops) };
+		let storage = (0..rng.below(8))
+			.map(|_| (H256::from_low_u64_be(rng.below(64)), rng.h256()))
+			.collect();
+
+		(address(index), MemoryAccount {
+			nonce: U256::from(rng.below(16)),
+			balance: U256::from(rng.next_u64()),
+			storage,
+			code: code.into(),
+		})
+	}).collect()
+}
+
+/// Random transaction between accounts of a generated state.
+pub fn random_tx(seed: u64) -> Transaction {
+	let mut rng = Rng::new(seed);
+	let caller = rng.address();
+	let action = match rng.below(8) {
+		0 => TransactionAction::Create,
+		1 => TransactionAction::Create2(rng.h256()),
+		_ => TransactionAction::Call(rng.address()),
+	};
+	let data = match action {
+		TransactionAction::Call(_) => {
+			let len = rng.below(64) as usize;
+			rng.bytes(len)
+		},
+		_ => {
+			let ops = rng.below(32) as usize;
+			random_code(&mut rng, ops)
+		},
+	};
+
+	Transaction {
+		caller,
+		action,
+		value: U256::from(rng.below(1_000)),
+		data,
+		gas_limit: 100_000 + rng.below(900_000) as usize,
+		nonce: None,
+	}
+}
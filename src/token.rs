@@ -0,0 +1,171 @@
+//! ERC-20 and ERC-721 simulation helpers.
+//!
+//! Every helper runs on a fresh executor and discards the resulting state.
+//! Queries run as static calls, so a token changing state while answering
+//! one fails with `ExitError::StaticModeViolation`.
+
+use alloc::vec::Vec;
+use std::sync::Arc;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::{Capture, Config, Context, ExitReason, StateMutator};
+use crate::backend::Backend;
+use crate::executor::StackExecutor;
+
+/// Gas limit of simulated calls.
+pub const GAS_LIMIT: usize = 30_000_000;
+
+const BALANCE_OF: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+const ALLOWANCE: [u8; 4] = [0xdd, 0x62, 0xed, 0x3e];
+const TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+const OWNER_OF: [u8; 4] = [0x63, 0x52, 0x21, 0x1e];
+
+/// Token call failure.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TokenError {
+	/// The call reverted with the given data.
+	Reverted(Vec<u8>),
+	/// The call failed.
+	Failed(ExitReason),
+	/// The token returned `false`.
+	ReturnedFalse,
+	/// The returned data could not be decoded.
+	InvalidOutput(Vec<u8>),
+}
+
+/// Outcome of a simulated transfer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TransferSimulation {
+	/// Gas used by the transfer transaction.
+	pub used_gas: usize,
+	/// Sender balance after the transfer.
+	pub from_balance: U256,
+	/// Recipient balance after the transfer.
+	pub to_balance: U256,
+}
+
+fn calldata(selector: [u8; 4], args: &[H256]) -> Vec<u8> {
+	let mut data = Vec::with_capacity(4 + args.len() * 32);
+	data.extend_from_slice(&selector);
+	for arg in args {
+		data.extend_from_slice(arg.as_bytes());
+	}
+	data
+}
+
+fn word(value: U256) -> H256 {
+	let mut out = H256::default();
+	value.to_big_endian(out.as_bytes_mut());
+	out
+}
+
+async fn call<B: Backend>(
+	executor: &mut StackExecutor<B>,
+	caller: H160,
+	token: H160,
+	data: Vec<u8>,
+) -> Result<Vec<u8>, TokenError> {
+	match executor.transact_call(caller, token, U256::zero(), data, GAS_LIMIT).await {
+		(ExitReason::Succeed(_), out) => Ok(out),
+		(ExitReason::Revert(_), out) => Err(TokenError::Reverted(out)),
+		(reason, _) => Err(TokenError::Failed(reason)),
+	}
+}
+
+/// Run a static call in a substate of `executor` that is then discarded.
+async fn static_call<B: Backend>(
+	executor: &StackExecutor<B>,
+	token: H160,
+	data: Vec<u8>,
+) -> Result<Vec<u8>, TokenError> {
+	let mut substate = executor.substate(GAS_LIMIT, true);
+	let context = Context { address: token, caller: H160::default(), apparent_value: U256::zero() };
+	let (reason, out) = match substate.call(token, None, data.into(), Some(GAS_LIMIT), true, context).await {
+		Capture::Exit((reason, out)) => (reason, out.into_vec()),
+		Capture::Trap(trap) => match trap {},
+	};
+	match reason {
+		ExitReason::Succeed(_) => Ok(out),
+		ExitReason::Revert(_) => Err(TokenError::Reverted(out)),
+		reason => Err(TokenError::Failed(reason)),
+	}
+}
+
+fn decode_uint(out: Vec<u8>) -> Result<U256, TokenError> {
+	if out.len() < 32 {
+		return Err(TokenError::InvalidOutput(out))
+	}
+	Ok(U256::from_big_endian(&out[..32]))
+}
+
+fn decode_address(out: Vec<u8>) -> Result<H160, TokenError> {
+	if out.len() < 32 || out[..12].iter().any(|b| *b != 0) {
+		return Err(TokenError::InvalidOutput(out))
+	}
+	Ok(H160::from_slice(&out[12..32]))
+}
+
+/// ERC-20 `balanceOf(owner)`.
+pub async fn balance_of<B: Backend>(
+	backend: Arc<B>,
+	config: &Config,
+	token: H160,
+	owner: H160,
+) -> Result<U256, TokenError> {
+	let executor = StackExecutor::new(backend, GAS_LIMIT, Arc::new(config.clone()));
+	let out = static_call(&executor, token, calldata(BALANCE_OF, &[owner.into()])).await?;
+	decode_uint(out)
+}
+
+/// ERC-20 `allowance(owner, spender)`.
+pub async fn allowance<B: Backend>(
+	backend: Arc<B>,
+	config: &Config,
+	token: H160,
+	owner: H160,
+	spender: H160,
+) -> Result<U256, TokenError> {
+	let executor = StackExecutor::new(backend, GAS_LIMIT, Arc::new(config.clone()));
+	let data = calldata(ALLOWANCE, &[owner.into(), spender.into()]);
+	decode_uint(static_call(&executor, token, data).await?)
+}
+
+/// ERC-721 `ownerOf(token_id)`.
+pub async fn owner_of<B: Backend>(
+	backend: Arc<B>,
+	config: &Config,
+	token: H160,
+	token_id: U256,
+) -> Result<H160, TokenError> {
+	let executor = StackExecutor::new(backend, GAS_LIMIT, Arc::new(config.clone()));
+	let out = static_call(&executor, token, calldata(OWNER_OF, &[word(token_id)])).await?;
+	decode_address(out)
+}
+
+/// Simulate ERC-20 `transfer(to, amount)` sent by `from`, and read both
+/// balances afterwards. Tokens returning no data are treated as successful.
+pub async fn simulate_transfer<B: Backend>(
+	backend: Arc<B>,
+	config: &Config,
+	token: H160,
+	from: H160,
+	to: H160,
+	amount: U256,
+) -> Result<TransferSimulation, TokenError> {
+	let mut executor = StackExecutor::new(backend, GAS_LIMIT, Arc::new(config.clone()));
+	let out = call(&mut executor, from, token, calldata(TRANSFER, &[to.into(), word(amount)])).await?;
+	if !out.is_empty() && decode_uint(out)? == U256::zero() {
+		return Err(TokenError::ReturnedFalse)
+	}
+	let used_gas = executor.used_gas();
+
+	let from_balance = decode_uint(
+		static_call(&executor, token, calldata(BALANCE_OF, &[from.into()])).await?
+	)?;
+	let to_balance = decode_uint(
+		static_call(&executor, token, calldata(BALANCE_OF, &[to.into()])).await?
+	)?;
+
+	Ok(TransferSimulation { used_gas, from_balance, to_balance })
+}
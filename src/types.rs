@@ -0,0 +1,193 @@
+//! Parsing and formatting helpers for user-facing tooling: hex words and
+//! quantities, and EIP-55 checksummed addresses.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use core::str::FromStr;
+
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+/// Failure to parse an address, word or quantity.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseError {
+	/// The value is empty.
+	Empty,
+	/// The character at the given position is not a hex digit.
+	InvalidCharacter {
+		/// Position of the character, after the `0x` prefix.
+		position: usize,
+		/// The character.
+		character: char,
+	},
+	/// The value does not have the expected number of hex digits.
+	InvalidLength {
+		/// Expected number of hex digits.
+		expected: usize,
+		/// Number of hex digits of the value.
+		found: usize,
+	},
+	/// The value is not a decimal number.
+	InvalidDecimal,
+	/// The value does not fit in 256 bits.
+	Overflow,
+	/// The mixed-case address does not match its EIP-55 checksum.
+	InvalidChecksum {
+		/// The address with the correct checksum.
+		expected: String,
+	},
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ParseError::Empty => write!(f, "empty value"),
+			ParseError::InvalidCharacter { position, character } =>
+				write!(f, "invalid hex character {:?} at position {}", character, position),
+			ParseError::InvalidLength { expected, found } =>
+				write!(f, "expected {} hex digits, found {}", expected, found),
+			ParseError::InvalidDecimal => write!(f, "invalid decimal number"),
+			ParseError::Overflow => write!(f, "value does not fit in 256 bits"),
+			ParseError::InvalidChecksum { expected } =>
+				write!(f, "invalid address checksum, expected {}", expected),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Address, parsed and displayed with its EIP-55 checksum.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Address(pub H160);
+
+impl Address {
+	/// Parse an address which must have its exact EIP-55 checksum, with
+	/// or without the `0x` prefix.
+	pub fn from_checksum_str(value: &str) -> Result<Self, ParseError> {
+		let digits = strip_prefix(value);
+		let address = Address(H160::from_slice(&decode(digits, 40)?));
+		let expected = address.to_checksum_string();
+		if digits != &expected[2..] {
+			return Err(ParseError::InvalidChecksum { expected })
+		}
+		Ok(address)
+	}
+
+	/// The address with the `0x` prefix and its EIP-55 checksum.
+	pub fn to_checksum_string(&self) -> String {
+		let lower = hex(self.0.as_bytes());
+		let hash = Keccak256::digest(lower.as_bytes());
+		let mut checksummed = String::with_capacity(42);
+		checksummed.push_str("0x");
+		for (i, c) in lower.chars().enumerate() {
+			let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+			checksummed.push(if nibble >= 8 { c.to_ascii_uppercase() } else { c });
+		}
+		checksummed
+	}
+}
+
+impl FromStr for Address {
+	type Err = ParseError;
+
+	/// Parse an address, with or without the `0x` prefix. All lowercase and
+	/// all uppercase addresses are accepted as is, and mixed-case addresses
+	/// must have their EIP-55 checksum.
+	fn from_str(value: &str) -> Result<Self, ParseError> {
+		let digits = strip_prefix(value);
+		let address = Address(H160::from_slice(&decode(digits, 40)?));
+		let is_mixed = digits.chars().any(|c| c.is_ascii_lowercase())
+			&& digits.chars().any(|c| c.is_ascii_uppercase());
+		if is_mixed {
+			return Self::from_checksum_str(digits)
+		}
+		Ok(address)
+	}
+}
+
+impl fmt::Display for Address {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.to_checksum_string())
+	}
+}
+
+impl From<H160> for Address {
+	fn from(address: H160) -> Self {
+		Address(address)
+	}
+}
+
+impl From<Address> for H160 {
+	fn from(address: Address) -> Self {
+		address.0
+	}
+}
+
+/// Parse an address, as `Address::from_str`.
+pub fn parse_address(value: &str) -> Result<H160, ParseError> {
+	Address::from_str(value).map(H160::from)
+}
+
+/// Parse a word of exactly 64 hex digits, with or without the `0x` prefix.
+pub fn parse_h256(value: &str) -> Result<H256, ParseError> {
+	Ok(H256::from_slice(&decode(strip_prefix(value), 64)?))
+}
+
+/// Parse a quantity, either as `0x` prefixed hex of at most 64 digits, or
+/// as a decimal number.
+pub fn parse_u256(value: &str) -> Result<U256, ParseError> {
+	if value.is_empty() {
+		return Err(ParseError::Empty)
+	}
+	match value.strip_prefix("0x") {
+		Some(digits) => {
+			check_digits(digits)?;
+			if digits.len() > 64 {
+				return Err(ParseError::Overflow)
+			}
+			let padded = if digits.len() % 2 == 1 { alloc::format!("0{}", digits) } else { digits.into() };
+			let bytes = crate::deploy::decode_hex(&padded).expect("hex digits of even length");
+			Ok(U256::from_big_endian(&bytes))
+		},
+		None => {
+			if !value.bytes().all(|b| b.is_ascii_digit()) {
+				return Err(ParseError::InvalidDecimal)
+			}
+			U256::from_dec_str(value).map_err(|_| ParseError::Overflow)
+		},
+	}
+}
+
+fn strip_prefix(value: &str) -> &str {
+	value.strip_prefix("0x").unwrap_or(value)
+}
+
+/// Check that the value is non-empty hex digits.
+fn check_digits(digits: &str) -> Result<(), ParseError> {
+	if digits.is_empty() {
+		return Err(ParseError::Empty)
+	}
+	match digits.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+		Some((position, character)) => Err(ParseError::InvalidCharacter { position, character }),
+		None => Ok(()),
+	}
+}
+
+/// Decode exactly `len` hex digits.
+fn decode(digits: &str, len: usize) -> Result<Vec<u8>, ParseError> {
+	check_digits(digits)?;
+	if digits.len() != len {
+		return Err(ParseError::InvalidLength { expected: len, found: digits.len() })
+	}
+	Ok(crate::deploy::decode_hex(digits).expect("hex digits of even length"))
+}
+
+fn hex(bytes: &[u8]) -> String {
+	let mut hex = String::with_capacity(bytes.len() * 2);
+	for byte in bytes {
+		write!(hex, "{:02x}", byte).expect("writing to a string cannot fail");
+	}
+	hex
+}
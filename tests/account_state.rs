@@ -0,0 +1,119 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::{ApplyBackend, MemoryAccount, MemoryBackend};
+use evm::executor::{AccountState, StackExecutor, Transaction, TransactionAction};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, account, block_on, vicinity};
+
+const CHILD: u64 = 0xbb;
+const EMPTY: u64 = 0xee;
+const MISSING: u64 = 0xdd;
+
+// REVERT with no data.
+const REVERT: &str = "60006000fd";
+
+fn address(low: u64) -> String {
+	format!("73{:040x}", low)
+}
+
+/// CALL the address with 10000 gas and no value, and pop the result.
+fn call(low: u64) -> String {
+	format!("60006000600060006000{}612710f150", address(low))
+}
+
+fn empty() -> MemoryAccount {
+	MemoryAccount { nonce: U256::zero(), balance: U256::zero(), ..Default::default() }
+}
+
+/// State after running a call to `TARGET`, applied without the backend
+/// deleting empty accounts.
+fn run(config: Config, accounts: Vec<(u64, MemoryAccount)>) -> BTreeMap<H160, MemoryAccount> {
+	let mut state = accounts.into_iter()
+		.map(|(low, account)| (H160::from_low_u64_be(low), account))
+		.collect::<BTreeMap<_, _>>();
+	state.insert(H160::from_low_u64_be(CALLER), account(""));
+	state.insert(H160::from_low_u64_be(EMPTY), empty());
+	let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity()), state.clone()));
+
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(config));
+	let result = block_on(executor.transact(Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action: TransactionAction::Call(H160::from_low_u64_be(TARGET)),
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: 1_000_000,
+		nonce: None,
+	}));
+	assert!(result.is_succeed(), "{:?}", result.reason);
+
+	let (applies, logs) = executor.deconstruct();
+	let mut post = MemoryBackend::new(Arc::new(vicinity()), state);
+	block_on(post.apply(applies, logs, false)).unwrap();
+	post.state().clone()
+}
+
+#[test]
+fn touched_empty_accounts_are_deleted() {
+	let target = format!("{}{}", call(EMPTY), call(MISSING));
+	let state = run(Config::istanbul(), vec![(TARGET, account(&target))]);
+	assert!(!state.contains_key(&H160::from_low_u64_be(EMPTY)));
+	assert!(!state.contains_key(&H160::from_low_u64_be(MISSING)));
+	assert!(state.contains_key(&H160::from_low_u64_be(TARGET)));
+}
+
+#[test]
+fn touched_empty_accounts_are_kept_before_eip161() {
+	let target = format!("{}{}", call(EMPTY), call(MISSING));
+	let state = run(Config::frontier(), vec![(TARGET, account(&target))]);
+	assert_eq!(state.get(&H160::from_low_u64_be(EMPTY)), Some(&empty()));
+	assert_eq!(state.get(&H160::from_low_u64_be(MISSING)), Some(&empty()));
+}
+
+#[test]
+fn read_empty_accounts_are_not_touched() {
+	// BALANCE and EXTCODESIZE of the empty account.
+	let target = format!("{}31{}3b", address(EMPTY), address(EMPTY));
+	let state = run(Config::istanbul(), vec![(TARGET, account(&target))]);
+	assert_eq!(state.get(&H160::from_low_u64_be(EMPTY)), Some(&empty()));
+}
+
+#[test]
+fn reverted_touches_are_discarded() {
+	let child = format!("{}{}", call(EMPTY), REVERT);
+	let state = run(Config::istanbul(), vec![
+		(TARGET, account(&call(CHILD))),
+		(CHILD, account(&child)),
+	]);
+	assert_eq!(state.get(&H160::from_low_u64_be(EMPTY)), Some(&empty()));
+}
+
+#[test]
+fn account_state_distinguishes_empty_accounts() {
+	let state = vec![
+		(H160::from_low_u64_be(TARGET), account("00")),
+		(H160::from_low_u64_be(EMPTY), empty()),
+	].into_iter().collect();
+	let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity()), state));
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+
+	let state = |executor: &StackExecutor<MemoryBackend>, low| {
+		block_on(executor.account_state(H160::from_low_u64_be(low)))
+	};
+	assert_eq!(state(&executor, TARGET), AccountState::NonEmpty);
+	assert_eq!(state(&executor, EMPTY), AccountState::Empty);
+	assert_eq!(state(&executor, MISSING), AccountState::NonExistent);
+
+	assert!(!AccountState::Empty.exists(&Config::istanbul()));
+	assert!(AccountState::Empty.exists(&Config::frontier()));
+
+	// Touched accounts are part of the state, empty or not.
+	block_on(executor.deposit(H160::from_low_u64_be(MISSING), U256::zero()));
+	assert_eq!(state(&executor, MISSING), AccountState::Empty);
+	block_on(executor.deposit(H160::from_low_u64_be(MISSING), U256::one()));
+	assert_eq!(state(&executor, MISSING), AccountState::NonEmpty);
+}
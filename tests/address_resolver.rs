@@ -0,0 +1,82 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::MemoryBackend;
+use evm::executor::{AddressResolver, L1_TO_L2_ALIAS_OFFSET, L1ToL2Alias, StackExecutor};
+use primitive_types::{H160, U256};
+
+use common::{account, backend, block_on};
+
+const CALLER: u64 = 0xa1;
+const ECHO: u64 = 0xaa;
+const OUTER: u64 = 0xcc;
+const REDIRECTED: u64 = 0xdd;
+
+// Return CALLER as a word.
+const ECHO_CALLER: &str = "3360005260206000f3";
+// Return ADDRESS as a word.
+const ECHO_ADDRESS: &str = "3060005260206000f3";
+
+fn executor() -> StackExecutor<MemoryBackend> {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(ECHO), account(ECHO_CALLER)),
+		// Call ECHO with all gas, then return its output.
+		(H160::from_low_u64_be(OUTER), account("602060006000600060007300000000000000000000000000000000000000aa5af160206000f3")),
+		(H160::from_low_u64_be(REDIRECTED), account(ECHO_ADDRESS)),
+	]);
+	StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()))
+}
+
+fn call(executor: &mut StackExecutor<MemoryBackend>, to: u64) -> H160 {
+	let (reason, output) = block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER),
+		H160::from_low_u64_be(to),
+		U256::zero(),
+		Vec::new(),
+		100_000,
+	));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	H160::from_slice(&output[12..])
+}
+
+struct Redirect;
+
+impl AddressResolver for Redirect {
+	fn target(&self, target: H160, _depth: usize) -> H160 {
+		if target == H160::from_low_u64_be(ECHO) { H160::from_low_u64_be(REDIRECTED) } else { target }
+	}
+}
+
+#[test]
+fn alias_adds_the_offset() {
+	assert_eq!(L1ToL2Alias::alias(H160::zero()), L1_TO_L2_ALIAS_OFFSET);
+	// Aliasing wraps around the address space.
+	let mut wrapped = L1_TO_L2_ALIAS_OFFSET;
+	wrapped.0[19] = 0x10;
+	assert_eq!(L1ToL2Alias::alias(H160::repeat_byte(0xff)), wrapped);
+}
+
+#[test]
+fn transaction_caller_is_aliased() {
+	let mut executor = executor();
+	executor.set_address_resolver(Some(Arc::new(L1ToL2Alias)));
+
+	assert_eq!(call(&mut executor, ECHO), L1ToL2Alias::alias(H160::from_low_u64_be(CALLER)));
+	// Only the transaction frame is aliased.
+	assert_eq!(call(&mut executor, OUTER), H160::from_low_u64_be(OUTER));
+}
+
+#[test]
+fn targets_are_redirected() {
+	let mut executor = executor();
+	assert_eq!(call(&mut executor, ECHO), H160::from_low_u64_be(CALLER));
+
+	executor.set_address_resolver(Some(Arc::new(Redirect)));
+	assert_eq!(call(&mut executor, ECHO), H160::from_low_u64_be(REDIRECTED));
+	assert_eq!(call(&mut executor, OUTER), H160::from_low_u64_be(REDIRECTED));
+
+	executor.set_address_resolver(None);
+	assert_eq!(call(&mut executor, ECHO), H160::from_low_u64_be(CALLER));
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "alloy")]
+
+mod common;
+
+use std::sync::Arc;
+
+use alloy_primitives::{Address, Bytes, B256, U64};
+use alloy_provider::{ProviderBuilder, RootProvider};
+use alloy_provider::transport::mock::Asserter;
+use alloy_rpc_types_eth::{Block, Transaction};
+use evm::backend::{AlloyProvider, Backend, BlockId, ProviderBackend, ProviderError};
+use primitive_types::{H160, H256, U256};
+
+use common::{TARGET, block_on};
+
+fn provider() -> (Asserter, AlloyProvider<RootProvider>) {
+	let asserter = Asserter::new();
+	let provider = ProviderBuilder::default().connect_mocked_client(asserter.clone());
+	(asserter, AlloyProvider::new(provider))
+}
+
+fn block() -> Block {
+	let mut block = Block::<Transaction>::default();
+	block.header.hash = B256::repeat_byte(0x11);
+	block.header.inner.number = 7;
+	block.header.inner.beneficiary = Address::repeat_byte(0xcb);
+	block.header.inner.timestamp = 1_000;
+	block.header.inner.mix_hash = B256::with_last_byte(0x42);
+	block.header.inner.gas_limit = 30_000_000;
+	block
+}
+
+#[test]
+fn pins_a_block_of_an_alloy_provider() {
+	let (asserter, provider) = provider();
+	asserter.push_success(&block());
+	asserter.push_success(&U64::from(5));
+
+	let backend = block_on(ProviderBackend::pin(Arc::new(provider), BlockId::Number(U256::from(7)))).unwrap();
+	let header = backend.header();
+	assert_eq!(header.number, U256::from(7));
+	assert_eq!(header.hash, H256::repeat_byte(0x11));
+	assert_eq!(header.coinbase, H160::repeat_byte(0xcb));
+	assert_eq!(header.timestamp, U256::from(1_000));
+	assert_eq!(header.difficulty, U256::from(0x42));
+	assert_eq!(header.gas_limit, U256::from(30_000_000));
+	assert_eq!(block_on(backend.chain_id()).unwrap(), U256::from(5));
+
+	let target = H160::from_low_u64_be(TARGET);
+	asserter.push_success(&alloy_primitives::U256::from(1_000_000));
+	asserter.push_success(&U64::from(3));
+	let basic = block_on(backend.basic(target)).unwrap();
+	assert_eq!((basic.balance, basic.nonce), (U256::from(1_000_000), U256::from(3)));
+
+	asserter.push_success(&Bytes::from(vec![0x60, 0x00]));
+	assert_eq!(block_on(backend.code(target)).unwrap(), vec![0x60, 0x00]);
+
+	asserter.push_success(&alloy_primitives::U256::from(9));
+	assert_eq!(block_on(backend.storage(target, H256::zero())).unwrap(), H256::from_low_u64_be(9));
+}
+
+#[test]
+fn reports_unknown_blocks() {
+	let (asserter, provider) = provider();
+	asserter.push_success(&Option::<Block>::None);
+
+	let pinned = block_on(ProviderBackend::pin(Arc::new(provider), BlockId::Number(U256::from(7))));
+	assert!(matches!(pinned, Err(ProviderError::UnknownBlock(BlockId::Number(_)))));
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{AnalyzedCode, Bytes, Config, Context, Opcode, Runtime};
+use evm::executor::{AnalysisCache, StackExecutor};
+use evm::gasometer::static_opcode_cost;
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+use common::{CALLER, TARGET, backend, block_on, call_target, deploy};
+
+// Return 1 + 2, jumping over an invalid opcode.
+const ADD: &str = "6001600201600956fe5b60005260206000f3";
+
+#[test]
+fn decodes_instructions() {
+	let config = Config::istanbul();
+	let analysis = AnalyzedCode::with_static_gas(
+		Arc::new(hex::decode(ADD).unwrap()),
+		|opcode| static_opcode_cost(opcode, &config),
+	);
+
+	let push = analysis.instruction(0).unwrap();
+	assert_eq!(push.opcode, Ok(Opcode::Push(1)));
+	assert_eq!(push.static_gas, Some(3));
+	assert_eq!(analysis.immediate(0), Some(U256::one()));
+	assert_eq!(analysis.immediate(1), None);
+	assert_eq!(analysis.immediate(2), Some(U256::from(2)));
+
+	assert_eq!(analysis.instruction(4).unwrap().opcode, Ok(Opcode::Add));
+	assert_eq!(analysis.instruction(8).unwrap().static_gas, None);
+	assert!(analysis.valids().is_valid(9));
+	assert!(!analysis.valids().is_valid(8));
+	assert!(analysis.instruction(ADD.len() / 2).is_none());
+}
+
+#[test]
+fn executions_share_analysis() {
+	let backend = deploy(ADD);
+	let cache = StackExecutor::new(backend.clone(), 0, Arc::new(Config::istanbul())).analysis_cache();
+
+	for _ in 0..2 {
+		let mut executor = StackExecutor::new(backend.clone(), 100_000, Arc::new(Config::istanbul()));
+		executor.set_analysis_cache(cache.clone());
+
+		let (reason, output) = call_target(&mut executor, Vec::new(), 100_000);
+		assert!(reason.is_succeed());
+		assert_eq!(output, H256::from_low_u64_be(3).as_bytes().to_vec());
+	}
+
+	let cache = cache.lock().unwrap();
+	assert_eq!(cache.len(), 1);
+	assert_eq!((cache.hits(), cache.misses()), (1, 1));
+}
+
+#[test]
+fn charges_static_gas_from_analysis() {
+	let backend = backend(Vec::new());
+	let config = Arc::new(Config::istanbul());
+	let mut executor = StackExecutor::new(backend, 100_000, config.clone());
+
+	// PUSH1 PUSH1 ADD STOP, priced at one gas per instruction.
+	let analysis = AnalyzedCode::with_static_gas(
+		Arc::new(hex::decode("600160020100").unwrap()),
+		|_| Some(1),
+	);
+	let context = Context {
+		address: H160::from_low_u64_be(TARGET),
+		caller: H160::from_low_u64_be(CALLER),
+		apparent_value: U256::zero(),
+	};
+	let mut runtime = Runtime::new_analyzed(Arc::new(analysis), Bytes::from(Vec::new()), context, config);
+
+	assert!(block_on(executor.execute(&mut runtime)).is_succeed());
+	assert_eq!(executor.used_gas(), 4);
+}
+
+#[test]
+fn evicts_least_recently_used_code() {
+	let config = Config::istanbul();
+	let mut cache = AnalysisCache::with_capacity(2);
+
+	cache.get_or_analyze(vec![0x01], &config);
+	cache.get_or_analyze(vec![0x02], &config);
+	cache.get_or_analyze(vec![0x01], &config);
+	cache.get_or_analyze(vec![0x03], &config);
+
+	assert_eq!(cache.len(), 2);
+	assert!(cache.get(&H256::from_slice(&Keccak256::digest(&[0x01])), &config).is_some());
+	assert!(cache.get(&H256::from_slice(&Keccak256::digest(&[0x02])), &config).is_none());
+}
+
+#[test]
+fn keys_analysis_by_config() {
+	let istanbul = Config::istanbul();
+	let overridden = Config::istanbul().with_gas_override(0x01, 1);
+	let mut cache = AnalysisCache::default();
+
+	let first = cache.get_or_analyze(hex::decode(ADD).unwrap(), &istanbul);
+	let second = cache.get_or_analyze(hex::decode(ADD).unwrap(), &overridden);
+
+	assert_eq!(cache.len(), 2);
+	assert_eq!(first.instruction(4).unwrap().static_gas, Some(3));
+	assert_eq!(second.instruction(4).unwrap().static_gas, Some(1));
+	assert!(Arc::ptr_eq(&cache.get_or_analyze(hex::decode(ADD).unwrap(), &istanbul), &first));
+}
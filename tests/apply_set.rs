@@ -0,0 +1,135 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::{Apply, ApplyBackend, Basic, Log, MemoryBackend};
+use evm::executor::{AccessSet, ApplySet, StackExecutor};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, account, block_on, vicinity};
+
+const A: u64 = 0xaa;
+const B: u64 = 0xbb;
+
+fn word(low: u64) -> H256 {
+	H256::from_low_u64_be(low)
+}
+
+fn modify(
+	low: u64,
+	balance: u64,
+	code: Option<Vec<u8>>,
+	storage: Vec<(u64, u64)>,
+	reset_storage: bool,
+) -> Apply<Vec<(H256, H256)>> {
+	Apply::Modify {
+		address: H160::from_low_u64_be(low),
+		basic: Basic { balance: U256::from(balance), nonce: U256::zero() },
+		code,
+		storage: storage.into_iter().map(|(index, value)| (word(index), word(value))).collect(),
+		reset_storage,
+	}
+}
+
+fn delete(low: u64) -> Apply<Vec<(H256, H256)>> {
+	Apply::Delete { address: H160::from_low_u64_be(low) }
+}
+
+fn storage(set: &ApplySet, low: u64) -> (Option<Vec<u8>>, BTreeMap<H256, H256>, bool) {
+	match set.get(&H160::from_low_u64_be(low)) {
+		Some(Apply::Modify { code, storage, reset_storage, .. }) => (code.clone(), storage.clone(), *reset_storage),
+		other => panic!("not modified: {:?}", other),
+	}
+}
+
+fn slots(pairs: &[(u64, u64)]) -> BTreeMap<H256, H256> {
+	pairs.iter().map(|(index, value)| (word(*index), word(*value))).collect()
+}
+
+#[test]
+fn later_changes_win() {
+	let mut set = ApplySet::from_applies(vec![modify(A, 1, Some(vec![1]), vec![(0, 1), (1, 1)], false)], Vec::new());
+	set.merge(ApplySet::from_applies(vec![modify(A, 2, None, vec![(1, 2), (2, 2)], false)], Vec::new()));
+
+	assert_eq!(set.len(), 1);
+	assert_eq!(storage(&set, A), (Some(vec![1]), slots(&[(0, 1), (1, 2), (2, 2)]), false));
+	match set.get(&H160::from_low_u64_be(A)) {
+		Some(Apply::Modify { basic, .. }) => assert_eq!(basic.balance, U256::from(2)),
+		other => panic!("not modified: {:?}", other),
+	}
+}
+
+#[test]
+fn storage_resets_and_deletions_discard_earlier_changes() {
+	let set = ApplySet::from_applies(vec![
+		modify(A, 1, Some(vec![1]), vec![(0, 1)], false),
+		modify(A, 1, None, vec![(1, 1)], true),
+		modify(B, 1, Some(vec![1]), vec![(0, 1)], false),
+		delete(B),
+	], Vec::new());
+	assert_eq!(storage(&set, A), (Some(vec![1]), slots(&[(1, 1)]), true));
+	assert_eq!(set.get(&H160::from_low_u64_be(B)), Some(&Apply::Delete { address: H160::from_low_u64_be(B) }));
+
+	// Recreated after deletion, with empty code and storage.
+	let mut set = set;
+	set.push(modify(B, 3, None, vec![(2, 2)], false));
+	assert_eq!(storage(&set, B), (Some(Vec::new()), slots(&[(2, 2)]), true));
+}
+
+#[test]
+fn merged_changes_apply_like_sequential_ones() {
+	let first = vec![modify(A, 1, Some(vec![1]), vec![(0, 1), (1, 1)], false), delete(CALLER)];
+	let second = vec![modify(A, 2, None, vec![(1, 0)], false), modify(CALLER, 5, None, vec![(3, 3)], false)];
+	let log = |low| Log { address: H160::from_low_u64_be(low), topics: Vec::new(), data: Vec::new() };
+	let state = || MemoryBackend::new(Arc::new(vicinity()), vec![
+		(H160::from_low_u64_be(CALLER), account("00")),
+	].into_iter().collect());
+
+	let mut sequential = state();
+	block_on(sequential.apply(first.clone(), vec![log(1)], false)).unwrap();
+	block_on(sequential.apply(second.clone(), vec![log(2)], false)).unwrap();
+
+	let mut set = ApplySet::from_applies(first, vec![log(1)]);
+	set.merge(ApplySet::from_applies(second, vec![log(2)]));
+	let (values, logs) = set.into_applies();
+	let mut merged = state();
+	block_on(merged.apply(values, logs, false)).unwrap();
+
+	assert_eq!(merged.state(), sequential.state());
+	assert_eq!(merged.logs().collect::<Vec<_>>(), sequential.logs().collect::<Vec<_>>());
+}
+
+#[test]
+fn conflicts_are_read_write_intersections() {
+	let execute = |code: &str| {
+		let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity()), vec![
+			(H160::from_low_u64_be(CALLER), account("")),
+			(H160::from_low_u64_be(A), account(code)),
+		].into_iter().collect()));
+		let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+		let (reason, _) = block_on(executor.transact_call(
+			H160::from_low_u64_be(CALLER), H160::from_low_u64_be(A), U256::zero(), Vec::new(), 1_000_000,
+		));
+		assert!(reason.is_succeed(), "{:?}", reason);
+		executor.into_apply_set()
+	};
+	// SLOAD slot 1, and SSTORE 1 at slot 1.
+	let read = execute("60015450");
+	let write = execute("6001600155");
+
+	assert!(read.reads().storage.contains(&(H160::from_low_u64_be(A), word(1))));
+	assert!(write.writes().storage.contains(&(H160::from_low_u64_be(A), word(1))));
+	assert!(read.conflicts_with(&write));
+	assert!(write.conflicts_with(&read));
+
+	// Writes to other accounts, reading other state, do not conflict.
+	let mut reads = AccessSet::default();
+	reads.storage.insert((H160::from_low_u64_be(A), word(2)));
+	let disjoint = ApplySet::from_applies(vec![modify(B, 1, None, vec![(1, 1)], false)], Vec::new()).with_reads(reads);
+	let writer = ApplySet::from_applies(vec![modify(A, 1, None, vec![(1, 1)], false)], Vec::new());
+	assert!(!disjoint.conflicts_with(&writer));
+	assert!(!writer.conflicts_with(&disjoint));
+	assert!(disjoint.conflicts_with(&ApplySet::from_applies(vec![delete(A)], Vec::new())));
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, Engine, ExitError, ExitReason};
+use evm::executor::StackExecutor;
+use primitive_types::U256;
+
+use common::{call_target, deploy};
+
+const ADDMOD: u8 = 0x08;
+const MULMOD: u8 = 0x09;
+const EXP: u8 = 0x0a;
+
+/// Apply `opcode` to `operands`, the first on top of the stack, and return
+/// the result.
+fn eval(opcode: u8, operands: &[U256]) -> U256 {
+	let mut code = String::new();
+	for operand in operands.iter().rev() {
+		let mut word = [0u8; 32];
+		operand.to_big_endian(&mut word);
+		code += &format!("7f{}", hex::encode(word));
+	}
+	code += &format!("{:02x}60005260206000f3", opcode);
+
+	let backend = deploy(&code);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+
+	let (reason, output) = call_target(&mut executor, Vec::new(), 1_000_000);
+	assert!(reason.is_succeed(), "{:?}", reason);
+	U256::from_big_endian(&output)
+}
+
+fn hex(value: &str) -> U256 {
+	U256::from_big_endian(&hex::decode(value).unwrap())
+}
+
+#[test]
+fn addmod() {
+	let max = U256::max_value();
+
+	assert_eq!(eval(ADDMOD, &[max, max, max - 2]), U256::from(4));
+	assert_eq!(eval(ADDMOD, &[max, U256::one(), U256::zero()]), U256::zero());
+	assert_eq!(eval(ADDMOD, &[U256::from(5), U256::from(7), U256::from(3)]), U256::zero());
+}
+
+#[test]
+fn mulmod() {
+	let max = U256::max_value();
+
+	assert_eq!(eval(MULMOD, &[max, max, max - 2]), U256::from(4));
+	assert_eq!(eval(MULMOD, &[U256::from(12), U256::from(13), U256::zero()]), U256::zero());
+	assert_eq!(
+		eval(MULMOD, &[(U256::one() << 127) + 5, (U256::one() << 128) + 9, max]),
+		hex("800000000000000000000000000000098000000000000000000000000000002d"),
+	);
+	assert_eq!(
+		eval(MULMOD, &[(U256::one() << 127) + 5, (U256::one() << 127) + 9, max]),
+		hex("400000000000000000000000000000070000000000000000000000000000002d"),
+	);
+}
+
+#[test]
+fn exp() {
+	let max = U256::max_value();
+
+	assert_eq!(eval(EXP, &[U256::from(2), U256::from(255)]), U256::one() << 255);
+	assert_eq!(eval(EXP, &[U256::from(2), U256::from(256)]), U256::zero());
+	assert_eq!(eval(EXP, &[U256::from(3), max]), hex("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab"));
+	assert_eq!(eval(EXP, &[U256::zero(), U256::zero()]), U256::one());
+	assert_eq!(eval(EXP, &[max, U256::from(3)]), max);
+}
+
+#[test]
+fn shifts_rejected_before_constantinople() {
+	// Byzantium predates EIP-145. Like other opcodes of later forks, the
+	// shifts are priced as invalid and consume all gas.
+	let byzantium = Config {
+		has_bitwise_shifting: false,
+		has_create2: false,
+		has_ext_code_hash: false,
+		has_chain_id: false,
+		has_self_balance: false,
+		..Config::istanbul()
+	};
+
+	for engine in [Engine::Interpreter, Engine::Threaded] {
+		for opcode in [0x1b, 0x1c, 0x1d] {
+			let backend = deploy(&format!("6001600160ff{:02x}00", opcode));
+			let config = Config { engine, ..byzantium.clone() };
+			let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(config));
+
+			let (reason, _) = call_target(&mut executor, Vec::new(), 1_000_000);
+			assert_eq!(reason, ExitReason::Error(ExitError::OutOfGas), "{:?} {:02x}", engine, opcode);
+		}
+	}
+}
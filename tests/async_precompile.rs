@@ -0,0 +1,75 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, Context, ExitSucceed};
+use evm::backend::{Backend, MemoryBackend};
+use evm::executor::{AsyncPrecompile, PrecompileOutput, StackExecutor};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, account, backend, block_on};
+
+const ORACLE: u64 = 0x100;
+const FEED: u64 = 0xfeed;
+
+/// Return the storage of the feed account at the slot given as input.
+struct Oracle;
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl AsyncPrecompile<MemoryBackend> for Oracle {
+	async fn execute(
+		&self,
+		address: H160,
+		input: &[u8],
+		_target_gas: Option<usize>,
+		context: &Context,
+		backend: &MemoryBackend,
+	) -> Option<PrecompileOutput> {
+		if address != H160::from_low_u64_be(ORACLE) {
+			return None
+		}
+		assert_eq!(context.caller, H160::from_low_u64_be(CALLER));
+
+		let slot = H256::from_slice(input);
+		let value = backend.storage(H160::from_low_u64_be(FEED), slot).await.unwrap();
+		Some(Ok((ExitSucceed::Returned, value.as_bytes().to_vec(), 100)))
+	}
+}
+
+fn sync_precompile(address: H160, _input: &[u8], _target_gas: Option<usize>) -> Option<PrecompileOutput> {
+	if address == H160::from_low_u64_be(ORACLE + 1) {
+		Some(Ok((ExitSucceed::Returned, vec![1], 0)))
+	} else {
+		None
+	}
+}
+
+fn call(address: u64, input: Vec<u8>) -> Vec<u8> {
+	let caller = H160::from_low_u64_be(CALLER);
+	let mut feed = account("");
+	feed.storage.insert(H256::from_low_u64_be(1), H256::from_low_u64_be(42));
+	let backend = backend(vec![(caller, account("")), (H160::from_low_u64_be(FEED), feed)]);
+	let mut executor = StackExecutor::new_with_precompile(
+		backend, 100_000, Arc::new(Config::istanbul()), sync_precompile,
+	);
+	executor.set_async_precompile(Arc::new(Oracle));
+
+	let (reason, output) = block_on(executor.transact_call(
+		caller, H160::from_low_u64_be(address), U256::zero(), input, 100_000,
+	));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	output
+}
+
+#[test]
+fn async_precompile_reads_backend() {
+	let output = call(ORACLE, H256::from_low_u64_be(1).as_bytes().to_vec());
+	assert_eq!(output, H256::from_low_u64_be(42).as_bytes().to_vec());
+}
+
+#[test]
+fn sync_precompiles_take_priority() {
+	assert_eq!(call(ORACLE + 1, Vec::new()), vec![1]);
+	assert_eq!(call(0xaa, Vec::new()), Vec::<u8>::new());
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "auth")]
+
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitReason, ExitSucceed, StateQuery};
+use evm::executor::{StackExecutor, auth_message};
+use evm::signing::{secret_address, sign_hash};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, backend, block_on};
+
+const CALLER: u64 = 0xf0;
+const INVOKER: u64 = 0xaa;
+const TARGET: u64 = 0xbb;
+
+fn secret() -> H256 {
+	H256::repeat_byte(0x46)
+}
+
+/// Order of the secp256k1 curve.
+const SECP256K1N: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+
+/// Invoker code: AUTH with the given signature, store the result at slot 1,
+/// then AUTHCALL `TARGET`. With `high_s`, the signature is replaced by its
+/// malleated twin, which recovers the same address.
+fn invoker(authority: H160, nonce: U256, high_s: bool) -> String {
+	let message = auth_message(U256::one(), nonce, H160::from_low_u64_be(INVOKER), H256::zero());
+	let mut signature = sign_hash(&message, &secret()).unwrap();
+	if high_s {
+		signature.s = U256::from_big_endian(&hex::decode(SECP256K1N).unwrap()) - signature.s;
+		signature.y_parity = !signature.y_parity;
+	}
+	let mut r = [0u8; 32];
+	let mut s = [0u8; 32];
+	signature.r.to_big_endian(&mut r);
+	signature.s.to_big_endian(&mut s);
+
+	format!(
+		"60{:02x}600053\
+		7f{}600152\
+		7f{}602152\
+		6061600073{}f6600155\
+		600060006000600060007300000000000000000000000000000000000000bb5af75000",
+		signature.y_parity as u8,
+		hex::encode(r),
+		hex::encode(s),
+		hex::encode(authority),
+	)
+}
+
+fn run(config: Config, nonce: U256) -> (ExitReason, StackExecutor<evm::backend::MemoryBackend>) {
+	run_with(config, nonce, false)
+}
+
+fn run_with(config: Config, nonce: U256, high_s: bool) -> (ExitReason, StackExecutor<evm::backend::MemoryBackend>) {
+	let authority = secret_address(&secret()).unwrap();
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(INVOKER), account(&invoker(authority, nonce, high_s))),
+		(H160::from_low_u64_be(TARGET), account("3360005500")),
+		(authority, account("")),
+	]);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(config));
+
+	let (reason, _) = block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER),
+		H160::from_low_u64_be(INVOKER),
+		U256::zero(),
+		Vec::new(),
+		200_000,
+	));
+	(reason, executor)
+}
+
+fn auth_config() -> Config {
+	Config { has_auth: true, ..Config::istanbul() }
+}
+
+#[test]
+fn authcall_uses_authority_as_caller() {
+	let authority = secret_address(&secret()).unwrap();
+	let (reason, executor) = run(auth_config(), U256::one());
+
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(
+		block_on(executor.storage(H160::from_low_u64_be(INVOKER), H256::from_low_u64_be(1))),
+		H256::from_low_u64_be(1),
+	);
+	assert_eq!(
+		block_on(executor.storage(H160::from_low_u64_be(TARGET), H256::zero())),
+		H256::from(authority),
+	);
+}
+
+#[test]
+fn stale_nonce_is_not_authorized() {
+	let (reason, executor) = run(auth_config(), U256::zero());
+
+	assert!(matches!(reason, ExitReason::Error(_)));
+	assert_eq!(block_on(executor.storage(H160::from_low_u64_be(TARGET), H256::zero())), H256::zero());
+}
+
+#[test]
+fn high_s_is_not_authorized() {
+	let (reason, executor) = run_with(auth_config(), U256::one(), true);
+
+	assert!(matches!(reason, ExitReason::Error(_)));
+	assert_eq!(
+		block_on(executor.storage(H160::from_low_u64_be(INVOKER), H256::from_low_u64_be(1))),
+		H256::zero(),
+	);
+}
+
+#[test]
+fn auth_requires_fork_flag() {
+	let (reason, _) = run(Config::istanbul(), U256::one());
+	assert!(matches!(reason, ExitReason::Error(_)));
+}
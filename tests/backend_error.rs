@@ -0,0 +1,111 @@
+mod common;
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use evm::{Config, ExitFatal, ExitReason};
+use evm::backend::{Backend, Basic, MemoryBackend};
+use evm::executor::{FeeError, StackExecutor};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, account, backend, block_on};
+
+const INNER: u64 = 0xbb;
+const BROKEN_SLOT: u64 = 5;
+
+// SSTORE(0, SLOAD(5)).
+const LOAD_BROKEN: &str = "60055460005500";
+// CALL `INNER`, then SSTORE(1, 1).
+const CALL_INNER: &str = "600060006000600060007300000000000000000000000000000000000000bb5af150\
+	600160015500";
+
+#[derive(Debug, Eq, PartialEq)]
+struct ReadFailed(H160, H256);
+
+/// Memory backend failing reads of one storage slot.
+struct FailingBackend(Arc<MemoryBackend>);
+
+fn ok<T>(value: Result<T, Infallible>) -> Result<T, ReadFailed> {
+	let Ok(value) = value;
+	Ok(value)
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl Backend for FailingBackend {
+	type Error = ReadFailed;
+
+	async fn gas_price(&self) -> Result<U256, ReadFailed> { ok(self.0.gas_price().await) }
+	async fn origin(&self) -> Result<H160, ReadFailed> { ok(self.0.origin().await) }
+	async fn block_hash(&self, number: U256) -> Result<H256, ReadFailed> { ok(self.0.block_hash(number).await) }
+	async fn block_number(&self) -> Result<U256, ReadFailed> { ok(self.0.block_number().await) }
+	async fn block_coinbase(&self) -> Result<H160, ReadFailed> { ok(self.0.block_coinbase().await) }
+	async fn block_timestamp(&self) -> Result<U256, ReadFailed> { ok(self.0.block_timestamp().await) }
+	async fn block_difficulty(&self) -> Result<U256, ReadFailed> { ok(self.0.block_difficulty().await) }
+	async fn block_gas_limit(&self) -> Result<U256, ReadFailed> { ok(self.0.block_gas_limit().await) }
+	async fn chain_id(&self) -> Result<U256, ReadFailed> { ok(self.0.chain_id().await) }
+	async fn exists(&self, address: H160) -> Result<bool, ReadFailed> { ok(self.0.exists(address).await) }
+	async fn basic(&self, address: H160) -> Result<Basic, ReadFailed> { ok(self.0.basic(address).await) }
+	async fn code_hash(&self, address: H160) -> Result<H256, ReadFailed> { ok(self.0.code_hash(address).await) }
+	async fn code_size(&self, address: H160) -> Result<usize, ReadFailed> { ok(self.0.code_size(address).await) }
+	async fn code(&self, address: H160) -> Result<Vec<u8>, ReadFailed> { ok(self.0.code(address).await) }
+	async fn storage(&self, address: H160, index: H256) -> Result<H256, ReadFailed> {
+		if index == H256::from_low_u64_be(BROKEN_SLOT) {
+			return Err(ReadFailed(address, index))
+		}
+		ok(self.0.storage(address, index).await)
+	}
+}
+
+fn executor() -> StackExecutor<FailingBackend> {
+	let state = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(CALL_INNER)),
+		(H160::from_low_u64_be(INNER), account(LOAD_BROKEN)),
+	]);
+	StackExecutor::new(Arc::new(FailingBackend(state)), 1_000_000, Arc::new(Config::istanbul()))
+}
+
+fn call(executor: &mut StackExecutor<FailingBackend>, target: u64) -> ExitReason {
+	block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER),
+		H160::from_low_u64_be(target),
+		U256::zero(),
+		Vec::new(),
+		1_000_000,
+	)).0
+}
+
+#[test]
+fn failed_read_exits_with_backend_error() {
+	let mut executor = executor();
+
+	assert_eq!(call(&mut executor, INNER), ExitReason::Fatal(ExitFatal::BackendError));
+	assert_eq!(
+		executor.take_backend_error(),
+		Some(ReadFailed(H160::from_low_u64_be(INNER), H256::from_low_u64_be(BROKEN_SLOT))),
+	);
+}
+
+#[test]
+fn failed_read_in_subcall_aborts_the_transaction() {
+	let mut executor = executor();
+
+	assert_eq!(call(&mut executor, TARGET), ExitReason::Fatal(ExitFatal::BackendError));
+	assert_eq!(
+		executor.take_backend_error(),
+		Some(ReadFailed(H160::from_low_u64_be(INNER), H256::from_low_u64_be(BROKEN_SLOT))),
+	);
+}
+
+#[test]
+fn settling_fees_reports_the_backend_error() {
+	let mut executor = executor();
+	let caller = H160::from_low_u64_be(CALLER);
+
+	assert_eq!(call(&mut executor, INNER), ExitReason::Fatal(ExitFatal::BackendError));
+	assert_eq!(
+		block_on(executor.settle_fees(caller, U256::one())),
+		Err(FeeError::Backend(ReadFailed(H160::from_low_u64_be(INNER), H256::from_low_u64_be(BROKEN_SLOT)))),
+	);
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Capture, Config, Context, Runtime};
+use evm::executor::BackendHandler;
+use evm::backend::Apply;
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, account, backend, block_on};
+
+// Store the first input word at slot 0.
+const STORE: &str = "60003560005500";
+// Return the word at slot 0.
+const LOAD: &str = "60005460005260206000f3";
+// Store 2 at slot 0, then revert.
+const STORE_AND_REVERT: &str = "600260005560006000fd";
+
+fn context() -> Context {
+	Context {
+		address: H160::from_low_u64_be(TARGET),
+		caller: H160::from_low_u64_be(CALLER),
+		apparent_value: U256::zero(),
+	}
+}
+
+fn code(hex: &str) -> Vec<u8> {
+	hex::decode(hex).unwrap()
+}
+
+#[test]
+fn runs_bytecode_against_state() {
+	let backend = backend(vec![(H160::from_low_u64_be(TARGET), account(""))]);
+	let mut handler = BackendHandler::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+
+	let value = H256::from_low_u64_be(7);
+	let (reason, _) = block_on(handler.run(code(STORE), value.as_bytes().to_vec(), context()));
+	assert!(reason.is_succeed(), "{:?}", reason);
+
+	let (reason, _) = block_on(handler.run(code(STORE_AND_REVERT), Vec::new(), context()));
+	assert!(matches!(reason, evm::ExitReason::Revert(_)), "{:?}", reason);
+
+	let (reason, output) = block_on(handler.run(code(LOAD), Vec::new(), context()));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(output, value.as_bytes().to_vec());
+	assert!(handler.gas() < 1_000_000 - 20_000);
+
+	let (applies, _) = handler.into_executor().deconstruct();
+	let storage = applies.into_iter().find_map(|apply| match apply {
+		Apply::Modify { address, storage, .. } if address == H160::from_low_u64_be(TARGET) =>
+			Some(storage.into_iter().collect::<Vec<_>>()),
+		_ => None,
+	});
+	assert_eq!(storage, Some(vec![(H256::zero(), value)]));
+}
+
+#[test]
+fn drives_a_runtime() {
+	let value = H256::from_low_u64_be(7);
+	let mut target = account(LOAD);
+	target.storage.insert(H256::zero(), value);
+	let backend = backend(vec![(H160::from_low_u64_be(TARGET), target)]);
+	let config = Arc::new(Config::istanbul());
+	let mut handler = BackendHandler::new(backend, 1_000_000, config.clone());
+
+	let mut runtime = Runtime::new(Arc::new(code(LOAD)), Vec::new().into(), context(), config);
+	let reason = match block_on(runtime.run(&mut handler)) {
+		Capture::Exit(reason) => reason,
+		Capture::Trap(_) => panic!("unexpected trap"),
+	};
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(runtime.machine().return_value(), value.as_bytes().to_vec());
+	assert!(handler.gas() < 1_000_000);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError, ExitReason, StateQuery};
+use evm::backend::{MemoryBackend, MemoryVicinity};
+use evm::executor::{DefaultFeePolicy, StackExecutor, Transaction, TransactionAction, TxValidationError};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, account, block_on, vicinity};
+
+const COINBASE: u64 = 0xcc;
+const GAS_PRICE: u64 = 10;
+
+fn executor(config: Config, gas_price: u64) -> StackExecutor<MemoryBackend> {
+	let vicinity = MemoryVicinity {
+		gas_price: U256::from(gas_price),
+		block_coinbase: H160::from_low_u64_be(COINBASE),
+		..vicinity()
+	};
+	let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity), vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account("00")),
+	].into_iter().collect()));
+	StackExecutor::new(backend, 10_000_000, Arc::new(config))
+}
+
+fn call(value: u64, gas_limit: usize) -> Transaction {
+	Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action: TransactionAction::Call(H160::from_low_u64_be(TARGET)),
+		value: U256::from(value),
+		data: Vec::new(),
+		gas_limit,
+		nonce: None,
+	}
+}
+
+fn balance(executor: &StackExecutor<MemoryBackend>, low: u64) -> U256 {
+	block_on(executor.balance(H160::from_low_u64_be(low)))
+}
+
+#[test]
+fn unaffordable_transactions_are_rejected_upfront() {
+	let mut executor = executor(Config::istanbul(), GAS_PRICE);
+	// The value is affordable, but not together with the gas limit.
+	let result = block_on(executor.transact(call(1, 100_000_000)));
+	assert_eq!(result.reason, ExitReason::Error(ExitError::OutOfFund));
+	assert_eq!(result.validation_error, Some(TxValidationError::InsufficientFunds {
+		have: U256::from(1_000_000_000u64),
+		need: U256::from(1_000_000_001u64),
+	}));
+	assert_eq!(result.gas_used, 0);
+	assert_eq!(balance(&executor, CALLER), U256::from(1_000_000_000u64));
+}
+
+#[test]
+fn used_gas_is_charged_and_paid_to_the_coinbase() {
+	let mut executor = executor(Config::istanbul(), GAS_PRICE);
+	let result = block_on(executor.transact(call(0, 100_000)));
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert_eq!(result.validation_error, None);
+	assert_eq!(result.gas_used, 21_000);
+
+	let fee = U256::from(21_000 * GAS_PRICE);
+	assert_eq!(balance(&executor, CALLER), U256::from(1_000_000_000u64) - fee);
+	assert_eq!(balance(&executor, COINBASE), fee);
+}
+
+#[test]
+fn gas_prices_below_the_base_fee_are_rejected() {
+	let mut executor = executor(Config::istanbul(), GAS_PRICE);
+	executor.set_fee_policy(Arc::new(DefaultFeePolicy { base_fee: U256::from(GAS_PRICE + 1) }));
+	let result = block_on(executor.transact(call(0, 100_000)));
+	assert_eq!(result.reason, ExitReason::Error(ExitError::GasPriceBelowBaseFee));
+	assert_eq!(result.validation_error, Some(TxValidationError::FeeCapTooLow {
+		gas_price: U256::from(GAS_PRICE),
+		base_fee: U256::from(GAS_PRICE + 1),
+	}));
+
+	// The base fee is burned, and the remaining priority fee paid.
+	executor.set_fee_policy(Arc::new(DefaultFeePolicy { base_fee: U256::from(4) }));
+	let result = block_on(executor.transact(call(0, 100_000)));
+	assert!(result.is_succeed());
+	assert_eq!(result.burned, U256::from(21_000 * 4));
+	assert_eq!(balance(&executor, CALLER), U256::from(1_000_000_000u64 - 21_000 * GAS_PRICE));
+	assert_eq!(balance(&executor, COINBASE), U256::from(21_000 * (GAS_PRICE - 4)));
+}
+
+#[test]
+fn simulations_skip_fees() {
+	let mut config = Config::istanbul();
+	config.disable_balance_check = true;
+	config.disable_base_fee = true;
+	let mut executor = executor(config, GAS_PRICE);
+	executor.set_fee_policy(Arc::new(DefaultFeePolicy { base_fee: U256::from(GAS_PRICE + 1) }));
+
+	let result = block_on(executor.transact(call(0, 100_000_000)));
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert_eq!(balance(&executor, CALLER), U256::from(1_000_000_000u64));
+	assert_eq!(balance(&executor, COINBASE), U256::zero());
+}
+
+#[test]
+fn gas_limits_below_the_intrinsic_gas_are_rejected() {
+	let mut executor = executor(Config::istanbul(), GAS_PRICE);
+	let result = block_on(executor.transact(call(0, 20_999)));
+	assert_eq!(result.reason, ExitReason::Error(ExitError::OutOfGas));
+	assert_eq!(result.validation_error, Some(TxValidationError::GasLimitTooLow { needed: 21_000 }));
+	assert_eq!(result.gas_used, 0);
+	assert_eq!(balance(&executor, CALLER), U256::from(1_000_000_000u64));
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitReason};
+use evm::backend::{MemoryBackend, MemoryVicinity};
+use evm::executor::{
+	BEACON_ROOTS_ADDRESS, BEACON_ROOTS_HISTORY_LENGTH, BeaconRoots, Block,
+	BlockExecutor, StackExecutor,
+};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, account, block_on, vicinity};
+
+const TIMESTAMP: u64 = 1_700_000_000;
+
+// `evm::executor::BEACON_ROOTS_CODE` with `PUSH0` replaced by `PUSH1 0` and jump targets
+// moved accordingly.
+const BEACON_ROOTS_CODE_PUSH1: &str = concat!(
+	"3373fffffffffffffffffffffffffffffffffffffffe146056576020361460265760006000fd5b6000",
+	"35801560505762001fff81069081541460415760006000fd5b62001fff015460005260206000f35b60",
+	"006000fd5b62001fff42064281556000359062001fff015500",
+);
+
+fn backend() -> MemoryBackend {
+	MemoryBackend::new(
+		Arc::new(MemoryVicinity { block_timestamp: U256::from(TIMESTAMP), ..vicinity() }),
+		vec![
+			(H160::from_low_u64_be(CALLER), account("")),
+			(BEACON_ROOTS_ADDRESS, account(BEACON_ROOTS_CODE_PUSH1)),
+		].into_iter().collect(),
+	)
+}
+
+fn root() -> H256 {
+	H256::repeat_byte(0xbe)
+}
+
+fn execute(parent_beacon_block_root: Option<H256>) -> MemoryBackend {
+	let mut executor = BlockExecutor::new(backend(), Arc::new(Config::istanbul()))
+		.with_hook(Arc::new(BeaconRoots));
+	let result = block_on(executor.execute_block(&Block {
+		parent_beacon_block_root,
+		..Block::default()
+	})).unwrap();
+	for call in &result.system_calls {
+		assert!(call.reason.is_succeed(), "{:?}", call.reason);
+	}
+	executor.into_backend()
+}
+
+fn get(backend: MemoryBackend, timestamp: u64) -> (ExitReason, Vec<u8>) {
+	let mut executor = StackExecutor::new(Arc::new(backend), 100_000, Arc::new(Config::istanbul()));
+	block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER),
+		BEACON_ROOTS_ADDRESS,
+		U256::zero(),
+		H256::from_low_u64_be(timestamp).as_bytes().to_vec(),
+		100_000,
+	))
+}
+
+#[test]
+fn stores_parent_beacon_block_root() {
+	let backend = execute(Some(root()));
+	let storage = &backend.state()[&BEACON_ROOTS_ADDRESS].storage;
+	let index = TIMESTAMP % BEACON_ROOTS_HISTORY_LENGTH;
+	assert_eq!(storage[&H256::from_low_u64_be(index)], H256::from_low_u64_be(TIMESTAMP));
+	assert_eq!(storage[&H256::from_low_u64_be(index + BEACON_ROOTS_HISTORY_LENGTH)], root());
+}
+
+#[test]
+fn time_oracle_reads_root() {
+	let (reason, output) = get(execute(Some(root())), TIMESTAMP);
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(output, root().as_bytes());
+
+	let (reason, _) = get(execute(Some(root())), TIMESTAMP - 1);
+	assert!(matches!(reason, ExitReason::Revert(_)), "{:?}", reason);
+}
+
+#[test]
+fn no_call_without_root() {
+	let backend = execute(None);
+	assert!(backend.state()[&BEACON_ROOTS_ADDRESS].storage.is_empty());
+	let (reason, _) = get(backend, TIMESTAMP);
+	assert!(matches!(reason, ExitReason::Revert(_)), "{:?}", reason);
+}
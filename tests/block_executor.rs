@@ -0,0 +1,136 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::MemoryBackend;
+use evm::executor::{
+	Block, BlockExecutor, BlockHook, SYSTEM_ADDRESS, SystemOperation, Transaction,
+	TransactionAction, Withdrawal, WithdrawalCredits,
+};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, account, backend, block_on};
+
+const RECORDER: u64 = 0x1000;
+const WRITER: u64 = 0x2000;
+const REVERTER: u64 = 0x3000;
+const RECIPIENT: u64 = 0x4000;
+
+// Store the first word of call data at slot 0 and the caller at slot 1.
+const RECORD: &str = "6000356000553360015500";
+// Store 42 at slot 0.
+const WRITE: &str = "602a60005500";
+// Revert.
+const REVERT: &str = "60006000fd";
+
+/// Call the recorder with 1 before and 2 after the transactions, and the
+/// reverter after them.
+struct Recording;
+
+fn record(value: u64) -> SystemOperation {
+	SystemOperation::Call {
+		address: H160::from_low_u64_be(RECORDER),
+		data: H256::from_low_u64_be(value).as_bytes().to_vec(),
+	}
+}
+
+impl BlockHook for Recording {
+	fn pre_transactions(&self, _block: &Block) -> Vec<SystemOperation> {
+		vec![record(1)]
+	}
+
+	fn post_transactions(&self, _block: &Block) -> Vec<SystemOperation> {
+		vec![record(2), SystemOperation::Call {
+			address: H160::from_low_u64_be(REVERTER),
+			data: Vec::new(),
+		}]
+	}
+}
+
+fn executor() -> BlockExecutor<MemoryBackend> {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(RECORDER), account(RECORD)),
+		(H160::from_low_u64_be(WRITER), account(WRITE)),
+		(H160::from_low_u64_be(REVERTER), account(REVERT)),
+	]);
+	let backend = Arc::try_unwrap(backend).ok().unwrap();
+	BlockExecutor::new(backend, Arc::new(Config::istanbul()))
+}
+
+fn block() -> Block {
+	Block {
+		transactions: vec![Transaction {
+			caller: H160::from_low_u64_be(CALLER),
+			action: TransactionAction::Call(H160::from_low_u64_be(WRITER)),
+			value: U256::zero(),
+			data: Vec::new(),
+			gas_limit: 100_000,
+			nonce: None,
+		}],
+		withdrawals: vec![Withdrawal {
+			index: 0,
+			validator_index: 7,
+			address: H160::from_low_u64_be(RECIPIENT),
+			amount: 32,
+		}],
+		parent_beacon_block_root: None,
+		parent_hash: None,
+	}
+}
+
+fn storage(backend: &MemoryBackend, address: u64, index: u64) -> H256 {
+	backend.state()[&H160::from_low_u64_be(address)].storage
+		.get(&H256::from_low_u64_be(index))
+		.copied()
+		.unwrap_or_default()
+}
+
+#[test]
+fn system_calls_around_transactions() {
+	let mut executor = executor().with_hook(Arc::new(Recording));
+	let result = block_on(executor.execute_block(&block())).unwrap();
+
+	assert_eq!(result.results.len(), 1);
+	assert!(result.results[0].reason.is_succeed());
+	assert_eq!(result.cumulative_gas, result.results[0].used_gas);
+
+	let calls = result.system_calls.iter()
+		.map(|call| (call.address.to_low_u64_be(), call.reason.is_succeed()))
+		.collect::<Vec<_>>();
+	assert_eq!(calls, vec![(RECORDER, true), (RECORDER, true), (REVERTER, false)]);
+
+	let backend = executor.into_backend();
+	assert_eq!(storage(&backend, RECORDER, 0), H256::from_low_u64_be(2));
+	assert_eq!(storage(&backend, RECORDER, 1), H256::from(SYSTEM_ADDRESS));
+	assert_eq!(storage(&backend, WRITER, 0), H256::from_low_u64_be(42));
+	assert!(!backend.state().contains_key(&SYSTEM_ADDRESS));
+	assert_eq!(backend.state()[&H160::from_low_u64_be(CALLER)].nonce, U256::from(2));
+}
+
+#[test]
+fn withdrawals_credited() {
+	let mut executor = executor().with_hook(Arc::new(WithdrawalCredits));
+	let result = block_on(executor.execute_block(&block())).unwrap();
+	assert!(result.system_calls.is_empty());
+
+	let backend = executor.into_backend();
+	let recipient = &backend.state()[&H160::from_low_u64_be(RECIPIENT)];
+	assert_eq!(recipient.balance, U256::from(32) * U256::exp10(9));
+}
+
+#[test]
+fn system_call_to_empty_account() {
+	struct Empty;
+	impl BlockHook for Empty {
+		fn pre_transactions(&self, _block: &Block) -> Vec<SystemOperation> {
+			vec![SystemOperation::Call { address: H160::from_low_u64_be(0xdead), data: Vec::new() }]
+		}
+	}
+
+	let mut executor = executor().with_hook(Arc::new(Empty));
+	let result = block_on(executor.execute_block(&Block::default())).unwrap();
+	assert!(result.system_calls[0].reason.is_succeed());
+	assert!(!executor.backend().state().contains_key(&H160::from_low_u64_be(0xdead)));
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::{Config, ExitReason};
+use evm::backend::{MemoryBackend, MemoryVicinity};
+use evm::executor::{
+	HISTORY_SERVE_WINDOW, HISTORY_STORAGE_ADDRESS, HISTORY_STORAGE_CODE, Block, BlockExecutor,
+	BlockHashHistory, StackExecutor,
+};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, account, block_on, prague_eips, shanghai_eips, vicinity};
+
+const READER: u64 = 0xaa;
+const NUMBER: u64 = 10_000;
+
+// Return the `BLOCKHASH` of the number in the first call data word.
+const READ: &str = "6000354060005260206000f3";
+
+fn backend(block_hashes: Vec<H256>, storage: BTreeMap<H256, H256>) -> MemoryBackend {
+	let mut history = account("");
+	history.code = HISTORY_STORAGE_CODE.to_vec().into();
+	history.storage = storage;
+	MemoryBackend::new(
+		Arc::new(MemoryVicinity { block_number: U256::from(NUMBER), block_hashes, ..vicinity() }),
+		vec![
+			(H160::from_low_u64_be(CALLER), account("")),
+			(H160::from_low_u64_be(READER), account(READ)),
+			(HISTORY_STORAGE_ADDRESS, history),
+		].into_iter().collect(),
+	)
+}
+
+fn parent_hash() -> H256 {
+	H256::repeat_byte(0xab)
+}
+
+fn execute(backend: MemoryBackend, parent_hash: Option<H256>) -> MemoryBackend {
+	let mut executor = BlockExecutor::new(backend, Arc::new(prague_eips()))
+		.with_hook(Arc::new(BlockHashHistory));
+	let result = block_on(executor.execute_block(&Block { parent_hash, ..Block::default() })).unwrap();
+	for call in &result.system_calls {
+		assert!(call.reason.is_succeed(), "{:?}", call.reason);
+	}
+	executor.into_backend()
+}
+
+fn call(backend: &MemoryBackend, config: Config, address: H160, number: u64) -> (ExitReason, Vec<u8>) {
+	let mut executor = StackExecutor::new(Arc::new(backend.clone()), 100_000, Arc::new(config));
+	block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER),
+		address,
+		U256::zero(),
+		H256::from_low_u64_be(number).as_bytes().to_vec(),
+		100_000,
+	))
+}
+
+fn block_hash(backend: &MemoryBackend, config: Config, number: u64) -> H256 {
+	let (reason, output) = call(backend, config, H160::from_low_u64_be(READER), number);
+	assert!(reason.is_succeed(), "{:?}", reason);
+	H256::from_slice(&output)
+}
+
+#[test]
+fn stores_parent_hash() {
+	let backend = execute(backend(Vec::new(), BTreeMap::new()), Some(parent_hash()));
+	let storage = &backend.state()[&HISTORY_STORAGE_ADDRESS].storage;
+	assert_eq!(storage[&H256::from_low_u64_be((NUMBER - 1) % HISTORY_SERVE_WINDOW)], parent_hash());
+
+	let (reason, output) = call(&backend, prague_eips(), HISTORY_STORAGE_ADDRESS, NUMBER - 1);
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(output, parent_hash().as_bytes());
+	let (reason, _) = call(&backend, prague_eips(), HISTORY_STORAGE_ADDRESS, NUMBER);
+	assert!(matches!(reason, ExitReason::Revert(_)), "{:?}", reason);
+
+	assert!(execute(backend.clone(), None).state()[&HISTORY_STORAGE_ADDRESS].storage.len() == 1);
+}
+
+#[test]
+fn block_hash_reads_history() {
+	let old = H256::repeat_byte(0x01);
+	let slot = H256::from_low_u64_be((NUMBER - 1_000) % HISTORY_SERVE_WINDOW);
+	let backend = execute(backend(Vec::new(), BTreeMap::from([(slot, old)])), Some(parent_hash()));
+
+	assert_eq!(block_hash(&backend, prague_eips(), NUMBER - 1), parent_hash());
+	// Beyond the 256 blocks served by backends.
+	assert_eq!(block_hash(&backend, prague_eips(), NUMBER - 1_000), old);
+	// The slot of the parent, reused by the block a window earlier.
+	assert_eq!(block_hash(&backend, prague_eips(), NUMBER - 1 - HISTORY_SERVE_WINDOW), H256::zero());
+	assert_eq!(block_hash(&backend, prague_eips(), NUMBER), H256::zero());
+
+	// Older forks use the backend.
+	assert_eq!(block_hash(&backend, shanghai_eips(), NUMBER - 1), H256::zero());
+}
+
+#[test]
+fn empty_slots_fall_back_to_backend() {
+	let hash = H256::repeat_byte(0xcd);
+	let backend = backend(vec![hash], BTreeMap::new());
+	assert_eq!(block_hash(&backend, prague_eips(), NUMBER - 1), hash);
+	assert_eq!(block_hash(&backend, shanghai_eips(), NUMBER - 1), hash);
+}
+
+#[test]
+fn history_reads_are_not_accesses() {
+	let backend = execute(backend(Vec::new(), BTreeMap::new()), Some(parent_hash()));
+	let mut executor = StackExecutor::new(Arc::new(backend), 100_000, Arc::new(prague_eips()));
+	let (reason, output) = block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER),
+		H160::from_low_u64_be(READER),
+		U256::zero(),
+		H256::from_low_u64_be(NUMBER - 1).as_bytes().to_vec(),
+		100_000,
+	));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(output, parent_hash().as_bytes());
+
+	// The slot is read by the protocol, not by the reader.
+	let slot = (HISTORY_STORAGE_ADDRESS, H256::from_low_u64_be((NUMBER - 1) % HISTORY_SERVE_WINDOW));
+	assert!(!executor.accessed().storage.contains(&slot));
+	assert!(!executor.read_write_set().reads.storage.contains(&slot));
+}
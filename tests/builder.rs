@@ -0,0 +1,124 @@
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use evm::{Config, Context, ExitReason, ExitSucceed, ExternalOpcode, Opcode};
+use evm::backend::MemoryBackend;
+use evm::executor::{AsyncPrecompile, CodeOverrides, DefaultFeePolicy, ExecutorBuilder, FeeDistribution, FeePolicy,
+	GasAction, GasHook, PrecompileOutput, StackExecutor, Transaction};
+use primitive_types::{H160, H256, U256};
+
+use common::{TARGET, call_target, deploy};
+
+const PRECOMPILE: u64 = 0x99;
+
+// Return 1 as a word.
+const RETURN_ONE: &str = "600160005260206000f3";
+// CALL `PRECOMPILE` with all gas, and return the first word of its output.
+const CALL_PRECOMPILE: &str = "6020600060006000600060995af15060206000f3";
+
+/// Return 7 as a word at `PRECOMPILE`, for 50 gas.
+fn precompile(address: H160, _input: &[u8], _target_gas: Option<usize>) -> Option<PrecompileOutput> {
+	(address == H160::from_low_u64_be(PRECOMPILE))
+		.then(|| Ok((ExitSucceed::Returned, H256::from_low_u64_be(7).as_bytes().to_vec(), 50)))
+}
+
+/// Return 8 as a word at `PRECOMPILE`, for 50 gas.
+struct Eight;
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl AsyncPrecompile<MemoryBackend> for Eight {
+	async fn execute(
+		&self,
+		address: H160,
+		_input: &[u8],
+		_target_gas: Option<usize>,
+		_context: &Context,
+		_backend: &MemoryBackend,
+	) -> Option<PrecompileOutput> {
+		(address == H160::from_low_u64_be(PRECOMPILE))
+			.then(|| Ok((ExitSucceed::Returned, H256::from_low_u64_be(8).as_bytes().to_vec(), 50)))
+	}
+}
+
+/// Count the charged instructions.
+struct Counter(Arc<AtomicUsize>);
+
+impl GasHook for Counter {
+	fn after_charge(&self, _opcode: Result<Opcode, ExternalOpcode>, _cost: usize, _remaining: usize) -> GasAction {
+		self.0.fetch_add(1, Ordering::Relaxed);
+		GasAction::Continue
+	}
+}
+
+/// Flat intrinsic gas.
+struct FlatPolicy;
+
+impl FeePolicy for FlatPolicy {
+	fn intrinsic_gas(&self, _transaction: &Transaction, _config: &Config) -> usize {
+		1_000
+	}
+
+	fn refund(&self, used_gas: usize, refunded_gas: isize) -> usize {
+		DefaultFeePolicy::default().refund(used_gas, refunded_gas)
+	}
+
+	fn distribute(&self, used_gas: usize, gas_price: U256, coinbase: H160) -> FeeDistribution {
+		DefaultFeePolicy::default().distribute(used_gas, gas_price, coinbase)
+	}
+}
+
+fn builder() -> ExecutorBuilder<MemoryBackend> {
+	let backend = deploy(RETURN_ONE);
+	ExecutorBuilder::new(backend, 1_000_000, Arc::new(Config::istanbul()))
+}
+
+fn call(mut executor: StackExecutor<MemoryBackend>) -> (U256, usize) {
+	let (reason, output) = call_target(&mut executor, Vec::new(), 100_000);
+	assert!(matches!(reason, ExitReason::Succeed(_)), "{:?}", reason);
+	(U256::from_big_endian(&output), executor.used_gas())
+}
+
+#[test]
+fn unset_layers_build_a_default_executor() {
+	let (output, used_gas) = call(builder().build());
+
+	assert_eq!(output, U256::one());
+	assert_eq!(used_gas, 21_000 + 18);
+}
+
+#[test]
+fn layers_are_installed() {
+	let charged = Arc::new(AtomicUsize::new(0));
+	let overrides = CodeOverrides::new()
+		.with_code(H160::from_low_u64_be(TARGET), hex::decode(CALL_PRECOMPILE).unwrap());
+	let executor = builder()
+		.gas_limit(500_000)
+		.config(Arc::new(Config::istanbul()))
+		.precompile(precompile)
+		.inspector(Counter(charged.clone()))
+		.fee_policy(FlatPolicy)
+		.overrides(overrides)
+		.build();
+
+	let (output, used_gas) = call(executor);
+	assert_eq!(output, U256::from(7));
+	assert!(used_gas > 1_000 && used_gas < 21_000, "{}", used_gas);
+	// Every instruction of the replacement code is charged once.
+	assert_eq!(charged.load(Ordering::Relaxed), 12);
+}
+
+#[test]
+fn async_precompiles_are_installed() {
+	let overrides = CodeOverrides::new()
+		.with_code(H160::from_low_u64_be(TARGET), hex::decode(CALL_PRECOMPILE).unwrap());
+	let executor = builder()
+		.async_precompile(Eight)
+		.overrides(overrides)
+		.build();
+
+	let (output, _) = call(executor);
+	assert_eq!(output, U256::from(8));
+}
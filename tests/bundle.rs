@@ -0,0 +1,55 @@
+mod common;
+
+use evm::{Config, ExitReason, ExitSucceed};
+use evm::backend::{Apply, Backend, BlockOverrides};
+use evm::executor::{Transaction, TransactionAction, simulate_bundle};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, account, backend, block_on};
+
+const COUNTER: u64 = 0xaa;
+
+// Increment slot 0, and store COINBASE at slot 1.
+const INCREMENT: &str = "6000546001016000554160015500";
+
+#[test]
+fn transactions_see_previous_changes() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let counter = H160::from_low_u64_be(COUNTER);
+	let backend = backend(vec![(caller, account("")), (counter, account(INCREMENT))]);
+	let transaction = Transaction {
+		caller,
+		action: TransactionAction::Call(counter),
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: 100_000,
+		nonce: None,
+	};
+	let coinbase = H160::repeat_byte(0xcb);
+
+	let Ok(result) = block_on(simulate_bundle(
+		backend.clone(),
+		&Config::istanbul(),
+		vec![transaction.clone(), transaction],
+		BlockOverrides { coinbase: Some(coinbase), ..Default::default() },
+	));
+
+	assert_eq!(result.results.len(), 2);
+	for tx in &result.results {
+		assert_eq!(tx.reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	}
+	assert_eq!(
+		result.cumulative_gas,
+		result.results.iter().map(|tx| tx.used_gas).sum::<usize>(),
+	);
+
+	let storage = result.diff.iter().find_map(|apply| match apply {
+		Apply::Modify { address, storage, .. } if *address == counter => Some(storage.clone()),
+		_ => None,
+	}).unwrap();
+	assert_eq!(storage[&H256::zero()], H256::from_low_u64_be(2));
+	assert_eq!(storage[&H256::from_low_u64_be(1)], H256::from(coinbase));
+
+	// Nothing is committed to the backend.
+	assert_eq!(block_on(backend.storage(counter, H256::zero())), Ok(H256::zero()));
+}
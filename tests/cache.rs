@@ -0,0 +1,183 @@
+mod common;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+use std::thread;
+
+use evm::{Config, ExitReason, ExitSucceed};
+use evm::backend::{Backend, Basic, CachedBackend, MemoryBackend, warmup};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, account, backend, block_on, call_target};
+
+// Return SLOAD(0).
+const LOAD: &str = "60005460005260206000f3";
+
+#[test]
+fn executors_share_a_warmed_cache() {
+	let mut target = account(LOAD);
+	target.storage.insert(H256::zero(), H256::from_low_u64_be(42));
+	let cache = Arc::new(CachedBackend::new(backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), target),
+	])));
+	assert!(cache.is_empty());
+
+	let handles = (0..4).map(|_| {
+		let cache = cache.clone();
+		thread::spawn(move || {
+			let mut executor = StackExecutor::new(cache, 1_000_000, Arc::new(Config::istanbul()));
+			call_target(&mut executor, Vec::new(), 1_000_000)
+		})
+	}).collect::<Vec<_>>();
+
+	for handle in handles {
+		let (reason, out) = handle.join().unwrap();
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+		assert_eq!(H256::from_slice(&out), H256::from_low_u64_be(42));
+	}
+	assert_eq!(cache.len(), 2);
+	assert_eq!(block_on(cache.storage(H160::from_low_u64_be(TARGET), H256::zero())), Ok(H256::from_low_u64_be(42)));
+
+	cache.clear();
+	assert!(cache.is_empty());
+}
+
+#[cfg(not(feature = "no-send"))]
+#[test]
+fn executor_answers_concurrent_queries() {
+	use evm::StateQuery;
+
+	let caller = H160::from_low_u64_be(CALLER);
+	let target = H160::from_low_u64_be(TARGET);
+	let mut target_account = account(LOAD);
+	target_account.storage.insert(H256::zero(), H256::from_low_u64_be(42));
+	let cache = Arc::new(CachedBackend::new(backend(vec![
+		(caller, account("")),
+		(target, target_account),
+	])));
+	let executor = StackExecutor::new(cache.clone(), 1_000_000, Arc::new(Config::istanbul()));
+
+	// Queries only borrow the executor.
+	let executor = &executor;
+	thread::scope(|scope| {
+		let handles = (0..4).map(|_| scope.spawn(move || (
+			block_on(executor.storage(target, H256::zero())),
+			block_on(executor.balance(caller)),
+		))).collect::<Vec<_>>();
+		for handle in handles {
+			assert_eq!(handle.join().unwrap(), (H256::from_low_u64_be(42), U256::from(1_000_000_000u64)));
+		}
+	});
+	assert_eq!(cache.len(), 2);
+}
+
+/// Future pending once, as a read waiting on the network would.
+struct Latency(bool);
+
+impl Future for Latency {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+		if self.0 {
+			return Poll::Ready(())
+		}
+		self.0 = true;
+		context.waker().wake_by_ref();
+		Poll::Pending
+	}
+}
+
+/// Backend with latency on account reads, counting them and the most
+/// reads in flight at once.
+struct SlowBackend {
+	inner: Arc<MemoryBackend>,
+	reads: AtomicUsize,
+	in_flight: AtomicUsize,
+	max_in_flight: AtomicUsize,
+}
+
+impl SlowBackend {
+	async fn read<T>(&self, value: T) -> T {
+		self.reads.fetch_add(1, Ordering::SeqCst);
+		let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+		self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+		Latency(false).await;
+		self.in_flight.fetch_sub(1, Ordering::SeqCst);
+		value
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl Backend for SlowBackend {
+	type Error = std::convert::Infallible;
+
+	async fn gas_price(&self) -> Result<U256, Self::Error> { self.inner.gas_price().await }
+	async fn origin(&self) -> Result<H160, Self::Error> { self.inner.origin().await }
+	async fn block_hash(&self, number: U256) -> Result<H256, Self::Error> { self.inner.block_hash(number).await }
+	async fn block_number(&self) -> Result<U256, Self::Error> { self.inner.block_number().await }
+	async fn block_coinbase(&self) -> Result<H160, Self::Error> { self.inner.block_coinbase().await }
+	async fn block_timestamp(&self) -> Result<U256, Self::Error> { self.inner.block_timestamp().await }
+	async fn block_difficulty(&self) -> Result<U256, Self::Error> { self.inner.block_difficulty().await }
+	async fn block_gas_limit(&self) -> Result<U256, Self::Error> { self.inner.block_gas_limit().await }
+	async fn chain_id(&self) -> Result<U256, Self::Error> { self.inner.chain_id().await }
+	async fn exists(&self, address: H160) -> Result<bool, Self::Error> {
+		self.read(self.inner.exists(address).await).await
+	}
+	async fn basic(&self, address: H160) -> Result<Basic, Self::Error> {
+		self.read(self.inner.basic(address).await).await
+	}
+	async fn code_hash(&self, address: H160) -> Result<H256, Self::Error> {
+		self.read(self.inner.code_hash(address).await).await
+	}
+	async fn code_size(&self, address: H160) -> Result<usize, Self::Error> {
+		self.read(self.inner.code_size(address).await).await
+	}
+	async fn code(&self, address: H160) -> Result<Vec<u8>, Self::Error> {
+		self.read(self.inner.code(address).await).await
+	}
+	async fn storage(&self, address: H160, index: H256) -> Result<H256, Self::Error> {
+		self.read(self.inner.storage(address, index).await).await
+	}
+}
+
+#[test]
+fn warmup_preloads_access_list_concurrently() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let target = H160::from_low_u64_be(TARGET);
+	let mut target_account = account(LOAD);
+	target_account.storage.insert(H256::zero(), H256::from_low_u64_be(42));
+	let slow = Arc::new(SlowBackend {
+		inner: backend(vec![(caller, account("")), (target, target_account)]),
+		reads: AtomicUsize::new(0),
+		in_flight: AtomicUsize::new(0),
+		max_in_flight: AtomicUsize::new(0),
+	});
+	let cache = Arc::new(CachedBackend::new(slow.clone()));
+
+	let access_list = vec![
+		(caller, Vec::new()),
+		(target, vec![H256::zero(), H256::from_low_u64_be(1)]),
+		(target, vec![H256::zero()]),
+	];
+	block_on(warmup(&cache, &access_list, 3)).unwrap();
+
+	// Four account reads for each address, and two slots.
+	assert_eq!(slow.reads.load(Ordering::SeqCst), 10);
+	assert_eq!(slow.max_in_flight.load(Ordering::SeqCst), 3);
+
+	let mut executor = StackExecutor::new(cache.clone(), 1_000_000, Arc::new(Config::istanbul()));
+	let (reason, out) = call_target(&mut executor, Vec::new(), 1_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(H256::from_slice(&out), H256::from_low_u64_be(42));
+	assert_eq!(slow.reads.load(Ordering::SeqCst), 10);
+
+	// Warming up cached values reads nothing.
+	block_on(warmup(&cache, &access_list, 1)).unwrap();
+	assert_eq!(slow.reads.load(Ordering::SeqCst), 10);
+}
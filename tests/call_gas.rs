@@ -0,0 +1,79 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitReason, ExitRevert, ExitSucceed};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, account, backend, block_on, call_target};
+
+const LIBRARY: u64 = 0xcc;
+
+// CALL `LIBRARY` with 10000 gas and stop.
+const CALL: &str = "600060006000600060007300000000000000000000000000000000000000cc\
+	612710f100";
+
+fn run(library: &str) -> (usize, StackExecutor<evm::backend::MemoryBackend>) {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(CALL)),
+		(H160::from_low_u64_be(LIBRARY), account(library)),
+	]);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+
+	let (reason, _) = call_target(&mut executor, Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	(executor.used_gas(), executor)
+}
+
+#[test]
+fn unused_gas_returned_on_success() {
+	let (_, executor) = run("00");
+	let child = &executor.call_traces()[0].calls[0];
+
+	assert_eq!(child.reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(child.gas_limit, 10000);
+	assert_eq!(child.gas_used, 0);
+	assert_eq!(child.gas_returned, 10000);
+}
+
+#[test]
+fn unused_gas_retained_on_revert() {
+	let (_, executor) = run("60006000fd");
+	let child = &executor.call_traces()[0].calls[0];
+
+	assert_eq!(child.reason, ExitReason::Revert(ExitRevert::Reverted));
+	assert_eq!(child.gas_used, 6);
+	assert_eq!(child.gas_returned, 9994);
+}
+
+#[test]
+fn gas_burned_on_exceptional_halt() {
+	let (used_revert, _) = run("60006000fd");
+	let (used_invalid, executor) = run("fe");
+	let root = &executor.call_traces()[0];
+	let child = &root.calls[0];
+
+	assert!(matches!(child.reason, ExitReason::Error(_)));
+	assert_eq!(child.gas_used, 10000);
+	assert_eq!(child.gas_returned, 0);
+	assert_eq!(used_invalid - used_revert, 9994);
+	assert_eq!(root.gas_used, root.gas_limit - root.gas_returned);
+}
+
+#[test]
+fn traces_cleared_per_transaction() {
+	let (_, mut executor) = run("00");
+	block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER),
+		H160::from_low_u64_be(LIBRARY),
+		U256::zero(),
+		Vec::new(),
+		100_000,
+	));
+
+	assert_eq!(executor.call_traces().len(), 1);
+	assert_eq!(executor.call_traces()[0].address, H160::from_low_u64_be(LIBRARY));
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use evm::{Config, ExitFatal, ExitReason};
+use evm::executor::{CancellationToken, StackExecutor};
+
+use common::{call_target, deploy};
+
+// JUMPDEST PUSH1 0 JUMP, looping until out of gas.
+const LOOP: &str = "5b600056";
+
+#[test]
+fn cancels_runaway_execution() {
+	let backend = deploy(LOOP);
+	let gas_limit = u64::MAX as usize;
+	let mut executor = StackExecutor::new(backend, gas_limit, Arc::new(Config::istanbul()));
+
+	let token = CancellationToken::new();
+	executor.set_cancellation_token(token.clone());
+	let canceller = thread::spawn(move || {
+		thread::sleep(Duration::from_millis(50));
+		token.cancel();
+	});
+
+	let (reason, _) = call_target(&mut executor, Vec::new(), gas_limit);
+	canceller.join().unwrap();
+	assert_eq!(reason, ExitReason::Fatal(ExitFatal::Cancelled));
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use evm::{Config, OpcodeFilter};
+use evm::gasometer::ConfigGasTable;
+
+
+use common::{prague_eips, shanghai_eips};
+
+fn presets() -> Vec<(&'static str, Config)> {
+	vec![
+		("frontier", Config::frontier()),
+		("istanbul", Config::istanbul()),
+		("shanghai eips", shanghai_eips()),
+		("prague eips", prague_eips()),
+	]
+}
+
+#[test]
+fn supports_agrees_with_gas_table() {
+	for (name, config) in presets() {
+		let table = config.gas_table();
+		for byte in 0..=255u8 {
+			assert_eq!(config.supports(byte), table.get(byte).is_some(), "{} {:#04x}", name, byte);
+		}
+	}
+}
+
+#[test]
+fn supports_follows_the_fork() {
+	// PUSH0
+	assert!(!Config::istanbul().supports(0x5f));
+	assert!(shanghai_eips().supports(0x5f));
+	// CHAINID
+	assert!(!Config::frontier().supports(0x46));
+	assert!(Config::istanbul().supports(0x46));
+	// INVALID and undefined opcodes
+	assert!(!prague_eips().supports(0xfe));
+	assert!(!prague_eips().supports(0x0c));
+}
+
+#[test]
+fn supports_honors_the_opcode_filter() {
+	let mut config = Config::istanbul();
+	config.opcode_filter = OpcodeFilter::allow_all().ban(0x55);
+	assert!(!config.supports(0x55));
+	assert!(config.supports(0x54));
+}
+
+#[test]
+fn active_eips_of_presets() {
+	let istanbul = vec![
+		2, 7, 140, 145, 150, 160, 161, 170, 211, 1014, 1052, 1108, 1344, 1706, 1884, 2028, 2200,
+	];
+	let mut shanghai = istanbul.clone();
+	shanghai.extend(&[3855, 3860]);
+	let mut prague = shanghai.clone();
+	prague.extend(&[2565, 2935, 7623, 7702]);
+	prague.sort();
+
+	assert_eq!(Config::frontier().active_eips(), Vec::<u32>::new());
+	assert_eq!(Config::istanbul().active_eips(), istanbul);
+	assert_eq!(shanghai_eips().active_eips(), shanghai);
+	assert_eq!(prague_eips().active_eips(), prague);
+}
+
+#[test]
+fn active_eips_follow_parameters() {
+	let mut config = Config::istanbul();
+	config.has_set_code = true;
+	assert!(config.active_eips().contains(&7702));
+	config.empty_considered_exists = true;
+	assert!(!config.active_eips().contains(&161));
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::backend::Backend;
+use evm::chainspec::{ChainConfig, ChainSpecError, Genesis, GenesisAccount};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256, U256};
+
+use common::block_on;
+
+const CONTRACT: &str = "0x00000000000000000000000000000000000000aa";
+const FUNDED: &str = "f000000000000000000000000000000000000000";
+
+fn genesis() -> Genesis {
+	let mut alloc = BTreeMap::new();
+	alloc.insert(FUNDED.to_string(), GenesisAccount {
+		balance: "1000000000000000000".to_string(),
+		..Default::default()
+	});
+	let mut storage = BTreeMap::new();
+	storage.insert("0x01".to_string(), "0x2a".to_string());
+	alloc.insert(CONTRACT.to_string(), GenesisAccount {
+		balance: "0x0".to_string(),
+		nonce: Some("0x1".to_string()),
+		// Return slot 1.
+		code: Some("0x60015460005260206000f3".to_string()),
+		storage,
+	});
+
+	Genesis {
+		config: ChainConfig {
+			chain_id: 1337,
+			istanbul_block: Some(10),
+			prague_time: Some(1_000),
+			..Default::default()
+		},
+		gas_limit: Some("0x1c9c380".to_string()),
+		alloc,
+		..Default::default()
+	}
+}
+
+#[test]
+fn loads_alloc_into_backend() {
+	let backend = genesis().backend().unwrap();
+	let contract = H160::from_low_u64_be(0xaa);
+	let funded = H160::from_slice(&hex::decode(FUNDED).unwrap());
+
+	block_on(async {
+		assert_eq!(backend.chain_id().await.unwrap(), U256::from(1337));
+		assert_eq!(backend.block_gas_limit().await.unwrap(), U256::from(30_000_000));
+		assert_eq!(backend.basic(funded).await.unwrap().balance, U256::exp10(18));
+		assert_eq!(backend.basic(contract).await.unwrap().nonce, U256::one());
+	});
+
+	let config = genesis().config_at(10, 0);
+	let mut executor = StackExecutor::new(Arc::new(backend), 100_000, config);
+	let (reason, output) = block_on(executor.transact_call(
+		funded, contract, U256::zero(), Vec::new(), 100_000,
+	));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(output, H256::from_low_u64_be(42).as_bytes().to_vec());
+}
+
+#[test]
+fn selects_forks() {
+	let genesis = genesis();
+	assert!(!genesis.config_at(9, 0).has_chain_id);
+	assert!(genesis.config_at(10, 0).has_chain_id);
+	// There is no config from Berlin on, so Prague blocks run with Istanbul's.
+	assert!(!genesis.config_at(10, 1_000).has_set_code);
+}
+
+#[test]
+fn rejects_invalid_values() {
+	let mut genesis = genesis();
+	genesis.alloc.insert("0x01".to_string(), GenesisAccount::default());
+	assert_eq!(genesis.state(), Err(ChainSpecError::InvalidAddress("0x01".to_string())));
+
+	let mut genesis = self::genesis();
+	genesis.alloc.get_mut(CONTRACT).unwrap().balance = "0xzz".to_string();
+	assert_eq!(genesis.state(), Err(ChainSpecError::InvalidQuantity("0xzz".to_string())));
+}
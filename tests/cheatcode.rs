@@ -0,0 +1,114 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Capture, Config, Context, ExitError, ExitReason, ExitRevert, ExitSucceed, StateMutator, StateQuery};
+use evm::executor::{CHEATCODE_ADDRESS, StackExecutor};
+use evm::backend::MemoryBackend;
+use primitive_types::{H160, H256, U256};
+
+use common::{account, backend, block_on};
+
+const STORE_CALLER: &str = "33600055";
+const REVERT: &str = "60006000fd";
+
+fn executor(code: &str) -> StackExecutor<MemoryBackend> {
+	let mut executor = StackExecutor::new(
+		backend(vec![(H160::from_low_u64_be(0xaa), account(code))]),
+		1_000_000,
+		Arc::new(Config::istanbul()),
+	);
+	executor.enable_cheatcodes();
+	executor
+}
+
+fn encode(selector: [u8; 4], words: &[H256]) -> Vec<u8> {
+	let mut input = selector.to_vec();
+	for word in words {
+		input.extend_from_slice(&word[..]);
+	}
+	input
+}
+
+fn call(executor: &mut StackExecutor<MemoryBackend>, address: H160, input: Vec<u8>) -> (ExitReason, Vec<u8>) {
+	call_with(executor, address, input, false)
+}
+
+fn call_with(
+	executor: &mut StackExecutor<MemoryBackend>,
+	address: H160,
+	input: Vec<u8>,
+	is_static: bool,
+) -> (ExitReason, Vec<u8>) {
+	let context = Context {
+		address,
+		caller: H160::from_low_u64_be(0x01),
+		apparent_value: U256::zero(),
+	};
+	match block_on(executor.call(address, None, input.into(), None, is_static, context)) {
+		Capture::Exit((reason, output)) => (reason, output.into_vec()),
+		Capture::Trap(trap) => match trap {},
+	}
+}
+
+#[test]
+fn prank_overrides_next_caller() {
+	let target = H160::from_low_u64_be(0xaa);
+	let pranked = H160::from_low_u64_be(0xbeef);
+	let mut executor = executor(STORE_CALLER);
+
+	let prank = encode([0xca, 0x66, 0x9f, 0xa7], &[pranked.into()]);
+	assert_eq!(call(&mut executor, CHEATCODE_ADDRESS, prank).0, ExitSucceed::Returned.into());
+	assert_eq!(call(&mut executor, target, Vec::new()).0, ExitSucceed::Stopped.into());
+	assert_eq!(block_on(executor.storage(target, H256::zero())), H256::from(pranked));
+
+	assert_eq!(call(&mut executor, target, Vec::new()).0, ExitSucceed::Stopped.into());
+	assert_eq!(block_on(executor.storage(target, H256::zero())), H256::from_low_u64_be(0x01));
+}
+
+#[test]
+fn deal_warp_and_roll() {
+	let address = H160::from_low_u64_be(0x1234);
+	let mut executor = executor("");
+
+	let deal = encode([0xc8, 0x8a, 0x5e, 0x6d], &[address.into(), H256::from_low_u64_be(500)]);
+	call(&mut executor, CHEATCODE_ADDRESS, deal);
+	call(&mut executor, CHEATCODE_ADDRESS, encode([0xe5, 0xd6, 0xbf, 0x02], &[H256::from_low_u64_be(1_700_000_000)]));
+	call(&mut executor, CHEATCODE_ADDRESS, encode([0x1f, 0x7b, 0x4f, 0x30], &[H256::from_low_u64_be(42)]));
+
+	assert_eq!(block_on(executor.balance(address)), U256::from(500));
+	assert_eq!(block_on(executor.block_timestamp()), U256::from(1_700_000_000u64));
+	assert_eq!(block_on(executor.block_number()), U256::from(42));
+}
+
+#[test]
+fn deal_fails_in_static_context() {
+	let address = H160::from_low_u64_be(0x1234);
+	let mut executor = executor("");
+
+	let deal = encode([0xc8, 0x8a, 0x5e, 0x6d], &[address.into(), H256::from_low_u64_be(500)]);
+	assert_eq!(call_with(&mut executor, CHEATCODE_ADDRESS, deal, true).0, ExitError::StaticModeViolation.into());
+	assert_eq!(block_on(executor.balance(address)), U256::zero());
+}
+
+#[test]
+fn expect_revert_inverts_result() {
+	let target = H160::from_low_u64_be(0xaa);
+
+	let mut executor = executor(REVERT);
+	call(&mut executor, CHEATCODE_ADDRESS, encode([0xf4, 0x84, 0x48, 0x14], &[]));
+	assert_eq!(call(&mut executor, target, Vec::new()), (ExitSucceed::Returned.into(), Vec::new()));
+	assert_eq!(call(&mut executor, target, Vec::new()).0, ExitRevert::Reverted.into());
+
+	let mut executor = self::executor(STORE_CALLER);
+	call(&mut executor, CHEATCODE_ADDRESS, encode([0xf4, 0x84, 0x48, 0x14], &[]));
+	let (reason, output) = call(&mut executor, target, Vec::new());
+	assert_eq!(reason, ExitRevert::Reverted.into());
+	assert_eq!(&output[..4], &[0x08, 0xc3, 0x79, 0xa0]);
+}
+
+#[test]
+fn unknown_cheatcode_reverts() {
+	let mut executor = executor("");
+	assert_eq!(call(&mut executor, CHEATCODE_ADDRESS, vec![0, 0, 0, 0]).0, ExitRevert::Reverted.into());
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::ExitReason;
+use evm::Config;
+use evm::executor::{CodeOverrides, StackExecutor};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, account, backend, call_target};
+
+const LIBRARY: u64 = 0xcc;
+
+// Return 1 as a word.
+const RETURN_ONE: &str = "600160005260206000f3";
+// Return storage slot 0 as a word.
+const READ_SLOT: &str = "60005460005260206000f3";
+// CALL `LIBRARY` with all gas, and return the first word of its output.
+const CALL: &str = "602060006000600060007300000000000000000000000000000000000000cc\
+	5af15060206000f3";
+// Return the EXTCODESIZE of `LIBRARY` as a word.
+const EXT_CODE_SIZE: &str = "7300000000000000000000000000000000000000cc3b60005260206000f3";
+
+fn run(target: &str, overrides: Option<CodeOverrides>) -> U256 {
+	let mut library = account(RETURN_ONE);
+	library.storage.insert(H256::zero(), H256::from_low_u64_be(42));
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(target)),
+		(H160::from_low_u64_be(LIBRARY), library),
+	]);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+	executor.set_code_overrides(overrides);
+
+	let (reason, output) = call_target(&mut executor, Vec::new(), 100_000);
+	assert!(matches!(reason, ExitReason::Succeed(_)), "{:?}", reason);
+	U256::from_big_endian(&output)
+}
+
+fn read_slot() -> CodeOverrides {
+	CodeOverrides::new().with_code(H160::from_low_u64_be(LIBRARY), hex::decode(READ_SLOT).unwrap())
+}
+
+#[test]
+fn replaces_code_of_inner_calls() {
+	assert_eq!(run(CALL, None), U256::one());
+	// The replacement code reads the storage of the account.
+	assert_eq!(run(CALL, Some(read_slot())), U256::from(42));
+}
+
+#[test]
+fn replaces_code_of_the_transaction_target() {
+	let overrides = CodeOverrides::new()
+		.with_code(H160::from_low_u64_be(TARGET), hex::decode(RETURN_ONE).unwrap());
+	assert_eq!(run(READ_SLOT, Some(overrides)), U256::one());
+}
+
+#[test]
+fn ext_code_is_configurable() {
+	assert_eq!(run(EXT_CODE_SIZE, None), U256::from(RETURN_ONE.len() / 2));
+	assert_eq!(run(EXT_CODE_SIZE, Some(read_slot())), U256::from(READ_SLOT.len() / 2));
+	assert_eq!(run(EXT_CODE_SIZE, Some(read_slot().with_ext_code(false))), U256::from(RETURN_ONE.len() / 2));
+}
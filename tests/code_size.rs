@@ -0,0 +1,64 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError, ExitReason};
+use evm::executor::{AnalysisCache, StackExecutor};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, account, backend, block_on};
+
+const GAS_LIMIT: usize = 10_000_000;
+
+// Deploy 0x6001 zero bytes, one more than the mainnet limit.
+const DEPLOY_OVER_LIMIT: &str = "6160016000f3";
+// CREATE with 11 bytes of init code.
+const CREATE_11: &str = "600b60006000f000";
+
+fn create(config: Config, init_code: &str) -> ExitReason {
+	let caller = H160::from_low_u64_be(CALLER);
+	let backend = backend(vec![(caller, account(""))]);
+	let mut executor = StackExecutor::new(backend, GAS_LIMIT, Arc::new(config));
+	block_on(executor.transact_create(
+		caller, U256::zero(), hex::decode(init_code).unwrap(), GAS_LIMIT,
+	))
+}
+
+#[test]
+fn deployment_over_code_size_limit() {
+	assert_eq!(
+		create(Config::istanbul(), DEPLOY_OVER_LIMIT),
+		ExitReason::Error(ExitError::CreateContractLimit),
+	);
+
+	let raised = Config::istanbul().with_code_size_limits(Some(0x8000), None);
+	assert!(create(raised, DEPLOY_OVER_LIMIT).is_succeed());
+}
+
+#[test]
+fn init_code_over_limit() {
+	let config = || Config::istanbul().with_code_size_limits(Some(0x6000), Some(10));
+	assert_eq!(create(config(), CREATE_11), ExitReason::Error(ExitError::InitCodeLimit));
+
+	let caller = H160::from_low_u64_be(CALLER);
+	let target = H160::from_low_u64_be(0xaa);
+	let backend = backend(vec![(caller, account("")), (target, account(CREATE_11))]);
+	let mut executor = StackExecutor::new(backend, GAS_LIMIT, Arc::new(config()));
+	let (reason, _) = block_on(executor.transact_call(
+		caller, target, U256::zero(), Vec::new(), GAS_LIMIT,
+	));
+	assert_eq!(reason, ExitReason::Error(ExitError::InitCodeLimit));
+
+	assert!(create(Config::istanbul(), CREATE_11).is_succeed());
+}
+
+#[test]
+fn analysis_cache_skips_oversized_code() {
+	let config = Config::istanbul().with_code_size_limits(Some(4), None);
+	let mut cache = AnalysisCache::default();
+
+	cache.get_or_analyze(vec![0; 5], &config);
+	assert!(cache.is_empty());
+	cache.get_or_analyze(vec![0; 4], &config);
+	assert_eq!(cache.len(), 1);
+}
@@ -0,0 +1,106 @@
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+use evm::{Config, ExitReason};
+use evm::backend::{Backend, MemoryAccount, MemoryBackend, MemoryVicinity};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, U256};
+
+/// Sender of the transactions of the tests, funded by `deploy`.
+pub const CALLER: u64 = 0xf0;
+/// Contract called by the tests, with the code given to `deploy`.
+pub const TARGET: u64 = 0xaa;
+
+/// Drive a future to completion. Memory backend futures never pend, so
+/// polling with a no-op waker is enough.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+	let mut future = Box::pin(future);
+	let mut context = Context::from_waker(Waker::noop());
+
+	loop {
+		if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+			return output
+		}
+	}
+}
+
+/// Istanbul with the Shanghai EIPs this crate implements: PUSH0 (EIP-3855)
+/// and init code metering (EIP-3860).
+pub fn shanghai_eips() -> Config {
+	Config {
+		has_push0: true,
+		gas_initcode_word: 2,
+		max_initcode_size: Some(0xc000),
+		..Config::istanbul()
+	}
+}
+
+/// `shanghai_eips` with the Prague EIPs this crate implements: modexp
+/// pricing (EIP-2565), block hash history (EIP-2935), the calldata floor
+/// (EIP-7623) and set code transactions (EIP-7702).
+pub fn prague_eips() -> Config {
+	Config {
+		modexp_eip2565: true,
+		gas_transaction_floor_per_token: Some(10),
+		has_set_code: true,
+		has_block_hash_history: true,
+		..shanghai_eips()
+	}
+}
+
+pub fn vicinity() -> MemoryVicinity {
+	MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: H160::default(),
+		chain_id: U256::one(),
+		block_hashes: Vec::new(),
+		block_number: U256::zero(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::zero(),
+		block_difficulty: U256::zero(),
+		block_gas_limit: U256::from(u64::MAX),
+	}
+}
+
+pub fn account(code: &str) -> MemoryAccount {
+	MemoryAccount {
+		nonce: U256::one(),
+		balance: U256::from(1_000_000_000u64),
+		storage: BTreeMap::new(),
+		code: hex::decode(code).unwrap().into(),
+	}
+}
+
+pub fn backend(accounts: Vec<(H160, MemoryAccount)>) -> Arc<MemoryBackend> {
+	Arc::new(MemoryBackend::new(
+		Arc::new(vicinity()),
+		accounts.into_iter().collect(),
+	))
+}
+
+/// Backend with a funded `CALLER` and `TARGET` running `code`.
+pub fn deploy(code: &str) -> Arc<MemoryBackend> {
+	backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(code)),
+	])
+}
+
+/// Call `TARGET` from `CALLER` with the given input and gas limit.
+pub fn call_target<B: Backend>(
+	executor: &mut StackExecutor<B>,
+	input: Vec<u8>,
+	gas_limit: usize,
+) -> (ExitReason, Vec<u8>) {
+	block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER),
+		H160::from_low_u64_be(TARGET),
+		U256::zero(),
+		input,
+		gas_limit,
+	))
+}
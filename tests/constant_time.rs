@@ -0,0 +1,81 @@
+#![cfg(all(feature = "p256", feature = "kzg"))]
+
+mod common;
+
+use evm::executor::KeccakCache;
+use evm::precompiles::{
+	is_constant_time, p256_verify_signature, p256_verify_signature_constant_time,
+	CONSTANT_TIME_PRECOMPILES, P256VERIFY_ADDRESS, POINT_EVALUATION_ADDRESS,
+};
+use primitive_types::{H160, U256};
+
+// Hash, signature and public key of the valid signature in `p256.rs`.
+const HASH: &str = "4e180fe880bc3d7272d606f65001db1861939ccc3c34f475f0ed63b1400b9e1b";
+const R: &str = "1a971d8cf3e8d0320ba2203674418dbe5dd28bd72ab2e744bc86da1ff000bc74";
+const S: &str = "d52b3c54c0348c471bd2d7b669420ca40be13b2991b7740e5b446843c9e72d13";
+const X: &str = "9fad84aeae08bbef7f010014d82cef6a09de2b0cf871b5ce0c4f1d13a59a5934";
+const Y: &str = "07cb45769f1070e2c2470fe5b1bfe63133c0b0cdc64ea4bf3791a8ec2a07fd4f";
+// P-256 group order and field modulus.
+const N: &str = "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551";
+const P: &str = "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff";
+
+fn word(hex: &str) -> U256 {
+	U256::from_big_endian(&hex::decode(hex).unwrap())
+}
+
+/// Verify with both implementations, checking that they agree.
+fn verify(hash: U256, r: U256, s: U256, x: U256, y: U256) -> bool {
+	let valid = p256_verify_signature(hash, r, s, x, y);
+	assert_eq!(p256_verify_signature_constant_time(hash, r, s, x, y), valid);
+	valid
+}
+
+#[test]
+fn constant_time_verification_matches() {
+	let (hash, r, s, x, y) = (word(HASH), word(R), word(S), word(X), word(Y));
+	assert!(verify(hash, r, s, x, y));
+	assert!(verify(hash, r, word(N) - s, x, y));
+
+	assert!(!verify(hash ^ U256::one(), r, s, x, y));
+	assert!(!verify(hash, r ^ U256::one(), s, x, y));
+	assert!(!verify(hash, r, s ^ U256::one(), x, y));
+	assert!(!verify(hash, r, s, x, y ^ U256::one()));
+	assert!(!verify(hash, r, s, x, word(P) - y));
+
+	assert!(!verify(hash, U256::zero(), s, x, y));
+	assert!(!verify(hash, r, U256::zero(), x, y));
+	assert!(!verify(hash, word(N), s, x, y));
+	assert!(!verify(hash, r, s, word(P), y));
+	assert!(!verify(hash, r, s, U256::zero(), U256::zero()));
+}
+
+#[test]
+fn hashes_above_the_group_order_are_reduced() {
+	let (r, s, x, y) = (word(R), word(S), word(X), word(Y));
+	for hash in [U256::MAX, word(N), word(N) + 1] {
+		assert!(!verify(hash, r, s, x, y));
+	}
+}
+
+#[test]
+fn constant_time_precompiles_are_listed() {
+	let enabled = cfg!(feature = "constant-time");
+	assert_eq!(is_constant_time(P256VERIFY_ADDRESS), enabled);
+	assert_eq!(CONSTANT_TIME_PRECOMPILES.len(), enabled as usize);
+	assert!(!is_constant_time(POINT_EVALUATION_ADDRESS));
+	assert!(!is_constant_time(H160::from_low_u64_be(1)));
+}
+
+#[test]
+fn keccak_cache_memoizes_only_without_constant_time() {
+	let mut cache = KeccakCache::default();
+	cache.keccak256(b"key");
+	cache.keccak256(b"key");
+	if cfg!(feature = "constant-time") {
+		assert!(cache.is_empty());
+		assert_eq!(cache.hits(), 0);
+	} else {
+		assert_eq!(cache.len(), 1);
+		assert_eq!(cache.hits(), 1);
+	}
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::executor::StackExecutor;
+use primitive_types::H256;
+use sha3::{Digest, Keccak256};
+
+use common::{call_target, deploy};
+
+// PUSH1 <condition> PUSH1 6 JUMPI STOP JUMPDEST STOP
+fn code(condition: u8) -> String {
+	format!("60{:02x}600657005b00", condition)
+}
+
+#[test]
+fn records_hits_and_branches() {
+	let backend = deploy(&code(1));
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	executor.enable_coverage();
+
+	call_target(&mut executor, Vec::new(), 100_000);
+	call_target(&mut executor, Vec::new(), 100_000);
+
+	let code_hash = H256::from_slice(Keccak256::digest(&hex::decode(code(1)).unwrap()).as_slice());
+	let coverage = &executor.coverage().unwrap().codes[&code_hash];
+	assert_eq!(
+		coverage.hits.iter().map(|(p, c)| (*p, *c)).collect::<Vec<_>>(),
+		vec![(0, 2), (2, 2), (4, 2), (6, 2), (7, 2)],
+	);
+	assert_eq!(coverage.branches.get(&4), Some(&(2, 0)));
+}
+
+#[test]
+fn disabled_by_default() {
+	let backend = deploy(&code(0));
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+
+	call_target(&mut executor, Vec::new(), 100_000);
+	assert!(executor.coverage().is_none());
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use evm::{Config, ExitError, ExitFatal, ExitReason};
+use evm::executor::{StackExecutor, gas_deadline};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, call_target, deploy};
+
+// JUMPDEST PUSH1 0 JUMP, looping until out of gas.
+const LOOP: &str = "5b600056";
+
+fn looping(gas_limit: usize) -> StackExecutor<evm::backend::MemoryBackend> {
+	let backend = deploy(LOOP);
+	StackExecutor::new(backend, gas_limit, Arc::new(Config::istanbul()))
+}
+
+/// Run the loop to completion, counting how many times it yielded.
+fn run_counting_yields(executor: &mut StackExecutor<evm::backend::MemoryBackend>, gas_limit: usize) -> (ExitReason, usize) {
+	let caller = H160::from_low_u64_be(CALLER);
+	let target = H160::from_low_u64_be(TARGET);
+	let mut future = Box::pin(executor.transact_call(caller, target, U256::zero(), Vec::new(), gas_limit));
+	let mut context = Context::from_waker(Waker::noop());
+
+	let mut yields = 0;
+	loop {
+		match future.as_mut().poll(&mut context) {
+			Poll::Ready((reason, _)) => return (reason, yields),
+			Poll::Pending => yields += 1,
+		}
+	}
+}
+
+#[test]
+fn aborts_after_the_deadline() {
+	let gas_limit = u64::MAX as usize;
+	let mut executor = looping(gas_limit);
+	executor.set_deadline(Some(Instant::now() + Duration::from_millis(20)));
+
+	let (reason, _) = call_target(&mut executor, Vec::new(), gas_limit);
+	assert_eq!(reason, ExitReason::Fatal(ExitFatal::DeadlineExceeded));
+}
+
+#[test]
+fn yields_every_interval() {
+	// Each iteration of the loop is three instructions.
+	let gas_limit = 21_000 + 1_000 * 12;
+
+	let mut executor = looping(gas_limit);
+	let (reason, yields) = run_counting_yields(&mut executor, gas_limit);
+	assert_eq!(reason, ExitReason::Error(ExitError::OutOfGas));
+	assert_eq!(yields, 0);
+
+	let mut executor = looping(gas_limit);
+	executor.set_yield_interval(Some(100));
+	let (reason, yields) = run_counting_yields(&mut executor, gas_limit);
+	assert_eq!(reason, ExitReason::Error(ExitError::OutOfGas));
+	assert_eq!(yields, 30);
+}
+
+#[test]
+fn gas_deadlines_are_proportional_to_gas() {
+	assert!(gas_deadline(1_000, 0).is_none());
+	assert!(gas_deadline(usize::MAX, 1).is_none());
+
+	let before = Instant::now();
+	let deadline = gas_deadline(3_000_000, 1_000_000).unwrap();
+	assert!(deadline >= before + Duration::from_secs(3));
+	assert!(deadline <= Instant::now() + Duration::from_secs(3));
+}
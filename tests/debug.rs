@@ -0,0 +1,74 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::backend::{
+	Apply, ApplyBackend, Backend, BlockOverrides, OverlayBackend, Recorder,
+};
+use evm::debug::{AccountDump, dump_account};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, backend, block_on};
+
+const TARGET: u64 = 0x100;
+
+fn slot(n: u64) -> H256 {
+	H256::from_low_u64_be(n)
+}
+
+fn target_backend() -> Arc<evm::backend::MemoryBackend> {
+	let mut target = account("6001");
+	target.storage.insert(slot(1), slot(10));
+	target.storage.insert(slot(2), H256::zero());
+	target.storage.insert(slot(3), slot(30));
+	backend(vec![(H160::from_low_u64_be(TARGET), target)])
+}
+
+#[test]
+fn memory_backend_lists_non_zero_keys() {
+	let backend = target_backend();
+	let target = H160::from_low_u64_be(TARGET);
+	assert_eq!(block_on(backend.account_storage_keys(target)).unwrap(), Some(vec![slot(1), slot(3)]));
+	assert_eq!(block_on(backend.account_storage_keys(H160::zero())).unwrap(), Some(Vec::new()));
+}
+
+#[test]
+fn overlay_merges_keys() {
+	let target = H160::from_low_u64_be(TARGET);
+	let mut overlay = OverlayBackend::new(target_backend(), BlockOverrides::default());
+	let storage = vec![(slot(1), H256::zero()), (slot(5), slot(50))].into_iter().collect::<BTreeMap<_, _>>();
+	block_on(overlay.apply(vec![Apply::Modify {
+		address: target,
+		basic: block_on(overlay.basic(target)).unwrap(),
+		code: None,
+		storage,
+		reset_storage: false,
+	}], Vec::new(), false)).unwrap();
+
+	assert_eq!(block_on(overlay.account_storage_keys(target)).unwrap(), Some(vec![slot(3), slot(5)]));
+}
+
+#[test]
+fn dumps_account() {
+	let backend = target_backend();
+	let dump = block_on(dump_account(&*backend, H160::from_low_u64_be(TARGET))).unwrap().unwrap();
+	assert_eq!(dump, AccountDump {
+		balance: U256::from(1_000_000_000u64),
+		nonce: U256::one(),
+		code: vec![0x60, 0x01],
+		storage: vec![(slot(1), slot(10)), (slot(3), slot(30))].into_iter().collect(),
+	});
+
+	let word = |n: u64| format!("0x{:064x}", n);
+	assert_eq!(dump.to_json(), format!(
+		"{{\"balance\":\"0x3b9aca00\",\"nonce\":\"0x1\",\"code\":\"0x6001\",\"storage\":{{\"{}\":\"{}\",\"{}\":\"{}\"}}}}",
+		word(1), word(10), word(3), word(30),
+	));
+}
+
+#[test]
+fn unsupported_backends_dump_nothing() {
+	let recorder = Recorder::new(target_backend());
+	assert_eq!(block_on(dump_account(&recorder, H160::from_low_u64_be(TARGET))).unwrap(), None);
+}
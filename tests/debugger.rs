@@ -0,0 +1,46 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Breakpoint, Bytes, Config, Context, Debugger, ExitReason, ExitSucceed, Pause, Runtime};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256, U256};
+
+use common::{TARGET, account, backend, block_on};
+
+// PUSH1 2 PUSH1 3 ADD PUSH1 0 SSTORE STOP
+const CODE: &str = "600260030160005500";
+
+#[test]
+fn breakpoints_and_state_modification() {
+	let address = H160::from_low_u64_be(TARGET);
+	let config = Arc::new(Config::istanbul());
+	let mut executor = StackExecutor::new(
+		backend(vec![(address, account(CODE))]),
+		100_000,
+		config.clone(),
+	);
+	let runtime = Runtime::new(
+		Arc::new(hex::decode(CODE).unwrap()),
+		Bytes::new(),
+		Context { address, caller: H160::default(), apparent_value: U256::zero() },
+		config,
+	);
+	let mut debugger = Debugger::new(runtime, &mut executor);
+	debugger.add_breakpoint(Breakpoint::Opcode(0x01));
+	debugger.add_breakpoint(Breakpoint::Position(7));
+
+	assert_eq!(block_on(debugger.step()), Pause::Step);
+	assert_eq!(debugger.position(), Some(2));
+
+	assert_eq!(block_on(debugger.resume()), Pause::Breakpoint(Breakpoint::Opcode(0x01)));
+	assert_eq!(debugger.position(), Some(4));
+	debugger.stack_mut().set(0, H256::from_low_u64_be(10)).unwrap();
+
+	assert_eq!(block_on(debugger.resume()), Pause::Breakpoint(Breakpoint::Position(7)));
+	assert_eq!(block_on(debugger.storage(H256::zero())), H256::zero());
+
+	assert_eq!(block_on(debugger.resume()), Pause::Exit(ExitReason::Succeed(ExitSucceed::Stopped)));
+	assert_eq!(debugger.position(), None);
+	assert_eq!(block_on(debugger.storage(H256::zero())), H256::from_low_u64_be(12));
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::{Config, CreateScheme, StateQuery};
+use evm::deploy::{Artifact, Bytecode, DeployError, Offset, from_artifact};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256};
+
+use common::{CALLER, account, backend, block_on};
+
+// SSTORE(0, library), SSTORE(1, first constructor argument), then return
+// a runtime code returning 42.
+const CREATION: &str = "73__$0123456789abcdef0123456789abcdef01$__600055\
+	6020603b600039600051600155\
+	600a6031600039600a6000f3\
+	602a60005260206000f3";
+const RUNTIME: &str = "602a60005260206000f3";
+
+fn offset(start: usize, length: usize) -> Offset {
+	Offset { start, length }
+}
+
+fn artifact() -> Artifact {
+	let mut link_references = BTreeMap::new();
+	link_references.insert("Lib.sol".to_string(), BTreeMap::new());
+	link_references.get_mut("Lib.sol").unwrap().insert("Lib".to_string(), vec![offset(1, 20)]);
+	Artifact {
+		bytecode: Bytecode {
+			object: format!("0x{}", CREATION),
+			link_references,
+			immutable_references: BTreeMap::new(),
+		},
+		deployed_bytecode: Bytecode::default(),
+	}
+}
+
+fn libraries(name: &str) -> BTreeMap<String, H160> {
+	let mut libraries = BTreeMap::new();
+	libraries.insert(name.to_string(), H160::repeat_byte(0x11));
+	libraries
+}
+
+#[test]
+fn deploys_linked_artifact_with_constructor_args() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let mut executor = StackExecutor::new(
+		backend(vec![(caller, account(""))]),
+		1_000_000,
+		Arc::new(Config::istanbul()),
+	);
+	let expected = block_on(executor.create_address(CreateScheme::Legacy { caller }));
+	let argument = H256::from_low_u64_be(0x1234);
+
+	let deployment = block_on(from_artifact(
+		&mut executor,
+		caller,
+		&artifact(),
+		argument.as_bytes(),
+		&libraries("Lib.sol:Lib"),
+		1_000_000,
+	)).unwrap();
+
+	assert_eq!(deployment.address, expected);
+	assert!(deployment.gas_used > 53_000);
+	assert_eq!(block_on(executor.code(expected)), hex::decode(RUNTIME).unwrap());
+	assert_eq!(block_on(executor.storage(expected, H256::zero())), H256::from(H160::repeat_byte(0x11)));
+	assert_eq!(block_on(executor.storage(expected, H256::from_low_u64_be(1))), argument);
+}
+
+#[test]
+fn libraries_resolve_by_name() {
+	let code = artifact().bytecode.link(&libraries("Lib")).unwrap();
+	assert_eq!(&code[1..21], H160::repeat_byte(0x11).as_bytes());
+}
+
+#[test]
+fn missing_library_is_an_error() {
+	assert_eq!(
+		artifact().bytecode.link(&BTreeMap::new()),
+		Err(DeployError::UnlinkedLibrary("Lib.sol:Lib".into())),
+	);
+
+	let mut unreferenced = artifact().bytecode;
+	unreferenced.link_references.clear();
+	assert!(matches!(unreferenced.link(&BTreeMap::new()), Err(DeployError::UnlinkedLibrary(_))));
+}
+
+#[test]
+fn immutables_are_written_into_runtime_code() {
+	let mut immutable_references = BTreeMap::new();
+	immutable_references.insert("7".to_string(), vec![offset(1, 32)]);
+	let runtime = Bytecode {
+		object: format!("7f{}60005260206000f3", "00".repeat(32)),
+		link_references: BTreeMap::new(),
+		immutable_references,
+	};
+
+	let mut immutables = BTreeMap::new();
+	immutables.insert("7".to_string(), H256::repeat_byte(0xab));
+	let code = runtime.link_with_immutables(&BTreeMap::new(), &immutables).unwrap();
+	assert_eq!(&code[1..33], H256::repeat_byte(0xab).as_bytes());
+
+	assert_eq!(
+		runtime.link_with_immutables(&BTreeMap::new(), &BTreeMap::new()),
+		Err(DeployError::MissingImmutable("7".into())),
+	);
+}
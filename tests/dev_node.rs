@@ -0,0 +1,98 @@
+#![cfg(feature = "rpc")]
+
+mod common;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use evm::Config;
+use evm::rpc::{DEV_ACCOUNTS, DEV_BALANCE, DevNode, INVALID_PARAMS, MiningMode};
+use primitive_types::{H160, H256, U256};
+
+use common::block_on;
+
+fn request(method: &str, params: &str) -> String {
+	format!("{{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"{}\",\"params\":{}}}", method, params)
+}
+
+fn result(response: &str) -> &str {
+	response.split("\"result\":").nth(1).unwrap_or_else(|| panic!("{}", response)).trim_end_matches('}')
+}
+
+fn send(node: &mut DevNode, from: H160, to: Option<H160>, value: u64, data: &str) -> String {
+	let to = to.map(|to| format!(",\"to\":\"{:?}\"", to)).unwrap_or_default();
+	let params = format!("[{{\"from\":\"{:?}\"{},\"value\":\"{:#x}\",\"data\":\"0x{}\"}}]", from, to, value, data);
+	block_on(node.handle(&request("eth_sendTransaction", &params)))
+}
+
+#[test]
+fn dev_accounts_are_prefunded() {
+	let mut node = DevNode::new(Arc::new(Config::istanbul()), MiningMode::Manual);
+
+	assert_eq!(node.accounts().len() as u64, DEV_ACCOUNTS);
+	for (address, _) in node.accounts() {
+		assert_eq!(node.backend().state()[address].balance, DEV_BALANCE);
+	}
+	let response = block_on(node.handle(&request("eth_accounts", "[]")));
+	assert!(result(&response).contains(&format!("{:?}", node.accounts()[9].0)));
+	let response = block_on(node.handle(&request("eth_chainId", "[]")));
+	assert_eq!(result(&response), "\"0x7a69\"");
+	let response = block_on(node.handle(&request("eth_blockNumber", "[]")));
+	assert_eq!(result(&response), "\"0x0\"");
+}
+
+#[test]
+fn auto_mining_mines_each_transaction() {
+	let mut node = DevNode::new(Arc::new(Config::istanbul()), MiningMode::Auto);
+	let from = node.accounts()[0].0;
+	let to = node.accounts()[1].0;
+
+	let response = send(&mut node, from, Some(to), 5, "");
+	assert!(result(&response).starts_with("\"0x"));
+	let response = send(&mut node, from, Some(to), 5, "");
+	assert!(result(&response).starts_with("\"0x"));
+
+	assert_eq!(node.block_number(), U256::from(2));
+	assert_eq!(node.backend().state()[&to].balance, DEV_BALANCE + 10);
+	assert_eq!(node.backend().state()[&from].nonce, U256::from(2));
+}
+
+#[test]
+fn manual_mining_follows_control_methods() {
+	let mut node = DevNode::new(Arc::new(Config::istanbul()), MiningMode::Manual);
+	let from = node.accounts()[0].0;
+
+	// Deploy code storing TIMESTAMP at 0 and NUMBER at 1.
+	send(&mut node, from, None, 0, "67426000554360015560005260086018f3");
+	assert_eq!(node.block_number(), U256::zero());
+	let (contract, _) = node.backend().state().iter().find(|(_, account)| !account.code.is_empty()).unwrap();
+	let contract = *contract;
+
+	let response = block_on(node.handle(&request("evm_setNextBlockTimestamp", "[4000000000]")));
+	assert_eq!(result(&response), "null");
+	send(&mut node, from, Some(contract), 0, "");
+	let response = block_on(node.handle(&request("evm_mine", "[]")));
+	assert_eq!(result(&response), "\"0x0\"");
+
+	let storage = &node.backend().state()[&contract].storage;
+	assert_eq!(storage[&H256::zero()], H256::from_low_u64_be(4_000_000_000));
+	assert_eq!(storage[&H256::from_low_u64_be(1)], H256::from_low_u64_be(1));
+	assert_eq!(node.block_number(), U256::one());
+	assert_eq!(node.backend().vicinity().block_number, U256::from(2));
+
+	let response = block_on(node.handle(&request("evm_setNextBlockTimestamp", "[\"0xee6b2800\"]")));
+	assert!(response.contains(&format!("\"code\":{}", INVALID_PARAMS)), "{}", response);
+	let response = block_on(node.handle(&request("evm_mine", "[4000000100]")));
+	assert!(response.contains("\"result\""));
+	assert_eq!(node.block_number(), U256::from(2));
+}
+
+#[test]
+fn interval_mining_mines_when_due() {
+	let mut node = DevNode::new(Arc::new(Config::istanbul()), MiningMode::Interval(Duration::from_secs(60)));
+
+	assert!(!node.poll(Instant::now()));
+	assert!(node.poll(Instant::now() + Duration::from_secs(61)));
+	assert_eq!(node.block_number(), U256::one());
+	assert!(!node.poll(Instant::now() + Duration::from_secs(62)));
+}
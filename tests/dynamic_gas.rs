@@ -0,0 +1,77 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitReason, ExitSucceed};
+use evm::executor::StackExecutor;
+
+use common::{call_target, deploy};
+
+/// Gas used by the code frame of a call into `code`.
+fn frame_gas(code: &str) -> usize {
+	frame_gas_with(code, Config::istanbul())
+}
+
+fn frame_gas_with(code: &str, config: Config) -> usize {
+	let backend = deploy(code);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(config));
+
+	let (reason, _) = call_target(&mut executor, Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	executor.call_traces()[0].gas_used
+}
+
+#[test]
+fn exp_charges_per_exponent_byte() {
+	// 2 ** 1
+	assert_eq!(frame_gas("600160020a00"), 3 + 3 + 10 + 50);
+	// 2 ** 256
+	assert_eq!(frame_gas("61010060020a00"), 3 + 3 + 10 + 50 * 2);
+	// 2 ** 0
+	assert_eq!(frame_gas("600060020a00"), 3 + 3 + 10);
+}
+
+#[test]
+fn sha3_charges_per_word() {
+	// SHA3 over memory 0..32, expanding memory by one word.
+	assert_eq!(frame_gas("602060002000"), 3 + 3 + 30 + 6 + 3);
+	// SHA3 over memory 0..33, expanding memory by two words.
+	assert_eq!(frame_gas("602160002000"), 3 + 3 + 30 + 6 * 2 + 3 * 2);
+}
+
+#[test]
+fn copy_charges_per_word() {
+	// CALLDATACOPY 64 bytes into memory 0.
+	assert_eq!(frame_gas("6040600060003700"), 3 * 3 + 3 + 3 * 2 + 3 * 2);
+	// CODECOPY 64 bytes into memory 0.
+	assert_eq!(frame_gas("6040600060003900"), 3 * 3 + 3 + 3 * 2 + 3 * 2);
+	// EXTCODECOPY 64 bytes of own code into memory 0.
+	assert_eq!(frame_gas("604060006000303c00"), 3 * 3 + 2 + 700 + 3 * 2 + 3 * 2);
+	// RETURNDATACOPY of the empty return data buffer.
+	assert_eq!(frame_gas("6000600060003e00"), 3 * 3 + 3);
+}
+
+#[test]
+fn opcode_gas_overrides() {
+	let config = Config::istanbul().with_gas_override(0x54, 2_100);
+	// SLOAD of slot 0.
+	assert_eq!(frame_gas("60005400"), 3 + 800);
+	assert_eq!(frame_gas_with("60005400", config.clone()), 3 + 2_100);
+
+	// Memory expansion is still charged on top of an overridden MSTORE.
+	let config = config.with_gas_override(0x52, 1);
+	assert_eq!(frame_gas_with("6000600052", config), 3 + 3 + 1 + 3);
+}
+
+#[test]
+#[should_panic(expected = "gas of opcode 0xf1 cannot be overridden")]
+fn call_gas_cannot_be_overridden() {
+	let _ = Config::istanbul().with_gas_override(0xf1, 1);
+}
+
+#[test]
+#[should_panic(expected = "gas of opcode 0x55 cannot be overridden")]
+fn sstore_gas_cannot_be_overridden() {
+	let _ = Config::istanbul().with_gas_override(0x55, 1);
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::{decode_apply_set, encode_apply_set, Apply, Basic, Log, OwnedApply};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256, U256};
+use rlp::{Decodable, Rlp};
+
+use common::{CALLER, TARGET, call_target, deploy};
+
+fn modify(code: Option<Vec<u8>>, reset_storage: bool) -> OwnedApply {
+	Apply::Modify {
+		address: H160::from_low_u64_be(TARGET),
+		basic: Basic { balance: U256::from(1000), nonce: U256::from(2) },
+		code,
+		storage: vec![
+			(H256::zero(), H256::from_low_u64_be(1)),
+			(H256::from_low_u64_be(1), H256::zero()),
+		],
+		reset_storage,
+	}
+}
+
+fn log() -> Log {
+	Log {
+		address: H160::from_low_u64_be(TARGET),
+		topics: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+		data: vec![1, 2, 3],
+	}
+}
+
+#[test]
+fn apply_sets_round_trip() {
+	let values = vec![
+		modify(None, false),
+		modify(Some(Vec::new()), true),
+		modify(Some(vec![0x60, 0x00]), false),
+		Apply::Delete { address: H160::from_low_u64_be(CALLER) },
+	];
+	let logs = vec![log(), Log { address: H160::zero(), topics: Vec::new(), data: Vec::new() }];
+
+	let encoded = encode_apply_set(&values, &logs);
+	assert_eq!(decode_apply_set(&encoded).unwrap(), (values, logs));
+	assert_eq!(decode_apply_set(&encode_apply_set(&[], &[])).unwrap(), (Vec::new(), Vec::new()));
+}
+
+#[test]
+fn encodings_are_canonical() {
+	let basic = Basic { balance: U256::from(1000), nonce: U256::from(2) };
+	assert_eq!(rlp::encode(&basic), hex::decode("c4028203e8").unwrap());
+	assert_eq!(rlp::encode(&Apply::<Vec<_>>::Delete { address: H160::zero() }),
+		hex::decode("d601940000000000000000000000000000000000000000").unwrap());
+	assert_eq!(rlp::encode(&log()), hex::decode(concat!(
+		"f85d94", "00000000000000000000000000000000000000aa",
+		"f842a0", "0000000000000000000000000000000000000000000000000000000000000001",
+		"a0", "0000000000000000000000000000000000000000000000000000000000000002",
+		"83010203",
+	)).unwrap());
+}
+
+#[test]
+fn malformed_encodings_are_rejected() {
+	let encoded = encode_apply_set(&[modify(None, false)], &[log()]);
+	assert!(decode_apply_set(&encoded[..encoded.len() - 1]).is_err());
+	assert!(decode_apply_set(&[0xc1, 0xff]).is_err());
+
+	// Unknown operation, invalid flag, and more than one code.
+	assert!(OwnedApply::decode(&Rlp::new(&hex::decode("c202c0").unwrap())).is_err());
+	let mut stream = rlp::RlpStream::new_list(6);
+	stream.append(&0u8).append(&H160::zero()).append(&Basic::default()).begin_list(0);
+	stream.begin_list(0).append(&2u8);
+	assert!(OwnedApply::decode(&Rlp::new(&stream.out())).is_err());
+	let mut stream = rlp::RlpStream::new_list(6);
+	stream.append(&0u8).append(&H160::zero()).append(&Basic::default());
+	stream.begin_list(2).append(&vec![0u8]).append(&vec![1u8]);
+	stream.begin_list(0).append(&0u8);
+	assert!(OwnedApply::decode(&Rlp::new(&stream.out())).is_err());
+}
+
+#[test]
+fn executor_output_round_trips() {
+	// Store 42 at slot 1, log one topic, and create an empty contract.
+	let code = "602a600155600760006000a1600060006000f0";
+	let backend = deploy(code);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+	let (reason, _) = call_target(&mut executor, Vec::new(), 1_000_000);
+	assert!(reason.is_succeed(), "{:?}", reason);
+
+	let (values, logs) = executor.deconstruct();
+	let values = values.into_iter().map(Apply::into_owned).collect::<Vec<_>>();
+	let logs = logs.into_iter().collect::<Vec<_>>();
+	assert_eq!(logs.len(), 1);
+	assert_eq!(decode_apply_set(&encode_apply_set(&values, &logs)).unwrap(), (values, logs));
+}
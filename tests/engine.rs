@@ -0,0 +1,59 @@
+#![cfg(feature = "testutil")]
+
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::{Config, Engine};
+use evm::backend::{ApplyBackend, MemoryAccount, MemoryBackend};
+use evm::executor::{ExecutionResult, StackExecutor};
+use evm::testutil::{random_state, random_tx};
+use primitive_types::{H160, H256};
+
+use common::{account, backend, block_on, vicinity};
+
+const CASES: u64 = 256;
+
+fn run(seed: u64, engine: Engine) -> (ExecutionResult, BTreeMap<H160, MemoryAccount>) {
+	let state = random_state(seed);
+	let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity()), state.clone()));
+	let transaction = random_tx(seed);
+	let config = Config::istanbul().with_engine(engine);
+	let mut executor = StackExecutor::new(backend, transaction.gas_limit, Arc::new(config));
+
+	let result = block_on(executor.transact(transaction));
+	let (applies, logs) = executor.deconstruct();
+	let mut post = MemoryBackend::new(Arc::new(vicinity()), state);
+	block_on(post.apply(applies, logs, false));
+
+	(result, post.state().clone())
+}
+
+#[test]
+fn threaded_matches_interpreter() {
+	for seed in 0..CASES {
+		assert_eq!(run(seed, Engine::Threaded), run(seed, Engine::Interpreter), "seed {}", seed);
+	}
+}
+
+#[test]
+fn threaded_runs_jumps_and_pushes() {
+	let caller = H160::from_low_u64_be(0xf0);
+	let target = H160::from_low_u64_be(0xaa);
+	// Count down from 3 in a loop, then return PC and the counter.
+	let backend = backend(vec![
+		(caller, account("")),
+		(target, account("60035b60019003806002575860005260205260406000f3")),
+	]);
+	let config = Arc::new(Config::istanbul().with_engine(Engine::Threaded));
+	let mut executor = StackExecutor::new(backend, 100_000, config);
+
+	let (reason, output) = block_on(executor.transact_call(
+		caller, target, Default::default(), Vec::new(), 100_000,
+	));
+
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(output[..32], H256::from_low_u64_be(11)[..]);
+	assert_eq!(output[32..], H256::zero()[..]);
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "eof")]
+
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, EofContainer, EofError, ExitError, ExitReason, ExitSucceed, StateQuery};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, account, backend, block_on, call_target, deploy};
+
+// Two code sections. The first skips a STOP with RJUMPI, then stores the
+// word returned by CALLF 1 at slot 0.
+const CONTAINER: &str = "ef0001\
+	010008\
+	020002000d0003\
+	040000\
+	00\
+	00000002\
+	00010001\
+	6001e1000100e3000160005500\
+	602ae4";
+
+// Init code returning `ef00`, which is not a valid container.
+const DEPLOY_MAGIC: &str = "61ef006000526002601ef3";
+
+fn container(code: &str) -> Vec<u8> {
+	hex::decode(code).unwrap()
+}
+
+fn eof_config() -> Config {
+	Config { has_eof: true, ..Config::istanbul() }
+}
+
+fn run(config: Config) -> (ExitReason, H256) {
+	let backend = deploy(CONTAINER);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(config));
+
+	let (reason, _) = call_target(&mut executor, Vec::new(), 100_000);
+	(reason, block_on(executor.storage(H160::from_low_u64_be(TARGET), H256::zero())))
+}
+
+#[test]
+fn executes_relative_jumps_and_functions() {
+	assert_eq!(
+		run(eof_config()),
+		(ExitReason::Succeed(ExitSucceed::Stopped), H256::from_low_u64_be(0x2a)),
+	);
+}
+
+#[test]
+fn container_is_legacy_code_without_eof() {
+	assert_eq!(run(Config::istanbul()).1, H256::zero());
+	assert!(matches!(run(Config::istanbul()).0, ExitReason::Error(_)));
+}
+
+#[test]
+fn parses_sections() {
+	let container = EofContainer::new(&container(CONTAINER)).unwrap();
+	assert_eq!(container.code, vec![25..38, 38..41]);
+	assert_eq!(container.types[1].outputs, 1);
+	assert_eq!(container.data, 41..41);
+}
+
+#[test]
+fn rejects_invalid_containers() {
+	let cases = [
+		("ef00020100040200010001040000000000000000", EofError::UnsupportedVersion(2)),
+		("ef00010100040200010001040000010000000000", EofError::InvalidHeader),
+		("ef000101000402000100010400000000000000", EofError::InvalidContainerSize),
+		("ef0001010004020001000204000000000000005600", EofError::UndefinedOpcode(0)),
+		("ef0001010004020001000204000000000000006100", EofError::TruncatedImmediate(0)),
+		("ef000101000402000100030400000000000000e00001", EofError::InvalidRelativeJump(0)),
+		("ef000101000402000100040400000000000000e0ffff00", EofError::InvalidRelativeJump(0)),
+		("ef00010100040200010004040000000000000060016001", EofError::MissingTerminator(0)),
+		("ef000101000402000100040400000000000000e3000100", EofError::InvalidSectionIndex(0)),
+		("ef0001010004020001000204000000000000000000", EofError::UnreachableCode(1)),
+		("ef0001010004020001000204000000000000005000", EofError::StackUnderflow(0)),
+		("ef000101000402000100030400000000000000600100", EofError::InvalidMaxStackHeight(0)),
+		("ef0001010004020001000804000000000000016001e10002600100", EofError::InvalidStackHeight(7)),
+		("ef000101000802000200040001040000000000000100010000e3000100e4", EofError::InvalidStackHeight(0)),
+	];
+
+	for (code, error) in cases.iter() {
+		assert_eq!(EofContainer::new(&container(code)), Err(*error), "{}", code);
+	}
+}
+
+#[test]
+fn create_rejects_invalid_eof_code() {
+	let backend = backend(vec![(H160::from_low_u64_be(CALLER), account(""))]);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(eof_config()));
+
+	let reason = block_on(executor.transact_create(
+		H160::from_low_u64_be(CALLER),
+		U256::zero(),
+		container(DEPLOY_MAGIC),
+		100_000,
+	));
+	assert_eq!(reason, ExitReason::Error(ExitError::InvalidCode));
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::Log;
+use evm::events::{DecodeError, Event, EventFilter, ParamType, Value, decode};
+use evm::executor::{StackExecutor, Transaction, TransactionAction};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, backend, block_on};
+
+const TRANSFER: &str = "event Transfer(address indexed from, address indexed to, uint256 value)";
+
+// Emit Transfer(0xf0, 0xbb, 5).
+const EMIT_TRANSFER: &str = concat!(
+	"600560005260bb60f0",
+	"7fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+	"60206000a300",
+);
+
+fn word(value: u64) -> Vec<u8> {
+	H256::from_low_u64_be(value).as_bytes().to_vec()
+}
+
+#[test]
+fn parses_signatures() {
+	let event = Event::parse(TRANSFER).unwrap();
+	assert_eq!(event.signature(), "Transfer(address,address,uint256)");
+	assert_eq!(
+		format!("{:x}", event.topic()),
+		"ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+	);
+	assert_eq!(
+		Event::parse("Message(uint indexed, string[])").unwrap().signature(),
+		"Message(uint256,string[])",
+	);
+	assert_eq!(ParamType::parse("bytes33"), None);
+	assert_eq!(Event::parse("event (uint256)"), None);
+}
+
+#[test]
+fn decodes_executor_logs() {
+	let caller = H160::from_low_u64_be(0xf0);
+	let token = H160::from_low_u64_be(0xaa);
+	let backend = backend(vec![
+		(caller, account("")),
+		(token, account(EMIT_TRANSFER)),
+	]);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	let result = block_on(executor.transact(Transaction {
+		caller,
+		action: TransactionAction::Call(token),
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: 100_000,
+		nonce: None,
+	}));
+	assert!(result.is_succeed(), "{:?}", result.reason);
+
+	let filter = EventFilter::new()
+		.event(Event::parse("event Approval(address indexed, address indexed, uint256)").unwrap())
+		.event(Event::parse(TRANSFER).unwrap())
+		.address(token);
+	let decoded = filter.filter(&result.logs);
+	assert_eq!(decoded.len(), 1);
+	assert_eq!(decoded[0].address, token);
+	assert_eq!(decoded[0].event, "Transfer");
+	assert_eq!(decoded[0].params, vec![
+		("from".to_string(), Value::Address(caller)),
+		("to".to_string(), Value::Address(H160::from_low_u64_be(0xbb))),
+		("value".to_string(), Value::Uint(U256::from(5))),
+	]);
+
+	let other = EventFilter::new().event(Event::parse(TRANSFER).unwrap()).address(caller);
+	assert!(!other.matches(&result.logs[0]));
+}
+
+#[test]
+fn decodes_dynamic_data() {
+	let event = Event::parse("event Note(string indexed tag, string text, uint8[] values) anonymous").unwrap();
+	let mut data = Vec::new();
+	data.extend(word(0x40));
+	data.extend(word(0x80));
+	data.extend(word(2));
+	data.extend(b"hi".iter().copied().chain(std::iter::repeat_n(0, 30)));
+	data.extend(word(2));
+	data.extend(word(1));
+	data.extend(word(255));
+	let tag = H256::repeat_byte(0x11);
+	let log = Log { address: H160::zero(), topics: vec![tag], data };
+
+	assert_eq!(decode(&log, &event), Ok(vec![
+		("tag".to_string(), Value::Hash(tag)),
+		("text".to_string(), Value::String("hi".to_string())),
+		("values".to_string(), Value::Array(vec![Value::Uint(U256::one()), Value::Uint(U256::from(255))])),
+	]));
+
+	let mut truncated = log.clone();
+	truncated.data.truncate(0xa0);
+	assert_eq!(decode(&truncated, &event), Err(DecodeError::InvalidData));
+
+	let transfer = Event::parse(TRANSFER).unwrap();
+	assert_eq!(decode(&log, &transfer), Err(DecodeError::SignatureMismatch));
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use std::sync::Arc;
+use std::sync::mpsc::channel;
+
+use evm::{Config, ExitReason, ExitSucceed};
+use evm::backend::Log;
+use evm::executor::{ExecutorEvent, StackExecutor};
+use primitive_types::{H160, H256};
+
+use common::{CALLER, TARGET, call_target, deploy};
+
+// SSTORE 1 at slot 0, then LOG0 with empty data.
+const STORE_AND_LOG: &str = "600160005560006000a000";
+
+#[test]
+fn streams_events_in_order() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let target = H160::from_low_u64_be(TARGET);
+	let backend = deploy(STORE_AND_LOG);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+
+	let (sender, receiver) = channel();
+	executor.set_event_sender(sender);
+	call_target(&mut executor, Vec::new(), 100_000);
+	drop(executor);
+
+	assert_eq!(receiver.iter().collect::<Vec<_>>(), vec![
+		ExecutorEvent::Enter { is_create: false, caller, address: target, depth: 0 },
+		ExecutorEvent::StorageChanged { address: target, index: H256::zero(), value: H256::from_low_u64_be(1) },
+		ExecutorEvent::Log(Log { address: target, topics: Vec::new(), data: Vec::new() }),
+		ExecutorEvent::Exit { address: target, depth: 0, reason: ExitReason::Succeed(ExitSucceed::Stopped) },
+	]);
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, CreateScheme, ExitReason, ExitSucceed};
+use evm::executor::{StackExecutor, Transaction, TransactionAction};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, account, backend, block_on, deploy};
+
+fn transaction(action: TransactionAction, data: Vec<u8>) -> Transaction {
+	Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action,
+		value: U256::zero(),
+		data,
+		gas_limit: 100_000,
+		nonce: None,
+	}
+}
+
+#[test]
+fn call_collects_artifacts() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let target = H160::from_low_u64_be(TARGET);
+	// SLOAD(1), LOG0 with empty data, then return 42 as a word.
+	let backend = deploy("6001545060006000a0602a60005260206000f3");
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+
+	let result = block_on(executor.transact(transaction(TransactionAction::Call(target), Vec::new())));
+
+	assert_eq!(result.reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(result.output, H256::from_low_u64_be(42).as_bytes().to_vec());
+	assert_eq!(result.created_address, None);
+	assert_eq!(result.logs.len(), 1);
+	assert_eq!(result.logs[0].address, target);
+	assert_eq!(result.gas_used, executor.used_gas());
+	assert_eq!(result.gas_forwarded, 100_000 - 21_000);
+	assert!(result.accessed.accounts.contains(&caller));
+	assert!(result.accessed.accounts.contains(&target));
+	assert!(result.accessed.storage.contains(&(target, H256::from_low_u64_be(1))));
+	assert!(result.backend_reads > 0);
+}
+
+#[test]
+fn create_reports_address() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let backend = backend(vec![(caller, account(""))]);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	let expected = block_on(executor.create_address(CreateScheme::Legacy { caller }));
+
+	let result = block_on(executor.transact(transaction(TransactionAction::Create, vec![0x00])));
+
+	assert!(result.is_succeed());
+	assert_eq!(result.created_address, Some(expected));
+	assert!(result.output.is_empty());
+}
+
+#[test]
+fn rejected_transaction_forwards_no_gas() {
+	let target = H160::from_low_u64_be(TARGET);
+	let backend = backend(vec![(target, account("00"))]);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+
+	let mut transaction = transaction(TransactionAction::Call(target), Vec::new());
+	transaction.value = U256::one();
+	let result = block_on(executor.transact(transaction));
+
+	assert!(!result.is_succeed());
+	assert_eq!(result.gas_forwarded, 0);
+	assert!(result.logs.is_empty());
+}
+
+#[test]
+fn logs_of_reverted_frames_are_dropped() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let target = H160::from_low_u64_be(TARGET);
+	let backend = backend(vec![
+		(caller, account("")),
+		// LOG1(1), call 0xbb, call 0xcc, then LOG1(4).
+		(target, account(concat!(
+			"600160006000a1",
+			"600060006000600060007300000000000000000000000000000000000000bb5af150",
+			"600060006000600060007300000000000000000000000000000000000000cc5af150",
+			"600460006000a100",
+		))),
+		// LOG1(2).
+		(H160::from_low_u64_be(0xbb), account("600260006000a100")),
+		// LOG1(3), then revert.
+		(H160::from_low_u64_be(0xcc), account("600360006000a160006000fd")),
+	]);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+
+	let result = block_on(executor.transact(transaction(TransactionAction::Call(target), Vec::new())));
+
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	let topics = result.logs.iter().map(|log| log.topics[0]).collect::<Vec<_>>();
+	assert_eq!(topics, vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2), H256::from_low_u64_be(4)]);
+	assert_eq!(result.logs[1].address, H160::from_low_u64_be(0xbb));
+	assert_eq!(executor.deconstruct().1.into_iter().count(), 3);
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitReason, ExitSucceed};
+use evm::backend::MemoryAccount;
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256};
+use sha3::{Digest, Keccak256};
+
+use common::{CALLER, TARGET, account, backend, call_target};
+
+const SUBJECT: u64 = 0xbb;
+
+// Return EXTCODEHASH of `SUBJECT`.
+const HASH: &str = "7300000000000000000000000000000000000000bb3f60005260206000f3";
+// CALL `SUBJECT`, then return its EXTCODEHASH.
+const CALL_THEN_HASH: &str = "600060006000600060007300000000000000000000000000000000000000bb5af150\
+	7300000000000000000000000000000000000000bb3f60005260206000f3";
+// SELFDESTRUCT to the caller.
+const SELFDESTRUCT: &str = "33ff";
+
+fn keccak(data: &[u8]) -> H256 {
+	H256::from_slice(Keccak256::digest(data).as_slice())
+}
+
+fn ext_code_hash(code: &str, subject: Option<MemoryAccount>) -> H256 {
+	let mut accounts = vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(code)),
+	];
+	if let Some(subject) = subject {
+		accounts.push((H160::from_low_u64_be(SUBJECT), subject));
+	}
+	let mut executor = StackExecutor::new(backend(accounts), 1_000_000, Arc::new(Config::istanbul()));
+
+	let (reason, out) = call_target(&mut executor, Vec::new(), 1_000_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	H256::from_slice(&out)
+}
+
+#[test]
+fn non_existent_account_is_zero() {
+	assert_eq!(ext_code_hash(HASH, None), H256::zero());
+}
+
+#[test]
+fn empty_account_is_zero() {
+	assert_eq!(ext_code_hash(HASH, Some(MemoryAccount::default())), H256::zero());
+}
+
+#[test]
+fn codeless_account_is_empty_code_hash() {
+	assert_eq!(ext_code_hash(HASH, Some(account(""))), keccak(&[]));
+}
+
+#[test]
+fn contract_is_code_hash() {
+	assert_eq!(ext_code_hash(HASH, Some(account("6000"))), keccak(&[0x60, 0x00]));
+}
+
+#[test]
+fn selfdestructed_in_same_transaction_keeps_code_hash() {
+	assert_eq!(
+		ext_code_hash(CALL_THEN_HASH, Some(account(SELFDESTRUCT))),
+		keccak(&hex::decode(SELFDESTRUCT).unwrap()),
+	);
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitReason, ExitSucceed};
+use evm::backend::MemoryAccount;
+use evm::executor::{PrecompileOutput, StackExecutor, delegation_designator};
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+use common::{CALLER, account, backend, block_on, prague_eips};
+
+const PROBE: u64 = 0xaa;
+const DELEGATE: u64 = 0xdd;
+const DELEGATED: u64 = 0xd1;
+const DELEGATED_TO_PRECOMPILE: u64 = 0xd2;
+const DELEGATED_TO_DELEGATED: u64 = 0xd3;
+const IDENTITY: u64 = 0x04;
+const FUNDED_IDENTITY: u64 = 0x05;
+
+// Return 42 as a word.
+const RETURN_42: &str = "602a60005260206000f3";
+
+/// Store `EXTCODESIZE`, `EXTCODEHASH` and the first word of `EXTCODECOPY`
+/// of the address given as the first calldata word, and return them.
+const PROBE_CODE: &str = concat!(
+	"6000353b600052",
+	"6000353f602052",
+	"6020600060406000353c",
+	"60606000f3",
+);
+
+fn precompile(address: H160, input: &[u8], _: Option<usize>) -> Option<PrecompileOutput> {
+	if address == H160::from_low_u64_be(IDENTITY) || address == H160::from_low_u64_be(FUNDED_IDENTITY) {
+		Some(Ok((ExitSucceed::Returned, input.to_vec(), 15)))
+	} else {
+		None
+	}
+}
+
+fn designated(low: u64) -> MemoryAccount {
+	let mut account = account("");
+	account.code = delegation_designator(H160::from_low_u64_be(low)).into();
+	account
+}
+
+fn call(config: Config, target: u64, data: Vec<u8>) -> (ExitReason, Vec<u8>) {
+	let mut funded = account("");
+	funded.nonce = U256::zero();
+	funded.balance = U256::one();
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(PROBE), account(PROBE_CODE)),
+		(H160::from_low_u64_be(DELEGATE), account(RETURN_42)),
+		(H160::from_low_u64_be(DELEGATED), designated(DELEGATE)),
+		(H160::from_low_u64_be(DELEGATED_TO_PRECOMPILE), designated(IDENTITY)),
+		(H160::from_low_u64_be(DELEGATED_TO_DELEGATED), designated(DELEGATED)),
+		(H160::from_low_u64_be(FUNDED_IDENTITY), funded),
+	]);
+	let mut executor = StackExecutor::new_with_precompile(backend, 1_000_000, Arc::new(config), precompile);
+	block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER), H160::from_low_u64_be(target), U256::zero(), data, 1_000_000,
+	))
+}
+
+/// Code size, code hash and first code word of the address.
+fn probe(config: Config, target: u64) -> (U256, H256, Vec<u8>) {
+	let (reason, output) = call(config, PROBE, H256::from_low_u64_be(target).as_bytes().to_vec());
+	assert!(reason.is_succeed(), "{:?}", reason);
+	(U256::from_big_endian(&output[..32]), H256::from_slice(&output[32..64]), output[64..].to_vec())
+}
+
+fn keccak(data: &[u8]) -> H256 {
+	H256::from_slice(Keccak256::digest(data).as_slice())
+}
+
+#[test]
+fn extcode_of_delegated_account_sees_designator() {
+	let designator = delegation_designator(H160::from_low_u64_be(DELEGATE));
+	let mut word = designator.clone();
+	word.resize(32, 0);
+
+	// The designator is ordinary code before EIP-7702.
+	for config in [prague_eips(), Config::istanbul()] {
+		let (size, hash, code) = probe(config, DELEGATED);
+		assert_eq!(size, U256::from(23));
+		assert_eq!(hash, keccak(&designator));
+		assert_eq!(code, word);
+	}
+}
+
+#[test]
+fn extcode_of_precompile_sees_stored_code() {
+	let (size, hash, code) = probe(prague_eips(), IDENTITY);
+	assert_eq!(size, U256::zero());
+	assert_eq!(hash, H256::zero());
+	assert_eq!(code, vec![0; 32]);
+
+	// A funded precompile exists, without code.
+	let (size, hash, _) = probe(prague_eips(), FUNDED_IDENTITY);
+	assert_eq!(size, U256::zero());
+	assert_eq!(hash, keccak(&[]));
+}
+
+#[test]
+fn calls_follow_delegation_with_set_code() {
+	let (reason, output) = call(prague_eips(), DELEGATED, Vec::new());
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(U256::from_big_endian(&output), U256::from(42));
+
+	// Before EIP-7702, the designator runs as code and fails on 0xef.
+	let (reason, _) = call(Config::istanbul(), DELEGATED, Vec::new());
+	assert!(!reason.is_succeed());
+}
+
+#[test]
+fn delegation_to_precompile_runs_empty_code() {
+	let (reason, output) = call(prague_eips(), DELEGATED_TO_PRECOMPILE, vec![1, 2, 3]);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert!(output.is_empty());
+}
+
+#[test]
+fn delegation_chains_are_not_followed() {
+	let (reason, _) = call(prague_eips(), DELEGATED_TO_DELEGATED, Vec::new());
+	assert!(!reason.is_succeed());
+}
+
+/// Gas used by a transaction to a contract calling `callee` twice.
+fn double_call_gas(callee: u64) -> usize {
+	let call = format!("600060006000600060007300000000000000000000000000000000000000{:02x}61fffff150", callee);
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(PROBE), account(&format!("{}{}00", call, call))),
+		(H160::from_low_u64_be(DELEGATE), account(RETURN_42)),
+		(H160::from_low_u64_be(DELEGATED), designated(DELEGATE)),
+	]);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(prague_eips()));
+
+	let (reason, _) = block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER), H160::from_low_u64_be(PROBE), U256::zero(), Vec::new(), 1_000_000,
+	));
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	executor.used_gas()
+}
+
+#[test]
+fn delegated_calls_charge_delegate_access() {
+	// Cold access of the delegate on the first call, warm on the second.
+	assert_eq!(double_call_gas(DELEGATED) - double_call_gas(DELEGATE), 2_600 + 100);
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError, ExitReason, ExitRevert};
+use evm::executor::{ExecutionResult, StackExecutor, Transaction, TransactionAction};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, account, backend, block_on};
+
+const LIBRARY: u64 = 0xcc;
+
+// CALL `LIBRARY` with all gas, then JUMP to a non-`JUMPDEST`.
+const CALL_THEN_FAIL: &str = "600060006000600060007300000000000000000000000000000000000000cc\
+	5af150600056";
+// Store 0xdead at the end of the first word, and revert with it.
+const REVERT: &str = "61dead6000526002601efd";
+
+fn transact(fault_snapshot_stack: Option<usize>) -> ExecutionResult {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(CALL_THEN_FAIL)),
+		(H160::from_low_u64_be(LIBRARY), account(REVERT)),
+	]);
+	let mut config = Config::istanbul();
+	config.fault_snapshot_stack = fault_snapshot_stack;
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(config));
+
+	block_on(executor.transact(Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action: TransactionAction::Call(H160::from_low_u64_be(TARGET)),
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: 100_000,
+		nonce: None,
+	}))
+}
+
+#[test]
+fn disabled_by_default() {
+	let result = transact(None);
+	assert_eq!(result.reason, ExitReason::Error(ExitError::InvalidJump));
+	assert!(result.fault_snapshots.is_empty());
+}
+
+#[test]
+fn snapshots_failed_frames_in_exit_order() {
+	let result = transact(Some(4));
+	assert_eq!(result.fault_snapshots.len(), 2);
+
+	let revert = &result.fault_snapshots[0];
+	assert_eq!((revert.frame, revert.depth, revert.position, revert.opcode), (1, 1, 10, 0xfd));
+	assert_eq!(revert.reason, ExitReason::Revert(ExitRevert::Reverted));
+	assert_eq!(revert.stack, vec![H256::from_low_u64_be(0x1e), H256::from_low_u64_be(2)]);
+	assert_eq!((revert.memory_offset, revert.memory.clone()), (0x1e, vec![0xde, 0xad]));
+
+	let jump = &result.fault_snapshots[1];
+	assert_eq!((jump.frame, jump.depth, jump.position, jump.opcode), (0, 0, 36, 0x56));
+	assert_eq!(jump.reason, ExitReason::Error(ExitError::InvalidJump));
+	assert_eq!(jump.stack, vec![H256::zero()]);
+	assert!(jump.memory.is_empty());
+}
+
+#[test]
+fn keeps_top_stack_items() {
+	let result = transact(Some(1));
+	assert_eq!(result.fault_snapshots[0].stack, vec![H256::from_low_u64_be(0x1e)]);
+}
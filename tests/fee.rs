@@ -0,0 +1,163 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError, ExitFatal, ExitReason, StateQuery};
+use evm::backend::{MemoryBackend, MemoryVicinity};
+use evm::executor::{DefaultFeePolicy, FeeDistribution, FeeError, FeePolicy, StackExecutor, Transaction,
+	TransactionAction, floor_gas, intrinsic_gas};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, account, backend, block_on, call_target, deploy, vicinity};
+
+const TREASURY: u64 = 0xee;
+
+/// Flat intrinsic gas, with every fee paid to a treasury.
+struct TreasuryPolicy;
+
+impl FeePolicy for TreasuryPolicy {
+	fn intrinsic_gas(&self, _transaction: &Transaction, _config: &Config) -> usize {
+		1_000
+	}
+
+	fn refund(&self, _used_gas: usize, _refunded_gas: isize) -> usize {
+		0
+	}
+
+	fn distribute(&self, used_gas: usize, gas_price: U256, _coinbase: H160) -> FeeDistribution {
+		FeeDistribution {
+			credits: vec![(H160::from_low_u64_be(TREASURY), U256::from(used_gas) * gas_price)],
+			burned: U256::zero(),
+		}
+	}
+}
+
+/// Credits the coinbase with twice the fee.
+struct GenerousPolicy;
+
+impl FeePolicy for GenerousPolicy {
+	fn intrinsic_gas(&self, transaction: &Transaction, config: &Config) -> usize {
+		intrinsic_gas(transaction, config)
+	}
+
+	fn refund(&self, _used_gas: usize, _refunded_gas: isize) -> usize {
+		0
+	}
+
+	fn distribute(&self, used_gas: usize, gas_price: U256, coinbase: H160) -> FeeDistribution {
+		FeeDistribution {
+			credits: vec![(coinbase, U256::from(used_gas) * gas_price * 2)],
+			burned: U256::zero(),
+		}
+	}
+}
+
+fn run(policy: Arc<dyn FeePolicy>, data: Vec<u8>) -> (usize, FeeDistribution, U256) {
+	let caller = H160::from_low_u64_be(CALLER);
+	let backend = backend(vec![
+		(caller, account("")),
+		(H160::from_low_u64_be(TARGET), account("00")),
+	]);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	executor.set_fee_policy(policy);
+
+	block_on(executor.transact_call(caller, H160::from_low_u64_be(TARGET), U256::zero(), data, 100_000));
+	let distribution = block_on(executor.settle_fees(caller, U256::from(10))).unwrap();
+	let balance = block_on(executor.account_mut(caller)).basic.balance;
+
+	(executor.used_gas(), distribution, balance)
+}
+
+#[test]
+fn default_policy_burns_base_fee() {
+	let policy = DefaultFeePolicy { base_fee: U256::from(7) };
+	let (used_gas, distribution, balance) = run(Arc::new(policy), vec![0, 1]);
+
+	assert_eq!(used_gas, 21_000 + 4 + 16);
+	assert_eq!(distribution, FeeDistribution {
+		credits: vec![(H160::default(), U256::from(used_gas * 3))],
+		burned: U256::from(used_gas * 7),
+	});
+	assert_eq!(balance, U256::from(1_000_000_000u64 - used_gas as u64 * 10));
+}
+
+#[test]
+fn custom_policy_routes_fees_to_treasury() {
+	let (used_gas, distribution, balance) = run(Arc::new(TreasuryPolicy), vec![1; 100]);
+
+	assert_eq!(used_gas, 1_000);
+	assert_eq!(distribution.credits, vec![(H160::from_low_u64_be(TREASURY), U256::from(10_000))]);
+	assert_eq!(balance, U256::from(1_000_000_000u64 - 10_000));
+}
+
+#[test]
+fn distributions_must_add_up_to_the_fee() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let backend = backend(vec![
+		(caller, account("")),
+		(H160::from_low_u64_be(TARGET), account("00")),
+	]);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	executor.set_fee_policy(Arc::new(GenerousPolicy));
+
+	block_on(executor.transact_call(caller, H160::from_low_u64_be(TARGET), U256::zero(), Vec::new(), 100_000));
+	let fee = U256::from(21_000 * 10);
+	assert_eq!(
+		block_on(executor.settle_fees(caller, U256::from(10))),
+		Err(FeeError::InvalidDistribution { fee, distributed: Some(fee * 2) }),
+	);
+	assert_eq!(block_on(executor.account_mut(caller)).basic.balance, U256::from(1_000_000_000u64));
+	assert_eq!(block_on(executor.account_mut(H160::default())).basic.balance, U256::zero());
+}
+
+#[test]
+fn transactions_settle_through_the_same_check() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let vicinity = MemoryVicinity { gas_price: U256::from(10), ..vicinity() };
+	let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity), vec![
+		(caller, account("")),
+		(H160::from_low_u64_be(TARGET), account("00")),
+	].into_iter().collect()));
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	executor.set_fee_policy(Arc::new(GenerousPolicy));
+
+	let result = block_on(executor.transact(Transaction {
+		caller,
+		action: TransactionAction::Call(H160::from_low_u64_be(TARGET)),
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: 100_000,
+		nonce: None,
+	}));
+	assert_eq!(result.reason, ExitReason::Fatal(ExitFatal::Other("invalid fee distribution")));
+	assert_eq!(block_on(executor.balance(caller)), U256::from(1_000_000_000u64));
+	assert_eq!(block_on(executor.balance(H160::default())), U256::zero());
+}
+
+#[test]
+fn calldata_floor() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let target = H160::from_low_u64_be(TARGET);
+	let config = Config { gas_transaction_floor_per_token: Some(10), ..Config::istanbul() };
+	let transaction = Transaction {
+		caller,
+		action: TransactionAction::Call(target),
+		value: U256::zero(),
+		data: vec![1; 100],
+		gas_limit: 100_000,
+		nonce: None,
+	};
+	assert_eq!(intrinsic_gas(&transaction, &config), 21_000 + 100 * 16);
+	assert_eq!(floor_gas(&transaction, &config), Some(21_000 + 400 * 10));
+	assert_eq!(floor_gas(&transaction, &Config::istanbul()), None);
+
+	let run = |gas_limit: usize| {
+		let backend = deploy("00");
+		let mut executor = StackExecutor::new(backend, 100_000, Arc::new(config.clone()));
+		let (reason, _) = call_target(&mut executor, vec![1; 100], gas_limit);
+		(reason, executor.used_gas())
+	};
+
+	assert_eq!(run(100_000).1, 25_000);
+	assert_eq!(run(24_999).0, ExitReason::Error(ExitError::OutOfGas));
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitReason};
+use evm::backend::MemoryBackend;
+use evm::executor::{ForkActivation, ForkSchedule, StackExecutor};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, account, block_on, call_target, prague_eips, vicinity};
+
+// Return the chain id, an Istanbul opcode.
+const CHAIN_ID: &str = "4660005260206000f3";
+
+fn schedule() -> ForkSchedule {
+	ForkSchedule::new(Config::frontier())
+		.with_fork(ForkActivation::Block(5), Config::istanbul())
+		.with_fork(ForkActivation::Timestamp(1_000), prague_eips())
+}
+
+fn run(number: u64, timestamp: u64) -> (ExitReason, Arc<Config>) {
+	let caller = H160::from_low_u64_be(CALLER);
+	let target = H160::from_low_u64_be(TARGET);
+	let mut vicinity = vicinity();
+	vicinity.block_number = U256::from(number);
+	vicinity.block_timestamp = U256::from(timestamp);
+	let state = vec![(caller, account("")), (target, account(CHAIN_ID))].into_iter().collect();
+	let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity), state));
+
+	let schedule = schedule();
+	let mut executor = block_on(StackExecutor::new_for_block(backend, 100_000, &schedule)).unwrap();
+	let (reason, _) = call_target(&mut executor, Vec::new(), 100_000);
+	(reason, schedule.config_at(number, timestamp))
+}
+
+#[test]
+fn selects_config_of_block() {
+	let (reason, config) = run(4, 0);
+	assert!(matches!(reason, ExitReason::Error(_)), "{:?}", reason);
+	assert!(!config.has_chain_id);
+
+	let (reason, config) = run(5, 0);
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert!(!config.has_set_code);
+
+	let (reason, config) = run(5, 1_000);
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert!(config.has_set_code);
+}
+
+#[test]
+fn genesis_config_before_first_fork() {
+	let schedule = ForkSchedule::new(Config::istanbul())
+		.with_fork(ForkActivation::Timestamp(10), prague_eips());
+	assert!(!schedule.config_at(0, 9).has_set_code);
+	assert!(schedule.config_at(0, 10).has_set_code);
+	assert_eq!(schedule.forks().len(), 1);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::executor::{AddressUsage, StackExecutor, Transaction, TransactionAction};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, account, backend, block_on};
+
+const LIBRARY: u64 = 0xcc;
+
+// SLOAD slot 0, SSTORE 1 to slot 1, then CALL `LIBRARY` with all gas.
+const TARGET_CODE: &str = "60005450600160015560006000600060006000\
+	7300000000000000000000000000000000000000cc5af15000";
+// SLOAD slot 0.
+const LIBRARY_CODE: &str = "6000545000";
+
+fn transaction() -> Transaction {
+	Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action: TransactionAction::Call(H160::from_low_u64_be(TARGET)),
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: 100_000,
+		nonce: None,
+	}
+}
+
+fn executor() -> StackExecutor<evm::backend::MemoryBackend> {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(TARGET_CODE)),
+		(H160::from_low_u64_be(LIBRARY), account(LIBRARY_CODE)),
+	]);
+	StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()))
+}
+
+#[test]
+fn disabled_by_default() {
+	let mut executor = executor();
+	assert_eq!(block_on(executor.transact(transaction())).gas_attribution, None);
+	assert!(executor.gas_attribution().is_none());
+}
+
+#[test]
+fn attributes_frames_and_storage_accesses() {
+	let mut executor = executor();
+	executor.enable_gas_attribution();
+	let result = block_on(executor.transact(transaction()));
+	assert!(result.is_succeed(), "{:?}", result.reason);
+
+	let attribution = result.gas_attribution.unwrap();
+	let target = attribution.addresses[&H160::from_low_u64_be(TARGET)];
+	let library = attribution.addresses[&H160::from_low_u64_be(LIBRARY)];
+	assert_eq!((target.calls, target.storage_reads, target.storage_writes), (1, 1, 1));
+	assert_eq!((library.calls, library.storage_reads, library.storage_writes), (1, 1, 0));
+	assert!(target.gas_used > library.gas_used);
+	assert_eq!(attribution.gas_used(), executor.call_traces()[0].gas_used);
+}
+
+#[test]
+fn results_are_per_transaction() {
+	let mut executor = executor();
+	executor.enable_gas_attribution();
+	let first = block_on(executor.transact(transaction())).gas_attribution.unwrap();
+	let second = block_on(executor.transact(transaction())).gas_attribution.unwrap();
+
+	let library = H160::from_low_u64_be(LIBRARY);
+	assert_eq!(first.addresses[&library], second.addresses[&library]);
+	let total = executor.gas_attribution().unwrap().addresses[&library];
+	assert_eq!(total, AddressUsage {
+		calls: 2,
+		gas_used: 2 * first.addresses[&library].gas_used,
+		storage_reads: 2,
+		storage_writes: 0,
+	});
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use evm::{Config, ExitError, ExitReason, ExternalOpcode, Opcode};
+use evm::executor::{GasAction, GasHook, StackExecutor};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, backend, block_on};
+
+const CALLER: u64 = 0xf0;
+const TARGET: u64 = 0x100;
+const INNER: u64 = 0x200;
+
+// PUSH1 1, PUSH1 2, ADD, STOP.
+const ADD: &str = "600160020100";
+// Store 42 at slot 0.
+const STORE: &str = "602a60005500";
+// Call INNER with all gas.
+const CALL_INNER: &str = "600060006000600060006102005af100";
+
+/// Charged opcode, its cost and the remaining gas.
+type Charge = (Result<Opcode, ExternalOpcode>, usize, usize);
+
+/// Record the charged instructions.
+#[derive(Default)]
+struct Recorder(Mutex<Vec<Charge>>);
+
+impl GasHook for Recorder {
+	fn after_charge(&self, opcode: Result<Opcode, ExternalOpcode>, cost: usize, remaining: usize) -> GasAction {
+		self.0.lock().unwrap().push((opcode, cost, remaining));
+		GasAction::Continue
+	}
+}
+
+/// Sponsor or forbid storage writes.
+struct Storage(GasAction);
+
+impl GasHook for Storage {
+	fn after_charge(&self, opcode: Result<Opcode, ExternalOpcode>, cost: usize, _remaining: usize) -> GasAction {
+		match (opcode, self.0) {
+			(Err(ExternalOpcode::SStore), GasAction::Grant(_)) => GasAction::Grant(cost),
+			(Err(ExternalOpcode::SStore), action) => action,
+			_ => GasAction::Continue,
+		}
+	}
+}
+
+fn run(code: &str, hook: Option<Arc<dyn GasHook>>) -> (ExitReason, usize, Option<H256>) {
+	let caller = H160::from_low_u64_be(CALLER);
+	let target = H160::from_low_u64_be(TARGET);
+	let backend = backend(vec![
+		(caller, account("")),
+		(target, account(code)),
+		(H160::from_low_u64_be(INNER), account(ADD)),
+	]);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	if let Some(hook) = hook {
+		executor.set_gas_hook(hook);
+	}
+
+	let (reason, _) = block_on(executor.transact_call(caller, target, U256::zero(), Vec::new(), 100_000));
+	let used_gas = executor.used_gas();
+	let stored = block_on(executor.account_mut(target)).storage.get(&H256::zero()).copied();
+	(reason, used_gas, stored)
+}
+
+#[test]
+fn observes_charged_instructions() {
+	let recorder = Arc::new(Recorder::default());
+	let (reason, used_gas, _) = run(ADD, Some(recorder.clone()));
+	assert!(reason.is_succeed());
+	assert_eq!(used_gas, 21000 + 9);
+
+	let charged = recorder.0.lock().unwrap().clone();
+	let remaining = 100_000 - 21000;
+	assert_eq!(charged, vec![
+		(Ok(Opcode::Push(1)), 3, remaining - 3),
+		(Ok(Opcode::Push(1)), 3, remaining - 6),
+		(Ok(Opcode::Add), 3, remaining - 9),
+		(Ok(Opcode::Stop), 0, remaining - 9),
+	]);
+}
+
+#[test]
+fn observes_nested_frames() {
+	let recorder = Arc::new(Recorder::default());
+	let (reason, _, _) = run(CALL_INNER, Some(recorder.clone()));
+	assert!(reason.is_succeed());
+
+	let opcodes = recorder.0.lock().unwrap().iter().map(|(opcode, _, _)| *opcode).collect::<Vec<_>>();
+	assert!(opcodes.contains(&Err(ExternalOpcode::Call)));
+	assert!(opcodes.contains(&Ok(Opcode::Add)));
+}
+
+#[test]
+fn grants_sponsor_instructions() {
+	let (_, unsponsored, _) = run(STORE, None);
+	let (reason, sponsored, stored) = run(STORE, Some(Arc::new(Storage(GasAction::Grant(0)))));
+	assert!(reason.is_succeed());
+	assert_eq!(stored, Some(H256::from_low_u64_be(42)));
+	assert_eq!(unsponsored - sponsored, 20000);
+}
+
+#[test]
+fn terminates_execution() {
+	let (reason, used_gas, stored) = run(STORE, Some(Arc::new(Storage(
+		GasAction::Terminate(ExitError::Other("storage writes are disabled")),
+	))));
+	assert_eq!(reason, ExitReason::Error(ExitError::Other("storage writes are disabled")));
+	assert_eq!(used_gas, 100_000);
+	assert_eq!(stored, None);
+}
@@ -0,0 +1,68 @@
+use evm::Config;
+use evm::gasometer::{ConfigGasTable, DynamicCost};
+
+#[test]
+fn static_costs_follow_the_fork() {
+	let frontier = Config::frontier().gas_table();
+	let istanbul = Config::istanbul().gas_table();
+
+	// SLOAD
+	assert_eq!(frontier.get(0x54).unwrap().static_cost, Some(50));
+	assert_eq!(istanbul.get(0x54).unwrap().static_cost, Some(800));
+	// ADD
+	assert_eq!(istanbul.get(0x01).unwrap().static_cost, Some(3));
+	assert_eq!(istanbul.get(0x01).unwrap().dynamic_cost, None);
+	// MSTORE
+	assert!(istanbul.get(0x52).unwrap().memory_expansion);
+	assert!(!istanbul.get(0x01).unwrap().memory_expansion);
+}
+
+#[test]
+fn dynamic_costs_are_identified() {
+	let table = Config::istanbul().gas_table();
+
+	for (byte, formula) in [
+		(0x20, DynamicCost::Sha3),
+		(0x0a, DynamicCost::Exp),
+		(0x37, DynamicCost::VeryLowCopy),
+		(0x55, DynamicCost::SStore),
+		(0xa2, DynamicCost::Log),
+		(0xf1, DynamicCost::Call),
+		(0xf5, DynamicCost::Create2),
+		(0xff, DynamicCost::Suicide),
+	].iter() {
+		let entry = table.get(*byte).unwrap();
+		assert_eq!(entry.dynamic_cost, Some(*formula));
+		assert_eq!(entry.static_cost, None);
+	}
+}
+
+#[test]
+fn opcodes_invalid_under_the_fork_are_absent() {
+	let frontier = Config::frontier().gas_table();
+	let istanbul = Config::istanbul().gas_table();
+
+	// CREATE2, SHL, CHAINID, REVERT
+	for byte in [0xf5, 0x1b, 0x46, 0xfd].iter() {
+		assert!(frontier.get(*byte).is_none());
+		assert!(istanbul.get(*byte).is_some());
+	}
+	assert!(istanbul.get(0x0c).is_none());
+	assert!(frontier.len() < istanbul.len());
+	assert!(istanbul.iter().zip(istanbul.iter().skip(1)).all(|(a, b)| a.byte < b.byte));
+}
+
+#[test]
+fn overrides_replace_the_cost() {
+	let mut config = Config::istanbul();
+	config.gas_overrides[0x20] = Some(7);
+	config.gas_overrides[0x0c] = Some(7);
+	config.gas_overrides[0xf1] = Some(7);
+	let table = config.gas_table();
+
+	let sha3 = table.get(0x20).unwrap();
+	assert_eq!(sha3.static_cost, Some(7));
+	assert_eq!(sha3.dynamic_cost, None);
+	assert!(table.get(0x0c).is_none());
+	assert_eq!(table.get(0xf1).unwrap().static_cost, None);
+}
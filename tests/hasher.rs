@@ -0,0 +1,101 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::{Config, CreateScheme, ExitReason, ExitSucceed};
+use evm::backend::{
+	Apply, ApplyBackend, Basic, BlockOverrides, MemoryBackend, OverlayBackend, Witness, state_root, state_root_with,
+};
+use evm::executor::StackExecutor;
+use evm::hasher::{Hasher, Keccak256Hasher};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, account, block_on, call_target, vicinity};
+
+const OTHER: u64 = 0xbb;
+
+// EXTCODEHASH(OTHER) and SHA3 of the empty preimage, returned as two
+// words.
+const HASHES: &str = "7300000000000000000000000000000000000000bb3f600052600060002060205260406000f3";
+
+/// Keccak256 with every bit inverted.
+#[derive(Debug)]
+struct Inverted;
+
+impl Hasher for Inverted {
+	fn hash(&self, data: &[u8]) -> H256 {
+		let mut digest = Keccak256Hasher.hash(data);
+		digest.0.iter_mut().for_each(|byte| *byte = !*byte);
+		digest
+	}
+}
+
+fn memory_backend() -> MemoryBackend {
+	let mut backend = MemoryBackend::new(Arc::new(vicinity()), BTreeMap::from([
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(HASHES)),
+		(H160::from_low_u64_be(OTHER), account("00")),
+	]));
+	backend.set_hasher(Arc::new(Inverted));
+	backend
+}
+
+#[test]
+fn executor_hashes_with_custom_hasher() {
+	let mut executor = StackExecutor::new(Arc::new(memory_backend()), 1_000_000, Arc::new(Config::istanbul()));
+	executor.set_hasher(Arc::new(Inverted));
+
+	let (reason, out) = call_target(&mut executor, Vec::new(), 1_000_000);
+
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(H256::from_slice(&out[..32]), Inverted.hash(&[0x00]));
+	assert_eq!(H256::from_slice(&out[32..]), Inverted.hash(&[]));
+
+	let caller = H160::from_low_u64_be(CALLER);
+	let scheme = CreateScheme::Create2 { caller, code_hash: H256::zero(), salt: H256::zero() };
+	let mut preimage = vec![0xff];
+	preimage.extend_from_slice(caller.as_bytes());
+	preimage.extend_from_slice(&[0; 64]);
+	assert_eq!(block_on(executor.create_address(scheme)), H160::from(Inverted.hash(&preimage)));
+}
+
+#[test]
+fn proofs_follow_backend_hasher() {
+	let backend = memory_backend();
+	let keccak_root = state_root(backend.state());
+	assert_eq!(state_root_with(backend.state(), &Keccak256Hasher), keccak_root);
+
+	let root = state_root_with(backend.state(), &Inverted);
+	assert_ne!(root, keccak_root);
+	let proof = backend.prove_account(H160::from_low_u64_be(TARGET), &[H256::zero()]);
+	assert_eq!(proof.code_hash, Inverted.hash(&hex::decode(HASHES).unwrap()));
+	assert_eq!(proof.verify_with(root, &Inverted), Ok(()));
+	assert!(proof.verify(keccak_root).is_err());
+	assert!(backend.dump_json().starts_with(&format!("{{\"root\":\"{:?}\"", root)));
+}
+
+#[test]
+fn overlay_hashes_modified_code_with_custom_hasher() {
+	let other = H160::from_low_u64_be(OTHER);
+	let mut overlay = OverlayBackend::new(Arc::new(memory_backend()), BlockOverrides::default());
+	overlay.set_hasher(Arc::new(Inverted));
+	block_on(overlay.apply(vec![Apply::Modify {
+		address: other,
+		basic: Basic { balance: U256::zero(), nonce: U256::one() },
+		code: Some(vec![0x60, 0x01]),
+		storage: BTreeMap::new(),
+		reset_storage: false,
+	}], Vec::new(), false));
+	let mut executor = StackExecutor::new(Arc::new(overlay), 1_000_000, Arc::new(Config::istanbul()));
+	executor.set_hasher(Arc::new(Inverted));
+
+	let (reason, out) = call_target(&mut executor, Vec::new(), 1_000_000);
+
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(H256::from_slice(&out[..32]), Inverted.hash(&[0x60, 0x01]));
+
+	let mut witness = Witness::default();
+	witness.insert_account_with(other, &account("6001"), &Inverted);
+	assert_eq!(witness.accounts[&other].code_hash, Some(Inverted.hash(&[0x60, 0x01])));
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "testutil")]
+
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::{Apply, MemoryBackend};
+use evm::executor::StackExecutor;
+use evm::testutil::{random_state, random_tx};
+
+use common::{block_on, vicinity};
+
+const CASES: u64 = 256;
+
+#[test]
+fn generators_are_deterministic() {
+	assert_eq!(random_state(7), random_state(7));
+	assert_ne!(random_state(7), random_state(8));
+	assert_eq!(random_tx(7).data, random_tx(7).data);
+}
+
+#[test]
+fn gas_stays_within_limit() {
+	for seed in 0..CASES {
+		let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity()), random_state(seed)));
+		let transaction = random_tx(seed);
+		let gas_limit = transaction.gas_limit;
+		let mut executor = StackExecutor::new(backend, gas_limit, Arc::new(Config::istanbul()));
+
+		block_on(executor.transact(transaction));
+		assert!(executor.gas() <= gas_limit, "seed {}", seed);
+		assert!(executor.used_gas() <= gas_limit, "seed {}", seed);
+	}
+}
+
+#[test]
+fn failed_transactions_only_bump_caller_nonce() {
+	for seed in 0..CASES {
+		let state = random_state(seed);
+		let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity()), state.clone()));
+		let transaction = random_tx(seed);
+		let caller = transaction.caller;
+		let mut executor = StackExecutor::new(backend, transaction.gas_limit, Arc::new(Config::istanbul()));
+
+		let reason = block_on(executor.transact(transaction)).reason;
+		if reason.is_succeed() {
+			continue
+		}
+
+		let (applies, logs) = executor.deconstruct();
+		assert_eq!(logs.into_iter().count(), 0, "seed {}", seed);
+		for apply in applies {
+			match apply {
+				Apply::Modify { address, basic, code, storage, reset_storage } => {
+					let original = state.get(&address).cloned().unwrap_or_default();
+					let nonce = if address == caller { original.nonce + 1 } else { original.nonce };
+					assert_eq!(basic.balance, original.balance, "seed {}", seed);
+					assert!(basic.nonce == nonce || basic.nonce == original.nonce, "seed {}", seed);
+					assert!(code.is_none(), "seed {}", seed);
+					assert_eq!(storage.into_iter().count(), 0, "seed {}", seed);
+					assert!(!reset_storage, "seed {}", seed);
+				},
+				Apply::Delete { address } => panic!("seed {}: deleted {:?}", seed, address),
+			}
+		}
+	}
+}
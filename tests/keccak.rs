@@ -0,0 +1,58 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::MemoryBackend;
+use evm::executor::StackExecutor;
+use evm::layout::{mapping_slot, mapping_slots};
+use primitive_types::H256;
+
+use common::{call_target, deploy};
+
+// Hash the mapping preimage of key 1 at slot 0 three times, returning the
+// last digest.
+const HASH_THRICE: &str = concat!(
+	"60016000526000602052",
+	"60406000205060406000205060406000",
+	"2060005260206000f3",
+);
+
+fn run(cache: bool) -> (Vec<u8>, StackExecutor<MemoryBackend>) {
+	let backend = deploy(HASH_THRICE);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	if cache {
+		executor.enable_keccak_cache();
+	}
+
+	let (reason, output) = call_target(&mut executor, Vec::new(), 100_000);
+	assert!(reason.is_succeed(), "{:?}", reason);
+	(output, executor)
+}
+
+#[test]
+fn memoizes_sha3() {
+	let expected = mapping_slot(H256::zero(), H256::from_low_u64_be(1).as_bytes());
+
+	let (output, executor) = run(true);
+	assert_eq!(output, expected.as_bytes().to_vec());
+	let cache = executor.keccak_cache().unwrap();
+	// The constant-time feature bypasses the cache.
+	let counts = if cfg!(feature = "constant-time") { (0, 0, 0) } else { (2, 1, 1) };
+	assert_eq!((cache.hits(), cache.misses(), cache.len()), counts);
+
+	let (uncached, executor) = run(false);
+	assert_eq!(uncached, output);
+	assert!(executor.keccak_cache().is_none());
+}
+
+#[test]
+fn batch_mapping_slots() {
+	let slot = H256::from_low_u64_be(3);
+	let keys = (0..4).map(H256::from_low_u64_be).collect::<Vec<_>>();
+
+	let slots = mapping_slots(slot, &keys);
+	for (key, value) in keys.iter().zip(slots) {
+		assert_eq!(value, mapping_slot(slot, key.as_bytes()));
+	}
+}
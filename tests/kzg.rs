@@ -0,0 +1,124 @@
+#![cfg(feature = "kzg")]
+
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError, ExitReason};
+use evm::executor::StackExecutor;
+use evm::precompiles::{
+	kzg_to_versioned_hash, PointEvaluation, BLS_MODULUS, POINT_EVALUATION_ADDRESS,
+	POINT_EVALUATION_GAS,
+};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, account, backend, block_on};
+
+// Compressed point at infinity, the commitment to and proof of the zero
+// polynomial.
+const INFINITY: &str = "c00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000";
+// Compressed `[5]G1`, the commitment to the constant polynomial `5`.
+const FIVE: &str = "b0e7791fb972fe014159aa33a98622da3cdc98ff707965e536d8636b5fcc5ac7a91a8c46e59a00dca575af0f18fb13dc";
+
+// `verify_kzg_proof_case_correct_proof_4_4` of the consensus spec tests,
+// also used by the point evaluation precompile tests of go-ethereum.
+const COMMITMENT: &str = "8f59a8d2a1a625a17f3fea0fe5eb8c896db3764f3185481bc22f91b4aaffcca25f26936857bc3a7c2539ea8ec3a952b7";
+const Z: &str = "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000000";
+const Y: &str = "1522a4a7f34e1ea350ae07c29c96c7e79655aa926122e95fe69fcbd932ca49e9";
+const PROOF: &str = "a62ad71d14c5719385c0686f1871430475bf3a00f0aa3f7b8dd99a9abc2160744faf0070725e00b60ad9a026a15b1a8c";
+const OUTPUT: &str = "000000000000000000000000000000000000000000000000000000000000100073eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001";
+
+fn word(value: u64) -> String {
+	format!("{:064x}", value)
+}
+
+fn input(commitment: &str, z: &str, y: &str, proof: &str) -> Vec<u8> {
+	let commitment = hex::decode(commitment).unwrap();
+	let mut input = kzg_to_versioned_hash(&commitment).to_vec();
+	input.extend_from_slice(&hex::decode(z).unwrap());
+	input.extend_from_slice(&hex::decode(y).unwrap());
+	input.extend_from_slice(&commitment);
+	input.extend_from_slice(&hex::decode(proof).unwrap());
+	input
+}
+
+fn verifies(input: &[u8]) -> bool {
+	match PointEvaluation::new().evaluate(input, None) {
+		Ok(_) => true,
+		Err(ExitError::Other("invalid kzg proof")) => false,
+		Err(err) => panic!("unexpected error {:?}", err),
+	}
+}
+
+#[test]
+fn versioned_hash() {
+	assert_eq!(
+		hex::encode(kzg_to_versioned_hash(&hex::decode(INFINITY).unwrap())),
+		"010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c444014",
+	);
+}
+
+#[test]
+fn evaluates_valid_proof() {
+	let (succeed, output, gas) = PointEvaluation::new()
+		.evaluate(&input(COMMITMENT, Z, Y, PROOF), None)
+		.unwrap();
+	assert!(matches!(succeed, evm::ExitSucceed::Returned));
+	assert_eq!(gas, POINT_EVALUATION_GAS);
+	assert_eq!(hex::encode(&output), OUTPUT);
+	assert_eq!(U256::from_big_endian(&output[..32]), U256::from(4096));
+	assert_eq!(output[32..], BLS_MODULUS);
+}
+
+#[test]
+fn verifies_against_the_mainnet_setup() {
+	assert!(verifies(&input(COMMITMENT, Z, Y, PROOF)));
+	assert!(verifies(&input(INFINITY, Z, &word(0), INFINITY)));
+	assert!(verifies(&input(FIVE, &word(3), &word(5), INFINITY)));
+
+	assert!(!verifies(&input(COMMITMENT, Z, &word(0), PROOF)));
+	assert!(!verifies(&input(COMMITMENT, &word(1), Y, PROOF)));
+	assert!(!verifies(&input(INFINITY, &word(0), &word(1), INFINITY)));
+	assert!(!verifies(&input(FIVE, &word(1), &word(0), INFINITY)));
+	assert!(!verifies(&input(COMMITMENT, Z, Y, COMMITMENT)));
+	// Without the compression flag.
+	assert!(!verifies(&input(&format!("0{}", &COMMITMENT[1..]), Z, Y, PROOF)));
+}
+
+#[test]
+fn rejects_invalid_inputs() {
+	let precompile = PointEvaluation::default();
+	let other = |reason: &'static str| Err(ExitError::Other(reason));
+	let valid = input(COMMITMENT, Z, Y, PROOF);
+
+	assert_eq!(precompile.evaluate(&valid[1..], None), other("invalid point evaluation input length"));
+	assert_eq!(precompile.evaluate(&input(COMMITMENT, &hex::encode(BLS_MODULUS), Y, PROOF), None), other("non-canonical field element"));
+	assert_eq!(precompile.evaluate(&input(COMMITMENT, Z, &hex::encode(BLS_MODULUS), PROOF), None), other("non-canonical field element"));
+	assert_eq!(precompile.evaluate(&valid, Some(POINT_EVALUATION_GAS - 1)), Err(ExitError::OutOfGas));
+
+	let mut mismatched = valid;
+	mismatched[0] = 0x02;
+	assert_eq!(precompile.evaluate(&mismatched, None), other("mismatched versioned hash"));
+}
+
+#[test]
+fn installed_as_async_precompile() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let backend = backend(vec![(caller, account(""))]);
+	let mut executor = StackExecutor::new(backend, 200_000, Arc::new(Config::istanbul()));
+	executor.set_async_precompile(Arc::new(PointEvaluation::new()));
+
+	let data = input(COMMITMENT, Z, Y, PROOF);
+	let data_gas = data.iter().map(|byte| if *byte == 0 { 4 } else { 16 }).sum::<usize>();
+	let (reason, output) = block_on(executor.transact_call(
+		caller, POINT_EVALUATION_ADDRESS, U256::zero(), data, 200_000,
+	));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(output[32..], BLS_MODULUS);
+	assert_eq!(executor.used_gas(), 21000 + data_gas + POINT_EVALUATION_GAS);
+
+	let (reason, _) = block_on(executor.transact_call(
+		caller, POINT_EVALUATION_ADDRESS, U256::zero(), input(COMMITMENT, Z, &word(0), PROOF), 200_000,
+	));
+	assert!(matches!(reason, ExitReason::Error(ExitError::Other(_))), "{:?}", reason);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use evm::layout::{LayoutError, PathSegment, StorageEntry, StorageLayout, StorageType, StorageValue, mapping_slot};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, backend, block_on};
+
+const TOKEN: u64 = 0xaa;
+
+fn entry(label: &str, slot: &str, offset: usize, ty: &str) -> StorageEntry {
+	StorageEntry { label: label.into(), offset, slot: slot.into(), ty: ty.into() }
+}
+
+fn ty(encoding: &str, label: &str, size: &str) -> StorageType {
+	StorageType {
+		encoding: encoding.into(),
+		label: label.into(),
+		number_of_bytes: size.into(),
+		key: None,
+		value: None,
+		base: None,
+		members: None,
+	}
+}
+
+/// `mapping(address => uint256) balances; string name; bool paused; address owner;`
+fn layout() -> StorageLayout {
+	let mut types = BTreeMap::new();
+	types.insert("t_address".into(), ty("inplace", "address", "20"));
+	types.insert("t_bool".into(), ty("inplace", "bool", "1"));
+	types.insert("t_string_storage".into(), ty("bytes", "string", "32"));
+	types.insert("t_uint256".into(), ty("inplace", "uint256", "32"));
+	types.insert("t_mapping(t_address,t_uint256)".into(), StorageType {
+		key: Some("t_address".into()),
+		value: Some("t_uint256".into()),
+		..ty("mapping", "mapping(address => uint256)", "32")
+	});
+
+	StorageLayout {
+		storage: vec![
+			entry("balances", "0", 0, "t_mapping(t_address,t_uint256)"),
+			entry("name", "1", 0, "t_string_storage"),
+			entry("paused", "2", 0, "t_bool"),
+			entry("owner", "2", 1, "t_address"),
+		],
+		types,
+	}
+}
+
+#[test]
+fn reads_token_state() {
+	let holder = H160::repeat_byte(0x11);
+	let owner = H160::repeat_byte(0x22);
+	let key = H256::from(holder);
+
+	let mut token = account("");
+	token.storage.insert(mapping_slot(H256::zero(), key.as_bytes()), H256::from_low_u64_be(500));
+	let mut name = H256::zero();
+	name[..5].copy_from_slice(b"Token");
+	name.as_bytes_mut()[31] = 10;
+	token.storage.insert(H256::from_low_u64_be(1), name);
+	let mut packed = H256::zero();
+	packed[11..31].copy_from_slice(owner.as_bytes());
+	packed.as_bytes_mut()[31] = 1;
+	token.storage.insert(H256::from_low_u64_be(2), packed);
+
+	let address = H160::from_low_u64_be(TOKEN);
+	let backend = backend(vec![(address, token)]);
+	let layout = layout();
+	let read = |path: &[PathSegment]| block_on(layout.read(&*backend, address, path)).unwrap();
+
+	assert_eq!(
+		read(&[PathSegment::Field("balances"), PathSegment::Key(key.as_bytes())]),
+		StorageValue::Uint(U256::from(500)),
+	);
+	assert_eq!(read(&[PathSegment::Field("name")]), StorageValue::String("Token".into()));
+	assert_eq!(read(&[PathSegment::Field("paused")]), StorageValue::Bool(true));
+	assert_eq!(read(&[PathSegment::Field("owner")]), StorageValue::Address(owner));
+}
+
+#[test]
+fn malformed_sizes_are_errors() {
+	let mut layout = layout();
+	layout.types.insert("t_empty".into(), ty("inplace", "uint256", "0"));
+	layout.types.insert("t_huge".into(), ty("inplace", "uint256", "100000000000000000000000000000000"));
+	layout.types.insert("t_array(t_empty)dyn_storage".into(), StorageType {
+		base: Some("t_empty".into()),
+		..ty("dynamic_array", "uint256[]", "32")
+	});
+	layout.storage.push(entry("empties", "3", 0, "t_array(t_empty)dyn_storage"));
+	layout.storage.push(entry("huge", "4", 0, "t_huge"));
+
+	let address = H160::from_low_u64_be(TOKEN);
+	let mut token = account("");
+	// A string whose length does not fit in memory.
+	token.storage.insert(H256::from_low_u64_be(1), H256::repeat_byte(0xff));
+	let backend = backend(vec![(address, token)]);
+	let read = |path: &[PathSegment]| block_on(layout.read(&*backend, address, path));
+
+	assert_eq!(
+		layout.locate(&[PathSegment::Field("empties"), PathSegment::Index(U256::one())]),
+		Err(LayoutError::InvalidSize("t_empty".into())),
+	);
+	assert_eq!(read(&[PathSegment::Field("huge")]), Err(LayoutError::InvalidSize("t_huge".into())));
+	assert!(matches!(read(&[PathSegment::Field("name")]), Err(LayoutError::InvalidSize(_))));
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::executor::StackExecutor;
+
+use common::{call_target, deploy};
+
+// Return the word at memory offset zero as first read, after writing 42
+// there.
+const DIRTY_MEMORY: &str = "600051602a60005260205260206020f3";
+
+#[test]
+fn frames_reuse_clean_buffers() {
+	let backend = deploy(DIRTY_MEMORY);
+	let pool = StackExecutor::new(backend.clone(), 0, Arc::new(Config::istanbul())).memory_pool();
+
+	for _ in 0..3 {
+		let mut executor = StackExecutor::new(backend.clone(), 100_000, Arc::new(Config::istanbul()));
+		executor.set_memory_pool(pool.clone());
+
+		let (reason, output) = call_target(&mut executor, Vec::new(), 100_000);
+		assert!(reason.is_succeed());
+		assert_eq!(output, vec![0; 32]);
+	}
+
+	let pool = pool.lock().unwrap();
+	assert_eq!(pool.reused(), 2);
+	assert!(!pool.is_empty());
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitFatal, ExitReason};
+use evm::backend::MemoryBackend;
+use evm::executor::{AccessSet, StackExecutor};
+use primitive_types::{H160, H256};
+
+use common::{CALLER, TARGET, call_target, deploy};
+
+// Load slots 1 and 2, then stop.
+const LOAD_TWO: &str = "60015450600254500000";
+
+fn run(allowlist: Option<AccessSet>) -> (ExitReason, StackExecutor<MemoryBackend>) {
+	let backend = deploy(LOAD_TWO);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	executor.set_state_allowlist(allowlist);
+
+	let (reason, _) = call_target(&mut executor, Vec::new(), 100_000);
+	(reason, executor)
+}
+
+fn slot(index: u64) -> (H160, H256) {
+	(H160::from_low_u64_be(TARGET), H256::from_low_u64_be(index))
+}
+
+#[test]
+fn touched_set_is_exact() {
+	let (reason, executor) = run(None);
+	assert!(reason.is_succeed(), "{:?}", reason);
+
+	let accessed = executor.accessed();
+	assert_eq!(
+		accessed.accounts.into_iter().collect::<Vec<_>>(),
+		vec![H160::from_low_u64_be(TARGET), H160::from_low_u64_be(CALLER)],
+	);
+	assert_eq!(accessed.storage.into_iter().collect::<Vec<_>>(), vec![slot(1), slot(2)]);
+	assert!(executor.missing_state().is_empty());
+}
+
+#[test]
+fn access_outside_allowlist_aborts() {
+	let (_, executor) = run(None);
+	let mut allowlist = executor.accessed();
+	allowlist.storage.remove(&slot(2));
+
+	let (reason, executor) = run(Some(allowlist.clone()));
+	assert_eq!(reason, ExitReason::Fatal(ExitFatal::MissingState));
+	assert_eq!(executor.missing_state().storage.into_iter().collect::<Vec<_>>(), vec![slot(2)]);
+
+	allowlist.cleared_storage.insert(H160::from_low_u64_be(TARGET));
+	let (reason, _) = run(Some(allowlist));
+	assert!(reason.is_succeed(), "{:?}", reason);
+}
+
+#[test]
+fn missing_account_aborts() {
+	let mut allowlist = AccessSet::default();
+	allowlist.accounts.insert(H160::from_low_u64_be(CALLER));
+
+	let (reason, executor) = run(Some(allowlist));
+	assert_eq!(reason, ExitReason::Fatal(ExitFatal::MissingState));
+	assert!(executor.missing_state().accounts.contains(&H160::from_low_u64_be(TARGET)));
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "no-send")]
+
+mod common;
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::{Backend, Basic, MemoryAccount};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+use common::{account, block_on};
+
+const CALLER: u64 = 0xf0;
+const TARGET: u64 = 0x100;
+
+// Return the value at slot 0.
+const LOAD: &str = "60005460005260206000f3";
+
+/// Backend over single-threaded shared state, counting its reads.
+struct LocalBackend {
+	state: Rc<RefCell<BTreeMap<H160, MemoryAccount>>>,
+	reads: Rc<Cell<usize>>,
+}
+
+impl LocalBackend {
+	fn account<T>(&self, address: H160, f: impl FnOnce(&MemoryAccount) -> T) -> Option<T> {
+		self.reads.set(self.reads.get() + 1);
+		self.state.borrow().get(&address).map(f)
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl Backend for LocalBackend {
+	type Error = Infallible;
+
+	async fn gas_price(&self) -> Result<U256, Infallible> { Ok(U256::zero()) }
+	async fn origin(&self) -> Result<H160, Infallible> { Ok(H160::zero()) }
+	async fn block_hash(&self, _number: U256) -> Result<H256, Infallible> { Ok(H256::zero()) }
+	async fn block_number(&self) -> Result<U256, Infallible> { Ok(U256::zero()) }
+	async fn block_coinbase(&self) -> Result<H160, Infallible> { Ok(H160::zero()) }
+	async fn block_timestamp(&self) -> Result<U256, Infallible> { Ok(U256::zero()) }
+	async fn block_difficulty(&self) -> Result<U256, Infallible> { Ok(U256::zero()) }
+	async fn block_gas_limit(&self) -> Result<U256, Infallible> { Ok(U256::from(u64::MAX)) }
+	async fn chain_id(&self) -> Result<U256, Infallible> { Ok(U256::one()) }
+
+	async fn exists(&self, address: H160) -> Result<bool, Infallible> {
+		Ok(self.account(address, |_| ()).is_some())
+	}
+
+	async fn basic(&self, address: H160) -> Result<Basic, Infallible> {
+		Ok(self.account(address, |a| Basic { balance: a.balance, nonce: a.nonce }).unwrap_or_default())
+	}
+
+	async fn code_hash(&self, address: H160) -> Result<H256, Infallible> {
+		Ok(self.account(address, |a| H256::from_slice(&Keccak256::digest(&a.code)))
+			.unwrap_or_default())
+	}
+
+	async fn code_size(&self, address: H160) -> Result<usize, Infallible> {
+		Ok(self.account(address, |a| a.code.len()).unwrap_or(0))
+	}
+
+	async fn code(&self, address: H160) -> Result<Vec<u8>, Infallible> {
+		Ok(self.account(address, |a| a.code.to_vec()).unwrap_or_default())
+	}
+
+	async fn storage(&self, address: H160, index: H256) -> Result<H256, Infallible> {
+		Ok(self.account(address, |a| a.storage.get(&index).copied())
+			.flatten()
+			.unwrap_or_default())
+	}
+}
+
+#[test]
+fn executes_over_non_send_backend() {
+	let mut target = account(LOAD);
+	target.storage.insert(H256::zero(), H256::from_low_u64_be(42));
+	let state = Rc::new(RefCell::new(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), target),
+	].into_iter().collect::<BTreeMap<_, _>>()));
+	let reads = Rc::new(Cell::new(0));
+
+	// Executors hold their backend in an `Arc` whatever its bounds.
+	#[allow(clippy::arc_with_non_send_sync)]
+	let executor = || StackExecutor::new(
+		Arc::new(LocalBackend { state: state.clone(), reads: reads.clone() }),
+		100_000,
+		Arc::new(Config::istanbul()),
+	);
+	let (reason, output) = block_on(executor().transact_call(
+		H160::from_low_u64_be(CALLER),
+		H160::from_low_u64_be(TARGET),
+		U256::zero(),
+		Vec::new(),
+		100_000,
+	));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(output, H256::from_low_u64_be(42).as_bytes());
+	assert!(reads.get() > 0);
+
+	// The shared state can be changed between executions.
+	state.borrow_mut().get_mut(&H160::from_low_u64_be(TARGET)).unwrap()
+		.storage.insert(H256::zero(), H256::from_low_u64_be(7));
+	let (_, output) = block_on(executor().transact_call(
+		H160::from_low_u64_be(CALLER),
+		H160::from_low_u64_be(TARGET),
+		U256::zero(),
+		Vec::new(),
+		100_000,
+	));
+	assert_eq!(output, H256::from_low_u64_be(7).as_bytes());
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError, ExitReason};
+use evm::backend::MemoryBackend;
+use evm::executor::{StackExecutor, Transaction, TransactionAction, TxValidationError};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, block_on, deploy};
+
+fn executor(config: Config) -> StackExecutor<MemoryBackend> {
+	let backend = deploy("00");
+	StackExecutor::new(backend, 10_000_000, Arc::new(config))
+}
+
+fn transaction(action: TransactionAction, nonce: Option<u64>) -> Transaction {
+	Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action,
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: 100_000,
+		nonce: nonce.map(U256::from),
+	}
+}
+
+fn call(nonce: Option<u64>) -> Transaction {
+	transaction(TransactionAction::Call(H160::from_low_u64_be(TARGET)), nonce)
+}
+
+fn nonce(executor: &StackExecutor<MemoryBackend>) -> U256 {
+	block_on(executor.nonce(H160::from_low_u64_be(CALLER)))
+}
+
+#[test]
+fn matching_nonces_are_incremented() {
+	let mut executor = executor(Config::istanbul());
+	for (i, action) in [
+		TransactionAction::Call(H160::from_low_u64_be(TARGET)),
+		TransactionAction::Create,
+		TransactionAction::Create2(Default::default()),
+	].iter().enumerate() {
+		let result = block_on(executor.transact(transaction(*action, Some(1 + i as u64))));
+		assert!(result.is_succeed(), "{:?}", result.reason);
+	}
+	assert_eq!(nonce(&executor), U256::from(4));
+}
+
+#[test]
+fn mismatched_nonces_are_rejected() {
+	let mut executor = executor(Config::istanbul());
+	for got in [0, 2].iter().cloned() {
+		let result = block_on(executor.transact(call(Some(got))));
+		assert_eq!(result.reason, ExitReason::Error(ExitError::InvalidNonce {
+			expected: U256::one(),
+			got: U256::from(got),
+		}));
+		let (expected, got) = (U256::one(), U256::from(got));
+		assert_eq!(result.validation_error, Some(if got < expected {
+			TxValidationError::NonceTooLow { expected, got }
+		} else {
+			TxValidationError::NonceTooHigh { expected, got }
+		}));
+		assert_eq!(result.gas_used, 0);
+	}
+	assert_eq!(nonce(&executor), U256::one());
+
+	// A replayed transaction is rejected.
+	assert!(block_on(executor.transact(call(Some(1)))).is_succeed());
+	assert_eq!(
+		block_on(executor.transact(call(Some(1)))).reason,
+		ExitReason::Error(ExitError::InvalidNonce { expected: U256::from(2), got: U256::one() }),
+	);
+}
+
+#[test]
+fn nonce_checks_can_be_skipped() {
+	let mut config = Config::istanbul();
+	config.disable_nonce_check = true;
+	let mut executor = executor(config);
+	assert!(block_on(executor.transact(call(Some(7)))).is_succeed());
+
+	let mut executor = self::executor(Config::istanbul());
+	assert!(block_on(executor.transact(call(None))).is_succeed());
+	assert_eq!(nonce(&executor), U256::from(2));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError, ExitReason, ExitSucceed, OpcodeFilter};
+use evm::executor::StackExecutor;
+use primitive_types::H160;
+
+use common::{CALLER, TARGET, account, backend, call_target};
+
+const LIBRARY: u64 = 0xcc;
+
+// CALL `LIBRARY` with all gas, and return the call result as a word.
+const CALL: &str = "600060006000600060007300000000000000000000000000000000000000cc\
+	5af160005260206000f3";
+// SELFDESTRUCT to the caller.
+const SUICIDE: &str = "33ff";
+
+fn run(code: &str, filter: OpcodeFilter) -> (ExitReason, Vec<u8>) {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(code)),
+		(H160::from_low_u64_be(LIBRARY), account(SUICIDE)),
+	]);
+	let mut config = Config::istanbul();
+	config.opcode_filter = filter;
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(config));
+
+	call_target(&mut executor, Vec::new(), 100_000)
+}
+
+#[test]
+fn banned_opcode_fails_frame() {
+	assert_eq!(
+		run(SUICIDE, OpcodeFilter::allow_all()).0,
+		ExitReason::Succeed(ExitSucceed::Suicided),
+	);
+	assert_eq!(
+		run(SUICIDE, OpcodeFilter::allow_all().ban(0xff)).0,
+		ExitReason::Error(ExitError::DisallowedOpcode(0xff)),
+	);
+}
+
+#[test]
+fn banned_opcode_in_inner_call() {
+	let (reason, out) = run(CALL, OpcodeFilter::allow_all().ban(0xff));
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out, vec![0; 32]);
+}
+
+#[test]
+fn allow_only_listed_opcodes() {
+	let filter = OpcodeFilter::allow_only(&[0x33, 0xff]);
+	assert!(filter.is_allowed(0xff));
+	assert!(!filter.is_allowed(0x00));
+
+	assert_eq!(run(SUICIDE, filter).0, ExitReason::Succeed(ExitSucceed::Suicided));
+	assert_eq!(run(CALL, filter).0, ExitReason::Error(ExitError::DisallowedOpcode(0x60)));
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use evm::{Config, ExternalOpcode, Fork, OPCODE_INFOS, Opcode, OpcodeInfo, opcode_infos_json};
+
+
+use common::shanghai_eips;
+
+#[test]
+fn table_matches_the_decoder() {
+	assert!(OPCODE_INFOS.windows(2).all(|pair| pair[0].opcode < pair[1].opcode));
+	for byte in 0..=255u8 {
+		let defined = match Opcode::parse(byte) {
+			// The designated invalid opcode.
+			Err(ExternalOpcode::Other(0xfe)) => true,
+			Err(ExternalOpcode::Other(_)) => false,
+			Err(ExternalOpcode::Auth) | Err(ExternalOpcode::AuthCall) => false,
+			_ => true,
+		};
+		assert_eq!(OpcodeInfo::of(byte).is_some(), defined, "{:#04x}", byte);
+	}
+}
+
+#[test]
+fn byte_inverts_parse() {
+	for byte in 0..=255u8 {
+		assert_eq!(Opcode::byte(Opcode::parse(byte)), byte);
+	}
+	assert_eq!(Opcode::byte(Ok(Opcode::Invalid)), 0xfe);
+	assert_eq!(Opcode::byte(Ok(Opcode::RetF)), 0xe4);
+}
+
+#[test]
+fn forks_match_the_presets() {
+	let presets = [
+		(Fork::Istanbul, Config::istanbul()),
+		(Fork::Shanghai, shanghai_eips()),
+	];
+	for (fork, config) in presets.iter() {
+		for info in OPCODE_INFOS.iter().filter(|info| info.mnemonic != "INVALID") {
+			assert_eq!(config.supports(info.opcode), info.fork <= *fork, "{:?} {}", fork, info.mnemonic);
+		}
+	}
+}
+
+#[test]
+fn describes_stack_and_immediates() {
+	let push = OpcodeInfo::of(0x61).unwrap();
+	assert_eq!((push.mnemonic, push.inputs, push.outputs, push.immediate_size), ("PUSH2", 0, 1, 2));
+	let call = OpcodeInfo::of(0xf1).unwrap();
+	assert_eq!((call.mnemonic, call.inputs, call.outputs, call.immediate_size), ("CALL", 7, 1, 0));
+	assert_eq!(OpcodeInfo::of(0x5f).unwrap().fork, Fork::Shanghai);
+	assert_eq!(OpcodeInfo::of(0x0c), None);
+}
+
+#[test]
+fn serializes_to_json() {
+	assert_eq!(
+		OpcodeInfo::of(0x55).unwrap().to_json(),
+		r#"{"opcode":85,"mnemonic":"SSTORE","inputs":2,"outputs":0,"immediateSize":0,"fork":"frontier"}"#,
+	);
+	let json = opcode_infos_json();
+	assert!(json.starts_with(r#"[{"opcode":0,"mnemonic":"STOP""#));
+	assert_eq!(json.matches("\"mnemonic\"").count(), OPCODE_INFOS.len());
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256, U256};
+
+use common::{account, backend, block_on};
+
+const CALLER: u64 = 0xf0;
+const PROXY: u64 = 0xaa;
+const TARGET: u64 = 0xbb;
+
+// Return ORIGIN and CALLER as a word.
+const RETURN_ORIGIN: &str = "3260005260206000f3";
+const RETURN_CALLER: &str = "3360005260206000f3";
+
+/// Forward to `TARGET` with `CALL` or `DELEGATECALL` and return the first
+/// word it returned.
+fn forward(delegate: bool) -> String {
+	let target = format!("{:040x}", TARGET);
+	if delegate {
+		format!("602060006000600073{}5af45060206000f3", target)
+	} else {
+		format!("6020600060006000600073{}5af15060206000f3", target)
+	}
+}
+
+fn run(proxy: &str, target: &str, origin: Option<H160>) -> H160 {
+	let caller = H160::from_low_u64_be(CALLER);
+	let backend = backend(vec![
+		(caller, account("")),
+		(H160::from_low_u64_be(PROXY), account(proxy)),
+		(H160::from_low_u64_be(TARGET), account(target)),
+	]);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	executor.set_origin(origin);
+
+	let (reason, output) = block_on(executor.transact_call(
+		caller, H160::from_low_u64_be(PROXY), U256::zero(), Vec::new(), 100_000,
+	));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	H256::from_slice(&output).into()
+}
+
+#[test]
+fn origin_is_transaction_caller() {
+	// The vicinity origin is zero; the transaction caller wins.
+	assert_eq!(run(RETURN_ORIGIN, "00", None), H160::from_low_u64_be(CALLER));
+	assert_eq!(run(&forward(false), RETURN_ORIGIN, None), H160::from_low_u64_be(CALLER));
+}
+
+#[test]
+fn origin_override() {
+	let origin = H160::from_low_u64_be(0x1234);
+
+	assert_eq!(run(RETURN_ORIGIN, "00", Some(origin)), origin);
+	assert_eq!(run(&forward(true), RETURN_ORIGIN, Some(origin)), origin);
+	assert_eq!(run(RETURN_CALLER, "00", Some(origin)), H160::from_low_u64_be(CALLER));
+}
+
+#[test]
+fn delegatecall_keeps_caller() {
+	assert_eq!(run(&forward(true), RETURN_CALLER, None), H160::from_low_u64_be(CALLER));
+	assert_eq!(run(&forward(false), RETURN_CALLER, None), H160::from_low_u64_be(PROXY));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError, ExitReason};
+use evm::executor::{ExecutionResult, StackExecutor, Transaction, TransactionAction};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, account, backend, block_on};
+
+const CHILD: u64 = 0xbb;
+
+// LOG0 of 32 bytes of memory.
+const LOG32: &str = "60206000a0";
+// REVERT with no data.
+const REVERT: &str = "60006000fd";
+// RETURN 64 bytes of memory.
+const RETURN64: &str = "60406000f3";
+
+/// CALL `CHILD` with all gas, then store the result in memory.
+fn call_child() -> String {
+	format!("600060006000600060007300000000000000000000000000000000000000{:02x}5af1600052", CHILD)
+}
+
+fn executor(target: &str, child: &str, config: Config) -> StackExecutor<evm::backend::MemoryBackend> {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(target)),
+		(H160::from_low_u64_be(CHILD), account(child)),
+	]);
+	StackExecutor::new(backend, 1_000_000, Arc::new(config))
+}
+
+fn transact(executor: &mut StackExecutor<evm::backend::MemoryBackend>) -> ExecutionResult {
+	block_on(executor.transact(Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action: TransactionAction::Call(H160::from_low_u64_be(TARGET)),
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: 100_000,
+		nonce: None,
+	}))
+}
+
+fn limits(log_data: Option<usize>, return_data: Option<usize>) -> Config {
+	Config::istanbul().with_output_limits(log_data, return_data)
+}
+
+#[test]
+fn log_data_is_limited_per_transaction() {
+	let target = format!("{}{}", LOG32, LOG32);
+
+	let result = transact(&mut executor(&target, "", limits(Some(64), None)));
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert_eq!(result.logs.len(), 2);
+
+	let result = transact(&mut executor(&target, "", limits(Some(48), None)));
+	assert_eq!(result.reason, ExitReason::Error(ExitError::LogDataLimit));
+	assert!(result.logs.is_empty());
+
+	// The limit applies to each transaction of the executor.
+	let mut executor = executor(LOG32, "", limits(Some(32), None));
+	assert!(transact(&mut executor).is_succeed());
+	assert!(transact(&mut executor).is_succeed());
+}
+
+#[test]
+fn reverted_logs_do_not_count() {
+	let child = format!("{}{}", LOG32, REVERT);
+	let target = format!("{}{}", call_child(), LOG32);
+	let result = transact(&mut executor(&target, &child, limits(Some(32), None)));
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert_eq!(result.logs.len(), 1);
+	assert_eq!(result.logs[0].address, H160::from_low_u64_be(TARGET));
+}
+
+#[test]
+fn return_data_is_limited_per_frame() {
+	let result = transact(&mut executor(RETURN64, "", limits(None, Some(64))));
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert_eq!(result.output.len(), 64);
+
+	let result = transact(&mut executor(RETURN64, "", limits(None, Some(32))));
+	assert_eq!(result.reason, ExitReason::Error(ExitError::ReturnDataLimit));
+	assert!(result.output.is_empty());
+
+	// A child frame exceeding the limit fails, and its caller continues.
+	let target = format!("{}60206000f3", call_child());
+	let result = transact(&mut executor(&target, RETURN64, limits(None, Some(32))));
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert_eq!(U256::from_big_endian(&result.output), U256::zero());
+}
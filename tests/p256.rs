@@ -0,0 +1,115 @@
+#![cfg(feature = "p256")]
+
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError};
+use evm::executor::{PrecompileOutput, StackExecutor};
+use evm::precompiles::{p256_verify, P256VERIFY_ADDRESS, P256VERIFY_GAS};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, account, backend, block_on};
+
+// SHA-256 hash of a message, signature and public key, signed with an
+// independent P-256 implementation.
+const VALID: &str = concat!(
+	"4e180fe880bc3d7272d606f65001db1861939ccc3c34f475f0ed63b1400b9e1b",
+	"1a971d8cf3e8d0320ba2203674418dbe5dd28bd72ab2e744bc86da1ff000bc74",
+	"d52b3c54c0348c471bd2d7b669420ca40be13b2991b7740e5b446843c9e72d13",
+	"9fad84aeae08bbef7f010014d82cef6a09de2b0cf871b5ce0c4f1d13a59a5934",
+	"07cb45769f1070e2c2470fe5b1bfe63133c0b0cdc64ea4bf3791a8ec2a07fd4f",
+);
+// `n - s` of the valid signature, which is also valid.
+const LOW_S: &str = "2ad4c3aa3fcb73b9e42d284996bdf35bb105bf8415602a769875627f327bf83e";
+// P-256 group order.
+const N: &str = "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551";
+
+fn valid() -> Vec<u8> {
+	hex::decode(VALID).unwrap()
+}
+
+fn with_word(mut input: Vec<u8>, index: usize, word: &str) -> Vec<u8> {
+	input[index * 32..(index + 1) * 32].copy_from_slice(&hex::decode(word).unwrap());
+	input
+}
+
+fn verifies(input: &[u8]) -> bool {
+	let (_, output, gas) = p256_verify(input, None).unwrap();
+	assert_eq!(gas, P256VERIFY_GAS);
+	match output.len() {
+		32 => {
+			assert_eq!(U256::from_big_endian(&output), U256::one());
+			true
+		},
+		0 => false,
+		_ => panic!("unexpected output {:?}", output),
+	}
+}
+
+#[test]
+fn valid_signatures() {
+	assert!(verifies(&valid()));
+	assert!(verifies(&with_word(valid(), 2, LOW_S)));
+}
+
+#[test]
+fn invalid_signatures() {
+	let mut tampered = valid();
+	tampered[31] ^= 1;
+	assert!(!verifies(&tampered));
+
+	let zero = "00".repeat(32);
+	assert!(!verifies(&with_word(valid(), 1, &zero)));
+	assert!(!verifies(&with_word(valid(), 2, &zero)));
+	assert!(!verifies(&with_word(valid(), 1, N)));
+	assert!(!verifies(&with_word(valid(), 2, N)));
+}
+
+#[test]
+fn invalid_public_keys() {
+	let mut off_curve = valid();
+	off_curve[159] ^= 1;
+	assert!(!verifies(&off_curve));
+
+	let zero = "00".repeat(32);
+	assert!(!verifies(&with_word(with_word(valid(), 3, &zero), 4, &zero)));
+}
+
+#[test]
+fn invalid_input_lengths() {
+	assert!(!verifies(&[]));
+	assert!(!verifies(&valid()[..159]));
+	let mut long = valid();
+	long.push(0);
+	assert!(!verifies(&long));
+}
+
+#[test]
+fn out_of_gas() {
+	assert_eq!(p256_verify(&valid(), Some(P256VERIFY_GAS - 1)), Err(ExitError::OutOfGas));
+	assert!(p256_verify(&valid(), Some(P256VERIFY_GAS)).is_ok());
+}
+
+fn precompile(address: H160, input: &[u8], target_gas: Option<usize>) -> Option<PrecompileOutput> {
+	if address == P256VERIFY_ADDRESS {
+		Some(p256_verify(input, target_gas))
+	} else {
+		None
+	}
+}
+
+#[test]
+fn installed_as_precompile() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let backend = backend(vec![(caller, account(""))]);
+	let mut executor = StackExecutor::new_with_precompile(
+		backend, 100_000, Arc::new(Config::istanbul()), precompile,
+	);
+
+	let (reason, output) = block_on(executor.transact_call(
+		caller, P256VERIFY_ADDRESS, U256::zero(), valid(), 100_000,
+	));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(U256::from_big_endian(&output), U256::one());
+}
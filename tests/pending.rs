@@ -0,0 +1,93 @@
+mod common;
+
+use evm::{Config, ExitReason, ExitSucceed};
+use evm::backend::{Backend, BlockOverrides, MemoryBackend};
+use evm::executor::{PendingState, Transaction, TransactionAction};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, backend, block_on};
+
+const COUNTER: u64 = 0xaa;
+const SETTER: u64 = 0xbb;
+
+// Increment slot 0.
+const INCREMENT: &str = "60005460010160005500";
+// Store 7 at slot 0.
+const SET: &str = "600760005500";
+
+fn call(caller: u64, target: u64) -> Transaction {
+	Transaction {
+		caller: H160::from_low_u64_be(caller),
+		action: TransactionAction::Call(H160::from_low_u64_be(target)),
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: 100_000,
+		nonce: None,
+	}
+}
+
+fn pending() -> PendingState<MemoryBackend> {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(0xf1), account("")),
+		(H160::from_low_u64_be(0xf2), account("")),
+		(H160::from_low_u64_be(0xf3), account("")),
+		(H160::from_low_u64_be(0xf4), account("")),
+		(H160::from_low_u64_be(COUNTER), account(INCREMENT)),
+		(H160::from_low_u64_be(SETTER), account(SET)),
+	]);
+	PendingState::new(backend, &Config::istanbul(), BlockOverrides::default())
+}
+
+fn slot(pending: &PendingState<MemoryBackend>, address: u64) -> H256 {
+	let Ok(value) = block_on(pending.state().unwrap().storage(H160::from_low_u64_be(address), H256::zero()));
+	value
+}
+
+#[test]
+fn only_dependent_transactions_are_executed_again() {
+	let mut pending = pending();
+	pending.push(call(0xf1, COUNTER));
+	pending.push(call(0xf2, SETTER));
+	pending.push(call(0xf3, COUNTER));
+
+	let Ok(results) = block_on(pending.results());
+	assert_eq!(results.len(), 3);
+	for result in results {
+		assert_eq!(result.result.reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	}
+	assert_eq!(pending.executions(), 3);
+	assert_eq!(slot(&pending, COUNTER), H256::from_low_u64_be(2));
+	assert_eq!(slot(&pending, SETTER), H256::from_low_u64_be(7));
+
+	// The setter does not touch the counter.
+	assert_eq!(pending.remove(1), call(0xf2, SETTER));
+	let Ok(_) = block_on(pending.results());
+	assert_eq!(pending.executions(), 3);
+	assert_eq!(slot(&pending, SETTER), H256::zero());
+
+	// Both counter transactions read the slot written by the other.
+	pending.reorder(1, 0);
+	let Ok(_) = block_on(pending.results());
+	assert_eq!(pending.executions(), 5);
+	assert_eq!(slot(&pending, COUNTER), H256::from_low_u64_be(2));
+
+	pending.insert(0, call(0xf4, SETTER));
+	let Ok(results) = block_on(pending.results());
+	assert_eq!(results.len(), 3);
+	assert_eq!(pending.executions(), 6);
+	assert_eq!(slot(&pending, SETTER), H256::from_low_u64_be(7));
+}
+
+#[test]
+fn access_sets_of_results() {
+	let mut pending = pending();
+	pending.push(call(0xf1, COUNTER));
+	pending.push(call(0xf2, SETTER));
+
+	let Ok(results) = block_on(pending.results());
+	let counter = (H160::from_low_u64_be(COUNTER), H256::zero());
+	assert!(results[0].reads.storage.contains(&counter));
+	assert!(results[0].writes.storage.contains(&counter));
+	assert!(!results[1].reads.intersects(&results[0].writes));
+	assert!(!results[0].reads.intersects(&results[1].writes));
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use evm::Config;
+use evm::precompiles::{
+	blake2f_gas, bn128_add_gas, bn128_mul_gas, bn128_pairing_gas, modexp_gas,
+	BLAKE2F_INPUT_LEN, BN128_PAIR_LEN,
+};
+
+
+use common::prague_eips;
+
+// Modexp input with the given lengths and exponent. The gas of the modexp
+// vectors only depends on the lengths and the exponent, so base and modulus
+// are filled with `0xff`.
+fn modexp_input(base_len: usize, exp: &[u8], mod_len: usize) -> Vec<u8> {
+	let mut input = Vec::new();
+	for len in [base_len, exp.len(), mod_len] {
+		let mut word = [0u8; 32];
+		word[24..].copy_from_slice(&(len as u64).to_be_bytes());
+		input.extend_from_slice(&word);
+	}
+	input.extend(std::iter::repeat_n(0xff, base_len));
+	input.extend_from_slice(exp);
+	input.extend(std::iter::repeat_n(0xff, mod_len));
+	input
+}
+
+// (name, base and modulus length, exponent, EIP-198 gas, EIP-2565 gas), from
+// the EIP-2565 test cases.
+const NAGYDANI: &[(&str, usize, &[u8], usize, usize)] = &[
+	("nagydani_1_square", 64, &[0x02], 204, 200),
+	("nagydani_1_qube", 64, &[0x03], 204, 200),
+	("nagydani_1_pow0x10001", 64, &[0x01, 0x00, 0x01], 3276, 341),
+	("nagydani_2_square", 128, &[0x02], 665, 200),
+	("nagydani_2_qube", 128, &[0x03], 665, 200),
+	("nagydani_2_pow0x10001", 128, &[0x01, 0x00, 0x01], 10649, 1365),
+	("nagydani_3_square", 256, &[0x02], 1894, 341),
+	("nagydani_3_qube", 256, &[0x03], 1894, 341),
+	("nagydani_3_pow0x10001", 256, &[0x01, 0x00, 0x01], 30310, 5461),
+	("nagydani_4_square", 512, &[0x02], 5580, 1365),
+	("nagydani_4_qube", 512, &[0x03], 5580, 1365),
+	("nagydani_4_pow0x10001", 512, &[0x01, 0x00, 0x01], 89292, 21845),
+	("nagydani_5_square", 1024, &[0x02], 17868, 5461),
+	("nagydani_5_qube", 1024, &[0x03], 17868, 5461),
+	("nagydani_5_pow0x10001", 1024, &[0x01, 0x00, 0x01], 285900, 87381),
+];
+
+#[test]
+fn modexp_nagydani_vectors() {
+	for (name, len, exp, eip198, eip2565) in NAGYDANI {
+		let input = modexp_input(*len, exp, *len);
+		assert_eq!(modexp_gas(&input, &Config::istanbul()), *eip198, "{}", name);
+		assert_eq!(modexp_gas(&input, &prague_eips()), *eip2565, "{}", name);
+	}
+}
+
+#[test]
+fn modexp_eip_examples() {
+	// EIP-198 examples: 3^(p-1) mod p and 0^(p-1) mod p, with p the secp256k1
+	// field modulus.
+	let exp = hex::decode("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e").unwrap();
+	for base_len in [1, 0] {
+		let input = modexp_input(base_len, &exp, 32);
+		assert_eq!(modexp_gas(&input, &Config::istanbul()), 13056);
+		assert_eq!(modexp_gas(&input, &prague_eips()), 1360);
+	}
+}
+
+#[test]
+fn modexp_long_exponent() {
+	// Exponent longer than 32 bytes: 8 per extra byte plus the bit length of
+	// the first 32 bytes.
+	let mut exp = vec![0u8; 40];
+	exp[31] = 0x01;
+	let input = modexp_input(32, &exp, 32);
+	assert_eq!(modexp_gas(&input, &Config::istanbul()), 1024 * 64 / 20);
+	assert_eq!(modexp_gas(&input, &prague_eips()), 16 * 64 / 3);
+}
+
+#[test]
+fn modexp_truncated_input() {
+	// Missing bytes read as zeros, so an empty input costs a single iteration
+	// of zero complexity.
+	assert_eq!(modexp_gas(&[], &Config::istanbul()), 0);
+	assert_eq!(modexp_gas(&[], &prague_eips()), 200);
+
+	// Lengths are given, the exponent and modulus are missing.
+	let mut input = modexp_input(0, &[], 0);
+	input[63] = 1;
+	input[95] = 32;
+	assert_eq!(modexp_gas(&input, &Config::istanbul()), 1024 / 20);
+}
+
+#[test]
+fn modexp_huge_lengths_saturate() {
+	let mut input = vec![0xff; 96];
+	input.extend_from_slice(&[0xff; 32]);
+	assert_eq!(modexp_gas(&input, &Config::istanbul()), usize::MAX);
+	assert_eq!(modexp_gas(&input, &prague_eips()), usize::MAX);
+}
+
+#[test]
+fn bn128_pricing_by_fork() {
+	// Byzantium pricing, which the frontier config carries.
+	let byzantium = Config::frontier();
+	assert_eq!(bn128_add_gas(&byzantium), 500);
+	assert_eq!(bn128_mul_gas(&byzantium), 40000);
+	assert_eq!(bn128_pairing_gas(0, &byzantium), 100000);
+	assert_eq!(bn128_pairing_gas(2 * BN128_PAIR_LEN, &byzantium), 260000);
+
+	// EIP-1108.
+	let istanbul = Config::istanbul();
+	assert_eq!(bn128_add_gas(&istanbul), 150);
+	assert_eq!(bn128_mul_gas(&istanbul), 6000);
+	assert_eq!(bn128_pairing_gas(0, &istanbul), 45000);
+	assert_eq!(bn128_pairing_gas(BN128_PAIR_LEN, &istanbul), 79000);
+	assert_eq!(bn128_pairing_gas(2 * BN128_PAIR_LEN, &istanbul), 113000);
+}
+
+fn blake2f_input(rounds: u32) -> Vec<u8> {
+	let mut input = vec![0u8; BLAKE2F_INPUT_LEN];
+	input[..4].copy_from_slice(&rounds.to_be_bytes());
+	input[BLAKE2F_INPUT_LEN - 1] = 1;
+	input
+}
+
+#[test]
+fn blake2f_rounds() {
+	// Rounds of the EIP-152 test vectors 4, 5 and 8.
+	assert_eq!(blake2f_gas(&blake2f_input(0)), Some(0));
+	assert_eq!(blake2f_gas(&blake2f_input(12)), Some(12));
+	assert_eq!(blake2f_gas(&blake2f_input(u32::MAX)), Some(u32::MAX as usize));
+}
+
+#[test]
+fn blake2f_invalid_length() {
+	// EIP-152 test vectors 0 to 2.
+	assert_eq!(blake2f_gas(&[]), None);
+	assert_eq!(blake2f_gas(&blake2f_input(12)[..BLAKE2F_INPUT_LEN - 1]), None);
+	let mut long = blake2f_input(12);
+	long.push(0);
+	assert_eq!(blake2f_gas(&long), None);
+}
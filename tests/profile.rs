@@ -0,0 +1,68 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+
+use common::{CALLER, account, backend, block_on};
+
+const OUTER: u64 = 0xaa;
+const INNER: u64 = 0xbb;
+
+// Call INNER with all gas, the CALL at pc 13.
+const CALL_INNER: &str = "6000600060006000600060bb5af100";
+// Store 1 at slot 0, then write a word at memory offset 0x100.
+const STORE: &str = "600160005560016101005200";
+
+fn run(profile: bool) -> StackExecutor<evm::backend::MemoryBackend> {
+	let caller = H160::from_low_u64_be(CALLER);
+	let backend = backend(vec![
+		(caller, account("")),
+		(H160::from_low_u64_be(OUTER), account(CALL_INNER)),
+		(H160::from_low_u64_be(INNER), account(STORE)),
+	]);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	if profile {
+		executor.enable_profile();
+	}
+
+	let (reason, _) = block_on(executor.transact_call(
+		caller, H160::from_low_u64_be(OUTER), U256::zero(), Vec::new(), 100_000,
+	));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	executor
+}
+
+#[test]
+fn records_watermarks_and_gas() {
+	let executor = run(true);
+	let profile = executor.profile().unwrap();
+
+	assert_eq!(profile.max_depth, 1);
+	assert_eq!(profile.max_stack, 7);
+	assert_eq!(profile.max_memory, 0x120);
+
+	let frames = profile.frames.iter()
+		.map(|frame| (frame.frame, frame.depth, frame.address))
+		.collect::<Vec<_>>();
+	assert_eq!(frames, vec![
+		(1, 1, H160::from_low_u64_be(INNER)),
+		(0, 0, H160::from_low_u64_be(OUTER)),
+	]);
+	assert!(profile.frames[1].gas_used > profile.frames[0].gas_used);
+
+	let outer = H256::from_slice(Keccak256::digest(&hex::decode(CALL_INNER).unwrap()).as_slice());
+	let inner = H256::from_slice(Keccak256::digest(&hex::decode(STORE).unwrap()).as_slice());
+	let top = profile.top(2);
+	assert_eq!(top[0].0, (outer, 13));
+	assert_eq!(top[1], ((inner, 4), 20_000));
+	assert!(top[0].1 > 20_000);
+}
+
+#[test]
+fn disabled_by_default() {
+	assert!(run(false).profile().is_none());
+}
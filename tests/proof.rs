@@ -0,0 +1,103 @@
+mod common;
+
+use evm::backend::{ProofError, state_root, trie_proof, trie_root, verify_proof};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, backend};
+
+fn h256(s: &str) -> H256 {
+	H256::from_slice(&hex::decode(s).unwrap())
+}
+
+fn accounts() -> Vec<(H160, evm::backend::MemoryAccount)> {
+	(1..40u64)
+		.map(|i| {
+			let mut account = account("6000");
+			account.balance = U256::from(i);
+			for slot in 0..i % 5 {
+				account.storage.insert(H256::from_low_u64_be(slot), H256::from_low_u64_be(i * 100 + slot));
+			}
+			(H160::from_low_u64_be(i), account)
+		})
+		.collect()
+}
+
+const DOGS: [(&str, &str); 4] = [
+	("do", "verb"),
+	("dog", "puppy"),
+	("doge", "coin"),
+	("horse", "stallion"),
+];
+
+#[test]
+fn trie_proofs_prove_values_and_absence() {
+	let root = trie_root(DOGS.iter().map(|(k, v)| (k.as_bytes(), v.as_bytes())));
+	assert_eq!(root, h256("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84"));
+
+	for (key, value) in DOGS.iter() {
+		let proof = trie_proof(DOGS.iter().map(|(k, v)| (k.as_bytes(), v.as_bytes())), key.as_bytes());
+		assert_eq!(verify_proof(root, key.as_bytes(), &proof), Ok(Some(value.as_bytes().to_vec())));
+	}
+
+	for key in ["d", "doges", "cat", "horses"].iter() {
+		let proof = trie_proof(DOGS.iter().map(|(k, v)| (k.as_bytes(), v.as_bytes())), key.as_bytes());
+		assert_eq!(verify_proof(root, key.as_bytes(), &proof), Ok(None));
+	}
+}
+
+#[test]
+fn empty_trie_proves_absence() {
+	let items: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+	let root = trie_root(items.clone());
+	let proof = trie_proof(items, b"key");
+	assert_eq!(proof, vec![vec![0x80]]);
+	assert_eq!(verify_proof(root, b"key", &proof), Ok(None));
+}
+
+#[test]
+fn account_proofs_verify_against_state_root() {
+	let backend = backend(accounts());
+	let root = state_root(backend.state());
+
+	let address = H160::from_low_u64_be(14);
+	let proof = backend.prove_account(address, &[H256::from_low_u64_be(3), H256::from_low_u64_be(9)]);
+	assert_eq!(proof.balance, U256::from(14));
+	assert_eq!(proof.nonce, U256::one());
+	assert_eq!(proof.storage_proof[0].value, U256::from(1403));
+	assert_eq!(proof.storage_proof[1].value, U256::zero());
+	assert!(proof.account_proof.len() > 1);
+	assert_eq!(proof.verify(root), Ok(()));
+
+	let absent = backend.prove_account(H160::from_low_u64_be(1000), &[H256::zero()]);
+	assert_eq!(absent.balance, U256::zero());
+	assert_eq!(absent.storage_proof[0].value, U256::zero());
+	assert_eq!(absent.verify(root), Ok(()));
+}
+
+#[test]
+fn tampered_proofs_fail() {
+	let backend = backend(accounts());
+	let root = state_root(backend.state());
+	let address = H160::from_low_u64_be(14);
+	let proof = backend.prove_account(address, &[H256::from_low_u64_be(3)]);
+
+	let mut claimed = proof.clone();
+	claimed.balance = U256::from(15);
+	assert_eq!(claimed.verify(root), Err(ProofError::ValueMismatch));
+
+	let mut claimed = proof.clone();
+	claimed.storage_proof[0].value = U256::from(1);
+	assert_eq!(claimed.verify(root), Err(ProofError::ValueMismatch));
+
+	let mut tampered = proof.clone();
+	let last = tampered.account_proof.len() - 1;
+	let len = tampered.account_proof[last].len();
+	tampered.account_proof[last][len - 1] ^= 1;
+	assert_eq!(tampered.verify(root), Err(ProofError::HashMismatch));
+
+	let mut truncated = proof.clone();
+	truncated.account_proof.pop();
+	assert_eq!(truncated.verify(root), Err(ProofError::MissingNode));
+
+	assert_eq!(proof.verify(H256::repeat_byte(1)), Err(ProofError::HashMismatch));
+}
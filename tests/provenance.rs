@@ -0,0 +1,56 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::executor::{StackExecutor, StorageProvenance};
+use primitive_types::{H160, H256};
+
+use common::{CALLER, TARGET, account, backend, call_target};
+
+const LIBRARY: u64 = 0xbb;
+
+// SSTORE 1 at slot 0, then CALL `LIBRARY`.
+const STORE_AND_CALL: &str = "6001600055\
+	600060006000600060007300000000000000000000000000000000000000bb5af100";
+
+fn run(library: &str) -> Vec<((H160, H256), StorageProvenance)> {
+	let caller = H160::from_low_u64_be(CALLER);
+	let target = H160::from_low_u64_be(TARGET);
+	let backend = backend(vec![
+		(caller, account("")),
+		(target, account(STORE_AND_CALL)),
+		(H160::from_low_u64_be(LIBRARY), account(library)),
+	]);
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	executor.enable_storage_provenance();
+
+	call_target(&mut executor, Vec::new(), 100_000);
+	executor.storage_provenance().unwrap().iter().map(|(k, v)| (*k, *v)).collect()
+}
+
+#[test]
+fn attributes_writes_to_frames() {
+	// PUSH1 2 PUSH1 1 SSTORE STOP
+	assert_eq!(run("600260015500"), vec![
+		(
+			(H160::from_low_u64_be(TARGET), H256::zero()),
+			StorageProvenance { frame: 0, depth: 0, position: 4 },
+		),
+		(
+			(H160::from_low_u64_be(LIBRARY), H256::from_low_u64_be(1)),
+			StorageProvenance { frame: 1, depth: 1, position: 4 },
+		),
+	]);
+}
+
+#[test]
+fn discards_reverted_writes() {
+	// PUSH1 2 PUSH1 1 SSTORE PUSH1 0 PUSH1 0 REVERT
+	assert_eq!(run("600260015560006000fd"), vec![
+		(
+			(H160::from_low_u64_be(TARGET), H256::zero()),
+			StorageProvenance { frame: 0, depth: 0, position: 4 },
+		),
+	]);
+}
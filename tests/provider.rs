@@ -0,0 +1,145 @@
+#![cfg(feature = "provider")]
+
+mod common;
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use evm::Config;
+use evm::backend::{Backend, BlockHeader, BlockId, Provider, ProviderBackend, ProviderError};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, block_on, call_target};
+
+// Return the value of storage slot 0.
+const RETURN_SLOT: &str = "60005460005260206000f3";
+
+/// Chain of two blocks, where slot 0 of `TARGET` holds the block number.
+struct MockProvider {
+	requests: AtomicUsize,
+}
+
+impl MockProvider {
+	fn new() -> Self {
+		Self { requests: AtomicUsize::new(0) }
+	}
+
+	fn header(number: u64) -> BlockHeader {
+		BlockHeader {
+			number: U256::from(number),
+			hash: H256::from_low_u64_be(0x100 + number),
+			timestamp: U256::from(1000 + number),
+			gas_limit: U256::from(30_000_000),
+			..Default::default()
+		}
+	}
+
+	fn number(&self, block: BlockId) -> u64 {
+		self.requests.fetch_add(1, Ordering::Relaxed);
+		match block {
+			BlockId::Hash(hash) => hash.to_low_u64_be() - 0x100,
+			BlockId::Number(_) => panic!("state reads must target the pinned hash"),
+		}
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl Provider for MockProvider {
+	type Error = Infallible;
+
+	async fn get_chain_id(&self) -> Result<U256, Infallible> {
+		Ok(U256::one())
+	}
+	async fn get_block(&self, block: BlockId) -> Result<Option<BlockHeader>, Infallible> {
+		let number = match block {
+			BlockId::Number(number) => number.low_u64(),
+			BlockId::Hash(hash) => hash.to_low_u64_be().wrapping_sub(0x100),
+		};
+		Ok(if number <= 2 { Some(Self::header(number)) } else { None })
+	}
+	async fn get_balance(&self, address: H160, block: BlockId) -> Result<U256, Infallible> {
+		self.number(block);
+		Ok(if address == H160::from_low_u64_be(CALLER) { U256::from(1_000_000_000u64) } else { U256::zero() })
+	}
+	async fn get_transaction_count(&self, address: H160, block: BlockId) -> Result<U256, Infallible> {
+		self.number(block);
+		Ok(if address == H160::from_low_u64_be(TARGET) { U256::one() } else { U256::zero() })
+	}
+	async fn get_code(&self, address: H160, block: BlockId) -> Result<Vec<u8>, Infallible> {
+		self.number(block);
+		Ok(if address == H160::from_low_u64_be(TARGET) { hex::decode(RETURN_SLOT).unwrap() } else { Vec::new() })
+	}
+	async fn get_storage_at(&self, address: H160, index: H256, block: BlockId) -> Result<H256, Infallible> {
+		let number = self.number(block);
+		Ok(if address == H160::from_low_u64_be(TARGET) && index == H256::zero() {
+			H256::from_low_u64_be(number)
+		} else {
+			H256::zero()
+		})
+	}
+}
+
+fn call<B: Backend>(backend: Arc<B>) -> U256 {
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+	let (reason, output) = call_target(&mut executor, Vec::new(), 1_000_000);
+	assert!(reason.is_succeed(), "{:?}", reason);
+	U256::from_big_endian(&output)
+}
+
+#[test]
+fn reads_target_the_pinned_block() {
+	let provider = Arc::new(MockProvider::new());
+	for number in 1..=2u64 {
+		let backend = block_on(ProviderBackend::pin(provider.clone(), BlockId::Number(number.into()))).unwrap();
+		assert_eq!(backend.header(), &MockProvider::header(number));
+		assert_eq!(call(Arc::new(backend)), U256::from(number));
+	}
+}
+
+#[test]
+fn environment_is_the_pinned_header() {
+	let provider = Arc::new(MockProvider::new());
+	let backend = block_on(ProviderBackend::pin(provider, BlockId::Hash(H256::from_low_u64_be(0x101))))
+		.unwrap()
+		.with_origin(H160::from_low_u64_be(CALLER))
+		.with_gas_price(U256::from(7));
+
+	assert_eq!(block_on(backend.block_number()).unwrap(), U256::one());
+	assert_eq!(block_on(backend.block_timestamp()).unwrap(), U256::from(1001));
+	assert_eq!(block_on(backend.origin()).unwrap(), H160::from_low_u64_be(CALLER));
+	assert_eq!(block_on(backend.gas_price()).unwrap(), U256::from(7));
+	assert_eq!(block_on(backend.block_hash(U256::zero())).unwrap(), H256::from_low_u64_be(0x100));
+	assert_eq!(block_on(backend.block_hash(U256::one())).unwrap(), H256::from_low_u64_be(0x101));
+	assert_eq!(block_on(backend.block_hash(U256::from(2))).unwrap(), H256::zero());
+}
+
+#[test]
+fn accounts_without_state_do_not_exist() {
+	let provider = Arc::new(MockProvider::new());
+	let backend = block_on(ProviderBackend::pin(provider, BlockId::Number(U256::one()))).unwrap();
+	assert!(block_on(backend.exists(H160::from_low_u64_be(TARGET))).unwrap());
+	assert!(!block_on(backend.exists(H160::from_low_u64_be(0xdd))).unwrap());
+	assert_eq!(block_on(backend.code_hash(H160::from_low_u64_be(0xdd))).unwrap(), H256::zero());
+}
+
+#[test]
+fn unknown_blocks_cannot_be_pinned() {
+	let provider = Arc::new(MockProvider::new());
+	let result = block_on(ProviderBackend::pin(provider, BlockId::Number(U256::from(3))));
+	assert_eq!(result.err(), Some(ProviderError::UnknownBlock(BlockId::Number(U256::from(3)))));
+}
+
+#[test]
+fn cached_backend_saves_requests() {
+	let provider = Arc::new(MockProvider::new());
+	let cached = Arc::new(block_on(ProviderBackend::pin(provider.clone(), BlockId::Number(U256::one()))).unwrap().cached());
+
+	assert_eq!(call(cached.clone()), U256::one());
+	assert!(provider.requests.swap(0, Ordering::Relaxed) > 0);
+
+	assert_eq!(call(cached), U256::one());
+	assert_eq!(provider.requests.load(Ordering::Relaxed), 0);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::backend::{Apply, ApplyBackend, Basic, Log, MemoryAccount, MemoryBackend, MemoryVicinity};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, block_on, vicinity};
+
+const CODE: &str = "6000600055";
+
+fn at_block(number: u64, hashes: usize) -> Arc<MemoryVicinity> {
+	Arc::new(MemoryVicinity {
+		block_number: U256::from(number),
+		block_hashes: (0..hashes as u64).map(H256::from_low_u64_be).collect(),
+		..vicinity()
+	})
+}
+
+fn log(data: &[u8]) -> Log {
+	Log { address: H160::from_low_u64_be(1), topics: vec![H256::zero()], data: data.to_vec() }
+}
+
+fn set_code(backend: &mut MemoryBackend, address: u64, code: &str) {
+	let apply = Apply::Modify {
+		address: H160::from_low_u64_be(address),
+		basic: Basic { balance: U256::one(), nonce: U256::one() },
+		code: Some(hex::decode(code).unwrap()),
+		storage: Vec::new(),
+		reset_storage: false,
+	};
+	block_on(backend.apply(vec![apply], Vec::new(), false)).unwrap();
+}
+
+#[test]
+fn prune_drops_logs_and_block_hashes_of_old_blocks() {
+	let mut backend = MemoryBackend::new(at_block(10, 0), BTreeMap::new());
+	for number in 10..20 {
+		backend.set_vicinity(at_block(number, 256));
+		block_on(backend.apply(Vec::<Apply<Vec<(H256, H256)>>>::new(), vec![log(&[number as u8])], false)).unwrap();
+	}
+
+	let before = backend.memory_usage();
+	let freed = backend.prune(3);
+	assert_eq!(backend.logs().map(|log| log.data[0]).collect::<Vec<_>>(), vec![16, 17, 18, 19]);
+	assert_eq!(backend.vicinity().block_hashes.len(), 3);
+	assert_eq!(freed, before.total() - backend.memory_usage().total());
+	assert_eq!(freed, before.logs - backend.memory_usage().logs);
+
+	assert_eq!(backend.prune(3), 0);
+}
+
+#[test]
+fn prune_drops_zero_storage() {
+	let mut target = account("");
+	target.storage.insert(H256::from_low_u64_be(1), H256::zero());
+	target.storage.insert(H256::from_low_u64_be(2), H256::from_low_u64_be(2));
+	let mut state = BTreeMap::new();
+	state.insert(H160::from_low_u64_be(1), target);
+	let mut backend = MemoryBackend::new(Arc::new(vicinity()), state);
+
+	assert_eq!(backend.memory_usage().storage, 2 * 64);
+	assert_eq!(backend.prune(0), 64);
+	assert_eq!(backend.state()[&H160::from_low_u64_be(1)].storage.len(), 1);
+}
+
+#[test]
+fn memory_usage_counts_accounts_storage_and_code() {
+	let mut target = account(CODE);
+	target.storage.insert(H256::from_low_u64_be(1), H256::from_low_u64_be(1));
+	let mut state = BTreeMap::new();
+	state.insert(H160::from_low_u64_be(1), target);
+	state.insert(H160::from_low_u64_be(2), MemoryAccount::default());
+	let backend = MemoryBackend::new(Arc::new(vicinity()), state);
+
+	let usage = backend.memory_usage();
+	assert_eq!(usage.accounts, 2 * (20 + std::mem::size_of::<MemoryAccount>()));
+	assert_eq!(usage.storage, 64);
+	assert_eq!(usage.code, 5);
+	assert_eq!(usage.logs, 0);
+	assert_eq!(usage.total(), usage.accounts + 64 + 5);
+}
+
+#[test]
+fn code_is_deduplicated_by_hash() {
+	let mut state = BTreeMap::new();
+	state.insert(H160::from_low_u64_be(1), account(CODE));
+	state.insert(H160::from_low_u64_be(2), account(CODE));
+	let mut backend = MemoryBackend::new(Arc::new(vicinity()), state);
+	assert_eq!(backend.memory_usage().code, 10);
+
+	backend.set_code_deduplication(true);
+	assert_eq!(backend.memory_usage().code, 5);
+	set_code(&mut backend, 3, CODE);
+	set_code(&mut backend, 4, "00");
+	assert_eq!(backend.memory_usage().code, 6);
+	assert_eq!(backend.state()[&H160::from_low_u64_be(3)].code, hex::decode(CODE).unwrap());
+
+	// Unused code is dropped once pruned.
+	set_code(&mut backend, 4, CODE);
+	assert_eq!(backend.memory_usage().code, 6);
+	assert_eq!(backend.prune(0), 1);
+	assert_eq!(backend.memory_usage().code, 5);
+
+	backend.set_code_deduplication(false);
+	assert_eq!(backend.memory_usage().code, 20);
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::MemoryBackend;
+use evm::executor::{ExecutionResult, LocationSet, StackExecutor, Transaction, TransactionAction};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, backend, block_on};
+
+const ALICE: u64 = 0xa1;
+const BOB: u64 = 0xb0;
+const STORE: u64 = 0xaa;
+const REVERT: u64 = 0xbb;
+const OUTER: u64 = 0xcc;
+const WATCH: u64 = 0xdd;
+const PAYEE: u64 = 0xee;
+
+fn executor() -> StackExecutor<MemoryBackend> {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(ALICE), account("")),
+		(H160::from_low_u64_be(BOB), account("")),
+		// SLOAD(0), then SSTORE(1, 5).
+		(H160::from_low_u64_be(STORE), account("600054506005600155")),
+		// SSTORE(2, 1), then revert.
+		(H160::from_low_u64_be(REVERT), account("600160025560006000fd")),
+		// Call REVERT, then SSTORE(3, 1).
+		(H160::from_low_u64_be(OUTER), account("600060006000600060007300000000000000000000000000000000000000bb5af150600160035500")),
+		// BALANCE(PAYEE).
+		(H160::from_low_u64_be(WATCH), account("7300000000000000000000000000000000000000ee3150")),
+	]);
+	StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()))
+}
+
+fn call(executor: &mut StackExecutor<MemoryBackend>, caller: u64, to: u64, value: u64) -> ExecutionResult {
+	let result = block_on(executor.transact(Transaction {
+		caller: H160::from_low_u64_be(caller),
+		action: TransactionAction::Call(H160::from_low_u64_be(to)),
+		value: U256::from(value),
+		data: Vec::new(),
+		gas_limit: 100_000,
+		nonce: None,
+	}));
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	result
+}
+
+fn slot(address: u64, index: u64) -> (H160, H256) {
+	(H160::from_low_u64_be(address), H256::from_low_u64_be(index))
+}
+
+#[test]
+fn storage_reads_and_writes_are_separated() {
+	let mut executor = executor();
+	let result = call(&mut executor, ALICE, STORE, 0);
+	let set = result.read_write_set;
+
+	assert!(set.reads.storage.contains(&slot(STORE, 0)));
+	assert_eq!(set.writes.storage, BTreeSet::from([slot(STORE, 1)]));
+	assert!(set.reads.nonces.contains(&H160::from_low_u64_be(ALICE)));
+	assert!(set.writes.nonces.contains(&H160::from_low_u64_be(ALICE)));
+	assert!(set.reads.code.contains(&H160::from_low_u64_be(STORE)));
+	assert!(set.writes.code.is_empty());
+}
+
+#[test]
+fn writes_of_reverted_frames_are_dropped() {
+	let mut executor = executor();
+	let result = call(&mut executor, ALICE, OUTER, 0);
+	let set = result.read_write_set;
+
+	// The reverted frame still read the slot it failed to write.
+	assert!(set.reads.storage.contains(&slot(REVERT, 2)));
+	assert_eq!(set.writes.storage, BTreeSet::from([slot(OUTER, 3)]));
+}
+
+#[test]
+fn sets_are_per_transaction() {
+	let mut executor = executor();
+	let pay = call(&mut executor, ALICE, PAYEE, 1);
+	let watch = call(&mut executor, BOB, WATCH, 0);
+
+	assert!(pay.read_write_set.writes.balances.contains(&H160::from_low_u64_be(PAYEE)));
+	assert!(!watch.read_write_set.writes.nonces.contains(&H160::from_low_u64_be(ALICE)));
+	assert!(watch.read_write_set.depends_on(&pay.read_write_set));
+	assert!(!pay.read_write_set.depends_on(&watch.read_write_set));
+	assert!(pay.read_write_set.conflicts_with(&watch.read_write_set));
+
+	let store = call(&mut executor, BOB, STORE, 0);
+	assert!(!store.read_write_set.conflicts_with(&pay.read_write_set));
+
+	let mut all = pay.read_write_set.clone();
+	all.extend(&watch.read_write_set);
+	all.extend(&store.read_write_set);
+	assert_eq!(executor.read_write_set(), all);
+}
+
+#[test]
+fn accounts_and_cleared_storage_overlap_their_fields() {
+	let address = H160::from_low_u64_be(STORE);
+	let existence = LocationSet { accounts: BTreeSet::from([address]), ..Default::default() };
+	let balance = LocationSet { balances: BTreeSet::from([address]), ..Default::default() };
+	let cleared = LocationSet { cleared_storage: BTreeSet::from([address]), ..Default::default() };
+	let storage = LocationSet { storage: BTreeSet::from([slot(STORE, 7)]), ..Default::default() };
+
+	assert!(existence.intersects(&balance));
+	assert_eq!(balance.intersection(&existence), balance);
+	assert!(cleared.intersects(&storage));
+	assert_eq!(storage.intersection(&cleared), storage);
+	assert!(!existence.intersects(&storage));
+	assert_eq!(storage.addresses(), BTreeSet::from([address]));
+}
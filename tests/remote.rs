@@ -0,0 +1,216 @@
+mod common;
+
+use std::io::{Cursor, ErrorKind};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use evm::Config;
+use evm::backend::{
+	decode_requests, decode_responses, encode_requests, read_frame, write_frame, Apply, ApplyBackend, Backend, Basic,
+	Log, MAX_FRAME_LEN, MemoryBackend, RemoteBackend, RemoteError, RemoteHost, RemoteRequest, RemoteResponse, RemoteTransport,
+};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, account, block_on, call_target, vicinity};
+
+// Store 42 at slot 1, log one topic, and return slot 0.
+const CODE: &str = "602a600155600760006000a160005460005260206000f3";
+
+/// In-process transport, framing messages as a stream transport would.
+struct LocalTransport {
+	host: Mutex<RemoteHost<MemoryBackend>>,
+	messages: AtomicUsize,
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl RemoteTransport for LocalTransport {
+	type Error = std::io::Error;
+
+	async fn round_trip(&self, request: Vec<u8>) -> Result<Vec<u8>, std::io::Error> {
+		self.messages.fetch_add(1, Ordering::Relaxed);
+		let mut stream = Vec::new();
+		write_frame(&mut stream, &request)?;
+		let request = read_frame(&mut Cursor::new(stream))?;
+		Ok(block_on(self.host.lock().unwrap().handle(&request)))
+	}
+}
+
+fn state() -> MemoryBackend {
+	let mut target = account(CODE);
+	target.storage.insert(H256::zero(), H256::from_low_u64_be(7));
+	MemoryBackend::new(Arc::new(vicinity()), vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), target),
+	].into_iter().collect())
+}
+
+fn remote() -> RemoteBackend<LocalTransport> {
+	RemoteBackend::new(LocalTransport {
+		host: Mutex::new(RemoteHost::new(state())),
+		messages: AtomicUsize::new(0),
+	})
+}
+
+fn messages(remote: &RemoteBackend<LocalTransport>) -> usize {
+	remote.transport().messages.swap(0, Ordering::Relaxed)
+}
+
+/// Run the call against the backend, and apply its changes.
+fn transact<B: ApplyBackend>(backend: B) -> (U256, B) {
+	let backend = Arc::new(backend);
+	let mut executor = StackExecutor::new(backend.clone(), 1_000_000, Arc::new(Config::istanbul()));
+	let (reason, output) = call_target(&mut executor, Vec::new(), 1_000_000);
+	assert!(reason.is_succeed(), "{:?}", reason);
+	let (values, logs) = executor.deconstruct();
+	let (values, logs) = (values.into_iter().collect::<Vec<_>>(), logs.into_iter().collect::<Vec<_>>());
+
+	let mut backend = Arc::try_unwrap(backend).ok().unwrap();
+	block_on(backend.apply(values, logs, false)).unwrap();
+	(U256::from_big_endian(&output), backend)
+}
+
+#[test]
+fn remote_execution_matches_local() {
+	let (local_output, local) = transact(state());
+	let (remote_output, remote) = transact(remote());
+	assert_eq!(local_output, U256::from(7));
+	assert_eq!(remote_output, local_output);
+
+	let host = remote.transport().host.lock().unwrap();
+	assert_eq!(host.backend().state(), local.state());
+	assert_eq!(host.backend().logs().collect::<Vec<_>>(), local.logs().collect::<Vec<_>>());
+}
+
+#[test]
+fn batches_are_one_message() {
+	let remote = remote();
+	let responses = block_on(remote.batch(&[
+		RemoteRequest::ChainId,
+		RemoteRequest::Basic(H160::from_low_u64_be(CALLER)),
+		RemoteRequest::Storage(H160::from_low_u64_be(TARGET), H256::zero()),
+		RemoteRequest::CodeSize(H160::from_low_u64_be(TARGET)),
+		RemoteRequest::Exists(H160::from_low_u64_be(0xdd)),
+		RemoteRequest::AccountStorageKeys(H160::from_low_u64_be(TARGET)),
+	])).unwrap();
+	assert_eq!(messages(&remote), 1);
+	assert_eq!(responses, vec![
+		RemoteResponse::Quantity(U256::one()),
+		RemoteResponse::Basic(Basic { balance: U256::from(1_000_000_000u64), nonce: U256::one() }),
+		RemoteResponse::Word(H256::from_low_u64_be(7)),
+		RemoteResponse::Size(CODE.len() / 2),
+		RemoteResponse::Bool(false),
+		RemoteResponse::Keys(Some(vec![H256::zero()])),
+	]);
+}
+
+#[test]
+fn applies_are_streamed_in_chunks() {
+	let mut remote = remote().with_apply_chunk(2);
+	let values = (1..=5u64).map(|low| Apply::Modify {
+		address: H160::from_low_u64_be(low),
+		basic: Basic { balance: U256::from(low), nonce: U256::zero() },
+		code: None,
+		storage: vec![(H256::from_low_u64_be(low), H256::from_low_u64_be(low))],
+		reset_storage: false,
+	}).chain(Some(Apply::Delete { address: H160::from_low_u64_be(CALLER) })).collect::<Vec<_>>();
+	let log = Log { address: H160::from_low_u64_be(1), topics: vec![H256::zero()], data: vec![1, 2] };
+	block_on(remote.apply(values, vec![log.clone()], false)).unwrap();
+	assert_eq!(messages(&remote), 3);
+
+	let host = remote.transport().host.lock().unwrap();
+	let state = host.backend().state();
+	assert!(!state.contains_key(&H160::from_low_u64_be(CALLER)));
+	for low in 1..=5u64 {
+		let account = &state[&H160::from_low_u64_be(low)];
+		assert_eq!(account.balance, U256::from(low));
+		assert_eq!(account.storage[&H256::from_low_u64_be(low)], H256::from_low_u64_be(low));
+	}
+	assert_eq!(host.backend().logs().collect::<Vec<_>>(), vec![&log]);
+}
+
+#[test]
+fn chunks_are_applied_with_the_last() {
+	let accounts = (1..=3).map(|low| (H160::from_low_u64_be(low), account(""))).collect();
+	let mut host = RemoteHost::new(MemoryBackend::new(Arc::new(vicinity()), accounts));
+	let chunk = |index, last| RemoteRequest::Apply {
+		index,
+		last,
+		values: vec![Apply::Delete { address: H160::from_low_u64_be(index as u64 + 1) }],
+		logs: Vec::new(),
+		delete_empty: false,
+	};
+	let send = |host: &mut RemoteHost<MemoryBackend>, request| {
+		decode_responses(&block_on(host.handle(&encode_requests(&[request])))).unwrap()
+	};
+
+	// Nothing is applied until the last chunk, and chunks out of order drop
+	// the apply.
+	assert_eq!(send(&mut host, chunk(0, false)), vec![RemoteResponse::Applied]);
+	assert_eq!(host.backend().state().len(), 3);
+	assert!(matches!(send(&mut host, chunk(2, true)).as_slice(), [RemoteResponse::Error(_)]));
+	assert_eq!(host.backend().state().len(), 3);
+
+	// A new apply starts from its first chunk.
+	assert_eq!(send(&mut host, chunk(0, false)), vec![RemoteResponse::Applied]);
+	assert_eq!(send(&mut host, chunk(1, true)), vec![RemoteResponse::Applied]);
+	let state = host.backend().state();
+	assert_eq!(state.keys().collect::<Vec<_>>(), vec![&H160::from_low_u64_be(3)]);
+}
+
+#[test]
+fn requests_round_trip() {
+	let requests = vec![
+		RemoteRequest::BlockHash(U256::from(3)),
+		RemoteRequest::StorageRange(H160::from_low_u64_be(1), H256::from_low_u64_be(2), 10),
+		RemoteRequest::Apply {
+			index: 2,
+			last: true,
+			values: vec![Apply::Modify {
+				address: H160::from_low_u64_be(1),
+				basic: Basic::default(),
+				code: Some(vec![0x60, 0x00]),
+				storage: vec![(H256::zero(), H256::from_low_u64_be(9))],
+				reset_storage: true,
+			}],
+			logs: Vec::new(),
+			delete_empty: true,
+		},
+	];
+	let encoded = encode_requests(&requests);
+	assert_eq!(encode_requests(&decode_requests(&encoded).unwrap()), encoded);
+}
+
+#[test]
+fn malformed_requests_are_host_errors() {
+	let remote = remote();
+	let response = block_on(remote.transport().round_trip(vec![0xc1, 0xff])).unwrap();
+	assert!(matches!(decode_responses(&response).unwrap().as_slice(), [RemoteResponse::Error(_)]));
+
+	struct Garbage;
+	#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+	#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+	impl RemoteTransport for Garbage {
+		type Error = ();
+		async fn round_trip(&self, _: Vec<u8>) -> Result<Vec<u8>, ()> {
+			Ok(vec![0xff])
+		}
+	}
+	assert_eq!(block_on(RemoteBackend::new(Garbage).chain_id()), Err(RemoteError::InvalidEncoding));
+}
+
+#[test]
+fn oversized_frames_are_rejected() {
+	let header = ((MAX_FRAME_LEN + 1) as u32).to_be_bytes();
+	let error = read_frame(&mut Cursor::new(header.to_vec())).unwrap_err();
+	assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+	// A truncated message fails instead of being padded.
+	let mut stream = 4u32.to_be_bytes().to_vec();
+	stream.extend_from_slice(&[1, 2]);
+	assert_eq!(read_frame(&mut Cursor::new(stream)).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+
+	let error = write_frame(&mut Vec::new(), &vec![0; MAX_FRAME_LEN + 1]).unwrap_err();
+	assert_eq!(error.kind(), ErrorKind::InvalidInput);
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::{MemoryAccount, MemoryBackend};
+use evm::executor::{ExecutionResult, ReplayLog, StackExecutor, Transaction, TransactionAction};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, backend, block_on};
+
+const CALLER: u64 = 0xa1;
+const TARGET: u64 = 0xaa;
+
+// SLOAD(0), SLOAD(1), then SSTORE(2, 1).
+const LOAD: &str = "6000545060015450600160025500";
+
+fn executor(slot: u64) -> StackExecutor<MemoryBackend> {
+	let target = MemoryAccount {
+		storage: BTreeMap::from([(H256::from_low_u64_be(1), H256::from_low_u64_be(slot))]),
+		..account(LOAD)
+	};
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), target),
+	]);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+	executor.enable_replay_log();
+	executor
+}
+
+fn call(executor: &mut StackExecutor<MemoryBackend>) -> ExecutionResult {
+	let result = block_on(executor.transact(Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action: TransactionAction::Call(H160::from_low_u64_be(TARGET)),
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: 100_000,
+		nonce: None,
+	}));
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	result
+}
+
+fn replay_log(slot: u64) -> ReplayLog {
+	call(&mut executor(slot)).replay_log.unwrap()
+}
+
+#[test]
+fn identical_state_has_equal_commitments() {
+	let log = replay_log(7);
+
+	assert_ne!(log.commitment, H256::zero());
+	assert_eq!(log.commitment, log.entries.last().unwrap().commitment);
+	assert_eq!(replay_log(7), log);
+	assert_eq!(log.first_divergence(&replay_log(7)), None);
+}
+
+#[test]
+fn first_divergent_read_is_found() {
+	let log = replay_log(7);
+	let stale = replay_log(8);
+	assert_ne!(log.commitment, stale.commitment);
+
+	let index = log.first_divergence(&stale).unwrap();
+	let entry = &stale.entries[index];
+	assert_eq!(entry.method, "storage");
+	let mut input = H160::from_low_u64_be(TARGET).as_bytes().to_vec();
+	input.extend_from_slice(H256::from_low_u64_be(1).as_bytes());
+	assert_eq!(entry.input, input);
+	assert_eq!(log.entries[..index], stale.entries[..index]);
+
+	let mut prefix = log.clone();
+	prefix.entries.truncate(index);
+	prefix.commitment = prefix.entries.last().map(|entry| entry.commitment).unwrap_or_default();
+	assert_eq!(log.first_divergence(&prefix), Some(index));
+}
+
+#[test]
+fn transaction_logs_extend_the_executor_log() {
+	let mut executor = executor(7);
+	let first = call(&mut executor).replay_log.unwrap();
+	let second = call(&mut executor).replay_log.unwrap();
+
+	// Later transactions read cached state, so logs are per transaction.
+	assert!(second.entries.len() < first.entries.len());
+	let log = executor.replay_log().unwrap();
+	assert_eq!(log.entries.len(), first.entries.len() + second.entries.len());
+	assert_eq!(log.entries[..first.entries.len()], first.entries[..]);
+	assert_ne!(log.commitment, second.commitment);
+}
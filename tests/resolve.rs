@@ -0,0 +1,283 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::{
+	Bytes, Capture, Config, Context, CreateScheme, ExitError, ExitReason, ExitSucceed, ExternalOpcode,
+	InterruptHandler, Opcode, Resolve, Runtime, Stack, StateMutator, StateQuery, Transfer, resolve,
+};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, block_on};
+
+const CALLEE: u64 = 0xbb;
+
+// SSTORE 42 at slot 0 and return 42 as a word.
+const STORE_AND_RETURN: &str = "602a600055602a60005260206000f3";
+// CALL `CALLEE` with the output in the first word of memory, then return
+// that word plus the call result.
+const CALL_AND_ADD: &str = "602060006000600060007300000000000000000000000000000000000000bb5af1\
+	6000510160005260206000f3";
+// CREATE a contract whose code is the byte 0x2a, then return its address.
+const CREATE: &str = "69602a60005360016000f3600052600a60166000f060005260206000f3";
+
+struct Call {
+	code: Vec<u8>,
+	input: Bytes,
+	context: Context,
+}
+
+struct Create {
+	address: H160,
+	init_code: Bytes,
+	context: Context,
+}
+
+/// Handler trapping every call and create, with frames run by `resolve`.
+#[derive(Default)]
+struct Frames {
+	codes: BTreeMap<H160, Vec<u8>>,
+	storage: BTreeMap<(H160, H256), H256>,
+	feedback: Vec<&'static str>,
+	depth: usize,
+	max_depth: usize,
+}
+
+impl Frames {
+	fn runtime(&self, code: Vec<u8>, input: Bytes, context: Context) -> Runtime {
+		Runtime::new(Arc::new(code), input, context, Arc::new(Config::istanbul()))
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl StateQuery for Frames {
+	async fn balance(&self, _address: H160) -> U256 { U256::zero() }
+	async fn code_size(&self, address: H160) -> U256 { U256::from(self.codes.get(&address).map(|c| c.len()).unwrap_or(0)) }
+	async fn code_hash(&self, _address: H160) -> H256 { H256::zero() }
+	async fn code(&self, address: H160) -> Vec<u8> { self.codes.get(&address).cloned().unwrap_or_default() }
+	async fn storage(&self, address: H160, index: H256) -> H256 { self.storage.get(&(address, index)).cloned().unwrap_or_default() }
+	async fn original_storage(&self, address: H160, index: H256) -> H256 { self.storage(address, index).await }
+	fn gas_left(&self) -> U256 { U256::from(1_000_000) }
+	async fn gas_price(&self) -> U256 { U256::zero() }
+	async fn origin(&self) -> H160 { H160::from_low_u64_be(CALLER) }
+	async fn block_hash(&self, _number: U256) -> H256 { H256::zero() }
+	async fn block_number(&self) -> U256 { U256::zero() }
+	async fn block_coinbase(&self) -> H160 { H160::zero() }
+	async fn block_timestamp(&self) -> U256 { U256::zero() }
+	async fn block_difficulty(&self) -> U256 { U256::zero() }
+	async fn block_gas_limit(&self) -> U256 { U256::zero() }
+	async fn chain_id(&self) -> U256 { U256::one() }
+	async fn exists(&self, address: H160) -> bool { self.codes.contains_key(&address) }
+	fn deleted(&self, _address: H160) -> bool { false }
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl StateMutator for Frames {
+	type CreateInterrupt = Create;
+	type CreateFeedback = &'static str;
+	type CallInterrupt = Call;
+	type CallFeedback = &'static str;
+
+	async fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError> {
+		self.storage.insert((address, index), value);
+		Ok(())
+	}
+
+	fn log(&mut self, _address: H160, _topics: Vec<H256>, _data: Vec<u8>) -> Result<(), ExitError> {
+		Ok(())
+	}
+
+	async fn mark_delete(&mut self, _address: H160, _target: H160) -> Result<(), ExitError> {
+		Ok(())
+	}
+
+	async fn create(
+		&mut self,
+		caller: H160,
+		_scheme: CreateScheme,
+		value: U256,
+		init_code: Bytes,
+		_target_gas: Option<usize>,
+	) -> Capture<(ExitReason, Option<H160>, Bytes), Self::CreateInterrupt> {
+		let address = H160::from_low_u64_be(0xc0 + self.codes.len() as u64);
+		let context = Context { address, caller, apparent_value: value };
+		Capture::Trap(Create { address, init_code, context })
+	}
+
+	fn create_feedback(&mut self, feedback: Self::CreateFeedback) -> Result<(), ExitError> {
+		self.feedback.push(feedback);
+		Ok(())
+	}
+
+	async fn call(
+		&mut self,
+		code_address: H160,
+		_transfer: Option<Transfer>,
+		input: Bytes,
+		_target_gas: Option<usize>,
+		_is_static: bool,
+		context: Context,
+	) -> Capture<(ExitReason, Bytes), Self::CallInterrupt> {
+		Capture::Trap(Call { code: self.code(code_address).await, input, context })
+	}
+
+	fn call_feedback(&mut self, feedback: Self::CallFeedback) -> Result<(), ExitError> {
+		self.feedback.push(feedback);
+		Ok(())
+	}
+
+	async fn auth(
+		&mut self,
+		_invoker: H160,
+		_authority: H160,
+		_y_parity: u8,
+		_r: H256,
+		_s: H256,
+		_commit: H256,
+	) -> Result<bool, ExitError> {
+		Ok(false)
+	}
+
+	async fn pre_validate(
+		&mut self,
+		_context: &Context,
+		_opcode: Result<Opcode, ExternalOpcode>,
+		_static_gas: Option<usize>,
+		_stack: &Stack,
+	) -> Result<(), ExitError> {
+		Ok(())
+	}
+}
+
+#[cfg_attr(not(feature = "no-send"), async_trait::async_trait)]
+#[cfg_attr(feature = "no-send", async_trait::async_trait(?Send))]
+impl InterruptHandler for Frames {
+	async fn enter_call(&mut self, interrupt: Call) -> Capture<(ExitReason, Bytes), Runtime> {
+		if interrupt.code.is_empty() {
+			return Capture::Exit((ExitSucceed::Stopped.into(), Bytes::new()))
+		}
+		self.depth += 1;
+		self.max_depth = self.max_depth.max(self.depth);
+		Capture::Trap(self.runtime(interrupt.code, interrupt.input, interrupt.context))
+	}
+
+	async fn exit_call(&mut self, runtime: Runtime, reason: ExitReason) -> ((ExitReason, Bytes), &'static str) {
+		self.depth -= 1;
+		((reason, runtime.machine().return_value().into()), "call")
+	}
+
+	async fn enter_create(&mut self, interrupt: Create) -> Capture<(ExitReason, Option<H160>, Bytes), Runtime> {
+		self.depth += 1;
+		self.max_depth = self.max_depth.max(self.depth);
+		self.codes.insert(interrupt.address, Vec::new());
+		Capture::Trap(self.runtime(interrupt.init_code.into_vec(), Bytes::new(), interrupt.context))
+	}
+
+	async fn exit_create(
+		&mut self,
+		runtime: Runtime,
+		reason: ExitReason,
+	) -> ((ExitReason, Option<H160>, Bytes), &'static str) {
+		self.depth -= 1;
+		let address = runtime.context().address;
+		self.codes.insert(address, runtime.machine().return_value());
+		((reason, Some(address), Bytes::new()), "create")
+	}
+}
+
+fn run(frames: &mut Frames) -> (ExitReason, Vec<u8>) {
+	let context = Context {
+		address: H160::from_low_u64_be(TARGET),
+		caller: H160::from_low_u64_be(CALLER),
+		apparent_value: U256::zero(),
+	};
+	let mut runtime = frames.runtime(frames.codes[&H160::from_low_u64_be(TARGET)].clone(), Bytes::new(), context);
+	let reason = block_on(resolve(&mut runtime, frames));
+	(reason, runtime.machine().return_value())
+}
+
+#[test]
+fn calls_are_run_in_child_runtimes() {
+	let mut frames = Frames::default();
+	frames.codes.insert(H160::from_low_u64_be(TARGET), hex::decode(CALL_AND_ADD).unwrap());
+	frames.codes.insert(H160::from_low_u64_be(CALLEE), hex::decode(STORE_AND_RETURN).unwrap());
+
+	let (reason, output) = run(&mut frames);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&output), U256::from(43));
+	assert_eq!(frames.storage[&(H160::from_low_u64_be(CALLEE), H256::zero())], H256::from_low_u64_be(42));
+	assert_eq!(frames.feedback, vec!["call"]);
+	assert_eq!((frames.depth, frames.max_depth), (0, 1));
+}
+
+#[test]
+fn nested_calls_are_resolved_recursively() {
+	let mut frames = Frames::default();
+	let forward = CALL_AND_ADD.replace("00bb", "00cc");
+	frames.codes.insert(H160::from_low_u64_be(TARGET), hex::decode(CALL_AND_ADD).unwrap());
+	frames.codes.insert(H160::from_low_u64_be(CALLEE), hex::decode(forward).unwrap());
+	frames.codes.insert(H160::from_low_u64_be(0xcc), hex::decode(STORE_AND_RETURN).unwrap());
+
+	let (reason, output) = run(&mut frames);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&output), U256::from(44));
+	assert_eq!(frames.feedback, vec!["call", "call"]);
+	assert_eq!((frames.depth, frames.max_depth), (0, 2));
+}
+
+#[test]
+fn creates_are_run_in_child_runtimes() {
+	let mut frames = Frames::default();
+	frames.codes.insert(H160::from_low_u64_be(TARGET), hex::decode(CREATE).unwrap());
+
+	let (reason, output) = run(&mut frames);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	let address = H160::from_slice(&output[12..]);
+	assert_eq!(address, H160::from_low_u64_be(0xc1));
+	assert_eq!(frames.codes[&address], vec![0x2a]);
+	assert_eq!(frames.feedback, vec!["create"]);
+}
+
+#[test]
+fn dropped_interrupts_exit_the_runtime() {
+	let mut frames = Frames::default();
+	let context = Context {
+		address: H160::from_low_u64_be(TARGET),
+		caller: H160::from_low_u64_be(CALLER),
+		apparent_value: U256::zero(),
+	};
+	let mut runtime = frames.runtime(hex::decode(CALL_AND_ADD).unwrap(), Bytes::new(), context);
+	match block_on(runtime.run(&mut frames)) {
+		Capture::Trap(_) => (),
+		Capture::Exit(reason) => panic!("unexpected exit {:?}", reason),
+	}
+	assert!(matches!(block_on(runtime.run(&mut frames)), Capture::Exit(ExitReason::Fatal(_))));
+}
+
+#[test]
+fn resolving_replaces_the_zero_pushed_on_trap() {
+	let mut frames = Frames::default();
+	let context = Context {
+		address: H160::from_low_u64_be(TARGET),
+		caller: H160::from_low_u64_be(CALLER),
+		apparent_value: U256::zero(),
+	};
+
+	// Manual trampolines read the zero pushed in place of the call result.
+	let mut runtime = frames.runtime(hex::decode(CALL_AND_ADD).unwrap(), Bytes::new(), context.clone());
+	assert!(matches!(block_on(runtime.run(&mut frames)), Capture::Trap(_)));
+	assert_eq!(runtime.machine().stack().len(), 1);
+	assert_eq!(runtime.machine().stack().peek(0), Ok(H256::zero()));
+
+	let mut runtime = frames.runtime(hex::decode(CALL_AND_ADD).unwrap(), Bytes::new(), context);
+	match block_on(runtime.run(&mut frames)) {
+		Capture::Trap(Resolve::Call(_, resolve)) =>
+			resolve.resolve(ExitReason::Succeed(ExitSucceed::Returned), Bytes::new()),
+		_ => panic!("expected a call interrupt"),
+	}
+	assert_eq!(runtime.machine().stack().len(), 1);
+	assert_eq!(runtime.machine().stack().peek(0), Ok(H256::from_low_u64_be(1)));
+}
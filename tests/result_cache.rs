@@ -0,0 +1,95 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::executor::{
+	LruResultCache, ResultCache, ResultCacheKey, StackExecutor, Transaction, TransactionAction,
+	transaction_hash,
+};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, block_on, deploy};
+
+// Return the call data length as a word.
+const LENGTH: &str = "3660005260206000f3";
+
+fn call(data: Vec<u8>) -> Transaction {
+	Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action: TransactionAction::Call(H160::from_low_u64_be(TARGET)),
+		value: U256::zero(),
+		data,
+		gas_limit: 100_000,
+		nonce: None,
+	}
+}
+
+#[test]
+fn identical_calls_are_served_from_the_cache() {
+	let backend = deploy(LENGTH);
+	let cache = LruResultCache::new(16);
+	let state = H256::repeat_byte(1);
+
+	let mut executor = StackExecutor::new(backend.clone(), 100_000, Arc::new(Config::istanbul()));
+	let first = block_on(executor.transact_cached(&cache, state, call(vec![1, 2, 3])));
+	assert!(first.is_succeed());
+	assert_eq!(U256::from_big_endian(&first.output), U256::from(3));
+	assert_eq!((cache.hits(), cache.misses(), cache.len()), (0, 1, 1));
+
+	let mut executor = StackExecutor::new(backend.clone(), 100_000, Arc::new(Config::istanbul()));
+	let second = block_on(executor.transact_cached(&cache, state, call(vec![1, 2, 3])));
+	assert_eq!(second, first);
+	assert_eq!(executor.used_gas(), 0);
+	assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+	let mut executor = StackExecutor::new(backend.clone(), 100_000, Arc::new(Config::istanbul()));
+	block_on(executor.transact_cached(&cache, H256::repeat_byte(2), call(vec![1, 2, 3])));
+	block_on(executor.transact_cached(&cache, state, call(vec![1, 2])));
+	assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 3, 3));
+
+	block_on(cache.invalidate(state));
+	assert_eq!(cache.len(), 1);
+	assert!(block_on(cache.get(&ResultCacheKey::new(state, &call(vec![1, 2, 3])))).is_none());
+	assert!(block_on(cache.get(&ResultCacheKey::new(H256::repeat_byte(2), &call(vec![1, 2, 3])))).is_some());
+}
+
+#[test]
+fn least_recently_used_results_are_evicted() {
+	let backend = deploy(LENGTH);
+	let cache = LruResultCache::new(2);
+	let state = H256::repeat_byte(1);
+
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	let result = block_on(executor.transact_cached(&cache, state, call(vec![1])));
+	block_on(cache.insert(ResultCacheKey::new(state, &call(vec![2])), result.clone()));
+	// Use the first result, so that the second is the least recently used.
+	assert!(block_on(cache.get(&ResultCacheKey::new(state, &call(vec![1])))).is_some());
+	block_on(cache.insert(ResultCacheKey::new(state, &call(vec![3])), result));
+
+	assert_eq!(cache.len(), 2);
+	assert!(block_on(cache.get(&ResultCacheKey::new(state, &call(vec![1])))).is_some());
+	assert!(block_on(cache.get(&ResultCacheKey::new(state, &call(vec![2])))).is_none());
+	assert!(block_on(cache.get(&ResultCacheKey::new(state, &call(vec![3])))).is_some());
+
+	block_on(cache.clear());
+	assert!(cache.is_empty());
+}
+
+#[test]
+fn transaction_hashes_cover_every_field() {
+	let base = call(vec![1]);
+	let mut variants = vec![base.clone(); 5];
+	variants[0].caller = H160::from_low_u64_be(1);
+	variants[1].action = TransactionAction::Create;
+	variants[2].value = U256::one();
+	variants[3].data = vec![2];
+	variants[4].gas_limit = 1;
+
+	let mut hashes = variants.iter().map(transaction_hash).collect::<Vec<_>>();
+	hashes.push(transaction_hash(&base));
+	hashes.push(transaction_hash(&Transaction { action: TransactionAction::Create2(H256::zero()), ..base }));
+	hashes.sort();
+	hashes.dedup();
+	assert_eq!(hashes.len(), 7);
+}
@@ -0,0 +1,111 @@
+//! `RETURNDATACOPY` bounds semantics, per EIP-211.
+
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Bytes, Config, ExitError, ExitReason, ExitSucceed};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, account, backend, block_on, call_target};
+
+const LIBRARY: u64 = 0xcc;
+const LARGE_LIBRARY: u64 = 0xbb;
+
+// Return the 32 byte word `42`.
+const RETURN_WORD: &str = "602a60005260206000f3";
+// CALL `LIBRARY` with all gas, discarding the result.
+const CALL: &str = "600060006000600060007300000000000000000000000000000000000000cc5af150";
+
+// Return 8 KiB of memory, starting with 0x11 and ending with 0x22.
+const LARGE: &str = "60116000536022611fff536120006000f3";
+// Call `LARGE_LIBRARY`, then return its return data.
+const FORWARD: &str = "6000600060006000600060bb5af1503d600060003e3d6000f3";
+
+/// Copy `len` bytes of return data at `offset` into memory 0 and return them.
+fn copy(offset: u8, len: u8) -> String {
+	format!("60{:02x}60{:02x}60003e60{:02x}6000f3", len, offset, len)
+}
+
+fn run(code: &str) -> (ExitReason, Vec<u8>) {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(code)),
+		(H160::from_low_u64_be(LIBRARY), account(RETURN_WORD)),
+	]);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+
+	call_target(&mut executor, vec![0x11; 4], 100_000)
+}
+
+#[test]
+fn copy_within_bounds() {
+	let (reason, out) = run(&(CALL.to_owned() + &copy(0, 32)));
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&out), U256::from(42));
+
+	let (reason, out) = run(&(CALL.to_owned() + &copy(31, 1)));
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out, vec![42]);
+}
+
+#[test]
+fn copy_past_end_fails() {
+	assert_eq!(
+		run(&(CALL.to_owned() + &copy(1, 32))).0,
+		ExitReason::Error(ExitError::OutOfOffset),
+	);
+	assert_eq!(
+		run(&(CALL.to_owned() + &copy(0, 33))).0,
+		ExitReason::Error(ExitError::OutOfOffset),
+	);
+}
+
+#[test]
+fn zero_length_copy_past_end_fails() {
+	assert_eq!(run(&copy(0, 0)).0, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(run(&copy(1, 0)).0, ExitReason::Error(ExitError::OutOfOffset));
+}
+
+#[test]
+fn calldatacopy_past_end_zero_fills() {
+	// CALLDATACOPY 8 bytes at offset 2 of the 4 byte input and return them.
+	let (reason, out) = run("6008600260003760086000f3");
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out, vec![0x11, 0x11, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn forwards_large_return_data() {
+	let caller = H160::from_low_u64_be(CALLER);
+	let backend = backend(vec![
+		(caller, account("")),
+		(H160::from_low_u64_be(TARGET), account(FORWARD)),
+		(H160::from_low_u64_be(LARGE_LIBRARY), account(LARGE)),
+	]);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+
+	let (reason, output) = block_on(executor.transact_call(
+		caller, H160::from_low_u64_be(TARGET), U256::zero(), Vec::new(), 1_000_000,
+	));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(output.len(), 0x2000);
+	assert_eq!((output[0], output[1], output[0x1fff]), (0x11, 0, 0x22));
+}
+
+#[test]
+fn bytes_share_their_buffer() {
+	let buffer = Arc::new((0..16).collect::<Vec<u8>>());
+	let bytes = Bytes::from_shared(buffer.clone(), 4..12);
+	assert_eq!(bytes.as_slice(), &buffer[4..12]);
+	assert_eq!(bytes.slice(2..20), Bytes::from(vec![6, 7, 8, 9, 10, 11]));
+	assert_eq!(Arc::strong_count(&buffer), 2);
+	assert_eq!(bytes.into_vec(), buffer[4..12].to_vec());
+	assert_eq!(Bytes::from_shared(buffer, 20..30), Bytes::new());
+
+	let owned = Bytes::from(vec![1, 2, 3]);
+	let ptr = owned.as_ptr();
+	let vec = owned.into_vec();
+	assert_eq!(vec.as_ptr(), ptr);
+}
@@ -0,0 +1,192 @@
+#![cfg(feature = "rpc")]
+
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::{MemoryAccount, MemoryBackend};
+use evm::rpc::{EXECUTION_REVERTED, INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR, RpcServer, TRANSACTION_REJECTED};
+use evm::signing::{LegacyTransaction, SignedTransaction, UnsignedTransaction, secret_address};
+use primitive_types::{H160, H256, U256};
+
+use common::{TARGET, account, block_on, vicinity};
+
+const REVERTER: u64 = 0xbb;
+const OUTER: u64 = 0xcc;
+
+fn secret() -> H256 {
+	H256::repeat_byte(0x46)
+}
+
+fn server() -> RpcServer<MemoryBackend> {
+	let sender = secret_address(&secret()).unwrap();
+	let state = BTreeMap::from([
+		(sender, MemoryAccount { nonce: U256::zero(), ..account("") }),
+		// SSTORE(0, 42), LOG1 with topic 7 and empty data, then return 42
+		// as a word.
+		(H160::from_low_u64_be(TARGET), account("602a600055600760006000a1602a60005260206000f3")),
+		// Revert with 0xdead.
+		(H160::from_low_u64_be(REVERTER), account("61dead6000526002601efd")),
+		// Call TARGET with all gas, then stop.
+		(H160::from_low_u64_be(OUTER), account("600060006000600060007300000000000000000000000000000000000000aa5af100")),
+	]);
+	RpcServer::new(MemoryBackend::new(Arc::new(vicinity()), state), Arc::new(Config::istanbul()))
+}
+
+fn raw_transaction(nonce: u64, to: u64) -> String {
+	let signed = UnsignedTransaction::Legacy(LegacyTransaction {
+		chain_id: Some(U256::one()),
+		nonce: U256::from(nonce),
+		gas_price: U256::zero(),
+		gas_limit: U256::from(100_000),
+		to: Some(H160::from_low_u64_be(to)),
+		value: U256::zero(),
+		data: Vec::new(),
+	}).sign(&secret()).unwrap();
+	format!("0x{}", hex::encode(signed.encode()))
+}
+
+fn request(method: &str, params: &str) -> String {
+	format!("{{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"{}\",\"params\":{}}}", method, params)
+}
+
+fn call_params(to: u64, gas: Option<usize>) -> String {
+	let gas = gas.map(|gas| format!(",\"gas\":\"{:#x}\"", gas)).unwrap_or_default();
+	format!("[{{\"to\":\"{:?}\"{}}},\"latest\"]", H160::from_low_u64_be(to), gas)
+}
+
+#[test]
+fn call_returns_output() {
+	let mut server = server();
+
+	let response = block_on(server.handle(&request("eth_call", &call_params(TARGET, Some(100_000)))));
+
+	assert_eq!(
+		response,
+		format!("{{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":\"0x{}\"}}", hex::encode(H256::from_low_u64_be(42))),
+	);
+	assert!(server.backend().state()[&H160::from_low_u64_be(TARGET)].storage.is_empty());
+}
+
+#[test]
+fn call_reports_revert_data() {
+	let mut server = server();
+
+	let response = block_on(server.handle(&request("eth_call", &call_params(REVERTER, None))));
+
+	assert_eq!(
+		response,
+		format!(
+			"{{\"jsonrpc\":\"2.0\",\"id\":1,\"error\":{{\"code\":{},\"message\":\"execution reverted\",\"data\":\"0xdead\"}}}}",
+			EXECUTION_REVERTED,
+		),
+	);
+}
+
+#[test]
+fn estimate_gas_finds_lowest_limit() {
+	let mut server = server();
+
+	let response = block_on(server.handle(&request("eth_estimateGas", &call_params(TARGET, Some(1_000_000)))));
+	let estimate = response.split("\"result\":\"0x").nth(1).unwrap().trim_end_matches("\"}");
+	let estimate = usize::from_str_radix(estimate, 16).unwrap();
+
+	let succeeds = |server: &mut RpcServer<MemoryBackend>, gas| {
+		!block_on(server.handle(&request("eth_call", &call_params(TARGET, Some(gas))))).contains("\"error\"")
+	};
+	assert!(succeeds(&mut server, estimate));
+	assert!(!succeeds(&mut server, estimate - 1));
+}
+
+#[test]
+fn raw_transaction_is_applied_and_logged() {
+	let mut server = server();
+	let target = H160::from_low_u64_be(TARGET);
+
+	let response = block_on(server.handle(&request("eth_sendRawTransaction", &format!("[\"{}\"]", raw_transaction(0, TARGET)))));
+	assert!(response.contains("\"result\":\"0x"), "{}", response);
+	assert_eq!(server.backend().state()[&target].storage.get(&H256::zero()), Some(&H256::from_low_u64_be(42)));
+
+	let filter = format!("[{{\"fromBlock\":\"earliest\",\"address\":\"{:?}\",\"topics\":[\"{:?}\"]}}]", target, H256::from_low_u64_be(7));
+	let response = block_on(server.handle(&request("eth_getLogs", &filter)));
+	assert_eq!(
+		response,
+		format!(
+			"{{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":[{{\"address\":\"{:?}\",\"topics\":[\"{:?}\"],\"data\":\"0x\",\"blockNumber\":\"0x0\",\"logIndex\":\"0x0\"}}]}}",
+			target,
+			H256::from_low_u64_be(7),
+		),
+	);
+
+	let filter = format!("[{{\"topics\":[\"{:?}\"]}}]", H256::from_low_u64_be(8));
+	let response = block_on(server.handle(&request("eth_getLogs", &filter)));
+	assert!(response.ends_with("\"result\":[]}"));
+
+	// Replaying the transaction fails the nonce check.
+	let response = block_on(server.handle(&request("eth_sendRawTransaction", &format!("[\"{}\"]", raw_transaction(0, TARGET)))));
+	assert!(response.contains(&format!("\"code\":{},\"message\":\"nonce too low", TRANSACTION_REJECTED)), "{}", response);
+	assert_eq!(server.backend().logs().count(), 1);
+}
+
+#[test]
+fn malleable_raw_transaction_is_rejected() {
+	let mut server = server();
+	let raw = hex::decode(&raw_transaction(0, TARGET)[2..]).unwrap();
+	let mut signed = SignedTransaction::decode(&raw).unwrap();
+	// The malleated twin of the signature, with `s` replaced by `n - s`.
+	let order = hex::decode("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141").unwrap();
+	signed.signature.s = U256::from_big_endian(&order) - signed.signature.s;
+	signed.signature.y_parity = !signed.signature.y_parity;
+	let raw = format!("0x{}", hex::encode(signed.encode()));
+
+	let response = block_on(server.handle(&request("eth_sendRawTransaction", &format!("[\"{}\"]", raw))));
+	assert!(response.contains(&format!("\"code\":{},\"message\":\"invalid signature", INVALID_PARAMS)), "{}", response);
+	assert!(server.backend().state()[&H160::from_low_u64_be(TARGET)].storage.is_empty());
+}
+
+#[test]
+fn trace_call_nests_frames() {
+	let mut server = server();
+
+	let response = block_on(server.handle(&request("debug_traceCall", &call_params(OUTER, Some(100_000)))));
+
+	assert!(response.contains("\"result\":{\"type\":\"CALL\""), "{}", response);
+	assert!(response.contains(&format!(
+		"\"calls\":[{{\"type\":\"CALL\",\"from\":\"{:?}\",\"to\":\"{:?}\"",
+		H160::from_low_u64_be(OUTER),
+		H160::from_low_u64_be(TARGET),
+	)), "{}", response);
+}
+
+#[test]
+fn malformed_requests_are_rejected() {
+	let mut server = server();
+
+	let response = block_on(server.handle("{"));
+	assert!(response.contains(&format!("\"id\":null,\"error\":{{\"code\":{}", PARSE_ERROR)));
+
+	let response = block_on(server.handle(&request("eth_chainId", "[]")));
+	assert!(response.contains(&format!("\"code\":{}", METHOD_NOT_FOUND)));
+
+	let response = block_on(server.handle(&request("eth_call", "[{\"to\":\"0x12\"}]")));
+	assert!(response.contains(&format!("\"code\":{}", INVALID_PARAMS)));
+
+	let batch = format!("[{},{}]", request("eth_call", &call_params(TARGET, None)), request("eth_foo", "[]"));
+	let response = block_on(server.handle(&batch));
+	assert!(response.starts_with("[{\"jsonrpc\""));
+	assert!(response.contains(&format!("\"code\":{}", METHOD_NOT_FOUND)));
+}
+
+#[test]
+fn deeply_nested_requests_are_rejected() {
+	let mut server = server();
+
+	let response = block_on(server.handle(&"[".repeat(200_000)));
+	assert!(response.contains(&format!("\"error\":{{\"code\":{}", PARSE_ERROR)), "{}", response);
+
+	// Nesting within the limit parses, and fails as an invalid request.
+	let response = block_on(server.handle(&format!("{}{}", "[".repeat(100), "]".repeat(100))));
+	assert!(!response.contains(&format!("\"code\":{}", PARSE_ERROR)), "{}", response);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::Config;
+use evm::executor::{Finding, StackExecutor};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, account, backend, block_on};
+
+const VAULT: u64 = 0xaa;
+const ATTACKER: u64 = 0xbb;
+const PROXY: u64 = 0xcc;
+const IMPLEMENTATION: u64 = 0xdd;
+
+// Stop if called with data. Otherwise SLOAD slot 0, CALL `ATTACKER`, then
+// SSTORE 1 to slot 0.
+const VAULT_CODE: &str = "36603057600054506000600060006000600073\
+	00000000000000000000000000000000000000bb5af1506001600055005b00";
+// CALL `VAULT` with one byte of data.
+const ATTACKER_CODE: &str = "600060006001600060007300000000000000000000000000000000000000aa5af15000";
+// SLOAD slot 0, then DELEGATECALL `IMPLEMENTATION`.
+const PROXY_CODE: &str = "6000545060006000600060007300000000000000000000000000000000000000dd5af45000";
+// SSTORE 1 to slot `SLOT`.
+fn implementation_code(slot: u8) -> String {
+	format!("600160{:02x}5500", slot)
+}
+
+fn run(target: u64, slot: u8, enable: bool) -> Option<Vec<Finding>> {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(VAULT), account(VAULT_CODE)),
+		(H160::from_low_u64_be(ATTACKER), account(ATTACKER_CODE)),
+		(H160::from_low_u64_be(PROXY), account(PROXY_CODE)),
+		(H160::from_low_u64_be(IMPLEMENTATION), account(&implementation_code(slot))),
+	]);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+	if enable {
+		executor.enable_security_analysis();
+	}
+
+	let (reason, _) = block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER),
+		H160::from_low_u64_be(target),
+		U256::zero(),
+		Vec::new(),
+		200_000,
+	));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	executor.security_findings()
+}
+
+#[test]
+fn disabled_by_default() {
+	assert_eq!(run(VAULT, 0, false), None);
+}
+
+#[test]
+fn flags_reentrancy_and_write_after_call() {
+	let vault = H160::from_low_u64_be(VAULT);
+	assert_eq!(run(VAULT, 0, true), Some(vec![
+		Finding::Reentrancy { address: vault, depth: 2 },
+		Finding::WriteAfterCall { address: vault, index: H256::zero(), position: 46 },
+	]));
+}
+
+#[test]
+fn flags_delegated_storage_collisions() {
+	assert_eq!(run(PROXY, 0, true), Some(vec![Finding::StorageCollision {
+		address: H160::from_low_u64_be(PROXY),
+		code_address: H160::from_low_u64_be(IMPLEMENTATION),
+		index: H256::zero(),
+	}]));
+	assert_eq!(run(PROXY, 1, true), Some(Vec::new()));
+}
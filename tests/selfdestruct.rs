@@ -0,0 +1,114 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::{ApplyBackend, MemoryAccount, MemoryBackend};
+use evm::executor::{ExecutionResult, StackExecutor, Transaction, TransactionAction};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, account, block_on, vicinity};
+
+const VICTIM: u64 = 0xcc;
+const DONOR: u64 = 0xdd;
+const BENEFICIARY: u64 = 0xbb;
+
+const BALANCE: u64 = 1_000_000_000;
+
+// SELFDESTRUCT to the executing address.
+const DESTRUCT_SELF: &str = "30ff";
+// SELFDESTRUCT to `BENEFICIARY`.
+const DESTRUCT_OTHER: &str = "7300000000000000000000000000000000000000bbff";
+// SELFDESTRUCT to `VICTIM`.
+const DESTRUCT_TO_VICTIM: &str = "7300000000000000000000000000000000000000ccff";
+
+fn address(low: u64) -> String {
+	format!("73{:040x}", low)
+}
+
+/// CALL the address with all gas and no value, and pop the result.
+fn call(low: u64) -> String {
+	format!("60006000600060006000{}5af150", address(low))
+}
+
+fn run(accounts: Vec<(u64, &str)>) -> (ExecutionResult, BTreeMap<H160, MemoryAccount>) {
+	let mut state = accounts.into_iter()
+		.map(|(low, code)| (H160::from_low_u64_be(low), account(code)))
+		.collect::<BTreeMap<_, _>>();
+	state.insert(H160::from_low_u64_be(CALLER), account(""));
+	let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity()), state.clone()));
+
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+	let result = block_on(executor.transact(Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action: TransactionAction::Call(H160::from_low_u64_be(TARGET)),
+		value: U256::zero(),
+		data: Vec::new(),
+		gas_limit: 1_000_000,
+		nonce: None,
+	}));
+	let (applies, logs) = executor.deconstruct();
+	let mut post = MemoryBackend::new(Arc::new(vicinity()), state);
+	block_on(post.apply(applies, logs, false));
+
+	(result, post.state().clone())
+}
+
+#[test]
+fn self_destruct_to_self_burns_the_balance() {
+	let (result, state) = run(vec![(TARGET, DESTRUCT_SELF)]);
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert_eq!(result.burned, U256::from(BALANCE));
+	assert!(!state.contains_key(&H160::from_low_u64_be(TARGET)));
+}
+
+#[test]
+fn self_destruct_to_another_account_burns_nothing() {
+	let (result, state) = run(vec![(TARGET, DESTRUCT_OTHER)]);
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert_eq!(result.burned, U256::zero());
+	assert!(!state.contains_key(&H160::from_low_u64_be(TARGET)));
+	assert_eq!(state[&H160::from_low_u64_be(BENEFICIARY)].balance, U256::from(BALANCE));
+}
+
+#[test]
+fn value_received_after_self_destruct_is_burned() {
+	let code = format!("{}{}00", call(VICTIM), call(DONOR));
+	let (result, state) = run(vec![
+		(TARGET, &code),
+		(VICTIM, DESTRUCT_OTHER),
+		(DONOR, DESTRUCT_TO_VICTIM),
+	]);
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert_eq!(result.burned, U256::from(BALANCE));
+	assert!(!state.contains_key(&H160::from_low_u64_be(VICTIM)));
+	assert!(!state.contains_key(&H160::from_low_u64_be(DONOR)));
+	assert_eq!(state[&H160::from_low_u64_be(BENEFICIARY)].balance, U256::from(BALANCE));
+}
+
+#[test]
+fn self_destructed_code_runs_until_the_end_of_the_transaction() {
+	// Call the victim twice, then return its code size.
+	let code = format!("{}{}{}3b60005260206000f3", call(VICTIM), call(VICTIM), address(VICTIM));
+	let (result, state) = run(vec![(TARGET, &code), (VICTIM, DESTRUCT_SELF)]);
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert_eq!(U256::from_big_endian(&result.output), U256::from(2));
+	assert_eq!(result.burned, U256::from(BALANCE));
+	assert!(!state.contains_key(&H160::from_low_u64_be(VICTIM)));
+}
+
+#[test]
+fn reverted_self_destruct_burns_nothing() {
+	// Call the victim, then revert.
+	let reverting = format!("{}60006000fd", call(VICTIM));
+	let code = format!("{}00", call(DONOR));
+	let (result, state) = run(vec![
+		(TARGET, &code),
+		(DONOR, &reverting),
+		(VICTIM, DESTRUCT_SELF),
+	]);
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert_eq!(result.burned, U256::zero());
+	assert_eq!(state[&H160::from_low_u64_be(VICTIM)].balance, U256::from(BALANCE));
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "k256")]
+
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError, ExitFatal, ExitReason, ExitSucceed, StateQuery};
+use evm::backend::{MemoryBackend, MemoryVicinity};
+use evm::executor::{ExecutionResult, StackExecutor, TxValidationError, delegated_address, delegation_designator};
+use evm::signing::{Authorization, Eip7702Transaction, SignedAuthorization, UnsignedTransaction,
+				   decode_authorization_list, secret_address};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, account, block_on, prague_eips, vicinity};
+
+const DELEGATE: u64 = 0xdd;
+const GAS_PRICE: u64 = 10;
+
+fn secret() -> H256 {
+	H256::repeat_byte(0x46)
+}
+
+fn authorization(nonce: u64) -> SignedAuthorization {
+	Authorization {
+		chain_id: U256::one(),
+		address: H160::from_low_u64_be(DELEGATE),
+		nonce: U256::from(nonce),
+	}.sign(&secret()).unwrap()
+}
+
+fn run(config: Config, authorizations: &[SignedAuthorization]) -> (ExitReason, StackExecutor<MemoryBackend>) {
+	let (result, executor) = run_with(config, 200_000, authorizations);
+	(result.reason, executor)
+}
+
+fn run_with(
+	config: Config,
+	gas_limit: usize,
+	authorizations: &[SignedAuthorization],
+) -> (ExecutionResult, StackExecutor<MemoryBackend>) {
+	let authority = secret_address(&secret()).unwrap();
+	let vicinity = MemoryVicinity { gas_price: U256::from(GAS_PRICE), ..vicinity() };
+	let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity), vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		// Store the caller at slot 0.
+		(H160::from_low_u64_be(DELEGATE), account("3360005500")),
+		(authority, account("")),
+	].into_iter().collect()));
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(config));
+
+	let result = block_on(executor.transact_set_code(
+		H160::from_low_u64_be(CALLER),
+		authority,
+		U256::zero(),
+		Vec::new(),
+		gas_limit,
+		authorizations,
+	));
+	(result, executor)
+}
+
+#[test]
+fn delegation_runs_delegate_code_on_authority() {
+	let authority = secret_address(&secret()).unwrap();
+	let (reason, executor) = run(prague_eips(), &[authorization(1)]);
+
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	let code = block_on(executor.code(authority));
+	assert_eq!(code, delegation_designator(H160::from_low_u64_be(DELEGATE)));
+	assert_eq!(delegated_address(&code), Some(H160::from_low_u64_be(DELEGATE)));
+	assert_eq!(block_on(executor.nonce(authority)), U256::from(2));
+	assert_eq!(
+		block_on(executor.storage(authority, H256::zero())),
+		H256::from(H160::from_low_u64_be(CALLER)),
+	);
+}
+
+#[test]
+fn mismatched_nonce_is_skipped() {
+	let authority = secret_address(&secret()).unwrap();
+	let (reason, executor) = run(prague_eips(), &[authorization(0)]);
+
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert!(block_on(executor.code(authority)).is_empty());
+	assert_eq!(block_on(executor.storage(authority, H256::zero())), H256::zero());
+}
+
+#[test]
+fn requires_prague() {
+	let (reason, _) = run(Config::istanbul(), &[authorization(1)]);
+	assert_eq!(reason, ExitReason::Fatal(ExitFatal::NotSupported));
+}
+
+#[test]
+fn caller_pays_for_gas() {
+	let (result, executor) = run_with(prague_eips(), 200_000, &[authorization(1)]);
+
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert!(result.gas_used > 21_000 + 25_000 - 12_500);
+	assert_eq!(
+		block_on(executor.balance(H160::from_low_u64_be(CALLER))),
+		U256::from(1_000_000_000u64) - U256::from(result.gas_used) * GAS_PRICE,
+	);
+}
+
+#[test]
+fn gas_limit_must_cover_authorizations() {
+	let (result, executor) = run_with(prague_eips(), 21_000 + 25_000 - 1, &[authorization(1)]);
+
+	assert_eq!(result.reason, ExitReason::Error(ExitError::OutOfGas));
+	assert_eq!(result.validation_error, Some(TxValidationError::GasLimitTooLow { needed: 21_000 + 25_000 }));
+	assert_eq!(result.gas_used, 0);
+	assert_eq!(block_on(executor.balance(H160::from_low_u64_be(CALLER))), U256::from(1_000_000_000u64));
+	assert!(block_on(executor.code(secret_address(&secret()).unwrap())).is_empty());
+}
+
+#[test]
+fn authorization_list_round_trip() {
+	let list = vec![authorization(1), authorization(2)];
+	let encoded = rlp::encode_list(&list);
+
+	let decoded = decode_authorization_list(&encoded).unwrap();
+	assert_eq!(decoded, list);
+	assert_eq!(decoded[0].authority().unwrap(), secret_address(&secret()).unwrap());
+}
+
+#[test]
+fn eip7702_sender_round_trip() {
+	let tx = UnsignedTransaction::Eip7702(Eip7702Transaction {
+		chain_id: U256::one(),
+		nonce: U256::zero(),
+		max_priority_fee_per_gas: U256::one(),
+		max_fee_per_gas: U256::from(100),
+		gas_limit: U256::from(100_000),
+		to: H160::repeat_byte(1),
+		value: U256::zero(),
+		data: Vec::new(),
+		access_list: Vec::new(),
+		authorization_list: vec![authorization(1)],
+	});
+
+	let signed = tx.sign(&secret()).unwrap();
+	assert_eq!(signed.encode()[0], 0x04);
+	assert_eq!(signed.sender().unwrap(), secret_address(&secret()).unwrap());
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitReason};
+use evm::executor::{ExecutionResult, StackExecutor, Transaction, TransactionAction, intrinsic_gas};
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, block_on, deploy, prague_eips, shanghai_eips};
+
+fn transact(config: Config, code: &str, action: Option<TransactionAction>, data: Vec<u8>) -> ExecutionResult {
+	let backend = deploy(code);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(config));
+	block_on(executor.transact(Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action: action.unwrap_or(TransactionAction::Call(H160::from_low_u64_be(TARGET))),
+		value: U256::zero(),
+		data,
+		gas_limit: 1_000_000,
+		nonce: None,
+	}))
+}
+
+fn call(config: Config, code: &str) -> ExecutionResult {
+	transact(config, code, None, Vec::new())
+}
+
+#[test]
+fn push0_is_shanghai_only() {
+	// PUSH0 PUSH1 0x00 MSTORE, then RETURN 32 bytes of memory.
+	let code = "5f60005260206000f3";
+	let result = call(shanghai_eips(), code);
+	assert!(result.is_succeed(), "{:?}", result.reason);
+	assert_eq!(result.output, vec![0; 32]);
+
+	assert!(!call(Config::istanbul(), code).is_succeed());
+	assert!(call(prague_eips(), code).is_succeed());
+}
+
+#[test]
+fn push0_costs_base_gas() {
+	let push0 = call(shanghai_eips(), "5f50");
+	let push1 = call(shanghai_eips(), "600050");
+	assert!(push0.is_succeed() && push1.is_succeed());
+	assert_eq!(push1.gas_used - push0.gas_used, 1);
+}
+
+#[test]
+fn initcode_is_metered_in_create_transactions() {
+	let transaction = Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action: TransactionAction::Create,
+		value: U256::zero(),
+		data: vec![0; 33],
+		gas_limit: 100_000,
+		nonce: None,
+	};
+	assert_eq!(
+		intrinsic_gas(&transaction, &shanghai_eips()) - intrinsic_gas(&transaction, &Config::istanbul()),
+		4,
+	);
+
+	let shanghai = transact(shanghai_eips(), "", Some(TransactionAction::Create), vec![0; 64]);
+	let istanbul = transact(Config::istanbul(), "", Some(TransactionAction::Create), vec![0; 64]);
+	assert!(shanghai.is_succeed() && istanbul.is_succeed());
+	assert_eq!(shanghai.gas_used - istanbul.gas_used, 4);
+}
+
+#[test]
+fn initcode_is_metered_in_create_opcodes() {
+	// CREATE and CREATE2 of 64 zero bytes of memory, running STOP.
+	for code in &["604060006000f0", "6000604060006000f5"] {
+		let shanghai = call(shanghai_eips(), code);
+		let istanbul = call(Config::istanbul(), code);
+		assert!(shanghai.is_succeed() && istanbul.is_succeed());
+		assert_eq!(shanghai.gas_used - istanbul.gas_used, 4);
+	}
+}
+
+#[test]
+fn shanghai_limits_initcode_size() {
+	assert_eq!(shanghai_eips().max_initcode_size, Some(0xc000));
+	let result = transact(shanghai_eips(), "", Some(TransactionAction::Create), vec![0; 0xc001]);
+	assert_eq!(result.reason, ExitReason::Error(evm::ExitError::InitCodeLimit));
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "k256")]
+
+use evm::signing::{Eip1559Transaction, LegacyTransaction, SignedTransaction, UnsignedTransaction, secret_address};
+use primitive_types::{H160, H256, U256};
+
+fn secret() -> H256 {
+	H256::repeat_byte(0x46)
+}
+
+#[test]
+fn eip155_example() {
+	let tx = UnsignedTransaction::Legacy(LegacyTransaction {
+		chain_id: Some(U256::one()),
+		nonce: U256::from(9),
+		gas_price: U256::from(20_000_000_000u64),
+		gas_limit: U256::from(21_000),
+		to: Some(H160::repeat_byte(0x35)),
+		value: U256::from(1_000_000_000_000_000_000u64),
+		data: Vec::new(),
+	});
+	assert_eq!(
+		hex::encode(tx.signing_hash()),
+		"daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53",
+	);
+
+	let signed = tx.sign(&secret()).unwrap();
+	assert_eq!(
+		hex::encode(signed.encode()),
+		"f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000\
+		8025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761a\
+		ecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83",
+	);
+	assert_eq!(signed.sender().unwrap(), secret_address(&secret()).unwrap());
+}
+
+#[test]
+fn eip1559_sender_round_trip() {
+	let tx = UnsignedTransaction::Eip1559(Eip1559Transaction {
+		chain_id: U256::from(5),
+		nonce: U256::zero(),
+		max_priority_fee_per_gas: U256::one(),
+		max_fee_per_gas: U256::from(100),
+		gas_limit: U256::from(50_000),
+		to: None,
+		value: U256::zero(),
+		data: vec![0x60, 0x00],
+		access_list: vec![(H160::repeat_byte(1), vec![H256::zero()])],
+	});
+
+	let signed = tx.sign(&secret()).unwrap();
+	assert_eq!(signed.encode()[0], 0x02);
+	assert_eq!(signed.sender().unwrap(), secret_address(&secret()).unwrap());
+}
+
+#[test]
+fn decode_round_trips() {
+	let legacy = UnsignedTransaction::Legacy(LegacyTransaction {
+		chain_id: Some(U256::one()),
+		nonce: U256::from(9),
+		gas_price: U256::from(20),
+		gas_limit: U256::from(21_000),
+		to: Some(H160::repeat_byte(0x35)),
+		value: U256::one(),
+		data: vec![0x01],
+	});
+	let eip1559 = UnsignedTransaction::Eip1559(Eip1559Transaction {
+		chain_id: U256::from(5),
+		nonce: U256::zero(),
+		max_priority_fee_per_gas: U256::one(),
+		max_fee_per_gas: U256::from(100),
+		gas_limit: U256::from(50_000),
+		to: None,
+		value: U256::zero(),
+		data: vec![0x60, 0x00],
+		access_list: vec![(H160::repeat_byte(1), vec![H256::zero()])],
+	});
+
+	for tx in [legacy, eip1559] {
+		let signed = tx.sign(&secret()).unwrap();
+		assert_eq!(SignedTransaction::decode(&signed.encode()), Ok(signed));
+	}
+	assert!(SignedTransaction::decode(&[0x05, 0xc0]).is_err());
+}
+
+#[test]
+fn high_s_signatures_are_rejected() {
+	let tx = UnsignedTransaction::Eip1559(Eip1559Transaction {
+		chain_id: U256::from(5),
+		nonce: U256::zero(),
+		max_priority_fee_per_gas: U256::one(),
+		max_fee_per_gas: U256::from(100),
+		gas_limit: U256::from(50_000),
+		to: Some(H160::repeat_byte(0x35)),
+		value: U256::zero(),
+		data: Vec::new(),
+		access_list: Vec::new(),
+	});
+	let mut signed = tx.sign(&secret()).unwrap();
+	assert!(!signed.signature.is_high_s());
+
+	// The malleated twin of the signature, with `s` replaced by `n - s`.
+	let order = hex::decode("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141").unwrap();
+	signed.signature.s = U256::from_big_endian(&order) - signed.signature.s;
+	signed.signature.y_parity = !signed.signature.y_parity;
+	assert!(signed.signature.is_high_s());
+	assert!(signed.sender().is_err());
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::backend::{state_root, MemoryBackend, MemoryVicinity, SnapshotError, SNAPSHOT_MAGIC, SNAPSHOT_VERSION};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, vicinity};
+
+fn backend() -> MemoryBackend {
+	let mut contract = account("6000600055");
+	contract.storage.insert(H256::from_low_u64_be(1), H256::from_low_u64_be(0x2a));
+	contract.storage.insert(H256::repeat_byte(0xff), H256::repeat_byte(0xee));
+	let mut state = BTreeMap::new();
+	state.insert(H160::from_low_u64_be(0xaa), contract);
+	state.insert(H160::from_low_u64_be(0xf0), account(""));
+
+	let vicinity = MemoryVicinity {
+		block_number: U256::from(7),
+		block_hashes: vec![H256::repeat_byte(1), H256::repeat_byte(2)],
+		..vicinity()
+	};
+	MemoryBackend::new(Arc::new(vicinity), state)
+}
+
+#[test]
+fn json_dumps_round_trip() {
+	let backend = backend();
+	let json = backend.dump_json();
+	assert!(json.starts_with(&format!("{{\"root\":\"{:?}\"", state_root(backend.state()))));
+	assert!(json.contains("\"0x0000000000000000000000000000000000000000000000000000000000000001\":\"2a\""));
+	assert!(json.contains("\"code\":\"0x6000600055\""));
+
+	let loaded = MemoryBackend::load_json(Arc::new(vicinity()), &json).unwrap();
+	assert_eq!(loaded.state(), backend.state());
+}
+
+#[test]
+fn geth_dumps_are_loaded() {
+	let json = r#"{
+		"root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+		"accounts": {
+			"0x00000000000000000000000000000000000000aa": {
+				"balance": "1000",
+				"nonce": 2,
+				"root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+				"codeHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+				"code": "0x00",
+				"storage": {
+					"0x0000000000000000000000000000000000000000000000000000000000000002": "0102",
+					"0x0000000000000000000000000000000000000000000000000000000000000003": "0x00"
+				}
+			},
+			"0x00000000000000000000000000000000000000bb": { "balance": "0x10" }
+		}
+	}"#;
+	let loaded = MemoryBackend::load_json(Arc::new(vicinity()), json).unwrap();
+	let state = loaded.state();
+
+	let contract = &state[&H160::from_low_u64_be(0xaa)];
+	assert_eq!((contract.balance, contract.nonce), (U256::from(1000), U256::from(2)));
+	assert_eq!(contract.code, vec![0]);
+	assert_eq!(contract.storage.len(), 1);
+	assert_eq!(contract.storage[&H256::from_low_u64_be(2)], H256::from_low_u64_be(0x0102));
+	assert_eq!(state[&H160::from_low_u64_be(0xbb)].balance, U256::from(16));
+}
+
+#[test]
+fn invalid_json_dumps_are_rejected() {
+	let load = |json: &str| MemoryBackend::load_json(Arc::new(vicinity()), json).map(|_| ());
+	assert_eq!(load("{\"accounts\":"), Err(SnapshotError::InvalidJson(12)));
+	assert_eq!(load("{}"), Err(SnapshotError::InvalidField("accounts".to_string())));
+	assert_eq!(
+		load(r#"{"accounts":{"0x00000000000000000000000000000000000000aa":{"balance":"x"}}}"#),
+		Err(SnapshotError::InvalidField("0x00000000000000000000000000000000000000aa.balance".to_string())),
+	);
+}
+
+#[test]
+fn binary_snapshots_round_trip() {
+	let backend = backend();
+	let snapshot = backend.snapshot();
+	assert_eq!(&snapshot[..4], &SNAPSHOT_MAGIC);
+	assert_eq!(snapshot[4], SNAPSHOT_VERSION);
+	assert!(snapshot.len() < backend.dump_json().len() / 2);
+
+	let restored = MemoryBackend::from_snapshot(&snapshot).unwrap();
+	assert_eq!(restored.state(), backend.state());
+	assert_eq!(restored.vicinity(), backend.vicinity());
+}
+
+#[test]
+fn invalid_snapshots_are_rejected() {
+	let snapshot = backend().snapshot();
+	assert_eq!(MemoryBackend::from_snapshot(b"EVM").err(), Some(SnapshotError::InvalidHeader));
+	assert_eq!(MemoryBackend::from_snapshot(&snapshot[1..]).err(), Some(SnapshotError::InvalidHeader));
+
+	let mut future = snapshot.clone();
+	future[4] = SNAPSHOT_VERSION + 1;
+	assert_eq!(MemoryBackend::from_snapshot(&future).err(), Some(SnapshotError::UnsupportedVersion(SNAPSHOT_VERSION + 1)));
+
+	assert_eq!(
+		MemoryBackend::from_snapshot(&snapshot[..snapshot.len() - 1]).err(),
+		Some(SnapshotError::InvalidEncoding),
+	);
+}
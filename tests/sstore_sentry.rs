@@ -0,0 +1,69 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError, ExitReason, ExitSucceed};
+use evm::executor::StackExecutor;
+use primitive_types::H160;
+
+use common::{CALLER, TARGET, account, backend, call_target};
+
+const RECEIVER: u64 = 0xbb;
+
+// SSTORE 0 at slot 0, a no-op write costing `gas_sload`.
+const STORE_ZERO: &str = "600060005500";
+// SSTORE 1 at slot 0.
+const STORE: &str = "600160005500";
+// Emit LOG0 with empty data.
+const LOG: &str = "60006000a000";
+// Send 1 wei to `RECEIVER` with the stipend only and revert if the call
+// fails, as Solidity's `transfer`.
+const TRANSFER: &str = "6000600060006000600173\
+	00000000000000000000000000000000000000bb6000f1\
+	602a5760006000fd5b00";
+
+/// Intrinsic gas plus the two pushes before `SSTORE`.
+const BEFORE_SSTORE: usize = 21_000 + 6;
+
+fn run(code: &str, receiver: &str, gas_limit: usize, config: Config) -> ExitReason {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(code)),
+		(H160::from_low_u64_be(RECEIVER), account(receiver)),
+	]);
+	let mut executor = StackExecutor::new(backend, gas_limit, Arc::new(config));
+	call_target(&mut executor, Vec::new(), gas_limit).0
+}
+
+#[test]
+fn sstore_fails_with_the_stipend_or_less_left() {
+	let reason = run(STORE_ZERO, "", BEFORE_SSTORE + 2300, Config::istanbul());
+	assert_eq!(reason, ExitReason::Error(ExitError::OutOfGas));
+
+	let reason = run(STORE_ZERO, "", BEFORE_SSTORE + 2301, Config::istanbul());
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+}
+
+#[test]
+fn sentry_is_not_enforced_before_istanbul() {
+	let reason = run(STORE, "", BEFORE_SSTORE + 20_000, Config::frontier());
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+}
+
+#[test]
+fn transfer_into_a_storage_writing_contract_reverts() {
+	let reason = run(TRANSFER, STORE, 100_000, Config::istanbul());
+	assert!(matches!(reason, ExitReason::Revert(_)), "{:?}", reason);
+
+	let reason = run(TRANSFER, STORE_ZERO, 100_000, Config::istanbul());
+	assert!(matches!(reason, ExitReason::Revert(_)), "{:?}", reason);
+}
+
+#[test]
+fn transfer_into_a_contract_within_the_stipend_succeeds() {
+	let reason = run(TRANSFER, "", 100_000, Config::istanbul());
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let reason = run(TRANSFER, LOG, 100_000, Config::istanbul());
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, MaybeSync, StateMutator, StateQuery};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256, U256};
+
+use common::{TARGET, account, backend, block_on};
+
+/// A read-only inspector only needs the narrower trait.
+async fn snapshot<Q: StateQuery + MaybeSync>(query: &Q, address: H160, index: H256) -> (U256, H256) {
+	(query.balance(address).await, query.storage(address, index).await)
+}
+
+#[test]
+fn read_only_code_sees_mutations() {
+	let address = H160::from_low_u64_be(TARGET);
+	let index = H256::from_low_u64_be(1);
+	let mut executor = StackExecutor::new(
+		backend(vec![(address, account(""))]),
+		1_000_000,
+		Arc::new(Config::istanbul()),
+	);
+
+	assert_eq!(block_on(snapshot(&executor, address, index)), (U256::from(1_000_000_000u64), H256::zero()));
+
+	block_on(executor.set_storage(address, index, H256::from_low_u64_be(7))).unwrap();
+	assert_eq!(block_on(snapshot(&executor, address, index)).1, H256::from_low_u64_be(7));
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError, ExitReason, ExitSucceed};
+use evm::executor::{CallTrace, StackExecutor};
+use primitive_types::H160;
+
+use common::{CALLER, TARGET, account, backend, call_target};
+
+const CALLEE: u64 = 0xcc;
+const LIBRARY: u64 = 0xdd;
+
+// STATICCALL `CALLEE` with all gas, and return its return data.
+const STATICCALL: &str = "600060006000600073\
+	00000000000000000000000000000000000000cc5afa50\
+	3d600060003e3d6000f3";
+// CALL `CALLEE` with all gas, and return its return data.
+const CALL: &str = "60006000600060006000\
+	73\
+	00000000000000000000000000000000000000cc5af150\
+	3d600060003e3d6000f3";
+// DELEGATECALL into `LIBRARY` and return the call result as a word.
+const DELEGATE: &str = "600060006000600073\
+	00000000000000000000000000000000000000dd5af4600052\
+	60206000f3";
+// SSTORE 1 at slot 0.
+const STORE: &str = "600160005500";
+// LOG0 with empty data.
+const LOG: &str = "60006000a000";
+// CALL `LIBRARY` with value 1.
+const CALL_VALUE: &str = "6000600060006000600173\
+	00000000000000000000000000000000000000dd5af100";
+// CREATE with empty init code.
+const CREATE: &str = "600060006000f000";
+// CREATE2 with empty init code and salt 0.
+const CREATE2: &str = "6000600060006000f500";
+// SELFDESTRUCT to the zero address.
+const SELFDESTRUCT: &str = "6000ff";
+
+/// Call `CALLEE` running `code` from `TARGET`, with STATICCALL if
+/// `is_static` and CALL otherwise. Returns the trace of the callee frame
+/// and its return data.
+fn run(code: &str, library: &str, is_static: bool) -> (CallTrace, Vec<u8>) {
+	let entry = if is_static { STATICCALL } else { CALL };
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(entry)),
+		(H160::from_low_u64_be(CALLEE), account(code)),
+		(H160::from_low_u64_be(LIBRARY), account(library)),
+	]);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+
+	let (reason, output) = call_target(&mut executor, Vec::new(), 100_000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	(executor.call_traces()[0].calls[0].clone(), output)
+}
+
+fn violation() -> ExitReason {
+	ExitReason::Error(ExitError::StaticModeViolation)
+}
+
+#[test]
+fn static_sstore_is_violation() {
+	assert_eq!(run(STORE, "", true).0.reason, violation());
+	assert_eq!(run(STORE, "", false).0.reason, ExitReason::Succeed(ExitSucceed::Stopped));
+}
+
+#[test]
+fn static_log_is_violation() {
+	assert_eq!(run(LOG, "", true).0.reason, violation());
+	assert_eq!(run(LOG, "", false).0.reason, ExitReason::Succeed(ExitSucceed::Stopped));
+}
+
+#[test]
+fn static_value_call_is_violation() {
+	assert_eq!(run(CALL_VALUE, "", true).0.reason, violation());
+	assert_eq!(run(CALL_VALUE, "", false).0.reason, ExitReason::Succeed(ExitSucceed::Stopped));
+}
+
+#[test]
+fn static_create_is_violation() {
+	assert_eq!(run(CREATE, "", true).0.reason, violation());
+	assert_eq!(run(CREATE, "", false).0.reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(run(CREATE2, "", true).0.reason, violation());
+	assert_eq!(run(CREATE2, "", false).0.reason, ExitReason::Succeed(ExitSucceed::Stopped));
+}
+
+#[test]
+fn static_selfdestruct_is_violation() {
+	assert_eq!(run(SELFDESTRUCT, "", true).0.reason, violation());
+	assert!(run(SELFDESTRUCT, "", false).0.reason.is_succeed());
+}
+
+#[test]
+fn static_propagates_through_delegatecall() {
+	let (callee, out) = run(DELEGATE, STORE, false);
+	assert_eq!(callee.reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out[31], 1);
+
+	let (callee, out) = run(DELEGATE, STORE, true);
+	assert_eq!(callee.reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(out[31], 0);
+}
+
+#[test]
+fn static_writes_through_delegatecall_are_violations() {
+	for library in [STORE, LOG, CREATE, CREATE2, SELFDESTRUCT] {
+		let (callee, out) = run(DELEGATE, library, true);
+		assert_eq!(callee.calls[0].reason, violation(), "{}", library);
+		assert_eq!(out[31], 0);
+
+		let (callee, out) = run(DELEGATE, library, false);
+		assert!(callee.calls[0].reason.is_succeed(), "{}: {:?}", library, callee.calls[0].reason);
+		assert_eq!(out[31], 1);
+	}
+}
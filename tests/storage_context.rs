@@ -0,0 +1,87 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, Context, StateQuery};
+use evm::backend::MemoryBackend;
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, account, backend, block_on};
+
+const PROXY: u64 = 0xaa;
+const IMPLEMENTATION: u64 = 0xbb;
+
+// Store CALLVALUE at slot 0 and CALLER at slot 1.
+const RECORD: &str = "34600055336001550000";
+// DELEGATECALL the implementation with all gas.
+const DELEGATE: &str = "600060006000600060bb5af400";
+// CALLCODE the implementation with value 3 and all gas.
+const CALLCODE: &str = "6000600060006000600360bb5af200";
+
+fn address(value: u64) -> H160 {
+	H160::from_low_u64_be(value)
+}
+
+fn run(proxy: &str, value: u64) -> StackExecutor<MemoryBackend> {
+	let backend = backend(vec![
+		(address(CALLER), account("")),
+		(address(PROXY), account(proxy)),
+		(address(IMPLEMENTATION), account(RECORD)),
+	]);
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(Config::istanbul()));
+	assert!(executor.context().is_none());
+
+	let (reason, _) = block_on(executor.transact_call(
+		address(CALLER), address(PROXY), U256::from(value), Vec::new(), 1_000_000,
+	));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	executor
+}
+
+fn storage(executor: &StackExecutor<MemoryBackend>, owner: u64, index: u64) -> H256 {
+	block_on(executor.storage(address(owner), H256::from_low_u64_be(index)))
+}
+
+#[test]
+fn delegatecall_keeps_storage_caller_and_value() {
+	let executor = run(DELEGATE, 7);
+
+	assert_eq!(storage(&executor, PROXY, 0), H256::from_low_u64_be(7));
+	assert_eq!(storage(&executor, PROXY, 1), H256::from(address(CALLER)));
+	assert_eq!(storage(&executor, IMPLEMENTATION, 0), H256::zero());
+
+	let proxy = &executor.call_traces()[0];
+	assert_eq!(proxy.context, Context {
+		address: address(PROXY),
+		caller: address(CALLER),
+		apparent_value: U256::from(7),
+	});
+	assert_eq!(proxy.calls[0].address, address(IMPLEMENTATION));
+	assert_eq!(proxy.calls[0].context, proxy.context);
+}
+
+#[test]
+fn callcode_keeps_storage_with_proxy_as_caller() {
+	let executor = run(CALLCODE, 7);
+
+	assert_eq!(storage(&executor, PROXY, 0), H256::from_low_u64_be(3));
+	assert_eq!(storage(&executor, PROXY, 1), H256::from(address(PROXY)));
+	assert_eq!(storage(&executor, IMPLEMENTATION, 1), H256::zero());
+
+	let implementation = &executor.call_traces()[0].calls[0];
+	assert_eq!(implementation.address, address(IMPLEMENTATION));
+	assert_eq!(implementation.context, Context {
+		address: address(PROXY),
+		caller: address(PROXY),
+		apparent_value: U256::from(3),
+	});
+}
+
+#[test]
+fn substates_have_no_frame_context() {
+	let executor = run(DELEGATE, 0);
+	let substate = executor.substate(1_000, false);
+	assert_eq!(substate.context(), None);
+	assert_eq!(substate.storage_address(), None);
+}
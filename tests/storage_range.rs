@@ -0,0 +1,70 @@
+mod common;
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::{Backend, MemoryBackend};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, H256};
+
+use common::{TARGET, account, backend, block_on};
+
+fn slot(index: u64) -> H256 {
+	H256::from_low_u64_be(index)
+}
+
+fn slots(range: Result<Vec<(H256, H256)>, Infallible>) -> Vec<u64> {
+	let Ok(range) = range;
+	range.into_iter().map(|(index, _)| index.to_low_u64_be()).collect()
+}
+
+fn state() -> Arc<MemoryBackend> {
+	let mut target = account("");
+	for index in &[1, 2, 3, 5] {
+		target.storage.insert(slot(*index), slot(0x10 + *index));
+	}
+	backend(vec![(H160::from_low_u64_be(TARGET), target)])
+}
+
+fn executor() -> StackExecutor<MemoryBackend> {
+	StackExecutor::new(state(), 1_000_000, Arc::new(Config::istanbul()))
+}
+
+#[test]
+fn memory_backend_range_is_ordered_and_limited() {
+	let state = state();
+	let address = H160::from_low_u64_be(TARGET);
+
+	assert_eq!(slots(block_on(state.storage_range(address, slot(2), 2))), vec![2, 3]);
+	assert_eq!(slots(block_on(state.storage_range(address, slot(6), 2))), Vec::<u64>::new());
+}
+
+#[test]
+fn pending_writes_are_applied_over_backend() {
+	let mut executor = executor();
+	let address = H160::from_low_u64_be(TARGET);
+	block_on(async {
+		let account = executor.account_mut(address).await;
+		account.storage.insert(slot(2), H256::zero());
+		account.storage.insert(slot(4), slot(0x14));
+	});
+
+	assert_eq!(slots(block_on(executor.storage_range(address, H256::zero(), 3))), vec![1, 3, 4]);
+	assert_eq!(slots(block_on(executor.storage_range(address, H256::zero(), 10))), vec![1, 3, 4, 5]);
+	assert_eq!(slots(block_on(executor.storage_range(address, slot(4), 10))), vec![4, 5]);
+	assert_eq!(block_on(executor.storage_range(address, slot(4), 1)), Ok(vec![(slot(4), slot(0x14))]));
+}
+
+#[test]
+fn reset_storage_ignores_backend() {
+	let mut executor = executor();
+	let address = H160::from_low_u64_be(TARGET);
+	block_on(async {
+		let account = executor.account_mut(address).await;
+		account.reset_storage = true;
+		account.storage.insert(slot(7), slot(0x17));
+	});
+
+	assert_eq!(slots(block_on(executor.storage_range(address, H256::zero(), 10))), vec![7]);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitError, ExitFatal, ExitReason, ExitSucceed, StrictViolation};
+use evm::executor::StackExecutor;
+use primitive_types::{H160, U256};
+
+use common::{CALLER, TARGET, account, backend, call_target};
+
+const LIBRARY: u64 = 0xcc;
+
+// DUP1 on an empty stack.
+const DUP: &str = "80";
+// RETURNDATACOPY of one byte of an empty return data buffer.
+const RETURN_DATA: &str = "6001600060003e";
+// JUMP to the `JUMPDEST` byte of a `PUSH1` immediate.
+const JUMP: &str = "600456605b00";
+// JUMPI to the same kind of byte, not taken.
+const JUMPI_NOT_TAKEN: &str = "6000600657605b00";
+// CALL `LIBRARY` with all gas, and return the call result as a word.
+const CALL: &str = "600060006000600060007300000000000000000000000000000000000000cc\
+	5af160005260206000f3";
+
+fn run(code: &str, strict: bool) -> ExitReason {
+	let backend = backend(vec![
+		(H160::from_low_u64_be(CALLER), account("")),
+		(H160::from_low_u64_be(TARGET), account(code)),
+		(H160::from_low_u64_be(LIBRARY), account(DUP)),
+	]);
+	let mut config = Config::istanbul();
+	config.strict = strict;
+	let mut executor = StackExecutor::new(backend, 1_000_000, Arc::new(config));
+
+	call_target(&mut executor, Vec::new(), 100_000).0
+}
+
+#[test]
+fn stack_out_of_range_is_fatal() {
+	assert_eq!(run(DUP, false), ExitReason::Error(ExitError::StackUnderflow));
+	assert_eq!(
+		run(DUP, true),
+		ExitFatal::Strict(StrictViolation::StackOutOfRange { position: 0, opcode: 0x80, len: 0 }).into(),
+	);
+}
+
+#[test]
+fn return_data_out_of_bounds_is_fatal() {
+	assert_eq!(run(RETURN_DATA, false), ExitReason::Error(ExitError::OutOfOffset));
+	assert_eq!(
+		run(RETURN_DATA, true),
+		StrictViolation::ReturnDataOutOfBounds {
+			position: 6,
+			offset: U256::zero(),
+			len: U256::one(),
+			available: 0,
+		}.into(),
+	);
+}
+
+#[test]
+fn jump_into_push_data_is_fatal() {
+	assert_eq!(run(JUMP, false), ExitReason::Error(ExitError::InvalidJump));
+	assert_eq!(run(JUMP, true), StrictViolation::JumpIntoPushData { position: 2, destination: 4 }.into());
+	assert_eq!(run(JUMPI_NOT_TAKEN, true), ExitReason::Succeed(ExitSucceed::Stopped));
+}
+
+#[test]
+fn inner_violation_is_fatal() {
+	assert_eq!(run(CALL, false), ExitReason::Succeed(ExitSucceed::Returned));
+	assert!(matches!(run(CALL, true), ExitReason::Fatal(ExitFatal::Strict(_))));
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use evm::Config;
+use evm::backend::{ApplyBackend, MemoryAccount, MemoryBackend};
+use evm::executor::{SYSTEM_ADDRESS, StackExecutor};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, block_on, vicinity};
+
+const TARGET: u64 = 0xaa;
+
+// SSTORE(0, CALLER), SSTORE(1, ORIGIN).
+const STORE_CALLER: &str = "336000553260015500";
+
+fn run(system_address: Option<H160>, address: H160) -> BTreeMap<H160, MemoryAccount> {
+	let state = BTreeMap::from([(H160::from_low_u64_be(TARGET), account(STORE_CALLER))]);
+	let mut vicinity = vicinity();
+	vicinity.gas_price = U256::from(1_000_000_000u64);
+	let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity.clone()), state.clone()));
+
+	let mut executor = StackExecutor::new(backend, 100_000, Arc::new(Config::istanbul()));
+	if let Some(system_address) = system_address {
+		executor.set_system_address(system_address);
+	}
+	let (reason, output) = block_on(executor.transact_system_call(address, Vec::new()));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert!(output.is_empty());
+
+	let (applies, logs) = executor.deconstruct();
+	let mut post = MemoryBackend::new(Arc::new(vicinity), state);
+	block_on(post.apply(applies, logs, true)).unwrap();
+	post.state().clone()
+}
+
+#[test]
+fn system_call_is_free() {
+	let state = run(None, H160::from_low_u64_be(TARGET));
+
+	// The system address holds no balance, and is neither charged nor
+	// given a nonce.
+	assert!(!state.contains_key(&SYSTEM_ADDRESS));
+	let storage = &state[&H160::from_low_u64_be(TARGET)].storage;
+	assert_eq!(storage.get(&H256::zero()), Some(&H256::from(SYSTEM_ADDRESS)));
+	assert_eq!(storage.get(&H256::from_low_u64_be(1)), Some(&H256::from(SYSTEM_ADDRESS)));
+}
+
+#[test]
+fn system_address_is_configurable() {
+	let system_address = H160::from_low_u64_be(0x1234);
+	let state = run(Some(system_address), H160::from_low_u64_be(TARGET));
+
+	assert!(!state.contains_key(&system_address));
+	let storage = &state[&H160::from_low_u64_be(TARGET)].storage;
+	assert_eq!(storage.get(&H256::zero()), Some(&H256::from(system_address)));
+}
+
+#[test]
+fn system_call_to_empty_account_succeeds() {
+	let state = run(None, H160::from_low_u64_be(0xbb));
+
+	assert!(!state.contains_key(&H160::from_low_u64_be(0xbb)));
+	assert!(!state.contains_key(&SYSTEM_ADDRESS));
+}
+
+#[test]
+fn system_call_has_its_own_gas() {
+	let state = BTreeMap::from([(H160::from_low_u64_be(TARGET), account(STORE_CALLER))]);
+	let backend = Arc::new(MemoryBackend::new(Arc::new(vicinity()), state));
+
+	// The executor has no gas left, yet the call runs with `SYSTEM_CALL_GAS`
+	// and leaves it unchanged.
+	let mut executor = StackExecutor::new(backend, 0, Arc::new(Config::istanbul()));
+	let (reason, _) = block_on(executor.transact_system_call(H160::from_low_u64_be(TARGET), Vec::new()));
+	assert!(reason.is_succeed(), "{:?}", reason);
+	assert_eq!(executor.gas(), 0);
+	assert_eq!(executor.used_gas(), 0);
+}
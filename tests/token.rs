@@ -0,0 +1,72 @@
+mod common;
+
+use evm::{Config, ExitError};
+use evm::token::{self, TokenError};
+use primitive_types::{H160, H256, U256};
+
+use common::{account, backend, block_on};
+
+const TOKEN: u64 = 0xaa;
+
+// Minimal token keeping balances at storage slots keyed by address, with
+// `balanceOf(address)` and `transfer(address,uint256)` reverting on
+// insufficient balance.
+const TOKEN_CODE: &str = "60003560e01c806370a0823114601e578063a9059cbb14602b57600080fd5b6004355460\
+	005260206000f35b6024353354818110605057819003335560043580548201905550600160005260206000f35b\
+	600080fd";
+
+fn setup() -> (std::sync::Arc<evm::backend::MemoryBackend>, H160, H160) {
+	let holder = H160::repeat_byte(0x11);
+	let mut token = account(TOKEN_CODE);
+	token.storage.insert(H256::from(holder), H256::from_low_u64_be(500));
+	let backend = backend(vec![
+		(holder, account("")),
+		(H160::from_low_u64_be(TOKEN), token),
+	]);
+	(backend, holder, H160::repeat_byte(0x22))
+}
+
+#[test]
+fn balance_and_transfer() {
+	let (backend, holder, recipient) = setup();
+	let config = Config::istanbul();
+	let address = H160::from_low_u64_be(TOKEN);
+
+	assert_eq!(
+		block_on(token::balance_of(backend.clone(), &config, address, holder)),
+		Ok(U256::from(500)),
+	);
+
+	let simulation = block_on(token::simulate_transfer(
+		backend.clone(), &config, address, holder, recipient, U256::from(200),
+	)).unwrap();
+	assert_eq!(simulation.from_balance, U256::from(300));
+	assert_eq!(simulation.to_balance, U256::from(200));
+
+	// The simulation is not committed.
+	assert_eq!(
+		block_on(token::balance_of(backend, &config, address, recipient)),
+		Ok(U256::zero()),
+	);
+}
+
+#[test]
+fn transfer_exceeding_balance_reverts() {
+	let (backend, holder, recipient) = setup();
+	assert_eq!(
+		block_on(token::simulate_transfer(
+			backend, &Config::istanbul(), H160::from_low_u64_be(TOKEN), holder, recipient, U256::from(501),
+		)),
+		Err(TokenError::Reverted(Vec::new())),
+	);
+}
+
+#[test]
+fn queries_are_static() {
+	// Stores to slot 0 before returning a zero balance.
+	let backend = backend(vec![(H160::from_low_u64_be(TOKEN), account("600160005560206000f3"))]);
+	assert_eq!(
+		block_on(token::balance_of(backend, &Config::istanbul(), H160::from_low_u64_be(TOKEN), H160::repeat_byte(0x11))),
+		Err(TokenError::Failed(ExitError::StaticModeViolation.into())),
+	);
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "tracing")]
+
+mod common;
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use evm::Config;
+use evm::executor::StackExecutor;
+use tracing::{Event, Id, Metadata, Subscriber};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Record};
+
+use common::{call_target, deploy};
+
+// CALL `0xbb`, then SLOAD(0).
+const CALL_THEN_LOAD: &str = "600060006000600060007300000000000000000000000000000000000000bb5af150\
+	60005400";
+
+/// Recorded fields, in order.
+type FieldValues = Vec<(&'static str, String)>;
+/// Span name with its recorded fields.
+type Recorded = (&'static str, FieldValues);
+
+#[derive(Clone, Default)]
+struct Collector {
+	next_id: Arc<AtomicU64>,
+	spans: Arc<Mutex<Vec<Recorded>>>,
+	events: Arc<Mutex<Vec<FieldValues>>>,
+}
+
+struct Fields<'a>(&'a mut FieldValues);
+
+impl Visit for Fields<'_> {
+	fn record_str(&mut self, field: &Field, value: &str) {
+		self.0.push((field.name(), value.into()));
+	}
+
+	fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+		self.0.push((field.name(), format!("{:?}", value)));
+	}
+}
+
+impl Subscriber for Collector {
+	fn enabled(&self, _metadata: &Metadata<'_>) -> bool { true }
+
+	fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+		let mut fields = Vec::new();
+		attributes.record(&mut Fields(&mut fields));
+		self.spans.lock().unwrap().push((attributes.metadata().name(), fields));
+		Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+	}
+
+	fn record(&self, span: &Id, values: &Record<'_>) {
+		let mut spans = self.spans.lock().unwrap();
+		values.record(&mut Fields(&mut spans[span.into_u64() as usize - 1].1));
+	}
+
+	fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+	fn event(&self, event: &Event<'_>) {
+		let mut fields = Vec::new();
+		event.record(&mut Fields(&mut fields));
+		self.events.lock().unwrap().push(fields);
+	}
+
+	fn enter(&self, _span: &Id) {}
+	fn exit(&self, _span: &Id) {}
+}
+
+fn field<'a>(fields: &'a [(&'static str, String)], name: &str) -> Option<&'a str> {
+	fields.iter().rev().find(|(field, _)| *field == name).map(|(_, value)| value.as_str())
+}
+
+#[test]
+fn spans_per_transaction_and_frame() {
+	let collector = Collector::default();
+	let mut executor = StackExecutor::new(
+		deploy(CALL_THEN_LOAD),
+		1_000_000,
+		Arc::new(Config::istanbul()),
+	);
+
+	tracing::subscriber::with_default(collector.clone(), || {
+		call_target(&mut executor, Vec::new(), 1_000_000)
+	});
+
+	let spans = collector.spans.lock().unwrap();
+	let names = spans.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+	assert_eq!(names, vec!["transaction", "call"]);
+
+	let (_, transaction) = &spans[0];
+	assert_eq!(field(transaction, "reason"), Some("Succeed(Stopped)"));
+	assert!(field(transaction, "gas_used").is_some());
+	let (_, call) = &spans[1];
+	assert_eq!(field(call, "depth"), Some("Some(0)"));
+	assert_eq!(field(call, "reason"), Some("Succeed(Stopped)"));
+
+	let events = collector.events.lock().unwrap();
+	assert!(events.iter().any(|fields| field(fields, "method") == Some("storage")));
+	assert!(events.iter().all(|fields| field(fields, "latency_us").is_some()));
+}
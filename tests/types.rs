@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use evm::types::{parse_address, parse_h256, parse_u256, Address, ParseError};
+use primitive_types::{H160, H256, U256};
+
+// EIP-55 test vectors.
+const CHECKSUMMED: [&str; 4] = [
+	"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+	"0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+	"0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+	"0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+];
+
+#[test]
+fn checksums_are_formatted() {
+	for checksummed in CHECKSUMMED {
+		let address = Address::from_str(&checksummed.to_lowercase()).unwrap();
+		assert_eq!(address.to_checksum_string(), checksummed);
+		assert_eq!(address.to_string(), checksummed);
+		assert_eq!(Address::from_checksum_str(checksummed), Ok(address));
+	}
+}
+
+#[test]
+fn invalid_checksums_are_rejected() {
+	let wrong = "0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+	let expected = ParseError::InvalidChecksum { expected: CHECKSUMMED[0].to_string() };
+	assert_eq!(Address::from_checksum_str(wrong), Err(expected.clone()));
+	assert_eq!(Address::from_str(wrong), Err(expected.clone()));
+
+	// Single case addresses carry no checksum, and are only accepted by the
+	// lenient parser.
+	let lower = CHECKSUMMED[0].to_lowercase();
+	assert_eq!(Address::from_checksum_str(&lower), Err(expected));
+	assert!(Address::from_str(&lower).is_ok());
+	assert!(Address::from_str(&lower.to_uppercase()[2..]).is_ok());
+}
+
+#[test]
+fn addresses_convert_to_h160() {
+	let address = H160::from_low_u64_be(0xaa);
+	assert_eq!(H160::from(Address::from(address)), address);
+	assert_eq!(parse_address("00000000000000000000000000000000000000aa"), Ok(address));
+	assert_eq!(parse_address("0xaa"), Err(ParseError::InvalidLength { expected: 40, found: 2 }));
+	assert_eq!(parse_address("0x"), Err(ParseError::Empty));
+	assert_eq!(
+		parse_address("0x00000000000000000000000000000000000000ag"),
+		Err(ParseError::InvalidCharacter { position: 39, character: 'g' }),
+	);
+}
+
+#[test]
+fn words_and_quantities_are_parsed() {
+	let word = "0x000000000000000000000000000000000000000000000000000000000000002a";
+	assert_eq!(parse_h256(word), Ok(H256::from_low_u64_be(42)));
+	assert_eq!(parse_h256(&word[2..]), Ok(H256::from_low_u64_be(42)));
+	assert_eq!(parse_h256("0x2a"), Err(ParseError::InvalidLength { expected: 64, found: 2 }));
+
+	assert_eq!(parse_u256("0x2a"), Ok(U256::from(42)));
+	assert_eq!(parse_u256("0xa"), Ok(U256::from(10)));
+	assert_eq!(parse_u256("42"), Ok(U256::from(42)));
+	assert_eq!(parse_u256(&format!("0x{}", "f".repeat(64))), Ok(U256::MAX));
+	assert_eq!(parse_u256(&format!("0x1{}", "0".repeat(64))), Err(ParseError::Overflow));
+	assert_eq!(parse_u256(&format!("1{}", U256::MAX)), Err(ParseError::Overflow));
+	assert_eq!(parse_u256("4x"), Err(ParseError::InvalidDecimal));
+	assert_eq!(parse_u256("0x4x"), Err(ParseError::InvalidCharacter { position: 1, character: 'x' }));
+	assert_eq!(parse_u256(""), Err(ParseError::Empty));
+	assert_eq!(ParseError::Overflow.to_string(), "value does not fit in 256 bits");
+}
@@ -0,0 +1,202 @@
+mod common;
+
+use std::sync::Arc;
+
+use evm::{Config, ExitReason, ExitSucceed};
+use evm::backend::{
+	AccountProof, ApplyBackend, Backend, MemoryBackend, ProofError, Recorder, Witness, WitnessBackend,
+	WitnessError, state_root, trie_root,
+};
+use evm::executor::{StackExecutor, Transaction, TransactionAction, VerifyError, verify_execution};
+use primitive_types::{H160, H256, U256};
+
+use common::{CALLER, TARGET, account, backend, block_on, deploy};
+
+// SSTORE 1 at slot 0.
+const STORE: &str = "600160005500";
+// SSTORE 0 at slot 0.
+const CLEAR: &str = "600060005500";
+// BALANCE of 0xbb, an account absent from the state.
+const BALANCE: &str = "60bb3100";
+// Return the sum of slot 1, NUMBER and BALANCE of 0xbb.
+const READ: &str = "600154430160bb310160005260206000f3";
+
+fn h256(s: &str) -> H256 {
+	H256::from_slice(&hex::decode(s).unwrap())
+}
+
+fn transaction() -> Transaction {
+	Transaction {
+		caller: H160::from_low_u64_be(CALLER),
+		action: TransactionAction::Call(H160::from_low_u64_be(TARGET)),
+		value: U256::from(7),
+		data: Vec::new(),
+		gas_limit: 100_000,
+		nonce: None,
+	}
+}
+
+/// Run the transaction on `code` against a memory backend through a
+/// recorder, and return the recorded witness, proofs of the witnessed
+/// values and the pre- and post-state roots.
+fn record(code: &str) -> (Witness, Vec<AccountProof>, H256, H256) {
+	record_on(deploy(code))
+}
+
+fn record_on(memory: Arc<MemoryBackend>) -> (Witness, Vec<AccountProof>, H256, H256) {
+	let recorder = Arc::new(Recorder::new(memory.clone()));
+	let config = Config::istanbul();
+	let mut executor = StackExecutor::new(recorder.clone(), 100_000, Arc::new(config));
+
+	let result = block_on(executor.transact(transaction()));
+	assert_eq!(result.reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let (applies, logs) = executor.deconstruct();
+	let mut post = MemoryBackend::clone(&memory);
+	block_on(post.apply(applies, logs, true));
+
+	let witness = recorder.witness();
+	let proofs = witness.accounts.iter()
+		.map(|(address, account)| {
+			memory.prove_account(*address, &account.storage.keys().cloned().collect::<Vec<_>>())
+		})
+		.collect();
+	(witness, proofs, state_root(memory.state()), state_root(post.state()))
+}
+
+#[test]
+fn trie_roots() {
+	let empty: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+	assert_eq!(
+		trie_root(empty),
+		h256("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"),
+	);
+	assert_eq!(
+		trie_root(vec![
+			("doe", "reindeer"),
+			("dog", "puppy"),
+			("dogglesworth", "cat"),
+		]),
+		h256("8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"),
+	);
+}
+
+#[test]
+fn recorder_captures_consumed_values() {
+	let target = H160::from_low_u64_be(TARGET);
+	let absent = H160::from_low_u64_be(0xbb);
+	let mut target_account = account(READ);
+	target_account.storage.insert(H256::from_low_u64_be(1), H256::from_low_u64_be(5));
+	let memory = backend(vec![(H160::from_low_u64_be(CALLER), account("")), (target, target_account)]);
+	let recorder = Arc::new(Recorder::new(memory));
+	let mut executor = StackExecutor::new(recorder.clone(), 100_000, Arc::new(Config::istanbul()));
+
+	let (reason, recorded) = block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER), target, U256::zero(), Vec::new(), 100_000,
+	));
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&recorded), U256::from(5));
+
+	let witness = recorder.witness();
+	assert_eq!(witness.block_number, Some(U256::zero()));
+	assert_eq!(witness.block_timestamp, None);
+	assert_eq!(witness.accounts[&target].storage.get(&H256::from_low_u64_be(1)), Some(&H256::from_low_u64_be(5)));
+	assert_eq!(witness.accounts[&target].code, Some(hex::decode(READ).unwrap()));
+	assert_eq!(witness.accounts[&absent].basic, Some(Default::default()));
+	assert!(!witness.state().contains_key(&absent));
+
+	// Replaying the witness reproduces the execution offline.
+	let replay = Arc::new(WitnessBackend::new(witness));
+	let mut executor = StackExecutor::new(replay, 100_000, Arc::new(Config::istanbul()));
+	let (reason, replayed) = block_on(executor.transact_call(
+		H160::from_low_u64_be(CALLER), target, U256::zero(), Vec::new(), 100_000,
+	));
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(replayed, recorded);
+}
+
+#[test]
+fn verify_recorded_execution() {
+	let (witness, proofs, pre_root, root) = record(STORE);
+	let config = Config::istanbul();
+
+	assert_eq!(
+		block_on(verify_execution(witness.clone(), &proofs, transaction(), pre_root, root, &config)),
+		Ok((ExitReason::Succeed(ExitSucceed::Stopped), Vec::new())),
+	);
+	assert_eq!(
+		block_on(verify_execution(witness, &proofs, transaction(), pre_root, H256::zero(), &config)),
+		Err(VerifyError::StateRootMismatch { expected: H256::zero(), actual: root }),
+	);
+}
+
+#[test]
+fn unproven_witness_is_rejected() {
+	let target = H160::from_low_u64_be(TARGET);
+	let (mut witness, mut proofs, pre_root, root) = record(STORE);
+	let config = Config::istanbul();
+
+	let mut forged = witness.clone();
+	forged.accounts.get_mut(&target).unwrap().code = Some(hex::decode(BALANCE).unwrap());
+	assert_eq!(
+		block_on(verify_execution(forged, &proofs, transaction(), pre_root, root, &config)),
+		Err(VerifyError::UnprovenWitness(WitnessError::Account(target, "code"))),
+	);
+
+	witness.accounts.get_mut(&target).unwrap().storage.insert(H256::zero(), H256::from_low_u64_be(1));
+	assert_eq!(
+		block_on(verify_execution(witness.clone(), &proofs, transaction(), pre_root, root, &config)),
+		Err(VerifyError::UnprovenWitness(WitnessError::Storage(target, H256::zero()))),
+	);
+
+	let proof = proofs.iter_mut().find(|proof| proof.address == target).unwrap();
+	proof.storage_proof[0].value = U256::one();
+	assert_eq!(
+		block_on(verify_execution(witness, &proofs, transaction(), pre_root, root, &config)),
+		Err(VerifyError::InvalidProof(target, ProofError::ValueMismatch)),
+	);
+}
+
+#[test]
+fn clearing_a_slot_needs_its_sibling() {
+	let target = H160::from_low_u64_be(TARGET);
+	let mut target_account = account(CLEAR);
+	target_account.storage.insert(H256::zero(), H256::from_low_u64_be(1));
+	target_account.storage.insert(H256::from_low_u64_be(1), H256::from_low_u64_be(2));
+	let memory = backend(vec![(H160::from_low_u64_be(CALLER), account("")), (target, target_account)]);
+	let (witness, mut proofs, pre_root, root) = record_on(memory.clone());
+	let config = Config::istanbul();
+
+	// The branch of both slots is left with the unproven slot 1 only.
+	assert_eq!(
+		block_on(verify_execution(witness.clone(), &proofs, transaction(), pre_root, root, &config)),
+		Err(VerifyError::IncompleteProof(target, ProofError::MissingNode)),
+	);
+
+	let proof = proofs.iter_mut().find(|proof| proof.address == target).unwrap();
+	*proof = memory.prove_account(target, &[H256::zero(), H256::from_low_u64_be(1)]);
+	assert_eq!(
+		block_on(verify_execution(witness, &proofs, transaction(), pre_root, root, &config)),
+		Ok((ExitReason::Succeed(ExitSucceed::Stopped), Vec::new())),
+	);
+}
+
+#[test]
+fn out_of_witness_access_fails() {
+	let absent = H160::from_low_u64_be(0xbb);
+	let (mut witness, proofs, pre_root, root) = record(BALANCE);
+	assert!(witness.accounts.remove(&absent).is_some());
+
+	let backend = WitnessBackend::new(witness.clone());
+	assert_eq!(block_on(backend.basic(absent)), Err(WitnessError::Account(absent, "basic")));
+	assert_eq!(
+		block_on(backend.storage(absent, H256::zero())),
+		Err(WitnessError::Storage(absent, H256::zero())),
+	);
+	assert_eq!(block_on(backend.block_hash(U256::one())), Err(WitnessError::BlockHash(U256::one())));
+
+	assert_eq!(
+		block_on(verify_execution(witness, &proofs, transaction(), pre_root, root, &Config::istanbul())),
+		Err(VerifyError::IncompleteWitness(WitnessError::Account(absent, "basic"))),
+	);
+}